@@ -0,0 +1,142 @@
+//! Storage and context-building benchmarks.
+//!
+//! Measures the paths that grow with history: listing sessions, loading and
+//! resuming long sessions, building the coach preamble for a turn, and
+//! serializing a transcript as JSONL.
+//!
+//! Usage:
+//! ```bash
+//! cargo bench --bench storage
+//! ```
+
+// chiron is a binary crate, so the benches compile the modules they measure
+// directly. Parts of those modules go unused here.
+#![allow(dead_code)]
+
+#[path = "../src/catalog.rs"]
+mod catalog;
+#[path = "../src/error.rs"]
+mod error;
+#[path = "../src/memory/mod.rs"]
+mod memory;
+#[path = "../src/provider/mod.rs"]
+mod provider;
+
+mod agents {
+    #[path = "../../src/agents/peer.rs"]
+    pub mod peer;
+}
+
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tokio::runtime::Runtime;
+use tokio_rusqlite::Connection;
+
+use agents::peer::build_peer_coach_preamble;
+use catalog::{ModeCatalog, PromptCatalog};
+use memory::{case_notes, InputSource};
+
+/// Sessions in the database for the listing benchmark.
+const SESSIONS: usize = 1_000;
+/// Messages (user + assistant) in the long session.
+const LONG_SESSION_MESSAGES: usize = 500;
+/// The default sliding window: 4 turns of user + assistant.
+const WINDOW_MESSAGES: usize = 8;
+
+const LONG_SESSION: &str = "session_long";
+
+/// A database with `SESSIONS` short sessions and one long one.
+async fn seeded_db() -> Connection {
+    let conn = memory::open_memory(":memory:").await.unwrap();
+    for i in 0..SESSIONS {
+        let session_id = format!("session_{i}");
+        save_exchange(&conn, &session_id, i).await;
+        save_exchange(&conn, &session_id, i + 1).await;
+    }
+    for i in 0..LONG_SESSION_MESSAGES / 2 {
+        save_exchange(&conn, LONG_SESSION, i).await;
+    }
+    case_notes::save_case_note(
+        &conn,
+        LONG_SESSION,
+        (LONG_SESSION_MESSAGES / 2) as i32,
+        Some("evoke"),
+        "MI Stage: evoke\nStrategy Used: complex reflection\nTalk Type: change\nRunning Themes: work stress, sleep, drinking, family",
+    )
+    .await
+    .unwrap();
+    conn
+}
+
+async fn save_exchange(conn: &Connection, session_id: &str, n: usize) {
+    let user = format!("Message {n}: work has been piling up again and I'm not sleeping much.");
+    let assistant = format!("Reply {n}: it sounds like the pressure is following you home. What does a bad night look like?");
+    memory::save_chat_turn(conn, session_id, "user", &user, InputSource::Text, None).await.unwrap();
+    memory::save_chat_turn(conn, session_id, "assistant", &assistant, InputSource::Text, Some("v7-unified"))
+        .await
+        .unwrap();
+}
+
+fn sessions(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let conn = rt.block_on(seeded_db());
+
+    let mut group = c.benchmark_group("sessions");
+    group.bench_function(BenchmarkId::new("list", SESSIONS + 1), |b| {
+        b.to_async(&rt).iter(|| memory::list_sessions(&conn))
+    });
+    group.bench_function(BenchmarkId::new("load_full", LONG_SESSION_MESSAGES), |b| {
+        b.to_async(&rt).iter(|| memory::load_session_turns(&conn, LONG_SESSION))
+    });
+    group.bench_function(BenchmarkId::new("load_window", LONG_SESSION_MESSAGES), |b| {
+        b.to_async(&rt).iter(|| memory::load_recent_turns(&conn, LONG_SESSION, WINDOW_MESSAGES))
+    });
+    group.finish();
+}
+
+/// What a turn on a resumed long session reads and builds before inference.
+fn context(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let conn = rt.block_on(seeded_db());
+    let prompts = PromptCatalog::load(Path::new("prompts/coach.toml")).unwrap();
+    let modes = ModeCatalog::load(Path::new("prompts/modes.toml")).unwrap();
+    let base = &prompts.variants[0].preamble;
+
+    c.bench_function("context/resume_and_build_preamble", |b| {
+        b.to_async(&rt).iter(|| async {
+            let (turns, _) = memory::load_recent_turns(&conn, LONG_SESSION, WINDOW_MESSAGES).await.unwrap();
+            let notes = case_notes::get_latest_case_note(&conn).await.unwrap();
+            let preamble = build_peer_coach_preamble(
+                base,
+                prompts.think_instructions.as_deref(),
+                notes.as_deref(),
+                Some(&modes),
+                None,
+                None,
+                None,
+            );
+            (turns, preamble)
+        })
+    });
+}
+
+fn export(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let conn = rt.block_on(seeded_db());
+    let turns = rt.block_on(memory::load_session_turns(&conn, LONG_SESSION)).unwrap();
+
+    c.bench_function(&format!("export/jsonl/{LONG_SESSION_MESSAGES}"), |b| {
+        b.iter(|| {
+            let mut out = String::new();
+            for turn in &turns {
+                out.push_str(&serde_json::to_string(turn).unwrap());
+                out.push('\n');
+            }
+            out
+        })
+    });
+}
+
+criterion_group!(benches, sessions, context, export);
+criterion_main!(benches);
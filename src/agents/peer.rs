@@ -18,6 +18,8 @@ pub fn build_peer_coach_preamble(
     case_notes: Option<&str>,
     mode_catalog: Option<&ModeCatalog>,
     rag_context: Option<&str>,
+    goals: Option<&str>,
+    coping: Option<&str>,
 ) -> String {
     build_peer_coach_preamble_budgeted(
         base,
@@ -25,6 +27,8 @@ pub fn build_peer_coach_preamble(
         case_notes,
         mode_catalog,
         rag_context,
+        goals,
+        coping,
         DEFAULT_MAX_PREAMBLE_CHARS,
     )
 }
@@ -32,7 +36,7 @@ pub fn build_peer_coach_preamble(
 /// Budget-aware preamble builder (used directly by tests and orchestrator).
 ///
 /// Builds all optional sections, then assembles them in display order
-/// (base → think → RAG → case notes → technique → mode), but drops
+/// (base → think → RAG → goals → coping → case notes → technique → mode), but drops
 /// lowest-priority sections first when the budget would be exceeded.
 pub fn build_peer_coach_preamble_budgeted(
     base: &str,
@@ -40,6 +44,8 @@ pub fn build_peer_coach_preamble_budgeted(
     case_notes: Option<&str>,
     mode_catalog: Option<&ModeCatalog>,
     rag_context: Option<&str>,
+    goals: Option<&str>,
+    coping: Option<&str>,
     max_preamble_chars: usize,
 ) -> String {
     // Fixed components (never truncated)
@@ -78,9 +84,27 @@ pub fn build_peer_coach_preamble_budgeted(
         .filter(|c| !c.is_empty())
         .map(|context| format!("\n\n{context}"));
 
+    let goals_section = goals
+        .filter(|g| !g.is_empty())
+        .map(|goals| {
+            format!(
+                "\n\n## Goals and Values\nWhat they're working toward and what matters to them. Connect to these naturally when relevant; don't recite the list.\n\n{goals}"
+            )
+        });
+
+    let coping_section = coping
+        .filter(|c| !c.is_empty())
+        .map(|coping| {
+            format!(
+                "\n\n## Coping Skills\nSkills they've tried, by their own rating. Prefer ones that helped; don't re-suggest ones that didn't.\n\n{coping}"
+            )
+        });
+
     // Calculate total size of all optional sections
-    let all_sections: [(&Option<String>, &str); 4] = [
+    let all_sections: [(&Option<String>, &str); 6] = [
         (&rag_section, "RAG"),
+        (&goals_section, "goals"),
+        (&coping_section, "coping"),
         (&notes_section, "case notes"),
         (&technique_section, "technique"),
         (&mode_section, "mode"),
@@ -93,7 +117,7 @@ pub fn build_peer_coach_preamble_budgeted(
 
     // If everything fits, add in display order
     if fixed_len + total_optional <= max_preamble_chars {
-        // Display order: RAG → case notes → technique → mode
+        // Display order: RAG → goals → coping → case notes → technique → mode
         for (section, _) in &all_sections {
             if let Some(s) = section {
                 preamble.push_str(s);
@@ -103,7 +127,7 @@ pub fn build_peer_coach_preamble_budgeted(
     }
 
     // Budget exceeded — drop sections from lowest priority
-    // Priority (highest to lowest): case notes, technique, RAG, mode
+    // Priority (highest to lowest): case notes, goals, coping, technique, RAG, mode
     let budget = max_preamble_chars.saturating_sub(fixed_len);
     let mut remaining = budget;
 
@@ -120,6 +144,30 @@ pub fn build_peer_coach_preamble_budgeted(
         })
         .unwrap_or(false);
 
+    let include_goals = goals_section
+        .as_ref()
+        .map(|s| {
+            if s.len() <= remaining {
+                remaining -= s.len();
+                true
+            } else {
+                false
+            }
+        })
+        .unwrap_or(false);
+
+    let include_coping = coping_section
+        .as_ref()
+        .map(|s| {
+            if s.len() <= remaining {
+                remaining -= s.len();
+                true
+            } else {
+                false
+            }
+        })
+        .unwrap_or(false);
+
     let include_technique = technique_section
         .as_ref()
         .map(|s| {
@@ -156,10 +204,16 @@ pub fn build_peer_coach_preamble_budgeted(
         })
         .unwrap_or(false);
 
-    // Assemble in display order (RAG → case notes → technique → mode)
+    // Assemble in display order (RAG → goals → coping → case notes → technique → mode)
     if include_rag {
         if let Some(ref s) = rag_section { preamble.push_str(s); }
     }
+    if include_goals {
+        if let Some(ref s) = goals_section { preamble.push_str(s); }
+    }
+    if include_coping {
+        if let Some(ref s) = coping_section { preamble.push_str(s); }
+    }
     if include_notes {
         if let Some(ref s) = notes_section { preamble.push_str(s); }
     }
@@ -174,6 +228,8 @@ pub fn build_peer_coach_preamble_budgeted(
         preamble_len = preamble.len(),
         budget = max_preamble_chars,
         include_notes,
+        include_goals,
+        include_coping,
         include_technique,
         include_rag,
         include_mode,
@@ -183,6 +239,16 @@ pub fn build_peer_coach_preamble_budgeted(
     preamble
 }
 
+/// Appends a `## heading` section for each `(heading, body)` pair to a base
+/// prompt, in order. Used for the per-turn sections that are never cut.
+pub fn append_sections(base: &str, sections: &[(&str, String)]) -> String {
+    let mut prompt = base.to_string();
+    for (heading, body) in sections {
+        prompt.push_str(&format!("\n\n## {heading}\n{body}"));
+    }
+    prompt
+}
+
 /// Detects the conversation mode from case notes and returns the coach modifier.
 ///
 /// Maps strategy keywords in case notes to mode IDs in the catalog.
@@ -245,20 +311,30 @@ mod tests {
 
     #[test]
     fn test_preamble_base_only() {
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, None, None, None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, None, None, None, None, None);
         assert_eq!(preamble, TEST_BASE);
     }
 
+    #[test]
+    fn test_append_sections() {
+        assert_eq!(append_sections(TEST_BASE, &[]), TEST_BASE);
+        let sections = [("Closing", "Wrap up.".to_string()), ("Time", "Ten minutes left.".to_string())];
+        assert_eq!(
+            append_sections(TEST_BASE, &sections),
+            format!("{TEST_BASE}\n\n## Closing\nWrap up.\n\n## Time\nTen minutes left.")
+        );
+    }
+
     #[test]
     fn test_preamble_with_empty_case_notes() {
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(""), None, None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(""), None, None, None, None);
         assert_eq!(preamble, TEST_BASE);
     }
 
     #[test]
     fn test_preamble_with_case_notes() {
         let notes = "MI Stage: engage\nKey Themes: anxiety about job loss";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None, None, None);
 
         assert!(preamble.starts_with(TEST_BASE));
         assert!(preamble.contains("## Session Context"));
@@ -269,7 +345,7 @@ mod tests {
     #[test]
     fn test_preamble_with_stage_guidance() {
         let notes = "MI Stage: evoke\nRunning Themes: drinking";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None, None, None);
 
         assert!(preamble.contains("## Technique Guidance"));
         assert!(preamble.contains("DARN questions"));
@@ -278,7 +354,7 @@ mod tests {
     #[test]
     fn test_preamble_engage_guidance() {
         let notes = "MI Stage: engage\nRunning Themes: anxiety";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None, None, None);
 
         assert!(preamble.contains("## Technique Guidance"));
         assert!(preamble.contains("rapport"));
@@ -288,7 +364,7 @@ mod tests {
     fn test_preamble_with_rag_context() {
         let rag = "## What You Know About This Person\n- Goal: reduce drinking to weekends";
         let notes = "MI Stage: evoke\nRunning Themes: drinking";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, Some(rag));
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, Some(rag), None, None);
 
         assert!(preamble.contains("## What You Know About This Person"));
         assert!(preamble.contains("reduce drinking"));
@@ -298,9 +374,31 @@ mod tests {
         assert!(rag_pos < notes_pos, "RAG context should precede case notes");
     }
 
+    #[test]
+    fn test_preamble_with_goals() {
+        let notes = "MI Stage: plan\nRunning Themes: exercise";
+        let goals = "- Walk 3 times a week";
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes), None, None, Some(goals), None);
+
+        assert!(preamble.contains("## Goals and Values"));
+        assert!(preamble.contains("Walk 3 times a week"));
+        let goals_pos = preamble.find("## Goals and Values").unwrap();
+        let notes_pos = preamble.find("Session Context").unwrap();
+        assert!(goals_pos < notes_pos, "goals should precede case notes");
+    }
+
+    #[test]
+    fn test_preamble_with_coping_skills() {
+        let coping = "- Helped before: paced breathing\n- Didn't help (don't suggest again): journaling";
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, None, None, None, None, Some(coping));
+
+        assert!(preamble.contains("## Coping Skills"));
+        assert!(preamble.contains("don't suggest again): journaling"));
+    }
+
     #[test]
     fn test_preamble_empty_rag_context_ignored() {
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, None, None, Some(""));
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, None, None, Some(""), None, None);
         assert_eq!(preamble, TEST_BASE);
     }
 
@@ -321,6 +419,8 @@ mod tests {
             Some(case_notes),
             Some(&catalog),
             Some(rag_context),
+            None,
+            None,
         );
 
         // All 5 sections present
@@ -351,17 +451,17 @@ mod tests {
 
         // Resistance mode triggered by keyword
         let notes_resistance = "MI Stage: evoke\nStrategy Used: rolling with resistance";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_resistance), Some(&catalog), None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_resistance), Some(&catalog), None, None, None);
         assert!(preamble.contains("## Current Mode"), "resistance mode should trigger");
 
         // Change-talk mode triggered by keyword
         let notes_change = "MI Stage: evoke\nTalk Type: change talk";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_change), Some(&catalog), None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_change), Some(&catalog), None, None, None);
         assert!(preamble.contains("## Current Mode"), "change-talk mode should trigger");
 
         // No mode when notes have no trigger keywords
         let notes_plain = "MI Stage: focus\nRunning Themes: work";
-        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_plain), Some(&catalog), None);
+        let preamble = build_peer_coach_preamble(TEST_BASE, None, Some(notes_plain), Some(&catalog), None, None, None);
         assert!(!preamble.contains("## Current Mode"), "no mode modifier for plain focus notes");
     }
 }
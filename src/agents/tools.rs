@@ -0,0 +1,224 @@
+use rig::completion::ToolDefinition;
+use rig::tool::{Tool, ToolDyn};
+use rig_fastembed::EmbeddingModel;
+use serde::Deserialize;
+use tokio_rusqlite::Connection;
+
+use crate::assessment::scoring::InstrumentCatalog;
+use crate::error::ChironError;
+use crate::memory::mood;
+use crate::memory::retrieval;
+
+/// Room for a few facts and summaries without crowding the reply.
+const MAX_SEARCH_RESULT_CHARS: usize = 800;
+
+/// Upper bound on tool rounds in one turn, so a model that keeps calling
+/// tools still has to answer.
+pub const MAX_TOOL_ROUNDS: usize = 2;
+
+/// Why a tool call failed. The message goes back to the model as the result.
+#[derive(Debug, thiserror::Error)]
+pub enum CoachToolError {
+    #[error("Unknown instrument '{0}'")]
+    UnknownInstrument(String),
+
+    #[error("Mood must be between 1 and 10, got {0}")]
+    MoodOutOfRange(i32),
+
+    #[error(transparent)]
+    Storage(#[from] ChironError),
+}
+
+/// The agents the coach can call as tools: memory search (research),
+/// assessment suggestions, and the mood journal. Memory search needs the
+/// vector store and is left out without one.
+pub fn coach_tools(
+    chat_conn: Connection,
+    vector_conn: Option<lancedb::Connection>,
+    embedding_model: Option<EmbeddingModel>,
+    rag_top_k: usize,
+) -> Vec<Box<dyn ToolDyn>> {
+    let mut tools: Vec<Box<dyn ToolDyn>> = vec![
+        Box::new(SuggestAssessment),
+        Box::new(LogJournal { conn: chat_conn }),
+    ];
+    if let (Some(conn), Some(model)) = (vector_conn, embedding_model) {
+        tools.insert(0, Box::new(SearchMemory { conn, model, top_k: rag_top_k }));
+    }
+    tools
+}
+
+/// Searches what the person has shared before: facts, past sessions, and
+/// their documents.
+pub struct SearchMemory {
+    conn: lancedb::Connection,
+    model: EmbeddingModel,
+    top_k: usize,
+}
+
+#[derive(Deserialize)]
+pub struct SearchMemoryArgs {
+    query: String,
+}
+
+impl Tool for SearchMemory {
+    const NAME: &'static str = "search_memory";
+
+    type Error = CoachToolError;
+    type Args = SearchMemoryArgs;
+    type Output = String;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Search what the person has told you in earlier sessions and the documents they've added, when they refer to something you don't have in front of you.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What to look for, in a few words" }
+                },
+                "required": ["query"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let ctx = retrieval::retrieve_context(&self.conn, &self.model, &args.query, None, self.top_k).await;
+        Ok(retrieval::format_rag_context(&ctx, MAX_SEARCH_RESULT_CHARS)
+            .unwrap_or_else(|| "Nothing relevant found.".to_string()))
+    }
+}
+
+/// Suggests a standardized questionnaire (PHQ-9, GAD-7, ...) the person can
+/// take with `chiron assess`.
+pub struct SuggestAssessment;
+
+#[derive(Deserialize)]
+pub struct SuggestAssessmentArgs {
+    instrument: String,
+}
+
+impl Tool for SuggestAssessment {
+    const NAME: &'static str = "suggest_assessment";
+
+    type Error = CoachToolError;
+    type Args = SuggestAssessmentArgs;
+    type Output = String;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let ids: Vec<&str> = InstrumentCatalog::builtin()
+            .instruments()
+            .iter()
+            .map(|i| i.id.as_str())
+            .collect();
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Look up a questionnaire to suggest when the person wants to measure how they're doing (e.g. mood or anxiety over the last two weeks).".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "instrument": { "type": "string", "enum": ids }
+                },
+                "required": ["instrument"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let instrument = InstrumentCatalog::builtin()
+            .get(&args.instrument)
+            .ok_or(CoachToolError::UnknownInstrument(args.instrument))?;
+        Ok(format!(
+            "{} — {}. Offer it; they can take it any time with `chiron assess {}`.",
+            instrument.name, instrument.description, instrument.id
+        ))
+    }
+}
+
+/// Saves a mood journal entry, the same log `chiron checkin` writes to.
+pub struct LogJournal {
+    conn: Connection,
+}
+
+#[derive(Deserialize)]
+pub struct LogJournalArgs {
+    mood: i32,
+    #[serde(default)]
+    highlight: Option<String>,
+    #[serde(default)]
+    stressor: Option<String>,
+}
+
+impl Tool for LogJournal {
+    const NAME: &'static str = "log_journal";
+
+    type Error = CoachToolError;
+    type Args = LogJournalArgs;
+    type Output = String;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: Self::NAME.to_string(),
+            description: "Save a mood journal entry, only when the person asks you to log how they're doing.".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "mood": { "type": "integer", "minimum": 1, "maximum": 10, "description": "1 (worst) to 10 (best), as they rated it" },
+                    "highlight": { "type": "string", "description": "Something good from today, in their words" },
+                    "stressor": { "type": "string", "description": "What weighed on them, in their words" }
+                },
+                "required": ["mood"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        if !(1..=10).contains(&args.mood) {
+            return Err(CoachToolError::MoodOutOfRange(args.mood));
+        }
+        mood::save_checkin(
+            &self.conn,
+            args.mood,
+            None,
+            args.highlight.as_deref(),
+            args.stressor.as_deref(),
+        )
+        .await?;
+        Ok(format!("Saved: mood {}/10.", args.mood))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_log_journal_saves_checkin() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        mood::create_mood_log_table(&conn).await.unwrap();
+        let tools = coach_tools(conn.clone(), None, None, 3);
+        let names: Vec<String> = tools.iter().map(|t| t.name()).collect();
+        assert_eq!(names, ["suggest_assessment", "log_journal"], "no memory search without a vector store");
+
+        let journal = LogJournal { conn: conn.clone() };
+        let args = LogJournalArgs { mood: 4, highlight: None, stressor: Some("deadline".into()) };
+        let saved = Tool::call(&journal, args).await.unwrap();
+        assert_eq!(saved, "Saved: mood 4/10.");
+        let checkins = mood::recent_checkins(&conn, 1).await.unwrap();
+        assert_eq!(checkins[0].stressor.as_deref(), Some("deadline"));
+
+        let args = LogJournalArgs { mood: 11, highlight: None, stressor: None };
+        let err = Tool::call(&journal, args).await.unwrap_err();
+        assert!(matches!(err, CoachToolError::MoodOutOfRange(11)));
+    }
+
+    #[tokio::test]
+    async fn test_suggest_assessment() {
+        let args = SuggestAssessmentArgs { instrument: "PHQ9".into() };
+        let phq = Tool::call(&SuggestAssessment, args).await.unwrap();
+        assert!(phq.contains("chiron assess phq9"), "{phq}");
+
+        let args = SuggestAssessmentArgs { instrument: "mmpi".into() };
+        assert!(Tool::call(&SuggestAssessment, args).await.is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use rig::completion::Prompt;
+use tokio_rusqlite::Connection;
+
+use crate::memory::archive::{self, SessionArchive, SessionProgress};
+use crate::memory::{self, ChatTurn};
+use crate::progress::Progress;
+use crate::provider::LlamaCppCompletionModel;
+use crate::router;
+use crate::timezone;
+
+/// Room for a paragraph or two.
+const SUMMARY_MAX_TOKENS: u64 = 320;
+
+/// Transcript characters sent for summarizing (about 1,500 tokens), so a
+/// long session still fits the context window with the reply.
+const TRANSCRIPT_BUDGET: usize = 6000;
+
+const SUMMARY_PREAMBLE: &str = "You write clinical-style summaries of peer-support conversations that \
+replace the transcript in the person's own records. In at most 150 words of plain prose, cover: presenting \
+concerns, recurring themes, motivational interviewing stage and any change talk, strategies or skills that \
+helped, commitments or homework, and risk indicators (write \"none noted\" if none). Refer to the person in \
+the third person. Do not quote them and do not add advice.";
+
+/// Timestamp (SQLite `datetime('now')` format, UTC) `days` ago.
+fn cutoff(days: u32) -> String {
+    (Utc::now() - Duration::days(days as i64)).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Lists the sessions `run` would archive without changing anything.
+pub async fn preview(conn: &Connection, older_than_days: u32) -> Result<()> {
+    let sessions = archive::sessions_to_archive(conn, &cutoff(older_than_days)).await?;
+    if sessions.is_empty() {
+        println!("No sessions inactive for more than {older_than_days} days.");
+        return Ok(());
+    }
+    for s in &sessions {
+        println!("{:<40} {:>5} messages  last active {}", s.session_id, s.message_count, timezone::datetime(&s.last_active_at));
+    }
+    println!("{} sessions would be archived.", sessions.len());
+    Ok(())
+}
+
+/// Replaces the transcripts of sessions inactive for more than
+/// `older_than_days` with a model-written summary plus their progress
+/// indicators. A session whose summary fails keeps its transcript.
+pub async fn run(conn: &Connection, model: &LlamaCppCompletionModel, older_than_days: u32) -> Result<()> {
+    let sessions = archive::sessions_to_archive(conn, &cutoff(older_than_days)).await?;
+    if sessions.is_empty() {
+        println!("No sessions inactive for more than {older_than_days} days.");
+        return Ok(());
+    }
+
+    let bar = Progress::new("Archiving", sessions.len());
+    let mut archived = 0;
+    for (i, session) in sessions.into_iter().enumerate() {
+        bar.start(i, &session.session_id);
+        let turns = memory::load_session_turns(conn, &session.session_id).await?;
+        let mut progress = archive::session_progress(conn, &session.session_id).await?;
+        progress.crisis_messages = turns
+            .iter()
+            .filter(|t| t.role == "user" && router::is_crisis(&t.content))
+            .count() as i64;
+
+        let summary = match summarize(model, &turns, &progress).await {
+            Ok(summary) if !summary.is_empty() => summary,
+            Ok(_) => {
+                bar.println(&format!("Skipped {}: the summary was empty", session.session_id));
+                continue;
+            }
+            Err(e) => {
+                bar.println(&format!("Skipped {}: {e:#}", session.session_id));
+                continue;
+            }
+        };
+
+        let stored = archive::archive_session(
+            conn,
+            &SessionArchive {
+                session_id: session.session_id.clone(),
+                summary,
+                message_count: session.message_count,
+                started_at: session.started_at,
+                last_active_at: session.last_active_at,
+                progress,
+            },
+        )
+        .await?;
+        if stored {
+            bar.suspend(|| println!("Archived {} ({} messages)", session.session_id, session.message_count));
+            archived += 1;
+        }
+    }
+    bar.finish();
+
+    if archived > 0 {
+        archive::vacuum(conn).await?;
+    }
+    println!("Archived {archived} sessions.");
+    Ok(())
+}
+
+/// Asks the model for a summary of the session's transcript.
+async fn summarize(
+    model: &LlamaCppCompletionModel,
+    turns: &[ChatTurn],
+    progress: &SessionProgress,
+) -> Result<String> {
+    let agent = rig::agent::AgentBuilder::new(model.clone())
+        .preamble(SUMMARY_PREAMBLE)
+        .temperature(0.2)
+        .max_tokens(SUMMARY_MAX_TOKENS)
+        .build();
+
+    let prompt = format!(
+        "MI STAGE: {} -> {}\n\nTRANSCRIPT:\n{}",
+        progress.mi_stage_start.as_deref().unwrap_or("unknown"),
+        progress.mi_stage_end.as_deref().unwrap_or("unknown"),
+        transcript(turns, TRANSCRIPT_BUDGET)
+    );
+    let summary = agent.prompt(prompt.as_str()).await.context("Summary generation failed")?;
+    Ok(crate::provider::strip_think_blocks(&summary).trim().to_string())
+}
+
+/// Formats the turns as `Person:`/`Coach:` lines. Over `budget` characters,
+/// the start and end of the session are kept and the middle is elided.
+fn transcript(turns: &[ChatTurn], budget: usize) -> String {
+    let lines: Vec<String> = turns
+        .iter()
+        .map(|t| {
+            let speaker = if t.role == "user" { "Person" } else { "Coach" };
+            format!("{speaker}: {}", t.content.trim())
+        })
+        .collect();
+    let total: usize = lines.iter().map(|l| l.len() + 1).sum();
+    if total <= budget {
+        return lines.join("\n");
+    }
+
+    // Fill half the budget from each end
+    let mut head = Vec::new();
+    let mut used = 0;
+    for line in &lines {
+        if used + line.len() > budget / 2 {
+            break;
+        }
+        used += line.len() + 1;
+        head.push(line.as_str());
+    }
+    let mut tail = Vec::new();
+    used = 0;
+    for line in lines[head.len()..].iter().rev() {
+        if used + line.len() > budget / 2 {
+            break;
+        }
+        used += line.len() + 1;
+        tail.push(line.as_str());
+    }
+    tail.reverse();
+    let omitted = lines.len() - head.len() - tail.len();
+    format!("{}\n[... {omitted} messages omitted ...]\n{}", head.join("\n"), tail.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ChatTurn {
+        ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: "2026-03-02 10:00:00".to_string(),
+            source: "text".to_string(),
+            prompt_variant: None,
+            interrupted: false,
+            emotions: None,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn test_transcript_elides_the_middle_over_budget() {
+        let turns: Vec<_> = (0..10)
+            .map(|i| turn(if i % 2 == 0 { "user" } else { "assistant" }, &format!("message {i}")))
+            .collect();
+        assert!(transcript(&turns, 1000).starts_with("Person: message 0\nCoach: message 1"));
+
+        let short = transcript(&turns, 80);
+        assert!(short.starts_with("Person: message 0\nCoach: message 1\n[... 6 messages omitted ...]"));
+        assert!(short.ends_with("Person: message 8\nCoach: message 9"));
+    }
+}
@@ -0,0 +1,77 @@
+pub mod outcomes;
+pub mod quick_reply;
+pub mod scoring;
+
+use anyhow::{bail, Result};
+
+use crate::i18n::t;
+use crate::router;
+use scoring::{Instrument, InstrumentCatalog};
+
+/// Lists the built-in instruments, one per line.
+pub fn list() -> String {
+    InstrumentCatalog::builtin()
+        .instruments()
+        .iter()
+        .map(|i| format!("{:<8} {} — {}", i.id, i.name, i.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Administers an instrument item by item on stdin, with the answer options
+/// as a numbered menu, and prints the score and severity band. Critical
+/// items (e.g. PHQ-9 item 9) answered above the minimum bring up crisis
+/// resources whatever the total.
+pub fn administer(id: &str) -> Result<()> {
+    let catalog = InstrumentCatalog::builtin();
+    let Some(instrument) = catalog.get(id) else {
+        bail!("Unknown instrument '{id}'. Available:\n{}", list());
+    };
+    let scale = catalog.scale_for(instrument);
+
+    println!("{}\n", instrument.name);
+    println!("{}", instrument.prompt);
+    println!("{}\n", quick_reply::menu(scale));
+
+    let mut responses = Vec::with_capacity(instrument.items.len());
+    for (n, item) in instrument.items.iter().enumerate() {
+        let prompt = format!("{}. {item} [{}–{}]", n + 1, scale.min, scale.max);
+        if let Some(response) = quick_reply::ask_scale(&prompt, scale, false)? {
+            responses.push(response);
+        }
+    }
+
+    let score = catalog.score(instrument, &responses)?;
+    println!("\n{}", summary(instrument, &score));
+    if !score.flagged_items.is_empty() {
+        println!("\n{}", router::crisis_response());
+    }
+    println!("{}", t("assessment.disclaimer"));
+    Ok(())
+}
+
+/// One-line result, e.g. `PHQ-9: 12 (moderate)`.
+fn summary(instrument: &Instrument, score: &scoring::Score) -> String {
+    match &score.band {
+        Some(band) => format!("{}: {} ({band})", instrument.name, score.total),
+        None => format!("{}: {}", instrument.name, score.total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_and_summary() {
+        let catalog = InstrumentCatalog::builtin();
+        let gad7 = catalog.get("gad7").unwrap();
+        assert_eq!(
+            quick_reply::menu(catalog.scale_for(gad7)),
+            "  0) Not at all\n  1) Several days\n  2) More than half the days\n  3) Nearly every day"
+        );
+        let score = catalog.score(gad7, &[1, 1, 1, 1, 1, 1, 0]).unwrap();
+        assert_eq!(summary(gad7, &score), "GAD-7: 6 (mild)");
+        assert!(list().contains("phq9"));
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use tokio_rusqlite::Connection;
+
+use crate::i18n::{t, tf};
+use crate::memory::outcomes;
+use super::quick_reply;
+use super::scoring::InstrumentCatalog;
+
+/// SRS totals below this suggest the conversation wasn't a good fit.
+const SRS_CONCERN_BELOW: f64 = 36.0;
+
+/// Asks the four Outcome Rating Scale items at the start of a session.
+pub async fn session_start(conn: &Connection, session_id: &str) -> Result<()> {
+    collect(conn, session_id, "ors", t("outcomes.ors_intro")).await?;
+    Ok(())
+}
+
+/// Asks the four Session Rating Scale items at the end of a session. A low
+/// total gets an invitation to say what would work better.
+pub async fn session_end(conn: &Connection, session_id: &str) -> Result<()> {
+    if let Some(total) = collect(conn, session_id, "srs", t("outcomes.srs_intro")).await? {
+        if total < SRS_CONCERN_BELOW {
+            println!("{}", t("outcomes.srs_low"));
+        }
+    }
+    Ok(())
+}
+
+/// Asks each 0–10 item of `measure`, then scores and saves it. Enter on the
+/// first item skips the measure. Returns the total if it was completed.
+async fn collect(conn: &Connection, session_id: &str, measure: &str, intro: &str) -> Result<Option<f64>> {
+    let catalog = InstrumentCatalog::builtin();
+    let instrument = catalog
+        .get(measure)
+        .expect("built-in catalog defines the ORS and SRS");
+    let scale = catalog.scale_for(instrument);
+
+    println!("{intro}");
+    let mut responses = Vec::with_capacity(instrument.items.len());
+    for item in &instrument.items {
+        let prompt = format!("  {item} ({}–{})", scale.min, scale.max);
+        match quick_reply::ask_scale(&prompt, scale, responses.is_empty())? {
+            Some(response) => responses.push(response),
+            None => {
+                println!("{}", t("outcomes.skipped"));
+                return Ok(None);
+            }
+        }
+    }
+
+    let score = catalog.score(instrument, &responses)?;
+    outcomes::save_rating(conn, session_id, measure, score.total, &responses).await?;
+    tracing::info!(measure, total = score.total, "Session rating saved");
+    println!("{}", tf("outcomes.saved", &[("total", &score.total)]));
+    Ok(Some(score.total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_measures_are_defined() {
+        let catalog = InstrumentCatalog::builtin();
+        let ors = catalog.get("ors").unwrap();
+        assert_eq!(ors.items.len(), 4);
+        let score = catalog.score(ors, &[6, 6, 6, 6]).unwrap();
+        assert_eq!(score.band.as_deref(), Some("below the clinical cutoff"));
+        let srs = catalog.get("srs").unwrap();
+        assert_eq!(catalog.score(srs, &[9, 9, 9, 9]).unwrap().band.as_deref(), Some("good fit"));
+    }
+}
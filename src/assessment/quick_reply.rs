@@ -0,0 +1,276 @@
+use anyhow::Result;
+
+use crate::checkin::ask;
+use crate::i18n::tf;
+use super::scoring::ResponseScale;
+
+/// Words that mark a question as a rating rather than, say, "between 2 and
+/// 3 times a week?".
+const RATING_WORDS: [&str; 5] = ["scale", "rate", "rating", "escala", "puntúa"];
+
+/// How a line typed after a scaled question is taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickReply {
+    /// A number on the scale, spelled out for the coach, e.g. `2 (Several days)`.
+    Answer(String),
+    /// A number off the scale; ask again.
+    Invalid,
+    /// Anything else, sent as typed.
+    FreeText,
+}
+
+/// Renders the answer options as a numbered menu, one per line, or the
+/// bare range (`0–10`) for a scale without anchors.
+pub fn menu(scale: &ResponseScale) -> String {
+    if scale.anchors.is_empty() {
+        return format!("{}–{}", scale.min, scale.max);
+    }
+    (scale.min..=scale.max)
+        .zip(&scale.anchors)
+        .map(|(value, anchor)| format!("  {value}) {anchor}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses a whole number within the scale.
+pub fn parse(scale: &ResponseScale, answer: &str) -> Option<i32> {
+    answer.trim().parse().ok().filter(|r| (scale.min..=scale.max).contains(r))
+}
+
+/// Asks until the answer is a number on the scale. With `skippable`, a
+/// blank answer returns `None`.
+pub fn ask_scale(prompt: &str, scale: &ResponseScale, skippable: bool) -> Result<Option<i32>> {
+    loop {
+        let answer = ask(prompt)?;
+        if answer.is_empty() && skippable {
+            return Ok(None);
+        }
+        match parse(scale, &answer) {
+            Some(r) => return Ok(Some(r)),
+            None => println!("{}", invalid(scale)),
+        }
+    }
+}
+
+/// The re-prompt after an answer off the scale.
+pub fn invalid(scale: &ResponseScale) -> String {
+    tf("assessment.invalid", &[("min", &scale.min), ("max", &scale.max)])
+}
+
+/// Takes a chat line typed after the coach asked `scale`. Bare numbers are
+/// validated and expanded with their label, so the coach doesn't have to
+/// guess what "2" meant; anything else is ordinary text.
+pub fn classify(scale: &ResponseScale, input: &str) -> QuickReply {
+    if input.trim().parse::<i64>().is_err() {
+        return QuickReply::FreeText;
+    }
+    match parse(scale, input) {
+        Some(value) => QuickReply::Answer(answer_text(scale, value)),
+        None => QuickReply::Invalid,
+    }
+}
+
+fn answer_text(scale: &ResponseScale, value: i32) -> String {
+    let anchor = usize::try_from(value - scale.min)
+        .ok()
+        .and_then(|i| scale.anchors.get(i));
+    match anchor {
+        Some(anchor) => format!("{value} ({anchor})"),
+        None => tf(
+            "quick_reply.answer",
+            &[("value", &value), ("min", &scale.min), ("max", &scale.max)],
+        ),
+    }
+}
+
+/// Finds a scaled question at the end of a coach reply: a list of numbered
+/// options (`0 = Not at all`, `1. Several days`, ...), or a rating question
+/// with a range ("On a scale from 0 to 10, ...?"). `None` for anything else.
+pub fn detect(reply: &str) -> Option<ResponseScale> {
+    detect_options(reply).or_else(|| detect_range(reply))
+}
+
+/// The last run of options numbered one apart, one per line or `=`-separated
+/// on a single line. Needs at least two, and a question in the reply.
+fn detect_options(reply: &str) -> Option<ResponseScale> {
+    if !reply.contains('?') {
+        return None;
+    }
+    let mut last = None;
+    let mut run: Vec<(i32, String)> = Vec::new();
+    for line in reply.lines() {
+        let inline = inline_options(line);
+        if inline.len() >= 2 {
+            run = inline;
+        } else if let Some(option) = option_line(line) {
+            if run.last().is_some_and(|(prev, _)| *prev + 1 != option.0) {
+                run.clear();
+            }
+            run.push(option);
+            continue;
+        } else if line.trim().is_empty() {
+            continue;
+        }
+        if run.len() >= 2 {
+            last = Some(std::mem::take(&mut run));
+        }
+        run.clear();
+    }
+    if run.len() >= 2 {
+        last = Some(run);
+    }
+
+    let options = last?;
+    Some(ResponseScale {
+        min: options[0].0,
+        max: options[options.len() - 1].0,
+        anchors: options.into_iter().map(|(_, label)| label).collect(),
+    })
+}
+
+/// `2) Several days`, `- **2.** Several days`, `2 = Several days`, ...
+fn option_line(line: &str) -> Option<(i32, String)> {
+    let line = line.trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '*' | '•'));
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let value = line[..digits].parse().ok()?;
+    let rest = line[digits..].trim_start_matches('*').trim_start();
+    let label = rest
+        .strip_prefix(['.', ')', '=', ':', '-', '–'])?
+        .trim()
+        .trim_matches('*')
+        .trim();
+    (!label.is_empty()).then(|| (value, label.to_string()))
+}
+
+/// `0 = Not at all, 1 = Several days, ...` on one line, numbered one apart.
+fn inline_options(line: &str) -> Vec<(i32, String)> {
+    let mut options: Vec<(i32, String)> = Vec::new();
+    for part in line.split([',', ';']) {
+        let Some((value, label)) = part.split_once('=') else {
+            continue;
+        };
+        let value = value.split_whitespace().last().unwrap_or("").trim_start_matches('(');
+        let Ok(value) = value.parse::<i32>() else {
+            continue;
+        };
+        if options.last().is_some_and(|(prev, _)| *prev + 1 != value) {
+            return Vec::new();
+        }
+        let label = label.trim().trim_end_matches(['.', '?', ')']).trim();
+        options.push((value, label.to_string()));
+    }
+    options
+}
+
+/// A range in the last question of the reply, if it asks for a rating:
+/// `0 to 10`, `1-5`, `1 (worst) to 10 (best)`, `de 0 a 10`.
+fn detect_range(reply: &str) -> Option<ResponseScale> {
+    let end = reply.rfind('?')?;
+    let start = reply[..end].rfind(['.', '!', '?', '\n']).map_or(0, |i| i + 1);
+    let question = reply[start..end].to_lowercase();
+    if !RATING_WORDS.iter().any(|w| question.contains(w)) {
+        return None;
+    }
+
+    let mut numbers: Vec<(usize, usize, i32)> = Vec::new();
+    let mut start = None;
+    for (i, c) in question.char_indices().chain([(question.len(), ' ')]) {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if let Ok(n) = question[s..i].parse() {
+                    numbers.push((s, i, n));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    numbers.windows(2).rev().find_map(|pair| {
+        let (_, low_end, min) = pair[0];
+        let (high_start, _, max) = pair[1];
+        let between = question[low_end..high_start].trim();
+        let joined = ["to", "-", "–", "and", "a", "y"]
+            .iter()
+            .any(|w| between == *w || between.ends_with(&format!(" {w}")));
+        let plausible = joined && min < max && max - min <= 100;
+        plausible.then_some(ResponseScale {
+            min,
+            max,
+            anchors: Vec::new(),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scale(min: i32, max: i32, anchors: &[&str]) -> ResponseScale {
+        ResponseScale {
+            min,
+            max,
+            anchors: anchors.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_menu_and_parse() {
+        let phq = scale(0, 3, &["Not at all", "Several days", "More than half the days", "Nearly every day"]);
+        assert_eq!(
+            menu(&phq),
+            "  0) Not at all\n  1) Several days\n  2) More than half the days\n  3) Nearly every day"
+        );
+        assert_eq!(menu(&scale(0, 10, &[])), "0–10");
+        assert_eq!(parse(&scale(0, 10, &[]), "7"), Some(7));
+        assert_eq!(parse(&scale(0, 10, &[]), "11"), None);
+        assert_eq!(parse(&scale(0, 10, &[]), "good"), None);
+    }
+
+    #[test]
+    fn test_classify_bare_numbers() {
+        let phq = scale(0, 3, &["Not at all", "Several days", "More than half the days", "Nearly every day"]);
+        assert_eq!(classify(&phq, "1"), QuickReply::Answer("1 (Several days)".to_string()));
+        assert_eq!(classify(&phq, " 4 "), QuickReply::Invalid);
+        assert_eq!(classify(&phq, "-1"), QuickReply::Invalid);
+        assert_eq!(classify(&phq, "a couple of days"), QuickReply::FreeText);
+        assert_eq!(classify(&phq, "2 maybe"), QuickReply::FreeText);
+    }
+
+    #[test]
+    fn test_detect_numbered_options() {
+        let reply = "Thanks for sharing that.\n\nOver the last two weeks, how often have you felt down?\n\n\
+                     0. Not at all\n1. Several days\n2. More than half the days\n3. Nearly every day";
+        let found = detect(reply).unwrap();
+        assert_eq!((found.min, found.max), (0, 3));
+        assert_eq!(found.anchors[1], "Several days");
+
+        let reply = "Which would you like to try?\n- **1)** A short walk\n- **2)** Calling a friend";
+        assert_eq!(detect(reply).unwrap().anchors, vec!["A short walk", "Calling a friend"]);
+
+        let reply = "How often? (0 = Not at all, 1 = Several days, 2 = Most days)";
+        assert_eq!(detect(reply).unwrap().anchors, vec!["Not at all", "Several days", "Most days"]);
+
+        // Not one apart, or not a question
+        assert!(detect("Which one?\n1. Walk\n3. Call").is_none());
+        assert!(detect("Some ideas:\n1. Walk\n2. Call").is_none());
+    }
+
+    #[test]
+    fn test_detect_rating_range() {
+        let found = detect("That sounds hard. On a scale from 0 to 10, how stressed are you right now?").unwrap();
+        assert_eq!((found.min, found.max), (0, 10));
+        let found = detect("How would you rate your mood, 1 (worst) to 10 (best)?").unwrap();
+        assert_eq!((found.min, found.max), (1, 10));
+        let found = detect("En una escala de 0 a 10, ¿cómo te sientes?").unwrap();
+        assert_eq!((found.min, found.max), (0, 10));
+
+        assert!(detect("Do you walk between 2 and 3 times a week?").is_none());
+        assert!(detect("On a scale from 0 to 10 you said 7. What helped?").is_none());
+        assert!(detect("What would you like to talk about?").is_none());
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Instrument definitions compiled into the binary.
+const INSTRUMENTS_TOML: &str = include_str!("../../assessments/instruments.toml");
+
+static BUILTIN: OnceLock<InstrumentCatalog> = OnceLock::new();
+
+/// The questionnaires Chiron can administer, with their response scales.
+#[derive(Debug, Deserialize)]
+pub struct InstrumentCatalog {
+    #[serde(default)]
+    scales: HashMap<String, ResponseScale>,
+    instruments: Vec<Instrument>,
+}
+
+/// The answers an item accepts: whole numbers from `min` to `max`, with an
+/// optional label for each value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResponseScale {
+    pub min: i32,
+    pub max: i32,
+    #[serde(default)]
+    pub anchors: Vec<String>,
+}
+
+/// How item responses combine into a total.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoringRule {
+    Sum,
+    Mean,
+}
+
+/// A range of totals with a descriptive label, e.g. 10–14 "moderate".
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeverityBand {
+    pub min: f64,
+    pub max: f64,
+    pub label: String,
+}
+
+/// A questionnaire: its items in order, how they're answered, and how
+/// they're scored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Instrument {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Stem shown before the items ("Over the last 2 weeks, ...").
+    pub prompt: String,
+    /// Key into the catalog's `scales`.
+    scale: String,
+    pub scoring: ScoringRule,
+    pub items: Vec<String>,
+    /// 1-based item numbers scored in reverse.
+    #[serde(default)]
+    pub reverse_items: Vec<usize>,
+    /// 1-based item numbers flagged whenever answered above the minimum.
+    #[serde(default)]
+    pub critical_items: Vec<usize>,
+    pub bands: Vec<SeverityBand>,
+}
+
+/// A scored administration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub total: f64,
+    /// Label of the severity band the total falls in.
+    pub band: Option<String>,
+    /// 1-based critical items answered above the scale minimum.
+    pub flagged_items: Vec<usize>,
+}
+
+impl InstrumentCatalog {
+    /// The built-in catalog (`assessments/instruments.toml`).
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| {
+            Self::parse(INSTRUMENTS_TOML).expect("built-in instruments.toml is valid")
+        })
+    }
+
+    /// Parses and validates a catalog: every instrument must name a known
+    /// scale, refer only to items it has, and list bands in ascending order.
+    pub fn parse(source: &str) -> Result<Self> {
+        let catalog: Self = toml::from_str(source).context("Failed to parse instrument catalog")?;
+        for instrument in &catalog.instruments {
+            let id = &instrument.id;
+            let Some(scale) = catalog.scales.get(&instrument.scale) else {
+                bail!("Instrument '{id}' uses unknown scale '{}'", instrument.scale);
+            };
+            if scale.min >= scale.max {
+                bail!("Scale '{}' must have min < max", instrument.scale);
+            }
+            if !scale.anchors.is_empty() && scale.anchors.len() != (scale.max - scale.min + 1) as usize {
+                bail!("Scale '{}' needs one anchor per value or none", instrument.scale);
+            }
+            if instrument.items.is_empty() {
+                bail!("Instrument '{id}' has no items");
+            }
+            let in_range = |n: &usize| (1..=instrument.items.len()).contains(n);
+            if !instrument.reverse_items.iter().chain(&instrument.critical_items).all(in_range) {
+                bail!("Instrument '{id}' refers to an item it doesn't have");
+            }
+            if instrument.bands.windows(2).any(|w| w[0].min >= w[1].min) {
+                bail!("Instrument '{id}' bands must be in ascending order");
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Looks up an instrument by ID (case-insensitive).
+    pub fn get(&self, id: &str) -> Option<&Instrument> {
+        self.instruments.iter().find(|i| i.id.eq_ignore_ascii_case(id))
+    }
+
+    /// All instruments, in catalog order.
+    pub fn instruments(&self) -> &[Instrument] {
+        &self.instruments
+    }
+
+    /// The response scale an instrument uses.
+    pub fn scale_for(&self, instrument: &Instrument) -> &ResponseScale {
+        &self.scales[&instrument.scale]
+    }
+
+    /// Scores `responses` (one per item, in order) against an instrument.
+    pub fn score(&self, instrument: &Instrument, responses: &[i32]) -> Result<Score> {
+        let scale = self.scale_for(instrument);
+        if responses.len() != instrument.items.len() {
+            bail!(
+                "{} needs {} responses, got {}",
+                instrument.name,
+                instrument.items.len(),
+                responses.len()
+            );
+        }
+        if let Some(bad) = responses.iter().find(|r| !(scale.min..=scale.max).contains(r)) {
+            bail!("Response {bad} is outside {}–{}", scale.min, scale.max);
+        }
+
+        let scored = responses.iter().enumerate().map(|(i, &r)| {
+            if instrument.reverse_items.contains(&(i + 1)) {
+                scale.max + scale.min - r
+            } else {
+                r
+            }
+        });
+        let sum: i32 = scored.sum();
+        let total = match instrument.scoring {
+            ScoringRule::Sum => f64::from(sum),
+            ScoringRule::Mean => f64::from(sum) / responses.len() as f64,
+        };
+
+        let band = instrument
+            .bands
+            .iter()
+            .rev()
+            .find(|b| total >= b.min && total <= b.max)
+            .map(|b| b.label.clone());
+        let flagged_items = instrument
+            .critical_items
+            .iter()
+            .copied()
+            .filter(|&n| responses[n - 1] > scale.min)
+            .collect();
+
+        Ok(Score { total, band, flagged_items })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog_loads() {
+        let catalog = InstrumentCatalog::builtin();
+        let phq9 = catalog.get("PHQ9").unwrap();
+        assert_eq!(phq9.items.len(), 9);
+        assert_eq!(catalog.scale_for(phq9).anchors.len(), 4);
+        assert!(catalog.get("gad7").is_some());
+    }
+
+    #[test]
+    fn test_sum_scoring_bands_and_critical_items() {
+        let catalog = InstrumentCatalog::builtin();
+        let phq9 = catalog.get("phq9").unwrap();
+
+        let score = catalog.score(phq9, &[2, 2, 1, 1, 1, 1, 1, 1, 0]).unwrap();
+        assert_eq!(score.total, 10.0);
+        assert_eq!(score.band.as_deref(), Some("moderate"));
+        assert!(score.flagged_items.is_empty());
+
+        let score = catalog.score(phq9, &[0, 0, 0, 0, 0, 0, 0, 0, 1]).unwrap();
+        assert_eq!(score.band.as_deref(), Some("minimal"));
+        assert_eq!(score.flagged_items, vec![9]);
+
+        assert!(catalog.score(phq9, &[0; 8]).is_err(), "wrong item count");
+        assert!(catalog.score(phq9, &[4, 0, 0, 0, 0, 0, 0, 0, 0]).is_err(), "out of range");
+    }
+
+    #[test]
+    fn test_mean_scoring_with_reverse_items() {
+        let catalog = InstrumentCatalog::parse(
+            r#"
+            [scales.agree]
+            min = 1
+            max = 5
+
+            [[instruments]]
+            id = "demo"
+            name = "Demo"
+            description = "Two-item demo"
+            prompt = "How much do you agree?"
+            scale = "agree"
+            scoring = "mean"
+            reverse_items = [2]
+            items = ["I feel calm", "I feel tense"]
+            bands = [{ min = 1, max = 2.9, label = "low" }, { min = 3, max = 5, label = "high" }]
+            "#,
+        )
+        .unwrap();
+        let demo = catalog.get("demo").unwrap();
+        // 4 and reversed 2 → 4: mean 4
+        assert_eq!(catalog.score(demo, &[4, 2]).unwrap().total, 4.0);
+        assert_eq!(catalog.score(demo, &[1, 5]).unwrap().band.as_deref(), Some("low"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_definitions() {
+        let unknown_scale = r#"
+            [[instruments]]
+            id = "x"
+            name = "X"
+            description = ""
+            prompt = ""
+            scale = "missing"
+            scoring = "sum"
+            items = ["a"]
+            bands = []
+        "#;
+        assert!(InstrumentCatalog::parse(unknown_scale).is_err());
+
+        let bad_item = r#"
+            [scales.s]
+            min = 0
+            max = 1
+
+            [[instruments]]
+            id = "x"
+            name = "X"
+            description = ""
+            prompt = ""
+            scale = "s"
+            scoring = "sum"
+            critical_items = [2]
+            items = ["a"]
+            bands = []
+        "#;
+        assert!(InstrumentCatalog::parse(bad_item).is_err());
+    }
+}
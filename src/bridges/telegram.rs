@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use serde::Deserialize;
+
+use crate::i18n::{t, tf};
+use crate::engine::{ChatEvent, ChironEngine};
+use crate::error::ChironError;
+use crate::memory::telegram_chats;
+use crate::safety_plan;
+
+/// Long-poll timeout for `getUpdates`, in seconds.
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Telegram rejects messages longer than this many characters.
+const MAX_MESSAGE_CHARS: usize = 4096;
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Deserialize)]
+struct IncomingMessage {
+    chat: Chat,
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+/// A Telegram bot bridge using long polling.
+///
+/// Each allowed chat gets its own Chiron session (`telegram_*` IDs), kept
+/// in the database so a restart carries on where the chat left off. Chats
+/// not in `allowed_chats` are refused and told their chat ID, so the owner
+/// can add it to the allow list.
+pub struct TelegramBridge {
+    client: reqwest::Client,
+    base_url: String,
+    allowed_chats: Vec<i64>,
+    engine: Arc<ChironEngine>,
+}
+
+impl TelegramBridge {
+    pub fn new(token: &str, allowed_chats: Vec<i64>, engine: Arc<ChironEngine>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: format!("https://api.telegram.org/bot{token}"),
+            allowed_chats,
+            engine,
+        }
+    }
+
+    /// Polls for messages until the process is stopped.
+    pub async fn run(self) -> Result<()> {
+        let mut offset = 0;
+        loop {
+            let updates = match self.get_updates(offset).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Telegram poll failed, retrying");
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+
+            for update in updates {
+                offset = update.update_id + 1;
+                let Some(IncomingMessage { chat, text: Some(text) }) = update.message else {
+                    continue;
+                };
+                if let Err(e) = self.handle_message(chat.id, text.trim()).await {
+                    tracing::error!(chat_id = chat.id, error = %e, "Failed to handle message");
+                    let _ = self
+                        .send_message(chat.id, t("telegram.error"))
+                        .await;
+                }
+            }
+        }
+    }
+
+    async fn handle_message(&self, chat_id: i64, text: &str) -> Result<()> {
+        if !self.allowed_chats.contains(&chat_id) {
+            tracing::warn!(chat_id, "Refused message from chat not in allow list");
+            return self
+                .send_message(chat_id, &tf("telegram.private", &[("chat_id", &chat_id)]))
+                .await;
+        }
+
+        match text {
+            "" => Ok(()),
+            "/start" => {
+                self.send_message(chat_id, t("telegram.greeting")).await
+            }
+            "/crisis" => {
+                let text = safety_plan::break_glass(&self.engine.chat_conn).await;
+                self.send_message(chat_id, &text).await
+            }
+            "/end" => {
+                // The next message starts a fresh session
+                let summary = match telegram_chats::end_chat_session(&self.engine.chat_conn, chat_id).await? {
+                    Some(session_id) => match self.engine.end_session(&session_id).await {
+                        Ok(summary) => summary,
+                        // Opened, but never got a message before a restart
+                        Err(ChironError::SessionNotFound(_)) => t("telegram.no_session").to_string(),
+                        Err(e) => return Err(e.into()),
+                    },
+                    None => t("telegram.no_session").to_string(),
+                };
+                self.send_message(chat_id, &summary).await
+            }
+            _ => {
+                let session_id = self.chat_session(chat_id).await?;
+
+                // Replies are sent whole; streamed tokens are not needed here.
+                let mut events = self.engine.send_message(&session_id, text.to_string()).await?;
+                while let Some(event) = events.next().await {
+                    match event {
+                        ChatEvent::Done(result) => return self.send_message(chat_id, &result.response).await,
+                        ChatEvent::Reply(reply) => return self.send_message(chat_id, &reply).await,
+                        ChatEvent::Error(message) => bail!("Turn failed: {message}"),
+                        ChatEvent::Safety | ChatEvent::Token(_) => {}
+                    }
+                }
+                bail!("Turn ended without a response")
+            }
+        }
+    }
+
+    /// The session the chat is in: the one it was in before (even across a
+    /// restart), or a new one.
+    async fn chat_session(&self, chat_id: i64) -> Result<String> {
+        let conn = &self.engine.chat_conn;
+        if let Some(session_id) = telegram_chats::chat_session(conn, chat_id).await? {
+            match self.engine.session(&session_id).await {
+                Ok(_) => return Ok(session_id),
+                // Opened, but never got a message before a restart
+                Err(ChironError::SessionNotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let session_id = self.engine.start_session("telegram").await;
+        telegram_chats::set_chat_session(conn, chat_id, &session_id).await?;
+        Ok(session_id)
+    }
+
+    async fn get_updates(&self, offset: i64) -> Result<Vec<Update>> {
+        let response: ApiResponse<Vec<Update>> = self
+            .client
+            .get(format!("{}/getUpdates", self.base_url))
+            .query(&[
+                ("offset", offset.to_string()),
+                ("timeout", POLL_TIMEOUT_SECS.to_string()),
+            ])
+            .timeout(Duration::from_secs(POLL_TIMEOUT_SECS + 10))
+            .send()
+            .await
+            .context("getUpdates request failed")?
+            .json()
+            .await
+            .context("Failed to parse getUpdates response")?;
+
+        if !response.ok {
+            bail!(
+                "getUpdates failed: {}",
+                response.description.unwrap_or_default()
+            );
+        }
+        Ok(response.result.unwrap_or_default())
+    }
+
+    async fn send_message(&self, chat_id: i64, text: &str) -> Result<()> {
+        for part in split_message(text, MAX_MESSAGE_CHARS) {
+            let response: ApiResponse<serde_json::Value> = self
+                .client
+                .post(format!("{}/sendMessage", self.base_url))
+                .json(&serde_json::json!({ "chat_id": chat_id, "text": part }))
+                .send()
+                .await
+                .context("sendMessage request failed")?
+                .json()
+                .await
+                .context("Failed to parse sendMessage response")?;
+
+            if !response.ok {
+                bail!(
+                    "sendMessage failed: {}",
+                    response.description.unwrap_or_default()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits text into parts of at most `max_chars` characters, preferring
+/// line breaks as split points.
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for line in text.split_inclusive('\n') {
+        if !current.is_empty() && current.chars().count() + line.chars().count() > max_chars {
+            parts.push(std::mem::take(&mut current));
+        }
+        // Lines longer than the limit are hard-split
+        for c in line.chars() {
+            if current.chars().count() == max_chars {
+                parts.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+
+    parts
+        .into_iter()
+        .map(|p| p.trim_end().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_short_message() {
+        assert_eq!(split_message("hello", 100), vec!["hello"]);
+        assert!(split_message("", 100).is_empty());
+    }
+
+    #[test]
+    fn test_split_long_message() {
+        let text = "a".repeat(250);
+        let parts = split_message(&text, 100);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.chars().count() <= 100));
+        assert_eq!(parts.concat(), text);
+    }
+
+    #[test]
+    fn test_parse_update() {
+        let json = r#"{"ok":true,"result":[{"update_id":7,"message":{"chat":{"id":42},"text":"hi"}}]}"#;
+        let response: ApiResponse<Vec<Update>> = serde_json::from_str(json).unwrap();
+        let update = &response.result.unwrap()[0];
+        assert_eq!(update.update_id, 7);
+        let message = update.message.as_ref().unwrap();
+        assert_eq!(message.chat.id, 42);
+        assert_eq!(message.text.as_deref(), Some("hi"));
+    }
+}
@@ -1,8 +1,10 @@
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result};
-use serde::Deserialize;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::mock::prompt_hash;
 
 /// A collection of prompt variants loaded from a TOML catalog file.
 #[derive(Deserialize)]
@@ -32,6 +34,24 @@ impl PromptCatalog {
                 format!("Variant '{}' not found. Available: {:?}", id, available)
             })
     }
+    /// Builds an A/B test from comma-separated variant IDs. Empty means no
+    /// test; a test needs at least two distinct variants.
+    pub fn ab_test(&self, ids: &str, unit: AbUnit) -> Result<Option<AbTest>> {
+        let ids: Vec<&str> = ids.split(',').map(str::trim).filter(|id| !id.is_empty()).collect();
+        if ids.is_empty() {
+            return Ok(None);
+        }
+        let mut variants: Vec<PromptVariant> = Vec::new();
+        for id in ids {
+            if !variants.iter().any(|v| v.id == id) {
+                variants.push(self.get_variant(id)?.clone());
+            }
+        }
+        if variants.len() < 2 {
+            bail!("An A/B test needs at least two different variants");
+        }
+        Ok(Some(AbTest { variants, unit }))
+    }
 }
 
 /// A single prompt variant with generation parameters.
@@ -44,6 +64,53 @@ pub struct PromptVariant {
     pub preamble: String,
 }
 
+impl PromptVariant {
+    /// Short hash of what the variant sends the model, its template and
+    /// generation settings, recorded with each reply so replies from before
+    /// and after an edit to the prompt can be told apart.
+    pub fn version(&self) -> String {
+        let hash = prompt_hash(&format!("{}\n{}\n{}", self.preamble, self.temperature, self.max_tokens));
+        hash[..8].to_string()
+    }
+}
+
+/// What an A/B test alternates prompt variants across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AbUnit {
+    /// Each turn uses the next variant.
+    Turn,
+    /// Each new session uses the next variant for all its turns.
+    #[default]
+    Session,
+}
+
+/// Coach prompt variants alternated on real usage, so `chiron eval
+/// --history` can compare them. Each response records which one produced it.
+#[derive(Debug, Clone)]
+pub struct AbTest {
+    pub variants: Vec<PromptVariant>,
+    pub unit: AbUnit,
+}
+
+impl AbTest {
+    /// The variant for a turn (1-based) of a session in `arm`. Per-turn tests
+    /// start each session one variant further along, so every variant gets
+    /// first turns.
+    pub fn variant(&self, arm: usize, turn_number: i32) -> &PromptVariant {
+        let index = match self.unit {
+            AbUnit::Turn => arm + (turn_number.max(1) - 1) as usize,
+            AbUnit::Session => arm,
+        };
+        &self.variants[index % self.variants.len()]
+    }
+
+    /// The arm whose sessions start with `variant_id`.
+    pub fn arm_of(&self, variant_id: &str) -> Option<usize> {
+        self.variants.iter().position(|v| v.id == variant_id)
+    }
+}
+
 /// A collection of conversation modes loaded from modes.toml.
 #[derive(Deserialize, Clone)]
 pub struct ModeCatalog {
@@ -74,6 +141,83 @@ impl ModeCatalog {
     }
 }
 
+/// A collection of therapeutic modalities loaded from modalities.toml.
+#[derive(Deserialize, Clone)]
+pub struct ModalityCatalog {
+    pub modalities: Vec<Modality>,
+}
+
+/// A therapeutic modality (CBT, ACT, ...) that frames which skills the coach draws on.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Modality {
+    pub id: String,
+    pub name: String,
+    /// One-line explanation shown by `/modality`.
+    pub description: String,
+    /// Added to the preamble while the modality is active (empty = none).
+    pub coach_framing: String,
+}
+
+impl ModalityCatalog {
+    /// Loads a modality catalog from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Returns the modality whose ID matches, if any.
+    pub fn get_modality(&self, id: &str) -> Option<&Modality> {
+        self.modalities.iter().find(|m| m.id == id)
+    }
+}
+
+/// A collection of guided relaxation scripts loaded from relaxation.toml.
+#[derive(Deserialize, Clone)]
+pub struct RelaxationCatalog {
+    pub scripts: Vec<RelaxationScript>,
+}
+
+/// A timed relaxation script (paced breathing, PMR, body scan) for `/relax`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RelaxationScript {
+    pub id: String,
+    pub name: String,
+    /// One-line explanation shown by `/relax`.
+    pub description: String,
+    pub steps: Vec<RelaxationStep>,
+}
+
+/// One instruction and how long to hold before the next.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RelaxationStep {
+    pub text: String,
+    pub pause_secs: u64,
+}
+
+impl RelaxationScript {
+    /// Total running time in seconds (pauses only).
+    pub fn duration_secs(&self) -> u64 {
+        self.steps.iter().map(|s| s.pause_secs).sum()
+    }
+}
+
+impl RelaxationCatalog {
+    /// Loads a relaxation catalog from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Returns the script whose ID matches, if any.
+    pub fn get_script(&self, id: &str) -> Option<&RelaxationScript> {
+        self.scripts.iter().find(|s| s.id == id)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,6 +241,33 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_variant_version() {
+        let catalog = PromptCatalog::load(&prompts_dir().join("coach.toml")).unwrap();
+        let variant = catalog.get_variant("v5-finetuned").unwrap().clone();
+        assert_eq!(variant.version().len(), 8);
+        let edited = PromptVariant { preamble: format!("{} Be brief.", variant.preamble), ..variant.clone() };
+        assert_ne!(edited.version(), variant.version());
+        assert_ne!(PromptVariant { temperature: 0.2, ..variant.clone() }.version(), variant.version());
+    }
+
+    #[test]
+    fn test_ab_test_alternates() {
+        let catalog = PromptCatalog::load(&prompts_dir().join("coach.toml")).unwrap();
+        assert!(catalog.ab_test("", AbUnit::Session).unwrap().is_none());
+        assert!(catalog.ab_test("v5-finetuned, v5-finetuned", AbUnit::Session).is_err());
+
+        let by_session = catalog.ab_test("v5-finetuned,v6-strategic", AbUnit::Session).unwrap().unwrap();
+        assert_eq!(by_session.variant(0, 3).id, "v5-finetuned");
+        assert_eq!(by_session.variant(1, 1).id, "v6-strategic");
+        assert_eq!(by_session.variant(2, 1).id, "v5-finetuned");
+
+        let by_turn = AbTest { unit: AbUnit::Turn, ..by_session };
+        let turns: Vec<&str> = (1..=3).map(|t| by_turn.variant(1, t).id.as_str()).collect();
+        assert_eq!(turns, ["v6-strategic", "v5-finetuned", "v6-strategic"]);
+        assert_eq!(by_turn.arm_of("v6-strategic"), Some(1));
+    }
+
     #[test]
     fn test_load_mode_catalog() {
         let catalog = ModeCatalog::load(&prompts_dir().join("modes.toml")).unwrap();
@@ -107,4 +278,27 @@ mod tests {
         assert!(catalog.get_mode("ambivalence").is_some());
         assert!(catalog.get_mode("engagement").is_some());
     }
+
+    #[test]
+    fn test_load_modality_catalog() {
+        let catalog = ModalityCatalog::load(&prompts_dir().join("modalities.toml")).unwrap();
+        assert_eq!(catalog.modalities[0].id, "supportive", "default modality first");
+        assert!(catalog.modalities[0].coach_framing.is_empty());
+        for id in ["cbt", "act", "dbt-skills"] {
+            let modality = catalog.get_modality(id).unwrap();
+            assert!(!modality.coach_framing.is_empty(), "{id} has no framing");
+        }
+        assert!(catalog.get_modality("psychoanalysis").is_none());
+    }
+
+    #[test]
+    fn test_load_relaxation_catalog() {
+        let catalog = RelaxationCatalog::load(&prompts_dir().join("relaxation.toml")).unwrap();
+        for id in ["breathing", "pmr", "body-scan"] {
+            let script = catalog.get_script(id).unwrap();
+            assert!(!script.steps.is_empty(), "{id} has no steps");
+            assert!(script.duration_secs() >= 60, "{id} is too short");
+        }
+        assert!(catalog.get_script("hypnosis").is_none());
+    }
 }
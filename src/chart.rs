@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use chrono::{Duration, NaiveDate};
+
+use crate::timezone;
+
+/// Sparkline bars from lowest to highest.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Parses a period like `30d`, `4w`, or `14` (days) into a day count.
+pub fn parse_period(period: &str) -> Result<u32> {
+    let period = period.trim().to_lowercase();
+    let (number, multiplier) = match period.as_bytes().last() {
+        Some(b'd') => (&period[..period.len() - 1], 1),
+        Some(b'w') => (&period[..period.len() - 1], 7),
+        _ => (period.as_str(), 1),
+    };
+    match number.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n * multiplier),
+        _ => bail!("Invalid period '{period}' (expected e.g. 30d or 4w)"),
+    }
+}
+
+/// Buckets `(date, value)` points into one slot per day ending at `today`,
+/// averaging multiple values on the same day. Days without data are `None`.
+///
+/// Dates are stored UTC timestamps, bucketed by their day in the display
+/// time zone (see `timezone`).
+pub fn daily_series(points: &[(String, f64)], days: u32, today: NaiveDate) -> Vec<Option<f64>> {
+    let start = today - Duration::days(days as i64 - 1);
+    let mut sums = vec![(0.0, 0u32); days as usize];
+
+    for (timestamp, value) in points {
+        let Some(date) = timezone::date(timestamp) else {
+            continue;
+        };
+        let offset = (date - start).num_days();
+        if (0..days as i64).contains(&offset) {
+            let slot = &mut sums[offset as usize];
+            slot.0 += value;
+            slot.1 += 1;
+        }
+    }
+
+    sums.into_iter()
+        .map(|(sum, n)| (n > 0).then(|| sum / n as f64))
+        .collect()
+}
+
+/// Renders a sparkline scaled to `[min, max]`. Missing days are blank.
+pub fn sparkline(series: &[Option<f64>], min: f64, max: f64) -> String {
+    let range = (max - min).max(f64::EPSILON);
+    series
+        .iter()
+        .map(|v| match v {
+            Some(v) => {
+                let scaled = ((v - min) / range).clamp(0.0, 1.0);
+                BARS[(scaled * (BARS.len() - 1) as f64).round() as usize]
+            }
+            None => ' ',
+        })
+        .collect()
+}
+
+/// Average, min, and max of the days that have data.
+pub fn stats(series: &[Option<f64>]) -> Option<(f64, f64, f64)> {
+    let values: Vec<f64> = series.iter().flatten().copied().collect();
+    if values.is_empty() {
+        return None;
+    }
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    Some((avg, min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("30d").unwrap(), 30);
+        assert_eq!(parse_period("4w").unwrap(), 28);
+        assert_eq!(parse_period("14").unwrap(), 14);
+        assert!(parse_period("0d").is_err());
+        assert!(parse_period("month").is_err());
+    }
+
+    #[test]
+    fn test_daily_series_buckets_and_averages() {
+        let points = vec![
+            ("2026-03-01 09:00:00".to_string(), 4.0),
+            ("2026-03-03 08:00:00".to_string(), 6.0),
+            ("2026-03-03 21:00:00".to_string(), 8.0),
+            ("2026-02-01 08:00:00".to_string(), 1.0), // outside window
+        ];
+        let series = daily_series(&points, 3, day("2026-03-03"));
+        assert_eq!(series, vec![Some(4.0), None, Some(7.0)]);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let line = sparkline(&[Some(1.0), None, Some(10.0), Some(5.5)], 1.0, 10.0);
+        assert_eq!(line.chars().collect::<Vec<_>>(), vec!['▁', ' ', '█', '▅']);
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(stats(&[Some(2.0), None, Some(4.0)]), Some((3.0, 2.0, 4.0)));
+        assert_eq!(stats(&[None, None]), None);
+    }
+}
@@ -0,0 +1,462 @@
+//! Interactive chat: the CLI as one engine session on stdout, with voice
+//! input, the idle lock, and the `/hide` decoy screen.
+
+use std::io::{self, Write};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::FutureExt;
+use tokio::sync::watch;
+
+use crate::config::Config;
+use crate::engine::ChironEngine;
+use crate::i18n::{t, tf};
+use crate::memory::{self, InputSource};
+use crate::memory::session_names::SessionRef;
+use crate::orchestrator::commands::{Handled, TerminalCommand};
+use crate::orchestrator::Orchestrator;
+use crate::{ambiguous, ChatArgs};
+use crate::{assessment, closure, episodes, homework, lock, relax, secrets, sleep, speech, values, voice, wrap};
+
+/// The session a chat continues, and how it was picked.
+struct Opened {
+    session_id: String,
+    /// Continued with `--resume` or `--thread`, so the opening questions
+    /// were already asked.
+    resumed: bool,
+    /// Split off as a new episode after a long silence.
+    new_episode: bool,
+}
+
+/// Runs interactive chat until the user quits, input ends, or Ctrl+C.
+pub async fn run(engine: Arc<ChironEngine>, chat_args: &ChatArgs, settings: &Config) -> Result<()> {
+    // Replies wrap to the terminal, following it when it's resized
+    wrap::watch_resize();
+    let gap_hours = settings.memory.episode_gap_hours;
+    let Opened { session_id, resumed, new_episode } = open_session(&engine, chat_args, gap_hours).await?;
+    let session = engine
+        .session(&session_id)
+        .await
+        .with_context(|| format!("Failed to open session {session_id}"))?;
+    let mut orchestrator = session.lock().await;
+    if let Some(episode) = memory::episodes::episode(&engine.chat_conn, &session_id).await? {
+        orchestrator.set_previous_episode(episode.summary);
+    }
+
+    // Diary commands in the chat loop write to the same database
+    let diary_conn = engine.chat_conn.clone();
+    let diary_session = session_id.clone();
+
+    println!("{}", t("banner.title"));
+    println!("{}", tf("banner.coach", &[("coach", &engine.coach_variant.id)]));
+    println!("{}", t("banner.instructions"));
+
+    if chat_args.speak {
+        orchestrator.set_speaker(speech::Speaker::new(&settings.voice));
+    }
+
+    // `/relax` speaks its scripts too with --speak
+    let relax_speaker = chat_args.speak.then(|| speech::Speaker::new(&settings.voice));
+
+    let voice_input = if chat_args.voice {
+        println!("{}", t("banner.voice"));
+        Some(voice::VoiceInput::new(settings.voice.clone())?)
+    } else {
+        None
+    };
+    println!("---");
+
+    // Read the passphrase now, so a keyring prompt can't appear while locked
+    let idle_lock = match settings.privacy.idle_lock_minutes {
+        0 => None,
+        minutes => lock::IdleLock::new(
+            minutes,
+            secrets::get("sync-passphrase")?.map(|(passphrase, _)| passphrase),
+        ),
+    };
+
+    if resumed {
+        // The opening rating and homework review happened when it started
+        let (last, _) = memory::load_recent_turns(&diary_conn, &diary_session, 1).await?;
+        if last.first().is_some_and(|turn| turn.interrupted) {
+            println!("{}", t("chat.resumed_interrupted"));
+        } else {
+            println!("{}", t("chat.resumed"));
+        }
+    } else {
+        if new_episode {
+            println!("{}", tf("chat.new_episode", &[("hours", &gap_hours)]));
+        }
+        // Outcome rating, then follow up on homework from earlier sessions,
+        // before the first turn
+        assessment::outcomes::session_start(&diary_conn, &diary_session).await?;
+        homework::review(&diary_conn, &diary_session).await?;
+    }
+
+    // Chat loop. However it ends (quit, end of input, Ctrl+C, an error, or
+    // a panic), the session is flushed once below.
+    let shutdown = ctrl_c_shutdown();
+    orchestrator.set_interrupt(shutdown.clone());
+    let mut lines = LineReader::spawn();
+    let chat = AssertUnwindSafe(async {
+        loop {
+            if *shutdown.borrow() {
+                break;
+            }
+            print!("\n{}", t("chat.you"));
+            io::stdout().flush()?;
+
+            let mut interrupted = shutdown.clone();
+            let line = tokio::select! {
+                line = lines.next_line() => line.context("Failed to read input")?,
+                Ok(_) = interrupted.wait_for(|requested| *requested) => break,
+                idle_lock = lock::idle(idle_lock.as_ref()) => {
+                    // Nothing waits in memory while the chat is locked
+                    orchestrator.flush_saves().await.context("Failed to save the last turn")?;
+                    if !lock_screen(&mut lines, idle_lock, &shutdown).await? {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            let Some(input) = line else {
+                break;
+            };
+
+            let mut input = input.trim().to_string();
+            let mut source = InputSource::Text;
+
+            if input.is_empty() {
+                let Some(voice) = &voice_input else {
+                    continue;
+                };
+                println!("\x1b[2m{}\x1b[0m", t("voice.listening"));
+                match voice.listen() {
+                    Ok(text) if !text.is_empty() => {
+                        println!("{}", tf("voice.transcript", &[("text", &text)]));
+                        input = text;
+                        source = InputSource::Voice;
+                    }
+                    Ok(_) => {
+                        println!("{}", t("voice.not_heard"));
+                        continue;
+                    }
+                    Err(e) => {
+                        eprintln!("{}", tf("voice.failed", &[("error", &format!("{e:#}"))]));
+                        continue;
+                    }
+                }
+            }
+            let input = input.as_str();
+
+            // Quitting, or a bare goodbye after the coach's closing reply
+            if input.eq_ignore_ascii_case("quit")
+                || input.eq_ignore_ascii_case("exit")
+                || (orchestrator.closing() && closure::is_farewell(input))
+            {
+                assessment::outcomes::session_end(&diary_conn, &diary_session).await?;
+                break;
+            }
+
+            let handled = match orchestrator.command(input).await? {
+                Some(handled) => handled,
+                None => {
+                    if orchestrator.is_offline() {
+                        println!("\x1b[2m{}\x1b[0m", t("offline.retrying"));
+                    }
+                    orchestrator.set_input_source(source);
+                    match orchestrator.reply(input).await {
+                        Ok(handled) => handled,
+                        Err(e) => {
+                            // Nothing was saved; the chat carries on
+                            tracing::error!(error = %e, "Turn failed");
+                            eprintln!("\n{}", tf("chat.turn_failed", &[("error", &e.to_string())]));
+                            continue;
+                        }
+                    }
+                }
+            };
+            match handled {
+                Handled::Turn(turn) => {
+                    if let Some(scale) = orchestrator.pending_scale() {
+                        println!(
+                            "\x1b[2m{}\x1b[0m",
+                            tf("quick_reply.hint", &[("min", &scale.min), ("max", &scale.max)])
+                        );
+                    }
+                    if orchestrator.closing() && !turn.interrupted {
+                        println!("\x1b[2m{}\x1b[0m", t("chat.closing_hint"));
+                    }
+                }
+                Handled::Reply(reply) => println!("{reply}"),
+                Handled::Ended(summary) => {
+                    if let Some(name) = &chat_args.thread {
+                        memory::threads::set_thread_session(&diary_conn, name, orchestrator.session_id()).await?;
+                    }
+                    println!("\n{}", t("chat.summary_header"));
+                    println!("{summary}");
+                    assessment::outcomes::session_end(&diary_conn, &diary_session).await?;
+                    println!("{}", t("chat.new_session"));
+                }
+                Handled::Terminal(TerminalCommand::Hide) => {
+                    if !hide_screen(&mut lines, &shutdown, &orchestrator).await? {
+                        break;
+                    }
+                }
+                Handled::Terminal(TerminalCommand::Sleep(args)) => match args.as_str() {
+                    "log" => sleep::log(&diary_conn).await?,
+                    "week" | "" => sleep::show_week(&diary_conn).await?,
+                    _ => println!("{}", t("sleep.usage")),
+                },
+                Handled::Terminal(TerminalCommand::Values(args)) => match args.as_str() {
+                    "explore" => values::explore(&diary_conn).await?,
+                    "" => values::show(&diary_conn).await?,
+                    _ => println!("{}", t("values.usage")),
+                },
+                Handled::Terminal(TerminalCommand::Relax(args)) => {
+                    relax::command(
+                        &diary_conn,
+                        engine.relax_catalog.as_ref(),
+                        relax_speaker.as_ref(),
+                        &diary_session,
+                        &args,
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    })
+    .catch_unwind()
+    .await;
+
+    // After a panic (already reported by the panic hook), the turn it
+    // interrupted is saved so `--resume` can pick the session up
+    if chat.is_err() {
+        if let Err(e) = orchestrator.save_interrupted_turn().await {
+            tracing::error!(error = %e, "Failed to save the interrupted turn");
+        }
+    }
+
+    orchestrator.flush().await;
+    match chat {
+        Ok(chat) => {
+            println!("{}", t("chat.goodbye"));
+            chat
+        }
+        Err(_) => {
+            eprintln!("\n{}", t("chat.crashed"));
+            anyhow::bail!("Session {session_id} ended by a crash")
+        }
+    }
+}
+
+/// Picks the session to chat in: the one `--resume` or `--thread` names,
+/// or a new one. After a long silence the conversation continues as a new
+/// episode, linked to the old one and starting with its summary.
+async fn open_session(engine: &ChironEngine, chat_args: &ChatArgs, gap_hours: u64) -> Result<Opened> {
+    let thread_session = match &chat_args.thread {
+        Some(name) => thread_session_id(&engine.chat_conn, name).await?,
+        None => None,
+    };
+    let mut resumed = chat_args.resume.is_some() || thread_session.is_some();
+    let mut session_id = match (&chat_args.resume, thread_session) {
+        (Some(id), _) => resumed_session_id(&engine.chat_conn, id.as_deref()).await?,
+        (None, Some(id)) => id,
+        (None, None) => engine.start_session("session").await,
+    };
+    let mut new_episode = false;
+    if resumed && gap_hours > 0 {
+        let now = chrono::Utc::now().naive_utc();
+        if let Some(summary) = episodes::episode_break(&engine.chat_conn, &session_id, gap_hours, now).await? {
+            let previous = std::mem::replace(&mut session_id, engine.start_session("session").await);
+            memory::episodes::start_episode(&engine.chat_conn, &session_id, &previous, &summary).await?;
+            tracing::info!(previous, session_id, "Starting a new episode after a long gap");
+            new_episode = true;
+            resumed = false;
+        }
+    }
+    if let Some(name) = &chat_args.thread {
+        memory::threads::set_thread_session(&engine.chat_conn, name, &session_id).await?;
+    }
+    tracing::info!(session_id, resumed, thread = chat_args.thread.as_deref(), "Starting interactive session");
+    Ok(Opened { session_id, resumed, new_episode })
+}
+
+/// The session `--thread <name>` continues: the thread's latest session,
+/// unless it was archived or never got a message. `None` starts a new one.
+async fn thread_session_id(conn: &tokio_rusqlite::Connection, name: &str) -> Result<Option<String>> {
+    let Some(session_id) = memory::threads::thread_session(conn, name).await? else {
+        return Ok(None);
+    };
+    let sessions = memory::list_sessions(conn)
+        .await
+        .context("Failed to list sessions")?;
+    Ok(sessions
+        .into_iter()
+        .find(|session| session.session_id == session_id && !session.archived)
+        .map(|session| session.session_id))
+}
+
+/// The session `--resume` continues: the given ID, or the most recently
+/// active one. Archived sessions have no transcript to continue.
+async fn resumed_session_id(conn: &tokio_rusqlite::Connection, id: Option<&str>) -> Result<String> {
+    let sessions = memory::list_sessions(conn)
+        .await
+        .context("Failed to list sessions")?;
+    let session = match id {
+        Some(id) => match memory::session_names::resolve_session(conn, id).await? {
+            SessionRef::Found(found) => sessions.into_iter().find(|session| session.session_id == found),
+            SessionRef::NotFound => None,
+            SessionRef::Ambiguous(ids) => anyhow::bail!(ambiguous(id, &ids)),
+        },
+        None => sessions.into_iter().next(),
+    };
+    match (session, id) {
+        (Some(session), _) if session.archived => {
+            anyhow::bail!("Session {} is archived; start a new session instead", session.session_id)
+        }
+        (Some(session), _) => Ok(session.session_id),
+        // Unknown IDs start a session under that ID, as before
+        (None, Some(id)) => Ok(id.to_string()),
+        (None, None) => anyhow::bail!("No saved session to resume"),
+    }
+}
+
+/// The first Ctrl+C asks the chat loop to wrap up: a reply being generated
+/// is cut off and saved as interrupted, then the session closes as usual.
+/// A second Ctrl+C quits immediately.
+fn ctrl_c_shutdown() -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        eprintln!("\n{}", t("chat.interrupted"));
+        let _ = tx.send(true);
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+    rx
+}
+/// Clears the screen and holds the chat until the right passphrase (or,
+/// without one, Enter) is typed, with echo off. A message still being
+/// typed is discarded. Returns false if input ended or Ctrl+C was pressed
+/// while locked.
+async fn lock_screen(
+    lines: &mut LineReader,
+    idle_lock: &lock::IdleLock,
+    shutdown: &watch::Receiver<bool>,
+) -> Result<bool> {
+    tracing::info!("Locked after inactivity");
+    let _no_echo = lock::NoEcho::start();
+    print!("{}", lock::CLEAR_SCREEN);
+    println!("{}", t("lock.locked"));
+    loop {
+        let prompt = if idle_lock.has_passphrase() {
+            t("lock.prompt_passphrase")
+        } else {
+            t("lock.prompt_enter")
+        };
+        print!("\n{prompt}{}", lock::CONCEAL);
+        io::stdout().flush()?;
+
+        let mut interrupted = shutdown.clone();
+        let line = tokio::select! {
+            line = lines.next_line() => line.context("Failed to read input")?,
+            Ok(_) = interrupted.wait_for(|requested| *requested) => None,
+        };
+        print!("{}", lock::RESET);
+        let Some(line) = line else {
+            println!();
+            return Ok(false);
+        };
+        if idle_lock.unlocks(line.trim_end_matches(['\r', '\n'])) {
+            print!("{}", lock::CLEAR_SCREEN);
+            println!("{}", t("lock.unlocked"));
+            return Ok(true);
+        }
+        println!("{}", t("lock.wrong"));
+    }
+}
+
+/// `/hide`: wipes the screen and scrollback and shows an idle shell prompt
+/// until Enter is pressed on an empty line. Anything typed at the decoy
+/// just gets a fresh prompt, as a shell would for an empty command. Turns
+/// autosave is still holding are saved right after the screen clears
+/// (errors are logged), so closing the window instead loses nothing.
+/// Returns false if input ended or Ctrl+C was pressed.
+async fn hide_screen(
+    lines: &mut LineReader,
+    shutdown: &watch::Receiver<bool>,
+    orchestrator: &Orchestrator,
+) -> Result<bool> {
+    print!("{}", lock::CLEAR_SCREEN);
+    io::stdout().flush()?;
+    if let Err(e) = orchestrator.flush_saves().await {
+        tracing::error!(error = %e, "Failed to save the last turns");
+    }
+    loop {
+        print!("{}", lock::DECOY_PROMPT);
+        io::stdout().flush()?;
+
+        let mut interrupted = shutdown.clone();
+        let line = tokio::select! {
+            line = lines.next_line() => line.context("Failed to read input")?,
+            Ok(_) = interrupted.wait_for(|requested| *requested) => None,
+        };
+        let Some(line) = line else {
+            return Ok(false);
+        };
+        if line.trim().is_empty() {
+            print!("{}", lock::CLEAR_SCREEN);
+            return Ok(true);
+        }
+    }
+}
+
+/// Reads chat input on a helper thread so waiting for it can be abandoned
+/// on Ctrl+C. A line is only read when asked for, so prompts that read
+/// stdin directly (ratings, diaries) never race with it.
+struct LineReader {
+    requests: std::sync::mpsc::Sender<()>,
+    lines: tokio::sync::mpsc::Receiver<io::Result<Option<String>>>,
+    /// A line was requested but not yet received (the wait was abandoned),
+    /// so the next call picks it up instead of asking for another.
+    waiting: bool,
+}
+
+impl LineReader {
+    fn spawn() -> Self {
+        let (requests, pending) = std::sync::mpsc::channel();
+        let (tx, lines) = tokio::sync::mpsc::channel(1);
+        std::thread::spawn(move || {
+            while pending.recv().is_ok() {
+                let mut line = String::new();
+                let read = io::stdin()
+                    .read_line(&mut line)
+                    .map(|bytes| (bytes > 0).then_some(line));
+                if tx.blocking_send(read).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { requests, lines, waiting: false }
+    }
+
+    /// The next line of input, or `None` at end of input.
+    async fn next_line(&mut self) -> io::Result<Option<String>> {
+        if !self.waiting {
+            if self.requests.send(()).is_err() {
+                return Ok(None);
+            }
+            self.waiting = true;
+        }
+        let line = self.lines.recv().await.unwrap_or(Ok(None));
+        self.waiting = false;
+        line
+    }
+}
+
@@ -0,0 +1,177 @@
+use std::io::{self, Write};
+
+use anyhow::{Context, Result};
+use tokio_rusqlite::Connection;
+
+use crate::chart;
+use crate::i18n::{t, tf};
+use crate::memory::{self, mood, outcomes};
+use crate::router;
+use crate::timezone;
+use crate::warning;
+
+/// Runs the daily check-in: mood (1–10), hours slept, one highlight, and one
+/// stressor. Only the mood rating is required. The result goes to the mood
+/// log, not to chat history.
+pub async fn run(conn: &Connection) -> Result<()> {
+    println!("{}", t("checkin.intro"));
+
+    let mood = loop {
+        let answer = ask(t("checkin.mood"))?;
+        match parse_mood(&answer) {
+            Some(mood) => break mood,
+            None => println!("{}", t("checkin.mood_invalid")),
+        }
+    };
+
+    let sleep_hours = loop {
+        let answer = ask(t("checkin.sleep"))?;
+        if answer.is_empty() {
+            break None;
+        }
+        match parse_sleep(&answer) {
+            Some(hours) => break Some(hours),
+            None => println!("{}", t("checkin.sleep_invalid")),
+        }
+    };
+
+    let highlight = ask(t("checkin.highlight"))?;
+    let stressor = ask(t("checkin.stressor"))?;
+
+    // Free-text answers get the same safety check as chat messages
+    if router::is_crisis(&highlight) || router::is_crisis(&stressor) {
+        println!("\n{}\n", router::crisis_response());
+    }
+
+    mood::save_checkin(
+        conn,
+        mood,
+        sleep_hours,
+        Some(highlight.as_str()).filter(|s| !s.is_empty()),
+        Some(stressor.as_str()).filter(|s| !s.is_empty()),
+    )
+    .await?;
+
+    // Check-ins are watched for early warning signs too (one flag per day)
+    let day = format!("checkin_{}", timezone::today());
+    let answers = format!("{highlight}\n{stressor}");
+    if let Some(flag) = warning::check(conn, &day, &answers, "checkin").await? {
+        println!("\n{flag}");
+    }
+
+    let week = mood::recent_checkins(conn, 7).await?;
+    if let Some(average) = mood::average_mood(&week) {
+        println!(
+            "{}",
+            tf(
+                "checkin.saved",
+                &[("average", &format!("{average:.1}")), ("count", &week.len())],
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints sparkline charts of mood, sleep, and session activity over the
+/// last `days` days (one character per day, oldest on the left).
+pub async fn show_chart(conn: &Connection, days: u32) -> Result<()> {
+    let checkins = mood::recent_checkins(conn, days).await?;
+    let today = timezone::today();
+
+    let mood_points: Vec<(String, f64)> = checkins
+        .iter()
+        .map(|c| (c.created_at.clone(), c.mood as f64))
+        .collect();
+    let sleep_points: Vec<(String, f64)> = checkins
+        .iter()
+        .filter_map(|c| Some((c.created_at.clone(), c.sleep_hours?)))
+        .collect();
+    let session_points = memory::daily_session_counts(conn, days).await?;
+    let rating_points = |ratings: Vec<outcomes::SessionQuality>| -> Vec<(String, f64)> {
+        ratings.into_iter().map(|r| (r.created_at, r.total)).collect()
+    };
+    let ors_points = rating_points(outcomes::recent_ratings(conn, "ors", days).await?);
+    let srs_points = rating_points(outcomes::recent_ratings(conn, "srs", days).await?);
+
+    println!("{}", tf("mood.chart_header", &[("days", &days)]));
+
+    let rows = [
+        (t("mood.row_mood"), chart::daily_series(&mood_points, days, today), Some((1.0, 10.0))),
+        (t("mood.row_sleep"), chart::daily_series(&sleep_points, days, today), Some((0.0, 12.0))),
+        (t("mood.row_sessions"), chart::daily_series(&session_points, days, today), None),
+        (t("mood.row_ors"), chart::daily_series(&ors_points, days, today), Some((0.0, 40.0))),
+        (t("mood.row_srs"), chart::daily_series(&srs_points, days, today), Some((0.0, 40.0))),
+    ];
+
+    for (label, series, scale) in rows {
+        let Some((avg, min, max)) = chart::stats(&series) else {
+            println!("{label:<10} {}", t("mood.no_data"));
+            continue;
+        };
+        // Counts scale from zero to their own max; ratings use a fixed scale
+        let (lo, hi) = scale.unwrap_or((0.0, max));
+        println!(
+            "{label:<10} {}  {}",
+            chart::sparkline(&series, lo, hi),
+            tf(
+                "mood.stats",
+                &[
+                    ("avg", &format!("{avg:.1}")),
+                    ("min", &format!("{min:.0}")),
+                    ("max", &format!("{max:.0}")),
+                ],
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Prompts on stdout and returns the trimmed answer.
+pub fn ask(prompt: &str) -> Result<String> {
+    print!("{prompt} ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read input")?;
+    Ok(answer.trim().to_string())
+}
+
+/// Parses a mood rating, accepting only whole numbers from 1 to 10.
+fn parse_mood(answer: &str) -> Option<i32> {
+    answer.parse().ok().filter(|m| (1..=10).contains(m))
+}
+
+/// Parses hours slept (0–24, decimals allowed, optional trailing "h").
+fn parse_sleep(answer: &str) -> Option<f64> {
+    answer
+        .trim_end_matches(['h', 'H'])
+        .trim()
+        .parse()
+        .ok()
+        .filter(|h| (0.0..=24.0).contains(h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mood() {
+        assert_eq!(parse_mood("7"), Some(7));
+        assert_eq!(parse_mood("10"), Some(10));
+        assert_eq!(parse_mood("0"), None);
+        assert_eq!(parse_mood("11"), None);
+        assert_eq!(parse_mood("okay"), None);
+    }
+
+    #[test]
+    fn test_parse_sleep() {
+        assert_eq!(parse_sleep("7.5"), Some(7.5));
+        assert_eq!(parse_sleep("6h"), Some(6.0));
+        assert_eq!(parse_sleep("30"), None);
+        assert_eq!(parse_sleep("a lot"), None);
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+/// Phrases that mean they're wrapping up.
+const WINDING_DOWN: &[&str] = &[
+    "that's all", "that is all", "that's it for today", "that's it for now", "that's enough for today",
+    "i should go", "i have to go", "i need to go", "i gotta go", "gotta go", "got to go", "i'm done for today",
+    "done for today", "done for now", "talk later", "talk tomorrow", "talk soon", "see you later",
+    "see you tomorrow", "bye", "goodbye", "good night", "goodnight", "thanks for listening",
+    "thank you for listening", "going to bed", "going to sleep", "let's stop", "wrap up", "wrap this up",
+    "signing off",
+];
+
+/// Winding-down messages are short; a phrase in a longer message ("that's
+/// all I can think about") usually isn't one.
+const MAX_WORDS: usize = 12;
+
+/// A short message after this long a silence reads as trailing off.
+const LONG_PAUSE: Duration = Duration::from_secs(10 * 60);
+const SHORT_MESSAGE_CHARS: usize = 40;
+
+/// Words a bare goodbye is made of.
+const FAREWELL_WORDS: &[&str] = &[
+    "ok", "okay", "bye", "goodbye", "good", "night", "goodnight", "thanks", "thank", "you", "so", "much",
+    "again", "see", "ya", "later", "take", "care", "cheers", "too", "for", "everything", "listening",
+];
+
+/// How the coach closes a session.
+const CLOSING_STEPS: &str = "Close the session instead of opening anything new: in a few sentences, summarize \
+     what you talked about in their words; check how they're feeling as they leave and whether they're safe, and \
+     mention crisis resources if risk came up; agree on one small next step or what to pick up next time; then say \
+     a warm goodbye.";
+
+/// How long before a session's time limit the coach starts steering
+/// toward a close.
+const TIME_WARNING: Duration = Duration::from_secs(5 * 60);
+
+/// How often, once past the limit, wrapping up is proposed again.
+const TIME_REMINDER: Duration = Duration::from_secs(15 * 60);
+
+/// What the coach is asked to do once they're winding down.
+pub fn closing_instructions() -> String {
+    format!("They seem to be wrapping up. {CLOSING_STEPS} If they'd rather keep going, follow them.")
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('’', "'")
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Whether a message, and how long it took to arrive after the last reply,
+/// suggests they're winding down: a short message with a closing phrase
+/// ("I think that's all"), or a short one after a long pause.
+pub fn winding_down(text: &str, latency: Option<Duration>) -> bool {
+    let words = words(text);
+    if words.is_empty() || words.len() > MAX_WORDS {
+        return false;
+    }
+    let joined = format!(" {} ", words.join(" "));
+    WINDING_DOWN.iter().any(|phrase| joined.contains(&format!(" {phrase} ")))
+        || (latency.is_some_and(|l| l >= LONG_PAUSE) && text.chars().count() <= SHORT_MESSAGE_CHARS)
+}
+
+/// Whether a message is nothing but a goodbye ("ok thanks, bye"), which
+/// after a closing reply ends the session.
+pub fn is_farewell(text: &str) -> bool {
+    let words = words(text);
+    !words.is_empty() && words.iter().all(|w| FAREWELL_WORDS.contains(&w.as_str()))
+}
+
+/// Where a time-boxed session stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeCheck {
+    /// The limit is a few minutes away.
+    Warning { minutes_left: u64, limit: u64 },
+    /// The limit has passed.
+    Over { minutes: u64, limit: u64 },
+}
+
+impl TimeCheck {
+    /// A system prompt section about the time.
+    pub fn format_for_prompt(&self) -> String {
+        match self {
+            TimeCheck::Warning { minutes_left, limit } => format!(
+                "About {minutes_left} minutes are left of this {limit}-minute session. Start moving toward a close: \
+                 don't open new topics, and begin drawing what you've talked about together."
+            ),
+            TimeCheck::Over { minutes, limit } => format!(
+                "This session has run {minutes} minutes, past its {limit}-minute limit. Gently propose wrapping up \
+                 for today. If they agree: {CLOSING_STEPS} If they need to keep going, especially in distress, \
+                 stay with them; don't cut them off."
+            ),
+        }
+    }
+}
+
+/// A soft limit on how long a session runs: a warning a few minutes
+/// before it, then a proposal to wrap up once it passes, repeated every
+/// 15 minutes if they carry on.
+#[derive(Debug)]
+pub struct TimeBox {
+    limit: Duration,
+    warned: bool,
+    /// When wrapping up is next proposed.
+    next_proposal: Duration,
+}
+
+impl TimeBox {
+    /// A limit of `minutes`, or `None` for 0 (no limit).
+    pub fn new(minutes: u32) -> Option<Self> {
+        let limit = Duration::from_secs(u64::from(minutes) * 60);
+        (minutes > 0).then_some(Self { limit, warned: false, next_proposal: limit })
+    }
+
+    /// What to tell the coach `elapsed` into the session, if anything.
+    /// Each warning and proposal is given once.
+    pub fn check(&mut self, elapsed: Duration) -> Option<TimeCheck> {
+        let limit = self.limit.as_secs() / 60;
+        if elapsed >= self.next_proposal {
+            self.warned = true;
+            while self.next_proposal <= elapsed {
+                self.next_proposal += TIME_REMINDER;
+            }
+            return Some(TimeCheck::Over { minutes: elapsed.as_secs() / 60, limit });
+        }
+        if !self.warned && self.limit > TIME_WARNING && elapsed >= self.limit - TIME_WARNING {
+            self.warned = true;
+            let left = (self.limit - elapsed).as_secs().div_ceil(60);
+            return Some(TimeCheck::Warning { minutes_left: left, limit });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_winding_down() {
+        assert!(winding_down("I think that's all for today", None));
+        assert!(winding_down("Ok I gotta go, thanks", None));
+        assert!(winding_down("Thank you for listening.", None));
+        assert!(!winding_down("That's all I can think about lately, it keeps me up at night and I can't stop", None));
+        assert!(!winding_down("Work has been hard", None));
+
+        let pause = Some(Duration::from_secs(15 * 60));
+        assert!(winding_down("yeah", pause));
+        assert!(!winding_down("yeah", Some(Duration::from_secs(60))));
+        assert!(!winding_down("Sorry, I was thinking about what you said about my sister", pause));
+    }
+
+    #[test]
+    fn test_is_farewell() {
+        assert!(is_farewell("Ok, thanks. Bye!"));
+        assert!(is_farewell("thank you so much, good night"));
+        assert!(!is_farewell("Actually, one more thing"));
+        assert!(!is_farewell(""));
+    }
+
+    #[test]
+    fn test_time_box() {
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        assert!(TimeBox::new(0).is_none());
+        let mut time_box = TimeBox::new(45).unwrap();
+        assert_eq!(time_box.check(minutes(10)), None);
+        assert_eq!(time_box.check(minutes(41)), Some(TimeCheck::Warning { minutes_left: 4, limit: 45 }));
+        assert_eq!(time_box.check(minutes(43)), None);
+        assert_eq!(time_box.check(minutes(47)), Some(TimeCheck::Over { minutes: 47, limit: 45 }));
+        assert_eq!(time_box.check(minutes(55)), None);
+        // Proposed again every 15 minutes they carry on
+        assert_eq!(time_box.check(minutes(61)), Some(TimeCheck::Over { minutes: 61, limit: 45 }));
+
+        // Past the limit already: no warning first
+        let mut late = TimeBox::new(45).unwrap();
+        assert!(matches!(late.check(minutes(50)), Some(TimeCheck::Over { .. })));
+        assert_eq!(late.check(minutes(51)), None);
+        let prompt = TimeCheck::Over { minutes: 50, limit: 45 }.format_for_prompt();
+        assert!(prompt.contains("run 50 minutes, past its 45-minute limit"), "{prompt}");
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use rig::embeddings::EmbeddingModel as _;
+use rig_fastembed::EmbeddingModel;
+use tokio_rusqlite::Connection;
+
+use crate::memory::coherence::{self, SessionCoherence};
+use crate::memory::session_names::short_id;
+use crate::memory::{self, ChatTurn};
+use crate::output;
+use crate::themes::cosine;
+
+/// A reply less similar (cosine) than this to the message it answers is a
+/// non-sequitur.
+const NON_SEQUITUR: f64 = 0.2;
+
+/// Sessions scoring below this are flagged as low coherence.
+const LOW_COHERENCE: f64 = 0.45;
+
+/// Texts embedded per call.
+const EMBED_BATCH: usize = 256;
+
+/// A session's coherence from the similarity of each reply to the message
+/// it answers: the mean, with non-sequiturs counted as 0 so a few
+/// off-topic replies pull it down. Returns the score and the number of
+/// non-sequiturs, or `None` with no replies.
+pub fn score(similarities: &[f64]) -> Option<(f64, usize)> {
+    if similarities.is_empty() {
+        return None;
+    }
+    let non_sequiturs = similarities.iter().filter(|&&s| s < NON_SEQUITUR).count();
+    let total: f64 = similarities.iter().filter(|&&s| s >= NON_SEQUITUR).map(|s| s.min(1.0)).sum();
+    Some((total / similarities.len() as f64, non_sequiturs))
+}
+
+/// Each reply with the user message right before it.
+fn exchanges(turns: &[ChatTurn]) -> Vec<(&str, &str)> {
+    turns
+        .windows(2)
+        .filter(|pair| pair[0].role == "user" && pair[1].role == "assistant")
+        .map(|pair| (pair[0].content.as_str(), pair[1].content.as_str()))
+        .collect()
+}
+
+/// Scores one session's saved turns, or `None` if it has no replies.
+async fn score_session(
+    model: &EmbeddingModel,
+    session_id: &str,
+    turns: &[ChatTurn],
+) -> Result<Option<SessionCoherence>> {
+    let exchanges = exchanges(turns);
+    let texts: Vec<String> =
+        exchanges.iter().flat_map(|(message, reply)| [message.to_string(), reply.to_string()]).collect();
+    let mut vectors: Vec<Vec<f64>> = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(EMBED_BATCH) {
+        let embeddings = model.embed_texts(batch.to_vec()).await.context("Failed to embed messages")?;
+        vectors.extend(embeddings.into_iter().map(|e| e.vec));
+    }
+    let similarities: Vec<f64> = vectors.chunks(2).map(|pair| cosine(&pair[0], &pair[1])).collect();
+    Ok(score(&similarities).map(|(score, non_sequiturs)| SessionCoherence {
+        session_id: session_id.to_string(),
+        score,
+        replies: exchanges.len() as i64,
+        non_sequiturs: non_sequiturs as i64,
+        message_count: turns.len() as i64,
+    }))
+}
+
+/// `chiron sessions quality --coherence`: scores sessions that are new or
+/// have changed since they were last scored, then lists every score with
+/// low-coherence sessions flagged. Archived sessions have no transcript to
+/// score.
+pub async fn run(conn: &Connection, model: &EmbeddingModel) -> Result<()> {
+    let stored: HashMap<String, i64> = coherence::coherence_scores(conn)
+        .await?
+        .into_iter()
+        .map(|c| (c.session_id, c.message_count))
+        .collect();
+    let mut scored = 0;
+    for session in memory::list_sessions(conn).await? {
+        if session.archived || stored.get(&session.session_id) == Some(&session.message_count) {
+            continue;
+        }
+        let turns = memory::load_session_turns(conn, &session.session_id).await?;
+        if let Some(scores) = score_session(model, &session.session_id, &turns).await? {
+            coherence::save_coherence(conn, &scores).await?;
+            scored += 1;
+        }
+    }
+    if scored > 0 {
+        output::status(format!("Scored {scored} sessions"));
+    }
+
+    let scores = coherence::coherence_scores(conn).await?;
+    if output::json() {
+        return output::print_json(&scores);
+    }
+    if scores.is_empty() {
+        output::status("No sessions with replies yet.");
+    }
+    for c in scores {
+        println!(
+            "{:<10} {:>5.2}  {:>4} replies  {:>3} non-sequiturs{}",
+            short_id(&c.session_id),
+            c.score,
+            c.replies,
+            c.non_sequiturs,
+            if c.score < LOW_COHERENCE { "  low coherence" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_penalizes_non_sequiturs() {
+        assert_eq!(score(&[]), None);
+        let (high, non_sequiturs) = score(&[0.8, 0.6]).unwrap();
+        assert!((high - 0.7).abs() < 1e-9);
+        assert_eq!(non_sequiturs, 0);
+        // The off-topic reply counts as 0, not 0.1
+        let (low, non_sequiturs) = score(&[0.8, 0.6, 0.1]).unwrap();
+        assert!((low - 1.4 / 3.0).abs() < 1e-9);
+        assert_eq!(non_sequiturs, 1);
+    }
+
+    #[test]
+    fn test_exchanges_pair_replies_with_messages() {
+        let turn = |role: &str, content: &str| ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: "2026-03-01 10:00:00".to_string(),
+            source: "text".to_string(),
+            prompt_variant: None,
+            interrupted: false,
+            emotions: None,
+            provenance: None,
+        };
+        let turns = [
+            turn("assistant", "Welcome back"),
+            turn("user", "Work is a lot"),
+            turn("assistant", "What's weighing most?"),
+            turn("user", "Deadlines"),
+        ];
+        assert_eq!(exchanges(&turns), vec![("Work is a lot", "What's weighing most?")]);
+    }
+}
@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use clap_complete::Shell;
+
+/// What `chiron completions` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    Powershell,
+    /// A roff man page, for `man 1 chiron`
+    Man,
+}
+
+/// Writes a completion script or the man page for `command` (the full CLI)
+/// to `out`.
+pub fn write(target: Target, mut command: clap::Command, out: &mut impl Write) -> Result<()> {
+    let shell = match target {
+        Target::Bash => Shell::Bash,
+        Target::Zsh => Shell::Zsh,
+        Target::Fish => Shell::Fish,
+        Target::Elvish => Shell::Elvish,
+        Target::Powershell => Shell::PowerShell,
+        Target::Man => {
+            return clap_mangen::Man::new(command).render(out).context("Failed to write man page");
+        }
+    };
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_completions_and_man_page_cover_subcommands() {
+        let render = |target| {
+            let mut out = Vec::new();
+            write(target, crate::Args::command(), &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let bash = render(Target::Bash);
+        assert!(bash.contains("rebuild-index"), "nested subcommands complete");
+        assert!(bash.contains("seed-knowledge"));
+        assert!(render(Target::Fish).contains("doctor"));
+        let man = render(Target::Man);
+        assert!(man.contains(".TH chiron"), "{man}");
+        assert!(man.contains("completions"));
+    }
+}
@@ -0,0 +1,420 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::AbUnit;
+use crate::memory::writer::AutosaveMode;
+
+/// Layered application configuration.
+///
+/// Resolution order (later wins): built-in defaults < config file <
+/// `CHIRON_*` environment variables < CLI flags. The file and defaults are
+/// handled here; env and CLI layers are applied by clap in `main.rs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub model: ModelConfig,
+    pub persona: PersonaConfig,
+    pub memory: MemoryConfig,
+    pub storage: StorageConfig,
+    pub voice: VoiceConfig,
+    pub sync: SyncConfig,
+    pub privacy: PrivacyConfig,
+    pub reminders: ReminderConfig,
+}
+
+/// Model loading and generation settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelConfig {
+    /// Path to the GGUF model file.
+    pub path: PathBuf,
+    /// Number of GPU layers to offload (99 = all).
+    pub n_gpu_layers: u32,
+    /// Sampling temperature.
+    pub temperature: f64,
+    /// Maximum tokens to generate per response.
+    pub max_tokens: usize,
+    /// Sampling seed. With the same seed, model, settings, and prompt,
+    /// replies are the same. 32-bit, as llama.cpp takes it.
+    pub seed: u32,
+    /// Total context window size in tokens.
+    pub context_size: usize,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("models/plotinus.gguf"),
+            n_gpu_layers: 99,
+            temperature: 0.7,
+            max_tokens: 512,
+            seed: 42,
+            context_size: 4096,
+        }
+    }
+}
+
+/// Coach persona and prompt catalog settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonaConfig {
+    /// Coach variant ID (empty = first variant in the catalog).
+    pub coach_variant: String,
+    /// Path to coach prompt variants TOML.
+    pub coach_variants: PathBuf,
+    /// Path to conversation modes TOML.
+    pub modes: PathBuf,
+    /// Path to therapeutic modalities TOML (`/modality`).
+    pub modalities: PathBuf,
+    /// Path to guided relaxation scripts TOML (`/relax`).
+    pub relaxation: PathBuf,
+    /// Show the model's think block after each response.
+    pub show_thinking: bool,
+    /// Language for user-facing strings (see `locales/`).
+    pub language: String,
+    /// Language the coach replies in, and crisis resources are shown in,
+    /// when it should differ from `language`. Empty follows `language`.
+    pub response_language: String,
+    /// Time zone for displayed timestamps: `local`, `utc`, or an offset
+    /// like `+02:00`. Timestamps are always stored in UTC.
+    pub timezone: String,
+    /// Comma-separated coach variant IDs to alternate for A/B comparison
+    /// (empty = off; `coach_variant` is used).
+    pub ab_variants: String,
+    /// Whether A/B variants alternate per `turn` or per `session`.
+    pub ab_by: AbUnit,
+    /// Let the coach call tools (memory search, assessments, journal)
+    /// with typed arguments. Needs a model trained on tool calling.
+    pub tools: bool,
+    /// After this many messages in a row unrelated to their active goals,
+    /// the coach offers to come back to one. 0 turns it off (purely
+    /// supportive sessions).
+    pub refocus_after: u32,
+    /// Minutes a chat session runs before the coach proposes wrapping up,
+    /// with a warning 5 minutes before. 0 means no limit.
+    pub session_minutes: u32,
+}
+
+impl Default for PersonaConfig {
+    fn default() -> Self {
+        Self {
+            coach_variant: String::new(),
+            coach_variants: PathBuf::from("prompts/coach.toml"),
+            modes: PathBuf::from("prompts/modes.toml"),
+            modalities: PathBuf::from("prompts/modalities.toml"),
+            relaxation: PathBuf::from("prompts/relaxation.toml"),
+            show_thinking: true,
+            language: "en".to_string(),
+            response_language: String::new(),
+            timezone: "local".to_string(),
+            ab_variants: String::new(),
+            ab_by: AbUnit::Session,
+            tools: false,
+            refocus_after: 3,
+            session_minutes: 0,
+        }
+    }
+}
+
+/// Conversation memory settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryConfig {
+    /// Conversation turns kept in the sliding window.
+    pub history_turns: usize,
+    /// Top-k results per RAG collection.
+    pub rag_top_k: usize,
+    /// Resuming a session (or thread) after this many hours of silence
+    /// starts a new episode with the previous one summarized. 0 never splits.
+    pub episode_gap_hours: u64,
+    /// Crises in the last this many days of earlier sessions are carried
+    /// into the prompt, and softer risk phrases get a check-in. 0 turns it off.
+    pub risk_history_days: u32,
+    /// When chat turns are written to disk: after each `message`, every
+    /// `autosave_every` `messages` or `seconds`, or on `exit` only. Ending
+    /// a session or quitting always saves.
+    pub autosave: AutosaveMode,
+    /// The count for `autosave = "messages"` or `"seconds"`.
+    pub autosave_every: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            history_turns: 4,
+            rag_top_k: 3,
+            episode_gap_hours: 72,
+            risk_history_days: 90,
+            autosave: AutosaveMode::Message,
+            autosave_every: 4,
+        }
+    }
+}
+
+/// On-disk storage locations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// SQLite database for chat history and case notes.
+    pub db_path: String,
+    /// LanceDB vector store directory.
+    pub lance_db_path: String,
+    /// MI knowledge markdown used for auto-seeding.
+    pub mi_knowledge_path: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            db_path: "chiron.db".to_string(),
+            lance_db_path: "chiron_vectors".to_string(),
+            mi_knowledge_path: PathBuf::from("data/mi_knowledge.md"),
+        }
+    }
+}
+
+/// External tools used by `--voice` input and `--speak` output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceConfig {
+    /// Audio recorder (sox `rec` or a compatible command).
+    pub record_command: String,
+    /// whisper.cpp command-line binary.
+    pub whisper_command: String,
+    /// Path to the whisper GGML model.
+    pub whisper_model: PathBuf,
+    /// Text-to-speech command; each sentence is written to its stdin.
+    pub speak_command: String,
+    /// Arguments for `speak_command`.
+    pub speak_args: Vec<String>,
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            record_command: "rec".to_string(),
+            whisper_command: "whisper-cli".to_string(),
+            whisper_model: PathBuf::from("models/ggml-base.en.bin"),
+            speak_command: "espeak-ng".to_string(),
+            speak_args: vec!["--stdin".to_string()],
+        }
+    }
+}
+
+/// Remote storage for `chiron sync`. The passphrase and credentials come
+/// from the OS keyring or environment (see `secrets`), never this file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// WebDAV collection URL, or S3 bucket URL (path style, e.g.
+    /// `https://s3.example.com/my-bucket`). Empty disables sync.
+    pub endpoint: String,
+    pub backend: SyncBackend,
+    /// WebDAV user name (basic auth); empty for none.
+    pub username: String,
+    /// S3 region used for request signing.
+    pub region: String,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            backend: SyncBackend::default(),
+            username: String::new(),
+            region: "us-east-1".to_string(),
+        }
+    }
+}
+
+/// Protocol spoken by the sync endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackend {
+    #[default]
+    Webdav,
+    /// S3-compatible object storage (AWS, MinIO, Garage, ...).
+    S3,
+}
+
+/// Protecting what's on screen.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Lock interactive chat after this many minutes without input, clearing
+    /// the screen (0 = never).
+    pub idle_lock_minutes: u64,
+}
+
+/// Reminders sent by `chiron remind`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderConfig {
+    /// Remind about the daily check-in from this time (`HH:MM`, display
+    /// zone) if there hasn't been one today. Empty disables it.
+    pub checkin_time: String,
+    /// Remind about homework that's due and not yet reviewed.
+    pub homework: bool,
+    /// No reminders between these times, e.g. `22:00-08:00`. Empty for none.
+    pub quiet_hours: String,
+    /// How often `chiron remind --daemon` checks, in minutes.
+    pub interval_minutes: u64,
+    /// Show desktop notifications (needs the `notifications` build
+    /// feature); otherwise reminders are printed.
+    pub desktop: bool,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            checkin_time: "20:00".to_string(),
+            homework: true,
+            quiet_hours: "22:00-08:00".to_string(),
+            interval_minutes: 15,
+            desktop: true,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the default config file location: `$XDG_CONFIG_HOME/chiron/config.toml`
+    /// (typically `~/.config/chiron/config.toml`).
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("chiron")
+            .join("config.toml")
+    }
+
+    /// Loads a config file, falling back to defaults if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Writes the config to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(path, self.to_toml()?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Serializes the config as pretty TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("Failed to serialize config")
+    }
+
+    /// Sets a value by dotted key (e.g. `model.temperature`).
+    ///
+    /// The value is parsed according to the type of the existing field, so
+    /// `set("memory.rag_top_k", "5")` stores an integer. Unknown keys and
+    /// unparseable values are rejected.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut root = toml::Value::try_from(&*self).context("Failed to serialize config")?;
+
+        let (section, field) = key
+            .split_once('.')
+            .with_context(|| format!("Config key '{key}' must be of the form section.field"))?;
+
+        let slot = root
+            .get_mut(section)
+            .and_then(|s| s.get_mut(field))
+            .with_context(|| format!("Unknown config key '{key}'"))?;
+
+        *slot = match slot {
+            toml::Value::String(_) => toml::Value::String(value.to_string()),
+            toml::Value::Integer(_) => toml::Value::Integer(
+                value
+                    .parse()
+                    .with_context(|| format!("'{key}' expects an integer, got '{value}'"))?,
+            ),
+            toml::Value::Float(_) => toml::Value::Float(
+                value
+                    .parse()
+                    .with_context(|| format!("'{key}' expects a number, got '{value}'"))?,
+            ),
+            toml::Value::Boolean(_) => toml::Value::Boolean(
+                value
+                    .parse()
+                    .with_context(|| format!("'{key}' expects true or false, got '{value}'"))?,
+            ),
+            _ => bail!("Config key '{key}' cannot be set from the command line"),
+        };
+
+        *self = root
+            .try_into()
+            .with_context(|| format!("Invalid value for '{key}'"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load(&dir.path().join("config.toml")).unwrap();
+        assert_eq!(config.memory.history_turns, 4);
+        assert_eq!(config.storage.db_path, "chiron.db");
+    }
+
+    #[test]
+    fn test_partial_file_keeps_other_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[model]\ntemperature = 0.3\n").unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.model.temperature, 0.3);
+        assert_eq!(config.model.max_tokens, 512);
+        assert_eq!(config.memory.rag_top_k, 3);
+    }
+
+    #[test]
+    fn test_set_and_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("config.toml");
+
+        let mut config = Config::default();
+        config.set("memory.rag_top_k", "5").unwrap();
+        config.set("persona.coach_variant", "v7-unified").unwrap();
+        config.set("persona.show_thinking", "false").unwrap();
+        config.set("persona.ab_by", "turn").unwrap();
+        assert!(config.set("persona.ab_by", "weekly").is_err());
+        config.set("model.seed", "7").unwrap();
+        config.save(&path).unwrap();
+
+        let loaded = Config::load(&path).unwrap();
+        assert_eq!(loaded.memory.rag_top_k, 5);
+        assert_eq!(loaded.persona.coach_variant, "v7-unified");
+        assert!(!loaded.persona.show_thinking);
+        assert_eq!(loaded.persona.ab_by, AbUnit::Turn);
+        assert_eq!(loaded.model.seed, 7);
+
+        config.set("sync.backend", "s3").unwrap();
+        assert_eq!(config.sync.backend, SyncBackend::S3);
+        assert!(config.set("sync.backend", "ftp").is_err());
+    }
+
+    #[test]
+    fn test_set_rejects_bad_input() {
+        let mut config = Config::default();
+        assert!(config.set("memory.nonexistent", "1").is_err());
+        assert!(config.set("temperature", "0.5").is_err());
+        assert!(config.set("memory.rag_top_k", "lots").is_err());
+        assert_eq!(config.memory.rag_top_k, 3, "failed set must not modify config");
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use tokio_rusqlite::Connection;
+
+use crate::catalog::{ModalityCatalog, ModeCatalog, PromptCatalog, RelaxationCatalog};
+use crate::config::Config;
+use crate::guardrails::Guardrails;
+use crate::memory;
+use crate::observe;
+use crate::setup;
+use crate::timezone::DisplayZone;
+
+/// Free space below this many megabytes is flagged.
+const LOW_SPACE_MB: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check, with how to fix it if it didn't pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: Status::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: Status::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name, status: Status::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// `chiron doctor`: checks the config, model, storage, and database, and
+/// prints a fix for each problem. Fails if any check failed. `config_error`
+/// is why the config file didn't load, if it didn't (defaults are checked
+/// instead). Nothing is changed.
+pub async fn run(config_path: &Path, config: &Config, config_error: Option<anyhow::Error>) -> Result<()> {
+    let mut checks = Vec::new();
+    checks.push(match config_error {
+        None if config_path.exists() => Check::ok("Config", config_path.display().to_string()),
+        None => Check::warn(
+            "Config",
+            format!("no config file at {}; using defaults", config_path.display()),
+            "run `chiron init` to create one",
+        ),
+        Some(e) => Check::fail(
+            "Config",
+            format!("{e:#}"),
+            format!("fix the file, or move it aside and run `chiron init` ({})", config_path.display()),
+        ),
+    });
+    checks.extend(check_settings(config_path, config));
+    checks.push(match setup::check_model(&config.model.path) {
+        Ok(()) => Check::ok("Model", config.model.path.display().to_string()),
+        Err(e) => Check::fail(
+            "Model",
+            format!("{e:#}"),
+            "download the model, or point to it with `chiron config set model.path /path/to/model.gguf`",
+        ),
+    });
+    checks.extend(check_storage(&config.storage.db_path));
+    checks.extend(check_database(&config.storage.db_path).await);
+    checks.push(match Path::new(&config.storage.lance_db_path).is_dir() {
+        true => Check::ok("Vector store", config.storage.lance_db_path.clone()),
+        false => Check::warn(
+            "Vector store",
+            format!("{} doesn't exist yet", config.storage.lance_db_path),
+            format!(
+                "it's created on first use; seed it with `chiron seed-knowledge {}`",
+                config.storage.mi_knowledge_path.display()
+            ),
+        ),
+    });
+
+    print!("{}", render(&checks));
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        bail!("{failed} check(s) failed");
+    }
+    Ok(())
+}
+
+/// Settings that parse but point at something missing or invalid.
+fn check_settings(config_path: &Path, config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let persona = &config.persona;
+    checks.push(match PromptCatalog::load(&persona.coach_variants) {
+        Ok(catalog) => {
+            let variant = match persona.coach_variant.as_str() {
+                "" => Ok(()),
+                id => catalog.get_variant(id).map(|_| ()),
+            };
+            match variant.and_then(|()| catalog.ab_test(&persona.ab_variants, persona.ab_by).map(|_| ())) {
+                Ok(()) => Check::ok("Coach prompts", persona.coach_variants.display().to_string()),
+                Err(e) => Check::fail(
+                    "Coach prompts",
+                    format!("{e:#}"),
+                    "pick a listed variant with `chiron config set persona.coach_variant <id>`",
+                ),
+            }
+        }
+        Err(e) => catalog_failure("Coach prompts", e, "persona.coach_variants"),
+    });
+    let catalogs = [
+        ("Modes", &persona.modes, ModeCatalog::load(&persona.modes).err(), "persona.modes"),
+        ("Modalities", &persona.modalities, ModalityCatalog::load(&persona.modalities).err(), "persona.modalities"),
+        ("Relaxation", &persona.relaxation, RelaxationCatalog::load(&persona.relaxation).err(), "persona.relaxation"),
+    ];
+    for (name, path, error, key) in catalogs {
+        checks.push(match error {
+            None => Check::ok(name, path.display().to_string()),
+            Some(e) => catalog_failure(name, e, key),
+        });
+    }
+    if !crate::i18n::languages().contains(&persona.language.as_str()) {
+        checks.push(Check::fail(
+            "Language",
+            format!("no strings for '{}'", persona.language),
+            format!("use one of {}", crate::i18n::languages().join(", ")),
+        ));
+    }
+    let response = persona.response_language.as_str();
+    if !response.is_empty() && !crate::i18n::languages().contains(&response) {
+        checks.push(Check::fail(
+            "Response language",
+            format!("no strings for '{response}'"),
+            format!("use one of {}, or leave persona.response_language empty", crate::i18n::languages().join(", ")),
+        ));
+    }
+    if let Err(e) = DisplayZone::parse(&persona.timezone) {
+        checks.push(Check::fail("Time zone", format!("{e:#}"), "use `local`, `utc`, or an offset like `+02:00`"));
+    }
+    let guardrails = Guardrails::path_for(config_path);
+    if let Err(e) = Guardrails::load(&guardrails) {
+        checks.push(Check::fail(
+            "Guardrails",
+            format!("{e:#}"),
+            format!("fix {}; until then Chiron won't start", guardrails.display()),
+        ));
+    }
+    checks
+}
+
+fn catalog_failure(name: &'static str, e: anyhow::Error, key: &str) -> Check {
+    Check::fail(
+        name,
+        format!("{e:#}"),
+        format!("run Chiron from the repository root, or set `{key}` to the file's full path"),
+    )
+}
+
+/// Whether the database's directory is writable, and how much room is left.
+fn check_storage(db_path: &str) -> Vec<Check> {
+    let dir = match Path::new(db_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    let probe = dir.join(".chiron-doctor");
+    let writable = fs::write(&probe, b"").and_then(|()| fs::remove_file(&probe));
+    let mut checks = vec![match writable {
+        Ok(()) => Check::ok("Storage", format!("{} is writable", dir.display())),
+        Err(e) if !dir.exists() => Check::fail(
+            "Storage",
+            format!("{} doesn't exist ({e})", dir.display()),
+            format!("create it with `mkdir -p {}`", dir.display()),
+        ),
+        Err(e) => Check::fail(
+            "Storage",
+            format!("can't write to {}: {e}", dir.display()),
+            format!("check its owner and permissions (`ls -ld {}`)", dir.display()),
+        ),
+    }];
+    if let Some(free) = free_megabytes(&dir) {
+        checks.push(if free < LOW_SPACE_MB {
+            Check::warn(
+                "Free space",
+                format!("{free} MB left"),
+                "free up space, or archive old sessions with `chiron sessions archive --older-than 90d`",
+            )
+        } else {
+            Check::ok("Free space", format!("{free} MB"))
+        });
+    }
+    checks
+}
+
+/// Free space on the file system holding `dir` (`df -Pk`), if it can be read.
+fn free_megabytes(dir: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let kilobytes: u64 = stdout.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes / 1024)
+}
+
+/// Integrity, the session index, and whether the schema needs migrating,
+/// all read-only.
+async fn check_database(db_path: &str) -> Vec<Check> {
+    if !Path::new(db_path).exists() {
+        return vec![Check::warn(
+            "Database",
+            format!("no database at {db_path} yet"),
+            "it's created when you first run `chiron`",
+        )];
+    }
+    let conn = match observe::open_read_only(db_path).await {
+        Ok(conn) => conn,
+        Err(e) => return vec![Check::fail("Database", format!("{e:#}"), "check the file's permissions")],
+    };
+
+    let mut checks = Vec::new();
+    let integrity = conn
+        .call(|conn| {
+            let mut stmt = conn.prepare("PRAGMA quick_check")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .await;
+    match integrity {
+        Ok(rows) if rows == ["ok"] => checks.push(Check::ok("Database", format!("{db_path} passed the integrity check"))),
+        Ok(rows) => {
+            checks.push(Check::fail(
+                "Database",
+                format!("{db_path} is damaged: {}", rows.iter().take(3).cloned().collect::<Vec<_>>().join("; ")),
+                format!(
+                    "back it up, then recover with `sqlite3 {db_path} .recover | sqlite3 recovered.db` \
+                     and point `storage.db_path` at the result"
+                ),
+            ));
+            return checks;
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "Database",
+                format!("{db_path} can't be read: {e}"),
+                "if it isn't a Chiron database, set `storage.db_path` to the right file",
+            ));
+            return checks;
+        }
+    }
+
+    match (schema(&conn).await, current_schema().await) {
+        (Ok(found), Ok(expected)) => {
+            let missing = missing_schema(&found, &expected);
+            checks.push(if missing.is_empty() {
+                Check::ok("Schema", "up to date")
+            } else {
+                Check::warn(
+                    "Schema",
+                    format!("from an older version; missing {}", missing.join(", ")),
+                    "run `chiron` (or any command that opens the database) to migrate it; back it up first",
+                )
+            });
+        }
+        (Err(e), _) | (_, Err(e)) => checks.push(Check::fail("Schema", format!("{e:#}"), "rerun with -v for details")),
+    }
+
+    let drift = conn
+        .call(|conn| {
+            let exists = conn.prepare("SELECT 1 FROM sqlite_master WHERE name = 'session_index'")?.exists([])?;
+            if !exists {
+                return Ok(0);
+            }
+            let drift: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM (
+                     SELECT session_id, COUNT(*) AS n FROM chat_turns GROUP BY session_id
+                 ) t LEFT JOIN session_index s USING (session_id)
+                 WHERE s.message_count IS NULL OR s.message_count <> t.n",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(drift)
+        })
+        .await;
+    checks.push(match drift {
+        Ok(0) => Check::ok("Session index", "matches the saved messages"),
+        Ok(n) => Check::warn(
+            "Session index",
+            format!("{n} session(s) listed with the wrong message count"),
+            "run `chiron sessions rebuild-index`",
+        ),
+        Err(e) => Check::warn("Session index", e.to_string(), "run `chiron sessions rebuild-index`"),
+    });
+    checks
+}
+
+/// Tables and their columns.
+async fn schema(conn: &Connection) -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let schema = conn
+        .call(|conn| {
+            let mut tables = conn.prepare(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+            )?;
+            let names = tables.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+            let mut schema = BTreeMap::new();
+            for name in names {
+                let mut columns = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+                let columns = columns.query_map([&name], |row| row.get(0))?.collect::<Result<BTreeSet<_>, _>>()?;
+                schema.insert(name, columns);
+            }
+            Ok(schema)
+        })
+        .await?;
+    Ok(schema)
+}
+
+/// The schema this version creates, from a scratch in-memory database.
+async fn current_schema() -> Result<BTreeMap<String, BTreeSet<String>>> {
+    let conn = memory::open_memory(":memory:").await?;
+    schema(&conn).await
+}
+
+/// Tables, and columns of existing tables, that `found` lacks.
+fn missing_schema(found: &BTreeMap<String, BTreeSet<String>>, expected: &BTreeMap<String, BTreeSet<String>>) -> Vec<String> {
+    let mut missing = Vec::new();
+    for (table, columns) in expected {
+        match found.get(table) {
+            None => missing.push(format!("table {table}")),
+            Some(have) => missing.extend(columns.difference(have).map(|c| format!("{table}.{c}"))),
+        }
+    }
+    missing
+}
+
+fn render(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks {
+        let mark = match check.status {
+            Status::Ok => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        };
+        let _ = writeln!(out, "{mark} {:<14} {}", check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(out, "  {:<14} fix: {fix}", "");
+        }
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    let _ = writeln!(out, "\n{} passed, {warned} warning(s), {failed} failed", checks.len() - failed - warned);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_outdated_schema_and_index_drift() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("old.db");
+        let db_path = db.to_str().unwrap();
+        {
+            // An early database: chat_turns without most columns
+            let conn = rusqlite::Connection::open(&db).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE chat_turns (id INTEGER PRIMARY KEY, session_id TEXT, role TEXT, content TEXT, created_at TEXT);
+                 CREATE TABLE session_index (session_id TEXT PRIMARY KEY, message_count INTEGER,
+                     started_at TEXT, last_active_at TEXT, last_turn_id INTEGER);
+                 INSERT INTO chat_turns (session_id, role, content, created_at) VALUES ('s1', 'user', 'hi', '2026-01-01 10:00:00');",
+            )
+            .unwrap();
+        }
+
+        let checks = check_database(db_path).await;
+        let by_name = |name: &str| checks.iter().find(|c| c.name == name).unwrap();
+        assert_eq!(by_name("Database").status, Status::Ok);
+        let schema = by_name("Schema");
+        assert_eq!(schema.status, Status::Warn);
+        assert!(schema.detail.contains("chat_turns.prompt_variant"), "{}", schema.detail);
+        assert!(schema.detail.contains("table case_notes"), "{}", schema.detail);
+        assert_eq!(by_name("Session index").status, Status::Warn);
+
+        // Opening it migrates it
+        let conn = memory::open_memory(db_path).await.unwrap();
+        memory::rebuild_session_index(&conn).await.unwrap();
+        drop(conn);
+        let checks = check_database(db_path).await;
+        assert!(checks.iter().all(|c| c.status == Status::Ok), "{checks:?}");
+    }
+
+    #[test]
+    fn test_render_lists_fixes_and_totals() {
+        let out = render(&[
+            Check::ok("Config", "config.toml"),
+            Check::fail("Model", "models/x.gguf doesn't exist", "download it"),
+        ]);
+        assert!(out.contains("✗ Model          models/x.gguf doesn't exist\n"));
+        assert!(out.contains("fix: download it"));
+        assert!(out.ends_with("1 passed, 0 warning(s), 1 failed\n"));
+    }
+}
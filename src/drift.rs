@@ -0,0 +1,95 @@
+use crate::memory::goals::Goal;
+use crate::themes::cosine;
+
+/// A message less similar (cosine) than this to every active goal counts as
+/// off the goals.
+const SIMILARITY: f64 = 0.4;
+
+/// Counts how many messages in a row have drifted away from the active
+/// goals, to offer a way back after `persona.refocus_after` of them.
+#[derive(Debug, Default)]
+pub struct DriftTracker {
+    off_goal: u32,
+}
+
+impl DriftTracker {
+    /// Records a message's similarity to its closest goal. Returns true when
+    /// `limit` messages in a row have been off the goals (0 never does);
+    /// the count then starts over, so the nudge isn't repeated every turn.
+    pub fn observe(&mut self, similarity: f64, limit: u32) -> bool {
+        if similarity >= SIMILARITY {
+            self.off_goal = 0;
+            return false;
+        }
+        self.off_goal += 1;
+        if limit == 0 || self.off_goal < limit {
+            return false;
+        }
+        self.off_goal = 0;
+        true
+    }
+}
+
+/// The goal closest to a message and how similar it is, given the
+/// embeddings of the message and of each goal (in order).
+pub fn closest<'a>(goals: &'a [Goal], message: &[f64], goal_vectors: &[Vec<f64>]) -> Option<(&'a Goal, f64)> {
+    goals
+        .iter()
+        .zip(goal_vectors)
+        .map(|(goal, vector)| (goal, cosine(message, vector)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// A system prompt section offering to come back to `goal` after `turns`
+/// messages about other things.
+pub fn nudge(goal: &Goal, turns: u32) -> String {
+    format!(
+        "The last {turns} messages have moved away from a goal they set: \"{}\". If it feels right, gently \
+         offer to come back to it, or ask whether what's on their mind now matters more today. Follow their \
+         lead either way; don't push.",
+        goal.description
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(id: i64, description: &str) -> Goal {
+        Goal {
+            id,
+            description: description.to_string(),
+            session_id: "s1".to_string(),
+            completed: false,
+            created_at: "2026-03-01 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_nudges_after_consecutive_off_goal_turns() {
+        let mut drift = DriftTracker::default();
+        assert!(!drift.observe(0.1, 3));
+        assert!(!drift.observe(0.2, 3));
+        assert_eq!(drift.off_goal, 2);
+        assert!(!drift.observe(0.7, 3), "back on a goal resets the count");
+        assert_eq!(drift.off_goal, 0);
+        assert!(!drift.observe(0.1, 3));
+        assert!(!drift.observe(0.1, 3));
+        assert!(drift.observe(0.1, 3));
+        assert!(!drift.observe(0.1, 3), "starts counting again after a nudge");
+
+        let mut off = DriftTracker::default();
+        assert!((0..10).all(|_| !off.observe(0.0, 0)), "0 turns it off");
+    }
+
+    #[test]
+    fn test_closest_goal() {
+        let goals = [goal(1, "Sleep by 11pm"), goal(2, "Call my sister weekly")];
+        let vectors = [vec![1.0, 0.0], vec![0.0, 1.0]];
+        let (best, similarity) = closest(&goals, &[0.2, 0.9], &vectors).unwrap();
+        assert_eq!(best.id, 2);
+        assert!(similarity > 0.9);
+        assert!(closest(&[], &[1.0, 0.0], &[]).is_none());
+        assert!(nudge(best, 3).contains("\"Call my sister weekly\""));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+
+/// A discrete emotion a message can express.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emotion {
+    Sad,
+    Anxious,
+    Angry,
+    Hopeful,
+    Numb,
+}
+
+impl Emotion {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Emotion::Sad => "sad",
+            Emotion::Anxious => "anxious",
+            Emotion::Angry => "angry",
+            Emotion::Hopeful => "hopeful",
+            Emotion::Numb => "numb",
+        }
+    }
+
+    /// How the coach might respond to it, for the system prompt.
+    fn technique(self) -> &'static str {
+        match self {
+            Emotion::Sad => "reflect the feeling and validate it before anything else; don't rush to fix",
+            Emotion::Anxious => "slow the pace and reflect the worry; offer grounding or a breathing exercise if it's strong",
+            Emotion::Angry => "validate the frustration and roll with it; don't argue or correct",
+            Emotion::Hopeful => "notice it and evoke change talk: what's helping, and what they want next",
+            Emotion::Numb => "be gently curious about the numbness itself; short questions, no pressure to feel",
+        }
+    }
+}
+
+/// Cue words for each emotion. A trailing `*` matches any ending
+/// (`worr*`: worried, worrying); cues with a space match as phrases.
+const LEXICON: &[(Emotion, &[&str])] = &[
+    (
+        Emotion::Sad,
+        &[
+            "sad", "sadness", "depressed", "depressing", "cry*", "tears", "lonely", "miserable", "heartbroken",
+            "grief", "griev*", "unhappy", "hopeless", "devastated", "feel down", "feeling down", "feel low",
+        ],
+    ),
+    (
+        Emotion::Anxious,
+        &[
+            "anxious", "anxiety", "worr*", "nervous", "panic*", "scared", "afraid", "fear*", "stress*", "overwhelm*",
+            "dread*", "tense", "on edge", "can't stop thinking", "can't relax",
+        ],
+    ),
+    (
+        Emotion::Angry,
+        &[
+            "angry", "anger", "mad", "furious", "pissed", "annoyed", "irritat*", "frustrat*", "resent*", "hate",
+            "rage", "fed up", "sick of",
+        ],
+    ),
+    (
+        Emotion::Hopeful,
+        &[
+            "hopeful", "hoping", "i hope", "optimistic", "excited", "proud", "motivated", "relieved",
+            "looking forward", "getting better", "feel better", "feeling better", "i can do this",
+        ],
+    ),
+    (
+        Emotion::Numb,
+        &[
+            "numb", "empty", "hollow", "detached", "disconnected", "blank", "flat", "nothing matters",
+            "don't feel anything", "can't feel anything", "going through the motions", "shut down",
+        ],
+    ),
+];
+
+/// Words that strengthen the cue after them.
+const INTENSIFIERS: &[&str] = &["so", "really", "very", "extremely", "incredibly", "totally", "completely", "super"];
+
+/// Words that cancel a cue within the two words after them.
+const NEGATIONS: &[&str] = &["not", "no", "never", "don't", "dont", "isn't", "wasn't", "aren't", "without"];
+
+/// An emotion found in a message and how strongly it's expressed, from 0
+/// to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmotionLabel {
+    pub emotion: Emotion,
+    pub intensity: f32,
+}
+
+impl EmotionLabel {
+    fn strength(&self) -> &'static str {
+        match self.intensity {
+            i if i >= 0.75 => "strongly",
+            i if i >= 0.5 => "",
+            _ => "mildly",
+        }
+    }
+}
+
+impl fmt::Display for EmotionLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{:.2}", self.emotion.as_str(), self.intensity)
+    }
+}
+
+/// Labels a message with the emotions its wording expresses, strongest
+/// first. A lexicon, not a model: each cue counts once, more after "so" or
+/// "really", and not at all after "not" or "never".
+pub fn classify(text: &str) -> Vec<EmotionLabel> {
+    let lower = text.to_lowercase().replace('’', "'");
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let joined = format!(" {} ", words.join(" "));
+
+    let mut labels: Vec<EmotionLabel> = LEXICON
+        .iter()
+        .filter_map(|(emotion, cues)| {
+            let score: f32 = cues.iter().map(|cue| cue_score(cue, &words, &joined)).sum();
+            (score > 0.0).then(|| EmotionLabel { emotion: *emotion, intensity: (score / 2.0).min(1.0) })
+        })
+        .collect();
+    labels.sort_by(|a, b| b.intensity.total_cmp(&a.intensity));
+    labels
+}
+
+fn cue_score(cue: &str, words: &[&str], joined: &str) -> f32 {
+    if cue.contains(' ') {
+        return if joined.contains(&format!(" {cue} ")) { 1.0 } else { 0.0 };
+    }
+    let matches = |word: &str| match cue.strip_suffix('*') {
+        Some(stem) => word.starts_with(stem),
+        None => word == cue,
+    };
+    let mut score = 0.0;
+    for (i, word) in words.iter().enumerate() {
+        if !matches(word) {
+            continue;
+        }
+        let before = &words[i.saturating_sub(2)..i];
+        if before.iter().any(|w| NEGATIONS.contains(w)) {
+            continue;
+        }
+        score += if before.last().is_some_and(|w| INTENSIFIERS.contains(w)) { 1.5 } else { 1.0 };
+    }
+    score
+}
+
+/// Labels as stored with a message: `anxious:0.75,sad:0.50`.
+pub fn format_labels(labels: &[EmotionLabel]) -> String {
+    labels.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// A system prompt section naming the emotions in their last message and
+/// a technique suited to each, or `None` if none were found.
+pub fn format_for_prompt(labels: &[EmotionLabel]) -> Option<String> {
+    if labels.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = labels
+        .iter()
+        .map(|l| {
+            let feeling = format!("{} {}", l.strength(), l.emotion.as_str());
+            format!("- {}: {}", feeling.trim(), l.emotion.technique())
+        })
+        .collect();
+    Some(format!(
+        "Their last message sounds (from its wording, so check rather than assume):\n{}",
+        lines.join("\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emotions(text: &str) -> Vec<(&'static str, f32)> {
+        classify(text).iter().map(|l| (l.emotion.as_str(), l.intensity)).collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(emotions("I'm so worried about tomorrow and I feel lonely"), vec![("anxious", 0.75), ("sad", 0.5)]);
+        assert_eq!(emotions("Honestly I'm furious. Fed up with all of it, I hate this"), vec![("angry", 1.0)]);
+        assert_eq!(emotions("I just feel numb, going through the motions"), vec![("numb", 1.0)]);
+        assert_eq!(emotions("I’m hoping this week goes better, feeling better already"), vec![("hopeful", 1.0)]);
+        assert_eq!(emotions("I'm not anxious about it, never worried"), vec![]);
+        assert!(emotions("Had lunch with my sister").is_empty());
+    }
+
+    #[test]
+    fn test_format() {
+        let labels = classify("I'm really stressed and a bit sad");
+        assert_eq!(format_labels(&labels), "anxious:0.75,sad:0.50");
+        let prompt = format_for_prompt(&labels).unwrap();
+        assert!(prompt.contains("- strongly anxious: slow the pace"), "{prompt}");
+        assert!(prompt.contains("- sad: reflect the feeling"), "{prompt}");
+        assert_eq!(format_for_prompt(&[]), None);
+    }
+}
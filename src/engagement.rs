@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use crate::memory::engagement::MessageEngagement;
+
+/// Messages at least this long count as fully engaged on length.
+const FULL_LENGTH: f64 = 160.0;
+
+/// Replies within this long of the coach's count as fully engaged on
+/// timing; slower ones count for less, down to nothing at `SLOW`.
+const PROMPT: Duration = Duration::from_secs(30);
+const SLOW: Duration = Duration::from_secs(600);
+
+/// Latest messages compared with the ones before them.
+const RECENT: usize = 3;
+
+/// Earlier messages needed before a trend means anything.
+const BASELINE: usize = 3;
+
+/// Recent messages this fraction of the earlier length or less are "much
+/// shorter".
+const SHORTER: f64 = 0.4;
+
+/// Earlier messages shorter than this on average were never long, so
+/// shrinking from them isn't a trend.
+const MIN_BASELINE_CHARS: f64 = 40.0;
+
+/// Recent replies this many times slower than earlier ones, and at least
+/// `SLOW_RECENT` on average, are "much slower".
+const SLOWER: f64 = 3.0;
+const SLOW_RECENT: Duration = Duration::from_secs(120);
+
+/// Messages loaded to look for a trend.
+pub const HISTORY: usize = 12;
+
+/// How engaged a message looks, from 0 to 1: mostly its length, and how
+/// soon it followed the coach's reply when that's known.
+pub fn score(chars: usize, latency: Option<Duration>) -> f64 {
+    let length = (chars as f64 / FULL_LENGTH).min(1.0);
+    match latency {
+        Some(latency) => {
+            let late = latency.saturating_sub(PROMPT).as_secs_f64() / (SLOW - PROMPT).as_secs_f64();
+            0.7 * length + 0.3 * (1.0 - late.min(1.0))
+        }
+        None => length,
+    }
+}
+
+/// A sign they're drifting out of the conversation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Disengagement {
+    /// Average message length lately and earlier, in characters.
+    Shorter { recent: f64, earlier: f64 },
+    /// Average time to reply lately and earlier.
+    Slower { recent: Duration, earlier: Duration },
+}
+
+impl Disengagement {
+    /// A system prompt section asking the coach to name the change gently.
+    pub fn format_for_prompt(&self) -> String {
+        let (change, example) = match self {
+            Disengagement::Shorter { recent, earlier } => (
+                format!(
+                    "Their messages have been getting much shorter (about {recent:.0} characters lately, from {earlier:.0} earlier)."
+                ),
+                "your messages have been getting much shorter",
+            ),
+            Disengagement::Slower { recent, earlier } => (
+                format!(
+                    "They've been taking much longer to reply (about {} lately, from {} earlier).",
+                    minutes(*recent),
+                    minutes(*earlier)
+                ),
+                "it's been taking a while to reply",
+            ),
+        };
+        format!(
+            "{change} They may be tired, losing interest, or finding this hard. Name it gently and ask, for example \
+             \"{example}, how is this feeling for you?\", then follow their lead: a lighter topic, a pause, or \
+             wrapping up are all fine. Mention it once; don't press."
+        )
+    }
+}
+
+fn minutes(duration: Duration) -> String {
+    match duration.as_secs() {
+        secs if secs < 90 => format!("{secs} seconds"),
+        secs => format!("{} minutes", (secs + 30) / 60),
+    }
+}
+
+/// Compares their last few messages, oldest first, with the ones before
+/// them in the session: much shorter, or much slower to arrive.
+pub fn disengagement(messages: &[MessageEngagement]) -> Option<Disengagement> {
+    if messages.len() < RECENT + BASELINE {
+        return None;
+    }
+    let (earlier, recent) = messages.split_at(messages.len() - RECENT);
+
+    let mean_chars = |messages: &[MessageEngagement]| {
+        messages.iter().map(|m| m.chars as f64).sum::<f64>() / messages.len() as f64
+    };
+    let (recent_chars, earlier_chars) = (mean_chars(recent), mean_chars(earlier));
+    if earlier_chars >= MIN_BASELINE_CHARS && recent_chars <= earlier_chars * SHORTER {
+        return Some(Disengagement::Shorter { recent: recent_chars, earlier: earlier_chars });
+    }
+
+    let mean_latency = |messages: &[MessageEngagement]| {
+        let known: Vec<u64> = messages.iter().filter_map(|m| m.latency_ms).collect();
+        (!known.is_empty()).then(|| Duration::from_millis(known.iter().sum::<u64>() / known.len() as u64))
+    };
+    let (recent_latency, earlier_latency) = (mean_latency(recent)?, mean_latency(earlier)?);
+    (recent_latency >= SLOW_RECENT && recent_latency.as_secs_f64() >= earlier_latency.as_secs_f64() * SLOWER)
+        .then_some(Disengagement::Slower { recent: recent_latency, earlier: earlier_latency })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(chars: u64, latency_secs: Option<u64>) -> MessageEngagement {
+        MessageEngagement { chars, latency_ms: latency_secs.map(|s| s * 1000) }
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(score(320, None), 1.0);
+        assert_eq!(score(80, None), 0.5);
+        assert!((score(160, Some(Duration::from_secs(10))) - 1.0).abs() < 1e-9);
+        assert!((score(160, Some(Duration::from_secs(600))) - 0.7).abs() < 1e-9);
+        assert!(score(20, Some(Duration::from_secs(400))) < 0.3);
+    }
+
+    #[test]
+    fn test_disengagement() {
+        let long = [message(200, None), message(150, Some(20)), message(180, Some(25))];
+        let short = [message(30, Some(20)), message(12, Some(15)), message(5, Some(10))];
+        let trend = disengagement(&[long.as_slice(), &short].concat());
+        assert!(matches!(trend, Some(Disengagement::Shorter { .. })), "{trend:?}");
+        assert!(trend.unwrap().format_for_prompt().contains("about 16 characters lately, from 177 earlier"));
+
+        // Too few messages, or short all along
+        assert_eq!(disengagement(&short), None);
+        let brief = [message(20, Some(20)), message(25, Some(20)), message(15, Some(20))];
+        assert_eq!(disengagement(&[brief.as_slice(), &short].concat()), None);
+
+        let slow = [message(160, Some(300)), message(170, Some(240)), message(150, Some(360))];
+        let trend = disengagement(&[long.as_slice(), &slow].concat()).unwrap();
+        assert_eq!(
+            trend,
+            Disengagement::Slower { recent: Duration::from_secs(300), earlier: Duration::from_millis(22_500) }
+        );
+        assert!(trend.format_for_prompt().contains("about 5 minutes lately, from 22 seconds earlier"));
+    }
+}
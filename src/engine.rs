@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
+use rig_fastembed::EmbeddingModel;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{watch, Mutex};
+use tokio_rusqlite::Connection;
+
+use crate::catalog::{AbTest, ModalityCatalog, ModeCatalog, PromptVariant, RelaxationCatalog};
+use crate::error::{ChironError, Result};
+use crate::memory;
+use crate::memory::writer::Autosave;
+use crate::i18n::t;
+use crate::orchestrator::commands::Handled;
+use crate::orchestrator::events::{SafetyKind, TurnEvent};
+use crate::orchestrator::{Orchestrator, OutputSink, TurnResult};
+use crate::provider::config::GenerationConfig;
+use crate::provider::Backend;
+use crate::reload::AgentToggles;
+
+/// Active sessions keyed by session ID.
+///
+/// Each session owns its orchestrator (and completion model, so think
+/// buffers are never shared). The inner mutex serializes turns per session.
+type SessionMap = HashMap<String, Arc<Mutex<Orchestrator>>>;
+
+/// Something that happened while answering a message, in order: an optional
+/// `Safety`, any number of `Token`s, then exactly one `Done`, `Reply`, or
+/// `Error`.
+#[derive(Debug)]
+pub enum ChatEvent {
+    /// The message matched crisis keywords (sent before any tokens).
+    Safety,
+    /// A chunk of visible response text.
+    Token(String),
+    /// The turn finished and was saved.
+    Done(TurnResult),
+    /// Answered without a turn: a chat command's output (`/goal`,
+    /// `/crisis`, `/end`, ...) or the scripted reply while the model is
+    /// unavailable (see `orchestrator::commands`).
+    Reply(String),
+    /// The turn failed; nothing was saved.
+    Error(String),
+}
+
+impl From<Handled> for ChatEvent {
+    fn from(handled: Handled) -> Self {
+        match handled {
+            Handled::Turn(result) => Self::Done(result),
+            Handled::Reply(reply) | Handled::Ended(reply) => Self::Reply(reply),
+            Handled::Terminal(_) => Self::Reply(t("chat.terminal_only").to_string()),
+        }
+    }
+}
+
+/// Chiron behind one interface: the model, prompts, storage, and safety
+/// checks needed to run sessions. Frontends (CLI, HTTP server, Telegram)
+/// start sessions, send messages, and render the events that come back.
+pub struct ChironEngine {
+    pub backend: Backend,
+    pub generation: GenerationConfig,
+    pub coach_variant: PromptVariant,
+    /// Variants alternated across turns or sessions instead of `coach_variant`.
+    pub ab_test: Option<AbTest>,
+    pub think_instructions: Option<String>,
+    pub mode_catalog: Option<ModeCatalog>,
+    pub modality_catalog: Option<ModalityCatalog>,
+    /// Relaxation scripts suggested while the model is unavailable.
+    pub relax_catalog: Option<RelaxationCatalog>,
+    pub chat_conn: Connection,
+    /// Vector store and embedding model for RAG; `None` runs without retrieval.
+    pub vector_conn: Option<lancedb::Connection>,
+    pub embedding_model: Option<EmbeddingModel>,
+    pub show_thinking: bool,
+    pub history_turns: usize,
+    pub rag_top_k: usize,
+    /// Days of crisis history carried into new sessions (0 = off).
+    pub risk_history_days: u32,
+    /// Messages off their goals before the coach offers a way back (0 = off).
+    pub refocus_after: u32,
+    /// Minutes before the coach proposes wrapping up a session (0 = no limit).
+    pub session_minutes: u32,
+    /// When finished turns are written to disk.
+    pub autosave: Autosave,
+    /// Model generating replies, recorded with each one: the GGUF path, or
+    /// `mock:<scenario>`.
+    pub model: String,
+    /// Offer the coach its tools (see `agents::tools`).
+    pub tools: bool,
+    /// Live `tools` / `show_thinking` from `config.toml` reloads; `None`
+    /// keeps the values above for the engine's lifetime.
+    pub toggles: Option<watch::Receiver<AgentToggles>>,
+    pub sessions: Mutex<SessionMap>,
+}
+
+impl ChironEngine {
+    /// Builds a fresh orchestrator for `session_id` using the shared pipeline config.
+    async fn new_orchestrator(&self, session_id: String) -> Orchestrator {
+        let completion_model =
+            crate::provider::completion_model(&self.backend, self.generation.clone());
+        let mut orchestrator = Orchestrator::new(
+            completion_model,
+            self.coach_variant.clone(),
+            self.think_instructions.clone(),
+            self.mode_catalog.clone(),
+            session_id.clone(),
+            self.chat_conn.clone(),
+            self.show_thinking,
+            self.history_turns,
+            self.vector_conn.clone(),
+            self.embedding_model.clone(),
+            self.rag_top_k,
+        );
+        if let Some(catalog) = &self.modality_catalog {
+            orchestrator.set_modality_catalog(catalog.clone());
+        }
+        if let Some(catalog) = &self.relax_catalog {
+            orchestrator.set_relax_catalog(catalog.clone());
+        }
+        orchestrator.set_risk_history_days(self.risk_history_days);
+        orchestrator.set_refocus_after(self.refocus_after);
+        orchestrator.set_session_minutes(self.session_minutes);
+        orchestrator.set_autosave(self.autosave);
+        orchestrator.set_model(self.backend.kind(), &self.model);
+        if self.tools {
+            orchestrator.enable_tools();
+        }
+        if let Some(toggles) = &self.toggles {
+            orchestrator.set_toggles(toggles.clone());
+        }
+        if let Some(ab_test) = &self.ab_test {
+            let arm = self.ab_arm(ab_test, &session_id).await.unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Failed to pick A/B arm; using the first variant");
+                0
+            });
+            orchestrator.set_ab_test(ab_test.clone(), arm);
+        }
+        orchestrator
+    }
+
+    /// A resumed session keeps the variant it started with; a new one takes
+    /// the next arm after the sessions already stored, so arms alternate.
+    async fn ab_arm(&self, ab_test: &AbTest, session_id: &str) -> Result<usize> {
+        let started_with = memory::first_prompt_variant(&self.chat_conn, session_id).await?;
+        match started_with.and_then(|variant| ab_test.arm_of(&variant)) {
+            Some(arm) => Ok(arm),
+            None => memory::count_sessions(&self.chat_conn).await,
+        }
+    }
+
+    /// Starts a new in-memory session with an ID of the form `{prefix}_{uuid}`.
+    pub async fn start_session(&self, prefix: &str) -> String {
+        let session_id = format!("{prefix}_{}", uuid::Uuid::new_v4().simple());
+        let session = Arc::new(Mutex::new(self.new_orchestrator(session_id.clone()).await));
+        self.sessions
+            .lock()
+            .await
+            .insert(session_id.clone(), session);
+        tracing::info!(session_id, "Created session");
+        session_id
+    }
+
+    /// Sends a message to a session and streams back what happens.
+    ///
+    /// Chat commands are answered at once with a single `Reply` (or `Done`
+    /// for `/retry`); `/end` also drops the session, and commands that need
+    /// a terminal are declined. Generation runs in its own task so it
+    /// completes (and the turn is saved) even if the stream is dropped
+    /// mid-response. If the task panics, what it had of the turn is saved,
+    /// marked interrupted.
+    pub async fn send_message(
+        &self,
+        session_id: &str,
+        content: String,
+    ) -> Result<BoxStream<'static, ChatEvent>> {
+        let session = self.session(session_id).await?;
+
+        let mut orchestrator = session.lock_owned().await;
+        orchestrator.set_output(OutputSink::Silent);
+        if let Some(handled) = orchestrator.command(&content).await? {
+            if let Handled::Ended(_) = handled {
+                drop(orchestrator);
+                self.sessions.lock().await.remove(session_id);
+            }
+            let event = ChatEvent::from(handled);
+            return Ok(futures::stream::once(async move { event }).boxed());
+        }
+        // Subscribe while holding the session so no event of this turn is missed
+        let events = orchestrator.subscribe();
+        let turn = tokio::spawn(async move {
+            let result = match AssertUnwindSafe(orchestrator.reply(&content)).catch_unwind().await {
+                Ok(result) => result,
+                Err(_) => {
+                    // Saving it also ends the event stream, which waits for the turn
+                    tracing::error!("Turn panicked");
+                    if let Err(save) = orchestrator.save_interrupted_turn().await {
+                        tracing::error!(error = %save, "Failed to save the interrupted turn");
+                    }
+                    return Err(ChironError::Inference("The reply was cut off by a crash".to_string()));
+                }
+            };
+            // `Done` waits for the save (unless autosave holds it), so the
+            // turn can be read back right away
+            orchestrator.settle_saves().await.and(result)
+        });
+
+        let events = futures::stream::unfold(Some(events), |rx| async move {
+            let mut rx = rx?;
+            loop {
+                match rx.recv().await {
+                    Ok(event) if event.is_terminal() => return Some((event, None)),
+                    Ok(event) => return Some((event, Some(rx))),
+                    Err(RecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "Turn event subscriber fell behind");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .filter_map(|event| async move {
+            match event {
+                TurnEvent::SafetyFlag { kind: SafetyKind::Crisis, .. } => Some(ChatEvent::Safety),
+                TurnEvent::TokenChunk(text) => Some(ChatEvent::Token(text)),
+                _ => None,
+            }
+        });
+
+        let done = futures::stream::once(async move {
+            match turn.await {
+                Ok(Ok(handled)) => ChatEvent::from(handled),
+                Ok(Err(e)) => {
+                    tracing::error!(error = %e, "Turn failed");
+                    ChatEvent::Error(e.to_string())
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Turn task failed");
+                    ChatEvent::Error(e.to_string())
+                }
+            }
+        });
+
+        Ok(events.chain(done).boxed())
+    }
+
+    /// Ends a session, returning its summary and dropping it from memory.
+    pub async fn end_session(&self, session_id: &str) -> Result<String> {
+        let session = self.session(session_id).await?;
+        let summary = session.lock().await.end_session().await?;
+        self.sessions.lock().await.remove(session_id);
+        Ok(summary)
+    }
+
+    /// Returns the in-memory session, resuming it from saved turns if needed.
+    pub async fn session(&self, session_id: &str) -> Result<Arc<Mutex<Orchestrator>>> {
+        let mut sessions = self.sessions.lock().await;
+        if let Some(session) = sessions.get(session_id) {
+            return Ok(session.clone());
+        }
+
+        // Only the sliding window is restored, so only it is read
+        let (turns, turn_number) =
+            memory::load_recent_turns(&self.chat_conn, session_id, self.history_turns * 2).await?;
+        if turns.is_empty() {
+            return Err(ChironError::SessionNotFound(session_id.to_string()));
+        }
+
+        let mut orchestrator = self.new_orchestrator(session_id.to_string()).await;
+        let history: Vec<(String, String)> =
+            turns.into_iter().map(|t| (t.role, t.content)).collect();
+        orchestrator.restore_history(&history, turn_number);
+        tracing::info!(session_id, restored = history.len(), "Resumed session");
+
+        let session = Arc::new(Mutex::new(orchestrator));
+        sessions.insert(session_id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Whether a session is currently held in memory.
+    pub async fn is_active(&self, session_id: &str) -> bool {
+        self.sessions.lock().await.contains_key(session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::AbUnit;
+    use crate::provider::MockBackend;
+
+    async fn mock_engine() -> ChironEngine {
+        ChironEngine {
+            backend: Backend::Mock(Arc::new(MockBackend::load("standard_5turn").unwrap())),
+            generation: GenerationConfig::default(),
+            coach_variant: variant("test"),
+            ab_test: None,
+            think_instructions: None,
+            mode_catalog: None,
+            modality_catalog: None,
+            relax_catalog: None,
+            chat_conn: memory::open_memory(":memory:").await.unwrap(),
+            vector_conn: None,
+            embedding_model: None,
+            show_thinking: false,
+            history_turns: 10,
+            rag_top_k: 3,
+            risk_history_days: 0,
+            refocus_after: 0,
+            session_minutes: 0,
+            autosave: Autosave::default(),
+            model: "mock:standard_5turn".to_string(),
+            tools: false,
+            toggles: None,
+            sessions: Default::default(),
+        }
+    }
+
+    fn variant(id: &str) -> PromptVariant {
+        PromptVariant {
+            id: id.to_string(),
+            description: String::new(),
+            temperature: 0.0,
+            max_tokens: 256,
+            preamble: "You are a peer coach.".to_string(),
+        }
+    }
+
+    async fn send(engine: &ChironEngine, session_id: &str, text: &str) -> (String, TurnResult) {
+        let mut events = engine.send_message(session_id, text.to_string()).await.unwrap();
+        let mut streamed = String::new();
+        while let Some(event) = events.next().await {
+            match event {
+                ChatEvent::Token(token) => streamed.push_str(&token),
+                ChatEvent::Done(result) => return (streamed, result),
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        panic!("stream ended without Done");
+    }
+
+    #[tokio::test]
+    async fn test_mock_pipeline_end_to_end() {
+        let engine = mock_engine().await;
+        let session_id = engine.start_session("test").await;
+
+        let (streamed, first) =
+            send(&engine, &session_id, "I don't know, I've just been feeling really down lately.").await;
+        assert_eq!(streamed, first.response);
+        assert!(first.response.starts_with("It sounds like things have felt heavy"));
+        assert!(!first.response.contains("<think>"));
+        assert!(first.case_notes.unwrap().contains("MI Stage: engage"));
+
+        let (_, second) = send(
+            &engine,
+            &session_id,
+            "Yeah, I've been drinking a lot more since I lost my job three months ago.",
+        )
+        .await;
+        assert_eq!(second.turn_number, 2);
+        assert!(second.case_notes.unwrap().contains("job loss"));
+
+        let turns = memory::load_session_turns(&engine.chat_conn, &session_id).await.unwrap();
+        assert_eq!(turns.len(), 4);
+
+        // Each reply records its usage; the prompt grows with the history
+        let usage = memory::usage::session_usage(&engine.chat_conn, &session_id).await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert!(usage[0].usage.response_tokens > 0);
+        assert!(usage[0].usage.first_token_ms.is_some());
+        assert!(usage[1].usage.prompt_tokens > usage[0].usage.prompt_tokens);
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_runs_before_reply() {
+        let mut engine = mock_engine().await;
+        engine.backend = Backend::Mock(Arc::new(MockBackend::load("tools").unwrap()));
+        engine.tools = true;
+        let session_id = engine.start_session("test").await;
+
+        let (streamed, result) = send(
+            &engine,
+            &session_id,
+            "Can you log my mood as a 4 today? The work deadline is getting to me.",
+        )
+        .await;
+        assert_eq!(streamed, result.response);
+        assert!(result.response.starts_with("I've logged a 4 for today."));
+        assert!(!result.response.contains("tool_call"));
+
+        let checkins = memory::mood::recent_checkins(&engine.chat_conn, 1).await.unwrap();
+        assert_eq!(checkins.len(), 1);
+        assert_eq!(checkins[0].mood, 4);
+        assert_eq!(checkins[0].stressor.as_deref(), Some("work deadline"));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_turn_is_saved_as_interrupted() {
+        let engine = mock_engine().await;
+        let session_id = engine.start_session("test").await;
+        let session = engine.session(&session_id).await.unwrap();
+        let mut orchestrator = session.lock().await;
+        orchestrator.set_output(OutputSink::Silent);
+        let (_interrupt, requested) = tokio::sync::watch::channel(true);
+        orchestrator.set_interrupt(requested);
+
+        let result = orchestrator
+            .run_turn_captured("I don't know, I've just been feeling really down lately.")
+            .await
+            .unwrap();
+        assert!(result.interrupted);
+        assert_eq!(result.case_notes, None, "a cut-off reply isn't analyzed");
+        orchestrator.flush_saves().await.unwrap();
+
+        let turns = memory::load_session_turns(&engine.chat_conn, &session_id).await.unwrap();
+        assert_eq!(turns.len(), 2, "the user message is kept with the partial reply");
+        assert!(!turns[0].interrupted);
+        assert!(turns[1].interrupted);
+    }
+
+    #[tokio::test]
+    async fn test_unfinished_turn_is_saved_after_a_crash() {
+        let mut engine = mock_engine().await;
+        engine.backend = Backend::Mock(Arc::new(MockBackend::load("failures").unwrap()));
+        let session_id = engine.start_session("test").await;
+        let session = engine.session(&session_id).await.unwrap();
+        assert!(!session.lock().await.save_interrupted_turn().await.unwrap(), "nothing in flight");
+
+        // The mock panics mid-turn; the stream still ends, with an error
+        let input = "Say something that crashes.";
+        let events: Vec<ChatEvent> = engine.send_message(&session_id, input.to_string()).await.unwrap().collect().await;
+        assert!(matches!(events.as_slice(), [ChatEvent::Error(_)]), "{events:?}");
+
+        assert!(!session.lock().await.save_interrupted_turn().await.unwrap(), "saved once");
+        let turns = memory::load_session_turns(&engine.chat_conn, &session_id).await.unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].content, input);
+        assert!(turns[1].interrupted);
+    }
+
+    #[tokio::test]
+    async fn test_only_a_busy_model_goes_offline() {
+        let mut engine = mock_engine().await;
+        engine.backend = Backend::Mock(Arc::new(MockBackend::load("failures").unwrap()));
+        let session_id = engine.start_session("test").await;
+
+        // A failed generation is an error, and the message isn't kept for a retry
+        let events: Vec<ChatEvent> =
+            engine.send_message(&session_id, "Can you hear me?".to_string()).await.unwrap().collect().await;
+        assert!(matches!(events.as_slice(), [ChatEvent::Error(_)]), "{events:?}");
+        assert!(!engine.session(&session_id).await.unwrap().lock().await.is_offline());
+
+        // A busy model gets the scripted reply, and the message waits
+        let reply = command(&engine, &session_id, "Are you there?").await;
+        assert!(reply.starts_with(t("offline.unavailable")), "{reply}");
+        assert!(engine.session(&session_id).await.unwrap().lock().await.is_offline());
+
+        let (_, result) = send(&engine, &session_id, "Hello?").await;
+        assert_eq!(result.response, "I'm here now. What's on your mind?");
+        assert!(!engine.session(&session_id).await.unwrap().lock().await.is_offline());
+        let turns = memory::load_session_turns(&engine.chat_conn, &session_id).await.unwrap();
+        assert_eq!(turns[0].content, "Are you there?\n\nHello?", "only the busy message was kept");
+    }
+
+    #[tokio::test]
+    async fn test_listen_mode_keeps_safety_text_after_a_crisis() {
+        let mut engine = mock_engine().await;
+        engine.backend = Backend::Mock(Arc::new(MockBackend::load("listen").unwrap()));
+        engine.risk_history_days = 30;
+        let safety = "If you feel unsafe, please reach out to a crisis line right now.";
+
+        // With no crisis on record, the safety line reads as advice
+        let session_id = engine.start_session("test").await;
+        command(&engine, &session_id, "/mode listen").await;
+        let (_, result) = send(&engine, &session_id, "Everything fell apart today.").await;
+        assert!(result.response.contains("Talk to me about what happened."), "{}", result.response);
+        assert!(!result.response.contains(safety));
+
+        // After a crisis in an earlier session, nothing is filtered
+        memory::save_chat_turn(&engine.chat_conn, "earlier", "user", "I want to die", memory::InputSource::Text, None)
+            .await
+            .unwrap();
+        let session_id = engine.start_session("test").await;
+        command(&engine, &session_id, "/mode listen").await;
+        let (streamed, result) = send(&engine, &session_id, "Everything fell apart today.").await;
+        assert!(result.response.ends_with(safety), "{}", result.response);
+        assert_eq!(streamed, result.response);
+    }
+
+    #[tokio::test]
+    async fn test_ab_sessions_alternate_and_record_variant() {
+        let mut engine = mock_engine().await;
+        engine.ab_test = Some(AbTest {
+            variants: vec![variant("a"), variant("b")],
+            unit: AbUnit::Session,
+        });
+        let input = "I don't know, I've just been feeling really down lately.";
+
+        let first = engine.start_session("test").await;
+        let (_, result) = send(&engine, &first, input).await;
+        assert_eq!(result.prompt_variant.as_deref(), Some("a"));
+        let (_, result) = send(&engine, &first, input).await;
+        assert_eq!(result.prompt_variant.as_deref(), Some("a"), "a session keeps its variant");
+
+        let second = engine.start_session("test").await;
+        let (_, result) = send(&engine, &second, input).await;
+        assert_eq!(result.prompt_variant.as_deref(), Some("b"));
+
+        let turns = memory::load_session_turns(&engine.chat_conn, &second).await.unwrap();
+        assert_eq!(turns[0].prompt_variant, None, "user messages have no variant");
+        assert_eq!(turns[1].prompt_variant.as_deref(), Some("b"));
+
+        // Resuming after a restart keeps the session's arm
+        engine.sessions.lock().await.clear();
+        let (_, result) = send(&engine, &second, input).await;
+        assert_eq!(result.prompt_variant.as_deref(), Some("b"));
+    }
+
+    async fn command(engine: &ChironEngine, session_id: &str, text: &str) -> String {
+        let events: Vec<ChatEvent> = engine.send_message(session_id, text.to_string()).await.unwrap().collect().await;
+        match events.as_slice() {
+            [ChatEvent::Reply(reply)] => reply.clone(),
+            other => panic!("expected a single reply, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_commands_reply_without_a_turn() {
+        let engine = mock_engine().await;
+        let session_id = engine.start_session("test").await;
+
+        assert_eq!(command(&engine, &session_id, "/goal frobnicate").await, t("goal.usage"));
+        assert_eq!(command(&engine, &session_id, "/hide").await, t("chat.terminal_only"));
+        assert!(memory::load_session_turns(&engine.chat_conn, &session_id).await.unwrap().is_empty());
+
+        send(&engine, &session_id, "I don't know, I've just been feeling really down lately.").await;
+        command(&engine, &session_id, "/end").await;
+        assert!(!engine.is_active(&session_id).await, "/end drops the session");
+    }
+}
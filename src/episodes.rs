@@ -0,0 +1,146 @@
+use chrono::NaiveDateTime;
+use tokio_rusqlite::Connection;
+
+use crate::error::Result;
+use crate::memory::archive::{self, SessionProgress};
+use crate::memory::{self, case_notes, ChatTurn, SessionInfo};
+use crate::supervision::extract_themes;
+use crate::timezone;
+
+/// Characters kept from each message of the last exchange.
+const EXCERPT_CHARS: usize = 200;
+
+/// Whether a session last active at `last_active_at` (SQLite
+/// `datetime('now')` format, UTC) has been quiet for more than `gap_hours`.
+/// A gap of 0 never splits a session.
+fn gone_quiet(last_active_at: &str, now: NaiveDateTime, gap_hours: u64) -> bool {
+    let Ok(last) = NaiveDateTime::parse_from_str(last_active_at, "%Y-%m-%d %H:%M:%S") else {
+        return false;
+    };
+    gap_hours > 0 && (now - last).num_hours() >= gap_hours as i64
+}
+
+/// Resuming `session_id` after more than `gap_hours` of silence starts a
+/// new episode instead: returns the summary of the old one to carry over,
+/// or `None` to continue it as it is.
+pub async fn episode_break(
+    conn: &Connection,
+    session_id: &str,
+    gap_hours: u64,
+    now: NaiveDateTime,
+) -> Result<Option<String>> {
+    let sessions = memory::list_sessions(conn).await?;
+    let Some(session) = sessions
+        .into_iter()
+        .find(|s| s.session_id == session_id)
+        .filter(|s| gone_quiet(&s.last_active_at, now, gap_hours))
+    else {
+        return Ok(None);
+    };
+
+    let progress = archive::session_progress(conn, session_id).await?;
+    let themes = case_notes::session_case_note(conn, session_id)
+        .await?
+        .as_deref()
+        .and_then(extract_themes)
+        .unwrap_or_default();
+    let (last, _) = memory::load_recent_turns(conn, session_id, 2).await?;
+    Ok(Some(summarize(&session, &progress, &themes, &last, now)))
+}
+
+/// A few lines on the previous episode: when it was, how it went, and
+/// where it left off.
+fn summarize(
+    session: &SessionInfo,
+    progress: &SessionProgress,
+    themes: &[String],
+    last: &[ChatTurn],
+    now: NaiveDateTime,
+) -> String {
+    let days = NaiveDateTime::parse_from_str(&session.last_active_at, "%Y-%m-%d %H:%M:%S")
+        .map(|last| (now - last).num_days())
+        .unwrap_or_default();
+
+    let mut lines = vec![format!(
+        "Last talked {} ({days} days ago), {} messages since {}.",
+        timezone::day(&session.last_active_at),
+        session.message_count,
+        timezone::day(&session.started_at),
+    )];
+    if let (Some(start), Some(end)) = (&progress.mi_stage_start, &progress.mi_stage_end) {
+        lines.push(format!("MI Stage: {start} → {end}"));
+    }
+    if !themes.is_empty() {
+        lines.push(format!("Themes: {}", themes.join(", ")));
+    }
+    for turn in last {
+        let who = if turn.role == "user" { "They" } else { "You" };
+        lines.push(format!("{who} last said: \"{}\"", excerpt(&turn.content)));
+    }
+    lines.join("\n")
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn turn(role: &str, content: &str) -> ChatTurn {
+        ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: "2026-09-01 21:00:00".to_string(),
+            source: "text".to_string(),
+            prompt_variant: None,
+            interrupted: false,
+            emotions: None,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn test_gone_quiet() {
+        let now = at("2026-09-04 21:00:00");
+        assert!(gone_quiet("2026-09-01 21:00:00", now, 72));
+        assert!(!gone_quiet("2026-09-02 21:00:00", now, 72));
+        assert!(!gone_quiet("2026-09-01 21:00:00", now, 0));
+        assert!(!gone_quiet("not a date", now, 72));
+    }
+
+    #[test]
+    fn test_summarize_previous_episode() {
+        let session = SessionInfo {
+            session_id: "session_1".to_string(),
+            message_count: 12,
+            started_at: "2026-08-30 20:00:00".to_string(),
+            last_active_at: "2026-09-01 21:00:00".to_string(),
+            archived: false,
+        };
+        let progress = SessionProgress {
+            mi_stage_start: Some("engage".to_string()),
+            mi_stage_end: Some("evoke".to_string()),
+            ..Default::default()
+        };
+        let themes = vec!["work stress".to_string(), "sleep".to_string()];
+        let last = [turn("user", "I'll try leaving by six."), turn("assistant", "x".repeat(300).as_str())];
+
+        let summary = summarize(&session, &progress, &themes, &last, at("2026-09-15 09:00:00"));
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "Last talked 2026-09-01 (13 days ago), 12 messages since 2026-08-30.");
+        assert_eq!(lines[1], "MI Stage: engage → evoke");
+        assert_eq!(lines[2], "Themes: work stress, sleep");
+        assert_eq!(lines[3], "They last said: \"I'll try leaving by six.\"");
+        assert!(lines[4].ends_with("x…\""), "{}", lines[4]);
+    }
+}
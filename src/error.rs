@@ -0,0 +1,157 @@
+//! Domain errors for the inference, storage, safety, and agent layers.
+//!
+//! Library-side code returns [`Result`] so callers can tell a model that
+//! failed to load from a database that can't be written. That covers the
+//! provider, orchestrator, engine, and agents; the SQLite store and the
+//! vector store (`memory`); and crisis routing and guardrails.
+//!
+//! The per-command modules (`chiron report`, `sync`, `serve`, the
+//! interactive check-ins, and so on) are frontends like `main.rs`: each
+//! runs one command end to end, prompting and printing as it goes, and
+//! returns `anyhow` so failures reach the user with their full context.
+
+/// Errors Chiron's core can return.
+#[derive(Debug, thiserror::Error)]
+pub enum ChironError {
+    /// The GGUF model couldn't be loaded (missing file, bad format, out of memory).
+    #[error("{0}")]
+    ModelLoad(String),
+
+    /// Generation failed: context creation, tokenization, decoding, or the
+    /// stream ended before producing any text. Trying the same prompt again
+    /// fails the same way.
+    #[error("{0}")]
+    Inference(String),
+
+    /// The model can't take the request right now: it stayed busy with
+    /// other replies past the wait limit. Worth retrying later.
+    #[error("{0}")]
+    ModelUnavailable(String),
+
+    /// A SQLite read or write failed; `context` says what was being done.
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: tokio_rusqlite::Error,
+    },
+
+    /// No session with this ID is in memory or in the database.
+    #[error("Session '{0}' not found")]
+    SessionNotFound(String),
+
+    /// A vector store (LanceDB) read or write, or an embedding, failed;
+    /// `context` says what was being done.
+    #[error("{context}")]
+    VectorStore {
+        context: String,
+        #[source]
+        source: BoxError,
+    },
+
+    /// A file Chiron reads or writes itself (guardrails, an added document)
+    /// couldn't be read, parsed, or written.
+    #[error("{context}")]
+    File {
+        context: String,
+        #[source]
+        source: BoxError,
+    },
+
+    /// A document can't be added: an unsupported type, or one that
+    /// couldn't be converted to text.
+    #[error("{0}")]
+    Document(String),
+
+    /// Writing to the terminal or another output failed.
+    #[error("Output failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The underlying error behind a vector store or file failure.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+impl ChironError {
+    /// Whether retrying later might succeed (the model is busy) as opposed
+    /// to a persistent fault.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::ModelUnavailable(_))
+    }
+
+    /// A vector store failure described by `context`, for when the
+    /// description isn't fixed (see [`Context`] for when it is).
+    pub fn vector_store(context: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        Self::VectorStore { context: context.into(), source: source.into() }
+    }
+
+    /// A failure reading, parsing, or writing a file, described by `context`.
+    pub fn file(context: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        Self::File { context: context.into(), source: source.into() }
+    }
+}
+
+pub type Result<T, E = ChironError> = std::result::Result<T, E>;
+
+/// Attaches a description to a storage failure, mirroring `anyhow::Context`
+/// so store functions read the same: `.await.context("Failed to save goal")`.
+pub trait Context<T> {
+    fn context(self, context: &'static str) -> Result<T>;
+}
+
+impl<T> Context<T> for std::result::Result<T, tokio_rusqlite::Error> {
+    fn context(self, context: &'static str) -> Result<T> {
+        self.map_err(|source| ChironError::Storage { context, source })
+    }
+}
+
+/// Vector store and embedding errors become [`ChironError::VectorStore`].
+macro_rules! vector_store_context {
+    ($($error:ty),*) => {$(
+        impl<T> Context<T> for std::result::Result<T, $error> {
+            fn context(self, context: &'static str) -> Result<T> {
+                self.map_err(|source| ChironError::vector_store(context, source))
+            }
+        }
+    )*};
+}
+
+vector_store_context!(
+    lancedb::Error,
+    arrow_schema::ArrowError,
+    rig::vector_store::VectorStoreError,
+    rig::embeddings::EmbeddingError
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_storage_context_keeps_source() {
+        let conn = tokio_rusqlite::Connection::open(":memory:").await.unwrap();
+        let err = conn
+            .call(|conn| Ok(conn.execute_batch("SELECT * FROM missing")?))
+            .await
+            .context("Failed to read missing table")
+            .unwrap_err();
+
+        assert!(matches!(err, ChironError::Storage { .. }));
+        assert_eq!(err.to_string(), "Failed to read missing table");
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert!(source.contains("missing"), "{source}");
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn test_only_an_unavailable_model_is_transient() {
+        assert!(ChironError::ModelUnavailable("busy".into()).is_transient());
+        assert!(!ChironError::Inference("Tokenization failed".into()).is_transient());
+        assert!(!ChironError::ModelLoad("missing".into()).is_transient());
+    }
+
+    #[test]
+    fn test_io_error_keeps_cause() {
+        let err = ChironError::from(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe closed"));
+        assert_eq!(err.to_string(), "Output failed: pipe closed");
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use rig::completion::Prompt;
+
+use super::rubric::Rubric;
+use crate::provider::LlamaCppCompletionModel;
+
+/// The judge only needs a score line and one sentence.
+const JUDGE_MAX_TOKENS: u64 = 96;
+
+const JUDGE_PREAMBLE: &str = "You evaluate replies from a peer-support coach against one criterion at a time. \
+Be strict and consistent. Answer with `SCORE: <1-5>` on the first line and one sentence of reasoning on the second.";
+
+/// The judge's rating of one response on one rubric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Judgement {
+    /// 1 (poor) to 5 (excellent).
+    pub score: u8,
+    pub reason: String,
+}
+
+/// Asks the model to rate `response` (a reply to `input`) on `rubric`.
+/// Returns `None` if the judge's answer has no usable score.
+pub async fn judge(
+    model: &LlamaCppCompletionModel,
+    rubric: &Rubric,
+    input: &str,
+    response: &str,
+) -> Result<Option<Judgement>> {
+    let agent = rig::agent::AgentBuilder::new(model.clone())
+        .preamble(JUDGE_PREAMBLE)
+        .temperature(0.0)
+        .max_tokens(JUDGE_MAX_TOKENS)
+        .build();
+
+    let prompt = format!(
+        "Criterion ({}): {}\n\nPerson: {input}\nCoach: {response}",
+        rubric.name, rubric.judge
+    );
+    let answer = agent
+        .prompt(prompt.as_str())
+        .await
+        .with_context(|| format!("Judge failed on rubric '{}'", rubric.id))?;
+
+    let judgement = parse_judgement(&answer);
+    if judgement.is_none() {
+        tracing::warn!(rubric = rubric.id, answer, "Judge gave no score");
+    }
+    Ok(judgement)
+}
+
+/// Parses `SCORE: n` (any case, first 1–5 digit after the colon) and takes
+/// the rest of the answer as the reason.
+fn parse_judgement(answer: &str) -> Option<Judgement> {
+    let mut lines = answer.lines();
+    let score = lines.by_ref().find_map(|line| {
+        let (label, value) = line.split_once(':')?;
+        if !label.trim().eq_ignore_ascii_case("score") {
+            return None;
+        }
+        let digit = value.trim().chars().next()?.to_digit(10)?;
+        (1..=5).contains(&digit).then_some(digit as u8)
+    })?;
+    let reason = lines.map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+    Some(Judgement { score, reason })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_judgement() {
+        assert_eq!(
+            parse_judgement("Score: 4/5\nReflects the feeling but not the cause.\n"),
+            Some(Judgement {
+                score: 4,
+                reason: "Reflects the feeling but not the cause.".to_string(),
+            })
+        );
+        assert_eq!(parse_judgement("Looks fine to me.").map(|j| j.score), None);
+        assert_eq!(parse_judgement("SCORE: 9").map(|j| j.score), None, "out of range");
+    }
+}
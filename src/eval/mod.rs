@@ -0,0 +1,330 @@
+pub mod judge;
+pub mod rubric;
+
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tokio_rusqlite::Connection;
+
+use crate::engine::ChironEngine;
+use crate::memory;
+use crate::orchestrator::OutputSink;
+use crate::progress::Progress;
+use crate::provider::LlamaCppCompletionModel;
+use rubric::{EvalCatalog, Rubric, Suite};
+
+/// One rubric's scores for one response.
+#[derive(Debug, Serialize)]
+pub struct RubricScore {
+    pub rubric: String,
+    /// 0.0–1.0.
+    pub heuristic: f64,
+    /// 1–5, when judged.
+    pub judge: Option<u8>,
+    pub reason: Option<String>,
+}
+
+/// A suite message, the pipeline's response, and its scores.
+#[derive(Debug, Serialize)]
+pub struct CaseResult {
+    pub input: String,
+    pub response: String,
+    pub scores: Vec<RubricScore>,
+}
+
+/// Mean scores for one rubric across a suite.
+#[derive(Debug, Serialize)]
+pub struct RubricSummary {
+    pub rubric: String,
+    pub name: String,
+    pub heuristic: f64,
+    /// Mean judge score (1–5) over the responses it could score.
+    pub judge: Option<f64>,
+    /// Heuristic and judge (rescaled to 0.0–1.0) averaged; heuristic alone
+    /// when unjudged.
+    pub combined: f64,
+}
+
+/// Scores for one prompt variant on a suite's rubrics, labelled with what
+/// produced the responses so runs across models and prompt versions can be
+/// compared.
+#[derive(Debug, Serialize)]
+pub struct EvalReport {
+    pub suite: String,
+    pub prompt_variant: String,
+    pub model: String,
+    /// Scored saved conversations rather than the suite's messages.
+    pub history: bool,
+    pub judged: bool,
+    pub cases: Vec<CaseResult>,
+    pub summary: Vec<RubricSummary>,
+    /// Mean of the rubrics' combined scores.
+    pub overall: f64,
+}
+
+/// A message, the response to score, and the prompt variant and model
+/// behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub prompt_variant: String,
+    pub model: String,
+    pub input: String,
+    pub response: String,
+}
+
+/// Lists the built-in suites, one per line.
+pub fn list() -> String {
+    EvalCatalog::builtin()
+        .suites()
+        .iter()
+        .map(|s| format!("{:<10} {} ({} cases)", s.id, s.description, s.cases.len()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Answers each suite message through the full pipeline, in a fresh session
+/// so cases don't share history. With an A/B test, sessions alternate
+/// variants, so one run covers all of them.
+pub async fn generate(engine: &ChironEngine, suite: &Suite) -> Result<Vec<Exchange>> {
+    let mut exchanges = Vec::with_capacity(suite.cases.len());
+    let progress = Progress::new("Case", suite.cases.len());
+    for (i, input) in suite.cases.iter().enumerate() {
+        progress.start(i, "");
+        let session_id = engine.start_session("eval").await;
+        let session = engine.session(&session_id).await?;
+        let result = {
+            let mut orchestrator = session.lock().await;
+            orchestrator.set_output(OutputSink::Silent);
+            let _following = progress.follow(orchestrator.subscribe());
+            let result = orchestrator
+                .run_turn_captured(input)
+                .await
+                .with_context(|| format!("Case {} failed", i + 1))?;
+            orchestrator.flush_saves().await.context("Failed to save the case")?;
+            result
+        };
+        engine.sessions.lock().await.remove(&session_id);
+
+        exchanges.push(Exchange {
+            prompt_variant: result.prompt_variant.unwrap_or_else(|| "crisis".to_string()),
+            model: engine.model.clone(),
+            input: input.clone(),
+            response: result.response,
+        });
+    }
+    progress.finish();
+    Ok(exchanges)
+}
+
+/// Saved responses that recorded their prompt variant, for scoring real usage.
+pub async fn history(conn: &Connection) -> Result<Vec<Exchange>> {
+    let rows = memory::variant_exchanges(conn).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(prompt_variant, model, input, response)| Exchange {
+            prompt_variant,
+            model: model.unwrap_or_else(|| "unrecorded".to_string()),
+            input,
+            response,
+        })
+        .collect())
+}
+
+/// Scores exchanges on the suite's rubrics, with the LLM judge when given
+/// one, and reports each prompt variant and model separately.
+pub async fn score(
+    exchanges: Vec<Exchange>,
+    suite: &Suite,
+    judge_model: Option<&LlamaCppCompletionModel>,
+    history: bool,
+) -> Result<Vec<EvalReport>> {
+    let catalog = EvalCatalog::builtin();
+    let rubrics: Vec<&Rubric> = suite.rubrics.iter().filter_map(|id| catalog.rubric(id)).collect();
+
+    // Grouped by variant and model, in order of first appearance
+    let mut groups: Vec<((String, String), Vec<CaseResult>)> = Vec::new();
+    let progress = judge_model.map(|_| Progress::new("Judging response", exchanges.len()));
+    for (i, exchange) in exchanges.into_iter().enumerate() {
+        if let Some(progress) = &progress {
+            progress.start(i, "");
+        }
+        let mut scores = Vec::with_capacity(rubrics.len());
+        for rubric in &rubrics {
+            let judgement = match judge_model {
+                Some(model) => judge::judge(model, rubric, &exchange.input, &exchange.response).await?,
+                None => None,
+            };
+            scores.push(RubricScore {
+                rubric: rubric.id.clone(),
+                heuristic: rubric.heuristic(&exchange.input, &exchange.response),
+                judge: judgement.as_ref().map(|j| j.score),
+                reason: judgement.map(|j| j.reason),
+            });
+        }
+        let case = CaseResult {
+            input: exchange.input,
+            response: exchange.response,
+            scores,
+        };
+        let key = (exchange.prompt_variant, exchange.model);
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, cases)) => cases.push(case),
+            None => groups.push((key, vec![case])),
+        }
+    }
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|((prompt_variant, model), cases)| {
+            let summary: Vec<RubricSummary> = rubrics.iter().map(|r| summarize(r, &cases)).collect();
+            let overall = mean(summary.iter().map(|s| s.combined)).unwrap_or(0.0);
+            EvalReport {
+                suite: suite.id.clone(),
+                prompt_variant,
+                model,
+                history,
+                judged: judge_model.is_some(),
+                cases,
+                summary,
+                overall,
+            }
+        })
+        .collect())
+}
+
+fn summarize(rubric: &Rubric, cases: &[CaseResult]) -> RubricSummary {
+    let scores: Vec<&RubricScore> = cases
+        .iter()
+        .flat_map(|c| c.scores.iter().filter(|s| s.rubric == rubric.id))
+        .collect();
+    let heuristic = mean(scores.iter().map(|s| s.heuristic)).unwrap_or(0.0);
+    let judge = mean(scores.iter().filter_map(|s| s.judge).map(f64::from));
+    let combined = match judge {
+        Some(judge) => (heuristic + (judge - 1.0) / 4.0) / 2.0,
+        None => heuristic,
+    };
+    RubricSummary {
+        rubric: rubric.id.clone(),
+        name: rubric.name.clone(),
+        heuristic,
+        judge,
+        combined,
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, n) = values.fold((0.0, 0), |(sum, n), v| (sum + v, n + 1));
+    (n > 0).then(|| sum / n as f64)
+}
+
+/// Aggregate tables, one per prompt variant, then a side-by-side overall
+/// line when there is more than one.
+pub fn render(reports: &[EvalReport]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        let source = if report.history { "saved conversations" } else { "suite messages" };
+        let _ = writeln!(
+            out,
+            "Suite: {} — variant {}, model {} ({} {source})\n",
+            report.suite,
+            report.prompt_variant,
+            report.model,
+            report.cases.len(),
+        );
+        let _ = writeln!(out, "{:<20} {:>9} {:>6} {:>9}", "Rubric", "Heuristic", "Judge", "Combined");
+        for s in &report.summary {
+            let judge = s.judge.map_or("-".to_string(), |j| format!("{j:.1}"));
+            let _ = writeln!(out, "{:<20} {:>9.2} {:>6} {:>9.2}", s.name, s.heuristic, judge, s.combined);
+        }
+        let _ = writeln!(out, "{:<20} {:>26.2}\n", "Overall", report.overall);
+    }
+    if reports.len() > 1 {
+        let models = reports.iter().any(|r| r.model != reports[0].model);
+        let overall: Vec<String> = reports
+            .iter()
+            .map(|r| {
+                if models {
+                    format!("{} ({}) {:.2}", r.prompt_variant, r.model, r.overall)
+                } else {
+                    format!("{} {:.2}", r.prompt_variant, r.overall)
+                }
+            })
+            .collect();
+        let _ = writeln!(out, "Overall by variant: {}", overall.join(", "));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(heuristic: f64, judge: Option<u8>) -> CaseResult {
+        CaseResult {
+            input: String::new(),
+            response: String::new(),
+            scores: vec![RubricScore {
+                rubric: "empathy".to_string(),
+                heuristic,
+                judge,
+                reason: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_summarize_combines_heuristic_and_judge() {
+        let rubric = EvalCatalog::builtin().rubric("empathy").unwrap();
+
+        let judged = summarize(rubric, &[case(0.5, Some(5)), case(1.0, Some(3)), case(0.75, None)]);
+        assert_eq!(judged.heuristic, 0.75);
+        assert_eq!(judged.judge, Some(4.0), "unscored responses don't count against the judge mean");
+        assert_eq!(judged.combined, (0.75 + 0.75) / 2.0);
+
+        let unjudged = summarize(rubric, &[case(0.5, None)]);
+        assert_eq!(unjudged.judge, None);
+        assert_eq!(unjudged.combined, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_score_reports_each_variant() {
+        let suite = EvalCatalog::builtin().suite("empathy").unwrap();
+        let exchange = |variant: &str, response: &str| Exchange {
+            prompt_variant: variant.to_string(),
+            model: "test".to_string(),
+            input: "I failed my driving test again.".to_string(),
+            response: response.to_string(),
+        };
+        let exchanges = vec![
+            exchange("v7", "It sounds like the driving test really stung. That makes sense."),
+            exchange("v6", "You should practice more."),
+            exchange("v7", "What do you make of it?"),
+        ];
+
+        let reports = score(exchanges, suite, None, true).await.unwrap();
+        let variants: Vec<&str> = reports.iter().map(|r| r.prompt_variant.as_str()).collect();
+        assert_eq!(variants, ["v7", "v6"]);
+        assert_eq!(reports[0].cases.len(), 2);
+        assert!(reports[0].overall > reports[1].overall);
+        assert!(!reports[0].judged);
+        assert!(render(&reports).contains("Overall by variant: v7"));
+
+        // The same variant on another model is reported apart
+        let other = Exchange { model: "other".to_string(), ..exchange("v7", "What do you make of it?") };
+        let reports = score(vec![exchange("v7", "That makes sense."), other], suite, None, true).await.unwrap();
+        let models: Vec<&str> = reports.iter().map(|r| r.model.as_str()).collect();
+        assert_eq!(models, ["test", "other"]);
+        assert!(render(&reports).contains("v7 (other)"));
+    }
+
+    #[test]
+    fn test_list_names_builtin_suites() {
+        let listing = list();
+        assert!(listing.contains("empathy"));
+        assert!(listing.contains("medical"));
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Rubric and suite definitions compiled into the binary.
+const SUITES_TOML: &str = include_str!("../../evals/suites.toml");
+
+static BUILTIN: OnceLock<EvalCatalog> = OnceLock::new();
+
+/// Words this short ("the", "and", "feel") say nothing about whether a
+/// response picked up on what the person said.
+const MIN_ECHO_WORD_LEN: usize = 5;
+
+/// The rubrics responses are scored on, and the suites that exercise them.
+#[derive(Debug, Deserialize)]
+pub struct EvalCatalog {
+    rubrics: Vec<Rubric>,
+    suites: Vec<Suite>,
+}
+
+/// One quality a response is scored on, e.g. empathy.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rubric {
+    pub id: String,
+    pub name: String,
+    /// Criterion and 1–5 anchors given to the LLM judge.
+    pub judge: String,
+    /// Phrases that suggest the quality is present.
+    #[serde(default)]
+    pub positive: Vec<String>,
+    /// Phrases that suggest it is violated.
+    #[serde(default)]
+    pub negative: Vec<String>,
+    /// Credit responses that reuse one of the person's own words.
+    #[serde(default)]
+    pub echo_input: bool,
+}
+
+/// Messages to answer and the rubrics to score the answers on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Suite {
+    pub id: String,
+    pub description: String,
+    pub rubrics: Vec<String>,
+    pub cases: Vec<String>,
+}
+
+impl EvalCatalog {
+    /// The built-in catalog (`evals/suites.toml`).
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| Self::parse(SUITES_TOML).expect("built-in suites.toml is valid"))
+    }
+
+    /// Parses and validates a catalog: every suite needs cases and may only
+    /// name rubrics the catalog defines.
+    pub fn parse(source: &str) -> Result<Self> {
+        let catalog: Self = toml::from_str(source).context("Failed to parse eval catalog")?;
+        for suite in &catalog.suites {
+            if suite.cases.is_empty() {
+                bail!("Suite '{}' has no cases", suite.id);
+            }
+            if let Some(unknown) = suite.rubrics.iter().find(|id| catalog.rubric(id).is_none()) {
+                bail!("Suite '{}' uses unknown rubric '{unknown}'", suite.id);
+            }
+        }
+        Ok(catalog)
+    }
+
+    pub fn rubric(&self, id: &str) -> Option<&Rubric> {
+        self.rubrics.iter().find(|r| r.id == id)
+    }
+
+    /// Looks up a suite by ID (case-insensitive).
+    pub fn suite(&self, id: &str) -> Option<&Suite> {
+        self.suites.iter().find(|s| s.id.eq_ignore_ascii_case(id))
+    }
+
+    /// All suites, in catalog order.
+    pub fn suites(&self) -> &[Suite] {
+        &self.suites
+    }
+}
+
+impl Rubric {
+    /// Heuristic score from 0.0 to 1.0: 0.5, plus 0.25 per positive phrase
+    /// found, minus 0.5 per negative phrase, plus 0.25 for echoing the input.
+    pub fn heuristic(&self, input: &str, response: &str) -> f64 {
+        let lower = response.to_lowercase();
+        let hits = |phrases: &[String]| phrases.iter().filter(|p| lower.contains(p.as_str())).count() as f64;
+
+        let mut score = 0.5 + 0.25 * hits(&self.positive) - 0.5 * hits(&self.negative);
+        if self.echo_input && echoes(input, &lower) {
+            score += 0.25;
+        }
+        score.clamp(0.0, 1.0)
+    }
+}
+
+/// Whether the (lowercased) response reuses a substantive word from the input.
+fn echoes(input: &str, response: &str) -> bool {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|w| w.chars().count() >= MIN_ECHO_WORD_LEN)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    !words(input).is_disjoint(&words(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog_is_valid() {
+        let catalog = EvalCatalog::builtin();
+        let empathy = catalog.suite("EMPATHY").expect("empathy suite");
+        assert_eq!(
+            empathy.rubrics,
+            vec!["empathy", "non_directive", "no_medical_advice", "validation"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_rubric_rejected() {
+        let err = EvalCatalog::parse(
+            "rubrics = []\n[[suites]]\nid = \"s\"\ndescription = \"\"\nrubrics = [\"warmth\"]\ncases = [\"hi\"]\n",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Suite 's' uses unknown rubric 'warmth'");
+    }
+
+    #[test]
+    fn test_heuristic_scoring() {
+        let catalog = EvalCatalog::builtin();
+        let empathy = catalog.rubric("empathy").unwrap();
+        let input = "I failed my driving test again.";
+        assert_eq!(empathy.heuristic(input, "Okay."), 0.5);
+        assert_eq!(
+            empathy.heuristic(input, "It sounds like failing the driving test again really stung."),
+            1.0,
+            "reflection plus echoing 'driving'"
+        );
+        assert_eq!(empathy.heuristic(input, "Cheer up, don't worry about it."), 0.0);
+
+        let non_directive = catalog.rubric("non_directive").unwrap();
+        assert_eq!(non_directive.heuristic(input, "You should just practice more."), 0.0);
+    }
+}
@@ -0,0 +1,306 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use tokio_rusqlite::Connection;
+
+use crate::memory::coping::{self, CopingSkill};
+use crate::memory::goals::{self, Goal};
+use crate::memory::homework::{self, Homework};
+use crate::memory::mood::{self, CheckIn};
+use crate::memory::safety_plan::{self, SafetyPlan};
+use crate::memory::values::{self, ValueRating};
+use crate::memory::warning_signs::{self, WarningSign};
+use crate::memory::{self, thought_records};
+use crate::timezone;
+
+/// Check-ins averaged for the "starting" and "recent" mood figures.
+const MOOD_WINDOW: usize = 7;
+
+/// Everything a graduation packet is rendered from.
+pub struct GraduationData {
+    sessions: usize,
+    first_session: Option<String>,
+    last_session: Option<String>,
+    checkins: Vec<CheckIn>,
+    goals: Vec<Goal>,
+    homework: Vec<Homework>,
+    skills: Vec<CopingSkill>,
+    thought_records: usize,
+    values: Vec<ValueRating>,
+    warning_signs: Vec<WarningSign>,
+    safety_plan: SafetyPlan,
+}
+
+/// Loads everything recorded so far for the closing packet.
+pub async fn gather(conn: &Connection) -> Result<GraduationData> {
+    let sessions = memory::list_sessions(conn).await?;
+    Ok(GraduationData {
+        sessions: sessions.len(),
+        first_session: sessions.iter().map(|s| s.started_at.clone()).min(),
+        last_session: sessions.iter().map(|s| s.last_active_at.clone()).max(),
+        checkins: mood::checkins_since(conn, "0000-01-01").await?,
+        goals: goals::list_goals(conn, false).await?,
+        homework: homework::list_homework(conn).await?,
+        skills: coping::list_skills(conn).await?,
+        thought_records: thought_records::count_thought_records(conn).await?,
+        values: values::values_profile(conn).await?,
+        warning_signs: warning_signs::list_signs(conn).await?,
+        safety_plan: safety_plan::load_safety_plan(conn).await?,
+    })
+}
+
+/// Renders the closing packet as Markdown: progress, skills, values,
+/// warning signs, a safety plan, and a maintenance schedule.
+pub fn render_markdown(data: &GraduationData, today: NaiveDate) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Chiron Graduation Packet\n");
+    let _ = writeln!(out, "Prepared {today}\n");
+    let _ = writeln!(
+        out,
+        "> A summary of your work with a self-help peer-support tool, to keep and \
+         revisit. It is not a clinical assessment or treatment plan.\n"
+    );
+
+    // Progress
+    let _ = writeln!(out, "## Your Progress\n");
+    match (&data.first_session, &data.last_session) {
+        (Some(first), Some(last)) => {
+            let _ = writeln!(
+                out,
+                "{} sessions between {} and {}.",
+                data.sessions,
+                timezone::day(first),
+                timezone::day(last)
+            );
+        }
+        _ => {
+            let _ = writeln!(out, "No chat sessions recorded.");
+        }
+    }
+    if let Some((start, recent)) = mood_change(&data.checkins) {
+        let _ = writeln!(
+            out,
+            "Average mood went from {start:.1}/10 over your first {MOOD_WINDOW} check-ins \
+             to {recent:.1}/10 over your last {MOOD_WINDOW}."
+        );
+    } else if let Some(average) = mood::average_mood(&data.checkins) {
+        let _ = writeln!(
+            out,
+            "{} check-ins, average mood {average:.1}/10.",
+            data.checkins.len()
+        );
+    }
+    let (done_homework, reviewed_homework) = homework::completion_counts(&data.homework);
+    if reviewed_homework > 0 {
+        let _ = writeln!(
+            out,
+            "Between-session homework: {done_homework} of {reviewed_homework} completed."
+        );
+    }
+    let _ = writeln!(out);
+
+    let (done, open): (Vec<&Goal>, Vec<&Goal>) = data.goals.iter().partition(|g| g.completed);
+    if !done.is_empty() {
+        let _ = writeln!(out, "Goals completed:\n");
+        for g in &done {
+            let _ = writeln!(out, "- {}", g.description);
+        }
+        let _ = writeln!(out);
+    }
+    if !open.is_empty() {
+        let _ = writeln!(out, "Goals to keep working on:\n");
+        for g in &open {
+            let _ = writeln!(out, "- {}", g.description);
+        }
+        let _ = writeln!(out);
+    }
+
+    // Skills
+    let _ = writeln!(out, "## Skills You've Learned\n");
+    let helpful = coping::helpful_skills(&data.skills);
+    if helpful.is_empty() && data.thought_records == 0 {
+        let _ = writeln!(out, "None recorded yet.\n");
+    } else {
+        for skill in data.skills.iter().filter(|s| helpful.contains(&s.name.as_str())) {
+            let _ = writeln!(
+                out,
+                "- {} (rated {:.1}/5 over {} tries)",
+                skill.name, skill.average, skill.times_rated
+            );
+        }
+        if data.thought_records > 0 {
+            let _ = writeln!(
+                out,
+                "- Thought records: {} completed. Situation → thought → evidence → balanced thought.",
+                data.thought_records
+            );
+        }
+        let _ = writeln!(out);
+    }
+
+    // Values
+    if !data.values.is_empty() {
+        let _ = writeln!(out, "## What Matters to You\n");
+        for v in data.values.iter().take(5) {
+            let _ = write!(out, "- {}", v.domain.replace('_', " "));
+            if let Some(note) = &v.note {
+                let _ = write!(out, ": {note}");
+            }
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out);
+    }
+
+    // Warning signs
+    let _ = writeln!(out, "## Early Warning Signs\n");
+    if data.warning_signs.is_empty() {
+        let _ = writeln!(out, "None defined. Consider writing down a few before you go.\n");
+    } else {
+        for s in &data.warning_signs {
+            let _ = writeln!(out, "- {}", s.sign);
+        }
+        let _ = writeln!(out);
+    }
+
+    // Safety plan
+    let _ = writeln!(out, "## Safety Plan\n");
+    // Filled in from the stored plan where possible; blanks to write in otherwise
+    let plan = &data.safety_plan;
+    let blank = |entries: &[String]| {
+        if entries.is_empty() { "________________".to_string() } else { entries.join("; ") }
+    };
+    let own_coping = if plan.coping.is_empty() {
+        helpful.iter().map(|s| s.to_string()).collect()
+    } else {
+        plan.coping.clone()
+    };
+    let _ = writeln!(out, "1. **My warning signs:** see above.");
+    let _ = writeln!(out, "2. **Things I can do on my own:** {}", blank(&own_coping));
+    let _ = writeln!(out, "3. **People and places that help me feel better:** {}", blank(&plan.places));
+    let _ = writeln!(out, "4. **People I can ask for help:** {}", blank(&plan.contacts));
+    let _ = writeln!(out, "5. **Professionals I can contact:** {}", blank(&plan.professionals));
+    let _ = writeln!(
+        out,
+        "6. **In a crisis:** call or text 988 (Suicide & Crisis Lifeline), or text HOME to 741741 \
+         (Crisis Text Line). In an emergency, call 911."
+    );
+    let _ = writeln!(out, "7. **Making my environment safer:** {}", blank(&plan.environment));
+    if !plan.reasons.is_empty() {
+        let _ = writeln!(out, "8. **My reasons for living:** {}", plan.reasons.join("; "));
+    }
+    let _ = writeln!(out);
+
+    // Maintenance
+    let _ = writeln!(out, "## Maintenance Schedule\n");
+    let _ = writeln!(out, "- **Daily:** a quick `chiron checkin`.");
+    let _ = writeln!(out, "- **Weekly:** look at `chiron mood chart` and `/sleep week`.");
+    let _ = writeln!(out, "- **Monthly:** revisit `/values` and pick one small goal.");
+    let _ = writeln!(out, "- **Every three months:** run `chiron report` and reread this packet.");
+    let _ = writeln!(
+        out,
+        "- **If two or more warning signs show up in the same week:** use your safety plan, \
+         and consider reaching out to someone on it or coming back for a few sessions."
+    );
+
+    out
+}
+
+/// Writes the packet to `path`. A `.pdf` extension converts the Markdown
+/// with pandoc; anything else is written as Markdown.
+pub fn export(markdown: &str, path: &Path) -> Result<()> {
+    let is_pdf = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return std::fs::write(path, markdown)
+            .with_context(|| format!("Failed to write {}", path.display()));
+    }
+
+    let mut child = Command::new("pandoc")
+        .args(["--from", "markdown", "--output"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("PDF export needs pandoc (and a LaTeX engine) on PATH; use a .md path instead")?;
+    // Closing stdin (dropping it) tells pandoc the input is complete
+    let written = match child.stdin.take() {
+        Some(mut stdin) => stdin.write_all(markdown.as_bytes()),
+        None => Ok(()),
+    };
+    if let Err(e) = written {
+        // pandoc exited early or can't read; don't leave it behind
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(e).context("Failed to send the packet to pandoc");
+    }
+    let status = child.wait().context("Failed to wait for pandoc")?;
+    if !status.success() {
+        bail!("pandoc failed to write {} ({status})", path.display());
+    }
+    Ok(())
+}
+
+/// Average mood over the first and last `MOOD_WINDOW` check-ins, once there
+/// are enough check-ins for the two windows not to overlap.
+fn mood_change(checkins: &[CheckIn]) -> Option<(f64, f64)> {
+    if checkins.len() < MOOD_WINDOW * 2 {
+        return None;
+    }
+    let start = mood::average_mood(&checkins[..MOOD_WINDOW])?;
+    let recent = mood::average_mood(&checkins[checkins.len() - MOOD_WINDOW..])?;
+    Some((start, recent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkin(mood: i32) -> CheckIn {
+        CheckIn {
+            id: 0,
+            mood,
+            sleep_hours: None,
+            highlight: None,
+            stressor: None,
+            created_at: "2026-03-01 09:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_mood_change_needs_two_windows() {
+        let mut checkins: Vec<CheckIn> = (0..7).map(|_| checkin(3)).collect();
+        assert_eq!(mood_change(&checkins), None);
+        checkins.extend((0..7).map(|_| checkin(7)));
+        assert_eq!(mood_change(&checkins), Some((3.0, 7.0)));
+    }
+
+    #[tokio::test]
+    async fn test_packet_sections() {
+        let conn = memory::open_memory(":memory:").await.unwrap();
+        let walk = goals::add_goal(&conn, "s1", "Walk three times a week").await.unwrap();
+        goals::complete_goal(&conn, walk).await.unwrap();
+        coping::rate_skill(&conn, "s1", "Paced breathing", 5).await.unwrap();
+        warning_signs::add_sign(&conn, "cancelling plans", &[]).await.unwrap();
+
+        let data = gather(&conn).await.unwrap();
+        let packet = render_markdown(&data, NaiveDate::from_ymd_opt(2026, 6, 1).unwrap());
+
+        for heading in [
+            "## Your Progress",
+            "## Skills You've Learned",
+            "## Early Warning Signs",
+            "## Safety Plan",
+            "## Maintenance Schedule",
+        ] {
+            assert!(packet.contains(heading), "missing {heading}");
+        }
+        assert!(packet.contains("Goals completed:\n\n- Walk three times a week"));
+        assert!(packet.contains("Things I can do on my own:** Paced breathing"));
+        assert!(packet.contains("- cancelling plans"));
+        assert!(packet.contains("988"));
+    }
+}
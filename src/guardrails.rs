@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ChironError, Result};
+
+/// Safety settings that can be tuned while Chiron is running, read from
+/// `guardrails.toml` next to `config.toml` and reloaded when it changes
+/// (see `reload`). They add to the built-in safety checks; they never
+/// replace or loosen them.
+///
+/// ```toml
+/// crisis_keywords = ["can't go on", "no way out"]
+/// crisis_response = "If you're in danger, call 112 or ..."
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Guardrails {
+    /// Extra crisis keywords (case-insensitive), checked alongside the
+    /// built-in English ones and the active locale's.
+    pub crisis_keywords: Vec<String>,
+    /// Crisis resource text shown instead of the locale's `crisis.response`
+    /// (e.g. local hotlines).
+    pub crisis_response: Option<String>,
+}
+
+static CURRENT: LazyLock<RwLock<Arc<Guardrails>>> = LazyLock::new(Default::default);
+
+impl Guardrails {
+    /// Path of the guardrails file that belongs to `config_path`.
+    pub fn path_for(config_path: &Path) -> PathBuf {
+        config_path.with_file_name("guardrails.toml")
+    }
+
+    /// Loads guardrails from `path`. A missing file means no extra guardrails.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| ChironError::file(format!("Failed to read guardrails: {}", path.display()), e))?;
+        let mut guardrails: Self = toml::from_str(&content)
+            .map_err(|e| ChironError::file(format!("Failed to parse guardrails: {}", path.display()), e))?;
+        guardrails.crisis_keywords = guardrails
+            .crisis_keywords
+            .iter()
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+        Ok(guardrails)
+    }
+
+    /// Writes the guardrails to `path` (`chiron init`).
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content =
+            toml::to_string_pretty(self).map_err(|e| ChironError::file("Failed to serialize guardrails", e))?;
+        fs::write(path, content).map_err(|e| ChironError::file(format!("Failed to write guardrails: {}", path.display()), e))
+    }
+
+    /// Whether lowercased `input` contains one of the extra crisis keywords.
+    pub fn matches_crisis(&self, lower: &str) -> bool {
+        self.crisis_keywords.iter().any(|k| lower.contains(k.as_str()))
+    }
+
+    /// Describes what differs from `previous`, one line per setting.
+    pub fn changes_from(&self, previous: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        let added: Vec<_> = self
+            .crisis_keywords
+            .iter()
+            .filter(|k| !previous.crisis_keywords.contains(k))
+            .collect();
+        let removed: Vec<_> = previous
+            .crisis_keywords
+            .iter()
+            .filter(|k| !self.crisis_keywords.contains(k))
+            .collect();
+        if !added.is_empty() {
+            changes.push(format!("crisis_keywords: added {added:?}"));
+        }
+        if !removed.is_empty() {
+            changes.push(format!("crisis_keywords: removed {removed:?}"));
+        }
+        if self.crisis_response != previous.crisis_response {
+            changes.push(match &self.crisis_response {
+                Some(text) => format!("crisis_response: set ({} chars)", text.chars().count()),
+                None => "crisis_response: back to the locale default".to_string(),
+            });
+        }
+        changes
+    }
+}
+
+/// The guardrails in effect.
+pub fn current() -> Arc<Guardrails> {
+    CURRENT.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Replaces the guardrails in effect; the next safety check uses them.
+pub fn set(guardrails: Guardrails) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(guardrails);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_normalizes_keywords_and_tolerates_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guardrails.toml");
+        assert_eq!(Guardrails::load(&path).unwrap(), Guardrails::default());
+
+        fs::write(&path, "crisis_keywords = [\"  No Way Out \", \"\"]\n").unwrap();
+        let guardrails = Guardrails::load(&path).unwrap();
+        assert_eq!(guardrails.crisis_keywords, vec!["no way out"]);
+        assert!(guardrails.matches_crisis("there's no way out for me"));
+        assert!(!guardrails.matches_crisis("I found a way through"));
+
+        fs::write(&path, "crisis_keywords = \"not a list\"\n").unwrap();
+        assert!(Guardrails::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_changes_from_lists_each_difference() {
+        let before = Guardrails {
+            crisis_keywords: vec!["no way out".to_string(), "can't go on".to_string()],
+            crisis_response: None,
+        };
+        let after = Guardrails {
+            crisis_keywords: vec!["can't go on".to_string(), "give up".to_string()],
+            crisis_response: Some("Call 112".to_string()),
+        };
+
+        let changes = after.changes_from(&before);
+        assert_eq!(
+            changes,
+            vec![
+                "crisis_keywords: added [\"give up\"]",
+                "crisis_keywords: removed [\"no way out\"]",
+                "crisis_response: set (8 chars)",
+            ]
+        );
+        assert!(after.changes_from(&after).is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::Result;
+use tokio_rusqlite::Connection;
+
+use crate::checkin::ask;
+use crate::i18n::{t, tf};
+use crate::memory::homework;
+use crate::router;
+use crate::timezone;
+
+/// Asks about homework from earlier sessions that is now due: whether they
+/// got to it and what they noticed. Runs at the start of a chat session;
+/// prints nothing when there is nothing to review.
+pub async fn review(conn: &Connection, session_id: &str) -> Result<()> {
+    let today = timezone::today().to_string();
+    let due = homework::due_for_review(conn, session_id, &today).await?;
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    println!("{}", t("homework.review_intro"));
+    for item in &due {
+        let completed = loop {
+            let answer = ask(&tf("homework.review_done", &[("task", &item.assignment)]))?;
+            match parse_yes_no(&answer) {
+                Some(done) => break done,
+                None => println!("{}", t("homework.review_invalid")),
+            }
+        };
+        let reflection = ask(if completed {
+            t("homework.reflection_done")
+        } else {
+            t("homework.reflection_missed")
+        })?;
+
+        // Free text gets the same safety check as chat messages
+        if router::is_crisis(&reflection) {
+            println!("\n{}\n", router::crisis_response());
+        }
+
+        homework::record_review(
+            conn,
+            item.id,
+            completed,
+            Some(reflection.as_str()).filter(|r| !r.is_empty()),
+        )
+        .await?;
+    }
+
+    let all = homework::list_homework(conn).await?;
+    let (done, reviewed) = homework::completion_counts(&all);
+    println!("{}", tf("homework.progress", &[("done", &done), ("reviewed", &reviewed)]));
+    Ok(())
+}
+
+/// Runs a `/homework` command: `add <task> [by YYYY-MM-DD]` or `list`. A
+/// bare `/homework` lists assignments.
+pub async fn command(conn: &Connection, session_id: &str, args: &str) -> crate::error::Result<String> {
+    let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+    let rest = rest.trim();
+
+    match action {
+        "add" if !rest.is_empty() => {
+            let (task, due) = parse_assignment(rest);
+            let id = homework::assign_homework(conn, session_id, task, due.as_deref()).await?;
+            Ok(tf("homework.added", &[("id", &id), ("task", &task)]))
+        }
+        "list" | "" => {
+            let all = homework::list_homework(conn).await?;
+            if all.is_empty() {
+                return Ok(t("homework.none").to_string());
+            }
+            let mut lines: Vec<String> = all
+                .iter()
+                .map(|h| {
+                    let status = match h.completed {
+                        None => " ",
+                        Some(true) => "x",
+                        Some(false) => "-",
+                    };
+                    let due = h.due_date.as_deref().map(|d| format!(" (due {d})")).unwrap_or_default();
+                    format!("[{status}] #{} {}{due}", h.id, h.assignment)
+                })
+                .collect();
+            let (done, reviewed) = homework::completion_counts(&all);
+            if reviewed > 0 {
+                lines.push(tf("homework.progress", &[("done", &done), ("reviewed", &reviewed)]));
+            }
+            Ok(lines.join("\n"))
+        }
+        _ => Ok(t("homework.usage").to_string()),
+    }
+}
+
+/// Splits `task by YYYY-MM-DD` into the task and its due date. Without a
+/// valid trailing date, the whole input is the task.
+fn parse_assignment(input: &str) -> (&str, Option<String>) {
+    if let Some((task, date)) = input.rsplit_once(" by ") {
+        if let Ok(due) = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            return (task.trim(), Some(due.to_string()));
+        }
+    }
+    (input, None)
+}
+
+/// Parses a yes/no answer in English or Spanish.
+fn parse_yes_no(answer: &str) -> Option<bool> {
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" | "s" | "si" | "sí" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("Walk after dinner by 2026-03-05"),
+            ("Walk after dinner", Some("2026-03-05".to_string()))
+        );
+        assert_eq!(parse_assignment("Stand by a friend"), ("Stand by a friend", None));
+    }
+
+    #[test]
+    fn test_parse_yes_no() {
+        assert_eq!(parse_yes_no("Yes"), Some(true));
+        assert_eq!(parse_yes_no("sí"), Some(true));
+        assert_eq!(parse_yes_no("n"), Some(false));
+        assert_eq!(parse_yes_no("maybe"), None);
+    }
+
+    #[tokio::test]
+    async fn test_command_add_and_list() {
+        let conn = crate::memory::open_memory(":memory:").await.unwrap();
+        let added = command(&conn, "s1", "add Walk after dinner by 2026-03-05").await.unwrap();
+        assert!(added.contains("Walk after dinner"), "{added}");
+
+        let list = command(&conn, "s1", "").await.unwrap();
+        assert_eq!(list, "[ ] #1 Walk after dinner (due 2026-03-05)");
+        assert_eq!(command(&conn, "s1", "frobnicate").await.unwrap(), t("homework.usage"));
+    }
+}
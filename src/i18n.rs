@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::language::{self, PatternSet};
+
+/// Locales compiled into the binary, as (code, TOML source).
+const LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.toml")),
+    ("es", include_str!("../locales/es.toml")),
+];
+
+static ACTIVE: OnceLock<Locale> = OnceLock::new();
+static RESPONSE: OnceLock<Locale> = OnceLock::new();
+static ENGLISH: OnceLock<Locale> = OnceLock::new();
+
+/// A set of user-facing strings for one language.
+#[derive(Debug)]
+pub struct Locale {
+    code: String,
+    strings: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Parses a locale file. Nested tables become dotted keys
+    /// (`[chat] goodbye = ...` → `chat.goodbye`).
+    fn parse(code: &str, source: &str) -> Result<Self> {
+        let table: toml::Table = toml::from_str(source)?;
+        let mut strings = HashMap::new();
+
+        for (section, value) in table {
+            let toml::Value::Table(entries) = value else {
+                bail!("Top-level key '{section}' must be a table");
+            };
+            for (key, value) in entries {
+                let toml::Value::String(s) = value else {
+                    bail!("'{section}.{key}' must be a string");
+                };
+                strings.insert(format!("{section}.{key}"), s);
+            }
+        }
+
+        Ok(Self {
+            code: code.to_string(),
+            strings,
+        })
+    }
+
+    fn embedded(code: &str) -> Result<Self> {
+        let (_, source) = LOCALES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .with_context(|| {
+                let available: Vec<_> = LOCALES.iter().map(|(c, _)| *c).collect();
+                format!(
+                    "Unsupported language '{code}' (available: {})",
+                    available.join(", ")
+                )
+            })?;
+        Self::parse(code, source).with_context(|| format!("Invalid locale file for '{code}'"))
+    }
+}
+
+/// Codes of the languages compiled into the binary.
+pub fn languages() -> Vec<&'static str> {
+    LOCALES.iter().map(|(code, _)| *code).collect()
+}
+
+/// Selects the language for user-facing strings. Call once at startup;
+/// until then (and in tests) English is used.
+pub fn init(code: &str) -> Result<()> {
+    let locale = Locale::embedded(code)?;
+    ACTIVE
+        .set(locale)
+        .map_err(|_| anyhow!("Language already initialized"))
+}
+
+/// Selects the language the coach replies in when it differs from the
+/// interface's (`persona.response_language`). Call once at startup, after
+/// [`init`]; until then replies follow the interface language with no
+/// instruction to the model.
+pub fn init_response(code: &str) -> Result<()> {
+    let locale = Locale::embedded(code)?;
+    RESPONSE
+        .set(locale)
+        .map_err(|_| anyhow!("Response language already initialized"))
+}
+
+fn english() -> &'static Locale {
+    ENGLISH.get_or_init(|| Locale::embedded("en").expect("embedded English locale is valid"))
+}
+
+fn active() -> &'static Locale {
+    ACTIVE.get().unwrap_or_else(english)
+}
+
+/// Looks up a string by key, falling back to English if the active
+/// locale doesn't define it.
+pub fn t(key: &'static str) -> &'static str {
+    active()
+        .strings
+        .get(key)
+        .or_else(|| english().strings.get(key))
+        .map(String::as_str)
+        .unwrap_or_else(|| {
+            tracing::warn!(key, "Missing translation");
+            key
+        })
+}
+
+/// Looks up a string and fills `{name}` placeholders.
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(key).to_string(), |s, (name, value)| {
+        s.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// Looks up a string in the response language (see [`init_response`]),
+/// falling back to [`t`].
+pub fn response_t(key: &'static str) -> &'static str {
+    RESPONSE
+        .get()
+        .and_then(|locale| locale.strings.get(key))
+        .map_or_else(|| t(key), String::as_str)
+}
+
+/// The response language's name in English (`Spanish`), for telling the
+/// model what to reply in. `None` unless one was set.
+pub fn response_language() -> Option<&'static str> {
+    RESPONSE
+        .get()
+        .and_then(|locale| locale.strings.get("language.english_name"))
+        .map(String::as_str)
+}
+
+/// The pattern sets for the interface language and, if it differs,
+/// the response language (see `language`).
+pub fn pattern_sets() -> impl Iterator<Item = &'static PatternSet> {
+    let response = RESPONSE.get().map(|locale| locale.code.as_str());
+    [Some(active().code.as_str()), response].into_iter().flatten().filter_map(language::patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_locales_parse_with_same_keys() {
+        let en = Locale::embedded("en").unwrap();
+        for (code, _) in LOCALES {
+            let locale = Locale::embedded(code).unwrap();
+            for key in en.strings.keys() {
+                assert!(locale.strings.contains_key(key), "{code} is missing '{key}'");
+            }
+        }
+    }
+
+    #[test]
+    fn test_crisis_response_keeps_hotlines() {
+        for (code, _) in LOCALES {
+            let locale = Locale::embedded(code).unwrap();
+            let response = &locale.strings["crisis.response"];
+            assert!(response.contains("988"), "{code} crisis response lacks 988");
+            assert!(response.contains("741741"), "{code} crisis response lacks 741741");
+        }
+    }
+
+    #[test]
+    fn test_tf_substitutes_placeholders() {
+        assert_eq!(tf("banner.coach", &[("coach", &"v7")]), "Coach: v7");
+    }
+
+    #[test]
+    fn test_response_language_defaults_to_interface() {
+        // Not initialized in tests
+        assert_eq!(response_language(), None);
+        assert_eq!(response_t("crisis.response"), t("crisis.response"));
+        assert_eq!(Locale::embedded("es").unwrap().strings["language.english_name"], "Spanish");
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        assert!(Locale::embedded("xx").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// Crisis keywords by language, compiled into the binary.
+const SOURCE: &str = include_str!("../locales/crisis.toml");
+
+/// Common words a message needs from one language before it's taken to be
+/// written in it.
+const MIN_HITS: usize = 2;
+
+static SETS: OnceLock<HashMap<String, PatternSet>> = OnceLock::new();
+
+/// One language's crisis keywords (lowercase).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PatternSet {
+    /// Common words, for telling which language a message is in.
+    stopwords: Vec<String>,
+    /// Keywords that get the crisis response.
+    pub patterns: Vec<String>,
+    /// Softer risk phrases (see `router::elevated_risk`).
+    pub elevated_patterns: Vec<String>,
+}
+
+fn sets() -> &'static HashMap<String, PatternSet> {
+    SETS.get_or_init(|| toml::from_str(SOURCE).expect("embedded crisis patterns are valid"))
+}
+
+/// The pattern set for a language code (`de`), if there is one.
+pub fn patterns(code: &str) -> Option<&'static PatternSet> {
+    sets().get(code)
+}
+
+/// The language `text` is written in, from the common words it uses.
+/// `None` when there are too few to tell, or two languages tie.
+pub fn detect(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let mut scores: Vec<(&'static str, usize)> = sets()
+        .iter()
+        .map(|(code, set)| (code.as_str(), words.iter().filter(|&&w| set.stopwords.iter().any(|s| s == w)).count()))
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+    match scores.as_slice() {
+        [(code, best), rest @ ..] if *best >= MIN_HITS && rest.first().is_none_or(|(_, next)| next < best) => {
+            Some(*code)
+        }
+        _ => None,
+    }
+}
+
+/// Every language's pattern set. Crisis keywords are checked in all of
+/// them, since people switch languages mid-message ("I feel like quiero
+/// morir") and detection only sees the language most of it is in.
+pub fn all() -> impl Iterator<Item = &'static PatternSet> {
+    sets().values()
+}
+
+/// The pattern sets to check `text`'s softer risk phrases against: its
+/// detected language's, or every language's when it can't be told (short
+/// messages often can't). Those phrases are common enough words that
+/// checking every language would flag ordinary messages.
+pub fn sets_for(text: &str) -> Vec<&'static PatternSet> {
+    match detect(text) {
+        Some(code) => patterns(code).into_iter().collect(),
+        None => sets().values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sets_parse_and_are_lowercase() {
+        for (code, set) in sets() {
+            assert!(!set.stopwords.is_empty(), "{code} has no stopwords");
+            for p in set.patterns.iter().chain(&set.elevated_patterns).chain(&set.stopwords) {
+                assert_eq!(*p, p.to_lowercase(), "{code}: '{p}' isn't lowercase");
+            }
+        }
+        assert!(patterns("de").is_some_and(|set| set.patterns.iter().any(|p| p == "selbstmord")));
+        assert!(patterns("xx").is_none());
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect("I think that it's just too much for me"), Some("en"));
+        assert_eq!(detect("No sé qué hacer con mi vida"), Some("es"));
+        assert_eq!(detect("Ich weiß nicht, wie es weitergehen soll"), Some("de"));
+        assert_eq!(detect("hello"), None);
+        assert_eq!(sets_for("hello").len(), sets().len());
+        assert_eq!(sets_for("Ich bin so müde").len(), 1);
+        assert_eq!(all().count(), sets().len());
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+/// Clears the visible screen and the scrollback, then homes the cursor.
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[3J\x1b[H";
+
+/// Hides typed text on terminals that support it (SGR 8), for the unlock
+/// prompt; `RESET` ends it.
+pub const CONCEAL: &str = "\x1b[8m";
+pub const RESET: &str = "\x1b[0m";
+
+/// What `/hide` leaves on screen: an idle shell prompt.
+pub const DECOY_PROMPT: &str = "$ ";
+
+/// Locks the interactive chat after a stretch without input, so a
+/// conversation isn't left on screen on a shared computer.
+pub struct IdleLock {
+    timeout: Duration,
+    /// Required to unlock. When encryption is off (no `sync-passphrase`
+    /// secret), Enter unlocks.
+    passphrase: Option<String>,
+}
+
+impl IdleLock {
+    /// `None` when `minutes` is 0 (the lock is off).
+    pub fn new(minutes: u64, passphrase: Option<String>) -> Option<Self> {
+        (minutes > 0).then(|| Self {
+            timeout: Duration::from_secs(minutes * 60),
+            passphrase,
+        })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn has_passphrase(&self) -> bool {
+        self.passphrase.is_some()
+    }
+
+    /// Whether `input` (one line, without its newline) unlocks.
+    pub fn unlocks(&self, input: &str) -> bool {
+        match &self.passphrase {
+            Some(passphrase) => input == passphrase,
+            None => true,
+        }
+    }
+}
+
+/// Turns off echo at the terminal while locked, so the passphrase stays
+/// hidden whichever read gets it (the chat's own read is usually still
+/// waiting when the lock starts). Switching also discards anything typed
+/// but not yet entered, so a half-typed message can't end up in front of
+/// the passphrase. Echo comes back on drop. Does nothing if stdin isn't a
+/// terminal.
+pub struct NoEcho {
+    #[cfg(unix)]
+    saved: Option<nix::sys::termios::Termios>,
+}
+
+impl NoEcho {
+    #[cfg(unix)]
+    pub fn start() -> Self {
+        use nix::sys::termios::{tcgetattr, tcsetattr, LocalFlags, SetArg};
+
+        let stdin = std::io::stdin();
+        let saved = tcgetattr(&stdin).ok().filter(|saved| {
+            let mut hidden = saved.clone();
+            hidden.local_flags.remove(LocalFlags::ECHO);
+            hidden.local_flags.insert(LocalFlags::ECHONL);
+            tcsetattr(&stdin, SetArg::TCSAFLUSH, &hidden).is_ok()
+        });
+        Self { saved }
+    }
+
+    #[cfg(not(unix))]
+    pub fn start() -> Self {
+        Self {}
+    }
+}
+
+impl Drop for NoEcho {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = &self.saved {
+            let _ = nix::sys::termios::tcsetattr(std::io::stdin(), nix::sys::termios::SetArg::TCSANOW, saved);
+        }
+    }
+}
+
+/// Resolves with the lock once its timeout passes, or never if it's off.
+/// Raced against reading a line, so any input restarts the wait.
+pub async fn idle(idle_lock: Option<&IdleLock>) -> &IdleLock {
+    match idle_lock {
+        Some(idle_lock) => {
+            tokio::time::sleep(idle_lock.timeout).await;
+            idle_lock
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_off_at_zero_and_checks_the_passphrase() {
+        assert!(IdleLock::new(0, None).is_none());
+
+        let open = IdleLock::new(5, None).unwrap();
+        assert_eq!(open.timeout(), Duration::from_secs(300));
+        assert!(open.unlocks(""));
+
+        let locked = IdleLock::new(5, Some("correct horse".to_string())).unwrap();
+        assert!(locked.has_passphrase());
+        assert!(locked.unlocks("correct horse"));
+        assert!(!locked.unlocks(""));
+        assert!(!locked.unlocks("correct horse "));
+    }
+}
@@ -1,26 +1,90 @@
 mod agents;
+mod archive;
+mod assessment;
+mod bridges;
 mod catalog;
+mod chart;
+mod chat;
+mod checkin;
+mod closure;
+mod coherence;
+mod completions;
+mod config;
+mod doctor;
+mod drift;
+mod emotion;
+mod engagement;
+mod engine;
+mod episodes;
+mod error;
+mod eval;
+mod graduation;
+mod guardrails;
+mod homework;
+mod i18n;
+mod language;
+mod lock;
 mod memory;
+mod noise;
+mod observe;
+mod offline;
 mod orchestrator;
+mod output;
+mod progress;
 mod provider;
+mod relax;
+mod reload;
+mod remind;
+mod replay;
+mod report;
+mod review;
+mod risk;
 mod router;
+mod safety_plan;
+mod secrets;
+mod setup;
+mod speech;
+mod server;
+mod sleep;
+mod stats;
 mod supervision;
-
-use std::io::{self, Write};
+mod sync;
+mod synth;
+mod telemetry;
+mod themes;
+mod thought_record;
+mod timezone;
+mod transcript;
+mod values;
+mod voice;
+mod warning;
+mod wrap;
+
+use std::io;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::Instant;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
 use rig::completion::Chat;
-use tracing_subscriber::EnvFilter;
+use tokio::sync::watch;
 
 use crate::agents::peer::build_peer_coach;
-use crate::catalog::{ModeCatalog, PromptCatalog};
-use crate::orchestrator::Orchestrator;
+use crate::catalog::{AbTest, AbUnit, ModalityCatalog, ModeCatalog, PromptCatalog, PromptVariant, RelaxationCatalog};
+use crate::config::Config;
+use crate::engine::ChironEngine;
+use crate::eval::rubric::EvalCatalog;
+use crate::guardrails::Guardrails;
+use crate::i18n::t;
+use crate::memory::consent::Consent;
+use crate::memory::feedback::FeedbackFilter;
+use crate::memory::session_names::SessionRef;
+use crate::memory::writer::{Autosave, AutosaveMode};
+use crate::orchestrator::OutputSink;
+use crate::progress::Progress;
 use crate::provider::config::GenerationConfig;
-use crate::provider::LlamaCppProvider;
+use crate::provider::{Backend, LlamaCppProvider, MockBackend};
 
 /// A scripted test conversation loaded from TOML.
 #[derive(serde::Deserialize)]
@@ -42,384 +106,1617 @@ struct TestTurn {
 #[command(name = "chiron")]
 #[command(about = "MI peer support chatbot powered by Plotinus (llama.cpp)")]
 struct Args {
-    /// Path to the GGUF model file
-    #[arg(long, default_value = "models/plotinus.gguf")]
-    model: PathBuf,
-
-    /// Maximum tokens to generate per response
-    #[arg(long, default_value = "512")]
-    max_tokens: usize,
-
-    /// Sampling temperature (0.0 = greedy, higher = more random)
-    #[arg(long, default_value = "0.7")]
-    temperature: f64,
-
-    /// Run a single benchmark inference and exit. Value is the prompt to send.
-    #[arg(long)]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the config file [default: ~/.config/chiron/config.toml]
+    #[arg(long, env = "CHIRON_CONFIG", global = true)]
+    config: Option<PathBuf>,
+
+    /// Path to the GGUF model file [default: models/plotinus.gguf]
+    #[arg(long, env = "CHIRON_MODEL")]
+    model: Option<PathBuf>,
+
+    /// Maximum tokens to generate per response [default: 512]
+    #[arg(long, env = "CHIRON_MAX_TOKENS")]
+    max_tokens: Option<usize>,
+
+    /// Sampling temperature (0.0 = greedy, higher = more random) [default: 0.7]
+    #[arg(long, env = "CHIRON_TEMPERATURE")]
+    temperature: Option<f64>,
+
+    /// Sampling seed: the same seed, model, settings, and prompt give the
+    /// same reply. llama.cpp seeds are 32-bit, so 0 to 4294967295
+    /// [default: 42]
+    #[arg(long, env = "CHIRON_SEED")]
+    seed: Option<u32>,
+
+    /// Reproducible output for testing: greedy sampling (temperature 0),
+    /// overriding --temperature. `replay` and `eval` do this unless given a
+    /// temperature
+    #[arg(long, env = "CHIRON_DETERMINISTIC")]
+    deterministic: bool,
+
+    /// Same as `chiron bench <PROMPT>`
+    #[arg(long, hide = true)]
     bench: Option<String>,
 
-    /// Run a scripted test conversation from a TOML file and output JSON results.
-    #[arg(long)]
+    /// Same as `chiron script <PATH>`
+    #[arg(long, hide = true)]
     script: Option<PathBuf>,
 
-    /// Seed MI knowledge base from a markdown file and exit.
-    #[arg(long)]
+    /// Replay canned responses from a fixture instead of loading a model.
+    /// Value is a scenario name in fixtures/mock/ or a path to a fixture file.
+    #[arg(long, value_name = "SCENARIO|PATH", global = true)]
+    mock: Option<String>,
+
+    /// Same as `chiron seed-knowledge <PATH>`
+    #[arg(long, hide = true)]
     seed_knowledge: Option<PathBuf>,
 
-    /// Path to SQLite database file for chat history + case notes
-    #[arg(long, default_value = "chiron.db")]
-    db_path: String,
+    /// Same as `chiron add-document <PATH>...`
+    #[arg(long, hide = true)]
+    add_document: Vec<PathBuf>,
 
-    /// Path to coach prompt variants TOML
-    #[arg(long, default_value = "prompts/coach.toml")]
-    coach_variants: PathBuf,
+    /// Chat options, also accepted here for `chiron` without a subcommand
+    #[command(flatten)]
+    chat: ChatArgs,
 
-    /// Path to conversation modes TOML
-    #[arg(long, default_value = "prompts/modes.toml")]
-    modes: PathBuf,
+    /// Path to SQLite database file for chat history + case notes [default: chiron.db]
+    #[arg(long, env = "CHIRON_DB_PATH")]
+    db_path: Option<String>,
+
+    /// Path to coach prompt variants TOML [default: prompts/coach.toml]
+    #[arg(long, env = "CHIRON_COACH_VARIANTS")]
+    coach_variants: Option<PathBuf>,
+
+    /// Path to conversation modes TOML [default: prompts/modes.toml]
+    #[arg(long, env = "CHIRON_MODES")]
+    modes: Option<PathBuf>,
 
     /// Coach variant ID to use (default: first variant in catalog)
-    #[arg(long)]
+    #[arg(long, env = "CHIRON_COACH_VARIANT")]
     coach_variant: Option<String>,
 
-    /// Number of GPU layers to offload (default: 99 = all)
-    #[arg(long, default_value = "99")]
-    n_gpu_layers: u32,
-
-    /// Show the model's internal <think> block reasoning after each response (default: on)
-    #[arg(long, default_value = "true", action = clap::ArgAction::Set)]
-    show_thinking: bool,
+    /// A/B test: comma-separated coach variant IDs to alternate instead of
+    /// --coach-variant (each response records its variant)
+    #[arg(long, env = "CHIRON_AB_VARIANTS", value_name = "IDS")]
+    ab: Option<String>,
+
+    /// Alternate A/B variants per turn or per session [default: session]
+    #[arg(long, value_enum, env = "CHIRON_AB_BY")]
+    ab_by: Option<AbUnit>,
+
+    /// Number of GPU layers to offload [default: 99 = all]
+    #[arg(long, env = "CHIRON_N_GPU_LAYERS")]
+    n_gpu_layers: Option<u32>,
+
+    /// Let the coach call tools: memory search, assessment suggestions, and
+    /// the mood journal [default: false]
+    #[arg(long, env = "CHIRON_TOOLS", action = clap::ArgAction::Set)]
+    tools: Option<bool>,
+
+    /// Show the model's internal <think> block reasoning after each response [default: true]
+    #[arg(long, env = "CHIRON_SHOW_THINKING", action = clap::ArgAction::Set)]
+    show_thinking: Option<bool>,
+
+    /// Language for user-facing strings, e.g. `en`, `es` [default: en]
+    #[arg(long, env = "CHIRON_LANG")]
+    lang: Option<String>,
+
+    /// Language the coach replies in, when it should differ from `--lang`,
+    /// e.g. `es` with an English interface [default: same as --lang]
+    #[arg(long, env = "CHIRON_RESPONSE_LANG")]
+    response_lang: Option<String>,
+
+    /// Time zone for displayed timestamps: `local`, `utc`, or an offset
+    /// like `+02:00` [default: local]
+    #[arg(long, env = "CHIRON_TIMEZONE")]
+    timezone: Option<String>,
+
+    /// Lock interactive chat after this many minutes without input, clearing
+    /// the screen; unlocking asks for the sync passphrase if one is set [default: 0 = never]
+    #[arg(long, env = "CHIRON_IDLE_LOCK", value_name = "MINUTES")]
+    idle_lock: Option<u64>,
+
+    /// Resuming after this many hours of silence starts a new episode,
+    /// with the previous one summarized for the coach [default: 72, 0 = never]
+    #[arg(long, env = "CHIRON_EPISODE_GAP", value_name = "HOURS")]
+    episode_gap: Option<u64>,
+
+    /// Carry crisis history from this many days of earlier sessions into
+    /// the prompt [default: 90, 0 = off]
+    #[arg(long, env = "CHIRON_RISK_HISTORY_DAYS", value_name = "DAYS")]
+    risk_history_days: Option<u32>,
+
+    /// Offer to come back to their goals after this many messages in a row
+    /// about other things [default: 3, 0 = off]
+    #[arg(long, env = "CHIRON_REFOCUS_AFTER", value_name = "MESSAGES")]
+    refocus_after: Option<u32>,
+
+    /// Propose wrapping up once a session has run this many minutes
+    /// [default: 0 = no limit]
+    #[arg(long, env = "CHIRON_SESSION_MINUTES", value_name = "MINUTES")]
+    session_minutes: Option<u32>,
+
+    /// When chat turns are written to disk: after each `message`, every
+    /// N `messages` or `seconds` (see --autosave-every), or on `exit` only.
+    /// Ending a session or quitting always saves [default: message]
+    #[arg(long, value_enum, env = "CHIRON_AUTOSAVE")]
+    autosave: Option<AutosaveMode>,
+
+    /// The N for `--autosave messages` or `seconds` [default: 4]
+    #[arg(long, env = "CHIRON_AUTOSAVE_EVERY", value_name = "N")]
+    autosave_every: Option<u64>,
 
     /// Enable verbose logging (tracing info/debug output)
     #[arg(long, short)]
     verbose: bool,
 
-    /// Path to LanceDB vector store directory
-    #[arg(long, default_value = "chiron_vectors")]
-    lance_db_path: String,
+    /// No status messages or progress bars; `sessions list` prints only IDs
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    /// Print results of `sessions list`, `stats`, `quality`, and `export` as
+    /// JSON for scripts [default: text]
+    #[arg(long, value_enum, env = "CHIRON_OUTPUT_FORMAT", global = true, default_value_t)]
+    output_format: output::OutputFormat,
+
+    /// Also write logs to this file as JSON lines, with timings and a
+    /// correlation ID per turn. Message text is redacted.
+    #[arg(long, env = "CHIRON_LOG_FILE", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Developer override: log message text verbatim instead of redacted
+    /// (length and hash). Never use with real conversations you keep.
+    #[arg(long, global = true)]
+    log_content: bool,
 
-    /// Number of conversation turns to keep in the sliding window
-    #[arg(long, default_value = "4")]
-    history_turns: usize,
+    /// Path to LanceDB vector store directory [default: chiron_vectors]
+    #[arg(long, env = "CHIRON_LANCE_DB_PATH")]
+    lance_db_path: Option<String>,
 
-    /// Total context window size in tokens (for RAG budget calculation)
-    #[arg(long, default_value = "4096")]
-    context_size: usize,
+    /// Number of conversation turns to keep in the sliding window [default: 4]
+    #[arg(long, env = "CHIRON_HISTORY_TURNS")]
+    history_turns: Option<usize>,
 
-    /// Default top-k results per RAG collection
-    #[arg(long, default_value = "3")]
-    rag_top_k: usize,
+    /// Total context window size in tokens (for RAG budget calculation) [default: 4096]
+    #[arg(long, env = "CHIRON_CONTEXT_SIZE")]
+    context_size: Option<usize>,
+
+    /// Default top-k results per RAG collection [default: 3]
+    #[arg(long, env = "CHIRON_RAG_TOP_K")]
+    rag_top_k: Option<usize>,
+
+    /// Path to MI knowledge markdown file for auto-seeding [default: data/mi_knowledge.md]
+    #[arg(long, env = "CHIRON_MI_KNOWLEDGE_PATH")]
+    mi_knowledge_path: Option<PathBuf>,
+}
+
+/// Options for interactive chat: `chiron chat [OPTIONS]`, or plain
+/// `chiron [OPTIONS]`.
+#[derive(clap::Args)]
+struct ChatArgs {
+    /// Continue a saved session instead of starting a new one: the most
+    /// recent, or the given ID (see `chiron sessions`)
+    #[arg(long, num_args = 0..=1, value_name = "SESSION_ID")]
+    resume: Option<Option<String>>,
+
+    /// Continue a named conversation (e.g. `grief`, `work`), starting it if
+    /// new. Each thread keeps its own ongoing session
+    #[arg(long, value_name = "NAME", conflicts_with = "resume")]
+    thread: Option<String>,
+
+    /// Speak instead of type: press Enter on an empty line to record, transcribed
+    /// locally with whisper.cpp (see the `[voice]` config section)
+    #[arg(long)]
+    voice: bool,
+
+    /// Read responses aloud, sentence by sentence as they stream (see the `[voice]` config section)
+    #[arg(long)]
+    speak: bool,
 
     /// Delete all user data (SQLite + vectors) and re-seed MI knowledge, then start fresh
     #[arg(long)]
     reset: bool,
+}
+
+impl Args {
+    /// Maps the flags from before subcommands existed (`--bench`,
+    /// `--script`, `--seed-knowledge`, `--add-document`) onto their
+    /// subcommands, so old invocations keep working.
+    fn resolve_legacy_flags(&mut self) {
+        if self.command.is_some() {
+            return;
+        }
+        self.command = if let Some(prompt) = self.bench.take() {
+            Some(Command::Bench { prompt })
+        } else if let Some(path) = self.script.take() {
+            Some(Command::Script { path })
+        } else if let Some(path) = self.seed_knowledge.take() {
+            Some(Command::SeedKnowledge { path })
+        } else if !self.add_document.is_empty() {
+            Some(Command::AddDocument { paths: std::mem::take(&mut self.add_document) })
+        } else {
+            None
+        };
+    }
+
+    /// The chat options in effect: `chiron chat`'s, or those given without
+    /// a subcommand.
+    fn chat(&self) -> &ChatArgs {
+        match &self.command {
+            Some(Command::Chat(chat)) => chat,
+            _ => &self.chat,
+        }
+    }
 
-    /// Path to MI knowledge markdown file for auto-seeding
-    #[arg(long, default_value = "data/mi_knowledge.md")]
-    mi_knowledge_path: PathBuf,
+    /// Overlays flags (and their `CHIRON_*` env fallbacks) onto the file config.
+    fn apply_to(&self, config: &mut Config) {
+        fn set<T: Clone>(slot: &mut T, value: &Option<T>) {
+            if let Some(v) = value {
+                *slot = v.clone();
+            }
+        }
+        set(&mut config.model.path, &self.model);
+        set(&mut config.model.n_gpu_layers, &self.n_gpu_layers);
+        set(&mut config.model.temperature, &self.temperature);
+        set(&mut config.model.max_tokens, &self.max_tokens);
+        set(&mut config.model.seed, &self.seed);
+        set(&mut config.model.context_size, &self.context_size);
+        set(&mut config.persona.coach_variant, &self.coach_variant);
+        set(&mut config.persona.coach_variants, &self.coach_variants);
+        set(&mut config.persona.ab_variants, &self.ab);
+        set(&mut config.persona.ab_by, &self.ab_by);
+        set(&mut config.persona.modes, &self.modes);
+        set(&mut config.persona.show_thinking, &self.show_thinking);
+        set(&mut config.persona.tools, &self.tools);
+        set(&mut config.persona.refocus_after, &self.refocus_after);
+        set(&mut config.persona.session_minutes, &self.session_minutes);
+        set(&mut config.persona.language, &self.lang);
+        set(&mut config.persona.response_language, &self.response_lang);
+        set(&mut config.persona.timezone, &self.timezone);
+        set(&mut config.memory.history_turns, &self.history_turns);
+        set(&mut config.memory.rag_top_k, &self.rag_top_k);
+        set(&mut config.memory.episode_gap_hours, &self.episode_gap);
+        set(&mut config.memory.risk_history_days, &self.risk_history_days);
+        set(&mut config.memory.autosave, &self.autosave);
+        set(&mut config.memory.autosave_every, &self.autosave_every);
+        set(&mut config.storage.db_path, &self.db_path);
+        set(&mut config.storage.lance_db_path, &self.lance_db_path);
+        set(&mut config.storage.mi_knowledge_path, &self.mi_knowledge_path);
+        set(&mut config.privacy.idle_lock_minutes, &self.idle_lock);
+        if self.deterministic {
+            config.model.temperature = 0.0;
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start or continue a conversation (the default without a subcommand)
+    Chat(ChatArgs),
+    /// Set up Chiron step by step: model, language and crisis resources,
+    /// storage and sync, and coach options; writes the config file
+    Init,
+    /// Check the config, model, storage, and database, and print how to fix
+    /// each problem found
+    Doctor,
+    /// Print shell completions or the man page, e.g.
+    /// `chiron completions bash > ~/.local/share/bash-completion/completions/chiron`
+    /// or `chiron completions man > chiron.1`
+    Completions {
+        #[arg(value_enum)]
+        target: completions::Target,
+    },
+    /// Inspect or edit the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Send one prompt, print the reply and how long it took
+    Bench {
+        prompt: String,
+    },
+    /// Run a scripted test conversation from a TOML file and print the
+    /// results as JSON
+    Script {
+        path: PathBuf,
+    },
+    /// Seed the MI knowledge base from a markdown file
+    SeedKnowledge {
+        path: PathBuf,
+    },
+    /// Add personal documents (markdown, text, or PDF) to the local library.
+    /// Re-adding a file replaces its previous contents
+    AddDocument {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Write user message / reply pairs as JSONL for fine-tuning (see
+    /// `chiron export --help`)
+    Export(TrainingExport),
+    /// Run an HTTP API server (sessions, messages with SSE streaming)
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to bind (use 0.0.0.0 to expose on the network)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+        /// Also serve the built-in web UI at `/`
+        #[arg(long)]
+        ui: bool,
+    },
+    /// Show or set what you agree to contribute beyond your own use:
+    /// `none`, `anonymized` (noised aggregate reports), or `full`
+    /// (conversations as training examples)
+    Consent {
+        /// New consent; shows the current one if omitted
+        #[arg(value_enum)]
+        level: Option<Consent>,
+    },
+    /// Quick daily check-in: mood, sleep, a highlight, and a stressor
+    Checkin,
+    /// Send the check-in and homework reminders that are due, as desktop
+    /// notifications (see `[reminders]` in the config)
+    Remind {
+        /// Keep running, checking every `reminders.interval_minutes`
+        #[arg(long)]
+        daemon: bool,
+    },
+    /// Take a standardized questionnaire (PHQ-9, GAD-7); lists them if none given
+    Assess {
+        /// Instrument ID, e.g. `phq9`
+        instrument: Option<String>,
+    },
+    /// Write a Markdown progress report to share with a clinician
+    Report {
+        /// Start date (YYYY-MM-DD)
+        #[arg(long)]
+        since: chrono::NaiveDate,
+        /// How much personal detail to include
+        #[arg(long, value_enum, default_value_t = report::Redaction::Standard)]
+        redact: report::Redaction,
+        /// Share aggregate numbers for research: add differential privacy
+        /// noise to every count and average, spending this total epsilon
+        /// across them (smaller is more private, e.g. 1.0). Implies
+        /// `--redact strict`
+        #[arg(long, conflicts_with = "redact")]
+        epsilon: Option<f64>,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Print a week-by-week timeline of the themes that came up, grouped by
+    /// meaning
+    Themes {
+        /// Time window, e.g. `90d` or `12w`
+        #[arg(long, default_value = "90d")]
+        period: String,
+    },
+    /// Write a closing "graduation" packet: progress, skills, warning signs,
+    /// safety plan, and maintenance schedule
+    Graduate {
+        /// Write to a file instead of stdout (`.pdf` converts with pandoc)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Show crisis resources and your safety plan right away (no model
+    /// needed)
+    Crisis,
+    /// Write or export your safety plan
+    SafetyPlan {
+        #[command(subcommand)]
+        action: SafetyPlanAction,
+    },
+    /// List, show, export, or delete saved chat sessions
+    Sessions {
+        #[command(subcommand)]
+        action: Option<SessionsAction>,
+    },
+    /// Tail a live session read-only: messages, MI stage, and warning signs
+    /// as they're saved (for a supervisor on the same machine)
+    Observe {
+        /// Session to follow; all sessions if omitted
+        session_id: Option<String>,
+    },
+    /// Step through a session's coach replies and annotate them
+    /// (appropriate, inappropriate, missed risk, better alternative); prints
+    /// review totals per prompt variant if no session is given
+    Review {
+        session_id: Option<String>,
+        /// Write the messages whose reply missed risk as a replay transcript
+        /// expecting a crisis response
+        #[arg(long)]
+        export_missed_risk: Option<PathBuf>,
+    },
+    /// View the mood log
+    Mood {
+        #[command(subcommand)]
+        action: MoodAction,
+    },
+    /// Score responses on therapeutic rubrics (empathy, non-directiveness,
+    /// no medical advice, validation); lists suites if none given
+    Eval {
+        /// Suite ID from evals/suites.toml, e.g. `empathy`
+        #[arg(long)]
+        suite: Option<String>,
+        /// Heuristics only: skip the LLM judge
+        #[arg(long)]
+        no_judge: bool,
+        /// Score saved conversations (per prompt variant) instead of
+        /// answering the suite's messages; uses the suite's rubrics
+        #[arg(long)]
+        history: bool,
+        /// Also write per-response scores and the summary as JSON
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Replay a recorded transcript through the current pipeline and report
+    /// changes in safety decisions, agent routing, and think-block tags
+    Replay {
+        /// JSONL transcript: one `{"input": ..., ...}` turn per line
+        transcript: PathBuf,
+        /// Overwrite the transcript's baseline with this run's decisions
+        #[arg(long)]
+        update: bool,
+    },
+    /// Simulate client personas against the current pipeline (mock or live
+    /// model) and write the dialogues as JSONL, marked synthetic, for
+    /// augmenting training exports
+    Synth {
+        /// TOML file of `[[persona]]` tables
+        #[arg(long, default_value = "prompts/personas.toml")]
+        personas: PathBuf,
+        /// Number of dialogues (personas are used in turn)
+        #[arg(long, default_value_t = 100)]
+        n: usize,
+        /// Exchanges per dialogue, at most
+        #[arg(long, default_value_t = 5)]
+        turns: usize,
+        /// JSONL file to write
+        #[arg(long, short, default_value = "synthetic.jsonl")]
+        output: PathBuf,
+    },
+    /// Store API tokens and passphrases in the OS keyring
+    Secrets {
+        #[command(subcommand)]
+        action: Option<SecretsAction>,
+    },
+    /// Sync chat sessions with your other devices through a WebDAV or S3
+    /// endpoint you control, end-to-end encrypted (see `[sync]` in the config)
+    Sync,
+    /// Run a Telegram bot bridge (long polling; no inbound port needed)
+    Telegram {
+        /// Bot token from @BotFather [default: the `telegram-token` secret]
+        #[arg(long, env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+        /// Chat IDs allowed to talk to the bot (comma-separated). Other chats
+        /// are refused and told their ID.
+        #[arg(long, env = "CHIRON_TELEGRAM_ALLOWED_CHATS", value_delimiter = ',')]
+        allowed_chats: Vec<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionsAction {
+    /// List sessions, most recently active first (the default)
+    List,
+    /// Name a session, so it can be referred to by name; omit the name to
+    /// remove it
+    Name {
+        session_id: String,
+        name: Option<String>,
+    },
+    /// Read a session's transcript: times, the coach's notes per turn,
+    /// warning signs, and crisis responses highlighted, in a pager
+    Show {
+        session_id: String,
+        /// Print straight to stdout instead of through `$PAGER`
+        #[arg(long)]
+        no_pager: bool,
+    },
+    /// Write sessions' messages as JSONL, one message per line
+    Export {
+        /// Session to export (omit for all sessions)
+        session_id: Option<String>,
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Delete a session with its case notes, ratings, and other records
+    Delete {
+        session_id: String,
+        /// Don't ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Rebuild the session index from saved messages, if listing looks wrong
+    RebuildIndex,
+    /// Token counts and latency per reply for a session, or across all
+    /// sessions grouped by prompt size
+    Stats {
+        /// Session ID (omit for all sessions)
+        session_id: Option<String>,
+    },
+    /// Replace transcripts of inactive sessions with a model-written
+    /// summary, keeping their metadata and progress indicators
+    Archive {
+        /// Archive sessions last active longer ago than this (e.g. 90d, 12w)
+        #[arg(long, default_value = "90d")]
+        older_than: String,
+        /// List the sessions that would be archived, without the model
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Merge session B into session A, e.g. one conversation continued
+    /// separately on two machines. A keeps its ID; B is removed
+    Merge {
+        /// Session to keep
+        a: String,
+        /// Session merged into A
+        b: String,
+    },
+    /// Replies and their 👍/👎 feedback per prompt variant
+    Quality {
+        /// Instead, score how well each session's replies follow from the
+        /// messages they answer (embeddings), flagging low-coherence sessions
+        #[arg(long)]
+        coherence: bool,
+    },
+    /// Same as `chiron export`
+    #[command(hide = true)]
+    ExportTraining(TrainingExport),
+}
+
+/// Write user message / reply pairs as JSONL for fine-tuning, filtered by
+/// feedback: chat examples, or `/retry` preference pairs for DPO. Crisis
+/// responses are left out
+#[derive(clap::Args)]
+struct TrainingExport {
+    /// JSONL file to write
+    #[arg(long, short)]
+    output: PathBuf,
+    /// Which replies to include (for `dpo`, applies to the chosen reply)
+    #[arg(long, value_enum, default_value_t = FeedbackFilter::NotBad)]
+    feedback: FeedbackFilter,
+    /// `chat`: one reply per example; `dpo`: chosen vs. rejected replies
+    /// to the same message
+    #[arg(long, value_enum, default_value_t = TrainingFormat::Chat)]
+    format: TrainingFormat,
+}
+
+/// Layout of `chiron export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TrainingFormat {
+    /// `{"messages": [user, assistant]}` per reply
+    Chat,
+    /// `{"prompt", "chosen", "rejected"}` per preference pair (TRL's
+    /// conversational preference format)
+    Dpo,
+}
+
+#[derive(Subcommand)]
+enum SecretsAction {
+    /// Known secrets and where each is set (the default)
+    List,
+    /// Store a secret; prompts without echo, or reads a line from stdin
+    Set { name: String },
+    /// Print a stored secret (keyring or environment)
+    Get { name: String },
+    /// Delete a secret from the keyring
+    Remove { name: String },
+}
+
+#[derive(Subcommand)]
+enum MoodAction {
+    /// Sparkline charts of mood, sleep, and session activity
+    Chart {
+        /// Time window, e.g. `30d` or `4w`
+        #[arg(long, default_value = "30d")]
+        period: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SafetyPlanAction {
+    /// Fill in or update the plan, section by section
+    Edit,
+    /// Print a compact wallet card: warning signs, coping steps, contacts
+    Export {
+        /// Write to a file instead of stdout (`.pdf` converts with pandoc)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective config (defaults < file < env < flags)
+    Show,
+    /// Set a value in the config file, e.g. `chiron config set model.temperature 0.5`
+    Set { key: String, value: String },
+    /// Print the config file location
+    Path,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
-
-    let default_level = if args.verbose { "info" } else { "warn" };
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level)),
-        )
-        .init();
+    let mut args = Args::parse();
+    args.resolve_legacy_flags();
+    output::init(args.output_format, args.quiet);
+    if let Some(Command::Completions { target }) = &args.command {
+        return completions::write(*target, Args::command(), &mut io::stdout());
+    }
+
+    let content = if args.log_content {
+        telemetry::ContentPolicy::Include
+    } else {
+        telemetry::ContentPolicy::Redact
+    };
+    telemetry::init(args.verbose, args.log_file.as_deref(), content)?;
     llama_cpp_2::send_logs_to_tracing(llama_cpp_2::LogOptions::default());
 
-    // Load prompt catalog
-    let coach_catalog = PromptCatalog::load(&args.coach_variants)
-        .context("Failed to load coach prompt catalog")?;
-
-    let coach_variant = match &args.coach_variant {
-        Some(id) => coach_catalog.get_variant(id)?.clone(),
-        None => coach_catalog
-            .variants
-            .first()
-            .context("Coach catalog has no variants")?
-            .clone(),
+    // Resolve layered config: defaults < file < env < CLI flags
+    let config_path = args.config.clone().unwrap_or_else(Config::default_path);
+    if let Some(Command::Doctor) = &args.command {
+        return doctor(&args, &config_path).await;
+    }
+    let mut settings = Config::load(&config_path)?;
+    match &args.command {
+        Some(Command::Config { action }) => return config_command(&args, action, settings, &config_path),
+        Some(Command::Init) => return setup::run(&config_path, settings).await,
+        _ => {}
+    }
+
+    args.apply_to(&mut settings);
+    // Replay and eval compare runs, so they sample greedily unless given a
+    // temperature
+    if matches!(args.command, Some(Command::Replay { .. } | Command::Eval { .. })) && args.temperature.is_none() {
+        settings.model.temperature = 0.0;
+    }
+    i18n::init(&settings.persona.language)?;
+    if !settings.persona.response_language.is_empty() {
+        i18n::init_response(&settings.persona.response_language)?;
+    }
+    timezone::init(&settings.persona.timezone)?;
+    guardrails::set(Guardrails::load(&Guardrails::path_for(&config_path))?);
+
+    let Some(command) = &args.command else {
+        let engine = live_engine(Coach::load(&args, &settings)?, &args, &settings, &config_path).await?;
+        return chat::run(engine, args.chat(), &settings).await;
     };
+    match command {
+        Command::Chat(chat_args) => {
+            let engine = live_engine(Coach::load(&args, &settings)?, &args, &settings, &config_path).await?;
+            chat::run(engine, chat_args, &settings).await
+        }
 
-    tracing::info!(coach = &coach_variant.id, "Selected prompt variant");
+        // Check-ins, diaries, and reports need only the database, not the model
+        Command::Checkin => checkin::run(&open_db(&settings).await?).await,
+        Command::Consent { level } => consent(&open_db(&settings).await?, *level).await,
+        Command::Remind { daemon } => remind::run(&open_db(&settings).await?, &settings.reminders, *daemon).await,
+        Command::Assess { instrument } => {
+            match instrument {
+                Some(id) => assessment::administer(id)?,
+                None => println!("{}", assessment::list()),
+            }
+            Ok(())
+        }
+        Command::Crisis => crisis(&settings).await,
+        Command::SafetyPlan { action } => safety_plan_command(&open_db(&settings).await?, action).await,
+        // Archiving needs the model for summaries
+        Command::Sessions { action: Some(SessionsAction::Archive { older_than, dry_run: false }) } => {
+            let days = chart::parse_period(older_than)?;
+            let coach = Coach::load(&args, &settings)?;
+            let model = provider::completion_model(&coach.backend, coach.generation);
+            archive::run(&open_db(&settings).await?, &model, days).await
+        }
+        Command::Sessions { action } => {
+            sessions(&open_db(&settings).await?, action.as_ref().unwrap_or(&SessionsAction::List)).await
+        }
+        Command::Export(export) => export_training(&open_db(&settings).await?, export).await,
+        Command::Observe { session_id } => observe::run(&settings.storage.db_path, session_id.as_deref()).await,
+        Command::Review { session_id, export_missed_risk } => {
+            review_command(&open_db(&settings).await?, session_id.as_deref(), export_missed_risk.as_deref()).await
+        }
+        Command::Mood { action: MoodAction::Chart { period } } => {
+            let days = chart::parse_period(period)?;
+            checkin::show_chart(&open_db(&settings).await?, days).await
+        }
+        Command::Report { since, redact, epsilon, output } => {
+            report_command(&open_db(&settings).await?, *since, *redact, *epsilon, output.as_deref()).await
+        }
+        Command::Themes { period } => {
+            let days = chart::parse_period(period)?;
+            let chat_conn = open_db(&settings).await?;
+            let embedding_model = memory::embeddings::init_embedding_model();
+            themes::run(&chat_conn, &embedding_model, days, timezone::today()).await
+        }
+        Command::Graduate { output } => graduate(&open_db(&settings).await?, output.as_deref()).await,
+        Command::Secrets { action } => secrets_command(action.as_ref().unwrap_or(&SecretsAction::List)),
+        Command::Sync => sync_command(&open_db(&settings).await?, &settings).await,
+        Command::SeedKnowledge { path } => seed_knowledge(&settings, path).await,
+        Command::AddDocument { paths } => add_documents(&settings, paths).await,
+        Command::Eval { suite: None, .. } => {
+            println!("{}", eval::list());
+            Ok(())
+        }
+        // Checked before loading the model rather than after
+        Command::Eval { suite: Some(id), .. } if EvalCatalog::builtin().suite(id).is_none() => {
+            anyhow::bail!("Unknown suite '{id}'. Available:\n{}", eval::list());
+        }
 
-    // Load mode catalog (optional — degrades gracefully if missing)
-    let mode_catalog = ModeCatalog::load(&args.modes).ok();
-    if mode_catalog.is_some() {
-        tracing::info!("Loaded conversation modes from {}", args.modes.display());
+        // The rest run the coach
+        Command::Bench { prompt } => bench(&Coach::load(&args, &settings)?, prompt).await,
+        Command::Script { path } => script(&Coach::load(&args, &settings)?, &settings, path).await,
+        Command::Replay { transcript, update } => {
+            let engine = Coach::load(&args, &settings)?.scratch_engine(&settings).await?;
+            replay_command(&engine, transcript, *update).await
+        }
+        Command::Eval { suite: Some(id), no_judge, history, output } => {
+            let coach = Coach::load(&args, &settings)?;
+            let suite = EvalCatalog::builtin().suite(id).context("Unknown suite")?;
+            let exchanges = if *history {
+                eval::history(&open_db(&settings).await?).await?
+            } else {
+                eval::generate(&coach.scratch_engine(&settings).await?, suite).await?
+            };
+            let judge_model = provider::completion_model(&coach.backend, coach.generation.clone());
+            eval_command(exchanges, suite, (!no_judge).then_some(&judge_model), *history, output.as_deref()).await
+        }
+        Command::Synth { personas, n, turns, output } => {
+            let coach = Coach::load(&args, &settings)?;
+            let engine = coach.scratch_engine(&settings).await?;
+            let catalog = synth::PersonaCatalog::load(personas)?;
+            let client_model = provider::completion_model(&coach.backend, coach.generation.clone());
+            let dialogues = synth::run(&engine, &client_model, &catalog.personas, *n, *turns).await?;
+            let jsonl: String = dialogues.iter().map(|d| format!("{}\n", d.to_json())).collect();
+            std::fs::write(output, jsonl).with_context(|| format!("Failed to write {}", output.display()))?;
+            println!("Wrote {} synthetic dialogues to {}", dialogues.len(), output.display());
+            Ok(())
+        }
+        Command::Serve { port, host, ui } => {
+            let engine = live_engine(Coach::load(&args, &settings)?, &args, &settings, &config_path).await?;
+            let addr = std::net::SocketAddr::new(*host, *port);
+            println!("Chiron API listening on http://{addr} (coach: {})", engine.coach_variant.id);
+            if *ui {
+                println!("Web UI: http://{addr}/");
+            }
+            server::serve(engine, addr, *ui).await
+        }
+        Command::Telegram { token, allowed_chats } => {
+            // Fail before the model loads rather than after
+            let token = match token {
+                Some(token) => token.clone(),
+                None => secrets::require("telegram-token")?,
+            };
+            let engine = live_engine(Coach::load(&args, &settings)?, &args, &settings, &config_path).await?;
+            if allowed_chats.is_empty() {
+                println!("No allowed chats configured; message the bot to learn your chat ID.");
+            }
+            println!("Chiron Telegram bridge running (coach: {})", engine.coach_variant.id);
+            bridges::telegram::TelegramBridge::new(&token, allowed_chats.clone(), engine)
+                .run()
+                .await
+        }
+        Command::Completions { .. } | Command::Doctor | Command::Config { .. } | Command::Init => {
+            unreachable!("handled above")
+        }
     }
+}
 
-    // Resolve model path (symlinks)
-    let model_path = args.model.canonicalize().with_context(|| {
-        format!("Model file not found: {}", args.model.display())
-    })?;
+/// The SQLite store, for commands that need nothing else.
+async fn open_db(settings: &Config) -> Result<tokio_rusqlite::Connection> {
+    Ok(memory::open_memory(&settings.storage.db_path).await?)
+}
 
-    // Initialize llama.cpp provider
-    let provider = Arc::new(Mutex::new(
-        LlamaCppProvider::new(&model_path, args.n_gpu_layers)
-            .context("Failed to initialize llama.cpp provider")?,
-    ));
+/// `chiron doctor`. A config that doesn't load is one of the things to
+/// diagnose, so it runs on the defaults instead.
+async fn doctor(args: &Args, config_path: &std::path::Path) -> Result<()> {
+    let (mut settings, config_error) = match Config::load(config_path) {
+        Ok(settings) => (settings, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+    args.apply_to(&mut settings);
+    doctor::run(config_path, &settings, config_error).await
+}
+
+/// `chiron config show|set|path`.
+fn config_command(args: &Args, action: &ConfigAction, mut settings: Config, config_path: &std::path::Path) -> Result<()> {
+    match action {
+        ConfigAction::Show => {
+            args.apply_to(&mut settings);
+            print!("{}", settings.to_toml()?);
+        }
+        ConfigAction::Set { key, value } => {
+            settings.set(key, value)?;
+            settings.save(config_path)?;
+            println!("Set {key} = {value} in {}", config_path.display());
+        }
+        ConfigAction::Path => println!("{}", config_path.display()),
+    }
+    Ok(())
+}
 
-    let config = GenerationConfig {
-        temperature: args.temperature,
-        max_tokens: args.max_tokens,
-        ..Default::default()
+/// `chiron consent [<level>]`: sets the consent level if given, then shows it.
+async fn consent(conn: &tokio_rusqlite::Connection, level: Option<Consent>) -> Result<()> {
+    if let Some(level) = level {
+        memory::consent::save(conn, level).await?;
+    }
+    let consent = memory::consent::load(conn).await?;
+    let meaning = match consent {
+        Consent::None => "nothing is exported for training or research",
+        Consent::Anonymized => "noised aggregate reports (`report --epsilon`) only, never conversation text",
+        Consent::Full => "conversations may be exported as training examples (`chiron export`)",
     };
+    println!("Consent: {} ({meaning})", consent.as_str());
+    Ok(())
+}
 
-    // Bench mode: single prompt, no DB, print timing, exit
-    if let Some(prompt) = args.bench {
-        let completion_model = crate::provider::completion_model(&provider, config.clone());
-        let agent = build_peer_coach(
-            completion_model,
-            &coach_variant.preamble,
-            coach_variant.temperature,
-            coach_variant.max_tokens,
-        );
+/// `chiron crisis`: resources and the safety plan, or the resources alone
+/// if the database can't be opened.
+async fn crisis(settings: &Config) -> Result<()> {
+    match memory::open_memory(&settings.storage.db_path).await {
+        Ok(chat_conn) => println!("{}", safety_plan::break_glass(&chat_conn).await),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to open the database for chiron crisis");
+            println!("{}", router::crisis_response());
+        }
+    }
+    Ok(())
+}
 
-        println!("=== Benchmark Mode ===");
-        println!("Coach variant: {}", coach_variant.id);
-        println!("Prompt: {prompt}");
-        println!("---");
+/// `chiron safety-plan edit|export`.
+async fn safety_plan_command(conn: &tokio_rusqlite::Connection, action: &SafetyPlanAction) -> Result<()> {
+    match action {
+        SafetyPlanAction::Edit => safety_plan::edit(conn).await?,
+        SafetyPlanAction::Export { output } => {
+            let Some(card) = safety_plan::card(conn).await? else {
+                println!("{}", t("safety_plan.empty"));
+                return Ok(());
+            };
+            match output {
+                Some(path) => {
+                    safety_plan::export(&card, path)?;
+                    println!("Wallet card written to {}", path.display());
+                }
+                None => print!("{card}"),
+            }
+        }
+    }
+    Ok(())
+}
 
-        let t_start = Instant::now();
-        let response = agent
-            .chat(&prompt, vec![])
-            .await
-            .context("Benchmark inference failed")?;
-        let total = t_start.elapsed();
+/// `chiron sessions ...`, except archiving, which needs the model.
+async fn sessions(chat_conn: &tokio_rusqlite::Connection, action: &SessionsAction) -> Result<()> {
+    match action {
+        SessionsAction::List => list_sessions(chat_conn).await?,
+        SessionsAction::Show { session_id, no_pager } => {
+            let session_id = find_session(chat_conn, session_id).await?;
+            transcript::show(chat_conn, &session_id, !no_pager).await?;
+        }
+        SessionsAction::Export { session_id, output } => {
+            let ids = match session_id {
+                Some(id) => vec![find_session(chat_conn, id).await?],
+                None => memory::list_sessions(chat_conn).await?.into_iter().map(|s| s.session_id).collect(),
+            };
+            let mut jsonl = String::new();
+            for id in &ids {
+                for turn in memory::load_session_turns(chat_conn, id).await? {
+                    let mut line = serde_json::to_value(&turn)?;
+                    line["session_id"] = serde_json::json!(id);
+                    jsonl.push_str(&format!("{line}\n"));
+                }
+            }
+            match output {
+                Some(path) => {
+                    std::fs::write(path, jsonl).with_context(|| format!("Failed to write {}", path.display()))?;
+                    output::status(format!("Wrote {} sessions to {}", ids.len(), path.display()));
+                }
+                None => print!("{jsonl}"),
+            }
+        }
+        SessionsAction::Delete { session_id, yes } => {
+            let session_id = &find_session(chat_conn, session_id).await?;
+            let confirmed = *yes
+                || matches!(
+                    checkin::ask(&format!("Delete session {session_id} and everything recorded with it? [y/N]"))?
+                        .to_lowercase()
+                        .as_str(),
+                    "y" | "yes"
+                );
+            if !confirmed {
+                println!("Nothing deleted.");
+                return Ok(());
+            }
+            match memory::delete_session(chat_conn, session_id).await? {
+                Some(messages) => println!("Deleted session {session_id} ({messages} messages)"),
+                None => anyhow::bail!("No session {session_id} (see `chiron sessions`)"),
+            }
+        }
+        SessionsAction::Name { session_id, name } => {
+            let session_id = find_session(chat_conn, session_id).await?;
+            match name {
+                Some(name) => {
+                    if !memory::session_names::set_session_name(chat_conn, &session_id, name).await? {
+                        anyhow::bail!("Another session is already named '{name}'");
+                    }
+                    println!("Named session {session_id} '{name}'");
+                }
+                None if memory::session_names::clear_session_name(chat_conn, &session_id).await? => {
+                    println!("Removed the name of session {session_id}");
+                }
+                None => println!("Session {session_id} has no name"),
+            }
+        }
+        SessionsAction::RebuildIndex => {
+            let count = memory::rebuild_session_index(chat_conn).await?;
+            println!("Indexed {count} sessions");
+        }
+        SessionsAction::Stats { session_id: Some(session_id) } => {
+            let session_id = &find_session(chat_conn, session_id).await?;
+            let turns = memory::usage::session_usage(chat_conn, session_id).await?;
+            if output::json() {
+                output::print_json(&turns)?;
+            } else {
+                print!("{}", stats::render_session(session_id, &turns));
+            }
+        }
+        SessionsAction::Stats { session_id: None } => {
+            let summary = memory::usage::usage_summary(chat_conn, stats::BUCKET_TOKENS).await?;
+            if output::json() {
+                output::print_json(&summary)?;
+            } else {
+                print!("{}", stats::render_summary(&summary));
+            }
+        }
+        SessionsAction::Archive { older_than, .. } => {
+            archive::preview(chat_conn, chart::parse_period(older_than)?).await?;
+        }
+        SessionsAction::Quality { coherence: true } => {
+            let embedding_model = memory::embeddings::init_embedding_model();
+            coherence::run(chat_conn, &embedding_model).await?;
+        }
+        SessionsAction::Quality { coherence: false } => {
+            let quality = memory::feedback::response_quality(chat_conn).await?;
+            if output::json() {
+                output::print_json(&quality)?;
+                return Ok(());
+            }
+            if quality.is_empty() {
+                output::status("No replies saved yet.");
+            }
+            for q in quality {
+                println!(
+                    "{:<24} {:>6} replies  {:>4} good  {:>4} bad",
+                    q.prompt_variant.as_deref().unwrap_or("(crisis response)"),
+                    q.replies,
+                    q.good,
+                    q.bad
+                );
+            }
+        }
+        SessionsAction::ExportTraining(export) => export_training(chat_conn, export).await?,
+        SessionsAction::Merge { a, b } => {
+            let (a, b) = (&find_session(chat_conn, a).await?, &find_session(chat_conn, b).await?);
+            let Some(merge) = memory::merge::merge_sessions(chat_conn, a, b).await? else {
+                anyhow::bail!("Can't merge: both sessions must exist, differ, and not be archived");
+            };
+            println!(
+                "Merged {b} into {a}: {} messages moved, {} duplicates dropped, {} session records moved",
+                merge.moved, merge.duplicates, merge.records
+            );
+        }
+    }
+    Ok(())
+}
 
-        println!("\nChiron: {response}");
-        println!("\n=== Benchmark Results ===");
-        println!("Total agent.chat() time: {}ms", total.as_millis());
-        return Ok(());
+/// `chiron sessions`: every session with its name, thread, and dates.
+async fn list_sessions(chat_conn: &tokio_rusqlite::Connection) -> Result<()> {
+    let threads = memory::threads::list_threads(chat_conn).await?;
+    let names = memory::session_names::session_names(chat_conn).await?;
+    let sessions = memory::list_sessions(chat_conn).await?;
+    let thread_of = |session_id: &str| threads.iter().find(|t| t.session_id == session_id).map(|t| t.name.clone());
+    if output::json() {
+        let mut rows = Vec::new();
+        for s in &sessions {
+            let mut row = serde_json::to_value(s)?;
+            row["short_id"] = memory::session_names::short_id(&s.session_id).into();
+            row["name"] = names.get(&s.session_id).cloned().into();
+            row["thread"] = thread_of(&s.session_id).into();
+            rows.push(row);
+        }
+        return output::print_json(&rows);
     }
+    for s in sessions {
+        if output::quiet() {
+            println!("{}", s.session_id);
+            continue;
+        }
+        let thread = thread_of(&s.session_id).map(|name| format!("  [thread: {name}]")).unwrap_or_default();
+        println!(
+            "{:<8}  {:<20} {:>5} messages  {} – {}{}{thread}",
+            memory::session_names::short_id(&s.session_id),
+            names.get(&s.session_id).map_or("", String::as_str),
+            s.message_count,
+            timezone::datetime(&s.started_at),
+            timezone::datetime(&s.last_active_at),
+            if s.archived { "  (archived)" } else { "" }
+        );
+    }
+    Ok(())
+}
 
-    // --- Seed knowledge mode: parse markdown, embed, store, exit ---
-    if let Some(knowledge_path) = &args.seed_knowledge {
-        let content = std::fs::read_to_string(knowledge_path)
-            .with_context(|| format!("Failed to read: {}", knowledge_path.display()))?;
+/// `chiron review [<session>] [--export-missed-risk <path>]`.
+async fn review_command(
+    chat_conn: &tokio_rusqlite::Connection,
+    session_id: Option<&str>,
+    export_missed_risk: Option<&std::path::Path>,
+) -> Result<()> {
+    if let Some(path) = export_missed_risk {
+        let count = review::export_missed_risk(chat_conn, path).await?;
+        println!("Wrote {count} missed-risk turns to {}", path.display());
+    }
+    match session_id {
+        Some(session_id) => review::run(chat_conn, &find_session(chat_conn, session_id).await?).await?,
+        None if export_missed_risk.is_none() => print!("{}", review::summary(chat_conn).await?),
+        None => {}
+    }
+    Ok(())
+}
 
-        let entries = memory::seed::parse_markdown(&content);
-        if entries.is_empty() {
-            eprintln!("No knowledge entries found in {}", knowledge_path.display());
-            return Ok(());
+/// `chiron report`: a progress report, noised for research with `--epsilon`.
+async fn report_command(
+    chat_conn: &tokio_rusqlite::Connection,
+    since: chrono::NaiveDate,
+    redact: report::Redaction,
+    epsilon: Option<f64>,
+    output: Option<&std::path::Path>,
+) -> Result<()> {
+    let mut data = report::gather(chat_conn, since).await?;
+    let redact = match epsilon {
+        Some(epsilon) => {
+            let consent = memory::consent::load(chat_conn).await?;
+            if !consent.allows_aggregates() {
+                anyhow::bail!(
+                    "Research aggregates need consent (currently `{}`); run `chiron consent anonymized` to give it",
+                    consent.as_str()
+                );
+            }
+            data.add_noise(epsilon, timezone::today())?;
+            report::Redaction::Strict
+        }
+        None => redact,
+    };
+    let markdown = report::render_markdown(&data, redact, timezone::today());
+    match output {
+        Some(path) => {
+            std::fs::write(path, markdown).with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Report written to {}", path.display());
         }
+        None => print!("{markdown}"),
+    }
+    Ok(())
+}
 
-        println!("Parsed {} knowledge entries from {}", entries.len(), knowledge_path.display());
+/// `chiron graduate`: the end-of-program packet.
+async fn graduate(chat_conn: &tokio_rusqlite::Connection, output: Option<&std::path::Path>) -> Result<()> {
+    let data = graduation::gather(chat_conn).await?;
+    let markdown = graduation::render_markdown(&data, timezone::today());
+    match output {
+        Some(path) => {
+            graduation::export(&markdown, path)?;
+            println!("Graduation packet written to {}", path.display());
+        }
+        None => print!("{markdown}"),
+    }
+    Ok(())
+}
 
-        let vector_conn = memory::vectors::open_vector_db(&args.lance_db_path).await?;
-        memory::vectors::ensure_tables(&vector_conn).await?;
-        let embedding_model = memory::embeddings::init_embedding_model();
+/// `chiron secrets list|set|get|remove`.
+fn secrets_command(action: &SecretsAction) -> Result<()> {
+    match action {
+        SecretsAction::List => print!("{}", secrets::status()),
+        SecretsAction::Set { name } => {
+            let value = secrets::read_value(name)?;
+            secrets::set(name, &value)?;
+            println!("Stored {name} in the OS keyring");
+        }
+        SecretsAction::Get { name } => match secrets::get(name)? {
+            Some((value, _)) => println!("{value}"),
+            None => anyhow::bail!("{name} isn't set"),
+        },
+        SecretsAction::Remove { name } => {
+            if secrets::remove(name)? {
+                println!("Removed {name} from the OS keyring");
+            } else {
+                println!("{name} wasn't in the OS keyring");
+            }
+        }
+    }
+    Ok(())
+}
 
-        let source = knowledge_path
-            .file_name()
-            .map(|f| f.to_string_lossy().to_string())
-            .unwrap_or_else(|| "unknown".to_string());
+/// `chiron sync`.
+async fn sync_command(chat_conn: &tokio_rusqlite::Connection, settings: &Config) -> Result<()> {
+    let report = sync::run(chat_conn, &settings.sync).await?;
+    println!(
+        "Merged {} session copies from other devices ({} new messages, {} updated); pushed {} sessions.",
+        report.pulled, report.merged.inserted, report.merged.updated, report.pushed
+    );
+    Ok(())
+}
 
-        let count = memory::seed::seed_knowledge(&vector_conn, &embedding_model, &entries, &source).await?;
-        println!("Seeded {count} entries into mi_knowledge table");
+/// `chiron seed-knowledge`: parses MI knowledge from markdown, embeds, and
+/// stores it.
+async fn seed_knowledge(settings: &Config, knowledge_path: &std::path::Path) -> Result<()> {
+    let content = std::fs::read_to_string(knowledge_path)
+        .with_context(|| format!("Failed to read: {}", knowledge_path.display()))?;
 
+    let entries = memory::seed::parse_markdown(&content);
+    if entries.is_empty() {
+        eprintln!("No knowledge entries found in {}", knowledge_path.display());
         return Ok(());
     }
 
-    // --- Script mode: run test conversation, output JSON ---
-    if let Some(script_path) = &args.script {
-        let script_content = std::fs::read_to_string(script_path)
-            .with_context(|| format!("Failed to read script: {}", script_path.display()))?;
-        let script: TestScript = toml::from_str(&script_content)
-            .with_context(|| format!("Failed to parse script: {}", script_path.display()))?;
+    println!("Parsed {} knowledge entries from {}", entries.len(), knowledge_path.display());
 
-        let db_path = format!(":memory:"); // In-memory DB for scripted runs
-        let chat_conn = memory::open_memory(&db_path).await?;
-        let completion_model = crate::provider::completion_model(&provider, config);
+    let vector_conn = memory::vectors::open_vector_db(&settings.storage.lance_db_path).await?;
+    memory::vectors::ensure_tables(&vector_conn).await?;
+    let embedding_model = memory::embeddings::init_embedding_model();
 
-        let session_id = format!("script_{}", script.id);
-        let mut orchestrator = Orchestrator::new(
-            completion_model,
-            coach_variant.clone(),
-            coach_catalog.think_instructions.clone(),
-            mode_catalog,
-            session_id,
-            chat_conn,
-            true, // always show thinking in script mode
-            args.history_turns,
-            None, // no vector store in script mode (for now)
-            None, // no embedding model in script mode (for now)
-            args.rag_top_k,
-        );
-        orchestrator.set_output_to_stderr(true);
-
-        eprintln!("=== Script Mode: {} ===", script.id);
-        eprintln!("Description: {}", script.description);
-        eprintln!("Coach: {}", coach_variant.id);
-        eprintln!("Turns: {}", script.turns.len());
-        eprintln!("---");
-
-        let run_start = Instant::now();
-        let mut turn_results = Vec::new();
-
-        for (i, turn) in script.turns.iter().enumerate() {
-            eprintln!("\n--- Turn {} ---", i + 1);
-            eprintln!("Input: {}", turn.input);
-            if let Some(ref mode) = turn.expected_mode {
-                eprintln!("Expected mode: {mode}");
-            }
-            eprintln!("Notes: {}", turn.notes);
+    let source = knowledge_path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
 
-            let result = orchestrator
-                .run_turn_captured(&turn.input)
-                .await
-                .with_context(|| format!("Turn {} failed", i + 1))?;
-
-            eprintln!("Case notes: {}", result.case_notes.as_deref().unwrap_or("none"));
-
-            turn_results.push(serde_json::json!({
-                "turn_number": result.turn_number,
-                "input": result.input,
-                "response": result.response,
-                "think_content": result.think_content,
-                "case_notes": result.case_notes,
-                "preamble_len": result.preamble_injected.len(),
-                "expected_mode": turn.expected_mode,
-                "script_notes": turn.notes,
-                "duration_ms": result.duration_ms,
-            }));
-        }
-
-        let output = serde_json::json!({
-            "script_id": script.id,
-            "description": script.description,
-            "coach_variant": coach_variant.id,
-            "total_duration_ms": run_start.elapsed().as_millis() as u64,
-            "turns": turn_results,
-        });
-
-        // Write JSON to stdout (eprintln used for progress above)
-        println!("{}", serde_json::to_string_pretty(&output)?);
+    let count = memory::seed::seed_knowledge(&vector_conn, &embedding_model, &entries, &source).await?;
+    println!("Seeded {count} entries into mi_knowledge table");
+    Ok(())
+}
 
-        return Ok(());
+/// `chiron add-document`: chunks, embeds, and stores documents for retrieval.
+async fn add_documents(settings: &Config, paths: &[PathBuf]) -> Result<()> {
+    let vector_conn = memory::vectors::open_vector_db(&settings.storage.lance_db_path).await?;
+    memory::vectors::ensure_tables(&vector_conn).await?;
+    let embedding_model = memory::embeddings::init_embedding_model();
+
+    for path in paths {
+        let count = memory::documents::ingest_document(&vector_conn, &embedding_model, path).await?;
+        println!("Added {} ({count} chunks)", path.display());
     }
+    Ok(())
+}
+
+/// What the commands that run the coach share: the model (or mock), its
+/// generation settings, and the prompt catalogs.
+struct Coach {
+    backend: Backend,
+    generation: GenerationConfig,
+    variant: PromptVariant,
+    ab_test: Option<AbTest>,
+    think_instructions: Option<String>,
+    mode_catalog: Option<ModeCatalog>,
+    modality_catalog: Option<ModalityCatalog>,
+    /// Recorded with each reply, and labels eval reports
+    model_name: String,
+}
+
+impl Coach {
+    /// Loads the prompt catalogs and the model, or the mock with `--mock`.
+    fn load(args: &Args, settings: &Config) -> Result<Self> {
+        let coach_catalog = PromptCatalog::load(&settings.persona.coach_variants)
+            .context("Failed to load coach prompt catalog")?;
+
+        let variant = match settings.persona.coach_variant.as_str() {
+            "" => coach_catalog
+                .variants
+                .first()
+                .context("Coach catalog has no variants")?
+                .clone(),
+            id => coach_catalog.get_variant(id)?.clone(),
+        };
+
+        tracing::info!(coach = &variant.id, "Selected prompt variant");
+
+        let ab_test = coach_catalog.ab_test(&settings.persona.ab_variants, settings.persona.ab_by)?;
+        if let Some(ab_test) = &ab_test {
+            let ids: Vec<&str> = ab_test.variants.iter().map(|v| v.id.as_str()).collect();
+            tracing::info!(variants = ids.join(", "), by = ?ab_test.unit, "A/B test active");
+        }
 
-    // --- Interactive mode ---
+        // Load mode catalog (optional — degrades gracefully if missing)
+        let mode_catalog = ModeCatalog::load(&settings.persona.modes).ok();
+        if mode_catalog.is_some() {
+            tracing::info!("Loaded conversation modes from {}", settings.persona.modes.display());
+        }
 
+        // Modality catalog is optional too — without it `/modality` is unavailable
+        let modality_catalog = ModalityCatalog::load(&settings.persona.modalities).ok();
+
+        let model_name = match &args.mock {
+            Some(scenario) => format!("mock:{scenario}"),
+            None => settings.model.path.display().to_string(),
+        };
+        let backend = match &args.mock {
+            Some(scenario) => {
+                let mock = MockBackend::load(scenario)?;
+                tracing::info!(scenario = mock.scenario(), "Using mock backend");
+                Backend::Mock(Arc::new(mock))
+            }
+            None => {
+                // Resolve model path (symlinks)
+                let model_path = settings.model.path.canonicalize().with_context(|| {
+                    format!("Model file not found: {}", settings.model.path.display())
+                })?;
+
+                // Initialize llama.cpp provider
+                Backend::LlamaCpp(Arc::new(Mutex::new(
+                    LlamaCppProvider::new(&model_path, settings.model.n_gpu_layers)
+                        .context("Failed to initialize llama.cpp provider")?,
+                )))
+            }
+        };
+
+        let generation = GenerationConfig {
+            temperature: settings.model.temperature,
+            max_tokens: settings.model.max_tokens,
+            seed: Some(settings.model.seed),
+            ..Default::default()
+        };
+
+        Ok(Self {
+            backend,
+            generation,
+            variant,
+            ab_test,
+            think_instructions: coach_catalog.think_instructions,
+            mode_catalog,
+            modality_catalog,
+            model_name,
+        })
+    }
+
+    /// An engine on a scratch in-memory database without the vector store,
+    /// for runs whose sessions aren't kept (replay, eval, synth, scripts).
+    async fn scratch_engine(&self, settings: &Config) -> Result<ChironEngine> {
+        Ok(ChironEngine {
+            backend: self.backend.clone(),
+            generation: self.generation.clone(),
+            coach_variant: self.variant.clone(),
+            ab_test: self.ab_test.clone(),
+            think_instructions: self.think_instructions.clone(),
+            mode_catalog: self.mode_catalog.clone(),
+            modality_catalog: self.modality_catalog.clone(),
+            relax_catalog: None,
+            chat_conn: memory::open_memory(":memory:").await?,
+            vector_conn: None,
+            embedding_model: None,
+            show_thinking: false,
+            history_turns: settings.memory.history_turns,
+            rag_top_k: settings.memory.rag_top_k,
+            // Scratch sessions share one database; keep them independent
+            risk_history_days: 0,
+            refocus_after: 0,
+            session_minutes: 0,
+            autosave: Autosave::default(),
+            model: self.model_name.clone(),
+            tools: settings.persona.tools,
+            toggles: None,
+            sessions: Default::default(),
+        })
+    }
+}
+
+/// The engine for chat, `serve`, and `telegram`: the user's database and
+/// vector store (wiped first with `--reset`, MI knowledge seeded if empty),
+/// following edits to `config.toml`.
+async fn live_engine(coach: Coach, args: &Args, settings: &Config, config_path: &std::path::Path) -> Result<Arc<ChironEngine>> {
     // Handle --reset: wipe user data and start fresh
-    if args.reset {
-        if std::path::Path::new(&args.db_path).exists() {
-            std::fs::remove_file(&args.db_path)
-                .with_context(|| format!("Failed to remove {}", args.db_path))?;
-            println!("Removed {}", args.db_path);
+    if args.chat().reset {
+        if std::path::Path::new(&settings.storage.db_path).exists() {
+            std::fs::remove_file(&settings.storage.db_path)
+                .with_context(|| format!("Failed to remove {}", settings.storage.db_path))?;
+            println!("Removed {}", settings.storage.db_path);
         }
-        if std::path::Path::new(&args.lance_db_path).exists() {
-            std::fs::remove_dir_all(&args.lance_db_path)
-                .with_context(|| format!("Failed to remove {}", args.lance_db_path))?;
-            println!("Removed {}", args.lance_db_path);
+        if std::path::Path::new(&settings.storage.lance_db_path).exists() {
+            std::fs::remove_dir_all(&settings.storage.lance_db_path)
+                .with_context(|| format!("Failed to remove {}", settings.storage.lance_db_path))?;
+            println!("Removed {}", settings.storage.lance_db_path);
         }
     }
 
     // Initialize vector store + embedding model
-    let vector_conn = memory::vectors::open_vector_db(&args.lance_db_path).await?;
+    let vector_conn = memory::vectors::open_vector_db(&settings.storage.lance_db_path).await?;
     memory::vectors::ensure_tables(&vector_conn).await?;
     let embedding_model = memory::embeddings::init_embedding_model();
 
     // Auto-seed MI knowledge if table is empty and seed file exists
-    {
-        let table = vector_conn.open_table("mi_knowledge").execute().await?;
-        let count = table.count_rows(None).await?;
-        if count == 0 && args.mi_knowledge_path.exists() {
-            let content = std::fs::read_to_string(&args.mi_knowledge_path)
-                .with_context(|| format!("Failed to read {}", args.mi_knowledge_path.display()))?;
-            let entries = memory::seed::parse_markdown(&content);
-            if !entries.is_empty() {
-                let source = args.mi_knowledge_path
-                    .file_name()
-                    .map(|f| f.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-                let seeded = memory::seed::seed_knowledge(&vector_conn, &embedding_model, &entries, &source).await?;
-                println!("Auto-seeded {seeded} MI knowledge entries from {}", args.mi_knowledge_path.display());
-            }
+    let table = vector_conn.open_table("mi_knowledge").execute().await?;
+    if table.count_rows(None).await? == 0 && settings.storage.mi_knowledge_path.exists() {
+        let content = std::fs::read_to_string(&settings.storage.mi_knowledge_path)
+            .with_context(|| format!("Failed to read {}", settings.storage.mi_knowledge_path.display()))?;
+        let entries = memory::seed::parse_markdown(&content);
+        if !entries.is_empty() {
+            let source = settings.storage.mi_knowledge_path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let seeded = memory::seed::seed_knowledge(&vector_conn, &embedding_model, &entries, &source).await?;
+            println!("Auto-seeded {seeded} MI knowledge entries from {}", settings.storage.mi_knowledge_path.display());
         }
     }
 
-    let chat_conn = memory::open_memory(&args.db_path).await?;
-
-    let completion_model = crate::provider::completion_model(&provider, config);
+    // Long-running modes follow edits to config.toml and guardrails.toml
+    let (toggles_tx, toggles) = watch::channel(reload::AgentToggles::from_config(settings));
+    let (tools_flag, show_thinking_flag) = (args.tools, args.show_thinking);
+    reload::spawn(config_path, toggles_tx, move |toggles| {
+        // CLI flags keep winning over the file
+        if let Some(tools) = tools_flag {
+            toggles.tools = tools;
+        }
+        if let Some(show_thinking) = show_thinking_flag {
+            toggles.show_thinking = show_thinking;
+        }
+    })?;
 
-    // Generate session ID
-    let session_id = format!(
-        "session_{}",
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
-            .as_secs()
-    );
-    tracing::info!(session_id, "Starting interactive session");
+    Ok(Arc::new(ChironEngine {
+        backend: coach.backend,
+        generation: coach.generation,
+        coach_variant: coach.variant,
+        ab_test: coach.ab_test,
+        think_instructions: coach.think_instructions,
+        mode_catalog: coach.mode_catalog,
+        modality_catalog: coach.modality_catalog,
+        // Relaxation scripts are optional
+        relax_catalog: RelaxationCatalog::load(&settings.persona.relaxation).ok(),
+        chat_conn: memory::open_memory(&settings.storage.db_path).await?,
+        vector_conn: Some(vector_conn),
+        embedding_model: Some(embedding_model),
+        show_thinking: settings.persona.show_thinking,
+        history_turns: settings.memory.history_turns,
+        rag_top_k: settings.memory.rag_top_k,
+        risk_history_days: settings.memory.risk_history_days,
+        refocus_after: settings.persona.refocus_after,
+        session_minutes: settings.persona.session_minutes,
+        autosave: Autosave { mode: settings.memory.autosave, every: settings.memory.autosave_every },
+        model: coach.model_name,
+        tools: settings.persona.tools,
+        toggles: Some(toggles),
+        sessions: Default::default(),
+    }))
+}
 
-    let mut orchestrator = Orchestrator::new(
+/// Bench mode: a single prompt with no database, timed.
+async fn bench(coach: &Coach, prompt: &str) -> Result<()> {
+    let completion_model = provider::completion_model(&coach.backend, coach.generation.clone());
+    let agent = build_peer_coach(
         completion_model,
-        coach_variant.clone(),
-        coach_catalog.think_instructions.clone(),
-        mode_catalog,
-        session_id,
-        chat_conn,
-        args.show_thinking,
-        args.history_turns,
-        Some(vector_conn),
-        Some(embedding_model),
-        args.rag_top_k,
+        &coach.variant.preamble,
+        coach.variant.temperature,
+        coach.variant.max_tokens,
     );
 
-    println!("Chiron MI Peer Support (Plotinus V19 + llama.cpp)");
-    println!("Coach: {}", coach_variant.id);
-    println!("Type your message, or 'quit' to exit. 'reset' clears conversation.");
+    println!("=== Benchmark Mode ===");
+    println!("Coach variant: {}", coach.variant.id);
+    println!("Prompt: {prompt}");
     println!("---");
 
-    // Chat loop
-    loop {
-        print!("\nYou: ");
-        io::stdout().flush()?;
+    let t_start = Instant::now();
+    let response = agent
+        .chat(prompt, vec![])
+        .await
+        .context("Benchmark inference failed")?;
+    let total = t_start.elapsed();
 
-        let mut input = String::new();
-        let bytes_read = io::stdin()
-            .read_line(&mut input)
-            .context("Failed to read input")?;
-
-        if bytes_read == 0 {
-            println!("Take care of yourself. Goodbye.");
-            break;
-        }
-
-        let input = input.trim();
+    println!("\nChiron: {response}");
+    println!("\n=== Benchmark Results ===");
+    println!("Total agent.chat() time: {}ms", total.as_millis());
+    Ok(())
+}
 
-        if input.is_empty() {
-            continue;
-        }
+/// `chiron replay`: reruns recorded turns and reports decisions that changed.
+async fn replay_command(engine: &ChironEngine, transcript: &std::path::Path, update: bool) -> Result<()> {
+    let baseline = replay::load(transcript)?;
+    let session_id = engine.start_session("replay").await;
+    let session = engine.session(&session_id).await?;
+    let mut orchestrator = session.lock().await;
+    orchestrator.set_output(OutputSink::Silent);
+
+    let progress = Progress::new("Turn", baseline.len());
+    let following = progress.follow(orchestrator.subscribe());
+    let mut replayed = Vec::new();
+    let mut regressions = Vec::new();
+    for (i, recorded) in baseline.iter().enumerate() {
+        progress.start(i, "");
+        let turn = replay::observe(&mut orchestrator, &recorded.input)
+            .await
+            .with_context(|| format!("Turn {} failed", i + 1))?;
+        regressions.extend(replay::diff(i + 1, recorded, &turn));
+        replayed.push(turn);
+    }
+    drop(following);
+    progress.finish();
 
-        if input.eq_ignore_ascii_case("quit") || input.eq_ignore_ascii_case("exit") {
-            println!("Take care of yourself. Goodbye.");
-            break;
-        }
+    if update {
+        replay::save(transcript, &replayed)?;
+        println!("Recorded baseline for {} turns in {}", replayed.len(), transcript.display());
+        return Ok(());
+    }
+    let unrecorded = baseline.iter().filter(|t| !t.has_baseline()).count();
+    print!("{}", replay::render_report(replayed.len(), unrecorded, &regressions));
+    if !regressions.is_empty() {
+        anyhow::bail!("{} regressions against {}", regressions.len(), transcript.display());
+    }
+    Ok(())
+}
 
-        if input.eq_ignore_ascii_case("reset") {
-            orchestrator.reset();
-            println!("Conversation reset.");
-            continue;
-        }
+/// `chiron eval`: scores a suite's responses and prints the report.
+async fn eval_command(
+    exchanges: Vec<eval::Exchange>,
+    suite: &eval::rubric::Suite,
+    judge_model: Option<&provider::LlamaCppCompletionModel>,
+    history: bool,
+    output: Option<&std::path::Path>,
+) -> Result<()> {
+    let reports = eval::score(exchanges, suite, judge_model, history).await?;
+    if reports.is_empty() {
+        println!("No responses with a recorded prompt variant to score.");
+        return Ok(());
+    }
+    print!("{}", eval::render(&reports));
+    if let Some(path) = output {
+        std::fs::write(path, serde_json::to_string_pretty(&reports)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("\nResults written to {}", path.display());
+    }
+    Ok(())
+}
 
-        if input == "/end" {
-            let summary = orchestrator.end_session().await?;
-            println!("\n--- Session Summary ---");
-            println!("{summary}");
-            println!("--- New session started ---");
-            continue;
+/// Script mode: runs a test conversation, printing progress to stderr and
+/// the turns as JSON to stdout.
+async fn script(coach: &Coach, settings: &Config, script_path: &std::path::Path) -> Result<()> {
+    let script_content = std::fs::read_to_string(script_path)
+        .with_context(|| format!("Failed to read script: {}", script_path.display()))?;
+    let script: TestScript = toml::from_str(&script_content)
+        .with_context(|| format!("Failed to parse script: {}", script_path.display()))?;
+
+    let mut engine = coach.scratch_engine(settings).await?;
+    engine.show_thinking = true; // always show thinking in script mode
+
+    let session_id = engine.start_session(&format!("script_{}", script.id)).await;
+    let session = engine.session(&session_id).await?;
+    let mut orchestrator = session.lock().await;
+    orchestrator.set_output(OutputSink::Stderr);
+
+    eprintln!("=== Script Mode: {} ===", script.id);
+    eprintln!("Description: {}", script.description);
+    eprintln!("Coach: {}", coach.variant.id);
+    eprintln!("Turns: {}", script.turns.len());
+    eprintln!("---");
+
+    let run_start = Instant::now();
+    let mut turn_results = Vec::new();
+
+    for (i, turn) in script.turns.iter().enumerate() {
+        eprintln!("\n--- Turn {} ---", i + 1);
+        eprintln!("Input: {}", turn.input);
+        if let Some(ref mode) = turn.expected_mode {
+            eprintln!("Expected mode: {mode}");
         }
+        eprintln!("Notes: {}", turn.notes);
 
-        orchestrator
-            .run_turn(input)
+        let result = orchestrator
+            .run_turn_captured(&turn.input)
             .await
-            .context("Turn failed")?;
+            .with_context(|| format!("Turn {} failed", i + 1))?;
+
+        eprintln!("Case notes: {}", result.case_notes.as_deref().unwrap_or("none"));
+
+        turn_results.push(serde_json::json!({
+            "turn_number": result.turn_number,
+            "input": result.input,
+            "response": result.response,
+            "think_content": result.think_content,
+            "case_notes": result.case_notes,
+            "preamble_len": result.preamble_injected.len(),
+            "expected_mode": turn.expected_mode,
+            "script_notes": turn.notes,
+            "duration_ms": result.duration_ms,
+            "prompt_variant": result.prompt_variant,
+        }));
     }
+    orchestrator.flush_saves().await.context("Failed to save the last turn")?;
+
+    let output = serde_json::json!({
+        "script_id": script.id,
+        "description": script.description,
+        "coach_variant": coach.variant.id,
+        "total_duration_ms": run_start.elapsed().as_millis() as u64,
+        "turns": turn_results,
+    });
+
+    // Write JSON to stdout (eprintln used for progress above)
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
 
+/// `chiron export`: training examples from saved replies as JSONL.
+async fn export_training(conn: &tokio_rusqlite::Connection, export: &TrainingExport) -> Result<()> {
+    let TrainingExport { output, feedback, format } = export;
+    let consent = memory::consent::load(conn).await?;
+    if !consent.allows_training() {
+        anyhow::bail!(
+            "Training export needs consent to contribute conversations (currently `{}`); run `chiron consent full` to give it",
+            consent.as_str()
+        );
+    }
+    let examples: Vec<serde_json::Value> = match format {
+        TrainingFormat::Chat => memory::feedback::training_pairs(conn, *feedback)
+            .await?
+            .into_iter()
+            .map(|pair| {
+                serde_json::json!({
+                    "messages": [
+                        { "role": "user", "content": pair.input },
+                        { "role": "assistant", "content": pair.response },
+                    ],
+                    "synthetic": false,
+                    "prompt_variant": pair.prompt_variant,
+                    "model": pair.model,
+                    "prompt_version": pair.prompt_version,
+                    "session_id": pair.session_id,
+                    "feedback": pair.feedback,
+                    "feedback_reason": pair.feedback_reason,
+                })
+            })
+            .collect(),
+        TrainingFormat::Dpo => memory::reply_variants::preference_pairs(conn, *feedback)
+            .await?
+            .into_iter()
+            .map(|pair| {
+                serde_json::json!({
+                    "prompt": [{ "role": "user", "content": pair.prompt }],
+                    "chosen": [{ "role": "assistant", "content": pair.chosen }],
+                    "rejected": [{ "role": "assistant", "content": pair.rejected }],
+                    "session_id": pair.session_id,
+                    "turn_number": pair.turn_number,
+                    "steer": pair.steer,
+                })
+            })
+            .collect(),
+    };
+    let jsonl: String = examples.iter().map(|example| format!("{example}\n")).collect();
+    std::fs::write(output, jsonl).with_context(|| format!("Failed to write {}", output.display()))?;
+    if crate::output::json() {
+        return crate::output::print_json(&serde_json::json!({
+            "examples": examples.len(),
+            "output": output,
+            "format": format!("{format:?}").to_lowercase(),
+            "consent": consent.as_str(),
+        }));
+    }
+    crate::output::status(format!(
+        "Wrote {} examples to {} (consent: {})",
+        examples.len(),
+        output.display(),
+        consent.as_str()
+    ));
     Ok(())
 }
+
+/// The session a user means by `reference`: an ID, a short ID or other
+/// ID prefix, or a name given with `chiron sessions name`.
+async fn find_session(conn: &tokio_rusqlite::Connection, reference: &str) -> Result<String> {
+    match memory::session_names::resolve_session(conn, reference).await? {
+        SessionRef::Found(session_id) => Ok(session_id),
+        SessionRef::NotFound => anyhow::bail!("No session matches '{reference}' (see `chiron sessions`)"),
+        SessionRef::Ambiguous(ids) => anyhow::bail!(ambiguous(reference, &ids)),
+    }
+}
+
+fn ambiguous(reference: &str, ids: &[String]) -> String {
+    format!("'{reference}' matches {} sessions; use more of the ID:\n  {}", ids.len(), ids.join("\n  "))
+}
@@ -0,0 +1,274 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A reviewer's judgement of one coach reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Appropriate,
+    Inappropriate,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Appropriate => "appropriate",
+            Verdict::Inappropriate => "inappropriate",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "appropriate" => Some(Verdict::Appropriate),
+            "inappropriate" => Some(Verdict::Inappropriate),
+            _ => None,
+        }
+    }
+}
+
+/// A clinician's review of one coach reply (`chiron review`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotation {
+    pub verdict: Option<Verdict>,
+    /// The conversation showed risk the reply (and the crisis check) missed.
+    pub missed_risk: bool,
+    /// What the coach should have said instead.
+    pub alternative: Option<String>,
+    pub note: Option<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A coach reply with the user message it answers and any review so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewItem {
+    /// The reply's `chat_turns` ID.
+    pub turn_id: i64,
+    pub input: String,
+    pub response: String,
+    /// `None` for the fixed crisis response.
+    pub prompt_variant: Option<String>,
+    pub annotation: Annotation,
+}
+
+/// Review totals for one coach prompt variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewSummary {
+    pub prompt_variant: Option<String>,
+    pub reviewed: i64,
+    pub appropriate: i64,
+    pub inappropriate: i64,
+    pub missed_risk: i64,
+}
+
+/// SQL for a reply's effective rating, `good` or `bad`: the reviewer's
+/// verdict when there is one (missed risk counts as bad), otherwise the
+/// user's own feedback. `reply` is the `chat_turns` alias and `review` the
+/// (left-joined) `annotations` alias.
+pub fn rating_sql(reply: &str, review: &str) -> String {
+    format!(
+        "COALESCE(CASE WHEN {review}.missed_risk = 1 OR {review}.verdict = 'inappropriate' THEN 'bad'
+                       WHEN {review}.verdict = 'appropriate' THEN 'good' END,
+                  {reply}.feedback)"
+    )
+}
+
+/// Creates the annotations table if it doesn't exist.
+pub async fn create_annotations_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS annotations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                turn_id INTEGER NOT NULL UNIQUE,
+                verdict TEXT,
+                missed_risk INTEGER NOT NULL DEFAULT 0,
+                alternative TEXT,
+                note TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_annotations_session ON annotations(session_id);",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create annotations table")?;
+
+    Ok(())
+}
+
+/// The session's coach replies in order, each with the message it answers.
+pub async fn review_items(conn: &Connection, session_id: &str) -> Result<Vec<ReviewItem>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT a.id, COALESCE(u.content, ''), a.content, a.prompt_variant,
+                    n.verdict, COALESCE(n.missed_risk, 0), n.alternative, n.note
+             FROM chat_turns a
+             LEFT JOIN chat_turns u ON u.id = (
+                 SELECT MAX(id) FROM chat_turns
+                 WHERE session_id = a.session_id AND role = 'user' AND id < a.id
+             )
+             LEFT JOIN annotations n ON n.turn_id = a.id
+             WHERE a.session_id = ?1 AND a.role = 'assistant'
+             ORDER BY a.id",
+        )?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                let verdict: Option<String> = row.get(4)?;
+                Ok(ReviewItem {
+                    turn_id: row.get(0)?,
+                    input: row.get(1)?,
+                    response: row.get(2)?,
+                    prompt_variant: row.get(3)?,
+                    annotation: Annotation {
+                        verdict: verdict.as_deref().and_then(Verdict::parse),
+                        missed_risk: row.get(5)?,
+                        alternative: row.get(6)?,
+                        note: row.get(7)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load replies for review")
+}
+
+/// Saves the review of reply `turn_id`, replacing any earlier one. An empty
+/// annotation removes it.
+pub async fn annotate(conn: &Connection, session_id: &str, turn_id: i64, annotation: &Annotation) -> Result<()> {
+    let session_id = session_id.to_string();
+    let annotation = annotation.clone();
+    conn.call(move |conn| {
+        if annotation.is_empty() {
+            conn.execute("DELETE FROM annotations WHERE turn_id = ?1", [turn_id])?;
+            return Ok(());
+        }
+        conn.execute(
+            "INSERT INTO annotations (session_id, turn_id, verdict, missed_risk, alternative, note)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(turn_id) DO UPDATE SET
+                 verdict = excluded.verdict, missed_risk = excluded.missed_risk,
+                 alternative = excluded.alternative, note = excluded.note,
+                 created_at = datetime('now')",
+            rusqlite::params![
+                session_id,
+                turn_id,
+                annotation.verdict.map(Verdict::as_str),
+                annotation.missed_risk,
+                annotation.alternative,
+                annotation.note,
+            ],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save annotation")
+}
+
+/// Review totals per prompt variant, most reviewed first.
+pub async fn review_summary(conn: &Connection) -> Result<Vec<ReviewSummary>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT t.prompt_variant, COUNT(*),
+                    COALESCE(SUM(n.verdict = 'appropriate'), 0),
+                    COALESCE(SUM(n.verdict = 'inappropriate'), 0),
+                    COALESCE(SUM(n.missed_risk), 0)
+             FROM annotations n JOIN chat_turns t ON t.id = n.turn_id
+             GROUP BY t.prompt_variant
+             ORDER BY COUNT(*) DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ReviewSummary {
+                    prompt_variant: row.get(0)?,
+                    reviewed: row.get(1)?,
+                    appropriate: row.get(2)?,
+                    inappropriate: row.get(3)?,
+                    missed_risk: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load review summary")
+}
+
+/// User messages whose reply was marked missed risk, oldest first, as
+/// safety cases for `chiron replay`.
+pub async fn missed_risk_inputs(conn: &Connection) -> Result<Vec<String>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT u.content
+             FROM annotations n
+             JOIN chat_turns u ON u.id = (
+                 SELECT MAX(id) FROM chat_turns t
+                 WHERE t.session_id = n.session_id AND t.role = 'user' AND t.id < n.turn_id
+             )
+             WHERE n.missed_risk = 1
+             ORDER BY n.turn_id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load missed-risk messages")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::feedback::{training_pairs, FeedbackFilter};
+    use crate::memory::reply_variants::preference_pairs;
+    use crate::memory::{open_memory, save_chat_turn, InputSource};
+
+    #[tokio::test]
+    async fn test_annotate_and_summarize() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "s1", "user", "hi", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "s1", "assistant", "Hello.", InputSource::Text, Some("v1")).await.unwrap();
+        save_chat_turn(&conn, "s1", "user", "I've stopped eating", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "s1", "assistant", "Try a new recipe!", InputSource::Text, Some("v1")).await.unwrap();
+
+        let items = review_items(&conn, "s1").await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].input, "I've stopped eating");
+        assert!(items[1].annotation.is_empty());
+
+        let review = Annotation {
+            verdict: Some(Verdict::Inappropriate),
+            missed_risk: true,
+            alternative: Some("That sounds worrying. How long has it been?".to_string()),
+            note: None,
+        };
+        annotate(&conn, "s1", items[1].turn_id, &review).await.unwrap();
+        let appropriate = Annotation { verdict: Some(Verdict::Appropriate), ..Default::default() };
+        annotate(&conn, "s1", items[0].turn_id, &appropriate).await.unwrap();
+
+        let items = review_items(&conn, "s1").await.unwrap();
+        assert_eq!(items[1].annotation, review);
+        assert_eq!(missed_risk_inputs(&conn).await.unwrap(), vec!["I've stopped eating"]);
+        let summary = review_summary(&conn).await.unwrap();
+        assert_eq!((summary[0].reviewed, summary[0].appropriate, summary[0].missed_risk), (2, 1, 1));
+
+        // Reviews curate training data: a judged-bad reply is left out and
+        // its alternative becomes the chosen side of a preference pair
+        let kept = training_pairs(&conn, FeedbackFilter::NotBad).await.unwrap();
+        assert_eq!(kept.iter().map(|p| p.input.as_str()).collect::<Vec<_>>(), vec!["hi"]);
+        let pairs = preference_pairs(&conn, FeedbackFilter::NotBad).await.unwrap();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].turn_number, pairs[0].rejected.as_str()), (2, "Try a new recipe!"));
+
+        annotate(&conn, "s1", items[0].turn_id, &Annotation::default()).await.unwrap();
+        assert!(review_items(&conn, "s1").await.unwrap()[0].annotation.is_empty());
+    }
+}
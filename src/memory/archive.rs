@@ -0,0 +1,285 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use super::SessionInfo;
+use crate::error::{Context, Result};
+
+/// A session whose transcript was replaced by a summary
+/// (`chiron sessions archive`). Case notes, ratings, and other records that
+/// reference the session are kept; only its messages are removed.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionArchive {
+    pub session_id: String,
+    /// Model-written clinical-style summary of the transcript.
+    pub summary: String,
+    pub message_count: i64,
+    pub started_at: String,
+    pub last_active_at: String,
+    pub progress: SessionProgress,
+}
+
+/// Progress indicators kept from an archived session.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct SessionProgress {
+    /// MI stage of the session's first and last case notes.
+    pub mi_stage_start: Option<String>,
+    pub mi_stage_end: Option<String>,
+    /// Outcome Rating Scale total at the start (0–40), if rated.
+    pub ors: Option<f64>,
+    /// Session Rating Scale total at the end (0–40), if rated.
+    pub srs: Option<f64>,
+    /// Coach prompt variants that replied, in first-use order.
+    pub prompt_variants: Vec<String>,
+    /// User messages that triggered the crisis response.
+    pub crisis_messages: i64,
+}
+
+/// Creates the session_archives table if it doesn't exist.
+pub async fn create_session_archives_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_archives (
+                session_id TEXT PRIMARY KEY,
+                summary TEXT NOT NULL,
+                message_count INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                last_active_at TEXT NOT NULL,
+                last_turn_id INTEGER NOT NULL,
+                mi_stage_start TEXT,
+                mi_stage_end TEXT,
+                ors REAL,
+                srs REAL,
+                prompt_variants TEXT NOT NULL DEFAULT '',
+                crisis_messages INTEGER NOT NULL DEFAULT 0,
+                archived_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create session_archives table")?;
+
+    Ok(())
+}
+
+/// Sessions last active before `before` (`YYYY-MM-DD HH:MM:SS`) that still
+/// have their transcript, oldest first.
+pub async fn sessions_to_archive(conn: &Connection, before: &str) -> Result<Vec<SessionInfo>> {
+    let before = before.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, message_count, started_at, last_active_at FROM session_index
+             WHERE last_active_at < ?1
+               AND session_id NOT IN (SELECT session_id FROM session_archives)
+             ORDER BY last_turn_id",
+        )?;
+        let rows = stmt
+            .query_map([before], |row| {
+                Ok(SessionInfo {
+                    session_id: row.get(0)?,
+                    message_count: row.get(1)?,
+                    started_at: row.get(2)?,
+                    last_active_at: row.get(3)?,
+                    archived: false,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to find sessions to archive")
+}
+
+/// Collects the progress indicators kept when a session is archived, apart
+/// from `crisis_messages`, which the caller counts from the transcript.
+pub async fn session_progress(conn: &Connection, session_id: &str) -> Result<SessionProgress> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let stage = |order: &str| -> rusqlite::Result<Option<String>> {
+            conn.query_row(
+                &format!(
+                    "SELECT mi_stage FROM case_notes WHERE session_id = ?1 AND mi_stage IS NOT NULL
+                     ORDER BY id {order} LIMIT 1"
+                ),
+                [&session_id],
+                |row| row.get(0),
+            )
+            .optional()
+        };
+        let rating = |measure: &str| -> rusqlite::Result<Option<f64>> {
+            conn.query_row(
+                "SELECT total FROM session_quality WHERE session_id = ?1 AND measure = ?2
+                 ORDER BY id DESC LIMIT 1",
+                [session_id.as_str(), measure],
+                |row| row.get(0),
+            )
+            .optional()
+        };
+        let prompt_variants = conn
+            .prepare(
+                "SELECT prompt_variant FROM chat_turns
+                 WHERE session_id = ?1 AND prompt_variant IS NOT NULL
+                 GROUP BY prompt_variant ORDER BY MIN(id)",
+            )?
+            .query_map([&session_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(SessionProgress {
+            mi_stage_start: stage("ASC")?,
+            mi_stage_end: stage("DESC")?,
+            ors: rating("ors")?,
+            srs: rating("srs")?,
+            prompt_variants,
+            crisis_messages: 0,
+        })
+    })
+    .await
+    .context("Failed to load session progress")
+}
+
+/// Stores the archive and deletes the session's messages in one
+/// transaction. The session stays in `session_index`, so it is still listed
+/// and counted. Returns false (and changes nothing) if the session isn't indexed.
+pub async fn archive_session(conn: &Connection, archive: &SessionArchive) -> Result<bool> {
+    let archive = archive.clone();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        let progress = &archive.progress;
+        let inserted = tx.execute(
+            "INSERT INTO session_archives (session_id, summary, message_count, started_at, last_active_at,
+                 last_turn_id, mi_stage_start, mi_stage_end, ors, srs, prompt_variants, crisis_messages)
+             SELECT ?1, ?2, ?3, ?4, ?5, last_turn_id, ?6, ?7, ?8, ?9, ?10, ?11
+             FROM session_index WHERE session_id = ?1",
+            rusqlite::params![
+                archive.session_id,
+                archive.summary,
+                archive.message_count,
+                archive.started_at,
+                archive.last_active_at,
+                progress.mi_stage_start,
+                progress.mi_stage_end,
+                progress.ors,
+                progress.srs,
+                progress.prompt_variants.join(","),
+                progress.crisis_messages,
+            ],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        tx.execute("DELETE FROM chat_turns WHERE session_id = ?1", [&archive.session_id])?;
+        tx.commit()?;
+        Ok(true)
+    })
+    .await
+    .context("Failed to archive session")
+}
+
+/// Archived sessions active on or after `since`, oldest first.
+pub async fn archives_since(conn: &Connection, since: &str) -> Result<Vec<SessionArchive>> {
+    let since = since.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, summary, message_count, started_at, last_active_at, mi_stage_start,
+                    mi_stage_end, ors, srs, prompt_variants, crisis_messages
+             FROM session_archives
+             WHERE last_active_at >= ?1
+             ORDER BY last_turn_id",
+        )?;
+        let rows = stmt
+            .query_map([since], |row| {
+                let prompt_variants: String = row.get(9)?;
+                Ok(SessionArchive {
+                    session_id: row.get(0)?,
+                    summary: row.get(1)?,
+                    message_count: row.get(2)?,
+                    started_at: row.get(3)?,
+                    last_active_at: row.get(4)?,
+                    progress: SessionProgress {
+                        mi_stage_start: row.get(5)?,
+                        mi_stage_end: row.get(6)?,
+                        ors: row.get(7)?,
+                        srs: row.get(8)?,
+                        prompt_variants: prompt_variants
+                            .split(',')
+                            .filter(|v| !v.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                        crisis_messages: row.get(10)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session archives")
+}
+
+/// Returns freed pages to the filesystem after archiving, so the database
+/// file actually shrinks.
+pub async fn vacuum(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch("VACUUM")?;
+        Ok(())
+    })
+    .await
+    .context("Failed to vacuum database")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{
+        case_notes, list_sessions, load_session_turns, open_memory, outcomes, rebuild_session_index,
+        save_chat_turn, InputSource,
+    };
+
+    #[tokio::test]
+    async fn test_archive_replaces_transcript_but_keeps_the_session() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "old", "user", "hi", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "old", "assistant", "hello", InputSource::Text, Some("v7")).await.unwrap();
+        save_chat_turn(&conn, "new", "user", "hey", InputSource::Text, None).await.unwrap();
+        case_notes::save_case_note(&conn, "old", 1, Some("engage"), "MI Stage: engage").await.unwrap();
+        case_notes::save_case_note(&conn, "old", 2, Some("focus"), "MI Stage: focus").await.unwrap();
+        outcomes::save_rating(&conn, "old", "ors", 21.0, &[5, 5, 5, 6]).await.unwrap();
+
+        // Everything is "older" than a cutoff in the future
+        let due = sessions_to_archive(&conn, "9999-01-01 00:00:00").await.unwrap();
+        assert_eq!(due.iter().map(|s| s.session_id.as_str()).collect::<Vec<_>>(), vec!["old", "new"]);
+
+        let mut progress = session_progress(&conn, "old").await.unwrap();
+        assert_eq!(progress.mi_stage_start.as_deref(), Some("engage"));
+        assert_eq!(progress.mi_stage_end.as_deref(), Some("focus"));
+        assert_eq!((progress.ors, progress.srs), (Some(21.0), None));
+        assert_eq!(progress.prompt_variants, vec!["v7"]);
+        progress.crisis_messages = 1;
+
+        let archive = SessionArchive {
+            session_id: "old".to_string(),
+            summary: "Discussed work stress.".to_string(),
+            message_count: due[0].message_count,
+            started_at: due[0].started_at.clone(),
+            last_active_at: due[0].last_active_at.clone(),
+            progress,
+        };
+        assert!(archive_session(&conn, &archive).await.unwrap());
+        assert!(load_session_turns(&conn, "old").await.unwrap().is_empty());
+        assert_eq!(load_session_turns(&conn, "new").await.unwrap().len(), 1);
+        assert_eq!(archives_since(&conn, "0000-01-01").await.unwrap(), vec![archive.clone()]);
+        assert!(archives_since(&conn, "9999-01-01").await.unwrap().is_empty());
+
+        // Still listed and counted, including after an index rebuild
+        rebuild_session_index(&conn).await.unwrap();
+        let sessions = list_sessions(&conn).await.unwrap();
+        let old = sessions.iter().find(|s| s.session_id == "old").unwrap();
+        assert!(old.archived);
+        assert_eq!(old.message_count, 2);
+        let due = sessions_to_archive(&conn, "9999-01-01 00:00:00").await.unwrap();
+        assert_eq!(due.len(), 1, "an archived session isn't archived again");
+
+        let unknown = SessionArchive { session_id: "missing".to_string(), ..archive };
+        assert!(!archive_session(&conn, &unknown).await.unwrap());
+        vacuum(&conn).await.unwrap();
+    }
+}
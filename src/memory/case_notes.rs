@@ -1,7 +1,8 @@
-use anyhow::{Context, Result};
 use rusqlite::OptionalExtension;
 use tokio_rusqlite::Connection;
 
+use crate::error::{Context, Result};
+
 /// Creates the case_notes table and index if they don't exist.
 pub async fn create_case_notes_table(conn: &Connection) -> Result<()> {
     conn.call(|conn| {
@@ -68,6 +69,55 @@ pub async fn get_latest_case_note(conn: &Connection) -> Result<Option<String>> {
     Ok(result)
 }
 
+/// Loads the most recent case note content from one session.
+pub async fn session_case_note(conn: &Connection, session_id: &str) -> Result<Option<String>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row(
+                "SELECT content FROM case_notes WHERE session_id = ?1 ORDER BY id DESC LIMIT 1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    })
+    .await
+    .context("Failed to load session case note")
+}
+
+/// A stored case note row.
+#[derive(Debug, Clone)]
+pub struct CaseNote {
+    pub session_id: String,
+    pub mi_stage: Option<String>,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// Loads all case notes created at or after `since` (`YYYY-MM-DD`), oldest first.
+pub async fn case_notes_since(conn: &Connection, since: &str) -> Result<Vec<CaseNote>> {
+    let since = since.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, mi_stage, content, created_at FROM case_notes
+             WHERE created_at >= ?1 ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([since], |row| {
+                Ok(CaseNote {
+                    session_id: row.get(0)?,
+                    mi_stage: row.get(1)?,
+                    content: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load case notes")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,5 +155,10 @@ mod tests {
 
         let latest = get_latest_case_note(&conn).await.unwrap();
         assert_eq!(latest.unwrap(), "Cross-session note.");
+
+        let all = case_notes_since(&conn, "2000-01-01").await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[1].mi_stage.as_deref(), Some("focus"));
+        assert!(case_notes_since(&conn, "9999-01-01").await.unwrap().is_empty());
     }
 }
@@ -0,0 +1,114 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// How well a session's replies follow from the messages they answer (see
+/// `coherence::score`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionCoherence {
+    pub session_id: String,
+    /// Mean similarity of each reply to the message before it, 0–1, with
+    /// non-sequiturs counted as 0.
+    pub score: f64,
+    pub replies: i64,
+    /// Replies barely related to the message they answer.
+    pub non_sequiturs: i64,
+    /// Messages in the session when it was scored; a different count means
+    /// the score is out of date.
+    pub message_count: i64,
+}
+
+/// Creates the session_coherence table if it doesn't exist.
+pub async fn create_session_coherence_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_coherence (
+                session_id TEXT PRIMARY KEY,
+                score REAL NOT NULL,
+                replies INTEGER NOT NULL,
+                non_sequiturs INTEGER NOT NULL,
+                message_count INTEGER NOT NULL,
+                computed_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create session_coherence table")?;
+
+    Ok(())
+}
+
+/// Stores a session's score, replacing any earlier one.
+pub async fn save_coherence(conn: &Connection, coherence: &SessionCoherence) -> Result<()> {
+    let c = coherence.clone();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO session_coherence (session_id, score, replies, non_sequiturs, message_count)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id) DO UPDATE SET
+                score = excluded.score,
+                replies = excluded.replies,
+                non_sequiturs = excluded.non_sequiturs,
+                message_count = excluded.message_count,
+                computed_at = datetime('now')",
+            rusqlite::params![c.session_id, c.score, c.replies, c.non_sequiturs, c.message_count],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save session coherence")
+}
+
+/// Scores of sessions that still exist, most recently active first.
+pub async fn coherence_scores(conn: &Connection) -> Result<Vec<SessionCoherence>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT c.session_id, c.score, c.replies, c.non_sequiturs, c.message_count
+             FROM session_coherence c JOIN session_index i ON i.session_id = c.session_id
+             ORDER BY i.last_turn_id DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SessionCoherence {
+                    session_id: row.get(0)?,
+                    score: row.get(1)?,
+                    replies: row.get(2)?,
+                    non_sequiturs: row.get(3)?,
+                    message_count: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session coherence")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{open_memory, save_chat_turn, InputSource};
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "s1", "user", "hi", InputSource::Text, None).await.unwrap();
+        let mut coherence = SessionCoherence {
+            session_id: "s1".to_string(),
+            score: 0.4,
+            replies: 3,
+            non_sequiturs: 1,
+            message_count: 6,
+        };
+        save_coherence(&conn, &coherence).await.unwrap();
+        coherence.score = 0.7;
+        save_coherence(&conn, &coherence).await.unwrap();
+        assert_eq!(coherence_scores(&conn).await.unwrap(), vec![coherence.clone()]);
+
+        // Scores of sessions that no longer exist aren't listed
+        coherence.session_id = "gone".to_string();
+        save_coherence(&conn, &coherence).await.unwrap();
+        assert_eq!(coherence_scores(&conn).await.unwrap().len(), 1);
+    }
+}
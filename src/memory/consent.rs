@@ -0,0 +1,83 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::Result;
+use crate::memory::preferences;
+
+/// What they've agreed to contribute beyond their own use of Chiron.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Consent {
+    /// Nothing leaves: no training exports, no research aggregates.
+    #[default]
+    None,
+    /// Noised aggregate numbers only (`chiron report --epsilon`), never
+    /// conversation text.
+    Anonymized,
+    /// Conversations too, as training examples (`chiron export`).
+    Full,
+}
+
+impl Consent {
+    /// Preference key in the `preferences` table.
+    pub const PREFERENCE: &'static str = "training_consent";
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "anonymized" => Some(Self::Anonymized),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Anonymized => "anonymized",
+            Self::Full => "full",
+        }
+    }
+
+    /// Whether conversation text may be exported for training.
+    pub fn allows_training(self) -> bool {
+        self == Self::Full
+    }
+
+    /// Whether noised aggregates may be shared.
+    pub fn allows_aggregates(self) -> bool {
+        self != Self::None
+    }
+}
+
+/// Their recorded consent; `None` until they give one, or if the stored
+/// value isn't recognized.
+pub async fn load(conn: &Connection) -> Result<Consent> {
+    let stored = preferences::get_preference(conn, Consent::PREFERENCE).await?;
+    Ok(stored.as_deref().and_then(Consent::parse).unwrap_or_default())
+}
+
+/// Records their consent, replacing the previous one.
+pub async fn save(conn: &Connection, consent: Consent) -> Result<()> {
+    preferences::set_preference(conn, Consent::PREFERENCE, consent.as_str()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::open_memory;
+
+    #[tokio::test]
+    async fn test_consent_round_trip() {
+        let conn = open_memory(":memory:").await.unwrap();
+        assert_eq!(load(&conn).await.unwrap(), Consent::None, "nothing until they agree");
+
+        save(&conn, Consent::Anonymized).await.unwrap();
+        let consent = load(&conn).await.unwrap();
+        assert!(consent.allows_aggregates() && !consent.allows_training());
+
+        save(&conn, Consent::Full).await.unwrap();
+        assert!(load(&conn).await.unwrap().allows_training());
+
+        preferences::set_preference(&conn, Consent::PREFERENCE, "maybe").await.unwrap();
+        assert_eq!(load(&conn).await.unwrap(), Consent::None);
+    }
+}
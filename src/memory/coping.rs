@@ -0,0 +1,157 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Average rating at or above which a skill counts as helpful.
+const HELPFUL_RATING: f64 = 4.0;
+
+/// Average rating at or below which a skill shouldn't be suggested again.
+const DISLIKED_RATING: f64 = 2.0;
+
+/// Maximum skills listed per group in the preamble.
+const MAX_PROMPT_SKILLS: usize = 3;
+
+/// A coping skill the user has tried, with their ratings combined.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CopingSkill {
+    /// Skill name as entered (ratings are combined case-insensitively).
+    pub name: String,
+    /// Mean self-rated effectiveness, 1 (didn't help) to 5 (helped a lot).
+    pub average: f64,
+    pub times_rated: i64,
+    pub last_rated_at: String,
+}
+
+/// Creates the coping_ratings table if it doesn't exist.
+pub async fn create_coping_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS coping_ratings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                skill TEXT NOT NULL,
+                rating INTEGER NOT NULL CHECK(rating BETWEEN 1 AND 5),
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create coping_ratings table")?;
+
+    Ok(())
+}
+
+/// Records that the user tried a skill and how well it worked (1–5).
+pub async fn rate_skill(conn: &Connection, session_id: &str, skill: &str, rating: i32) -> Result<()> {
+    let session_id = session_id.to_string();
+    let skill = skill.trim().to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO coping_ratings (session_id, skill, rating) VALUES (?1, ?2, ?3)",
+            rusqlite::params![session_id, skill, rating],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save coping rating")
+}
+
+/// Lists every rated skill (case-insensitive), best-rated first.
+pub async fn list_skills(conn: &Connection) -> Result<Vec<CopingSkill>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT MIN(skill), AVG(rating), COUNT(*), MAX(created_at) FROM coping_ratings
+             GROUP BY lower(skill)
+             ORDER BY AVG(rating) DESC, COUNT(*) DESC, MAX(id) DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(CopingSkill {
+                    name: row.get(0)?,
+                    average: row.get(1)?,
+                    times_rated: row.get(2)?,
+                    last_rated_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to list coping skills")
+}
+
+/// Names of the best-rated skills that helped (up to three), best first.
+/// Expects `skills` in `list_skills` order.
+pub fn helpful_skills(skills: &[CopingSkill]) -> Vec<&str> {
+    skills
+        .iter()
+        .filter(|s| s.average >= HELPFUL_RATING)
+        .take(MAX_PROMPT_SKILLS)
+        .map(|s| s.name.as_str())
+        .collect()
+}
+
+/// Formats what helped and what didn't for the preamble, or `None` if no
+/// skill is clearly in either group.
+pub fn format_coping_for_prompt(skills: &[CopingSkill]) -> Option<String> {
+    let helpful = helpful_skills(skills);
+    // Worst first, so the most disliked skills survive the cap
+    let mut disliked: Vec<&str> = skills
+        .iter()
+        .rev()
+        .filter(|s| s.average <= DISLIKED_RATING)
+        .take(MAX_PROMPT_SKILLS)
+        .map(|s| s.name.as_str())
+        .collect();
+    disliked.reverse();
+
+    let mut lines = Vec::new();
+    if !helpful.is_empty() {
+        lines.push(format!("- Helped before: {}", helpful.join(", ")));
+    }
+    if !disliked.is_empty() {
+        lines.push(format!("- Didn't help (don't suggest again): {}", disliked.join(", ")));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ratings_combine_per_skill() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_coping_table(&conn).await.unwrap();
+
+        rate_skill(&conn, "s1", "Paced breathing", 5).await.unwrap();
+        rate_skill(&conn, "s2", "paced breathing", 4).await.unwrap();
+        rate_skill(&conn, "s2", "Journaling", 1).await.unwrap();
+        rate_skill(&conn, "s2", "Cold water", 3).await.unwrap();
+        assert!(rate_skill(&conn, "s2", "Yoga", 9).await.is_err());
+
+        let skills = list_skills(&conn).await.unwrap();
+        assert_eq!(skills.len(), 3);
+        assert_eq!(skills[0].name, "Paced breathing");
+        assert_eq!(skills[0].average, 4.5);
+        assert_eq!(skills[0].times_rated, 2);
+
+        assert_eq!(
+            format_coping_for_prompt(&skills).unwrap(),
+            "- Helped before: Paced breathing\n- Didn't help (don't suggest again): Journaling"
+        );
+    }
+
+    #[test]
+    fn test_format_coping_needs_a_clear_signal() {
+        let neutral = CopingSkill {
+            name: "Cold water".into(),
+            average: 3.0,
+            times_rated: 1,
+            last_rated_at: String::new(),
+        };
+        assert_eq!(format_coping_for_prompt(&[neutral]), None);
+        assert_eq!(format_coping_for_prompt(&[]), None);
+    }
+}
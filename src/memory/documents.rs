@@ -0,0 +1,259 @@
+use std::path::Path;
+use std::process::Command;
+
+use rig::embeddings::EmbeddingModel as _;
+use rig_fastembed::EmbeddingModel;
+
+use super::vectors::{self, DocumentChunk};
+use crate::error::{ChironError, Result};
+
+/// Maximum characters per document chunk.
+///
+/// Kept small so a retrieved chunk fits inside the RAG context budget
+/// alongside user facts (see `MAX_RAG_CONTEXT_CHARS` in the orchestrator).
+const MAX_CHUNK_CHARS: usize = 200;
+
+/// File extensions accepted for ingestion: plain text, and PDFs (read with
+/// `pdftotext`).
+const SUPPORTED_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "pdf"];
+
+/// Derives a document title from its first `#` heading, falling back to the file stem.
+pub fn document_title(content: &str, path: &Path) -> String {
+    content
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("# "))
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_else(|| "untitled".to_string())
+        })
+}
+
+/// Splits document text into chunks of at most `max_chars` characters.
+///
+/// Paragraphs (blank-line separated) are packed together until the limit
+/// would be exceeded. Paragraphs longer than the limit are split on word
+/// boundaries. Markdown heading markers are stripped so chunks read as prose.
+pub fn chunk_text(content: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    let paragraphs = content
+        .split("\n\n")
+        .map(|p| {
+            p.lines()
+                .map(|l| l.trim().trim_start_matches('#').trim())
+                .filter(|l| !l.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|p| !p.is_empty());
+
+    for paragraph in paragraphs {
+        for piece in split_long(&paragraph, max_chars) {
+            let added = if current.is_empty() { piece.len() } else { piece.len() + 1 };
+            if !current.is_empty() && current.len() + added > max_chars {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(&piece);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+/// Splits a single paragraph on word boundaries so no piece exceeds `max_chars`.
+fn split_long(paragraph: &str, max_chars: usize) -> Vec<String> {
+    if paragraph.len() <= max_chars {
+        return vec![paragraph.to_string()];
+    }
+
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for word in paragraph.split_whitespace() {
+        if !current.is_empty() && current.len() + word.len() + 1 > max_chars {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Returns an error if the file extension is not a supported format.
+fn ensure_supported(path: &Path) -> Result<()> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(ChironError::Document(format!(
+            "Unsupported document type '{}' (supported: {})",
+            path.display(),
+            SUPPORTED_EXTENSIONS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// A document's text, one entry per part with where it is in the document:
+/// a PDF's pages (`p. 3`), or a text file as a whole (no location).
+fn read_document(path: &Path) -> Result<Vec<(String, String)>> {
+    if !is_pdf(path) {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ChironError::file(format!("Failed to read: {}", path.display()), e))?;
+        return Ok(vec![(String::new(), content)]);
+    }
+    let output = Command::new("pdftotext")
+        .args(["-enc", "UTF-8"])
+        .arg(path)
+        .arg("-")
+        .output()
+        .map_err(|e| ChironError::file("PDF documents need pdftotext (from poppler-utils) on PATH", e))?;
+    if !output.status.success() {
+        return Err(ChironError::Document(format!(
+            "pdftotext failed to read {} ({}): {}",
+            path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(pdf_pages(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn is_pdf(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// Splits `pdftotext` output into numbered pages (it ends each page with a
+/// form feed), skipping blank ones.
+fn pdf_pages(text: &str) -> Vec<(String, String)> {
+    text.split('\x0c')
+        .enumerate()
+        .filter(|(_, page)| !page.trim().is_empty())
+        .map(|(i, page)| (format!("p. {}", i + 1), page.to_string()))
+        .collect()
+}
+
+/// Ingests a text, markdown, or PDF document into the `user_documents`
+/// table.
+///
+/// Every chunk is embedded before anything is stored, and re-ingesting the
+/// same path only replaces its previous chunks once the new ones are
+/// written, so a failure leaves the existing copy intact.
+/// Returns the number of chunks stored.
+pub async fn ingest_document(
+    conn: &lancedb::Connection,
+    model: &EmbeddingModel,
+    path: &Path,
+) -> Result<usize> {
+    ensure_supported(path)?;
+
+    let parts = read_document(path)?;
+    let source_path = path
+        .canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string();
+    let title = document_title(parts.first().map_or("", |(_, text)| text.as_str()), path);
+    let chunks: Vec<(String, String)> = parts
+        .iter()
+        .flat_map(|(location, text)| {
+            chunk_text(text, MAX_CHUNK_CHARS).into_iter().map(move |chunk| (location.clone(), chunk))
+        })
+        .collect();
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut embedded = Vec::with_capacity(chunks.len());
+    for (i, (location, text)) in chunks.into_iter().enumerate() {
+        let embedding = model
+            .embed_text(&text)
+            .await
+            .map_err(|e| ChironError::vector_store(format!("Failed to embed chunk {i} of {}", path.display()), e))?;
+
+        let chunk = DocumentChunk {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: title.clone(),
+            source_path: source_path.clone(),
+            chunk_index: i as i32,
+            content: text,
+            created_at: now.clone(),
+            location,
+        };
+        embedded.push((chunk, embedding.vec));
+    }
+
+    vectors::replace_document(conn, &source_path, &embedded).await?;
+    Ok(embedded.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_packs_paragraphs() {
+        let text = "First paragraph.\n\nSecond paragraph.\n\nThird paragraph.";
+        let chunks = chunk_text(text, 40);
+        assert_eq!(chunks, vec!["First paragraph. Second paragraph.", "Third paragraph."]);
+    }
+
+    #[test]
+    fn test_chunk_splits_long_paragraph() {
+        let text = "word ".repeat(100);
+        let chunks = chunk_text(&text, 50);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 50));
+    }
+
+    #[test]
+    fn test_chunk_strips_headings() {
+        let chunks = chunk_text("# Sleep Plan\n\n## Evenings\nNo screens after 10pm.", 300);
+        assert_eq!(chunks, vec!["Sleep Plan Evenings No screens after 10pm."]);
+    }
+
+    #[test]
+    fn test_chunk_empty() {
+        assert!(chunk_text("", 300).is_empty());
+        assert!(chunk_text("\n\n\n", 300).is_empty());
+    }
+
+    #[test]
+    fn test_document_title() {
+        let path = Path::new("/tmp/worksheet.md");
+        assert_eq!(document_title("# Thought Log\nbody", path), "Thought Log");
+        assert_eq!(document_title("no heading here", path), "worksheet");
+    }
+
+    #[test]
+    fn test_supported_extensions() {
+        assert!(ensure_supported(Path::new("notes.md")).is_ok());
+        assert!(ensure_supported(Path::new("plan.TXT")).is_ok());
+        assert!(ensure_supported(Path::new("instructions.PDF")).is_ok());
+        assert!(ensure_supported(Path::new("scan.docx")).is_err());
+        assert!(ensure_supported(Path::new("no_extension")).is_err());
+    }
+
+    #[test]
+    fn test_pdf_pages() {
+        let pages = pdf_pages("Page one text\n\x0c\n\x0cPage three\n\x0c");
+        assert_eq!(
+            pages,
+            vec![("p. 1".to_string(), "Page one text\n".to_string()), ("p. 3".to_string(), "Page three\n".to_string())]
+        );
+    }
+}
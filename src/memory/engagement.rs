@@ -0,0 +1,71 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// How one user message was written, as stored on its `chat_turns` row.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct MessageEngagement {
+    /// Characters in the message.
+    pub chars: u64,
+    /// Time from the previous reply to this message. `None` for the first
+    /// message after starting or resuming a session.
+    pub latency_ms: Option<u64>,
+}
+
+/// The session's last `limit` user messages, oldest first.
+pub async fn recent_messages(conn: &Connection, session_id: &str, limit: usize) -> Result<Vec<MessageEngagement>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT length(content), response_latency_ms FROM chat_turns
+             WHERE session_id = ?1 AND role = 'user'
+             ORDER BY id DESC LIMIT ?2",
+        )?;
+        let mut rows = stmt
+            .query_map(rusqlite::params![session_id, limit as i64], |row| {
+                Ok(MessageEngagement {
+                    chars: row.get::<_, i64>(0)? as u64,
+                    latency_ms: row.get::<_, Option<i64>>(1)?.map(|ms| ms as u64),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.reverse();
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load message engagement")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::open_memory;
+    use crate::memory::writer::{Exchange, SaveQueue};
+
+    #[tokio::test]
+    async fn test_recent_messages() {
+        let conn = open_memory(":memory:").await.unwrap();
+        let queue = SaveQueue::spawn(conn.clone());
+        for (input, latency_ms) in [("Work is a lot", None), ("Deadlines", Some(12_000))] {
+            queue.save(Exchange {
+                session_id: "s1".to_string(),
+                input: input.to_string(),
+                latency_ms,
+                response: "What's weighing most?".to_string(),
+                ..Default::default()
+            });
+        }
+        queue.flush().await.unwrap();
+
+        // Only their messages, oldest first
+        let messages = recent_messages(&conn, "s1", 10).await.unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                MessageEngagement { chars: 13, latency_ms: None },
+                MessageEngagement { chars: 9, latency_ms: Some(12_000) },
+            ]
+        );
+        assert_eq!(recent_messages(&conn, "s1", 1).await.unwrap(), vec![messages[1]]);
+    }
+}
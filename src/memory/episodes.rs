@@ -0,0 +1,95 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A session started because its thread (or resumed session) had gone
+/// quiet for too long, linked to the one it follows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Episode {
+    pub session_id: String,
+    pub previous_session_id: String,
+    /// Summary of the previous episode, given to the coach as context.
+    pub summary: String,
+    pub created_at: String,
+}
+
+/// Creates the episodes table if it doesn't exist.
+pub async fn create_episodes_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS episodes (
+                session_id TEXT PRIMARY KEY,
+                previous_session_id TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create episodes table")?;
+
+    Ok(())
+}
+
+/// Records that `session_id` continues `previous_session_id` as a new episode.
+pub async fn start_episode(
+    conn: &Connection,
+    session_id: &str,
+    previous_session_id: &str,
+    summary: &str,
+) -> Result<()> {
+    let params = [session_id.to_string(), previous_session_id.to_string(), summary.to_string()];
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO episodes (session_id, previous_session_id, summary) VALUES (?1, ?2, ?3)",
+            params,
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save episode")
+}
+
+/// The episode link for a session. `None` if it wasn't started as a later
+/// episode of another session.
+pub async fn episode(conn: &Connection, session_id: &str) -> Result<Option<Episode>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row(
+                "SELECT session_id, previous_session_id, summary, created_at FROM episodes WHERE session_id = ?1",
+                [session_id],
+                |row| {
+                    Ok(Episode {
+                        session_id: row.get(0)?,
+                        previous_session_id: row.get(1)?,
+                        summary: row.get(2)?,
+                        created_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?)
+    })
+    .await
+    .context("Failed to load episode")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_episodes_link_sessions() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_episodes_table(&conn).await.unwrap();
+
+        assert_eq!(episode(&conn, "session_2").await.unwrap(), None);
+        start_episode(&conn, "session_2", "session_1", "Last talked 2026-09-01").await.unwrap();
+
+        let linked = episode(&conn, "session_2").await.unwrap().unwrap();
+        assert_eq!(linked.previous_session_id, "session_1");
+        assert_eq!(linked.summary, "Last talked 2026-09-01");
+    }
+}
@@ -0,0 +1,224 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+use crate::memory::annotations::rating_sql;
+
+/// A rating of one reply (`/good`, `/bad <reason>`, or the web UI's
+/// 👍/👎), stored on its `chat_turns` row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    Good,
+    Bad,
+}
+
+impl Feedback {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Feedback::Good => "good",
+            Feedback::Bad => "bad",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "good" | "👍" => Some(Feedback::Good),
+            "bad" | "👎" => Some(Feedback::Bad),
+            _ => None,
+        }
+    }
+}
+
+/// Which replies `training_pairs` exports, by their rating: the user's
+/// feedback, or a reviewer's verdict when there is one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FeedbackFilter {
+    /// Only replies rated good.
+    Good,
+    /// Everything except replies rated bad (or judged inappropriate or
+    /// missing risk by a reviewer).
+    NotBad,
+    /// All replies.
+    Any,
+}
+
+impl FeedbackFilter {
+    /// SQL condition on a reply's `feedback` column.
+    pub fn condition(self, column: &str) -> String {
+        match self {
+            FeedbackFilter::Good => format!("{column} = 'good'"),
+            FeedbackFilter::NotBad => format!("{column} IS NOT 'bad'"),
+            FeedbackFilter::Any => "1".to_string(),
+        }
+    }
+}
+
+/// Feedback totals for one coach prompt variant, from the
+/// `ai_response_quality` view.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ResponseQuality {
+    /// `None` for the fixed crisis response.
+    pub prompt_variant: Option<String>,
+    pub replies: i64,
+    pub good: i64,
+    pub bad: i64,
+}
+
+/// A user message and the coach's reply to it, for fine-tuning.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TrainingPair {
+    pub session_id: String,
+    pub prompt_variant: String,
+    /// Model and prompt version behind the reply, if recorded (see
+    /// `Provenance`).
+    pub model: Option<String>,
+    pub prompt_version: Option<String>,
+    pub input: String,
+    pub response: String,
+    pub feedback: Option<String>,
+    pub feedback_reason: Option<String>,
+}
+
+/// Creates the `ai_response_quality` view: replies and their feedback per
+/// prompt variant.
+pub async fn create_response_quality_view(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE VIEW IF NOT EXISTS ai_response_quality AS
+                SELECT prompt_variant,
+                       COUNT(*) AS replies,
+                       COALESCE(SUM(feedback = 'good'), 0) AS good,
+                       COALESCE(SUM(feedback = 'bad'), 0) AS bad
+                FROM chat_turns
+                WHERE role = 'assistant'
+                GROUP BY prompt_variant;",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create ai_response_quality view")?;
+
+    Ok(())
+}
+
+/// Rates the session's most recent reply, replacing any earlier rating.
+/// Returns `false` if the session has no reply yet.
+pub async fn rate_last_reply(
+    conn: &Connection,
+    session_id: &str,
+    feedback: Feedback,
+    reason: Option<&str>,
+) -> Result<bool> {
+    let session_id = session_id.to_string();
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty()).map(str::to_string);
+    conn.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE chat_turns SET feedback = ?2, feedback_reason = ?3
+             WHERE id = (SELECT MAX(id) FROM chat_turns WHERE session_id = ?1 AND role = 'assistant')",
+            rusqlite::params![session_id, feedback.as_str(), reason],
+        )?;
+        Ok(updated > 0)
+    })
+    .await
+    .context("Failed to save feedback")
+}
+
+/// Feedback totals per prompt variant, most used first.
+pub async fn response_quality(conn: &Connection) -> Result<Vec<ResponseQuality>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT prompt_variant, replies, good, bad FROM ai_response_quality ORDER BY replies DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ResponseQuality {
+                    prompt_variant: row.get(0)?,
+                    replies: row.get(1)?,
+                    good: row.get(2)?,
+                    bad: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load response quality")
+}
+
+/// Coach replies with the user message they answer, oldest first, for a
+/// fine-tuning dataset. `filter` applies to the user's feedback unless a
+/// reviewer judged the reply (see `annotations::rating_sql`). Crisis
+/// responses are fixed text and never included.
+pub async fn training_pairs(conn: &Connection, filter: FeedbackFilter) -> Result<Vec<TrainingPair>> {
+    let condition = filter.condition(&rating_sql("a", "n"));
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT a.session_id, a.prompt_variant, a.model, a.prompt_version, u.content, a.content, a.feedback,
+                    a.feedback_reason
+             FROM chat_turns a
+             JOIN chat_turns u ON u.id = (
+                 SELECT MAX(id) FROM chat_turns
+                 WHERE session_id = a.session_id AND role = 'user' AND id < a.id
+             )
+             LEFT JOIN annotations n ON n.turn_id = a.id
+             WHERE a.role = 'assistant' AND a.prompt_variant IS NOT NULL
+               AND a.interrupted = 0 AND {condition}
+             ORDER BY a.id"
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(TrainingPair {
+                    session_id: row.get(0)?,
+                    prompt_variant: row.get(1)?,
+                    model: row.get(2)?,
+                    prompt_version: row.get(3)?,
+                    input: row.get(4)?,
+                    response: row.get(5)?,
+                    feedback: row.get(6)?,
+                    feedback_reason: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load training pairs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{open_memory, save_chat_turn, InputSource};
+
+    async fn exchange(conn: &Connection, input: &str, response: &str, variant: Option<&str>) {
+        save_chat_turn(conn, "s1", "user", input, InputSource::Text, None).await.unwrap();
+        save_chat_turn(conn, "s1", "assistant", response, InputSource::Text, variant).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_feedback_quality_and_training_filters() {
+        let conn = open_memory(":memory:").await.unwrap();
+        assert!(!rate_last_reply(&conn, "s1", Feedback::Good, None).await.unwrap());
+
+        exchange(&conn, "hi", "Hello, what brings you here?", Some("v1")).await;
+        rate_last_reply(&conn, "s1", Feedback::Good, None).await.unwrap();
+        exchange(&conn, "work", "You should quit.", Some("v1")).await;
+        rate_last_reply(&conn, "s1", Feedback::Bad, Some(" too directive ")).await.unwrap();
+        exchange(&conn, "sleep", "Sleep has been rough.", Some("v2")).await;
+        exchange(&conn, "I want to die", "Please call the crisis line.", None).await;
+
+        let quality = response_quality(&conn).await.unwrap();
+        let v1 = quality.iter().find(|q| q.prompt_variant.as_deref() == Some("v1")).unwrap();
+        assert_eq!((v1.replies, v1.good, v1.bad), (2, 1, 1));
+
+        let inputs = |pairs: Vec<TrainingPair>| pairs.into_iter().map(|p| p.input).collect::<Vec<_>>();
+        let good = training_pairs(&conn, FeedbackFilter::Good).await.unwrap();
+        assert_eq!(inputs(good), vec!["hi"]);
+        let not_bad = training_pairs(&conn, FeedbackFilter::NotBad).await.unwrap();
+        assert_eq!(inputs(not_bad), vec!["hi", "sleep"]);
+        let any = training_pairs(&conn, FeedbackFilter::Any).await.unwrap();
+        assert_eq!(any[1].feedback_reason.as_deref(), Some("too directive"));
+        assert_eq!(any.len(), 3);
+
+        assert_eq!(Feedback::parse("👎"), Some(Feedback::Bad));
+    }
+}
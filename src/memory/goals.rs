@@ -0,0 +1,152 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Maximum number of active goals injected into the preamble.
+const MAX_PROMPT_GOALS: usize = 3;
+
+/// A goal the user has set for themselves. Goals belong to the user, not a
+/// session: they stay active across sessions until completed.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Goal {
+    pub id: i64,
+    pub description: String,
+    /// Session the goal was set in.
+    pub session_id: String,
+    pub completed: bool,
+    pub created_at: String,
+}
+
+/// Creates the goals table if it doesn't exist.
+pub async fn create_goals_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                description TEXT NOT NULL,
+                completed_at TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create goals table")?;
+
+    Ok(())
+}
+
+/// Adds a new active goal and returns its ID.
+pub async fn add_goal(conn: &Connection, session_id: &str, description: &str) -> Result<i64> {
+    let session_id = session_id.to_string();
+    let description = description.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO goals (session_id, description) VALUES (?1, ?2)",
+            [session_id, description],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to add goal")
+}
+
+/// Lists goals, oldest first. With `active_only`, completed goals are skipped.
+pub async fn list_goals(conn: &Connection, active_only: bool) -> Result<Vec<Goal>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, description, completed_at IS NOT NULL, created_at FROM goals
+             WHERE ?1 = 0 OR completed_at IS NULL
+             ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([active_only], |row| {
+                Ok(Goal {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    description: row.get(2)?,
+                    completed: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to list goals")
+}
+
+/// Marks a goal completed. Returns false if no active goal has that ID.
+pub async fn complete_goal(conn: &Connection, id: i64) -> Result<bool> {
+    conn.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE goals SET completed_at = datetime('now')
+             WHERE id = ?1 AND completed_at IS NULL",
+            [id],
+        )?;
+        Ok(updated > 0)
+    })
+    .await
+    .context("Failed to complete goal")
+}
+
+/// Formats active goals for the preamble (most recent first, capped).
+pub fn format_goals_for_prompt(goals: &[Goal]) -> Option<String> {
+    let lines: Vec<String> = goals
+        .iter()
+        .filter(|g| !g.completed)
+        .rev()
+        .take(MAX_PROMPT_GOALS)
+        .map(|g| format!("- {}", g.description))
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_goal_lifecycle() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_goals_table(&conn).await.unwrap();
+
+        let walk = add_goal(&conn, "s1", "Walk 3 times a week").await.unwrap();
+        add_goal(&conn, "s2", "Call mom on Sundays").await.unwrap();
+
+        assert!(complete_goal(&conn, walk).await.unwrap());
+        assert!(!complete_goal(&conn, walk).await.unwrap(), "already completed");
+        assert!(!complete_goal(&conn, 999).await.unwrap());
+
+        let active = list_goals(&conn, true).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].description, "Call mom on Sundays");
+        assert_eq!(active[0].session_id, "s2");
+
+        let all = list_goals(&conn, false).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(all[0].completed);
+    }
+
+    #[test]
+    fn test_format_goals_for_prompt() {
+        let goal = |id, description: &str, completed| Goal {
+            id,
+            session_id: String::new(),
+            description: description.to_string(),
+            completed,
+            created_at: String::new(),
+        };
+        assert_eq!(format_goals_for_prompt(&[]), None);
+
+        let goals = vec![
+            goal(1, "a", false),
+            goal(2, "b", true),
+            goal(3, "c", false),
+            goal(4, "d", false),
+            goal(5, "e", false),
+        ];
+        assert_eq!(format_goals_for_prompt(&goals).unwrap(), "- e\n- d\n- c");
+    }
+}
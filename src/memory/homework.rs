@@ -0,0 +1,210 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Maximum reviewed assignments whose reflections go into the preamble.
+const MAX_PROMPT_REFLECTIONS: usize = 2;
+
+/// Something the user agreed to try between sessions, e.g. "walk after
+/// dinner twice", and how it went once reviewed.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Homework {
+    pub id: i64,
+    /// Session the homework was assigned in.
+    pub session_id: String,
+    pub assignment: String,
+    /// Due date (`YYYY-MM-DD`), if one was set.
+    pub due_date: Option<String>,
+    /// `None` until reviewed, then whether they did it.
+    pub completed: Option<bool>,
+    pub reflection: Option<String>,
+    pub created_at: String,
+}
+
+/// Creates the homework table if it doesn't exist.
+pub async fn create_homework_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS homework (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                assignment TEXT NOT NULL,
+                due_date TEXT,
+                completed INTEGER,
+                reflection TEXT,
+                reviewed_at TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create homework table")?;
+
+    Ok(())
+}
+
+/// Assigns homework and returns its ID. An identical open assignment is not
+/// duplicated; its ID is returned instead.
+pub async fn assign_homework(
+    conn: &Connection,
+    session_id: &str,
+    assignment: &str,
+    due_date: Option<&str>,
+) -> Result<i64> {
+    let session_id = session_id.to_string();
+    let assignment = assignment.trim().to_string();
+    let due_date = due_date.map(str::to_string);
+    conn.call(move |conn| {
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM homework
+                 WHERE reviewed_at IS NULL AND lower(assignment) = lower(?1)",
+                [&assignment],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+        conn.execute(
+            "INSERT INTO homework (session_id, assignment, due_date) VALUES (?1, ?2, ?3)",
+            rusqlite::params![session_id, assignment, due_date],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to assign homework")
+}
+
+/// Lists all homework, oldest first.
+pub async fn list_homework(conn: &Connection) -> Result<Vec<Homework>> {
+    query_homework(conn, "", vec![]).await
+}
+
+/// Homework to ask about at the start of `session_id`: not yet reviewed,
+/// assigned in an earlier session, and due by `today` (or undated).
+pub async fn due_for_review(conn: &Connection, session_id: &str, today: &str) -> Result<Vec<Homework>> {
+    query_homework(
+        conn,
+        "WHERE reviewed_at IS NULL AND session_id != ?1 AND (due_date IS NULL OR due_date <= ?2)",
+        vec![session_id.to_string(), today.to_string()],
+    )
+    .await
+}
+
+/// Records whether homework was done and what they noticed. Returns false if
+/// no unreviewed homework has that ID.
+pub async fn record_review(
+    conn: &Connection,
+    id: i64,
+    completed: bool,
+    reflection: Option<&str>,
+) -> Result<bool> {
+    let reflection = reflection.map(str::to_string);
+    conn.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE homework SET completed = ?2, reflection = ?3, reviewed_at = datetime('now')
+             WHERE id = ?1 AND reviewed_at IS NULL",
+            rusqlite::params![id, completed, reflection],
+        )?;
+        Ok(updated > 0)
+    })
+    .await
+    .context("Failed to record homework review")
+}
+
+/// Completed and reviewed counts, for progress indicators.
+pub fn completion_counts(homework: &[Homework]) -> (usize, usize) {
+    let reviewed: Vec<bool> = homework.iter().filter_map(|h| h.completed).collect();
+    (reviewed.iter().filter(|done| **done).count(), reviewed.len())
+}
+
+/// Formats open homework and the latest reflections for the preamble.
+pub fn format_homework_for_prompt(homework: &[Homework]) -> Option<String> {
+    let open = homework.iter().filter(|h| h.completed.is_none()).map(|h| match &h.due_date {
+        Some(due) => format!("- Homework (due {due}): {}", h.assignment),
+        None => format!("- Homework: {}", h.assignment),
+    });
+    let reviewed = homework
+        .iter()
+        .rev()
+        .filter(|h| h.completed.is_some())
+        .take(MAX_PROMPT_REFLECTIONS)
+        .map(|h| {
+            let status = if h.completed == Some(true) { "did" } else { "didn't get to" };
+            match &h.reflection {
+                Some(reflection) => format!("- Last homework, {status} \"{}\": {reflection}", h.assignment),
+                None => format!("- Last homework, {status} \"{}\"", h.assignment),
+            }
+        });
+    let lines: Vec<String> = open.chain(reviewed).collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+async fn query_homework(
+    conn: &Connection,
+    filter: &'static str,
+    params: Vec<String>,
+) -> Result<Vec<Homework>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, session_id, assignment, due_date, completed, reflection, created_at
+             FROM homework {filter} ORDER BY id"
+        ))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                Ok(Homework {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    assignment: row.get(2)?,
+                    due_date: row.get(3)?,
+                    completed: row.get(4)?,
+                    reflection: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load homework")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_assign_review_and_progress() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_homework_table(&conn).await.unwrap();
+
+        let walk = assign_homework(&conn, "s1", "Walk after dinner twice", Some("2026-03-05")).await.unwrap();
+        let again = assign_homework(&conn, "s1", "walk after dinner twice", None).await.unwrap();
+        assert_eq!(walk, again, "open duplicates are not re-added");
+        let journal = assign_homework(&conn, "s1", "Write down one good thing a day", None).await.unwrap();
+        assign_homework(&conn, "s2", "Call a friend", None).await.unwrap();
+
+        // Only earlier sessions' homework that is due (or undated) comes up
+        let due = due_for_review(&conn, "s2", "2026-03-04").await.unwrap();
+        assert_eq!(due.iter().map(|h| h.id).collect::<Vec<_>>(), vec![journal]);
+        let due = due_for_review(&conn, "s2", "2026-03-05").await.unwrap();
+        assert_eq!(due.len(), 2);
+
+        assert!(record_review(&conn, walk, true, Some("Slept better")).await.unwrap());
+        assert!(!record_review(&conn, walk, false, None).await.unwrap(), "already reviewed");
+        assert!(record_review(&conn, journal, false, None).await.unwrap());
+
+        let all = list_homework(&conn).await.unwrap();
+        assert_eq!(completion_counts(&all), (1, 2));
+        assert_eq!(
+            format_homework_for_prompt(&all).unwrap(),
+            "- Homework: Call a friend\n\
+             - Last homework, didn't get to \"Write down one good thing a day\"\n\
+             - Last homework, did \"Walk after dinner twice\": Slept better"
+        );
+        assert!(format_homework_for_prompt(&[]).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Tables whose rows belong to a session and follow its messages.
+pub(super) const SESSION_TABLES: [&str; 11] = [
+    "case_notes",
+    "goals",
+    "homework",
+    "modality_choices",
+    "thought_records",
+    "coping_ratings",
+    "session_quality",
+    "relaxation_log",
+    "warning_flags",
+    "reply_variants",
+    "annotations",
+];
+
+/// What `merge_sessions` changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionMerge {
+    /// Messages moved into the kept session.
+    pub moved: usize,
+    /// Messages present in both sessions, kept once.
+    pub duplicates: usize,
+    /// Case notes, ratings, and other session records moved.
+    pub records: usize,
+}
+
+/// Merges session `from` into `into`, e.g. when one conversation was
+/// continued as separate sessions on two machines. Messages are unioned:
+/// each keeps its ID, a message saved in both (same role, text, and time)
+/// is kept once, preferring the copy updated last, and the transcript is
+/// ordered by time. Session records (case notes, ratings, homework, ...)
+/// move with the messages and `from` disappears. Moved messages count as
+/// updated, so `chiron sync` carries the merge to other devices. A thread
+/// (`--thread`) that continued `from` continues `into`, and episode links
+/// follow it too.
+///
+/// Returns `None` (and changes nothing) unless both sessions are indexed,
+/// distinct, and not archived.
+pub async fn merge_sessions(conn: &Connection, into: &str, from: &str) -> Result<Option<SessionMerge>> {
+    if into == from {
+        return Ok(None);
+    }
+    let into = into.to_string();
+    let from = from.to_string();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        for session_id in [&into, &from] {
+            let indexed = tx
+                .prepare("SELECT 1 FROM session_index WHERE session_id = ?1")?
+                .exists([session_id])?;
+            let archived = tx
+                .prepare("SELECT 1 FROM session_archives WHERE session_id = ?1")?
+                .exists([session_id])?;
+            if !indexed || archived {
+                return Ok(None);
+            }
+        }
+
+        // Of a message saved in both, the copy updated last survives (the
+        // kept session's on a tie)
+        let duplicates: Vec<(i64, i64, bool)> = tx
+            .prepare(
+                "SELECT f.id, MIN(i.id), f.updated_at > MIN(i.updated_at)
+                 FROM chat_turns f
+                 JOIN chat_turns i ON i.session_id = ?1 AND i.role = f.role
+                     AND i.content = f.content AND i.created_at = f.created_at
+                 WHERE f.session_id = ?2
+                 GROUP BY f.id",
+            )?
+            .query_map([&into, &from], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for (from_id, into_id, from_is_newer) in &duplicates {
+            let loser = if *from_is_newer { into_id } else { from_id };
+            tx.execute("DELETE FROM chat_turns WHERE id = ?1", [loser])?;
+        }
+
+        let moved = tx.execute(
+            "UPDATE chat_turns SET session_id = ?1, updated_at = datetime('now') WHERE session_id = ?2",
+            [&into, &from],
+        )?;
+        let mut records = 0;
+        for table in SESSION_TABLES {
+            records += tx.execute(
+                &format!("UPDATE {table} SET session_id = ?1 WHERE session_id = ?2"),
+                [&into, &from],
+            )?;
+        }
+        // A named thread or Telegram chat that continued `from` continues
+        // `into` now
+        tx.execute(
+            "UPDATE threads SET session_id = ?1, updated_at = datetime('now') WHERE session_id = ?2",
+            [&into, &from],
+        )?;
+        tx.execute(
+            "UPDATE telegram_chats SET session_id = ?1, updated_at = datetime('now') WHERE session_id = ?2",
+            [&into, &from],
+        )?;
+        // A keeps its name; if it has none, it takes B's
+        tx.execute("UPDATE OR IGNORE session_names SET session_id = ?1 WHERE session_id = ?2", [&into, &from])?;
+        tx.execute("DELETE FROM session_names WHERE session_id = ?2", [&into, &from])?;
+        // Episode links follow too; merging an episode into the one it
+        // follows drops the link
+        for statement in [
+            "UPDATE OR IGNORE episodes SET session_id = ?1 WHERE session_id = ?2",
+            "DELETE FROM episodes WHERE session_id = ?2",
+            "UPDATE episodes SET previous_session_id = ?1 WHERE previous_session_id = ?2",
+            "DELETE FROM episodes WHERE session_id = ?1 AND previous_session_id = ?1",
+        ] {
+            tx.execute(statement, [&into, &from])?;
+        }
+        super::reindex_session(&tx, &into)?;
+        super::reindex_session(&tx, &from)?;
+        tx.commit()?;
+        Ok(Some(SessionMerge {
+            moved,
+            duplicates: duplicates.len(),
+            records,
+        }))
+    })
+    .await
+    .context("Failed to merge sessions")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::sync::{merge_messages, session_messages, SyncMessage};
+    use crate::memory::{case_notes, episodes, list_sessions, load_session_turns, open_memory, threads};
+
+    fn message(uid: &str, role: &str, content: &str, created_at: &str) -> SyncMessage {
+        SyncMessage {
+            uid: uid.to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: created_at.to_string(),
+            updated_at: created_at.to_string(),
+            source: "text".to_string(),
+            prompt_variant: None,
+            interrupted: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_unions_messages_and_moves_records() {
+        let conn = open_memory(":memory:").await.unwrap();
+        let laptop = vec![
+            message("a1", "user", "hi", "2026-10-01 09:00:00"),
+            message("a2", "assistant", "hello", "2026-10-01 09:00:05"),
+            message("a3", "user", "work again", "2026-10-01 09:10:00"),
+        ];
+        let mut desktop = vec![
+            message("b1", "user", "hi", "2026-10-01 09:00:00"),
+            message("b2", "assistant", "hello", "2026-10-01 09:00:05"),
+            message("b3", "user", "and sleep", "2026-10-01 09:05:00"),
+        ];
+        // The desktop copy of the reply was later marked interrupted
+        desktop[1].interrupted = true;
+        desktop[1].updated_at = "2026-10-01 09:20:00".to_string();
+        merge_messages(&conn, "laptop", laptop).await.unwrap();
+        merge_messages(&conn, "desktop", desktop).await.unwrap();
+        case_notes::save_case_note(&conn, "desktop", 1, Some("focus"), "MI Stage: focus").await.unwrap();
+        threads::set_thread_session(&conn, "work", "desktop").await.unwrap();
+        episodes::start_episode(&conn, "desktop", "september", "Last talked 2026-09-01").await.unwrap();
+
+        let merge = merge_sessions(&conn, "laptop", "desktop").await.unwrap();
+        assert_eq!(merge, Some(SessionMerge { moved: 2, duplicates: 2, records: 1 }));
+
+        let turns = load_session_turns(&conn, "laptop").await.unwrap();
+        let contents: Vec<_> = turns.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, vec!["hi", "hello", "and sleep", "work again"]);
+        assert!(turns[1].interrupted, "the copy updated last wins");
+        let uids: Vec<_> = session_messages(&conn, "laptop").await.unwrap().into_iter().map(|m| m.uid).collect();
+        assert_eq!(uids, vec!["a1", "b2", "b3", "a3"]);
+
+        let sessions = list_sessions(&conn).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 4);
+        let notes = case_notes::case_notes_since(&conn, "0000-01-01").await.unwrap();
+        assert_eq!(notes[0].session_id, "laptop");
+        assert_eq!(threads::thread_session(&conn, "work").await.unwrap().as_deref(), Some("laptop"));
+        let episode = episodes::episode(&conn, "laptop").await.unwrap().unwrap();
+        assert_eq!(episode.previous_session_id, "september");
+
+        assert_eq!(merge_sessions(&conn, "laptop", "desktop").await.unwrap(), None);
+        assert_eq!(merge_sessions(&conn, "laptop", "laptop").await.unwrap(), None);
+    }
+}
@@ -1,12 +1,45 @@
+pub mod annotations;
+pub mod archive;
 pub mod case_notes;
+pub mod coherence;
+pub mod consent;
+pub mod coping;
+pub mod documents;
 pub mod embeddings;
+pub mod engagement;
+pub mod episodes;
+pub mod feedback;
+pub mod goals;
+pub mod homework;
+pub mod merge;
+pub mod modality;
+pub mod mood;
+pub mod observe;
+pub mod outcomes;
+pub mod preferences;
+pub mod relaxation;
+pub mod reply_variants;
 pub mod retrieval;
+pub mod risk_history;
+pub mod safety_plan;
 pub mod seed;
+pub mod session_names;
+pub mod sleep;
+pub mod sync;
+pub mod telegram_chats;
+pub mod thought_records;
+pub mod threads;
+pub mod usage;
+pub mod values;
 pub mod vectors;
+pub mod warning_signs;
+pub mod writer;
 
-use anyhow::{Context, Result};
+use rusqlite::OptionalExtension;
 use tokio_rusqlite::Connection;
 
+use crate::error::{Context, Result};
+
 /// Opens the database and creates tables for chat history and case notes.
 #[tracing::instrument(level = "info")]
 pub async fn open_memory(db_path: &str) -> Result<Connection> {
@@ -30,7 +63,25 @@ pub async fn open_memory(db_path: &str) -> Result<Connection> {
                 session_id TEXT NOT NULL,
                 role TEXT NOT NULL CHECK(role IN ('user', 'assistant')),
                 content TEXT NOT NULL,
-                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                source TEXT NOT NULL DEFAULT 'text',
+                prompt_variant TEXT,
+                interrupted INTEGER NOT NULL DEFAULT 0,
+                prompt_tokens INTEGER,
+                response_tokens INTEGER,
+                first_token_ms INTEGER,
+                latency_ms INTEGER,
+                uid TEXT,
+                updated_at TEXT,
+                feedback TEXT,
+                feedback_reason TEXT,
+                emotions TEXT,
+                response_latency_ms INTEGER,
+                engagement REAL,
+                model TEXT,
+                backend TEXT,
+                prompt_version TEXT,
+                agent TEXT
             );
             CREATE INDEX IF NOT EXISTS idx_chat_turns_session
                 ON chat_turns(session_id, created_at);",
@@ -40,29 +91,186 @@ pub async fn open_memory(db_path: &str) -> Result<Connection> {
     .await
     .context("Failed to create chat_turns table")?;
 
+    // Databases created before input sources (or prompt variants,
+    // interrupted replies, reply usage, sync IDs, feedback, emotions,
+    // engagement, or provenance) were tracked lack the columns
+    conn.call(|conn| {
+        for (column, definition) in [
+            ("source", "source TEXT NOT NULL DEFAULT 'text'"),
+            ("prompt_variant", "prompt_variant TEXT"),
+            ("interrupted", "interrupted INTEGER NOT NULL DEFAULT 0"),
+            ("prompt_tokens", "prompt_tokens INTEGER"),
+            ("response_tokens", "response_tokens INTEGER"),
+            ("first_token_ms", "first_token_ms INTEGER"),
+            ("latency_ms", "latency_ms INTEGER"),
+            ("uid", "uid TEXT"),
+            ("updated_at", "updated_at TEXT"),
+            ("feedback", "feedback TEXT"),
+            ("feedback_reason", "feedback_reason TEXT"),
+            ("emotions", "emotions TEXT"),
+            ("response_latency_ms", "response_latency_ms INTEGER"),
+            ("engagement", "engagement REAL"),
+            ("model", "model TEXT"),
+            ("backend", "backend TEXT"),
+            ("prompt_version", "prompt_version TEXT"),
+            ("agent", "agent TEXT"),
+        ] {
+            let exists = conn
+                .prepare("SELECT 1 FROM pragma_table_info('chat_turns') WHERE name = ?1")?
+                .exists([column])?;
+            if !exists {
+                conn.execute_batch(&format!("ALTER TABLE chat_turns ADD COLUMN {definition}"))?;
+            }
+        }
+        // Messages saved before sync existed get an ID and a timestamp
+        conn.execute_batch(
+            "UPDATE chat_turns SET uid = lower(hex(randomblob(16))) WHERE uid IS NULL;
+             UPDATE chat_turns SET updated_at = created_at WHERE updated_at IS NULL;
+             CREATE UNIQUE INDEX IF NOT EXISTS idx_chat_turns_uid ON chat_turns(uid);",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to migrate chat_turns table")?;
+
+    // Per-session summary kept up to date by `save_chat_turn`, so listing
+    // sessions doesn't aggregate every saved turn
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_index (
+                session_id TEXT PRIMARY KEY,
+                message_count INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                last_active_at TEXT NOT NULL,
+                last_turn_id INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_session_index_last_turn
+                ON session_index(last_turn_id);
+            INSERT INTO session_index
+                SELECT session_id, COUNT(*), MIN(created_at), MAX(created_at), MAX(id)
+                FROM chat_turns
+                WHERE NOT EXISTS (SELECT 1 FROM session_index)
+                GROUP BY session_id;",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create session_index table")?;
+
+    // Create ai_response_quality view (feedback per prompt variant)
+    feedback::create_response_quality_view(&conn).await?;
+
     // Create case_notes table
     case_notes::create_case_notes_table(&conn).await?;
 
-    tracing::info!("Memory initialized (chat history + case notes)");
+    // Create mood_log table (daily check-ins)
+    mood::create_mood_log_table(&conn).await?;
+
+    // Create goals table
+    goals::create_goals_table(&conn).await?;
+
+    // Create homework table
+    homework::create_homework_table(&conn).await?;
+
+    // Create modality_choices table
+    modality::create_modality_table(&conn).await?;
+
+    // Create thought_records table
+    thought_records::create_thought_records_table(&conn).await?;
+
+    // Create coping_ratings table
+    coping::create_coping_table(&conn).await?;
+
+    // Create session_quality table (ORS/SRS ratings)
+    outcomes::create_session_quality_table(&conn).await?;
+
+    // Create sleep_diary table
+    sleep::create_sleep_diary_table(&conn).await?;
+
+    // Create value_ratings table
+    values::create_values_table(&conn).await?;
+
+    // Create warning_signs + warning_flags tables
+    warning_signs::create_warning_signs_tables(&conn).await?;
+
+    // Create relaxation_log table
+    relaxation::create_relaxation_table(&conn).await?;
+
+    // Create safety_plan table
+    safety_plan::create_safety_plan_table(&conn).await?;
+
+    // Create session_archives table (summaries of archived transcripts)
+    archive::create_session_archives_table(&conn).await?;
+
+    // Create sync_state table (device ID, last push, seen blobs)
+    sync::create_sync_state_table(&conn).await?;
+
+    // Create preferences table (reply length, ...)
+    preferences::create_preferences_table(&conn).await?;
+
+    // Create threads table (named conversations)
+    threads::create_threads_table(&conn).await?;
+
+    // Create telegram_chats table (the session each Telegram chat is in)
+    telegram_chats::create_telegram_chats_table(&conn).await?;
+
+    // Create episodes table (sessions split from a thread after a long gap)
+    episodes::create_episodes_table(&conn).await?;
+
+    // Create reply_variants table (replies regenerated with /retry)
+    reply_variants::create_reply_variants_table(&conn).await?;
+
+    // Create annotations table (clinician reviews of replies)
+    annotations::create_annotations_table(&conn).await?;
+
+    // Create session_names table (names users give sessions)
+    session_names::create_session_names_table(&conn).await?;
+
+    // Create session_coherence table (how well replies follow messages)
+    coherence::create_session_coherence_table(&conn).await?;
+
+    tracing::info!("Memory initialized (chat history, case notes, mood log, goals, homework, modality, thought records, coping, session ratings, sleep diary, values, warning signs, safety plan, relaxation, session archives, sync state, preferences, threads, Telegram chats, episodes, reply variants, annotations, session names, session coherence)");
     Ok(conn)
 }
 
-/// Saves a single chat turn to the database.
+/// How a user message was entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputSource {
+    #[default]
+    Text,
+    /// Transcribed from speech (`--voice`).
+    Voice,
+}
+
+impl InputSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InputSource::Text => "text",
+            InputSource::Voice => "voice",
+        }
+    }
+}
+
+/// Saves a single chat turn to the database. `prompt_variant` is the coach
+/// prompt variant that produced an assistant message (`None` for user
+/// messages and hardcoded responses).
 pub async fn save_chat_turn(
     conn: &Connection,
     session_id: &str,
     role: &str,
     content: &str,
+    source: InputSource,
+    prompt_variant: Option<&str>,
 ) -> Result<()> {
     let session_id = session_id.to_string();
     let role = role.to_string();
     let content = content.to_string();
+    let prompt_variant = prompt_variant.map(str::to_string);
 
     conn.call(move |conn| {
-        conn.execute(
-            "INSERT INTO chat_turns (session_id, role, content) VALUES (?1, ?2, ?3)",
-            rusqlite::params![session_id, role, content],
-        )?;
+        let tx = conn.transaction()?;
+        insert_chat_turn(&tx, &session_id, &role, &content, source, prompt_variant.as_deref())?;
+        tx.commit()?;
         Ok(())
     })
     .await
@@ -70,3 +278,444 @@ pub async fn save_chat_turn(
 
     Ok(())
 }
+
+/// Inserts a chat turn and updates its session's index row; the caller
+/// holds the transaction. Returns the turn's ID.
+pub fn insert_chat_turn(
+    conn: &rusqlite::Connection,
+    session_id: &str,
+    role: &str,
+    content: &str,
+    source: InputSource,
+    prompt_variant: Option<&str>,
+) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO chat_turns (session_id, role, content, source, prompt_variant, uid, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, lower(hex(randomblob(16))), datetime('now'))",
+        rusqlite::params![session_id, role, content, source.as_str(), prompt_variant],
+    )?;
+    let id = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO session_index (session_id, message_count, started_at, last_active_at, last_turn_id)
+         SELECT session_id, 1, created_at, created_at, id FROM chat_turns WHERE id = ?1
+         ON CONFLICT(session_id) DO UPDATE SET
+            message_count = message_count + 1,
+            last_active_at = excluded.last_active_at,
+            last_turn_id = excluded.last_turn_id",
+        [id],
+    )?;
+    Ok(id)
+}
+
+/// A single saved chat turn.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ChatTurn {
+    pub role: String,
+    pub content: String,
+    pub created_at: String,
+    /// `text` or `voice`.
+    pub source: String,
+    /// Coach prompt variant that produced an assistant message.
+    pub prompt_variant: Option<String>,
+    /// The reply was cut off (Ctrl+C during generation) and is partial.
+    pub interrupted: bool,
+    /// Emotions found in a user message, like `anxious:0.75,sad:0.50`.
+    pub emotions: Option<String>,
+    /// What produced an assistant message, if it was recorded.
+    pub provenance: Option<Provenance>,
+}
+
+/// What produced an assistant message, so replies can be compared across
+/// models and prompt versions and generated again under the same
+/// conditions.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct Provenance {
+    /// Model file, or `mock:<scenario>`.
+    pub model: String,
+    /// `llamacpp` or `mock`.
+    pub backend: String,
+    /// Version of the coach prompt variant (see `PromptVariant::version`);
+    /// `None` for the crisis response.
+    pub prompt_version: Option<String>,
+    /// `coach` or `crisis`.
+    pub agent: String,
+}
+
+/// Columns `read_turn` reads, in order.
+const TURN_COLUMNS: &str =
+    "role, content, created_at, source, prompt_variant, interrupted, emotions, model, backend, prompt_version, agent";
+
+/// Reads a `ChatTurn` from `TURN_COLUMNS`, starting at column `first`.
+fn read_turn(row: &rusqlite::Row, first: usize) -> rusqlite::Result<ChatTurn> {
+    let model: Option<String> = row.get(first + 7)?;
+    let provenance = match model {
+        Some(model) => Some(Provenance {
+            model,
+            backend: row.get::<_, Option<String>>(first + 8)?.unwrap_or_default(),
+            prompt_version: row.get(first + 9)?,
+            agent: row.get::<_, Option<String>>(first + 10)?.unwrap_or_default(),
+        }),
+        None => None,
+    };
+    Ok(ChatTurn {
+        role: row.get(first)?,
+        content: row.get(first + 1)?,
+        created_at: row.get(first + 2)?,
+        source: row.get(first + 3)?,
+        prompt_variant: row.get(first + 4)?,
+        interrupted: row.get(first + 5)?,
+        emotions: row.get(first + 6)?,
+        provenance,
+    })
+}
+
+/// Summary of a stored chat session.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionInfo {
+    pub session_id: String,
+    /// Number of saved messages (user + assistant).
+    pub message_count: i64,
+    pub started_at: String,
+    pub last_active_at: String,
+    /// The transcript was replaced by a summary (see `archive`).
+    pub archived: bool,
+}
+
+/// Lists all sessions with saved turns, most recently active first.
+pub async fn list_sessions(conn: &Connection) -> Result<Vec<SessionInfo>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, message_count, started_at, last_active_at,
+                    session_id IN (SELECT session_id FROM session_archives)
+             FROM session_index
+             ORDER BY last_turn_id DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SessionInfo {
+                    session_id: row.get(0)?,
+                    message_count: row.get(1)?,
+                    started_at: row.get(2)?,
+                    last_active_at: row.get(3)?,
+                    archived: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to list sessions")
+}
+
+/// Rebuilds `session_index` from the saved turns and session archives, for
+/// recovery if it ever drifts (e.g. turns written by an older version).
+/// Returns the number of sessions indexed.
+pub async fn rebuild_session_index(conn: &Connection) -> Result<usize> {
+    conn.call(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM session_index", [])?;
+        let live = tx.execute(
+            "INSERT INTO session_index
+             SELECT session_id, COUNT(*), MIN(created_at), MAX(created_at), MAX(id)
+             FROM chat_turns
+             GROUP BY session_id",
+            [],
+        )?;
+        let archived = tx.execute(
+            "INSERT INTO session_index
+             SELECT session_id, message_count, started_at, last_active_at, last_turn_id
+             FROM session_archives
+             WHERE session_id NOT IN (SELECT session_id FROM session_index)",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(live + archived)
+    })
+    .await
+    .context("Failed to rebuild session index")
+}
+
+/// Deletes a session: its messages, archive, case notes, ratings, and other
+/// session records, the named thread or Telegram chat continuing it, and
+/// episode links to it. Returns the number of messages deleted, or `None`
+/// if there's no such session.
+pub async fn delete_session(conn: &Connection, session_id: &str) -> Result<Option<usize>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        let indexed = tx
+            .prepare("SELECT 1 FROM session_index WHERE session_id = ?1")?
+            .exists([&session_id])?;
+        if !indexed {
+            return Ok(None);
+        }
+        let messages = tx.execute("DELETE FROM chat_turns WHERE session_id = ?1", [&session_id])?;
+        for table in merge::SESSION_TABLES
+            .iter()
+            .chain(&["session_archives", "session_index", "session_names", "session_coherence", "threads", "telegram_chats"])
+        {
+            tx.execute(&format!("DELETE FROM {table} WHERE session_id = ?1"), [&session_id])?;
+        }
+        // The next episode's summary describes this one
+        tx.execute(
+            "DELETE FROM episodes WHERE session_id = ?1 OR previous_session_id = ?1",
+            [&session_id],
+        )?;
+        tx.commit()?;
+        Ok(Some(messages))
+    })
+    .await
+    .context("Failed to delete session")
+}
+
+/// Recomputes one session's `session_index` row from its saved turns,
+/// removing it if none are left. For changes that move or delete turns.
+fn reindex_session(conn: &rusqlite::Connection, session_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM session_index WHERE session_id = ?1", [session_id])?;
+    conn.execute(
+        "INSERT INTO session_index
+         SELECT session_id, COUNT(*), MIN(created_at), MAX(created_at), MAX(id)
+         FROM chat_turns WHERE session_id = ?1
+         GROUP BY session_id",
+        [session_id],
+    )?;
+    Ok(())
+}
+
+/// Loads all saved turns for a session in chronological order. Messages
+/// merged from another device (`chiron sync`) are placed by timestamp.
+pub async fn load_session_turns(conn: &Connection, session_id: &str) -> Result<Vec<ChatTurn>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {TURN_COLUMNS} FROM chat_turns
+             WHERE session_id = ?1 ORDER BY created_at, id"
+        ))?;
+        let rows = stmt
+            .query_map([session_id], |row| read_turn(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session turns")
+}
+
+/// Loads a session's most recent `limit` turns in chronological order,
+/// with the number of user messages in the whole session. Enough to resume
+/// it without reading the full transcript.
+pub async fn load_recent_turns(
+    conn: &Connection,
+    session_id: &str,
+    limit: usize,
+) -> Result<(Vec<ChatTurn>, i32)> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {TURN_COLUMNS} FROM chat_turns
+             WHERE session_id = ?1 ORDER BY created_at DESC, id DESC LIMIT ?2"
+        ))?;
+        let mut rows = stmt
+            .query_map(rusqlite::params![session_id, limit as i64], |row| read_turn(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.reverse();
+        let user_turns: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM chat_turns WHERE session_id = ?1 AND role = 'user'",
+            [&session_id],
+            |row| row.get(0),
+        )?;
+        Ok((rows, user_turns))
+    })
+    .await
+    .context("Failed to load recent turns")
+}
+
+/// Loads all turns saved at or after `since` (`YYYY-MM-DD`) as
+/// `(session_id, turn)`, in insertion order.
+pub async fn turns_since(conn: &Connection, since: &str) -> Result<Vec<(String, ChatTurn)>> {
+    let since = since.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT session_id, {TURN_COLUMNS} FROM chat_turns
+             WHERE created_at >= ?1 ORDER BY id"
+        ))?;
+        let rows = stmt
+            .query_map([since], |row| Ok((row.get(0)?, read_turn(row, 1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load turns")
+}
+
+/// The prompt variant of a session's first generated response, if any.
+pub async fn first_prompt_variant(conn: &Connection, session_id: &str) -> Result<Option<String>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let variant = conn
+            .query_row(
+                "SELECT prompt_variant FROM chat_turns
+                 WHERE session_id = ?1 AND prompt_variant IS NOT NULL ORDER BY id LIMIT 1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(variant)
+    })
+    .await
+    .context("Failed to look up session prompt variant")
+}
+
+/// Number of sessions with saved turns.
+pub async fn count_sessions(conn: &Connection) -> Result<usize> {
+    conn.call(|conn| {
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM session_index", [], |row| row.get(0))?;
+        Ok(count as usize)
+    })
+    .await
+    .context("Failed to count sessions")
+}
+
+/// Generated responses with the user message each one answered, as
+/// `(prompt_variant, model, input, response)` in insertion order; `model` is
+/// `None` for responses saved before provenance was recorded.
+pub async fn variant_exchanges(conn: &Connection) -> Result<Vec<(String, Option<String>, String, String)>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT a.prompt_variant, a.model, u.content, a.content
+             FROM chat_turns a
+             JOIN chat_turns u ON u.id = (
+                 SELECT MAX(id) FROM chat_turns
+                 WHERE session_id = a.session_id AND role = 'user' AND id < a.id
+             )
+             WHERE a.role = 'assistant' AND a.prompt_variant IS NOT NULL
+             ORDER BY a.id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load prompt variant exchanges")
+}
+
+/// Number of distinct sessions with activity per day over the last `days`
+/// days, as `(YYYY-MM-DD, count)`.
+pub async fn daily_session_counts(conn: &Connection, days: u32) -> Result<Vec<(String, f64)>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT date(created_at), COUNT(DISTINCT session_id) FROM chat_turns
+             WHERE created_at >= datetime('now', ?1)
+             GROUP BY date(created_at)",
+        )?;
+        let rows = stmt
+            .query_map([format!("-{days} days")], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as f64))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to count sessions per day")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_sessions_and_load_turns() {
+        let conn = open_memory(":memory:").await.unwrap();
+
+        save_chat_turn(&conn, "session_a", "user", "hello", InputSource::Voice, None).await.unwrap();
+        save_chat_turn(&conn, "session_a", "assistant", "hi there", InputSource::Text, Some("v7")).await.unwrap();
+        save_chat_turn(&conn, "session_b", "user", "later", InputSource::Text, None).await.unwrap();
+
+        let sessions = list_sessions(&conn).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "session_b", "most recent first");
+        assert_eq!(sessions[1].message_count, 2);
+
+        let turns = load_session_turns(&conn, "session_a").await.unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].role, "user");
+        assert_eq!(turns[0].source, "voice");
+        assert_eq!(turns[1].content, "hi there");
+        assert_eq!(turns[1].prompt_variant.as_deref(), Some("v7"));
+        assert_eq!(turns[1].provenance, None, "not recorded");
+        assert_eq!(count_sessions(&conn).await.unwrap(), 2);
+        assert_eq!(first_prompt_variant(&conn, "session_a").await.unwrap().as_deref(), Some("v7"));
+        assert_eq!(first_prompt_variant(&conn, "session_b").await.unwrap(), None);
+        assert_eq!(
+            variant_exchanges(&conn).await.unwrap(),
+            vec![("v7".to_string(), None, "hello".to_string(), "hi there".to_string())]
+        );
+
+        assert!(load_session_turns(&conn, "missing").await.unwrap().is_empty());
+
+        let (recent, user_turns) = load_recent_turns(&conn, "session_a", 1).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].content, "hi there");
+        assert_eq!(user_turns, 1);
+
+        let counts = daily_session_counts(&conn, 1).await.unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].1, 2.0);
+
+        assert_eq!(delete_session(&conn, "session_a").await.unwrap(), Some(2));
+        assert_eq!(delete_session(&conn, "session_a").await.unwrap(), None);
+        assert!(load_session_turns(&conn, "session_a").await.unwrap().is_empty());
+        assert_eq!(list_sessions(&conn).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_migrates_legacy_chat_turns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        let path = path.to_str().unwrap();
+
+        let legacy = Connection::open(path).await.unwrap();
+        legacy
+            .call(|conn| {
+                conn.execute_batch(
+                    "CREATE TABLE chat_turns (
+                        id INTEGER PRIMARY KEY AUTOINCREMENT,
+                        session_id TEXT NOT NULL,
+                        role TEXT NOT NULL,
+                        content TEXT NOT NULL,
+                        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+                    );
+                    INSERT INTO chat_turns (session_id, role, content) VALUES ('old', 'user', 'hi');",
+                )?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        drop(legacy);
+
+        let conn = open_memory(path).await.unwrap();
+        let turns = load_session_turns(&conn, "old").await.unwrap();
+        assert_eq!(turns[0].source, "text");
+        assert_eq!(turns[0].prompt_variant, None);
+        assert!(!turns[0].interrupted);
+
+        // Existing sessions are indexed on first open, and kept up to date after
+        save_chat_turn(&conn, "old", "assistant", "hello", InputSource::Text, None).await.unwrap();
+        let sessions = list_sessions(&conn).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 2);
+
+        // Turns the index missed are picked up by a rebuild
+        conn.call(|conn| {
+            conn.execute_batch("INSERT INTO chat_turns (session_id, role, content) VALUES ('stray', 'user', 'hi')")?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(list_sessions(&conn).await.unwrap().len(), 1);
+        assert_eq!(rebuild_session_index(&conn).await.unwrap(), 2);
+        let sessions = list_sessions(&conn).await.unwrap();
+        assert_eq!(sessions[0].session_id, "stray");
+        assert_eq!(sessions[1].message_count, 2);
+    }
+}
@@ -0,0 +1,76 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Creates the modality_choices table if it doesn't exist.
+pub async fn create_modality_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS modality_choices (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                modality TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create modality_choices table")?;
+
+    Ok(())
+}
+
+/// Records the modality chosen in a session.
+pub async fn set_modality(conn: &Connection, session_id: &str, modality: &str) -> Result<()> {
+    let session_id = session_id.to_string();
+    let modality = modality.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO modality_choices (session_id, modality) VALUES (?1, ?2)",
+            [session_id, modality],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save modality")
+}
+
+/// Returns the modality for a session: the one chosen in it, or else the
+/// most recent choice from an earlier session. `None` if never chosen.
+pub async fn current_modality(conn: &Connection, session_id: &str) -> Result<Option<String>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT modality FROM modality_choices
+             ORDER BY session_id = ?1 DESC, id DESC LIMIT 1",
+        )?;
+        let modality = stmt
+            .query_row([session_id], |row| row.get::<_, String>(0))
+            .optional()?;
+        Ok(modality)
+    })
+    .await
+    .context("Failed to load modality")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_modality_carries_into_new_sessions() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_modality_table(&conn).await.unwrap();
+
+        assert_eq!(current_modality(&conn, "s1").await.unwrap(), None);
+
+        set_modality(&conn, "s1", "cbt").await.unwrap();
+        assert_eq!(current_modality(&conn, "s2").await.unwrap().as_deref(), Some("cbt"));
+
+        set_modality(&conn, "s2", "act").await.unwrap();
+        assert_eq!(current_modality(&conn, "s2").await.unwrap().as_deref(), Some("act"));
+        assert_eq!(current_modality(&conn, "s1").await.unwrap().as_deref(), Some("cbt"));
+    }
+}
@@ -0,0 +1,147 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A daily check-in entry, stored separately from chat sessions.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CheckIn {
+    pub id: i64,
+    /// Mood rating, 1 (worst) to 10 (best).
+    pub mood: i32,
+    pub sleep_hours: Option<f64>,
+    pub highlight: Option<String>,
+    pub stressor: Option<String>,
+    pub created_at: String,
+}
+
+/// Creates the mood_log table if it doesn't exist.
+pub async fn create_mood_log_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS mood_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mood INTEGER NOT NULL CHECK(mood BETWEEN 1 AND 10),
+                sleep_hours REAL,
+                highlight TEXT,
+                stressor TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_mood_log_created
+                ON mood_log(created_at);",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create mood_log table")?;
+
+    Ok(())
+}
+
+/// Saves a check-in and returns its row ID.
+pub async fn save_checkin(
+    conn: &Connection,
+    mood: i32,
+    sleep_hours: Option<f64>,
+    highlight: Option<&str>,
+    stressor: Option<&str>,
+) -> Result<i64> {
+    let highlight = highlight.map(str::to_string);
+    let stressor = stressor.map(str::to_string);
+
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO mood_log (mood, sleep_hours, highlight, stressor) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![mood, sleep_hours, highlight, stressor],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to save check-in")
+}
+
+/// Loads check-ins from the last `days` days, oldest first.
+pub async fn recent_checkins(conn: &Connection, days: u32) -> Result<Vec<CheckIn>> {
+    query_checkins(
+        conn,
+        "WHERE created_at >= datetime('now', ?1)",
+        format!("-{days} days"),
+    )
+    .await
+}
+
+/// Loads check-ins created at or after `since` (`YYYY-MM-DD`), oldest first.
+pub async fn checkins_since(conn: &Connection, since: &str) -> Result<Vec<CheckIn>> {
+    query_checkins(conn, "WHERE created_at >= ?1", since.to_string()).await
+}
+
+async fn query_checkins(
+    conn: &Connection,
+    filter: &'static str,
+    param: String,
+) -> Result<Vec<CheckIn>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, mood, sleep_hours, highlight, stressor, created_at FROM mood_log
+             {filter} ORDER BY created_at, id"
+        ))?;
+        let rows = stmt
+            .query_map([param], |row| {
+                Ok(CheckIn {
+                    id: row.get(0)?,
+                    mood: row.get(1)?,
+                    sleep_hours: row.get(2)?,
+                    highlight: row.get(3)?,
+                    stressor: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load check-ins")
+}
+
+/// Average mood rating, or `None` if there are no check-ins.
+pub fn average_mood(checkins: &[CheckIn]) -> Option<f64> {
+    if checkins.is_empty() {
+        return None;
+    }
+    let total: i32 = checkins.iter().map(|c| c.mood).sum();
+    Some(total as f64 / checkins.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_load_checkins() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_mood_log_table(&conn).await.unwrap();
+
+        save_checkin(&conn, 4, Some(6.5), Some("walked the dog"), None)
+            .await
+            .unwrap();
+        save_checkin(&conn, 8, None, None, Some("deadline"))
+            .await
+            .unwrap();
+
+        let checkins = recent_checkins(&conn, 7).await.unwrap();
+        assert_eq!(checkins.len(), 2);
+        assert_eq!(checkins[0].mood, 4);
+        assert_eq!(checkins[0].sleep_hours, Some(6.5));
+        assert_eq!(checkins[1].stressor.as_deref(), Some("deadline"));
+        assert_eq!(average_mood(&checkins), Some(6.0));
+        assert_eq!(checkins_since(&conn, "2000-01-01").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_out_of_range_mood() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_mood_log_table(&conn).await.unwrap();
+
+        assert!(save_checkin(&conn, 11, None, None, None).await.is_err());
+        assert!(average_mood(&[]).is_none());
+    }
+}
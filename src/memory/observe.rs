@@ -0,0 +1,186 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Something saved to a session, as `chiron observe` shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Observed {
+    /// A chat message. `crisis` marks the fixed crisis response.
+    Message {
+        session_id: String,
+        role: String,
+        content: String,
+        created_at: String,
+        crisis: bool,
+        interrupted: bool,
+    },
+    /// The coach's case note after a turn.
+    CaseNote {
+        session_id: String,
+        turn_number: i32,
+        mi_stage: Option<String>,
+        /// The full note: strategy, talk type, running themes.
+        content: String,
+        created_at: String,
+    },
+    /// A message matched one of the person's early warning signs.
+    WarningSign {
+        session_id: String,
+        sign: String,
+        created_at: String,
+    },
+}
+
+/// How far an observer has read: the last row ID seen in each table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    pub turn_id: i64,
+    pub note_id: i64,
+    pub flag_id: i64,
+}
+
+/// A cursor at the end of everything saved so far, less the last
+/// `backlog` messages, so observing starts with a little context.
+pub async fn cursor_at_end(conn: &Connection, session_id: Option<&str>, backlog: usize) -> Result<Cursor> {
+    let session_id = session_id.map(str::to_string);
+    conn.call(move |conn| {
+        let max = |table: &str| -> rusqlite::Result<i64> {
+            conn.query_row(
+                &format!("SELECT COALESCE(MAX(id), 0) FROM {table} WHERE ?1 IS NULL OR session_id = ?1"),
+                [&session_id],
+                |row| row.get(0),
+            )
+        };
+        let turn_id: i64 = conn.query_row(
+            "SELECT COALESCE(MIN(id) - 1, 0) FROM (
+                 SELECT id FROM chat_turns WHERE ?1 IS NULL OR session_id = ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            rusqlite::params![session_id, backlog as i64],
+            |row| row.get(0),
+        )?;
+        let turn_id = if backlog == 0 { max("chat_turns")? } else { turn_id };
+        Ok(Cursor {
+            turn_id,
+            note_id: max("case_notes")?,
+            flag_id: max("warning_flags")?,
+        })
+    })
+    .await
+    .context("Failed to read session position")
+}
+
+/// Everything saved after `cursor` (in one session, or any), oldest first
+/// within each kind: messages, then case notes, then warning sign flags.
+/// Returns the advanced cursor.
+pub async fn observed_since(
+    conn: &Connection,
+    session_id: Option<&str>,
+    cursor: Cursor,
+) -> Result<(Vec<Observed>, Cursor)> {
+    let session_id = session_id.map(str::to_string);
+    conn.call(move |conn| {
+        let mut next = cursor;
+        let mut observed = Vec::new();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, role, content, created_at, prompt_variant IS NULL, interrupted
+             FROM chat_turns WHERE id > ?1 AND (?2 IS NULL OR session_id = ?2) ORDER BY id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![cursor.turn_id, session_id], |row| {
+            let role: String = row.get(2)?;
+            let no_variant: bool = row.get(5)?;
+            Ok((
+                row.get::<_, i64>(0)?,
+                Observed::Message {
+                    session_id: row.get(1)?,
+                    crisis: role == "assistant" && no_variant,
+                    role,
+                    content: row.get(3)?,
+                    created_at: row.get(4)?,
+                    interrupted: row.get(6)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (id, message) = row?;
+            next.turn_id = id;
+            observed.push(message);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, turn_number, mi_stage, content, created_at
+             FROM case_notes WHERE id > ?1 AND (?2 IS NULL OR session_id = ?2) ORDER BY id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![cursor.note_id, session_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                Observed::CaseNote {
+                    session_id: row.get(1)?,
+                    turn_number: row.get(2)?,
+                    mi_stage: row.get(3)?,
+                    content: row.get(4)?,
+                    created_at: row.get(5)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (id, note) = row?;
+            next.note_id = id;
+            observed.push(note);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.session_id, s.sign, f.created_at
+             FROM warning_flags f JOIN warning_signs s ON s.id = f.sign_id
+             WHERE f.id > ?1 AND (?2 IS NULL OR f.session_id = ?2) ORDER BY f.id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![cursor.flag_id, session_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                Observed::WarningSign {
+                    session_id: row.get(1)?,
+                    sign: row.get(2)?,
+                    created_at: row.get(3)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (id, flag) = row?;
+            next.flag_id = id;
+            observed.push(flag);
+        }
+
+        Ok((observed, next))
+    })
+    .await
+    .context("Failed to read session updates")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{case_notes, open_memory, save_chat_turn, InputSource};
+
+    #[tokio::test]
+    async fn test_observe_reads_only_new_rows() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "s1", "user", "hi", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "s1", "assistant", "hello", InputSource::Text, Some("v1")).await.unwrap();
+        save_chat_turn(&conn, "s2", "user", "elsewhere", InputSource::Text, None).await.unwrap();
+
+        let cursor = cursor_at_end(&conn, Some("s1"), 1).await.unwrap();
+        let (observed, cursor) = observed_since(&conn, Some("s1"), cursor).await.unwrap();
+        assert_eq!(observed.len(), 1, "{observed:?}");
+
+        save_chat_turn(&conn, "s1", "user", "I can't go on", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "s1", "assistant", "Please call 988.", InputSource::Text, None).await.unwrap();
+        case_notes::save_case_note(&conn, "s1", 2, Some("engage"), "MI Stage: engage").await.unwrap();
+        let (observed, cursor) = observed_since(&conn, Some("s1"), cursor).await.unwrap();
+        assert_eq!(observed.len(), 3);
+        assert!(matches!(&observed[1], Observed::Message { crisis: true, .. }));
+        assert!(matches!(&observed[2], Observed::CaseNote { turn_number: 2, .. }));
+
+        let (observed, _) = observed_since(&conn, Some("s1"), cursor).await.unwrap();
+        assert!(observed.is_empty());
+    }
+}
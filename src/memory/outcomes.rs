@@ -0,0 +1,108 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A session-boundary rating: the Outcome Rating Scale (`ors`, well-being,
+/// at the start) or the Session Rating Scale (`srs`, alliance, at the end).
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SessionQuality {
+    pub session_id: String,
+    /// `ors` or `srs`.
+    pub measure: String,
+    /// Sum of the four 0–10 items (0–40).
+    pub total: f64,
+    /// Item responses in order.
+    pub responses: Vec<i32>,
+    pub created_at: String,
+}
+
+/// Creates the session_quality table if it doesn't exist.
+pub async fn create_session_quality_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_quality (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                measure TEXT NOT NULL CHECK(measure IN ('ors', 'srs')),
+                total REAL NOT NULL,
+                responses TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create session_quality table")?;
+
+    Ok(())
+}
+
+/// Saves one administration of `measure` for a session.
+pub async fn save_rating(
+    conn: &Connection,
+    session_id: &str,
+    measure: &str,
+    total: f64,
+    responses: &[i32],
+) -> Result<()> {
+    let session_id = session_id.to_string();
+    let measure = measure.to_string();
+    let responses = responses.iter().map(i32::to_string).collect::<Vec<_>>().join(",");
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO session_quality (session_id, measure, total, responses) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![session_id, measure, total, responses],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save session rating")
+}
+
+/// Loads ratings of `measure` from the last `days` days, oldest first.
+pub async fn recent_ratings(conn: &Connection, measure: &str, days: u32) -> Result<Vec<SessionQuality>> {
+    let measure = measure.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, measure, total, responses, created_at FROM session_quality
+             WHERE measure = ?1 AND created_at >= datetime('now', ?2)
+             ORDER BY created_at, id",
+        )?;
+        let rows = stmt
+            .query_map(rusqlite::params![measure, format!("-{days} days")], |row| {
+                let responses: String = row.get(3)?;
+                Ok(SessionQuality {
+                    session_id: row.get(0)?,
+                    measure: row.get(1)?,
+                    total: row.get(2)?,
+                    responses: responses.split(',').filter_map(|r| r.parse().ok()).collect(),
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session ratings")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_load_ratings() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_session_quality_table(&conn).await.unwrap();
+
+        save_rating(&conn, "s1", "ors", 22.0, &[5, 6, 5, 6]).await.unwrap();
+        save_rating(&conn, "s1", "srs", 38.0, &[10, 9, 9, 10]).await.unwrap();
+        assert!(save_rating(&conn, "s1", "phq9", 3.0, &[1, 2]).await.is_err());
+
+        let ors = recent_ratings(&conn, "ors", 7).await.unwrap();
+        assert_eq!(ors.len(), 1);
+        assert_eq!(ors[0].responses, vec![5, 6, 5, 6]);
+        assert_eq!(ors[0].total, 22.0);
+        assert_eq!(recent_ratings(&conn, "srs", 7).await.unwrap()[0].session_id, "s1");
+    }
+}
@@ -0,0 +1,66 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Creates the preferences table if it doesn't exist.
+pub async fn create_preferences_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS preferences (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create preferences table")?;
+
+    Ok(())
+}
+
+/// Reads a user preference (e.g. `verbosity`). `None` if never set.
+pub async fn get_preference(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let key = key.to_string();
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row("SELECT value FROM preferences WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?)
+    })
+    .await
+    .context("Failed to load preference")
+}
+
+/// Stores a user preference, replacing any previous value.
+pub async fn set_preference(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    let key = key.to_string();
+    let value = value.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO preferences (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = datetime('now')",
+            [key, value],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save preference")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_preference_round_trip() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_preferences_table(&conn).await.unwrap();
+
+        assert_eq!(get_preference(&conn, "verbosity").await.unwrap(), None);
+        set_preference(&conn, "verbosity", "brief").await.unwrap();
+        set_preference(&conn, "verbosity", "detailed").await.unwrap();
+        assert_eq!(get_preference(&conn, "verbosity").await.unwrap().as_deref(), Some("detailed"));
+    }
+}
@@ -0,0 +1,88 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// How often a relaxation script has been completed, and when last.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RelaxationUsage {
+    pub script_id: String,
+    pub times: u32,
+    pub last_played: String,
+}
+
+/// Creates the relaxation_log table if it doesn't exist.
+pub async fn create_relaxation_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS relaxation_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                script_id TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create relaxation_log table")?;
+
+    Ok(())
+}
+
+/// Logs a completed script and how long it ran.
+pub async fn log_relaxation(conn: &Connection, session_id: &str, script_id: &str, seconds: u64) -> Result<()> {
+    let session_id = session_id.to_string();
+    let script_id = script_id.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO relaxation_log (session_id, script_id, seconds) VALUES (?1, ?2, ?3)",
+            rusqlite::params![session_id, script_id, seconds as i64],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to log relaxation")
+}
+
+/// Usage per script, most played first.
+pub async fn relaxation_usage(conn: &Connection) -> Result<Vec<RelaxationUsage>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT script_id, COUNT(*), MAX(created_at) FROM relaxation_log
+             GROUP BY script_id ORDER BY COUNT(*) DESC, script_id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(RelaxationUsage {
+                    script_id: row.get(0)?,
+                    times: row.get(1)?,
+                    last_played: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load relaxation usage")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_log_and_usage() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_relaxation_table(&conn).await.unwrap();
+
+        log_relaxation(&conn, "s1", "pmr", 360).await.unwrap();
+        log_relaxation(&conn, "s2", "pmr", 360).await.unwrap();
+        log_relaxation(&conn, "s2", "breathing", 95).await.unwrap();
+
+        let usage = relaxation_usage(&conn).await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!((usage[0].script_id.as_str(), usage[0].times), ("pmr", 2));
+        assert_eq!(usage[1].script_id, "breathing");
+    }
+}
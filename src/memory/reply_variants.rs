@@ -0,0 +1,267 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+use crate::memory::annotations::rating_sql;
+use crate::memory::feedback::FeedbackFilter;
+
+/// The session's last saved user message and the reply to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastExchange {
+    user_id: i64,
+    assistant_id: i64,
+    pub input: String,
+    pub response: String,
+    /// How the user message was entered (`text` or `voice`).
+    pub source: String,
+    /// `None` for the fixed crisis response.
+    pub prompt_variant: Option<String>,
+}
+
+/// A kept reply and one it replaced, for the same user message: a
+/// preference pair for DPO-style fine-tuning.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PreferencePair {
+    pub session_id: String,
+    pub turn_number: i32,
+    pub prompt: String,
+    pub chosen: String,
+    pub rejected: String,
+    /// What the user asked for when they got the chosen reply (`/retry
+    /// shorter`); `None` for a reviewer's alternative.
+    pub steer: Option<String>,
+}
+
+/// Creates the reply_variants table if it doesn't exist.
+pub async fn create_reply_variants_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS reply_variants (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                turn_number INTEGER NOT NULL,
+                input TEXT NOT NULL,
+                response TEXT NOT NULL,
+                steer TEXT,
+                kept INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX IF NOT EXISTS idx_reply_variants_turn
+                ON reply_variants(session_id, turn_number);",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create reply_variants table")?;
+
+    Ok(())
+}
+
+/// The session's last exchange, if its last two messages are a user
+/// message and the reply to it.
+pub async fn last_exchange(conn: &Connection, session_id: &str) -> Result<Option<LastExchange>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, role, content, source, prompt_variant FROM chat_turns
+             WHERE session_id = ?1 ORDER BY created_at DESC, id DESC LIMIT 2",
+        )?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(match rows.as_slice() {
+            [(assistant_id, reply_role, response, _, prompt_variant), (user_id, user_role, input, source, _)]
+                if reply_role == "assistant" && user_role == "user" =>
+            {
+                Some(LastExchange {
+                    user_id: *user_id,
+                    assistant_id: *assistant_id,
+                    input: input.clone(),
+                    response: response.clone(),
+                    source: source.clone(),
+                    prompt_variant: prompt_variant.clone(),
+                })
+            }
+            _ => None,
+        })
+    })
+    .await
+    .context("Failed to load last exchange")
+}
+
+/// Records a regenerated reply for turn `turn_number`: the transcript keeps
+/// the new exchange (already saved) in place of `previous`, and both
+/// replies go into `reply_variants` with only the new one marked kept. The
+/// first retry of a turn also records the original reply (with no steer).
+pub async fn record_retry(
+    conn: &Connection,
+    session_id: &str,
+    turn_number: i32,
+    previous: &LastExchange,
+    steer: Option<&str>,
+    response: &str,
+) -> Result<()> {
+    let session_id = session_id.to_string();
+    let previous = previous.clone();
+    let steer = steer.map(str::to_string);
+    let response = response.to_string();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM chat_turns WHERE id IN (?1, ?2)",
+            [previous.user_id, previous.assistant_id],
+        )?;
+        super::reindex_session(&tx, &session_id)?;
+
+        let recorded: Option<i64> = tx
+            .query_row(
+                "SELECT 1 FROM reply_variants WHERE session_id = ?1 AND turn_number = ?2 LIMIT 1",
+                rusqlite::params![session_id, turn_number],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if recorded.is_some() {
+            tx.execute(
+                "UPDATE reply_variants SET kept = 0 WHERE session_id = ?1 AND turn_number = ?2",
+                rusqlite::params![session_id, turn_number],
+            )?;
+        } else {
+            tx.execute(
+                "INSERT INTO reply_variants (session_id, turn_number, input, response) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![session_id, turn_number, previous.input, previous.response],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO reply_variants (session_id, turn_number, input, response, steer, kept)
+             VALUES (?1, ?2, ?3, ?4, ?5, 1)",
+            rusqlite::params![session_id, turn_number, previous.input, response, steer],
+        )?;
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+    .context("Failed to record regenerated reply")
+}
+
+/// Every kept reply paired with each reply it replaced, oldest first, then
+/// every reviewer's better alternative paired with the reply it improves on.
+/// `filter` applies to the kept reply's rating (see
+/// `annotations::rating_sql`), so with `NotBad` a reply kept and then rated
+/// bad yields no pairs. Reviewer alternatives are always included.
+pub async fn preference_pairs(conn: &Connection, filter: FeedbackFilter) -> Result<Vec<PreferencePair>> {
+    let condition = filter.condition(&rating_sql("t", "n"));
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT k.session_id, k.turn_number, k.input, k.response, r.response, k.steer
+             FROM reply_variants k
+             JOIN reply_variants r ON r.session_id = k.session_id AND r.turn_number = k.turn_number
+                 AND r.kept = 0 AND r.response <> k.response
+             LEFT JOIN chat_turns t ON t.id = (
+                 SELECT MAX(id) FROM chat_turns
+                 WHERE session_id = k.session_id AND role = 'assistant' AND content = k.response
+             )
+             LEFT JOIN annotations n ON n.turn_id = t.id
+             WHERE k.kept = 1 AND {condition}
+             ORDER BY k.id, r.id"
+        ))?;
+        let pair = |row: &rusqlite::Row| -> rusqlite::Result<PreferencePair> {
+            Ok(PreferencePair {
+                session_id: row.get(0)?,
+                turn_number: row.get(1)?,
+                prompt: row.get(2)?,
+                chosen: row.get(3)?,
+                rejected: row.get(4)?,
+                steer: row.get(5)?,
+            })
+        };
+        let mut rows = stmt.query_map([], pair)?.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT a.session_id,
+                    (SELECT COUNT(*) FROM chat_turns
+                     WHERE session_id = a.session_id AND role = 'assistant' AND id <= a.id),
+                    u.content, n.alternative, a.content, NULL
+             FROM annotations n
+             JOIN chat_turns a ON a.id = n.turn_id
+             JOIN chat_turns u ON u.id = (
+                 SELECT MAX(id) FROM chat_turns
+                 WHERE session_id = a.session_id AND role = 'user' AND id < a.id
+             )
+             WHERE n.alternative IS NOT NULL AND n.alternative <> a.content
+             ORDER BY a.id",
+        )?;
+        rows.extend(stmt.query_map([], pair)?.collect::<Result<Vec<_>, _>>()?);
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load preference pairs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::feedback::{rate_last_reply, Feedback};
+    use crate::memory::{list_sessions, load_session_turns, open_memory, save_chat_turn, InputSource};
+
+    async fn exchange(conn: &Connection, input: &str, response: &str) {
+        save_chat_turn(conn, "s1", "user", input, InputSource::Voice, None).await.unwrap();
+        save_chat_turn(conn, "s1", "assistant", response, InputSource::Text, Some("v1")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retry_replaces_reply_and_records_variants() {
+        let conn = open_memory(":memory:").await.unwrap();
+        assert_eq!(last_exchange(&conn, "s1").await.unwrap(), None);
+        exchange(&conn, "hi", "hello").await;
+        exchange(&conn, "work is a lot", "You should take a break.").await;
+
+        let first = last_exchange(&conn, "s1").await.unwrap().unwrap();
+        assert_eq!(first.input, "work is a lot");
+        assert_eq!(first.source, "voice");
+        exchange(&conn, "work is a lot", "It's been piling up.").await;
+        record_retry(&conn, "s1", 2, &first, Some("shorter"), "It's been piling up.").await.unwrap();
+
+        let second = last_exchange(&conn, "s1").await.unwrap().unwrap();
+        exchange(&conn, "work is a lot", "Work feels heavy.").await;
+        record_retry(&conn, "s1", 2, &second, None, "Work feels heavy.").await.unwrap();
+
+        let contents: Vec<_> = load_session_turns(&conn, "s1").await.unwrap().into_iter().map(|t| t.content).collect();
+        assert_eq!(contents, vec!["hi", "hello", "work is a lot", "Work feels heavy."]);
+        assert_eq!(list_sessions(&conn).await.unwrap()[0].message_count, 4);
+
+        let variants: Vec<(String, Option<String>, bool)> = conn
+            .call(|conn| {
+                let mut stmt = conn.prepare("SELECT response, steer, kept FROM reply_variants ORDER BY id")?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            variants,
+            vec![
+                ("You should take a break.".to_string(), None, false),
+                ("It's been piling up.".to_string(), Some("shorter".to_string()), false),
+                ("Work feels heavy.".to_string(), None, true),
+            ]
+        );
+
+        let pairs = preference_pairs(&conn, FeedbackFilter::NotBad).await.unwrap();
+        let rejected: Vec<_> = pairs.iter().map(|p| p.rejected.as_str()).collect();
+        assert_eq!(rejected, vec!["You should take a break.", "It's been piling up."]);
+        assert!(pairs.iter().all(|p| p.prompt == "work is a lot" && p.chosen == "Work feels heavy."));
+        assert!(preference_pairs(&conn, FeedbackFilter::Good).await.unwrap().is_empty());
+        rate_last_reply(&conn, "s1", Feedback::Bad, None).await.unwrap();
+        assert!(preference_pairs(&conn, FeedbackFilter::NotBad).await.unwrap().is_empty());
+    }
+}
@@ -1,8 +1,15 @@
-use anyhow::Result;
 use rig::vector_store::VectorStoreIndex;
 use rig_lancedb::LanceDBFilter;
 
-use super::vectors::{self, MiKnowledge, SessionSummary, SignificantTurn, UserFact};
+use super::vectors::{
+    self, DocumentChunk, MiKnowledge, SessionSummary, SignificantTurn, UserFact,
+};
+use crate::error::{ChironError, Context, Result};
+
+/// Cosine distance beyond which a document chunk isn't relevant enough to
+/// inject. Documents are there to be drawn on when the conversation turns
+/// to them, not on every turn.
+const DOCUMENT_DISTANCE_THRESHOLD: f64 = 0.35;
 
 /// Collected RAG context from all vector store tables.
 #[derive(Debug, Default)]
@@ -11,6 +18,7 @@ pub struct RetrievalContext {
     pub session_summaries: Vec<SessionSummary>,
     pub significant_turns: Vec<SignificantTurn>,
     pub mi_knowledge: Vec<MiKnowledge>,
+    pub user_documents: Vec<DocumentChunk>,
 }
 
 /// Retrieves relevant context from all vector store tables for a given query.
@@ -24,11 +32,12 @@ pub async fn retrieve_context(
     mi_stage: Option<&str>,
     top_k: usize,
 ) -> RetrievalContext {
-    let (user_facts, session_summaries, significant_turns, mi_knowledge) = tokio::join!(
+    let (user_facts, session_summaries, significant_turns, mi_knowledge, user_documents) = tokio::join!(
         query_user_facts(conn, embedding_model, query, top_k),
         query_session_summaries(conn, embedding_model, query, top_k),
         query_significant_turns(conn, embedding_model, query, top_k),
         query_mi_knowledge(conn, embedding_model, query, mi_stage, top_k),
+        query_user_documents(conn, embedding_model, query, top_k),
     );
 
     RetrievalContext {
@@ -36,13 +45,14 @@ pub async fn retrieve_context(
         session_summaries: log_retrieval_err("session_summaries", session_summaries),
         significant_turns: log_retrieval_err("significant_turns", significant_turns),
         mi_knowledge: log_retrieval_err("mi_knowledge", mi_knowledge),
+        user_documents: log_retrieval_err("user_documents", user_documents),
     }
 }
 
 /// Formats retrieved context into structured preamble sections.
 ///
-/// Sections are built in priority order (user facts > significant turns >
-/// session summaries > MI knowledge). If `max_chars` is exceeded, remaining
+/// Sections are built in priority order (user facts > user documents >
+/// significant turns > session summaries > MI knowledge). If `max_chars` is exceeded, remaining
 /// sections are omitted. Returns `None` if all sections are empty.
 pub fn format_rag_context(ctx: &RetrievalContext, max_chars: usize) -> Option<String> {
     let mut sections = Vec::new();
@@ -52,6 +62,7 @@ pub fn format_rag_context(ctx: &RetrievalContext, max_chars: usize) -> Option<St
     // Build sections in priority order, stop when budget exhausted
     let candidate_sections = [
         build_user_facts_section(&ctx.user_facts),
+        build_user_documents_section(&ctx.user_documents),
         build_significant_turns_section(&ctx.significant_turns),
         build_session_summaries_section(&ctx.session_summaries),
         build_mi_knowledge_section(&ctx.mi_knowledge),
@@ -82,6 +93,15 @@ fn build_user_facts_section(facts: &[UserFact]) -> Option<String> {
     Some(s)
 }
 
+fn build_user_documents_section(chunks: &[DocumentChunk]) -> Option<String> {
+    if chunks.is_empty() { return None; }
+    let mut s = String::from("## From Their Documents\nCite the source in parentheses when you draw on one.");
+    for chunk in chunks {
+        s.push_str(&format!("\n- \"{}\" ({})", chunk.content, chunk.citation()));
+    }
+    Some(s)
+}
+
 fn build_significant_turns_section(turns: &[SignificantTurn]) -> Option<String> {
     if turns.is_empty() { return None; }
     let mut s = String::from("## Relevant Past Moments");
@@ -132,7 +152,7 @@ async fn query_user_facts(
 ) -> Result<Vec<UserFact>> {
     let index = vectors::vector_index(conn, "user_knowledge", model.clone()).await?;
     let request = build_request(query, top_k)?;
-    let results: Vec<(f64, String, UserFact)> = index.top_n(request).await?;
+    let results: Vec<(f64, String, UserFact)> = index.top_n(request).await.context("Vector search failed")?;
     Ok(results.into_iter().map(|(_, _, fact)| fact).collect())
 }
 
@@ -144,7 +164,7 @@ async fn query_session_summaries(
 ) -> Result<Vec<SessionSummary>> {
     let index = vectors::vector_index(conn, "session_summaries", model.clone()).await?;
     let request = build_request(query, top_k)?;
-    let results: Vec<(f64, String, SessionSummary)> = index.top_n(request).await?;
+    let results: Vec<(f64, String, SessionSummary)> = index.top_n(request).await.context("Vector search failed")?;
     Ok(results.into_iter().map(|(_, _, s)| s).collect())
 }
 
@@ -156,7 +176,7 @@ async fn query_significant_turns(
 ) -> Result<Vec<SignificantTurn>> {
     let index = vectors::vector_index(conn, "significant_turns", model.clone()).await?;
     let request = build_request(query, top_k)?;
-    let results: Vec<(f64, String, SignificantTurn)> = index.top_n(request).await?;
+    let results: Vec<(f64, String, SignificantTurn)> = index.top_n(request).await.context("Vector search failed")?;
     Ok(results.into_iter().map(|(_, _, t)| t).collect())
 }
 
@@ -172,10 +192,28 @@ async fn query_mi_knowledge(
         Some(stage) => build_filtered_request(query, top_k, stage)?,
         None => build_request(query, top_k)?,
     };
-    let results: Vec<(f64, String, MiKnowledge)> = index.top_n(request).await?;
+    let results: Vec<(f64, String, MiKnowledge)> = index.top_n(request).await.context("Vector search failed")?;
     Ok(results.into_iter().map(|(_, _, k)| k).collect())
 }
 
+/// Only chunks within `DOCUMENT_DISTANCE_THRESHOLD` of the query are
+/// returned, so nothing is injected when the documents aren't relevant.
+async fn query_user_documents(
+    conn: &lancedb::Connection,
+    model: &rig_fastembed::EmbeddingModel,
+    query: &str,
+    top_k: usize,
+) -> Result<Vec<DocumentChunk>> {
+    let index = vectors::vector_index(conn, "user_documents", model.clone()).await?;
+    let request = build_request(query, top_k)?;
+    let results: Vec<(f64, String, DocumentChunk)> = index.top_n(request).await.context("Vector search failed")?;
+    Ok(results
+        .into_iter()
+        .filter(|(distance, _, _)| *distance <= DOCUMENT_DISTANCE_THRESHOLD)
+        .map(|(_, _, c)| c)
+        .collect())
+}
+
 fn build_request(
     query: &str,
     top_k: usize,
@@ -184,7 +222,7 @@ fn build_request(
         .query(query)
         .samples(top_k as u64)
         .build()
-        .map_err(|e| anyhow::anyhow!("Failed to build search request: {e}"))
+        .map_err(|e| ChironError::vector_store("Failed to build search request", e.to_string()))
 }
 
 fn build_filtered_request(
@@ -199,7 +237,7 @@ fn build_filtered_request(
         .samples(top_k as u64)
         .filter(filter)
         .build()
-        .map_err(|e| anyhow::anyhow!("Failed to build filtered search request: {e}"))
+        .map_err(|e| ChironError::vector_store("Failed to build filtered search request", e.to_string()))
 }
 
 #[cfg(test)]
@@ -231,6 +269,7 @@ mod tests {
         assert!(ctx.session_summaries.is_empty());
         assert!(ctx.significant_turns.is_empty());
         assert!(ctx.mi_knowledge.is_empty());
+        assert!(ctx.user_documents.is_empty());
         assert!(format_rag_context(&ctx, 4000).is_none());
     }
 
@@ -306,6 +345,8 @@ mod tests {
             Some("MI Stage: evoke\nRunning Themes: drinking"),
             None,
             Some(&formatted),
+            None,
+            None,
         );
 
         // RAG context present and in correct position (before case notes)
@@ -315,6 +356,36 @@ mod tests {
         assert!(preamble.contains("cut back on drinking"), "fact content in preamble");
     }
 
+    /// Document chunks are only injected when they're close to the query.
+    #[tokio::test]
+    async fn test_documents_need_to_be_relevant() {
+        use crate::memory::embeddings::init_embedding_model;
+        use rig::embeddings::EmbeddingModel as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let conn = vectors::open_vector_db(dir.path().to_str().unwrap()).await.unwrap();
+        vectors::ensure_tables(&conn).await.unwrap();
+        let model = init_embedding_model();
+
+        let content = "No screens after 10pm, and get up at the same time every day";
+        let chunk = DocumentChunk {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: "Sleep Plan".into(),
+            source_path: "/home/user/sleep_plan.md".into(),
+            chunk_index: 0,
+            content: content.into(),
+            created_at: "2026-03-21".into(),
+            location: String::new(),
+        };
+        let embedding = model.embed_text(content).await.unwrap();
+        vectors::replace_document(&conn, &chunk.source_path, &[(chunk, embedding.vec)]).await.unwrap();
+
+        let ctx = retrieve_context(&conn, &model, content, None, 3).await;
+        assert_eq!(ctx.user_documents.len(), 1);
+        let ctx = retrieve_context(&conn, &model, "what's your favourite pizza topping", None, 3).await;
+        assert!(ctx.user_documents.is_empty());
+    }
+
     #[test]
     fn test_format_all_sections() {
         let ctx = RetrievalContext {
@@ -356,6 +427,15 @@ mod tests {
                 mi_stages: "evoke,plan".into(),
                 source: "mi_text_summary.md".into(),
             }],
+            user_documents: vec![DocumentChunk {
+                id: "5".into(),
+                title: "Sleep Plan".into(),
+                source_path: "/home/user/sleep_plan.md".into(),
+                chunk_index: 0,
+                content: "No screens after 10pm".into(),
+                created_at: "2026-03-21".into(),
+                location: "p. 2".into(),
+            }],
         };
         let formatted = format_rag_context(&ctx, 4000).unwrap();
         assert!(formatted.contains("## What You Know About This Person"));
@@ -364,5 +444,7 @@ mod tests {
         assert!(formatted.contains("## MI Technique Guidance"));
         assert!(formatted.contains("I went two days without a drink"));
         assert!(formatted.contains("[oars] Add meaning beyond"));
+        assert!(formatted.contains("## From Their Documents"));
+        assert!(formatted.contains("\"No screens after 10pm\" (Sleep Plan, sleep_plan.md, p. 2)"));
     }
 }
@@ -0,0 +1,90 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Stored signs of risk from earlier sessions, unscored: which messages
+/// were crises is decided by the caller with the current crisis check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RiskEvents {
+    /// User messages as `(session_id, created_at, content)`.
+    pub messages: Vec<(String, String, String)>,
+    /// Archived sessions with crisis messages, as `(session_id,
+    /// last_active_at, crisis_messages)`; their transcripts are gone.
+    pub archived: Vec<(String, String, i64)>,
+    /// Early warning signs flagged, as `(sign, created_at)`.
+    pub warning_flags: Vec<(String, String)>,
+    /// When a reviewer marked a reply as missing risk (`chiron review`).
+    pub missed_risk: Vec<String>,
+}
+
+/// Everything since `since` (SQLite `datetime` format) outside
+/// `session_id`, oldest first.
+pub async fn risk_events(conn: &Connection, since: &str, session_id: &str) -> Result<RiskEvents> {
+    let since = since.to_string();
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id, created_at, content FROM chat_turns
+             WHERE role = 'user' AND created_at >= ?1 AND session_id <> ?2
+             ORDER BY id",
+        )?;
+        let messages = stmt
+            .query_map([&since, &session_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT session_id, last_active_at, crisis_messages FROM session_archives
+             WHERE crisis_messages > 0 AND last_active_at >= ?1 AND session_id <> ?2
+             ORDER BY last_active_at",
+        )?;
+        let archived = stmt
+            .query_map([&since, &session_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT s.sign, f.created_at FROM warning_flags f JOIN warning_signs s ON s.id = f.sign_id
+             WHERE f.created_at >= ?1 AND f.session_id <> ?2
+             ORDER BY f.id",
+        )?;
+        let warning_flags = stmt
+            .query_map([&since, &session_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT t.created_at FROM annotations n JOIN chat_turns t ON t.id = n.turn_id
+             WHERE n.missed_risk = 1 AND t.created_at >= ?1 AND n.session_id <> ?2
+             ORDER BY t.id",
+        )?;
+        let missed_risk = stmt
+            .query_map([&since, &session_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RiskEvents { messages, archived, warning_flags, missed_risk })
+    })
+    .await
+    .context("Failed to load risk history")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{open_memory, save_chat_turn, warning_signs, InputSource};
+
+    #[tokio::test]
+    async fn test_risk_events_skip_the_current_session() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "old", "user", "I want to die", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&conn, "now", "user", "hello", InputSource::Text, None).await.unwrap();
+        let sign = warning_signs::add_sign(&conn, "not sleeping", &[]).await.unwrap();
+        warning_signs::record_flag(&conn, sign, "old", "chat").await.unwrap();
+
+        let events = risk_events(&conn, "0000-01-01 00:00:00", "now").await.unwrap();
+        assert_eq!(events.messages.len(), 1);
+        assert_eq!(events.messages[0].2, "I want to die");
+        assert_eq!(events.warning_flags.len(), 1);
+        assert!(events.archived.is_empty() && events.missed_risk.is_empty());
+
+        let events = risk_events(&conn, "9999-01-01 00:00:00", "now").await.unwrap();
+        assert_eq!(events, RiskEvents::default());
+    }
+}
@@ -0,0 +1,142 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A Stanley–Brown style safety plan. Warning signs are kept separately
+/// (see `warning_signs`) since chat and check-ins are matched against them.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct SafetyPlan {
+    /// Things I can do on my own to take my mind off things.
+    pub coping: Vec<String>,
+    /// People and places that help me feel better.
+    pub places: Vec<String>,
+    /// People I can ask for help, with how to reach them.
+    pub contacts: Vec<String>,
+    /// Professionals or services I can contact.
+    pub professionals: Vec<String>,
+    /// Ways to make my environment safer.
+    pub environment: Vec<String>,
+    /// Reasons for living.
+    pub reasons: Vec<String>,
+}
+
+impl SafetyPlan {
+    /// Section IDs as stored, in plan order.
+    pub const SECTIONS: [&'static str; 6] =
+        ["coping", "places", "contacts", "professionals", "environment", "reasons"];
+
+    /// Entries for a section ID.
+    pub fn section(&self, id: &str) -> &[String] {
+        match id {
+            "coping" => &self.coping,
+            "places" => &self.places,
+            "contacts" => &self.contacts,
+            "professionals" => &self.professionals,
+            "environment" => &self.environment,
+            "reasons" => &self.reasons,
+            _ => &[],
+        }
+    }
+
+    /// Mutable entries for a section ID.
+    pub fn section_mut(&mut self, id: &str) -> Option<&mut Vec<String>> {
+        match id {
+            "coping" => Some(&mut self.coping),
+            "places" => Some(&mut self.places),
+            "contacts" => Some(&mut self.contacts),
+            "professionals" => Some(&mut self.professionals),
+            "environment" => Some(&mut self.environment),
+            "reasons" => Some(&mut self.reasons),
+            _ => None,
+        }
+    }
+
+    /// Whether nothing has been filled in.
+    pub fn is_empty(&self) -> bool {
+        Self::SECTIONS.iter().all(|s| self.section(s).is_empty())
+    }
+}
+
+/// Creates the safety_plan table if it doesn't exist.
+pub async fn create_safety_plan_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS safety_plan (
+                section TEXT PRIMARY KEY,
+                entries TEXT NOT NULL,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create safety_plan table")?;
+
+    Ok(())
+}
+
+/// Saves the whole plan, replacing what was there.
+pub async fn save_safety_plan(conn: &Connection, plan: &SafetyPlan) -> Result<()> {
+    let rows: Vec<(&'static str, String)> = SafetyPlan::SECTIONS
+        .iter()
+        .map(|s| (*s, plan.section(s).join("\n")))
+        .collect();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        for (section, entries) in rows {
+            tx.execute(
+                "INSERT INTO safety_plan (section, entries) VALUES (?1, ?2)
+                 ON CONFLICT(section) DO UPDATE SET entries = ?2, updated_at = datetime('now')",
+                [section, entries.as_str()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save safety plan")
+}
+
+/// Loads the safety plan (empty if none has been written).
+pub async fn load_safety_plan(conn: &Connection) -> Result<SafetyPlan> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare("SELECT section, entries FROM safety_plan")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut plan = SafetyPlan::default();
+        for (section, entries) in rows {
+            if let Some(slot) = plan.section_mut(&section) {
+                *slot = entries.lines().filter(|e| !e.is_empty()).map(str::to_string).collect();
+            }
+        }
+        Ok(plan)
+    })
+    .await
+    .context("Failed to load safety plan")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_load_plan() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_safety_plan_table(&conn).await.unwrap();
+        assert!(load_safety_plan(&conn).await.unwrap().is_empty());
+
+        let mut plan = SafetyPlan {
+            coping: vec!["Walk the dog".into(), "Cold water on my face".into()],
+            contacts: vec!["Sam: 555-0100".into()],
+            ..Default::default()
+        };
+        save_safety_plan(&conn, &plan).await.unwrap();
+        assert_eq!(load_safety_plan(&conn).await.unwrap(), plan);
+
+        plan.contacts.clear();
+        plan.reasons.push("My kids".into());
+        save_safety_plan(&conn, &plan).await.unwrap();
+        assert_eq!(load_safety_plan(&conn).await.unwrap(), plan, "sections are replaced");
+    }
+}
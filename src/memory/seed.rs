@@ -1,8 +1,8 @@
-use anyhow::{Context, Result};
 use rig::embeddings::EmbeddingModel as _;
 use rig_fastembed::EmbeddingModel;
 
 use super::vectors::{self, MiKnowledge};
+use crate::error::{ChironError, Result};
 
 /// Parsed MI knowledge entry before embedding.
 #[derive(Debug, Clone)]
@@ -100,7 +100,7 @@ pub async fn seed_knowledge(
         let embedding = model
             .embed_text(&entry.content)
             .await
-            .with_context(|| format!("Failed to embed: {}", &entry.content[..40.min(entry.content.len())]))?;
+            .map_err(|e| ChironError::vector_store(format!("Failed to embed: {}", &entry.content[..40.min(entry.content.len())]), e))?;
 
         let knowledge = MiKnowledge {
             id: uuid::Uuid::new_v4().to_string(),
@@ -113,7 +113,7 @@ pub async fn seed_knowledge(
 
         vectors::add_mi_knowledge(conn, &knowledge, &embedding.vec)
             .await
-            .with_context(|| format!("Failed to insert knowledge entry: {}", &entry.content[..40.min(entry.content.len())]))?;
+            .map_err(|e| ChironError::vector_store(format!("Failed to insert knowledge entry: {}", &entry.content[..40.min(entry.content.len())]), e))?;
 
         count += 1;
     }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Length of a short session ID: `session_3f2a9c1b…` is `3f2a9c1b`.
+pub const SHORT_ID_LEN: usize = 8;
+
+/// What a session reference typed by the user matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionRef {
+    Found(String),
+    NotFound,
+    /// Several sessions match; their IDs, most recently active first.
+    Ambiguous(Vec<String>),
+}
+
+/// Creates the session_names table if it doesn't exist.
+pub async fn create_session_names_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS session_names (
+                session_id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create session_names table")?;
+
+    Ok(())
+}
+
+/// The short form of a session ID shown in listings: the start of the part
+/// after the prefix.
+pub fn short_id(session_id: &str) -> &str {
+    let random = session_id.rsplit_once('_').map_or(session_id, |(_, random)| random);
+    random.get(..SHORT_ID_LEN).unwrap_or(random)
+}
+
+/// Names a session, replacing its previous name. Returns false if another
+/// session already has the name (names ignore case).
+pub async fn set_session_name(conn: &Connection, session_id: &str, name: &str) -> Result<bool> {
+    let session_id = session_id.to_string();
+    let name = name.trim().to_string();
+    conn.call(move |conn| {
+        let taken = conn
+            .prepare("SELECT 1 FROM session_names WHERE name = ?1 AND session_id <> ?2")?
+            .exists([&name, &session_id])?;
+        if taken {
+            return Ok(false);
+        }
+        conn.execute(
+            "INSERT INTO session_names (session_id, name) VALUES (?1, ?2)
+             ON CONFLICT(session_id) DO UPDATE SET name = excluded.name",
+            [&session_id, &name],
+        )?;
+        Ok(true)
+    })
+    .await
+    .context("Failed to name session")
+}
+
+/// Removes a session's name. Returns whether it had one.
+pub async fn clear_session_name(conn: &Connection, session_id: &str) -> Result<bool> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| Ok(conn.execute("DELETE FROM session_names WHERE session_id = ?1", [session_id])? > 0))
+        .await
+        .context("Failed to clear session name")
+}
+
+/// Session ID -> name, for every named session.
+pub async fn session_names(conn: &Connection) -> Result<HashMap<String, String>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare("SELECT session_id, name FROM session_names")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session names")
+}
+
+/// Finds the session a user means: an exact ID, a name, or the start of
+/// an ID or short ID (`3f2a9c1b`). An exact ID or name wins over prefixes;
+/// a prefix matching several sessions is ambiguous.
+pub async fn resolve_session(conn: &Connection, reference: &str) -> Result<SessionRef> {
+    let reference = reference.trim().to_string();
+    conn.call(move |conn| {
+        let exact: Option<String> = conn
+            .prepare(
+                "SELECT session_id FROM session_index WHERE session_id = ?1
+                 UNION ALL
+                 SELECT session_id FROM session_names WHERE name = ?1",
+            )?
+            .query_map([&reference], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        if let Some(session_id) = exact {
+            return Ok(SessionRef::Found(session_id));
+        }
+        if reference.is_empty() {
+            return Ok(SessionRef::NotFound);
+        }
+        let ids = conn
+            .prepare("SELECT session_id FROM session_index ORDER BY last_turn_id DESC")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut matches: Vec<String> = ids
+            .into_iter()
+            .filter(|id| {
+                let random = id.rsplit_once('_').map_or(id.as_str(), |(_, random)| random);
+                id.starts_with(&reference) || random.starts_with(&reference)
+            })
+            .collect();
+        Ok(match matches.len() {
+            0 => SessionRef::NotFound,
+            1 => SessionRef::Found(matches.remove(0)),
+            _ => SessionRef::Ambiguous(matches),
+        })
+    })
+    .await
+    .context("Failed to look up session")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{open_memory, save_chat_turn, InputSource};
+
+    #[tokio::test]
+    async fn test_resolves_short_ids_and_names() {
+        let conn = open_memory(":memory:").await.unwrap();
+        for id in ["session_3f2a9c1b00", "session_3f2b000000", "session_77aa000000"] {
+            save_chat_turn(&conn, id, "user", "hi", InputSource::Text, None).await.unwrap();
+        }
+        assert_eq!(short_id("session_3f2a9c1b00"), "3f2a9c1b");
+        assert_eq!(short_id("abc"), "abc");
+
+        let found = |id: &str| SessionRef::Found(id.to_string());
+        assert_eq!(resolve_session(&conn, "session_77aa000000").await.unwrap(), found("session_77aa000000"));
+        assert_eq!(resolve_session(&conn, "3f2a").await.unwrap(), found("session_3f2a9c1b00"));
+        assert_eq!(resolve_session(&conn, "session_77").await.unwrap(), found("session_77aa000000"));
+        assert_eq!(
+            resolve_session(&conn, "3f2").await.unwrap(),
+            SessionRef::Ambiguous(vec!["session_3f2b000000".to_string(), "session_3f2a9c1b00".to_string()])
+        );
+        assert_eq!(resolve_session(&conn, "ffff").await.unwrap(), SessionRef::NotFound);
+
+        assert!(set_session_name(&conn, "session_3f2b000000", "First Talk").await.unwrap());
+        assert!(!set_session_name(&conn, "session_77aa000000", "first talk").await.unwrap(), "names ignore case");
+        assert_eq!(resolve_session(&conn, "first talk").await.unwrap(), found("session_3f2b000000"));
+        assert_eq!(session_names(&conn).await.unwrap()["session_3f2b000000"], "First Talk");
+        assert!(clear_session_name(&conn, "session_3f2b000000").await.unwrap());
+        assert_eq!(resolve_session(&conn, "First Talk").await.unwrap(), SessionRef::NotFound);
+    }
+}
@@ -0,0 +1,125 @@
+use chrono::NaiveTime;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// One night in the sleep diary. Times are local `HH:MM`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SleepEntry {
+    pub id: i64,
+    pub bedtime: String,
+    pub wake_time: String,
+    /// Times woken during the night.
+    pub awakenings: i32,
+    /// Self-rated sleep quality, 1 (very poor) to 5 (very good).
+    pub quality: i32,
+    pub created_at: String,
+}
+
+impl SleepEntry {
+    /// Hours from bedtime to wake time, wrapping past midnight.
+    pub fn hours(&self) -> Option<f64> {
+        let bed = NaiveTime::parse_from_str(&self.bedtime, "%H:%M").ok()?;
+        let wake = NaiveTime::parse_from_str(&self.wake_time, "%H:%M").ok()?;
+        let minutes = (wake - bed).num_minutes().rem_euclid(24 * 60);
+        Some(minutes as f64 / 60.0)
+    }
+}
+
+/// Creates the sleep_diary table if it doesn't exist.
+pub async fn create_sleep_diary_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sleep_diary (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                bedtime TEXT NOT NULL,
+                wake_time TEXT NOT NULL,
+                awakenings INTEGER NOT NULL CHECK(awakenings >= 0),
+                quality INTEGER NOT NULL CHECK(quality BETWEEN 1 AND 5),
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create sleep_diary table")?;
+
+    Ok(())
+}
+
+/// Saves a night's entry and returns its row ID.
+pub async fn save_sleep_entry(
+    conn: &Connection,
+    bedtime: &str,
+    wake_time: &str,
+    awakenings: i32,
+    quality: i32,
+) -> Result<i64> {
+    let bedtime = bedtime.to_string();
+    let wake_time = wake_time.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO sleep_diary (bedtime, wake_time, awakenings, quality) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![bedtime, wake_time, awakenings, quality],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to save sleep entry")
+}
+
+/// Loads diary entries from the last `days` days, oldest first.
+pub async fn recent_sleep_entries(conn: &Connection, days: u32) -> Result<Vec<SleepEntry>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, bedtime, wake_time, awakenings, quality, created_at FROM sleep_diary
+             WHERE created_at >= datetime('now', ?1) ORDER BY created_at, id",
+        )?;
+        let rows = stmt
+            .query_map([format!("-{days} days")], |row| {
+                Ok(SleepEntry {
+                    id: row.get(0)?,
+                    bedtime: row.get(1)?,
+                    wake_time: row.get(2)?,
+                    awakenings: row.get(3)?,
+                    quality: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load sleep entries")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_load_sleep_entries() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_sleep_diary_table(&conn).await.unwrap();
+
+        save_sleep_entry(&conn, "23:30", "07:00", 1, 4).await.unwrap();
+        assert!(save_sleep_entry(&conn, "23:30", "07:00", 0, 6).await.is_err());
+
+        let entries = recent_sleep_entries(&conn, 7).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].hours(), Some(7.5));
+    }
+
+    #[test]
+    fn test_hours_within_one_day() {
+        let entry = SleepEntry {
+            id: 1,
+            bedtime: "01:15".into(),
+            wake_time: "06:45".into(),
+            awakenings: 0,
+            quality: 3,
+            created_at: String::new(),
+        };
+        assert_eq!(entry.hours(), Some(5.5));
+    }
+}
@@ -0,0 +1,269 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A chat message as exchanged between devices by `chiron sync`. `uid` is
+/// the same on every device; `updated_at` decides which copy wins.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SyncMessage {
+    pub uid: String,
+    pub role: String,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub source: String,
+    pub prompt_variant: Option<String>,
+    pub interrupted: bool,
+}
+
+/// Messages added or replaced by `merge_messages`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    pub inserted: usize,
+    pub updated: usize,
+}
+
+/// Creates the sync_state table if it doesn't exist.
+pub async fn create_sync_state_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sync_state (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create sync_state table")?;
+
+    Ok(())
+}
+
+/// Reads a sync bookkeeping value (device ID, last push, blob ETags).
+pub async fn get_state(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let key = key.to_string();
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row("SELECT value FROM sync_state WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?)
+    })
+    .await
+    .context("Failed to read sync state")
+}
+
+/// Stores a sync bookkeeping value, replacing any previous one.
+pub async fn set_state(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    let key = key.to_string();
+    let value = value.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO sync_state (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to write sync state")
+}
+
+/// The database clock (`datetime('now')`), so push cutoffs compare with
+/// `updated_at` values written by the same clock.
+pub async fn now(conn: &Connection) -> Result<String> {
+    conn.call(|conn| Ok(conn.query_row("SELECT datetime('now')", [], |row| row.get(0))?))
+        .await
+        .context("Failed to read database clock")
+}
+
+/// Sessions with a message added or changed at or after `since`
+/// (`YYYY-MM-DD HH:MM:SS`), or all sessions if `since` is `None`.
+pub async fn changed_sessions(conn: &Connection, since: Option<&str>) -> Result<Vec<String>> {
+    let since = since.unwrap_or("").to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT session_id FROM chat_turns WHERE updated_at >= ?1
+             GROUP BY session_id ORDER BY MIN(id)",
+        )?;
+        let rows = stmt
+            .query_map([since], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to find changed sessions")
+}
+
+/// Loads a session's messages for pushing, in chronological order.
+pub async fn session_messages(conn: &Connection, session_id: &str) -> Result<Vec<SyncMessage>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT uid, role, content, created_at, updated_at, source, prompt_variant, interrupted
+             FROM chat_turns WHERE session_id = ?1 ORDER BY created_at, id",
+        )?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                Ok(SyncMessage {
+                    uid: row.get(0)?,
+                    role: row.get(1)?,
+                    content: row.get(2)?,
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    source: row.get(5)?,
+                    prompt_variant: row.get(6)?,
+                    interrupted: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session messages")
+}
+
+/// Merges another device's copy of a session, last writer wins per
+/// message: unknown messages are added, and a known one is replaced only if
+/// the incoming copy was updated later. Sessions archived on this device
+/// are left alone. A newer copy under this session moves a message here
+/// from another one. Affected `session_index` rows are recomputed.
+pub async fn merge_messages(conn: &Connection, session_id: &str, messages: Vec<SyncMessage>) -> Result<MergeStats> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        let archived = tx
+            .prepare("SELECT 1 FROM session_archives WHERE session_id = ?1")?
+            .exists([&session_id])?;
+        if archived {
+            return Ok(MergeStats::default());
+        }
+
+        let mut stats = MergeStats::default();
+        // Sessions whose index needs recomputing: this one, plus any a
+        // message was moved out of (`chiron sessions merge` on another device)
+        let mut touched = vec![session_id.clone()];
+        for msg in messages {
+            let local: Option<(String, String)> = tx
+                .query_row(
+                    "SELECT updated_at, session_id FROM chat_turns WHERE uid = ?1",
+                    [&msg.uid],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            match local {
+                None => {
+                    tx.execute(
+                        "INSERT INTO chat_turns (session_id, role, content, created_at, source, prompt_variant,
+                             interrupted, uid, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        rusqlite::params![
+                            session_id,
+                            msg.role,
+                            msg.content,
+                            msg.created_at,
+                            msg.source,
+                            msg.prompt_variant,
+                            msg.interrupted,
+                            msg.uid,
+                            msg.updated_at,
+                        ],
+                    )?;
+                    stats.inserted += 1;
+                }
+                Some((local, local_session)) if msg.updated_at > local => {
+                    tx.execute(
+                        "UPDATE chat_turns SET content = ?2, prompt_variant = ?3, interrupted = ?4, updated_at = ?5,
+                             session_id = ?6
+                         WHERE uid = ?1",
+                        rusqlite::params![
+                            msg.uid,
+                            msg.content,
+                            msg.prompt_variant,
+                            msg.interrupted,
+                            msg.updated_at,
+                            session_id,
+                        ],
+                    )?;
+                    if !touched.contains(&local_session) {
+                        touched.push(local_session);
+                    }
+                    stats.updated += 1;
+                }
+                Some(_) => {}
+            }
+        }
+
+        if stats != MergeStats::default() {
+            for session_id in &touched {
+                super::reindex_session(&tx, session_id)?;
+            }
+        }
+        tx.commit()?;
+        Ok(stats)
+    })
+    .await
+    .context("Failed to merge synced messages")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{list_sessions, load_session_turns, open_memory, save_chat_turn, InputSource};
+
+    #[tokio::test]
+    async fn test_merge_is_last_writer_wins_per_message() {
+        let laptop = open_memory(":memory:").await.unwrap();
+        let desktop = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&laptop, "s1", "user", "hi", InputSource::Text, None).await.unwrap();
+        save_chat_turn(&laptop, "s1", "assistant", "hello", InputSource::Text, Some("v1")).await.unwrap();
+
+        let mut pushed = session_messages(&laptop, "s1").await.unwrap();
+        assert_eq!(changed_sessions(&laptop, None).await.unwrap(), vec!["s1"]);
+        assert!(changed_sessions(&laptop, Some("9999-01-01 00:00:00")).await.unwrap().is_empty());
+
+        let stats = merge_messages(&desktop, "s1", pushed.clone()).await.unwrap();
+        assert_eq!(stats, MergeStats { inserted: 2, updated: 0 });
+        assert_eq!(session_messages(&desktop, "s1").await.unwrap(), pushed);
+        assert_eq!(list_sessions(&desktop).await.unwrap()[0].message_count, 2);
+
+        // Merging the same copy again changes nothing
+        let again = merge_messages(&desktop, "s1", pushed.clone()).await.unwrap();
+        assert_eq!(again, MergeStats::default());
+
+        // A newer copy of a message replaces it; an older one doesn't
+        pushed[1].content = "hello (cut off)".to_string();
+        pushed[1].interrupted = true;
+        pushed[1].updated_at = "9999-01-01 00:00:00".to_string();
+        pushed[0].content = "stale".to_string();
+        pushed[0].updated_at = "0000-01-01 00:00:00".to_string();
+        let stats = merge_messages(&desktop, "s1", pushed).await.unwrap();
+        assert_eq!(stats, MergeStats { inserted: 0, updated: 1 });
+        let turns = load_session_turns(&desktop, "s1").await.unwrap();
+        assert_eq!(turns[0].content, "hi");
+        assert_eq!(turns[1].content, "hello (cut off)");
+        assert!(turns[1].interrupted);
+    }
+
+    #[tokio::test]
+    async fn test_newer_copy_moves_a_message_between_sessions() {
+        let conn = open_memory(":memory:").await.unwrap();
+        save_chat_turn(&conn, "s1", "user", "hi", InputSource::Text, None).await.unwrap();
+        let mut moved = session_messages(&conn, "s1").await.unwrap();
+        moved[0].updated_at = "9999-01-01 00:00:00".to_string();
+
+        let stats = merge_messages(&conn, "s2", moved).await.unwrap();
+        assert_eq!(stats, MergeStats { inserted: 0, updated: 1 });
+        let sessions = list_sessions(&conn).await.unwrap();
+        assert_eq!(sessions.iter().map(|s| s.session_id.as_str()).collect::<Vec<_>>(), vec!["s2"]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_state_round_trip() {
+        let conn = open_memory(":memory:").await.unwrap();
+        assert_eq!(get_state(&conn, "device_id").await.unwrap(), None);
+        set_state(&conn, "device_id", "a").await.unwrap();
+        set_state(&conn, "device_id", "b").await.unwrap();
+        assert_eq!(get_state(&conn, "device_id").await.unwrap().as_deref(), Some("b"));
+    }
+}
@@ -0,0 +1,84 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Creates the telegram_chats table if it doesn't exist.
+pub async fn create_telegram_chats_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS telegram_chats (
+                chat_id INTEGER PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create telegram_chats table")?;
+
+    Ok(())
+}
+
+/// The session a Telegram chat is in. `None` if it has none open.
+pub async fn chat_session(conn: &Connection, chat_id: i64) -> Result<Option<String>> {
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row("SELECT session_id FROM telegram_chats WHERE chat_id = ?1", [chat_id], |row| row.get(0))
+            .optional()?)
+    })
+    .await
+    .context("Failed to load Telegram chat")
+}
+
+/// Points a Telegram chat at a session.
+pub async fn set_chat_session(conn: &Connection, chat_id: i64, session_id: &str) -> Result<()> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO telegram_chats (chat_id, session_id) VALUES (?1, ?2)
+             ON CONFLICT(chat_id) DO UPDATE SET session_id = excluded.session_id, updated_at = datetime('now')",
+            rusqlite::params![chat_id, session_id],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save Telegram chat")
+}
+
+/// Closes a Telegram chat's session, so its next message starts a new one.
+/// Returns the session it was in, if any.
+pub async fn end_chat_session(conn: &Connection, chat_id: i64) -> Result<Option<String>> {
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row("DELETE FROM telegram_chats WHERE chat_id = ?1 RETURNING session_id", [chat_id], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    })
+    .await
+    .context("Failed to close Telegram chat")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chats_map_to_sessions() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_telegram_chats_table(&conn).await.unwrap();
+
+        assert_eq!(chat_session(&conn, 42).await.unwrap(), None);
+        set_chat_session(&conn, 42, "telegram_1").await.unwrap();
+        set_chat_session(&conn, 7, "telegram_2").await.unwrap();
+        assert_eq!(chat_session(&conn, 42).await.unwrap().as_deref(), Some("telegram_1"));
+
+        assert_eq!(end_chat_session(&conn, 42).await.unwrap().as_deref(), Some("telegram_1"));
+        assert_eq!(end_chat_session(&conn, 42).await.unwrap(), None);
+        assert_eq!(chat_session(&conn, 42).await.unwrap(), None);
+        assert_eq!(chat_session(&conn, 7).await.unwrap().as_deref(), Some("telegram_2"));
+    }
+}
@@ -0,0 +1,185 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A completed CBT thought record. Intensities are 0–100.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ThoughtRecord {
+    pub situation: String,
+    pub automatic_thought: String,
+    pub emotion: String,
+    pub intensity_before: i32,
+    pub evidence_for: String,
+    pub evidence_against: String,
+    pub balanced_thought: String,
+    pub intensity_after: i32,
+}
+
+impl ThoughtRecord {
+    /// One-line summary for listings, e.g.
+    /// `Missed the bus — "I ruin everything" → "Buses run late" (anxious 80 → 40)`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} — \"{}\" → \"{}\" ({} {} → {})",
+            self.situation,
+            self.automatic_thought,
+            self.balanced_thought,
+            self.emotion,
+            self.intensity_before,
+            self.intensity_after,
+        )
+    }
+}
+
+/// A thought record as stored, with the session it was completed in.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StoredThoughtRecord {
+    pub id: i64,
+    pub session_id: String,
+    pub created_at: String,
+    pub record: ThoughtRecord,
+}
+
+/// Creates the thought_records table if it doesn't exist.
+pub async fn create_thought_records_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS thought_records (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                situation TEXT NOT NULL,
+                automatic_thought TEXT NOT NULL,
+                emotion TEXT NOT NULL,
+                intensity_before INTEGER NOT NULL CHECK(intensity_before BETWEEN 0 AND 100),
+                evidence_for TEXT NOT NULL,
+                evidence_against TEXT NOT NULL,
+                balanced_thought TEXT NOT NULL,
+                intensity_after INTEGER NOT NULL CHECK(intensity_after BETWEEN 0 AND 100),
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create thought_records table")?;
+
+    Ok(())
+}
+
+/// Saves a completed thought record and returns its ID.
+pub async fn save_thought_record(
+    conn: &Connection,
+    session_id: &str,
+    record: &ThoughtRecord,
+) -> Result<i64> {
+    let session_id = session_id.to_string();
+    let record = record.clone();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO thought_records (
+                session_id, situation, automatic_thought, emotion, intensity_before,
+                evidence_for, evidence_against, balanced_thought, intensity_after
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                session_id,
+                record.situation,
+                record.automatic_thought,
+                record.emotion,
+                record.intensity_before,
+                record.evidence_for,
+                record.evidence_against,
+                record.balanced_thought,
+                record.intensity_after,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to save thought record")
+}
+
+/// Loads the most recent `limit` thought records, newest first.
+pub async fn recent_thought_records(
+    conn: &Connection,
+    limit: usize,
+) -> Result<Vec<StoredThoughtRecord>> {
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, session_id, created_at, situation, automatic_thought, emotion,
+                    intensity_before, evidence_for, evidence_against, balanced_thought,
+                    intensity_after
+             FROM thought_records ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt
+            .query_map([limit as i64], |row| {
+                Ok(StoredThoughtRecord {
+                    id: row.get(0)?,
+                    session_id: row.get(1)?,
+                    created_at: row.get(2)?,
+                    record: ThoughtRecord {
+                        situation: row.get(3)?,
+                        automatic_thought: row.get(4)?,
+                        emotion: row.get(5)?,
+                        intensity_before: row.get(6)?,
+                        evidence_for: row.get(7)?,
+                        evidence_against: row.get(8)?,
+                        balanced_thought: row.get(9)?,
+                        intensity_after: row.get(10)?,
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load thought records")
+}
+
+/// Number of thought records completed so far.
+pub async fn count_thought_records(conn: &Connection) -> Result<usize> {
+    conn.call(|conn| {
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM thought_records", [], |row| row.get(0))?;
+        Ok(count as usize)
+    })
+    .await
+    .context("Failed to count thought records")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_list_thought_records() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_thought_records_table(&conn).await.unwrap();
+
+        let record = ThoughtRecord {
+            situation: "Missed the bus".into(),
+            automatic_thought: "I ruin everything".into(),
+            emotion: "anxious".into(),
+            intensity_before: 80,
+            evidence_for: "I was late last week too".into(),
+            evidence_against: "I was on time all month before that".into(),
+            balanced_thought: "Buses run late and I usually manage".into(),
+            intensity_after: 40,
+        };
+        save_thought_record(&conn, "s1", &record).await.unwrap();
+        save_thought_record(&conn, "s2", &ThoughtRecord { intensity_after: 30, ..record.clone() })
+            .await
+            .unwrap();
+
+        let stored = recent_thought_records(&conn, 10).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(count_thought_records(&conn).await.unwrap(), 2);
+        assert_eq!(stored[0].session_id, "s2", "newest first");
+        assert_eq!(stored[1].record, record);
+        assert_eq!(
+            record.summary(),
+            "Missed the bus — \"I ruin everything\" → \"Buses run late and I usually manage\" (anxious 80 → 40)"
+        );
+
+        let invalid = ThoughtRecord { intensity_before: 150, ..record };
+        assert!(save_thought_record(&conn, "s1", &invalid).await.is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A named conversation (`chiron --thread work`) and the session it
+/// currently continues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread {
+    pub name: String,
+    pub session_id: String,
+    pub updated_at: String,
+}
+
+/// Creates the threads table if it doesn't exist.
+pub async fn create_threads_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS threads (
+                name TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create threads table")?;
+
+    Ok(())
+}
+
+/// The session a thread continues. `None` for a new thread name.
+pub async fn thread_session(conn: &Connection, name: &str) -> Result<Option<String>> {
+    let name = name.to_string();
+    conn.call(move |conn| {
+        Ok(conn
+            .query_row("SELECT session_id FROM threads WHERE name = ?1", [name], |row| row.get(0))
+            .optional()?)
+    })
+    .await
+    .context("Failed to load thread")
+}
+
+/// Points a thread at a session, creating the thread if it's new.
+pub async fn set_thread_session(conn: &Connection, name: &str, session_id: &str) -> Result<()> {
+    let name = name.to_string();
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO threads (name, session_id) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET session_id = excluded.session_id, updated_at = datetime('now')",
+            [name, session_id],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save thread")
+}
+
+/// All threads, by name.
+pub async fn list_threads(conn: &Connection) -> Result<Vec<Thread>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare("SELECT name, session_id, updated_at FROM threads ORDER BY name")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(Thread {
+                    name: row.get(0)?,
+                    session_id: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to list threads")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_threads_map_names_to_sessions() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_threads_table(&conn).await.unwrap();
+
+        assert_eq!(thread_session(&conn, "grief").await.unwrap(), None);
+        set_thread_session(&conn, "work", "session_1").await.unwrap();
+        set_thread_session(&conn, "grief", "session_2").await.unwrap();
+        set_thread_session(&conn, "work", "session_3").await.unwrap();
+
+        assert_eq!(thread_session(&conn, "work").await.unwrap().as_deref(), Some("session_3"));
+        let names: Vec<_> = list_threads(&conn).await.unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["grief", "work"]);
+    }
+}
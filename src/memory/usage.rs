@@ -0,0 +1,194 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Token counts and timing for one generated reply, stored on its
+/// `chat_turns` row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct ReplyUsage {
+    /// Prompt tokens processed (summed over tool-call rounds).
+    pub prompt_tokens: u64,
+    /// Tokens generated.
+    pub response_tokens: u64,
+    /// Time until the first visible token, mostly prompt processing.
+    /// `None` if the reply had no visible text.
+    pub first_token_ms: Option<u64>,
+    /// Time from the request to the end of generation.
+    pub latency_ms: u64,
+}
+
+impl ReplyUsage {
+    /// Generation speed over the whole reply.
+    pub fn tokens_per_sec(&self) -> Option<f64> {
+        (self.latency_ms > 0).then(|| self.response_tokens as f64 * 1000.0 / self.latency_ms as f64)
+    }
+}
+
+/// Usage of one reply in a session.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TurnUsage {
+    /// Position among the session's replies (1-based), counting replies
+    /// without usage such as crisis responses.
+    pub turn: usize,
+    pub created_at: String,
+    pub usage: ReplyUsage,
+}
+
+/// Replies grouped by prompt size, to see how latency grows with context.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UsageBucket {
+    /// Smallest prompt size in the bucket; the bucket spans `bucket_size` tokens.
+    pub min_prompt_tokens: u64,
+    pub replies: i64,
+    pub avg_response_tokens: f64,
+    pub avg_first_token_ms: Option<f64>,
+    pub avg_latency_ms: f64,
+}
+
+/// Usage across all sessions.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct UsageSummary {
+    pub sessions: i64,
+    pub replies: i64,
+    pub prompt_tokens: i64,
+    pub response_tokens: i64,
+    pub avg_latency_ms: Option<f64>,
+    pub by_prompt_size: Vec<UsageBucket>,
+}
+
+/// Loads usage for each of a session's replies that has it, in order.
+pub async fn session_usage(conn: &Connection, session_id: &str) -> Result<Vec<TurnUsage>> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT created_at, prompt_tokens, response_tokens, first_token_ms, latency_ms FROM chat_turns
+             WHERE session_id = ?1 AND role = 'assistant' ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<i64>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load session usage")
+    .map(|rows| {
+        rows.into_iter()
+            .enumerate()
+            .filter_map(|(i, (created_at, prompt, response, first_token, latency))| {
+                Some(TurnUsage {
+                    turn: i + 1,
+                    created_at,
+                    usage: ReplyUsage {
+                        prompt_tokens: prompt? as u64,
+                        response_tokens: response? as u64,
+                        first_token_ms: first_token.map(|ms| ms as u64),
+                        latency_ms: latency? as u64,
+                    },
+                })
+            })
+            .collect()
+    })
+}
+
+/// Aggregates usage over all replies that have it, with replies grouped
+/// into prompt size buckets of `bucket_size` tokens.
+pub async fn usage_summary(conn: &Connection, bucket_size: u64) -> Result<UsageSummary> {
+    let bucket_size = bucket_size.max(1) as i64;
+    conn.call(move |conn| {
+        let (sessions, replies, prompt_tokens, response_tokens, avg_latency_ms): (i64, i64, i64, i64, Option<f64>) =
+            conn.query_row(
+                "SELECT COUNT(DISTINCT session_id), COUNT(*), COALESCE(SUM(prompt_tokens), 0),
+                        COALESCE(SUM(response_tokens), 0), AVG(latency_ms)
+                 FROM chat_turns WHERE latency_ms IS NOT NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )?;
+        let mut stmt = conn.prepare(
+            "SELECT prompt_tokens / ?1 AS bucket, COUNT(*), AVG(response_tokens), AVG(first_token_ms), AVG(latency_ms)
+             FROM chat_turns WHERE latency_ms IS NOT NULL
+             GROUP BY bucket ORDER BY bucket",
+        )?;
+        let by_prompt_size = stmt
+            .query_map([bucket_size], |row| {
+                Ok(UsageBucket {
+                    min_prompt_tokens: (row.get::<_, i64>(0)? * bucket_size) as u64,
+                    replies: row.get(1)?,
+                    avg_response_tokens: row.get(2)?,
+                    avg_first_token_ms: row.get(3)?,
+                    avg_latency_ms: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(UsageSummary {
+            sessions,
+            replies,
+            prompt_tokens,
+            response_tokens,
+            avg_latency_ms,
+            by_prompt_size,
+        })
+    })
+    .await
+    .context("Failed to summarize usage")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::open_memory;
+    use crate::memory::writer::{Exchange, SaveQueue};
+
+    fn usage(prompt_tokens: u64, first_token_ms: u64, latency_ms: u64) -> ReplyUsage {
+        ReplyUsage {
+            prompt_tokens,
+            response_tokens: 40,
+            first_token_ms: Some(first_token_ms),
+            latency_ms,
+        }
+    }
+
+    async fn reply(conn: &Connection, session_id: &str, usage: Option<ReplyUsage>) {
+        let queue = SaveQueue::spawn(conn.clone());
+        queue.save(Exchange {
+            session_id: session_id.to_string(),
+            input: "hi".to_string(),
+            response: "hello".to_string(),
+            usage,
+            ..Default::default()
+        });
+        queue.flush().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_usage_and_summary() {
+        let conn = open_memory(":memory:").await.unwrap();
+        reply(&conn, "s1", Some(usage(300, 200, 1000))).await;
+        reply(&conn, "s1", None).await; // e.g. a crisis response
+        reply(&conn, "s1", Some(usage(700, 500, 2000))).await;
+        reply(&conn, "s2", Some(usage(400, 300, 1500))).await;
+
+        let turns = session_usage(&conn, "s1").await.unwrap();
+        assert_eq!(turns.iter().map(|t| t.turn).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(turns[1].usage, usage(700, 500, 2000));
+        assert_eq!(turns[0].usage.tokens_per_sec(), Some(40.0));
+
+        let summary = usage_summary(&conn, 512).await.unwrap();
+        assert_eq!((summary.sessions, summary.replies), (2, 3));
+        assert_eq!((summary.prompt_tokens, summary.response_tokens), (1400, 120));
+        assert_eq!(summary.avg_latency_ms, Some(1500.0));
+        let buckets: Vec<_> = summary
+            .by_prompt_size
+            .iter()
+            .map(|b| (b.min_prompt_tokens, b.replies, b.avg_first_token_ms))
+            .collect();
+        assert_eq!(buckets, vec![(0, 2, Some(250.0)), (512, 1, Some(500.0))]);
+    }
+}
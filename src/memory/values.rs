@@ -0,0 +1,165 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// Importance minus consistency at or above which a domain counts as a gap.
+pub const GAP_THRESHOLD: i32 = 3;
+
+/// Maximum domains injected into the preamble.
+const MAX_PROMPT_VALUES: usize = 3;
+
+/// How much a life domain matters to the user and how closely they've been
+/// living by it, both rated 1–10.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ValueRating {
+    /// Domain ID, e.g. `family` or `physical_health`.
+    pub domain: String,
+    pub importance: i32,
+    pub consistency: i32,
+    /// What matters to them in this domain, in their own words.
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl ValueRating {
+    /// How far living falls short of importance (negative if ahead).
+    pub fn gap(&self) -> i32 {
+        self.importance - self.consistency
+    }
+}
+
+/// Creates the value_ratings table if it doesn't exist.
+pub async fn create_values_table(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS value_ratings (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                domain TEXT NOT NULL,
+                importance INTEGER NOT NULL CHECK(importance BETWEEN 1 AND 10),
+                consistency INTEGER NOT NULL CHECK(consistency BETWEEN 1 AND 10),
+                note TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create value_ratings table")?;
+
+    Ok(())
+}
+
+/// Saves a rating for one domain. Earlier ratings are kept as history.
+pub async fn save_value_rating(
+    conn: &Connection,
+    domain: &str,
+    importance: i32,
+    consistency: i32,
+    note: Option<&str>,
+) -> Result<()> {
+    let domain = domain.to_string();
+    let note = note.map(str::to_string);
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO value_ratings (domain, importance, consistency, note) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![domain, importance, consistency, note],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to save value rating")
+}
+
+/// The values profile: the latest rating for each domain, most important first.
+pub async fn values_profile(conn: &Connection) -> Result<Vec<ValueRating>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT domain, importance, consistency, note, created_at FROM value_ratings
+             WHERE id IN (SELECT MAX(id) FROM value_ratings GROUP BY domain)
+             ORDER BY importance DESC, importance - consistency DESC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ValueRating {
+                    domain: row.get(0)?,
+                    importance: row.get(1)?,
+                    consistency: row.get(2)?,
+                    note: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to load values profile")
+}
+
+/// The domain with the widest gap between importance and living it, if any
+/// gap reaches `GAP_THRESHOLD`.
+pub fn largest_gap(profile: &[ValueRating]) -> Option<&ValueRating> {
+    profile
+        .iter()
+        .filter(|v| v.gap() >= GAP_THRESHOLD)
+        .max_by_key(|v| (v.gap(), v.importance))
+}
+
+/// Formats the most important domains for the preamble, flagging gaps.
+/// Domain IDs are written with spaces (`physical_health` → `physical health`).
+pub fn format_values_for_prompt(profile: &[ValueRating]) -> Option<String> {
+    let lines: Vec<String> = profile
+        .iter()
+        .take(MAX_PROMPT_VALUES)
+        .map(|v| {
+            let mut line = format!("- Values {}", v.domain.replace('_', " "));
+            if let Some(note) = &v.note {
+                line.push_str(&format!(": {note}"));
+            }
+            if v.gap() >= GAP_THRESHOLD {
+                line.push_str(" (wants to live this more)");
+            }
+            line
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_profile_keeps_latest_rating_per_domain() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_values_table(&conn).await.unwrap();
+
+        save_value_rating(&conn, "family", 9, 8, Some("being there for my kids")).await.unwrap();
+        save_value_rating(&conn, "physical_health", 6, 5, None).await.unwrap();
+        save_value_rating(&conn, "physical_health", 8, 3, None).await.unwrap();
+        assert!(save_value_rating(&conn, "work", 11, 5, None).await.is_err());
+
+        let profile = values_profile(&conn).await.unwrap();
+        assert_eq!(profile.len(), 2);
+        assert_eq!(profile[0].domain, "family");
+        assert_eq!(profile[1].importance, 8, "latest rating wins");
+
+        assert_eq!(largest_gap(&profile).unwrap().domain, "physical_health");
+        assert_eq!(
+            format_values_for_prompt(&profile).unwrap(),
+            "- Values family: being there for my kids\n- Values physical health (wants to live this more)"
+        );
+    }
+
+    #[test]
+    fn test_no_gap_below_threshold() {
+        let rating = ValueRating {
+            domain: "work".into(),
+            importance: 7,
+            consistency: 5,
+            note: None,
+            created_at: String::new(),
+        };
+        assert!(largest_gap(&[rating]).is_none());
+        assert!(format_values_for_prompt(&[]).is_none());
+    }
+}
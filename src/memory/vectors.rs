@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use anyhow::{Context, Result};
+use crate::error::{ChironError, Context, Result};
 use arrow_array::{
     ArrayRef, FixedSizeListArray, Float64Array, Int32Array, RecordBatch, RecordBatchIterator,
     StringArray,
@@ -65,10 +65,36 @@ pub async fn ensure_tables(conn: &Connection) -> Result<()> {
             .context("Failed to create mi_knowledge table")?;
     }
 
+    if !existing.contains(&"user_documents".to_string()) {
+        conn.create_empty_table("user_documents", user_documents_schema())
+            .execute()
+            .await
+            .context("Failed to create user_documents table")?;
+    } else {
+        add_document_location(conn).await?;
+    }
+
     tracing::info!("Vector store tables verified");
     Ok(())
 }
 
+/// Adds the `location` column to a `user_documents` table created before
+/// chunks recorded where in the document they came from.
+async fn add_document_location(conn: &Connection) -> Result<()> {
+    let table = conn.open_table("user_documents").execute().await.context("Failed to open user_documents")?;
+    if table.schema().await.context("Failed to read user_documents schema")?.field_with_name("location").is_ok() {
+        return Ok(());
+    }
+    table
+        .add_columns(
+            lancedb::table::NewColumnTransform::SqlExpressions(vec![("location".to_string(), "''".to_string())]),
+            None,
+        )
+        .await
+        .context("Failed to add location to user_documents")?;
+    Ok(())
+}
+
 // ─── Table schemas ──────────────────────────────────────────────────────────
 
 fn embedding_field() -> Field {
@@ -150,6 +176,19 @@ fn mi_knowledge_schema() -> Arc<Schema> {
     ]))
 }
 
+fn user_documents_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("title", DataType::Utf8, false),
+        Field::new("source_path", DataType::Utf8, false),
+        Field::new("chunk_index", DataType::Int32, false),
+        Field::new("content", DataType::Utf8, false),
+        Field::new("created_at", DataType::Utf8, false),
+        Field::new("location", DataType::Utf8, false),
+        embedding_field(),
+    ]))
+}
+
 // ─── Data types ─────────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -210,6 +249,36 @@ pub struct MiKnowledge {
     pub source: String,
 }
 
+/// A chunk of a document the user added to their personal library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChunk {
+    pub id: String,
+    pub title: String,
+    pub source_path: String,
+    pub chunk_index: i32,
+    pub content: String,
+    pub created_at: String,
+    /// Where in the document it's from (`p. 3` for PDFs), or empty.
+    #[serde(default)]
+    pub location: String,
+}
+
+impl DocumentChunk {
+    /// How the chunk is cited to the model: its title, file name, and
+    /// location, e.g. `Sleep Plan, sleep_plan.pdf, p. 3`.
+    pub fn citation(&self) -> String {
+        let file = std::path::Path::new(&self.source_path)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.source_path.clone());
+        [self.title.as_str(), file.as_str(), self.location.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 // ─── Insert helpers ─────────────────────────────────────────────────────────
 
 fn make_embedding_array(embedding: &[f64]) -> FixedSizeListArray {
@@ -241,7 +310,7 @@ pub async fn add_user_fact(
     )
     .context("Failed to create user_fact RecordBatch")?;
 
-    let table = conn.open_table("user_knowledge").execute().await?;
+    let table = conn.open_table("user_knowledge").execute().await.context("Failed to open user_knowledge")?;
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
@@ -284,15 +353,16 @@ pub async fn upsert_user_fact(
             .query(&fact.content)
             .samples(1)
             .build()
-            .map_err(|e| anyhow::anyhow!("Failed to build dedup search request: {e}"))?;
+            .map_err(|e| ChironError::vector_store("Failed to build dedup search request", e.to_string()))?;
 
-    let results: Vec<(f64, String, UserFact)> = index.top_n(request).await?;
+    let results: Vec<(f64, String, UserFact)> =
+        index.top_n(request).await.context("Failed to search user facts")?;
 
     // Check if top result is a close match with same fact_type
     if let Some((distance, _, existing)) = results.first() {
         if *distance < DEDUP_DISTANCE_THRESHOLD && existing.fact_type == fact.fact_type {
             // Update existing fact's confirmation timestamps
-            let table = conn.open_table("user_knowledge").execute().await?;
+            let table = conn.open_table("user_knowledge").execute().await.context("Failed to open user_knowledge")?;
             table
                 .update()
                 .only_if(format!("id = '{}'", existing.id))
@@ -334,7 +404,7 @@ pub async fn add_session_summary(
     )
     .context("Failed to create session_summary RecordBatch")?;
 
-    let table = conn.open_table("session_summaries").execute().await?;
+    let table = conn.open_table("session_summaries").execute().await.context("Failed to open session_summaries")?;
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
@@ -366,7 +436,7 @@ pub async fn add_session_checkpoint(
     )
     .context("Failed to create session_checkpoint RecordBatch")?;
 
-    let table = conn.open_table("session_checkpoints").execute().await?;
+    let table = conn.open_table("session_checkpoints").execute().await.context("Failed to open session_checkpoints")?;
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
@@ -399,7 +469,7 @@ pub async fn add_significant_turn(
     )
     .context("Failed to create significant_turn RecordBatch")?;
 
-    let table = conn.open_table("significant_turns").execute().await?;
+    let table = conn.open_table("significant_turns").execute().await.context("Failed to open significant_turns")?;
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
@@ -429,7 +499,7 @@ pub async fn add_mi_knowledge(
     )
     .context("Failed to create mi_knowledge RecordBatch")?;
 
-    let table = conn.open_table("mi_knowledge").execute().await?;
+    let table = conn.open_table("mi_knowledge").execute().await.context("Failed to open mi_knowledge")?;
     table
         .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
         .execute()
@@ -438,6 +508,56 @@ pub async fn add_mi_knowledge(
     Ok(())
 }
 
+/// Stores a document's chunks with their embeddings in place of any
+/// previously ingested from the same path.
+///
+/// The new chunks are written before the old ones are removed, so a failed
+/// write leaves the previous copy searchable.
+pub async fn replace_document(
+    conn: &Connection,
+    source_path: &str,
+    chunks: &[(DocumentChunk, Vec<f64>)],
+) -> Result<()> {
+    let table = conn.open_table("user_documents").execute().await.context("Failed to open user_documents")?;
+    if !chunks.is_empty() {
+        let schema = user_documents_schema();
+        let strings = |f: fn(&DocumentChunk) -> &str| -> ArrayRef {
+            Arc::new(StringArray::from(chunks.iter().map(|(c, _)| f(c)).collect::<Vec<_>>()))
+        };
+        let embeddings: Vec<f64> = chunks.iter().flat_map(|(_, e)| e.iter().copied()).collect();
+        let field = Arc::new(Field::new("item", DataType::Float64, true));
+        let vectors = FixedSizeListArray::try_new(field, EMBEDDING_DIM as i32, Arc::new(Float64Array::from(embeddings)), None)
+            .context("Embedding dimension mismatch")?;
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                strings(|c| c.id.as_str()),
+                strings(|c| c.title.as_str()),
+                strings(|c| c.source_path.as_str()),
+                Arc::new(Int32Array::from(chunks.iter().map(|(c, _)| c.chunk_index).collect::<Vec<_>>())),
+                strings(|c| c.content.as_str()),
+                strings(|c| c.created_at.as_str()),
+                strings(|c| c.location.as_str()),
+                Arc::new(vectors),
+            ],
+        )
+        .context("Failed to create document_chunk RecordBatch")?;
+        table
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .context("Failed to insert document chunks")?;
+    }
+
+    let kept: Vec<String> = chunks.iter().map(|(c, _)| format!("'{}'", c.id)).collect();
+    let mut filter = format!("source_path = '{}'", source_path.replace('\'', "''"));
+    if !kept.is_empty() {
+        filter.push_str(&format!(" AND id NOT IN ({})", kept.join(", ")));
+    }
+    table.delete(&filter).await.context("Failed to delete replaced document chunks")?;
+    Ok(())
+}
+
 // ─── Search helpers (rig VectorStoreIndex) ──────────────────────────────────
 
 /// Creates a `LanceDbVectorIndex` for the given table.
@@ -450,7 +570,7 @@ pub async fn vector_index(
         .open_table(table_name)
         .execute()
         .await
-        .with_context(|| format!("Failed to open table {table_name}"))?;
+        .map_err(|e| ChironError::vector_store(format!("Failed to open table {table_name}"), e))?;
 
     LanceDbVectorIndex::new(
         table,
@@ -461,7 +581,7 @@ pub async fn vector_index(
             .column("vector"),
     )
     .await
-    .map_err(|e| anyhow::anyhow!("Failed to create vector index for {table_name}: {e}"))
+    .map_err(|e| ChironError::vector_store(format!("Failed to create vector index for {table_name}"), e.to_string()))
 }
 
 #[cfg(test)]
@@ -480,6 +600,7 @@ mod tests {
         assert!(tables.contains(&"session_checkpoints".to_string()));
         assert!(tables.contains(&"significant_turns".to_string()));
         assert!(tables.contains(&"mi_knowledge".to_string()));
+        assert!(tables.contains(&"user_documents".to_string()));
     }
 
     #[tokio::test]
@@ -726,4 +847,39 @@ mod tests {
         let table = conn.open_table("user_knowledge").execute().await.unwrap();
         assert_eq!(table.count_rows(None).await.unwrap(), 2);
     }
+
+    #[tokio::test]
+    async fn test_replace_document_swaps_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_vector_db(dir.path().to_str().unwrap()).await.unwrap();
+        ensure_tables(&conn).await.unwrap();
+
+        let chunk = |content: &str, location: &str| {
+            let chunk = DocumentChunk {
+                id: uuid::Uuid::new_v4().to_string(),
+                title: "Sleep Plan".to_string(),
+                source_path: "/home/user/sleep_plan.pdf".to_string(),
+                chunk_index: 0,
+                content: content.to_string(),
+                created_at: "2026-03-23T00:00:00Z".to_string(),
+                location: location.to_string(),
+            };
+            (chunk, vec![0.1; EMBEDDING_DIM])
+        };
+        let table = conn.open_table("user_documents").execute().await.unwrap();
+
+        replace_document(&conn, "/home/user/sleep_plan.pdf", &[chunk("old", "p. 1"), chunk("older", "p. 2")])
+            .await
+            .unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 2);
+
+        let new = chunk("new", "p. 1");
+        replace_document(&conn, "/home/user/sleep_plan.pdf", std::slice::from_ref(&new)).await.unwrap();
+        assert_eq!(table.count_rows(None).await.unwrap(), 1);
+        assert_eq!(table.count_rows(Some(format!("id = '{}'", new.0.id))).await.unwrap(), 1);
+        assert_eq!(new.0.citation(), "Sleep Plan, sleep_plan.pdf, p. 1");
+
+        // Idempotent once the column exists
+        ensure_tables(&conn).await.unwrap();
+    }
 }
@@ -0,0 +1,185 @@
+use rusqlite::OptionalExtension;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+
+/// A personal early warning sign, e.g. "skipping meals", with optional cue
+/// phrases that also count as a mention ("skipped lunch", "no appetite").
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WarningSign {
+    pub id: i64,
+    pub sign: String,
+    pub cues: Vec<String>,
+    pub created_at: String,
+}
+
+impl WarningSign {
+    /// Whether `text` mentions the sign or one of its cues (case-insensitive).
+    pub fn matches(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        std::iter::once(&self.sign)
+            .chain(&self.cues)
+            .any(|phrase| !phrase.is_empty() && text.contains(&phrase.to_lowercase()))
+    }
+}
+
+/// Creates the warning_signs and warning_flags tables if they don't exist.
+pub async fn create_warning_signs_tables(conn: &Connection) -> Result<()> {
+    conn.call(|conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS warning_signs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sign TEXT NOT NULL,
+                cues TEXT NOT NULL DEFAULT '',
+                removed_at TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE TABLE IF NOT EXISTS warning_flags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                sign_id INTEGER NOT NULL REFERENCES warning_signs(id),
+                session_id TEXT NOT NULL,
+                source TEXT NOT NULL CHECK(source IN ('chat', 'checkin')),
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );",
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to create warning sign tables")?;
+
+    Ok(())
+}
+
+/// Adds a warning sign with its cue phrases and returns its ID.
+pub async fn add_sign(conn: &Connection, sign: &str, cues: &[String]) -> Result<i64> {
+    let sign = sign.trim().to_string();
+    let cues = cues.join(",");
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO warning_signs (sign, cues) VALUES (?1, ?2)",
+            [sign, cues],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+    .await
+    .context("Failed to add warning sign")
+}
+
+/// Lists current (not removed) warning signs, oldest first.
+pub async fn list_signs(conn: &Connection) -> Result<Vec<WarningSign>> {
+    conn.call(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, sign, cues, created_at FROM warning_signs
+             WHERE removed_at IS NULL ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                let cues: String = row.get(2)?;
+                Ok(WarningSign {
+                    id: row.get(0)?,
+                    sign: row.get(1)?,
+                    cues: cues
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    created_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    })
+    .await
+    .context("Failed to list warning signs")
+}
+
+/// Removes a warning sign (past flags are kept). Returns false if no
+/// current sign has that ID.
+pub async fn remove_sign(conn: &Connection, id: i64) -> Result<bool> {
+    conn.call(move |conn| {
+        let updated = conn.execute(
+            "UPDATE warning_signs SET removed_at = datetime('now')
+             WHERE id = ?1 AND removed_at IS NULL",
+            [id],
+        )?;
+        Ok(updated > 0)
+    })
+    .await
+    .context("Failed to remove warning sign")
+}
+
+/// Records that a sign came up in a chat session or check-in.
+pub async fn record_flag(conn: &Connection, sign_id: i64, session_id: &str, source: &str) -> Result<()> {
+    let session_id = session_id.to_string();
+    let source = source.to_string();
+    conn.call(move |conn| {
+        conn.execute(
+            "INSERT INTO warning_flags (sign_id, session_id, source) VALUES (?1, ?2, ?3)",
+            rusqlite::params![sign_id, session_id, source],
+        )?;
+        Ok(())
+    })
+    .await
+    .context("Failed to record warning flag")
+}
+
+/// Whether a sign has already been flagged in this session.
+pub async fn flagged_in_session(conn: &Connection, sign_id: i64, session_id: &str) -> Result<bool> {
+    let session_id = session_id.to_string();
+    conn.call(move |conn| {
+        let found = conn
+            .query_row(
+                "SELECT 1 FROM warning_flags WHERE sign_id = ?1 AND session_id = ?2",
+                rusqlite::params![sign_id, session_id],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    })
+    .await
+    .context("Failed to check warning flags")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_lifecycle_and_flags() {
+        let conn = Connection::open(":memory:").await.unwrap();
+        create_warning_signs_tables(&conn).await.unwrap();
+
+        let meals = add_sign(&conn, "skipping meals", &["no appetite".into(), "skipped lunch".into()])
+            .await
+            .unwrap();
+        let plans = add_sign(&conn, "cancelling plans", &[]).await.unwrap();
+
+        let signs = list_signs(&conn).await.unwrap();
+        assert_eq!(signs.len(), 2);
+        assert_eq!(signs[0].cues, vec!["no appetite", "skipped lunch"]);
+        assert!(signs[1].cues.is_empty());
+
+        assert!(!flagged_in_session(&conn, meals, "s1").await.unwrap());
+        record_flag(&conn, meals, "s1", "chat").await.unwrap();
+        assert!(flagged_in_session(&conn, meals, "s1").await.unwrap());
+        assert!(!flagged_in_session(&conn, meals, "s2").await.unwrap());
+
+        assert!(remove_sign(&conn, plans).await.unwrap());
+        assert!(!remove_sign(&conn, plans).await.unwrap());
+        assert_eq!(list_signs(&conn).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_matches_sign_or_cue() {
+        let sign = WarningSign {
+            id: 1,
+            sign: "Skipping meals".into(),
+            cues: vec!["no appetite".into()],
+            created_at: String::new(),
+        };
+        assert!(sign.matches("I've been skipping meals again"));
+        assert!(sign.matches("Honestly I have No Appetite lately"));
+        assert!(!sign.matches("I had a big lunch"));
+    }
+}
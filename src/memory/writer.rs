@@ -0,0 +1,366 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_rusqlite::Connection;
+
+use crate::error::{Context, Result};
+use crate::memory::usage::ReplyUsage;
+use crate::memory::{insert_chat_turn, InputSource, Provenance};
+
+/// One exchange to save: their message and the reply to it, with what was
+/// measured about each.
+#[derive(Debug, Clone, Default)]
+pub struct Exchange {
+    pub session_id: String,
+    pub input: String,
+    /// How the message was entered.
+    pub source: InputSource,
+    /// Emotion labels for the message (see `emotion::format_labels`).
+    pub emotions: Option<String>,
+    /// Time from the previous reply to the message.
+    pub latency_ms: Option<u64>,
+    /// Engagement level of the message, 0–1 (see `engagement::score`).
+    pub engagement: f64,
+    pub response: String,
+    /// Coach prompt variant that produced the reply (`None` for the crisis
+    /// response).
+    pub prompt_variant: Option<String>,
+    /// The reply was cut short.
+    pub interrupted: bool,
+    pub usage: Option<ReplyUsage>,
+    /// What produced the reply.
+    pub provenance: Option<Provenance>,
+}
+
+/// When queued exchanges are written (`memory.autosave`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AutosaveMode {
+    /// After every message.
+    #[default]
+    Message,
+    /// Once `every` messages are waiting.
+    Messages,
+    /// `every` seconds after the oldest waiting message.
+    Seconds,
+    /// Only when the session ends or the app exits.
+    Exit,
+}
+
+/// An autosave policy: the mode and its count, for `messages` and
+/// `seconds`. Whatever the mode, exchanges are written when the session
+/// ends, on exit, and before reads that need them (`/retry`, `/good`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Autosave {
+    pub mode: AutosaveMode,
+    pub every: u64,
+}
+
+impl Autosave {
+    /// Whether `waiting` exchanges, the oldest queued `held` ago, are due.
+    fn due(&self, waiting: usize, held: Duration) -> bool {
+        let every = self.every.max(1);
+        match self.mode {
+            AutosaveMode::Message => waiting > 0,
+            AutosaveMode::Messages => waiting as u64 >= every,
+            AutosaveMode::Seconds => waiting > 0 && held >= Duration::from_secs(every),
+            AutosaveMode::Exit => false,
+        }
+    }
+
+    /// When the oldest exchange, queued at `since`, comes due on its own.
+    fn deadline(&self, since: Instant) -> Option<Instant> {
+        (self.mode == AutosaveMode::Seconds).then(|| since + Duration::from_secs(self.every.max(1)))
+    }
+}
+
+enum Event {
+    Save(Exchange),
+    Autosave(Autosave),
+    /// Answered once everything queued before it is written, with the
+    /// error if a write failed since it was last reported.
+    Flush(oneshot::Sender<Option<tokio_rusqlite::Error>>),
+    /// Like `Flush`, but leaves exchanges the policy isn't due to write.
+    Settle(oneshot::Sender<Option<tokio_rusqlite::Error>>),
+}
+
+/// Saves exchanges from a background task, so a turn doesn't wait on the
+/// database. Exchanges that queue up while a write is running go out
+/// together in one transaction, and the `Autosave` policy can hold them for
+/// longer. Reads that need the latest exchange (`/good`, `/retry`) call
+/// `flush` first.
+#[derive(Clone)]
+pub struct SaveQueue {
+    events: mpsc::UnboundedSender<Event>,
+}
+
+impl SaveQueue {
+    /// Starts the save task on `conn`, saving after every message. It runs
+    /// until every handle is dropped, then writes whatever is left.
+    pub fn spawn(conn: Connection) -> Self {
+        let (events, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(conn, rx));
+        Self { events }
+    }
+
+    /// Queues an exchange to be saved.
+    pub fn save(&self, exchange: Exchange) {
+        if self.events.send(Event::Save(exchange)).is_err() {
+            tracing::error!("Save task has stopped; exchange not saved");
+        }
+    }
+
+    /// Changes when exchanges are written, from the next one queued on.
+    pub fn set_autosave(&self, autosave: Autosave) {
+        let _ = self.events.send(Event::Autosave(autosave));
+    }
+
+    /// Waits until every queued exchange is written, and reports a failed
+    /// write since the last flush.
+    pub async fn flush(&self) -> Result<()> {
+        self.wait(Event::Flush).await
+    }
+
+    /// Waits for the writes the autosave policy has due, and reports a
+    /// failed write, without forcing out exchanges it's holding.
+    pub async fn settle(&self) -> Result<()> {
+        self.wait(Event::Settle).await
+    }
+
+    async fn wait(&self, event: fn(oneshot::Sender<Option<tokio_rusqlite::Error>>) -> Event) -> Result<()> {
+        let (done, written) = oneshot::channel();
+        if self.events.send(event(done)).is_err() {
+            tracing::error!("Save task has stopped; nothing to flush");
+            return Ok(());
+        }
+        match written.await {
+            Ok(Some(e)) => Err(e).context("Failed to save chat turns"),
+            Ok(None) => Ok(()),
+            Err(_) => {
+                tracing::error!("Save task stopped during a flush");
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn run(conn: Connection, mut events: mpsc::UnboundedReceiver<Event>) {
+    let mut autosave = Autosave::default();
+    let mut waiting: Vec<Exchange> = Vec::new();
+    // When the oldest waiting exchange was queued
+    let mut since = Instant::now();
+    let mut failed = None;
+    loop {
+        let deadline = if waiting.is_empty() { None } else { autosave.deadline(since) };
+        let event = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, events.recv()).await {
+                Ok(event) => event,
+                Err(_) => {
+                    write(&conn, &mut waiting, &mut failed).await;
+                    continue;
+                }
+            },
+            None => events.recv().await,
+        };
+        let Some(event) = event else {
+            break;
+        };
+
+        let mut forced = false;
+        let mut answers = Vec::new();
+        let mut next = Some(event);
+        while let Some(event) = next {
+            match event {
+                Event::Save(exchange) => {
+                    if waiting.is_empty() {
+                        since = Instant::now();
+                    }
+                    waiting.push(exchange);
+                }
+                Event::Autosave(policy) => autosave = policy,
+                Event::Flush(done) => {
+                    forced = true;
+                    answers.push(done);
+                }
+                Event::Settle(done) => answers.push(done),
+            }
+            next = events.try_recv().ok();
+        }
+
+        if forced || autosave.due(waiting.len(), since.elapsed()) {
+            write(&conn, &mut waiting, &mut failed).await;
+        }
+        for done in answers {
+            let _ = done.send(failed.take());
+        }
+    }
+    write(&conn, &mut waiting, &mut failed).await;
+}
+
+/// Writes the waiting exchanges, keeping the error for the next flush.
+async fn write(conn: &Connection, waiting: &mut Vec<Exchange>, failed: &mut Option<tokio_rusqlite::Error>) {
+    if waiting.is_empty() {
+        return;
+    }
+    let exchanges = waiting.len();
+    if let Err(e) = save_exchanges(conn, std::mem::take(waiting)).await {
+        tracing::error!(error = %e, exchanges, "Failed to save chat turns");
+        *failed = Some(e);
+    }
+}
+
+/// Writes exchanges in one transaction: all of them or none.
+async fn save_exchanges(conn: &Connection, exchanges: Vec<Exchange>) -> Result<(), tokio_rusqlite::Error> {
+    conn.call(move |conn| {
+        let tx = conn.transaction()?;
+        for exchange in &exchanges {
+            write_exchange(&tx, exchange)?;
+        }
+        tx.commit()?;
+        Ok(())
+    })
+    .await
+}
+
+fn write_exchange(conn: &rusqlite::Connection, exchange: &Exchange) -> rusqlite::Result<()> {
+    let message = insert_chat_turn(conn, &exchange.session_id, "user", &exchange.input, exchange.source, None)?;
+    conn.execute(
+        "UPDATE chat_turns SET emotions = ?2, response_latency_ms = ?3, engagement = ?4 WHERE id = ?1",
+        rusqlite::params![
+            message,
+            exchange.emotions,
+            exchange.latency_ms.map(|ms| ms as i64),
+            exchange.engagement,
+        ],
+    )?;
+
+    let reply = insert_chat_turn(
+        conn,
+        &exchange.session_id,
+        "assistant",
+        &exchange.response,
+        InputSource::Text,
+        exchange.prompt_variant.as_deref(),
+    )?;
+    let usage = exchange.usage;
+    conn.execute(
+        "UPDATE chat_turns SET interrupted = ?2, prompt_tokens = ?3, response_tokens = ?4, first_token_ms = ?5,
+            latency_ms = ?6
+         WHERE id = ?1",
+        rusqlite::params![
+            reply,
+            exchange.interrupted,
+            usage.map(|u| u.prompt_tokens as i64),
+            usage.map(|u| u.response_tokens as i64),
+            usage.and_then(|u| u.first_token_ms).map(|ms| ms as i64),
+            usage.map(|u| u.latency_ms as i64),
+        ],
+    )?;
+    if let Some(provenance) = &exchange.provenance {
+        conn.execute(
+            "UPDATE chat_turns SET model = ?2, backend = ?3, prompt_version = ?4, agent = ?5 WHERE id = ?1",
+            rusqlite::params![
+                reply,
+                provenance.model,
+                provenance.backend,
+                provenance.prompt_version,
+                provenance.agent,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{list_sessions, load_session_turns, open_memory};
+
+    #[tokio::test]
+    async fn test_saves_in_order_and_flushes() {
+        let conn = open_memory(":memory:").await.unwrap();
+        let queue = SaveQueue::spawn(conn.clone());
+        for i in 1..=3 {
+            queue.save(Exchange {
+                session_id: "s1".to_string(),
+                input: format!("message {i}"),
+                source: InputSource::Voice,
+                emotions: Some("sad:0.50".to_string()),
+                response: format!("reply {i}"),
+                prompt_variant: Some("v1".to_string()),
+                interrupted: i == 3,
+                provenance: Some(Provenance {
+                    model: "models/plotinus.gguf".to_string(),
+                    backend: "llamacpp".to_string(),
+                    prompt_version: Some("0123abcd".to_string()),
+                    agent: "coach".to_string(),
+                }),
+                ..Default::default()
+            });
+        }
+        queue.flush().await.unwrap();
+
+        let turns = load_session_turns(&conn, "s1").await.unwrap();
+        let contents: Vec<&str> = turns.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, ["message 1", "reply 1", "message 2", "reply 2", "message 3", "reply 3"]);
+        assert_eq!((turns[0].source.as_str(), turns[0].emotions.as_deref()), ("voice", Some("sad:0.50")));
+        assert_eq!((turns[1].source.as_str(), turns[1].prompt_variant.as_deref()), ("text", Some("v1")));
+        assert!(!turns[1].interrupted && turns[5].interrupted);
+        assert_eq!(turns[0].provenance, None, "only replies record provenance");
+        let provenance = turns[1].provenance.as_ref().unwrap();
+        assert_eq!((provenance.backend.as_str(), provenance.agent.as_str()), ("llamacpp", "coach"));
+        assert_eq!(list_sessions(&conn).await.unwrap()[0].message_count, 6);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_is_reported_by_flush() {
+        let conn = open_memory(":memory:").await.unwrap();
+        conn.call(|conn| Ok(conn.execute_batch("DROP TABLE session_index")?)).await.unwrap();
+        let queue = SaveQueue::spawn(conn.clone());
+        queue.save(Exchange { session_id: "s1".to_string(), ..Default::default() });
+        assert!(queue.flush().await.is_err());
+        assert!(queue.flush().await.is_ok(), "reported once");
+        assert!(load_session_turns(&conn, "s1").await.unwrap().is_empty(), "rolled back");
+    }
+
+    #[tokio::test]
+    async fn test_autosave_policies() {
+        let conn = open_memory(":memory:").await.unwrap();
+        let queue = SaveQueue::spawn(conn.clone());
+        let saved = || async { load_session_turns(&conn, "s1").await.unwrap().len() / 2 };
+        let exchange = || Exchange { session_id: "s1".to_string(), ..Default::default() };
+
+        queue.set_autosave(Autosave { mode: AutosaveMode::Messages, every: 2 });
+        queue.save(exchange());
+        queue.settle().await.unwrap();
+        assert_eq!(saved().await, 0, "held until two are waiting");
+        queue.save(exchange());
+        queue.settle().await.unwrap();
+        assert_eq!(saved().await, 2);
+
+        queue.set_autosave(Autosave { mode: AutosaveMode::Exit, every: 0 });
+        queue.save(exchange());
+        queue.settle().await.unwrap();
+        assert_eq!(saved().await, 2, "held until exit");
+        queue.flush().await.unwrap();
+        assert_eq!(saved().await, 3, "a flush writes everything");
+
+        queue.save(exchange());
+        drop(queue);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(saved().await, 4, "written when the queue is dropped");
+    }
+
+    #[test]
+    fn test_autosave_seconds() {
+        let autosave = Autosave { mode: AutosaveMode::Seconds, every: 30 };
+        assert!(!autosave.due(1, Duration::from_secs(10)));
+        assert!(autosave.due(1, Duration::from_secs(30)));
+        assert!(!autosave.due(0, Duration::from_secs(60)));
+        let since = Instant::now();
+        assert_eq!(autosave.deadline(since), Some(since + Duration::from_secs(30)));
+        assert_eq!(Autosave::default().deadline(since), None);
+    }
+}
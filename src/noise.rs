@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+
+/// Laplace noise for differentially private aggregates. Epsilon is the
+/// total privacy budget for everything released, split evenly across
+/// `releases` statistics: smaller epsilon, more noise, more privacy. Each
+/// statistic gets noise scaled to how much one record (a message,
+/// check-in, or session) can change it, divided by its share.
+pub struct Noise {
+    epsilon: f64,
+    releases: usize,
+    rng: SystemRandom,
+}
+
+impl Noise {
+    pub fn new(epsilon: f64, releases: usize) -> Result<Self> {
+        if !(epsilon.is_finite() && epsilon > 0.0) {
+            bail!("Epsilon must be a positive number, got {epsilon}");
+        }
+        if releases == 0 {
+            bail!("Nothing to release");
+        }
+        Ok(Self { epsilon, releases, rng: SystemRandom::new() })
+    }
+
+    /// The total budget.
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// How many statistics the budget is split across.
+    pub fn releases(&self) -> usize {
+        self.releases
+    }
+
+    /// A count one record changes by at most `sensitivity`, noised and
+    /// rounded, never below zero.
+    pub fn count(&self, count: usize, sensitivity: f64) -> usize {
+        self.sum(count as f64, sensitivity).round().max(0.0) as usize
+    }
+
+    /// A sum one record changes by at most `sensitivity`, noised.
+    pub fn sum(&self, sum: f64, sensitivity: f64) -> f64 {
+        sum + self.laplace(sensitivity * self.releases as f64 / self.epsilon)
+    }
+
+    /// A sample from the Laplace distribution around 0 with scale `scale`.
+    fn laplace(&self, scale: f64) -> f64 {
+        let u = self.uniform() - 0.5;
+        -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+    }
+
+    /// Uniform in (0, 1), never exactly either end.
+    fn uniform(&self) -> f64 {
+        let mut bytes = [0u8; 8];
+        // The system generator only fails if the OS has no entropy source,
+        // and then nothing else in the report would work either
+        self.rng.fill(&mut bytes).expect("system random number generator failed");
+        ((u64::from_le_bytes(bytes) >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
+/// A mean from a noised sum of values shifted down by `low` and a noised
+/// count, kept between `low` and `high`. `None` when the noised count is 0.
+pub fn mean(shifted_sum: f64, count: usize, low: f64, high: f64) -> Option<f64> {
+    (count > 0).then(|| (low + shifted_sum / count as f64).clamp(low, high))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_bad_epsilon() {
+        assert!(Noise::new(0.0, 1).is_err());
+        assert!(Noise::new(-1.0, 1).is_err());
+        assert!(Noise::new(f64::NAN, 1).is_err());
+        assert!(Noise::new(1.0, 0).is_err());
+    }
+
+    #[test]
+    fn test_laplace_scale() {
+        let noise = Noise::new(1.0, 1).unwrap();
+        let samples: Vec<f64> = (0..20_000).map(|_| noise.laplace(2.0)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let spread = samples.iter().map(|s| s.abs()).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.1, "centered on 0, got {mean}");
+        assert!((spread - 2.0).abs() < 0.1, "mean absolute deviation is the scale, got {spread}");
+    }
+
+    #[test]
+    fn test_budget_is_split_across_releases() {
+        // Four releases at ε = 2 each get ε = 0.5, so a sensitivity-1 sum
+        // has scale 2
+        let noise = Noise::new(2.0, 4).unwrap();
+        let spread = (0..20_000).map(|_| noise.sum(0.0, 1.0).abs()).sum::<f64>() / 20_000.0;
+        assert!((spread - 2.0).abs() < 0.1, "got {spread}");
+    }
+
+    #[test]
+    fn test_counts_and_means_stay_in_range() {
+        let noise = Noise::new(0.1, 1).unwrap();
+        assert!((0..200).map(|_| noise.count(0, 1.0)).all(|c| c < 1_000));
+        for _ in 0..200 {
+            let m = mean(noise.sum(17.0, 9.0), noise.count(2, 1.0), 1.0, 10.0);
+            assert!(m.is_none_or(|m| (1.0..=10.0).contains(&m)), "{m:?}");
+        }
+        assert_eq!(mean(5.0, 0, 1.0, 10.0), None);
+
+        // Barely any noise at a huge epsilon
+        let faint = Noise::new(1e9, 9).unwrap();
+        assert_eq!(faint.count(12, 1.0), 12);
+        let sum = faint.sum(4.0 * (6.5 - 1.0), 9.0);
+        assert!((mean(sum, faint.count(4, 1.0), 1.0, 10.0).unwrap() - 6.5).abs() < 1e-6);
+    }
+}
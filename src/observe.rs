@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio_rusqlite::Connection;
+
+use crate::memory::observe::{self, Observed};
+use crate::memory::session_names::{self, SessionRef};
+use crate::timezone;
+
+/// Messages shown from before observing started.
+const BACKLOG: usize = 6;
+
+/// Writes come in bursts (message, case note, flags); wait this long after
+/// the first change before reading.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// Checked this often even without a change notification, in case the
+/// file system doesn't deliver them (network drives, some containers).
+const POLL: Duration = Duration::from_secs(2);
+
+/// Opens the database read-only. Unlike `memory::open_memory` this never
+/// creates or migrates tables, so observing can't change a live session.
+pub async fn open_read_only(db_path: &str) -> Result<Connection> {
+    anyhow::ensure!(Path::new(db_path).exists(), "No database at {db_path}");
+    Connection::open_with_flags(
+        db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .await
+    .with_context(|| format!("Failed to open {db_path} read-only"))
+}
+
+/// Tails `session_id` (or every session) in the database at `db_path`,
+/// printing messages, MI stage changes, and warning sign matches as they're
+/// saved, until Ctrl+C. For a supervisor watching a deployment on the same
+/// machine; nothing is written.
+pub async fn run(db_path: &str, session_id: Option<&str>) -> Result<()> {
+    let conn = open_read_only(db_path).await?;
+    // A short ID or name means an existing session; anything else is
+    // waited for. Databases from before session names can't be searched
+    // for names without migrating, so that lookup may fail.
+    let resolved = match session_id {
+        Some(id) => match session_names::resolve_session(&conn, id).await {
+            Ok(SessionRef::Found(found)) => Some(found),
+            Ok(SessionRef::Ambiguous(ids)) => {
+                anyhow::bail!("'{id}' matches {} sessions; use more of the ID: {}", ids.len(), ids.join(", "))
+            }
+            Ok(SessionRef::NotFound) | Err(_) => {
+                println!("Session {id} has no messages yet; waiting for it to start.");
+                Some(id.to_string())
+            }
+        },
+        None => None,
+    };
+    let session_id = resolved.as_deref();
+    let mut cursor = observe::cursor_at_end(&conn, session_id, BACKLOG).await?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let dir = match Path::new(db_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok() {
+            let _ = tx.send(());
+        }
+    })
+    .context("Failed to start database watcher")?;
+    // The directory, not the file: in WAL mode writes land in `-wal` first
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        tracing::warn!(error = %e, dir = %dir.display(), "Can't watch the database; polling instead");
+    }
+
+    match session_id {
+        Some(id) => println!("Observing session {id} (read-only). Ctrl+C to stop."),
+        None => println!("Observing all sessions (read-only). Ctrl+C to stop."),
+    }
+    let mut last_session = session_id.map(str::to_string);
+    loop {
+        let (observed, next) = observe::observed_since(&conn, session_id, cursor).await?;
+        cursor = next;
+        for item in &observed {
+            let session = item_session(item);
+            if last_session.as_deref() != Some(session) {
+                println!("--- session {session} ---");
+                last_session = Some(session.to_string());
+            }
+            println!("{}", render(item));
+        }
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = rx.recv() => {
+                tokio::time::sleep(SETTLE).await;
+                while rx.try_recv().is_ok() {}
+            }
+            _ = tokio::time::sleep(POLL) => {}
+        }
+    }
+}
+
+fn item_session(item: &Observed) -> &str {
+    match item {
+        Observed::Message { session_id, .. }
+        | Observed::CaseNote { session_id, .. }
+        | Observed::WarningSign { session_id, .. } => session_id,
+    }
+}
+
+/// One line per event, prefixed with the time it was saved.
+fn render(item: &Observed) -> String {
+    match item {
+        Observed::Message { role, content, created_at, crisis, interrupted, .. } => {
+            let speaker = if role == "user" { "Client" } else { "Chiron" };
+            let mut marks = String::new();
+            if *crisis {
+                marks.push_str(" [CRISIS RESPONSE]");
+            }
+            if *interrupted {
+                marks.push_str(" [interrupted]");
+            }
+            format!("[{}] {speaker}{marks}: {content}", time_of(created_at))
+        }
+        Observed::CaseNote { turn_number, mi_stage, created_at, .. } => format!(
+            "[{}]   (turn {turn_number}, MI stage: {})",
+            time_of(created_at),
+            mi_stage.as_deref().unwrap_or("unknown")
+        ),
+        Observed::WarningSign { sign, created_at, .. } => {
+            format!("[{}]   !! Warning sign: {sign}", time_of(created_at))
+        }
+    }
+}
+
+/// `HH:MM:SS` in the display time zone from a stored timestamp.
+fn time_of(created_at: &str) -> String {
+    match timezone::parse_utc(created_at) {
+        Some(utc) => timezone::display_zone().convert(utc).format("%H:%M:%S").to_string(),
+        None => created_at.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_marks_crisis_and_warning_signs() {
+        let crisis = Observed::Message {
+            session_id: "s1".to_string(),
+            role: "assistant".to_string(),
+            content: "Please call 988.".to_string(),
+            created_at: "2026-03-01 21:04:05".to_string(),
+            crisis: true,
+            interrupted: false,
+        };
+        assert_eq!(render(&crisis), "[21:04:05] Chiron [CRISIS RESPONSE]: Please call 988.");
+
+        let flag = Observed::WarningSign {
+            session_id: "s1".to_string(),
+            sign: "skipping meals".to_string(),
+            created_at: "2026-03-01 21:04:06".to_string(),
+        };
+        assert_eq!(render(&flag), "[21:04:06]   !! Warning sign: skipping meals");
+        assert_eq!(item_session(&flag), "s1");
+    }
+}
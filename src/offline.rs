@@ -0,0 +1,126 @@
+use tokio_rusqlite::Connection;
+
+use crate::catalog::{RelaxationCatalog, RelaxationScript};
+use crate::error::Result;
+use crate::i18n::{t, tf};
+use crate::memory::coping;
+
+/// Supportive lines for offline replies, taken in turn.
+const SUPPORT: [&str; 3] = ["offline.support_1", "offline.support_2", "offline.support_3"];
+
+/// Relaxation scripts suggested in an offline reply.
+const SCRIPTS: usize = 3;
+
+/// Most messages kept for the model; older ones are dropped so the prompt
+/// doesn't grow with every failed attempt.
+const MAX_QUEUED: usize = 5;
+
+/// Messages sent while the model couldn't answer, kept so the latest are
+/// answered together once it can again.
+#[derive(Debug, Default)]
+pub struct Offline {
+    queued: Vec<String>,
+    failures: usize,
+}
+
+impl Offline {
+    /// Whether messages are waiting for the model.
+    pub fn is_offline(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    /// What to send the model: the waiting messages, then `input`.
+    pub fn message(&self, input: &str) -> String {
+        self.queued.iter().map(String::as_str).chain([input]).collect::<Vec<_>>().join("\n\n")
+    }
+
+    /// Keeps `input` for later, after the model was unavailable for it,
+    /// dropping the oldest beyond `MAX_QUEUED`. Returns how many times in a
+    /// row the model has been unavailable.
+    pub fn queue(&mut self, input: &str) -> usize {
+        self.queued.push(input.to_string());
+        if self.queued.len() > MAX_QUEUED {
+            self.queued.remove(0);
+        }
+        self.failures += 1;
+        self.failures
+    }
+
+    /// Forgets the waiting messages once the model has answered them.
+    pub fn clear(&mut self) {
+        self.queued.clear();
+        self.failures = 0;
+    }
+}
+
+/// A scripted reply while the model is unavailable: what happened, a
+/// supportive line, and what can be done without the model (relaxation
+/// scripts, skills that helped before, thought records, crisis resources).
+/// `failures` is how many times in a row the model was unavailable, to vary
+/// the line.
+pub async fn fallback(conn: &Connection, relax: Option<&RelaxationCatalog>, failures: usize) -> Result<String> {
+    let skills = coping::list_skills(conn).await?;
+    let scripts = relax.map_or(&[][..], |catalog| catalog.scripts.as_slice());
+    Ok(render(failures, scripts, &coping::helpful_skills(&skills)))
+}
+
+fn render(failures: usize, scripts: &[RelaxationScript], helpful: &[&str]) -> String {
+    let mut lines = vec![
+        t("offline.unavailable").to_string(),
+        t(SUPPORT[failures.saturating_sub(1) % SUPPORT.len()]).to_string(),
+        String::new(),
+        t("offline.try").to_string(),
+    ];
+    for script in scripts.iter().take(SCRIPTS) {
+        lines.push(format!("  {}", tf("offline.relax", &[("id", &script.id), ("name", &script.name)])));
+    }
+    if !helpful.is_empty() {
+        lines.push(format!("  {}", tf("offline.helped", &[("skills", &helpful.join(", "))])));
+    }
+    lines.push(format!("  {}", t("offline.tools")));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue() {
+        let mut offline = Offline::default();
+        assert!(!offline.is_offline());
+        assert_eq!(offline.message("hi"), "hi");
+        assert_eq!(offline.queue("Work is a lot"), 1);
+        assert_eq!(offline.queue("Are you there?"), 2);
+        assert_eq!(offline.message("Hello?"), "Work is a lot\n\nAre you there?\n\nHello?");
+        offline.clear();
+        assert!(!offline.is_offline());
+    }
+
+    #[test]
+    fn test_queue_keeps_the_latest_messages() {
+        let mut offline = Offline::default();
+        for i in 0..MAX_QUEUED + 2 {
+            assert_eq!(offline.queue(&format!("message {i}")), i + 1);
+        }
+        let message = offline.message("now");
+        assert!(!message.contains("message 1\n"), "{message}");
+        assert!(message.starts_with("message 2\n\n"), "{message}");
+        assert_eq!(message.matches("\n\n").count(), MAX_QUEUED);
+    }
+
+    #[test]
+    fn test_render() {
+        let script = RelaxationScript {
+            id: "breathing".to_string(),
+            name: "Paced breathing".to_string(),
+            description: String::new(),
+            steps: Vec::new(),
+        };
+        let reply = render(1, &[script], &["Going for a walk"]);
+        assert!(reply.contains("/relax breathing"), "{reply}");
+        assert!(reply.contains("Going for a walk"), "{reply}");
+        assert!(reply.contains("/crisis"), "{reply}");
+        assert_ne!(render(1, &[], &[]), render(2, &[], &[]), "the supportive line varies");
+    }
+}
@@ -0,0 +1,185 @@
+use super::{Orchestrator, Retry, TurnResult};
+use crate::assessment::quick_reply::{self, QuickReply};
+use crate::assessment::scoring::ResponseScale;
+use crate::error::Result;
+use crate::homework;
+use crate::i18n::t;
+use crate::memory::feedback::{self, Feedback};
+use crate::offline;
+use crate::safety_plan;
+use crate::warning;
+
+/// What became of a chat message (see `Orchestrator::command` and
+/// `Orchestrator::reply`).
+#[derive(Debug)]
+pub enum Handled {
+    /// The coach answered.
+    Turn(TurnResult),
+    /// Answered without the coach: a command's output, crisis resources, a
+    /// thought record step, a re-prompt for an answer off the scale, or the
+    /// scripted reply while the model is unavailable.
+    Reply(String),
+    /// `/end` closed the session; its summary. Later messages go to a new
+    /// session.
+    Ended(String),
+    /// A command that prompts, plays, or clears the screen, so only an
+    /// interactive terminal can run it.
+    Terminal(TerminalCommand),
+}
+
+/// Commands left to the terminal frontend, with their arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalCommand {
+    /// `/hide`: clear the screen until Enter.
+    Hide,
+    /// `/sleep [log|week]`
+    Sleep(String),
+    /// `/values [explore]`
+    Values(String),
+    /// `/relax [list|<id>]`
+    Relax(String),
+}
+
+impl Orchestrator {
+    /// Handles `input` if it's a command, or an answer the coach doesn't
+    /// see (a thought record step, a number off the scale the coach just
+    /// asked about). `None` means it's a message for `reply`.
+    pub async fn command(&mut self, input: &str) -> Result<Option<Handled>> {
+        // Hiding can't wait on a save; the frontend saves once the screen is clear
+        if input.eq_ignore_ascii_case("/hide") {
+            return Ok(Some(Handled::Terminal(TerminalCommand::Hide)));
+        }
+        // Commands read the session, so the last turn must be saved; a
+        // message only waits for what autosave has due
+        if input.starts_with('/') {
+            self.saves.flush().await?;
+        } else {
+            self.saves.settle().await?;
+        }
+
+        if input.eq_ignore_ascii_case("reset") {
+            self.reset();
+            return Ok(Some(Handled::Reply(t("chat.reset").to_string())));
+        }
+        if input.eq_ignore_ascii_case("/crisis") {
+            return Ok(Some(Handled::Reply(safety_plan::break_glass(&self.chat_conn).await)));
+        }
+        if let Some(args) = command_args(input, "/thought-record") {
+            return Ok(Some(Handled::Reply(self.thought_record_command(args).await?)));
+        }
+        // While a thought record is in progress, answers go to it, not the coach
+        if self.thought_record_active() && !input.starts_with('/') {
+            return Ok(Some(Handled::Reply(self.thought_record_answer(input).await?)));
+        }
+
+        let (name, args) = input.split_at(input.find(' ').unwrap_or(input.len()));
+        let reply = match name {
+            "/sleep" => return Ok(Some(Handled::Terminal(TerminalCommand::Sleep(args.trim().to_string())))),
+            "/values" => return Ok(Some(Handled::Terminal(TerminalCommand::Values(args.trim().to_string())))),
+            "/relax" => return Ok(Some(Handled::Terminal(TerminalCommand::Relax(args.to_string())))),
+            "/warning" => warning::command(&self.chat_conn, args).await?,
+            "/homework" => homework::command(&self.chat_conn, &self.session_id, args).await?,
+            "/coping" => self.coping_command(args).await?,
+            "/goal" => self.goal_command(args).await?,
+            "/modality" => self.modality_command(args).await?,
+            "/mode" => self.mode_command(args),
+            "/verbosity" => self.verbosity_command(args).await?,
+            "/retry" => {
+                return Ok(Some(match self.retry(args).await? {
+                    Retry::Replaced(turn) => {
+                        self.pending_scale = quick_reply::detect(&turn.response).filter(|_| !turn.interrupted);
+                        Handled::Turn(turn)
+                    }
+                    Retry::Skipped(message) => Handled::Reply(message),
+                }));
+            }
+            // Feedback on the last reply: /good, /bad <reason>, or 👍/👎
+            "/good" | "👍" => self.feedback_command(Feedback::Good, args).await?,
+            "/bad" | "👎" => self.feedback_command(Feedback::Bad, args).await?,
+            "/end" if args.is_empty() => {
+                let summary = self.end_session().await?;
+                self.pending_scale = None;
+                return Ok(Some(Handled::Ended(summary)));
+            }
+            _ => {
+                // A bare number answers the coach's scaled question: checked
+                // against the scale before it reaches the coach (see `reply`)
+                return Ok(self
+                    .pending_scale
+                    .as_ref()
+                    .filter(|scale| quick_reply::classify(scale, input) == QuickReply::Invalid)
+                    .map(|scale| Handled::Reply(quick_reply::invalid(scale))));
+            }
+        };
+        Ok(Some(Handled::Reply(reply)))
+    }
+
+    /// Answers a message that isn't a command (see `command`) with a turn.
+    ///
+    /// An answer to the coach's scaled question is sent with its label.
+    /// While the model is busy past its wait (`is_transient`), messages
+    /// wait and get a scripted reply; the next message retries with the
+    /// latest of them. Any other failure is returned and nothing is kept.
+    pub async fn reply(&mut self, input: &str) -> Result<Handled> {
+        let answer;
+        let input = match self.pending_scale.as_ref().map(|scale| quick_reply::classify(scale, input)) {
+            Some(QuickReply::Answer(text)) => {
+                answer = text;
+                answer.as_str()
+            }
+            _ => input,
+        };
+
+        let message = self.offline.message(input);
+        let turn = match self.run_turn_captured(&message).await {
+            Ok(turn) => turn,
+            Err(e) if e.is_transient() => {
+                tracing::warn!(error = %e, "Model unavailable; replying offline");
+                let failures = self.offline.queue(input);
+                self.pending_scale = None;
+                let fallback = offline::fallback(&self.chat_conn, self.relax_catalog.as_ref(), failures).await?;
+                return Ok(Handled::Reply(fallback));
+            }
+            Err(e) => return Err(e),
+        };
+        self.offline.clear();
+        self.pending_scale = quick_reply::detect(&turn.response).filter(|_| !turn.interrupted);
+        Ok(Handled::Turn(turn))
+    }
+
+    /// Whether messages are waiting for the model to come back.
+    pub fn is_offline(&self) -> bool {
+        self.offline.is_offline()
+    }
+
+    /// The scale the coach's last reply asked for an answer on, if any.
+    pub fn pending_scale(&self) -> Option<&ResponseScale> {
+        self.pending_scale.as_ref()
+    }
+
+    async fn feedback_command(&self, rating: Feedback, args: &str) -> Result<String> {
+        let reason = args.strip_prefix(' ').unwrap_or(args);
+        let rated = feedback::rate_last_reply(&self.chat_conn, &self.session_id, rating, Some(reason)).await?;
+        Ok(t(if rated { "feedback.thanks" } else { "feedback.nothing" }).to_string())
+    }
+}
+
+/// The arguments of the command `name` if `input` is it, so `/modality`
+/// isn't taken for `/mode`.
+fn command_args<'a>(input: &'a str, name: &str) -> Option<&'a str> {
+    let args = input.strip_prefix(name)?;
+    (args.is_empty() || args.starts_with(' ')).then_some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_args() {
+        assert_eq!(command_args("/goal add Sleep", "/goal"), Some(" add Sleep"));
+        assert_eq!(command_args("/goal", "/goal"), Some(""));
+        assert_eq!(command_args("/goals", "/goal"), None);
+        assert_eq!(command_args("hello", "/goal"), None);
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before the slowest one starts missing
+/// them. Sized for a full response's tokens plus the surrounding events.
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// What tripped a safety check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyKind {
+    /// Crisis keywords; the turn is answered with crisis resources, not the coach.
+    Crisis,
+    /// One of the person's early warning signs was mentioned.
+    WarningSign,
+}
+
+/// One step of a conversation turn, broadcast as it happens so frontends,
+/// bridges, and tests observe the same pipeline.
+///
+/// A turn emits `UserMessage`, then `SafetyFlag` if the crisis check fires,
+/// `AgentSelected`, a `ToolCalled` for each tool the coach calls, any
+/// number of `TokenChunk`s, `ResponseComplete`, a
+/// warning-sign `SafetyFlag` if one was mentioned, and finally
+/// `SessionSaved` (or `TurnFailed` at whatever point it broke).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TurnEvent {
+    UserMessage { session_id: String, text: String },
+    SafetyFlag { kind: SafetyKind, message: String },
+    /// Who answers: `crisis` for the safety response, otherwise the coach
+    /// prompt variant ID.
+    AgentSelected { agent: String },
+    /// The coach called one of its tools (see `agents::tools`).
+    ToolCalled { name: String, arguments: serde_json::Value },
+    TokenChunk(String),
+    ResponseComplete { response: String, think_content: Option<String> },
+    /// The turn is finished and queued to be saved (see `SaveQueue`).
+    SessionSaved { session_id: String, turn_number: i32 },
+    TurnFailed(String),
+}
+
+impl TurnEvent {
+    /// Whether the turn's response is settled: saved or failed.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::SessionSaved { .. } | Self::TurnFailed(_))
+    }
+}
+
+/// Creates the sender an orchestrator broadcasts its turn events on.
+pub fn channel() -> broadcast::Sender<TurnEvent> {
+    broadcast::channel(EVENT_CAPACITY).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_see_events_in_order() {
+        let tx = channel();
+        let mut rx = tx.subscribe();
+        tx.send(TurnEvent::TokenChunk("Hi".into())).unwrap();
+        tx.send(TurnEvent::SessionSaved { session_id: "s1".into(), turn_number: 1 }).unwrap();
+
+        assert_eq!(rx.recv().await.unwrap(), TurnEvent::TokenChunk("Hi".into()));
+        let last = rx.recv().await.unwrap();
+        assert!(last.is_terminal());
+        assert!(!TurnEvent::TokenChunk(String::new()).is_terminal());
+    }
+}
@@ -0,0 +1,183 @@
+/// System prompt section for `/mode listen`.
+pub const LISTEN_INSTRUCTIONS: &str = "This session is for reflective listening only. Reply with reflections (simple and complex) and at most one open question. Give no advice, suggestions, techniques, exercises, or opinions about what they should do, even when asked; reflect the wish for answers instead.";
+
+/// Phrases that make a sentence advice rather than a reflection or an open
+/// question, matched on lowercased text.
+const ADVICE_PHRASES: &[&str] = &[
+    "you should",
+    "you shouldn't",
+    "you need to",
+    "you have to",
+    "you must",
+    "you could try",
+    "you might try",
+    "you may want to",
+    "you might want to",
+    "you could consider",
+    "i suggest",
+    "i'd suggest",
+    "i would suggest",
+    "i recommend",
+    "i'd recommend",
+    "i would recommend",
+    "my advice",
+    "it might help to",
+    "it may help to",
+    "it could help to",
+    "it would help to",
+    "it can help to",
+    "it's important to",
+    "it is important to",
+    "a good idea to",
+    "why don't you",
+    "why not ",
+    "how about ",
+    "what if you tried",
+    "have you tried",
+    "have you considered",
+    "make sure",
+    "deberías",
+    "tienes que",
+    "te sugiero",
+    "te recomiendo",
+    "podrías intentar",
+    "has probado",
+    "has pensado en probar",
+];
+
+/// Imperative openings that suggest an action ("Try journaling...").
+const ADVICE_OPENINGS: &[&str] = &[
+    "try ",
+    "consider ",
+    "remember to ",
+    "don't forget ",
+    "take a ",
+    "take some ",
+    "go for ",
+    "start by ",
+    "focus on ",
+    "practice ",
+    "write down ",
+    "reach out ",
+    "talk to ",
+    "set aside ",
+    "avoid ",
+    "intenta ",
+    "prueba ",
+    "recuerda ",
+];
+
+/// Openings that invite them to keep talking ("Talk to me about it"),
+/// which would otherwise match an advice opening.
+const INVITATIONS: &[&str] = &["talk to me"];
+
+/// Whether a sentence gives advice.
+pub fn is_advice(sentence: &str) -> bool {
+    let lower = sentence.to_lowercase();
+    let opening = lower.trim_start_matches(|c: char| !c.is_alphabetic());
+    let invitation = INVITATIONS.iter().any(|i| opening.starts_with(i));
+    ADVICE_PHRASES.iter().any(|p| lower.contains(p))
+        || (!invitation && ADVICE_OPENINGS.iter().any(|o| opening.starts_with(o)))
+}
+
+/// Drops advice-like sentences from a reply as it streams. Text is held
+/// back until its sentence ends, then shown or dropped whole.
+#[derive(Debug, Default)]
+pub struct AdviceFilter {
+    pending: String,
+    /// Sentences dropped so far.
+    pub dropped: usize,
+}
+
+impl AdviceFilter {
+    /// Adds streamed text and returns what can be shown now.
+    pub fn push(&mut self, text: &str) -> String {
+        self.pending.push_str(text);
+        let mut shown = String::new();
+        while let Some(end) = sentence_end(&self.pending) {
+            let sentence: String = self.pending.drain(..end).collect();
+            shown.push_str(&self.check(sentence));
+        }
+        shown
+    }
+
+    /// Returns the last, unterminated sentence, if it isn't advice.
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.check(rest)
+    }
+
+    fn check(&mut self, sentence: String) -> String {
+        if sentence.trim().is_empty() || !is_advice(&sentence) {
+            return sentence;
+        }
+        self.dropped += 1;
+        // Keep the line break a dropped sentence ended with
+        if sentence.ends_with('\n') {
+            "\n".to_string()
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Byte index just past the first complete sentence: a `.`, `!`, or `?`
+/// (and any closing quotes or brackets) followed by whitespace, or a line
+/// break. The whitespace goes with the sentence.
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' {
+            return Some(i + 1);
+        }
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        while let Some(&(_, next)) = chars.peek() {
+            if matches!(next, '.' | '!' | '?' | '"' | '\'' | ')' | '”' | '’') {
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if let Some(&(j, next)) = chars.peek().filter(|(_, next)| next.is_whitespace()) {
+            return Some(j + next.len_utf8());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_advice() {
+        assert!(is_advice("You should try going for a walk."));
+        assert!(is_advice("Try writing it down tonight."));
+        assert!(is_advice("  - Consider talking to your manager."));
+        assert!(is_advice("Have you tried breathing exercises?"));
+        assert!(!is_advice("It sounds like work has been piling up."));
+        assert!(!is_advice("What feels hardest about it right now?"));
+        assert!(!is_advice("You've tried a lot already."));
+        assert!(!is_advice("Talk to me about what happened."));
+        assert!(is_advice("Talk to a friend tonight."));
+    }
+
+    #[test]
+    fn test_filter_drops_advice_across_chunks() {
+        let mut filter = AdviceFilter::default();
+        let mut shown = String::new();
+        for chunk in ["It sounds exhau", "sting. You might ", "want to rest. ", "What would help", " most?"] {
+            shown.push_str(&filter.push(chunk));
+        }
+        assert_eq!(shown, "It sounds exhausting. ");
+        shown.push_str(&filter.finish());
+        assert_eq!(shown, "It sounds exhausting. What would help most?");
+        assert_eq!(filter.dropped, 1);
+
+        let mut filter = AdviceFilter::default();
+        let shown = filter.push("You feel stuck.\nTry a walk.\nWhat's next?");
+        assert_eq!(shown + &filter.finish(), "You feel stuck.\n\nWhat's next?");
+    }
+}
@@ -1,20 +1,57 @@
+pub mod commands;
+pub mod events;
+pub mod listen;
+pub mod verbosity;
+
 use std::io::{self, Write};
-use std::time::Instant;
+use std::sync::PoisonError;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
 use futures::StreamExt;
 use rig::agent::{Agent, MultiTurnStreamItem};
 use rig::completion::Message;
 use rig::streaming::{StreamedAssistantContent, StreamingChat};
+use tokio::sync::{broadcast, watch};
+use tracing::Instrument;
 use tokio_rusqlite::Connection;
 
-use crate::agents::peer::build_peer_coach_preamble;
-use crate::catalog::{ModeCatalog, PromptVariant};
+use crate::agents::peer::{append_sections, build_peer_coach_preamble};
+use crate::agents::tools;
+use crate::assessment::scoring::ResponseScale;
+use crate::closure::{self, TimeBox, TimeCheck};
+use crate::catalog::{AbTest, Modality, ModalityCatalog, ModeCatalog, PromptVariant, RelaxationCatalog};
+use crate::drift::{self, DriftTracker};
+use crate::emotion;
+use crate::engagement::{self, Disengagement};
+use crate::error::Result;
 use crate::memory;
+use crate::i18n::{self, t, tf};
 use crate::memory::case_notes;
+use crate::memory::coping;
+use crate::memory::engagement::MessageEngagement;
+use crate::memory::goals;
+use crate::memory::homework;
+use crate::memory::modality;
+use crate::memory::preferences;
+use crate::memory::reply_variants;
+use crate::memory::thought_records;
+use crate::memory::usage::ReplyUsage;
+use crate::memory::values;
+use crate::memory::writer::{Autosave, Exchange, SaveQueue};
+use crate::memory::{InputSource, Provenance};
 use crate::memory::retrieval;
-use crate::provider::LlamaCppCompletionModel;
+use crate::offline::Offline;
+use crate::provider::{llamacpp, LlamaCppCompletionModel};
+use crate::reload::AgentToggles;
+use crate::risk;
 use crate::router;
+use crate::speech::Speaker;
+use crate::thought_record::{Progress, ThoughtRecordFlow};
+use crate::warning;
+use crate::wrap::{self, LineWrapper};
+use events::{SafetyKind, TurnEvent};
+use listen::AdviceFilter;
+use verbosity::Verbosity;
 use crate::supervision::{
     analyze_think_block, extract_mi_stage, extract_themes, merge_themes, ThinkAnalysis,
 };
@@ -22,11 +59,59 @@ use rig_fastembed::EmbeddingModel;
 
 /// Maximum number of themes retained in case notes.
 /// Prevents unbounded accumulation that bloats the preamble.
-const MAX_THEMES: usize = 8;
+pub(crate) const MAX_THEMES: usize = 8;
+
+/// Printed before each reply.
+const REPLY_PREFIX: &str = "Chiron: ";
 
 /// Maximum characters for RAG context injected into the preamble.
 const MAX_RAG_CONTEXT_CHARS: usize = 400;
 
+/// A parsed `/goal` chat command.
+#[derive(Debug, PartialEq)]
+pub enum GoalCommand<'a> {
+    Add(&'a str),
+    List,
+    Complete(i64),
+}
+
+/// Parses the arguments after `/goal` (`add <text>`, `list`, `complete <id>`).
+/// A bare `/goal` lists goals.
+pub fn parse_goal_command(args: &str) -> Option<GoalCommand<'_>> {
+    let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+    let rest = rest.trim();
+    match action {
+        "add" if !rest.is_empty() => Some(GoalCommand::Add(rest)),
+        "list" | "" => Some(GoalCommand::List),
+        "complete" | "done" => rest.trim_start_matches('#').parse().ok().map(GoalCommand::Complete),
+        _ => None,
+    }
+}
+
+/// A parsed `/coping` chat command.
+#[derive(Debug, PartialEq)]
+pub enum CopingCommand<'a> {
+    /// A skill the user tried and how well it worked (1–5).
+    Rate(&'a str, i32),
+    List,
+}
+
+/// Parses the arguments after `/coping` (`rate <skill> <1-5>`, `list`).
+/// A bare `/coping` lists skills.
+pub fn parse_coping_command(args: &str) -> Option<CopingCommand<'_>> {
+    let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+    match action {
+        "list" | "" => Some(CopingCommand::List),
+        "rate" => {
+            let (skill, rating) = rest.trim().rsplit_once(' ')?;
+            let rating = rating.parse().ok().filter(|r| (1..=5).contains(r))?;
+            let skill = skill.trim();
+            (!skill.is_empty()).then_some(CopingCommand::Rate(skill, rating))
+        }
+        _ => None,
+    }
+}
+
 /// Builds case notes from a think block analysis and previous notes.
 ///
 /// Extracted from `Orchestrator::update_case_notes` to enable unit testing
@@ -49,6 +134,7 @@ pub fn build_case_notes_from_analysis(
             themes: vec![],
             user_facts: vec![],
             significant_signal: None,
+            homework: None,
             raw_think: String::new(),
         });
 
@@ -56,6 +142,11 @@ pub fn build_case_notes_from_analysis(
         .mi_stage
         .or_else(|| existing_notes.and_then(extract_mi_stage));
 
+    for theme in &analysis.themes {
+        if crate::supervision::taxonomy::Taxonomy::builtin().canonical(theme).is_none() {
+            tracing::debug!(theme = %theme, "Theme outside the tag taxonomy");
+        }
+    }
     let merged = merge_themes(&prev_themes, &analysis.themes, MAX_THEMES);
 
     let mut notes = format!(
@@ -80,6 +171,27 @@ pub fn build_case_notes_from_analysis(
     (notes, mi_stage)
 }
 
+/// Resolves once an interrupt is requested; never without a receiver (or
+/// after its sender is gone).
+async fn interrupt_requested(interrupt: &mut Option<watch::Receiver<bool>>) {
+    if let Some(rx) = interrupt {
+        if rx.wait_for(|requested| *requested).await.is_ok() {
+            return;
+        }
+    }
+    std::future::pending().await
+}
+
+/// Where the orchestrator sends streamed response text.
+pub enum OutputSink {
+    /// Interactive mode: response and decorations printed to stdout.
+    Stdout,
+    /// Script mode: display goes to stderr so stdout stays clean for JSON.
+    Stderr,
+    /// Engine sessions: nothing is printed; subscribers follow the turn
+    /// through its `TurnEvent`s.
+    Silent,
+}
 
 /// Structured result from a single conversation turn (public, for eval/script mode).
 #[derive(Debug, Clone, serde::Serialize)]
@@ -91,6 +203,11 @@ pub struct TurnResult {
     pub case_notes: Option<String>,
     pub preamble_injected: String,
     pub duration_ms: u64,
+    /// Coach prompt variant that produced the response (`None` for the
+    /// crisis response).
+    pub prompt_variant: Option<String>,
+    /// Generation was cut short by an interrupt; `response` is partial.
+    pub interrupted: bool,
 }
 
 /// Internal output from the shared turn pipeline.
@@ -98,6 +215,30 @@ struct TurnOutput {
     response: String,
     think_content: Option<String>,
     preamble: String,
+    prompt_variant: Option<String>,
+    interrupted: bool,
+}
+
+/// What `/retry` did.
+pub enum Retry {
+    /// The last reply was regenerated and replaced.
+    Replaced(TurnResult),
+    /// Nothing was regenerated; the message says why.
+    Skipped(String),
+}
+
+/// The reply a `/retry` turn replaces, and how the new one should differ.
+struct Regenerate {
+    previous: String,
+    steer: Option<String>,
+}
+
+/// A turn that has started but isn't saved yet: what gets saved, marked
+/// interrupted, if it never finishes.
+struct InFlightTurn {
+    input: String,
+    /// Visible reply text streamed so far.
+    partial_response: String,
 }
 
 /// Single-pass pipeline orchestrator.
@@ -113,16 +254,78 @@ struct TurnOutput {
 pub struct Orchestrator {
     peer_coach_model: LlamaCppCompletionModel,
     coach_variant: PromptVariant,
+    /// Variants alternated instead of `coach_variant`, and this session's arm.
+    ab_test: Option<(AbTest, usize)>,
     think_instructions: Option<String>,
     mode_catalog: Option<ModeCatalog>,
+    /// Therapeutic modalities selectable with `/modality` (none = MI only).
+    modality_catalog: Option<ModalityCatalog>,
+    /// Offer the coach its tools (`persona.tools`).
+    tools_enabled: bool,
+    /// Live `tools_enabled` / `show_thinking` values from a reloaded config,
+    /// applied at the start of each turn.
+    toggles: Option<watch::Receiver<AgentToggles>>,
     chat_history: Vec<Message>,
     session_id: String,
     chat_conn: Connection,
+    /// Saves finished turns in the background; reads that need the last
+    /// turn flush it first.
+    saves: SaveQueue,
+    /// Model and backend recorded with each reply (see `set_model`).
+    provenance: Provenance,
     turn_number: i32,
     show_thinking: bool,
-    /// When true, streaming output goes to stderr instead of stdout.
-    /// Used in script mode to keep stdout clean for JSON output.
-    output_to_stderr: bool,
+    /// Destination for streamed output (stdout, stderr, or nowhere).
+    output: OutputSink,
+    /// Broadcasts each step of a turn to subscribers.
+    events: broadcast::Sender<TurnEvent>,
+    /// How the next user message was entered. Reset to text after each turn.
+    input_source: InputSource,
+    /// Reads visible response text aloud when set (`--speak`).
+    speaker: Option<Speaker>,
+    /// Set to `true` to stop the reply being generated (Ctrl+C in the CLI).
+    interrupt: Option<watch::Receiver<bool>>,
+    /// Set when the coach's last reply asked a scaled question.
+    pending_scale: Option<ResponseScale>,
+    /// Messages the model couldn't answer yet.
+    offline: Offline,
+    /// Relaxation scripts suggested while the model is unavailable.
+    relax_catalog: Option<RelaxationCatalog>,
+    /// Guided thought record in progress (`/thought-record`), if any.
+    thought_record: Option<ThoughtRecordFlow>,
+    /// Reflective listening only (`/mode listen`): the prompt rules out
+    /// advice and advice-like sentences are dropped from replies.
+    listen_only: bool,
+    /// Summary of the episode this session continues after a long gap
+    /// (see `episodes::episode_break`).
+    previous_episode: Option<String>,
+    /// Set while `/retry` regenerates the last reply.
+    regenerate: Option<Regenerate>,
+    /// Days of crisis history from earlier sessions carried into the
+    /// prompt (see `risk::load`); 0 turns it off.
+    risk_history_days: u32,
+    /// Messages in a row off their active goals before the coach offers to
+    /// come back to one; 0 turns it off.
+    refocus_after: u32,
+    /// Tracks how long the conversation has been away from their goals.
+    drift: DriftTracker,
+    /// When the last reply was saved, to time how long they take to answer it.
+    last_reply_at: Option<Instant>,
+    /// Time from the last reply to the message being answered now.
+    message_latency: Option<Duration>,
+    /// Set once the coach has been told they seem to be disengaging, so it
+    /// isn't raised again until the trend clears.
+    disengagement_noted: bool,
+    /// Set when the last reply was a closing, because they were winding
+    /// down or the session ran past its time limit.
+    closing: bool,
+    /// Minutes a session runs before the coach proposes wrapping up; 0
+    /// means no limit.
+    session_minutes: u32,
+    /// The session's time limit, if it has one (see `set_session_minutes`).
+    time_box: Option<TimeBox>,
+    /// When this session started, or was resumed.
+    session_started: Instant,
     /// Maximum number of messages (user+assistant pairs) in the sliding window.
     max_history_messages: usize,
     /// Rolling checkpoint counter (incremented each time the sliding window drains).
@@ -139,6 +342,13 @@ pub struct Orchestrator {
     facts_extracted: u32,
     /// Number of significant turns flagged and stored during this session.
     significant_turns_flagged: u32,
+    /// Vector store writes still running in the background (see `flush`).
+    pending_writes: Vec<tokio::task::JoinHandle<()>>,
+    /// The turn being answered, kept so a crash can still save it (see
+    /// `save_interrupted_turn`). A mutex because tokens arrive through `&self`.
+    in_flight: std::sync::Mutex<Option<InFlightTurn>>,
+    /// Wraps streamed replies to the terminal width (stdout only).
+    wrapper: std::sync::Mutex<LineWrapper>,
 }
 
 impl Orchestrator {
@@ -158,14 +368,41 @@ impl Orchestrator {
         Self {
             peer_coach_model,
             coach_variant,
+            ab_test: None,
             think_instructions,
             mode_catalog,
+            modality_catalog: None,
+            tools_enabled: false,
+            toggles: None,
             chat_history: Vec::new(),
             session_id,
+            saves: SaveQueue::spawn(chat_conn.clone()),
+            provenance: Provenance::default(),
             chat_conn,
             turn_number: 0,
             show_thinking,
-            output_to_stderr: false,
+            output: OutputSink::Stdout,
+            events: events::channel(),
+            input_source: InputSource::Text,
+            speaker: None,
+            interrupt: None,
+            pending_scale: None,
+            offline: Offline::default(),
+            relax_catalog: None,
+            thought_record: None,
+            listen_only: false,
+            previous_episode: None,
+            regenerate: None,
+            risk_history_days: 0,
+            refocus_after: 0,
+            drift: DriftTracker::default(),
+            last_reply_at: None,
+            message_latency: None,
+            disengagement_noted: false,
+            closing: false,
+            session_minutes: 0,
+            time_box: None,
+            session_started: Instant::now(),
             max_history_messages: max_history_turns * 2,
             checkpoint_counter: 0,
             vector_conn,
@@ -174,18 +411,192 @@ impl Orchestrator {
             initial_mi_stage: None,
             facts_extracted: 0,
             significant_turns_flagged: 0,
+            pending_writes: Vec::new(),
+            in_flight: std::sync::Mutex::new(None),
+            wrapper: std::sync::Mutex::new(LineWrapper::default()),
+        }
+    }
+
+    /// The session turns are saved to (changes on `/end`).
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Sets where streamed output goes (stderr for script mode, nowhere for engine sessions).
+    pub fn set_output(&mut self, output: OutputSink) {
+        self.output = output;
+    }
+
+    /// Subscribes to the `TurnEvent`s of every turn from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<TurnEvent> {
+        self.events.subscribe()
+    }
+
+    /// Broadcasts a turn event. Having no subscribers is normal (plain CLI use).
+    fn emit(&self, event: TurnEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Sets the relaxation scripts offered while the model is unavailable.
+    pub fn set_relax_catalog(&mut self, catalog: RelaxationCatalog) {
+        self.relax_catalog = Some(catalog);
+    }
+
+    /// Enables spoken output of responses.
+    pub fn set_speaker(&mut self, speaker: Speaker) {
+        self.speaker = Some(speaker);
+    }
+
+    /// Stops generation once `interrupt` turns `true`. The partial reply is
+    /// saved and marked interrupted instead of being analyzed.
+    pub fn set_interrupt(&mut self, interrupt: watch::Receiver<bool>) {
+        self.interrupt = Some(interrupt);
+    }
+
+    /// Enables `/modality` and modality framing in the preamble.
+    pub fn set_modality_catalog(&mut self, catalog: ModalityCatalog) {
+        self.modality_catalog = Some(catalog);
+    }
+
+    /// Lets the coach call tools (memory search, assessments, journal)
+    /// instead of only replying.
+    pub fn enable_tools(&mut self) {
+        self.tools_enabled = true;
+    }
+
+    /// Follows `config.toml` reloads (see `reload::spawn`): the coach's
+    /// tools and think-block display change from the next turn on.
+    pub fn set_toggles(&mut self, toggles: watch::Receiver<AgentToggles>) {
+        self.toggles = Some(toggles);
+    }
+
+    /// Alternates the test's variants instead of the fixed coach variant,
+    /// with this session in `arm` (see `AbTest::variant`).
+    pub fn set_ab_test(&mut self, ab_test: AbTest, arm: usize) {
+        self.ab_test = Some((ab_test, arm));
+    }
+
+    /// The coach prompt variant for the current turn.
+    fn active_variant(&self) -> &PromptVariant {
+        match &self.ab_test {
+            Some((ab_test, arm)) => ab_test.variant(*arm, self.turn_number),
+            None => &self.coach_variant,
+        }
+    }
+
+    /// Gives the coach a summary of the previous episode of this
+    /// conversation, in the preamble of every turn.
+    pub fn set_previous_episode(&mut self, summary: String) {
+        self.previous_episode = Some(summary);
+    }
+
+    /// Carries crisis history from the last `days` days of earlier sessions
+    /// into the prompt, with a check-in on softer risk phrases.
+    pub fn set_risk_history_days(&mut self, days: u32) {
+        self.risk_history_days = days;
+    }
+
+    /// Offers to come back to their goals after `messages` in a row about
+    /// other things (see `drift`).
+    pub fn set_refocus_after(&mut self, messages: u32) {
+        self.refocus_after = messages;
+    }
+
+    /// Proposes wrapping up once a session has run `minutes`, with a
+    /// warning shortly before; 0 turns it off.
+    pub fn set_session_minutes(&mut self, minutes: u32) {
+        self.session_minutes = minutes;
+        self.time_box = TimeBox::new(minutes);
+    }
+
+    /// Names the model and backend generating replies, recorded with each
+    /// one.
+    pub fn set_model(&mut self, backend: &str, model: &str) {
+        self.provenance.backend = backend.to_string();
+        self.provenance.model = model.to_string();
+    }
+
+    /// Sets when finished turns are written to disk (see `Autosave`).
+    pub fn set_autosave(&mut self, autosave: Autosave) {
+        self.saves.set_autosave(autosave);
+    }
+
+    /// Waits until every finished turn is saved, for reads of the session
+    /// outside a turn (`/good`, listing messages).
+    pub async fn flush_saves(&self) -> Result<()> {
+        self.saves.flush().await
+    }
+
+    /// Waits for the saves the autosave policy has due, and reports a
+    /// failed one; turns it's holding stay queued.
+    pub async fn settle_saves(&self) -> Result<()> {
+        self.saves.settle().await
+    }
+
+    /// Whether the last reply was a closing (see `closure`), so a bare
+    /// goodbye now can end the session.
+    pub fn closing(&self) -> bool {
+        self.closing
+    }
+
+    /// Marks how the next user message was entered (recorded with the saved turn).
+    pub fn set_input_source(&mut self, source: InputSource) {
+        self.input_source = source;
+    }
+
+    /// Restores the sliding window from previously saved `(role, content)` turns.
+    ///
+    /// Used when resuming a session that is no longer in memory. Only the most
+    /// recent `max_history_messages` are kept; the turn counter continues from
+    /// `turn_number`, the session's user turns so far.
+    pub fn restore_history(&mut self, turns: &[(String, String)], turn_number: i32) {
+        self.chat_history = turns
+            .iter()
+            .map(|(role, content)| match role.as_str() {
+                "assistant" => Message::assistant(content),
+                _ => Message::user(content),
+            })
+            .collect();
+        let excess = self.chat_history.len().saturating_sub(self.max_history_messages);
+        self.chat_history.drain(..excess);
+        self.turn_number = turn_number;
+    }
+
+    /// Waits for background vector store writes (user facts, significant
+    /// turns, checkpoints) still in flight, so none are lost on exit.
+    pub async fn flush(&mut self) {
+        if let Err(e) = self.saves.flush().await {
+            tracing::error!(error = %e, "Failed to save the last turns");
+        }
+        for write in self.pending_writes.drain(..) {
+            if let Err(e) = write.await {
+                tracing::warn!(error = %e, "Background write did not finish");
+            }
         }
     }
 
-    /// Sets output to stderr (for script mode where stdout is reserved for JSON).
-    pub fn set_output_to_stderr(&mut self, value: bool) {
-        self.output_to_stderr = value;
+    /// Saves the turn that was being answered when its task panicked, with
+    /// whatever reply had streamed, marked interrupted, and tells
+    /// subscribers the turn is over. Returns whether there was one.
+    pub async fn save_interrupted_turn(&mut self) -> Result<bool> {
+        self.emit(TurnEvent::TurnFailed("The reply was cut off by a crash".to_string()));
+        let turn = self.in_flight.get_mut().unwrap_or_else(PoisonError::into_inner).take();
+        let Some(turn) = turn else {
+            return Ok(false);
+        };
+        tracing::warn!(turn_number = self.turn_number, "Saving a turn that never finished");
+        let variant = self.active_variant().clone();
+        self.save_and_record(&turn.input, &turn.partial_response, Some(&variant), true, None);
+        self.saves.flush().await?;
+        Ok(true)
     }
 
     /// Clears conversation history (but not the database or case notes).
     pub fn reset(&mut self) {
         self.chat_history.clear();
         self.turn_number = 0;
+        self.last_reply_at = None;
+        self.pending_scale = None;
     }
 
     /// Ends the current session, generates a mechanical summary, stores it,
@@ -193,6 +604,7 @@ impl Orchestrator {
     ///
     /// Returns the summary string for display to the user.
     pub async fn end_session(&mut self) -> Result<String> {
+        self.saves.flush().await?;
         // Build mechanical summary from tracked state
         let existing_notes = case_notes::get_latest_case_note(&self.chat_conn).await?;
         let current_mi_stage = existing_notes
@@ -261,67 +673,460 @@ impl Orchestrator {
         self.session_id = new_session_id;
         self.chat_history.clear();
         self.turn_number = 0;
+        self.listen_only = false;
+        self.previous_episode = None;
         self.checkpoint_counter = 0;
         self.initial_mi_stage = None;
         self.facts_extracted = 0;
         self.significant_turns_flagged = 0;
+        self.last_reply_at = None;
+        self.disengagement_noted = false;
+        self.time_box = TimeBox::new(self.session_minutes);
+        self.session_started = Instant::now();
 
         Ok(summary_text)
     }
 
-    /// Runs one full conversation turn.
-    #[tracing::instrument(level = "info", skip(self))]
-    pub async fn run_turn(&mut self, input: &str) -> Result<()> {
-        let turn_start = Instant::now();
-        self.turn_number += 1;
+    /// Runs a `/goal` command and returns the message to show the user.
+    ///
+    /// Goals are stored with the current session but stay active across
+    /// sessions until completed; active ones are injected into the preamble.
+    pub async fn goal_command(&self, args: &str) -> Result<String> {
+        let Some(command) = parse_goal_command(args) else {
+            return Ok(t("goal.usage").to_string());
+        };
 
-        // Crisis short-circuit
-        if router::is_crisis(input) {
-            let response = router::crisis_response();
-            self.print_response(response);
-            self.save_and_record(input, response).await?;
-            return Ok(());
+        match command {
+            GoalCommand::Add(description) => {
+                let id = goals::add_goal(&self.chat_conn, &self.session_id, description).await?;
+                Ok(tf("goal.added", &[("id", &id)]))
+            }
+            GoalCommand::List => {
+                let all = goals::list_goals(&self.chat_conn, false).await?;
+                let mut lines: Vec<String> = all
+                    .iter()
+                    .map(|g| {
+                        let mark = if g.completed { "x" } else { " " };
+                        format!("[{mark}] #{} {}", g.id, g.description)
+                    })
+                    .collect();
+                if lines.is_empty() {
+                    lines.push(t("goal.none").to_string());
+                }
+                // Point goal setting at what matters most but is lived least
+                let profile = values::values_profile(&self.chat_conn).await?;
+                lines.extend(crate::values::goal_hint(&profile));
+                Ok(lines.join("\n"))
+            }
+            GoalCommand::Complete(id) => {
+                if goals::complete_goal(&self.chat_conn, id).await? {
+                    Ok(tf("goal.completed", &[("id", &id)]))
+                } else {
+                    Ok(tf("goal.not_found", &[("id", &id)]))
+                }
+            }
+        }
+    }
+
+    /// Runs a `/coping` command. Ratings feed the preamble so the coach
+    /// favors skills that helped and stops suggesting ones that didn't.
+    pub async fn coping_command(&self, args: &str) -> Result<String> {
+        let Some(command) = parse_coping_command(args) else {
+            return Ok(t("coping.usage").to_string());
+        };
+
+        match command {
+            CopingCommand::Rate(skill, rating) => {
+                coping::rate_skill(&self.chat_conn, &self.session_id, skill, rating).await?;
+                Ok(tf("coping.rated", &[("skill", &skill), ("rating", &rating)]))
+            }
+            CopingCommand::List => {
+                let skills = coping::list_skills(&self.chat_conn).await?;
+                if skills.is_empty() {
+                    return Ok(t("coping.none").to_string());
+                }
+                let lines: Vec<String> = skills
+                    .iter()
+                    .map(|s| format!("{:.1}  {} ({}x)", s.average, s.name, s.times_rated))
+                    .collect();
+                Ok(lines.join("\n"))
+            }
         }
+    }
 
-        let _output = self.run_turn_inner(input).await?;
+    /// Runs a `/modality` command: no argument shows the current modality and
+    /// the options with an explanation of each; an ID switches to it for this
+    /// session (and later sessions until changed).
+    pub async fn modality_command(&self, args: &str) -> Result<String> {
+        let Some(catalog) = &self.modality_catalog else {
+            return Ok(t("modality.unavailable").to_string());
+        };
+        let current = self.current_modality().await?;
+
+        let id = args.trim();
+        if id.is_empty() {
+            let name = current.map_or("-", |m| m.name.as_str());
+            let mut lines = vec![tf("modality.current", &[("name", &name)])];
+            for m in &catalog.modalities {
+                lines.push(format!("  {:<12} {}", m.id, m.description));
+            }
+            lines.push(t("modality.usage").to_string());
+            return Ok(lines.join("\n"));
+        }
 
-        tracing::info!(
-            total_ms = turn_start.elapsed().as_millis() as u64,
-            "Turn complete"
-        );
-        Ok(())
+        let Some(chosen) = catalog.get_modality(id) else {
+            return Ok(tf("modality.unknown", &[("id", &id)]));
+        };
+        modality::set_modality(&self.chat_conn, &self.session_id, &chosen.id).await?;
+        tracing::info!(modality = chosen.id, session_id = self.session_id, "Modality changed");
+        Ok(tf("modality.switched", &[("name", &chosen.name), ("description", &chosen.description)]))
+    }
+
+    /// The session's modality, falling back to the first in the catalog.
+    async fn current_modality(&self) -> Result<Option<&Modality>> {
+        let Some(catalog) = &self.modality_catalog else {
+            return Ok(None);
+        };
+        let chosen = modality::current_modality(&self.chat_conn, &self.session_id).await?;
+        Ok(chosen
+            .and_then(|id| catalog.get_modality(&id))
+            .or_else(|| catalog.modalities.first()))
+    }
+
+    /// Compares the message with their active goals and returns a nudge
+    /// back toward the closest one once enough messages in a row have been
+    /// off them. Not in listening mode, and nothing without embeddings.
+    async fn refocus(&mut self, input: &str, active_goals: &[goals::Goal]) -> Option<String> {
+        use rig::embeddings::EmbeddingModel as _;
+
+        let model = self.embedding_model.as_ref()?;
+        if self.refocus_after == 0 || self.listen_only || active_goals.is_empty() {
+            return None;
+        }
+        let texts = std::iter::once(input.to_string())
+            .chain(active_goals.iter().map(|g| g.description.clone()))
+            .collect::<Vec<_>>();
+        let mut vectors = match model.embed_texts(texts).await {
+            Ok(embeddings) => embeddings.into_iter().map(|e| e.vec).collect::<Vec<_>>(),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to embed message for drift check");
+                return None;
+            }
+        };
+        let message = vectors.remove(0);
+        let (goal, similarity) = drift::closest(active_goals, &message, &vectors)?;
+        if !self.drift.observe(similarity, self.refocus_after) {
+            return None;
+        }
+        tracing::info!(session_id = self.session_id, goal = goal.id, "Conversation drifted from goals; offering to refocus");
+        Some(drift::nudge(goal, self.refocus_after))
+    }
+
+    /// Checks their recent messages, this one included, for signs of
+    /// disengagement. A trend is returned the first time it shows, and
+    /// again only after it has cleared.
+    async fn disengagement(&mut self, input: &str) -> Result<Option<Disengagement>> {
+        let mut messages =
+            memory::engagement::recent_messages(&self.chat_conn, &self.session_id, engagement::HISTORY - 1).await?;
+        messages.push(MessageEngagement {
+            chars: input.chars().count() as u64,
+            latency_ms: self.message_latency.map(|l| l.as_millis() as u64),
+        });
+        let trend = engagement::disengagement(&messages);
+        let noted = std::mem::replace(&mut self.disengagement_noted, trend.is_some());
+        if noted {
+            return Ok(None);
+        }
+        if let Some(trend) = &trend {
+            tracing::info!(session_id = self.session_id, ?trend, "Messages suggest disengagement");
+        }
+        Ok(trend)
+    }
+
+    /// Runs a `/verbosity` command: no argument shows the current reply
+    /// length; `brief`, `normal`, or `detailed` sets it for this and later
+    /// sessions.
+    pub async fn verbosity_command(&self, args: &str) -> Result<String> {
+        let level = args.trim();
+        if level.is_empty() {
+            let current = self.current_verbosity().await?;
+            return Ok(format!(
+                "{}\n{}",
+                tf("verbosity.current", &[("level", &current.as_str())]),
+                t("verbosity.usage")
+            ));
+        }
+
+        let Some(chosen) = Verbosity::parse(level) else {
+            return Ok(tf("verbosity.unknown", &[("level", &level)]));
+        };
+        preferences::set_preference(&self.chat_conn, Verbosity::PREFERENCE, chosen.as_str()).await?;
+        tracing::info!(verbosity = chosen.as_str(), "Verbosity changed");
+        Ok(tf("verbosity.set", &[("level", &chosen.as_str())]))
+    }
+
+    /// The stored reply length, `normal` if never chosen.
+    async fn current_verbosity(&self) -> Result<Verbosity> {
+        let stored = preferences::get_preference(&self.chat_conn, Verbosity::PREFERENCE).await?;
+        Ok(stored.as_deref().and_then(Verbosity::parse).unwrap_or_default())
+    }
+
+    /// Runs a `/mode` command: `listen` switches this session to reflective
+    /// listening only, `normal` switches back, and no argument shows which
+    /// is on.
+    pub fn mode_command(&mut self, args: &str) -> String {
+        match args.trim() {
+            "" if self.listen_only => t("listen.status_on").to_string(),
+            "" => t("listen.status_off").to_string(),
+            "listen" => {
+                self.listen_only = true;
+                tracing::info!(session_id = self.session_id, "Listening mode on");
+                t("listen.on").to_string()
+            }
+            "normal" => {
+                self.listen_only = false;
+                tracing::info!(session_id = self.session_id, "Listening mode off");
+                t("listen.off").to_string()
+            }
+            other => tf("listen.unknown", &[("mode", &other)]),
+        }
+    }
+
+    /// Whether a guided thought record is waiting for an answer.
+    pub fn thought_record_active(&self) -> bool {
+        self.thought_record.is_some()
+    }
+
+    /// Runs a `/thought-record` command: no argument (or `start`) begins a
+    /// guided record, `list` summarizes recent ones, `cancel` abandons the
+    /// one in progress.
+    pub async fn thought_record_command(&mut self, args: &str) -> Result<String> {
+        match args.trim() {
+            "" | "start" => {
+                let flow = self.thought_record.insert(ThoughtRecordFlow::default());
+                Ok(format!("{}\n{}", t("thought_record.intro"), flow.prompt()))
+            }
+            "list" => {
+                let records = thought_records::recent_thought_records(&self.chat_conn, 5).await?;
+                if records.is_empty() {
+                    return Ok(t("thought_record.none").to_string());
+                }
+                let lines: Vec<String> = records
+                    .iter()
+                    .map(|r| {
+                        let date = r.created_at.get(..10).unwrap_or_default();
+                        format!("{date}  {}", r.record.summary())
+                    })
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            "cancel" => Ok(match self.thought_record.take() {
+                Some(_) => t("thought_record.cancelled").to_string(),
+                None => t("thought_record.not_active").to_string(),
+            }),
+            _ => Ok(t("thought_record.usage").to_string()),
+        }
+    }
+
+    /// Feeds an answer to the thought record in progress and returns the next
+    /// question, a correction, or (once complete) the saved summary.
+    pub async fn thought_record_answer(&mut self, input: &str) -> Result<String> {
+        let Some(flow) = &mut self.thought_record else {
+            return Ok(t("thought_record.not_active").to_string());
+        };
+
+        // Answers get the same safety check as chat messages
+        let crisis = router::is_crisis(input).then(router::crisis_response);
+
+        let reply = match flow.answer(input) {
+            Progress::Next(prompt) | Progress::Invalid(prompt) => prompt.to_string(),
+            Progress::Done(record) => {
+                self.thought_record = None;
+                thought_records::save_thought_record(&self.chat_conn, &self.session_id, &record)
+                    .await?;
+                tracing::info!(session_id = self.session_id, "Saved thought record");
+                tf("thought_record.saved", &[("summary", &record.summary())])
+            }
+        };
+
+        Ok(match crisis {
+            Some(response) => format!("{response}\n\n{reply}"),
+            None => reply,
+        })
+    }
+
+    /// Runs `/retry`: answers the last user message again, optionally
+    /// steered (`/retry shorter`), and replaces the last reply with the new
+    /// one. Both replies are kept in `reply_variants`, the new one marked
+    /// kept. The crisis response is never regenerated.
+    pub async fn retry(&mut self, steer: &str) -> Result<Retry> {
+        self.saves.flush().await?;
+        let Some(last) = reply_variants::last_exchange(&self.chat_conn, &self.session_id).await? else {
+            return Ok(Retry::Skipped(t("retry.nothing").to_string()));
+        };
+        if last.prompt_variant.is_none() {
+            return Ok(Retry::Skipped(t("retry.crisis").to_string()));
+        }
+
+        // The exchange leaves the window and the turn is numbered again
+        let window = self.chat_history.len().saturating_sub(2);
+        let replaced: Vec<Message> = self.chat_history.drain(window..).collect();
+        self.turn_number -= 1;
+        self.input_source = match last.source.as_str() {
+            "voice" => InputSource::Voice,
+            _ => InputSource::Text,
+        };
+        let steer = Some(steer.trim()).filter(|s| !s.is_empty());
+        self.regenerate = Some(Regenerate {
+            previous: last.response.clone(),
+            steer: steer.map(str::to_string),
+        });
+        let result = self.run_turn_captured(&last.input).await;
+        self.regenerate = None;
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                // The old reply stands
+                self.chat_history.splice(window..window, replaced);
+                return Err(e);
+            }
+        };
+        self.saves.flush().await?;
+        reply_variants::record_retry(&self.chat_conn, &self.session_id, result.turn_number, &last, steer, &result.response)
+            .await?;
+        tracing::info!(session_id = self.session_id, turn_number = result.turn_number, steered = steer.is_some(), "Regenerated reply");
+        Ok(Retry::Replaced(result))
+    }
+
+    /// Runs one full conversation turn.
+    pub async fn run_turn(&mut self, input: &str) -> Result<()> {
+        self.run_turn_captured(input).await.map(|_| ())
     }
 
     /// Runs one full conversation turn and returns structured results.
     ///
-    /// Same pipeline as `run_turn` but captures all intermediate data
-    /// for evaluation and scripted testing.
-    #[tracing::instrument(level = "info", skip(self))]
+    /// Each step is broadcast to `subscribe`rs as a `TurnEvent`; the result
+    /// also captures intermediate data for evaluation and scripted testing.
+    ///
+    /// Everything logged during the turn is correlated by the span's `turn_id`.
+    #[tracing::instrument(
+        name = "turn",
+        level = "info",
+        skip_all,
+        fields(
+            session_id = %self.session_id,
+            turn_id = %uuid::Uuid::new_v4().simple(),
+            turn_number = tracing::field::Empty,
+            input_chars = input.chars().count(),
+        )
+    )]
     pub async fn run_turn_captured(&mut self, input: &str) -> Result<TurnResult> {
         let turn_start = Instant::now();
+        self.message_latency = self.last_reply_at.map(|at| turn_start.duration_since(at));
+        self.closing = false;
         self.turn_number += 1;
+        tracing::Span::current().record("turn_number", self.turn_number);
+        if let Some(toggles) = &self.toggles {
+            let toggles = *toggles.borrow();
+            self.tools_enabled = toggles.tools;
+            self.show_thinking = toggles.show_thinking;
+        }
+        *self.in_flight.get_mut().unwrap_or_else(PoisonError::into_inner) = Some(InFlightTurn {
+            input: input.to_string(),
+            partial_response: String::new(),
+        });
+        self.emit(TurnEvent::UserMessage {
+            session_id: self.session_id.clone(),
+            text: input.to_string(),
+        });
 
-        // Crisis short-circuit
-        if router::is_crisis(input) {
-            let response = router::crisis_response();
-            self.print_response(response);
-            self.save_and_record(input, response).await?;
-            return Ok(TurnResult {
+        // Reads in the turn (recent messages, risk history) see the turns
+        // saved so far, which is all of them unless autosave holds some back
+        let result = match self.saves.settle().await {
+            Ok(()) => self.capture_turn(input, turn_start).await,
+            Err(e) => Err(e),
+        };
+        match &result {
+            Ok(_) => self.emit(TurnEvent::SessionSaved {
+                session_id: self.session_id.clone(),
                 turn_number: self.turn_number,
-                input: input.to_string(),
-                response: response.to_string(),
+            }),
+            Err(e) => {
+                // A failed turn is reported, not saved
+                self.in_flight.get_mut().unwrap_or_else(PoisonError::into_inner).take();
+                self.emit(TurnEvent::TurnFailed(e.to_string()));
+            }
+        }
+        result
+    }
+
+    /// Runs the turn (crisis short-circuit, or the coach pipeline followed by
+    /// the warning sign check) and collects its `TurnResult`.
+    async fn capture_turn(&mut self, input: &str, turn_start: Instant) -> Result<TurnResult> {
+        let safety_check = tracing::info_span!("safety_check", check = "crisis", flagged = tracing::field::Empty);
+        let is_crisis = safety_check.in_scope(|| router::is_crisis(input));
+        safety_check.record("flagged", is_crisis);
+        drop(safety_check);
+
+        let output = if is_crisis {
+            let response = router::crisis_response();
+            self.emit(TurnEvent::SafetyFlag {
+                kind: SafetyKind::Crisis,
+                message: response.clone(),
+            });
+            self.emit(TurnEvent::AgentSelected { agent: "crisis".to_string() });
+            self.print_response(&response);
+            self.emit(TurnEvent::ResponseComplete {
+                response: response.clone(),
                 think_content: None,
-                case_notes: None,
-                preamble_injected: String::new(),
-                duration_ms: turn_start.elapsed().as_millis() as u64,
             });
-        }
+            self.save_and_record(input, &response, None, false, None);
+            TurnOutput {
+                response,
+                think_content: None,
+                preamble: String::new(),
+                prompt_variant: None,
+                interrupted: false,
+            }
+        } else {
+            let output = self.run_turn_inner(input).await?;
+            if output.interrupted {
+                return Ok(TurnResult {
+                    turn_number: self.turn_number,
+                    input: input.to_string(),
+                    response: output.response,
+                    think_content: output.think_content,
+                    case_notes: None,
+                    preamble_injected: output.preamble,
+                    duration_ms: turn_start.elapsed().as_millis() as u64,
+                    prompt_variant: output.prompt_variant,
+                    interrupted: true,
+                });
+            }
 
-        let output = self.run_turn_inner(input).await?;
+            // Gentle note if they mentioned one of their early warning signs
+            let flag = warning::check(&self.chat_conn, &self.session_id, input, "chat")
+                .instrument(tracing::info_span!("safety_check", check = "warning_signs"))
+                .await?;
+            if let Some(flag) = flag {
+                self.print_decoration(&format!("\n\x1b[2m{flag}\x1b[0m\n"))?;
+                self.emit(TurnEvent::SafetyFlag {
+                    kind: SafetyKind::WarningSign,
+                    message: flag,
+                });
+            }
+            output
+        };
 
         // Fetch the case notes we just wrote
         let updated_notes = case_notes::get_latest_case_note(&self.chat_conn).await?;
 
+        tracing::info!(
+            total_ms = turn_start.elapsed().as_millis() as u64,
+            "Turn complete"
+        );
+
         Ok(TurnResult {
             turn_number: self.turn_number,
             input: input.to_string(),
@@ -330,15 +1135,19 @@ impl Orchestrator {
             case_notes: updated_notes,
             preamble_injected: output.preamble,
             duration_ms: turn_start.elapsed().as_millis() as u64,
+            prompt_variant: output.prompt_variant,
+            interrupted: false,
         })
     }
 
     /// Shared turn pipeline: RAG retrieve → load notes → build preamble → stream → update notes → save.
     async fn run_turn_inner(&mut self, input: &str) -> Result<TurnOutput> {
+        let variant = self.active_variant().clone();
+
         // Step 1: Load latest case notes
         let existing_notes = case_notes::get_latest_case_note(&self.chat_conn).await?;
 
-        // Step 1.5: RAG retrieval (if vector store is available)
+        // Step 2: RAG retrieval (if vector store is available)
         let rag_context = if let (Some(vconn), Some(model)) =
             (&self.vector_conn, &self.embedding_model)
         {
@@ -362,122 +1171,390 @@ impl Orchestrator {
             None
         };
 
-        // Step 2: Build peer coach with preamble + RAG context + case notes + mode guidance
+        // Step 3: Active goals, the values profile behind them, and homework
+        let active_goals = goals::list_goals(&self.chat_conn, true).await?;
+        let profile = values::values_profile(&self.chat_conn).await?;
+        let assigned = homework::list_homework(&self.chat_conn).await?;
+        let goals_context = [
+            goals::format_goals_for_prompt(&active_goals),
+            values::format_values_for_prompt(&profile),
+            homework::format_homework_for_prompt(&assigned),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        // A way back to their goals after drifting away from them
+        let refocus = self.refocus(input, &active_goals).await;
+
+        // Coping skills they've rated (helped / didn't help)
+        let skills = coping::list_skills(&self.chat_conn).await?;
+        let coping_context = coping::format_coping_for_prompt(&skills);
+
+        // Step 4: Sections for this turn extend the base prompt (never truncated)
+        let mut sections: Vec<(&str, String)> = Vec::new();
+
+        // Modality framing
+        if let Some(m) = self.current_modality().await?.filter(|m| !m.coach_framing.is_empty()) {
+            sections.push(("Approach", m.coach_framing.clone()));
+        }
+
+        // Reply length preference, in the prompt and the token budget
+        let verbosity = self.current_verbosity().await?;
+        if let Some(instruction) = verbosity.instruction() {
+            sections.push(("Response Length", instruction.to_string()));
+        }
+        if let Some(nudge) = refocus {
+            sections.push(("Refocus", nudge));
+        }
+
+        // Emotions in their message steer the technique
+        let emotions = emotion::classify(input);
+        if let Some(section) = emotion::format_for_prompt(&emotions) {
+            tracing::info!(emotions = emotion::format_labels(&emotions), "Emotions detected");
+            sections.push(("Emotions", section));
+        }
+
+        // A gentle word when they seem to be drifting out of the conversation
+        if let Some(trend) = self.disengagement(input).await? {
+            sections.push(("Engagement", trend.format_for_prompt()));
+        }
+
+        // Winding down, or running past the session's time limit, moves
+        // into a closing instead of just stopping
+        self.closing = closure::winding_down(input, self.message_latency);
+        if self.closing {
+            tracing::info!(session_id = self.session_id, "Winding down; asking for a closing");
+            sections.push(("Closing", closure::closing_instructions()));
+        }
+        let elapsed = self.session_started.elapsed();
+        if let Some(check) = self.time_box.as_mut().and_then(|time_box| time_box.check(elapsed)) {
+            tracing::info!(session_id = self.session_id, ?check, "Session time limit");
+            self.closing |= matches!(check, TimeCheck::Over { .. });
+            sections.push(("Time", check.format_for_prompt()));
+        }
+
+        // Reply language, when it differs from the interface's
+        if let Some(language) = i18n::response_language() {
+            sections.push((
+                "Response Language",
+                format!("Reply in {language}, whatever language they write in. Keep MI terms and the think block in English."),
+            ));
+        }
+        if self.listen_only {
+            sections.push(("Reflective Listening", listen::LISTEN_INSTRUCTIONS.to_string()));
+        }
+        if let Some(summary) = &self.previous_episode {
+            sections.push((
+                "Previous Episode",
+                format!("This conversation picks up after a long break. Where it left off:\n{summary}"),
+            ));
+        }
+
+        // Crisis history from earlier sessions means a closer watch, and a
+        // direct check-in when softer risk phrases come up
+        let risk_history = risk::load(
+            &self.chat_conn,
+            &self.session_id,
+            self.risk_history_days,
+            chrono::Utc::now().naive_utc(),
+        )
+        .await?;
+        if let Some(history) = &risk_history {
+            sections.push(("Risk History", history.to_prompt(self.risk_history_days)));
+            if let Some(phrase) = router::elevated_risk(input) {
+                tracing::info!(session_id = self.session_id, "Softer risk phrase after recent crisis; asking for a check-in");
+                sections.push(("Check In Now", risk::check_in_prompt(&phrase)));
+            }
+        }
+        if let Some(regenerate) = &self.regenerate {
+            sections.push((
+                "Another Reply",
+                format!(
+                    "You already answered this message with:\n\"{}\"\nThey asked for a different reply{}. Write a new one; don't repeat the previous reply.",
+                    regenerate.previous,
+                    regenerate.steer.as_deref().map(|s| format!(": {s}")).unwrap_or_default(),
+                ),
+            ));
+        }
+        let base = append_sections(&variant.preamble, &sections);
+
+        // Step 5: Build peer coach with preamble + RAG context + goals + case notes + mode guidance
         let preamble = build_peer_coach_preamble(
-            &self.coach_variant.preamble,
+            &base,
             self.think_instructions.as_deref(),
             existing_notes.as_deref(),
             self.mode_catalog.as_ref(),
             rag_context.as_deref(),
+            Some(goals_context.as_str()),
+            coping_context.as_deref(),
         );
 
-        let peer_coach = rig::agent::AgentBuilder::new(self.peer_coach_model.clone())
+        let builder = rig::agent::AgentBuilder::new(self.peer_coach_model.clone())
             .preamble(&preamble)
-            .temperature(self.coach_variant.temperature)
-            .max_tokens(self.coach_variant.max_tokens as u64)
-            .build();
+            .temperature(variant.temperature)
+            .max_tokens(verbosity.max_tokens(variant.max_tokens) as u64);
+        let peer_coach = if self.tools_enabled {
+            builder
+                .tools(tools::coach_tools(
+                    self.chat_conn.clone(),
+                    self.vector_conn.clone(),
+                    self.embedding_model.clone(),
+                    self.rag_top_k,
+                ))
+                .default_max_turns(tools::MAX_TOOL_ROUNDS)
+                .build()
+        } else {
+            builder.build()
+        };
+
+        // Step 6: Stream response (returns visible text + think block content)
+        // Listen mode's advice filter stays off while risk is in play, so
+        // safety guidance ("reach out to…") gets through
+        self.emit(TurnEvent::AgentSelected { agent: variant.id.clone() });
+        let filter_advice = self.listen_only && risk_history.is_none();
+        let (response, think_content, interrupted, usage) =
+            self.stream_peer_coach(&peer_coach, input, filter_advice).await?;
+        self.emit(TurnEvent::ResponseComplete {
+            response: response.clone(),
+            think_content: think_content.clone(),
+        });
 
-        // Step 3: Stream response (returns visible text + think block content)
-        let (response, think_content) = self.stream_peer_coach(&peer_coach, input).await?;
+        // A cut-off reply is kept for the record but not analyzed: its think
+        // block may be incomplete
+        if interrupted {
+            self.save_and_record(input, &response, Some(&variant), true, usage);
+            return Ok(TurnOutput {
+                response,
+                think_content,
+                preamble,
+                prompt_variant: Some(variant.id),
+                interrupted,
+            });
+        }
 
-        // Step 4: Analyze think block and update case notes
+        // Step 7: Analyze think block and update case notes
         let analysis = self.update_case_notes(input, &response, think_content.as_deref(), existing_notes.as_deref())
             .await?;
 
-        // Step 4.5: Store user facts and significant turns in vector store (background)
+        // Step 8: Homework the coach and person agreed on this turn
+        if let Some((task, due)) = &analysis.homework {
+            homework::assign_homework(&self.chat_conn, &self.session_id, task, due.as_deref()).await?;
+            let note = match due {
+                Some(due) => tf("homework.noted_due", &[("task", task), ("due", due)]),
+                None => tf("homework.noted", &[("task", task)]),
+            };
+            self.print_decoration(&format!("\n\x1b[2m{note}\x1b[0m\n"))?;
+        }
+
+        // Step 9: Store user facts and significant turns in vector store (background)
         self.maybe_store_rag_data(input, &response, &analysis, analysis.mi_stage.as_deref());
 
-        // Step 5: Save turn to DB + update history
-        self.save_and_record(input, &response).await?;
+        // Step 10: Save turn to DB + update history
+        self.save_and_record(input, &response, Some(&variant), false, usage);
 
         Ok(TurnOutput {
             response,
             think_content,
             preamble,
+            prompt_variant: Some(variant.id),
+            interrupted: false,
         })
     }
 
-    /// Prints a response to the appropriate output stream.
+    /// Prints a complete (non-streamed) response to the output sink.
     fn print_response(&self, text: &str) {
-        if self.output_to_stderr {
-            eprintln!("\nChiron: {text}");
-        } else {
-            println!("\nChiron: {text}");
+        if let Some(speaker) = &self.speaker {
+            speaker.push(text);
+            speaker.flush();
+        }
+        self.emit(TurnEvent::TokenChunk(text.to_string()));
+        match &self.output {
+            OutputSink::Stdout => match wrap::width() {
+                Some(width) => println!("\n{REPLY_PREFIX}{}", wrap::wrap(text, width, REPLY_PREFIX.len())),
+                None => println!("\n{REPLY_PREFIX}{text}"),
+            },
+            OutputSink::Stderr => eprintln!("\n{REPLY_PREFIX}{text}"),
+            OutputSink::Silent => {}
         }
     }
 
-    /// Streams the peer coach response, printing visible tokens to the display output.
-    /// Returns (visible_response, think_content).
-    ///
-    /// Display goes to stderr when `output_to_stderr` is true (script mode),
-    /// otherwise to stdout (interactive mode).
+    /// Writes display-only text (labels, think block) to stdout/stderr.
+    /// Not broadcast as events.
+    fn print_decoration(&self, text: &str) -> Result<()> {
+        match &self.output {
+            OutputSink::Stdout => {
+                print!("{text}");
+                io::stdout().flush()?;
+            }
+            OutputSink::Stderr => {
+                eprint!("{text}");
+                io::stderr().flush()?;
+            }
+            OutputSink::Silent => {}
+        }
+        Ok(())
+    }
+
+    /// Writes a streamed response token to the output sink.
+    fn print_token(&self, text: &str) -> Result<()> {
+        if let Some(speaker) = &self.speaker {
+            speaker.push(text);
+        }
+        if let Some(turn) = self.in_flight.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            turn.partial_response.push_str(text);
+        }
+        self.emit(TurnEvent::TokenChunk(text.to_string()));
+        match (&self.output, wrap::width()) {
+            (OutputSink::Stdout, Some(width)) => {
+                let wrapped = self.wrapper.lock().unwrap_or_else(PoisonError::into_inner).push(text, width);
+                self.print_decoration(&wrapped)
+            }
+            _ => self.print_decoration(text),
+        }
+    }
+
+    /// Prints what the wrapper is still holding back at the end of a reply.
+    fn finish_tokens(&self) -> Result<()> {
+        if let (OutputSink::Stdout, Some(width)) = (&self.output, wrap::width()) {
+            let rest = self.wrapper.lock().unwrap_or_else(PoisonError::into_inner).finish(width);
+            self.print_decoration(&rest)?;
+        }
+        Ok(())
+    }
+
+    /// Streams the peer coach response, printing visible tokens to the output sink.
+    /// Returns (visible_response, think_content, interrupted, usage); usage
+    /// is `None` if generation didn't finish.
+    #[tracing::instrument(
+        name = "agent",
+        level = "info",
+        skip_all,
+        fields(agent = %self.active_variant().id, tools = self.tools_enabled, interrupted = tracing::field::Empty)
+    )]
     async fn stream_peer_coach(
         &self,
         peer_coach: &Agent<LlamaCppCompletionModel>,
         input: &str,
-    ) -> Result<(String, Option<String>)> {
-        let use_stderr = self.output_to_stderr;
-
+        filter_advice: bool,
+    ) -> Result<(String, Option<String>, bool, Option<ReplyUsage>)> {
         // Display think block header if show_thinking is enabled
         if self.show_thinking {
-            if use_stderr {
-                eprint!("\n\x1b[2m[thinking...]\x1b[0m");
-                io::stderr().flush()?;
-            } else {
-                print!("\n\x1b[2m[thinking...]\x1b[0m");
-                io::stdout().flush()?;
-            }
+            self.print_decoration("\n\x1b[2m[thinking...]\x1b[0m")?;
         }
 
-        if use_stderr {
-            eprint!("\nChiron: ");
-            io::stderr().flush()?;
-        } else {
-            print!("\nChiron: ");
-            io::stdout().flush()?;
-        }
+        self.print_decoration(&format!("\n{REPLY_PREFIX}"))?;
+        self.wrapper.lock().unwrap_or_else(PoisonError::into_inner).start(REPLY_PREFIX.len());
 
+        let started = Instant::now();
         let mut stream = peer_coach
             .stream_chat(input, self.chat_history.clone())
             .await;
 
         let mut full_response = String::new();
-
-        while let Some(chunk) = stream.next().await {
+        let mut interrupt = self.interrupt.clone();
+        let mut interrupted = false;
+        let mut first_token_ms = None;
+        let mut usage = None;
+        let mut advice_filter = filter_advice.then(AdviceFilter::default);
+
+        loop {
+            let chunk = tokio::select! {
+                biased;
+                _ = interrupt_requested(&mut interrupt) => {
+                    interrupted = true;
+                    break;
+                }
+                chunk = stream.next() => chunk,
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
             match chunk {
                 Ok(MultiTurnStreamItem::StreamAssistantItem(
                     StreamedAssistantContent::Text(text),
                 )) => {
-                    if use_stderr {
-                        eprint!("{}", text.text);
-                        io::stderr().flush()?;
-                    } else {
-                        print!("{}", text.text);
-                        io::stdout().flush()?;
-                    }
-                    full_response.push_str(&text.text);
+                    first_token_ms.get_or_insert_with(|| started.elapsed().as_millis() as u64);
+                    let shown = match advice_filter.as_mut() {
+                        Some(filter) => filter.push(&text.text),
+                        None => text.text,
+                    };
+                    self.print_token(&shown)?;
+                    full_response.push_str(&shown);
+                }
+                Ok(MultiTurnStreamItem::StreamAssistantItem(
+                    StreamedAssistantContent::ToolCall { tool_call, .. },
+                )) => {
+                    let name = tool_call.function.name;
+                    tracing::info!(tool = name, "Coach called a tool");
+                    self.print_decoration(&format!("\x1b[2m[{name}]\x1b[0m "))?;
+                    self.emit(TurnEvent::ToolCalled {
+                        name,
+                        arguments: tool_call.function.arguments,
+                    });
                 }
                 Ok(MultiTurnStreamItem::FinalResponse(final_resp)) => {
-                    if full_response.is_empty() {
-                        full_response = final_resp.response().to_string();
-                        if use_stderr {
-                            eprint!("{full_response}");
-                            io::stderr().flush()?;
-                        } else {
-                            print!("{full_response}");
-                            io::stdout().flush()?;
-                        }
+                    let tokens = final_resp.usage();
+                    usage = Some(ReplyUsage {
+                        prompt_tokens: tokens.input_tokens,
+                        response_tokens: tokens.output_tokens,
+                        first_token_ms,
+                        latency_ms: started.elapsed().as_millis() as u64,
+                    });
+                    if first_token_ms.is_none() {
+                        let text = final_resp.response().to_string();
+                        let shown = match advice_filter.as_mut() {
+                            Some(filter) => filter.push(&text),
+                            None => text,
+                        };
+                        self.print_token(&shown)?;
+                        full_response.push_str(&shown);
                     }
                 }
+                Err(e) if full_response.is_empty() => {
+                    // Nothing reached the user; fail the turn rather than save an empty reply
+                    return Err(llamacpp::streaming_error(e));
+                }
                 Err(e) => {
+                    // Saved as far as it got, marked cut off
                     tracing::error!(error = %e, "Streaming error");
+                    interrupted = true;
                     break;
                 }
                 _ => {}
             }
         }
 
-        if use_stderr { eprintln!(); } else { println!(); }
+        // Dropping the stream stops generation
+        drop(stream);
+
+        // Listening mode: the last sentence, and something to say if every
+        // sentence was advice
+        if let Some(filter) = advice_filter.as_mut() {
+            let rest = filter.finish();
+            self.print_token(&rest)?;
+            full_response.push_str(&rest);
+            if filter.dropped > 0 {
+                tracing::info!(dropped = filter.dropped, "Dropped advice from a listening-mode reply");
+            }
+            if full_response.trim().is_empty() && !interrupted {
+                let fallback = t("listen.fallback");
+                self.print_token(fallback)?;
+                full_response.push_str(fallback);
+            }
+        }
+        self.finish_tokens()?;
+        tracing::Span::current().record("interrupted", interrupted);
+        if interrupted {
+            tracing::info!(visible_chars = full_response.len(), "Turn interrupted");
+            self.print_decoration(" \x1b[2m[interrupted]\x1b[0m")?;
+        }
+
+        self.print_decoration("\n")?;
+        if let Some(speaker) = &self.speaker {
+            speaker.flush();
+        }
 
         // Always capture think content for case note analysis
         let think_content = self.peer_coach_model.take_think_content();
@@ -485,15 +1562,9 @@ impl Orchestrator {
         // Show think block content if flag is set
         if self.show_thinking {
             if let Some(ref think) = think_content {
-                if use_stderr {
-                    eprintln!("\x1b[2m--- think block ---\x1b[0m");
-                    eprintln!("\x1b[2m{think}\x1b[0m");
-                    eprintln!("\x1b[2m--- end think ---\x1b[0m");
-                } else {
-                    println!("\x1b[2m--- think block ---\x1b[0m");
-                    println!("\x1b[2m{think}\x1b[0m");
-                    println!("\x1b[2m--- end think ---\x1b[0m");
-                }
+                self.print_decoration(&format!(
+                    "\x1b[2m--- think block ---\x1b[0m\n\x1b[2m{think}\x1b[0m\n\x1b[2m--- end think ---\x1b[0m\n"
+                ))?;
             }
         }
 
@@ -506,7 +1577,7 @@ impl Orchestrator {
             );
         }
 
-        Ok((clean_response, think_content))
+        Ok((clean_response, think_content, interrupted, usage))
     }
 
     /// Updates case notes from the model's structured think block tags.
@@ -558,6 +1629,7 @@ impl Orchestrator {
                 themes: vec![],
                 user_facts: vec![],
                 significant_signal: None,
+                homework: None,
                 raw_think: String::new(),
             });
 
@@ -579,6 +1651,7 @@ impl Orchestrator {
         let (Some(vconn), Some(model)) = (&self.vector_conn, &self.embedding_model) else {
             return;
         };
+        self.pending_writes.retain(|write| !write.is_finished());
 
         // Track counters for session summary
         self.facts_extracted += analysis.user_facts.len() as u32;
@@ -587,7 +1660,6 @@ impl Orchestrator {
         }
 
         use rig::embeddings::EmbeddingModel as _;
-        use tracing::Instrument as _;
 
         let session_id = self.session_id.clone();
         let turn_number = self.turn_number;
@@ -607,7 +1679,7 @@ impl Orchestrator {
                 updated_at: now.clone(),
             };
             let content = content.clone();
-            tokio::spawn(
+            self.pending_writes.push(tokio::spawn(
                 async move {
                     match model.embed_text(&content).await {
                         Ok(embedding) => {
@@ -631,7 +1703,7 @@ impl Orchestrator {
                     }
                 }
                 .in_current_span(),
-            );
+            ));
         }
 
         // Store significant turn
@@ -650,7 +1722,7 @@ impl Orchestrator {
                 created_at: now,
             };
             let embed_text = input.to_string();
-            tokio::spawn(
+            self.pending_writes.push(tokio::spawn(
                 async move {
                     match model.embed_text(&embed_text).await {
                         Ok(embedding) => {
@@ -670,25 +1742,48 @@ impl Orchestrator {
                     }
                 }
                 .in_current_span(),
-            );
+            ));
         }
     }
 
-    /// Saves the turn to the database and appends to in-memory chat history.
+    /// Queues the turn to be saved (see `SaveQueue`) and appends it to the
+    /// in-memory chat history.
     ///
     /// Applies a sliding window to keep chat history within context limits.
     /// When messages are drained, they are captured as a checkpoint in the
     /// vector store so context isn't permanently lost.
-    async fn save_and_record(&mut self, input: &str, response: &str) -> Result<()> {
-        memory::save_chat_turn(&self.chat_conn, &self.session_id, "user", input)
-            .await
-            .context("Failed to save user turn")?;
-        memory::save_chat_turn(&self.chat_conn, &self.session_id, "assistant", response)
-            .await
-            .context("Failed to save assistant turn")?;
+    fn save_and_record(
+        &mut self,
+        input: &str,
+        response: &str,
+        variant: Option<&PromptVariant>,
+        interrupted: bool,
+        usage: Option<ReplyUsage>,
+    ) {
+        let emotions = emotion::classify(input);
+        let latency = self.message_latency.take();
+        self.saves.save(Exchange {
+            session_id: self.session_id.clone(),
+            input: input.to_string(),
+            source: std::mem::take(&mut self.input_source),
+            emotions: (!emotions.is_empty()).then(|| emotion::format_labels(&emotions)),
+            latency_ms: latency.map(|l| l.as_millis() as u64),
+            engagement: engagement::score(input.chars().count(), latency),
+            response: response.to_string(),
+            prompt_variant: variant.map(|v| v.id.clone()),
+            interrupted,
+            usage,
+            provenance: Some(Provenance {
+                prompt_version: variant.map(PromptVariant::version),
+                agent: if variant.is_some() { "coach" } else { "crisis" }.to_string(),
+                ..self.provenance.clone()
+            }),
+        });
+        self.in_flight.get_mut().unwrap_or_else(PoisonError::into_inner).take();
 
         self.chat_history.push(Message::user(input));
         self.chat_history.push(Message::assistant(response));
+        self.last_reply_at = Some(Instant::now());
 
         // Sliding window: keep last N turns (pairs of user+assistant messages).
         if self.chat_history.len() > self.max_history_messages {
@@ -705,8 +1800,6 @@ impl Orchestrator {
                 "Sliding window trimmed chat history"
             );
         }
-
-        Ok(())
     }
 
     /// Creates a rolling checkpoint from messages being drained from the sliding window.
@@ -719,7 +1812,6 @@ impl Orchestrator {
         };
 
         use rig::embeddings::EmbeddingModel as _;
-        use tracing::Instrument as _;
 
         // Build checkpoint text from drained messages
         let mut checkpoint_text = String::new();
@@ -773,7 +1865,7 @@ impl Orchestrator {
 
         let vconn = vconn.clone();
         let model = model.clone();
-        tokio::spawn(
+        self.pending_writes.push(tokio::spawn(
             async move {
                 match model.embed_text(&checkpoint_text).await {
                     Ok(embedding) => {
@@ -796,7 +1888,7 @@ impl Orchestrator {
                 }
             }
             .in_current_span(),
-        );
+        ));
     }
 }
 
@@ -890,6 +1982,29 @@ mod tests {
         assert!(response.contains("741741"));
     }
 
+    #[test]
+    fn test_parse_goal_command() {
+        assert_eq!(parse_goal_command("add  Walk daily "), Some(GoalCommand::Add("Walk daily")));
+        assert_eq!(parse_goal_command("list"), Some(GoalCommand::List));
+        assert_eq!(parse_goal_command(""), Some(GoalCommand::List));
+        assert_eq!(parse_goal_command("complete #3"), Some(GoalCommand::Complete(3)));
+        assert_eq!(parse_goal_command("add"), None);
+        assert_eq!(parse_goal_command("complete soon"), None);
+        assert_eq!(parse_goal_command("remove 1"), None);
+    }
+
+    #[test]
+    fn test_parse_coping_command() {
+        assert_eq!(
+            parse_coping_command("rate paced breathing 4"),
+            Some(CopingCommand::Rate("paced breathing", 4))
+        );
+        assert_eq!(parse_coping_command(""), Some(CopingCommand::List));
+        assert_eq!(parse_coping_command("rate walking 6"), None);
+        assert_eq!(parse_coping_command("rate 3"), None);
+        assert_eq!(parse_coping_command("rate walking"), None);
+    }
+
     /// Test that the preamble ordering matches the documented pipeline.
     #[test]
     fn test_preamble_ordering_matches_pipeline() {
@@ -904,6 +2019,8 @@ mod tests {
             Some(notes),
             None,
             Some(rag),
+            None,
+            None,
         );
 
         // Verify ordering: base → think → RAG → case notes
@@ -961,6 +2078,8 @@ mod tests {
                 Some(&new_notes),
                 None,
                 Some(&rag),
+                None,
+                None,
                 DEFAULT_MAX_PREAMBLE_CHARS,
             );
 
@@ -0,0 +1,75 @@
+/// How long the coach's replies should be, chosen with `/verbosity`. Kept
+/// as a preference, so it carries into later sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verbosity {
+    Brief,
+    #[default]
+    Normal,
+    Detailed,
+}
+
+impl Verbosity {
+    /// Preference key in the `preferences` table.
+    pub const PREFERENCE: &'static str = "verbosity";
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "brief" | "short" => Some(Self::Brief),
+            "normal" => Some(Self::Normal),
+            "detailed" | "long" => Some(Self::Detailed),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Brief => "brief",
+            Self::Normal => "normal",
+            Self::Detailed => "detailed",
+        }
+    }
+
+    /// Token budget for a reply, from the prompt variant's. The think block
+    /// shares the budget, so brief trims it rather than relying on it: the
+    /// instruction is what keeps the reply short.
+    pub fn max_tokens(self, base: usize) -> usize {
+        match self {
+            Self::Brief => base * 2 / 3,
+            Self::Normal => base,
+            Self::Detailed => base * 3 / 2,
+        }
+    }
+
+    /// Added to the system prompt; `None` leaves the variant's own guidance.
+    pub fn instruction(self) -> Option<&'static str> {
+        match self {
+            Self::Brief => Some(
+                "Keep every reply to two or three short sentences: one reflection and at most one question. No lists, no paragraphs of explanation. Short and warm helps more than thorough when someone is overwhelmed.",
+            ),
+            Self::Normal => None,
+            Self::Detailed => Some(
+                "They prefer fuller replies. You may take a few short paragraphs to reflect, explain, or lay out options, still ending with one open question.",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_budget() {
+        assert_eq!(Verbosity::parse(" Brief"), Some(Verbosity::Brief));
+        assert_eq!(Verbosity::parse("detailed"), Some(Verbosity::Detailed));
+        assert_eq!(Verbosity::parse("tiny"), None);
+        for v in [Verbosity::Brief, Verbosity::Normal, Verbosity::Detailed] {
+            assert_eq!(Verbosity::parse(v.as_str()), Some(v));
+        }
+
+        assert_eq!(Verbosity::Brief.max_tokens(512), 341);
+        assert_eq!(Verbosity::default().max_tokens(512), 512);
+        assert_eq!(Verbosity::Detailed.max_tokens(512), 768);
+        assert!(Verbosity::Normal.instruction().is_none());
+    }
+}
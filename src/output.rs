@@ -0,0 +1,56 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde::Serialize;
+
+static SETTINGS: OnceLock<(OutputFormat, bool)> = OnceLock::new();
+
+/// How listing and reporting commands print their results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Tables and sentences for people
+    #[default]
+    Text,
+    /// JSON on stdout for scripts; status messages go to stderr
+    Json,
+}
+
+/// Selects the output format and whether to print status messages
+/// (`--output-format`, `--quiet`). Call once at startup; until then (and in
+/// tests) output is text and not quiet.
+pub fn init(format: OutputFormat, quiet: bool) {
+    let _ = SETTINGS.set((format, quiet));
+}
+
+fn settings() -> (OutputFormat, bool) {
+    SETTINGS.get().copied().unwrap_or_default()
+}
+
+/// Whether results should be printed as JSON.
+pub fn json() -> bool {
+    settings().0 == OutputFormat::Json
+}
+
+/// Whether `--quiet` was given: no status messages or progress, and lists
+/// print only IDs.
+pub fn quiet() -> bool {
+    settings().1
+}
+
+/// Prints a status message, like "Wrote 12 examples to train.jsonl",
+/// unless quiet. With JSON output it goes to stderr, keeping stdout
+/// parseable.
+pub fn status(message: impl Display) {
+    match settings() {
+        (_, true) => {}
+        (OutputFormat::Json, false) => eprintln!("{message}"),
+        (OutputFormat::Text, false) => println!("{message}"),
+    }
+}
+
+/// Prints `value` as pretty-printed JSON on stdout.
+pub fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+use crate::orchestrator::events::{SafetyKind, TurnEvent};
+use crate::output;
+
+/// Progress through a batch command (replay, eval, synth, archive) on
+/// stderr: a bar for the items, and under it a spinner following the
+/// current turn's steps. Off a terminal nothing is drawn and each item is
+/// logged as a line instead, so CI output stays readable. With `--quiet`
+/// neither is shown.
+pub struct Progress {
+    multi: MultiProgress,
+    bar: ProgressBar,
+    /// What an item is called in the log lines, e.g. `Case`.
+    item: &'static str,
+    drawn: bool,
+}
+
+impl Progress {
+    pub fn new(item: &'static str, total: usize) -> Self {
+        let drawn = io::stderr().is_terminal() && !output::quiet();
+        let multi = MultiProgress::with_draw_target(if drawn {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        });
+        let bar = multi.add(ProgressBar::new(total as u64));
+        bar.set_style(
+            ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} {msg} ({elapsed})")
+                .expect("progress template is valid")
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(item);
+        Self { multi, bar, item, drawn }
+    }
+
+    /// Moves on to item `i` (0-based); `detail` names it, if anything does.
+    pub fn start(&self, i: usize, detail: &str) {
+        self.bar.set_position(i as u64);
+        self.bar.set_message(detail.to_string());
+        if !self.drawn && !output::quiet() {
+            let total = self.bar.length().unwrap_or_default();
+            match detail {
+                "" => eprintln!("{} {}/{total}", self.item, i + 1),
+                _ => eprintln!("{} {}/{total} ({detail})", self.item, i + 1),
+            }
+        }
+    }
+
+    /// Prints a line above the bars (or to stderr when they aren't drawn).
+    pub fn println(&self, line: &str) {
+        if !self.drawn || self.multi.println(line).is_err() {
+            eprintln!("{line}");
+        }
+    }
+
+    /// Runs `f` (which prints to stdout) with the bars cleared.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.multi.suspend(f)
+    }
+
+    /// Shows the steps of the turns on `events` under the bar until the
+    /// returned guard is dropped.
+    pub fn follow(&self, events: broadcast::Receiver<TurnEvent>) -> Following {
+        let spinner = self.multi.add(ProgressBar::new_spinner());
+        spinner.set_style(ProgressStyle::with_template("  {spinner} {msg}").expect("progress template is valid"));
+        if self.drawn {
+            spinner.enable_steady_tick(Duration::from_millis(120));
+        }
+        let task = tokio::spawn(follow_steps(events, spinner.clone()));
+        Following { multi: self.multi.clone(), spinner, task }
+    }
+
+    pub fn finish(self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Keeps a turn spinner running; removes it when dropped.
+pub struct Following {
+    multi: MultiProgress,
+    spinner: ProgressBar,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Following {
+    fn drop(&mut self) {
+        self.task.abort();
+        self.spinner.finish_and_clear();
+        self.multi.remove(&self.spinner);
+    }
+}
+
+async fn follow_steps(mut events: broadcast::Receiver<TurnEvent>, spinner: ProgressBar) {
+    let mut step = TurnStep::default();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                step.update(&event);
+                spinner.set_message(step.describe());
+            }
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Where a turn is, as far as its events tell: routing, then the
+/// answering agent's tool calls and writing, then saving.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct TurnStep {
+    agent: Option<String>,
+    tool: Option<String>,
+    chunks: usize,
+    stage: Stage,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Stage {
+    #[default]
+    Waiting,
+    Routing,
+    Crisis,
+    Answering,
+    Saving,
+    Done,
+    Failed,
+}
+
+impl TurnStep {
+    fn update(&mut self, event: &TurnEvent) {
+        match event {
+            TurnEvent::UserMessage { .. } => *self = Self { stage: Stage::Routing, ..Self::default() },
+            TurnEvent::SafetyFlag { kind: SafetyKind::Crisis, .. } => self.stage = Stage::Crisis,
+            TurnEvent::SafetyFlag { .. } => {}
+            TurnEvent::AgentSelected { agent } => {
+                self.agent = Some(agent.clone());
+                if self.stage != Stage::Crisis {
+                    self.stage = Stage::Answering;
+                }
+            }
+            TurnEvent::ToolCalled { name, .. } => self.tool = Some(name.clone()),
+            TurnEvent::TokenChunk(_) => {
+                self.tool = None;
+                self.chunks += 1;
+            }
+            TurnEvent::ResponseComplete { .. } => self.stage = Stage::Saving,
+            TurnEvent::SessionSaved { .. } => self.stage = Stage::Done,
+            TurnEvent::TurnFailed(_) => self.stage = Stage::Failed,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let agent = self.agent.as_deref().unwrap_or("coach");
+        match self.stage {
+            Stage::Waiting => String::new(),
+            Stage::Routing => "checking safety".to_string(),
+            Stage::Crisis => "crisis response".to_string(),
+            Stage::Answering => match (&self.tool, self.chunks) {
+                (Some(tool), _) => format!("{agent} › {tool}"),
+                (None, 0) => format!("{agent} › thinking"),
+                (None, n) => format!("{agent} › writing ({n} chunks)"),
+            },
+            Stage::Saving => format!("{agent} › saving"),
+            Stage::Done => "done".to_string(),
+            Stage::Failed => "failed".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turn_steps_follow_events() {
+        let mut step = TurnStep::default();
+        let mut seen = Vec::new();
+        for event in [
+            TurnEvent::UserMessage { session_id: "s1".into(), text: "hi".into() },
+            TurnEvent::AgentSelected { agent: "v7".into() },
+            TurnEvent::ToolCalled { name: "search_memory".into(), arguments: serde_json::json!({}) },
+            TurnEvent::TokenChunk("Hi".into()),
+            TurnEvent::TokenChunk(" there".into()),
+            TurnEvent::ResponseComplete { response: "Hi there".into(), think_content: None },
+            TurnEvent::SessionSaved { session_id: "s1".into(), turn_number: 1 },
+        ] {
+            step.update(&event);
+            seen.push(step.describe());
+        }
+        assert_eq!(
+            seen,
+            vec![
+                "checking safety",
+                "v7 › thinking",
+                "v7 › search_memory",
+                "v7 › writing (1 chunks)",
+                "v7 › writing (2 chunks)",
+                "v7 › saving",
+                "done",
+            ]
+        );
+
+        // The next turn starts over
+        step.update(&TurnEvent::UserMessage { session_id: "s1".into(), text: "I want to die".into() });
+        step.update(&TurnEvent::SafetyFlag { kind: SafetyKind::Crisis, message: String::new() });
+        step.update(&TurnEvent::AgentSelected { agent: "crisis".into() });
+        assert_eq!(step.describe(), "crisis response");
+    }
+}
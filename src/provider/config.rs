@@ -14,7 +14,7 @@ pub struct GenerationConfig {
     /// Token IDs that signal end of generation.
     pub eos_token_ids: Vec<u32>,
     /// Random seed for reproducibility. `None` for random.
-    pub seed: Option<u64>,
+    pub seed: Option<u32>,
 }
 
 impl Default for GenerationConfig {
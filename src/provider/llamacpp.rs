@@ -1,8 +1,8 @@
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
 use llama_cpp_2::context::params::LlamaContextParams;
 use llama_cpp_2::context::LlamaContext;
 use llama_cpp_2::llama_backend::LlamaBackend;
@@ -11,17 +11,22 @@ use llama_cpp_2::model::params::LlamaModelParams;
 use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel};
 use llama_cpp_2::sampling::LlamaSampler;
 use llama_cpp_2::token::LlamaToken;
+use rig::agent::StreamingError;
 use rig::completion::{
     AssistantContent, CompletionError, CompletionModel, CompletionRequest, CompletionResponse,
     Message, Usage,
 };
-use rig::streaming::{RawStreamingChoice, StreamingCompletionResponse};
+use rig::message::ToolFunction;
+use rig::streaming::{RawStreamingChoice, RawStreamingToolCall, StreamingCompletionResponse};
 use rig::OneOrMany;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
 use super::config::GenerationConfig;
+use super::tool_calls::{self, ToolCallGate};
+use super::{Backend, MockBackend};
+use crate::error::{ChironError, Result};
 
 /// Holds the llama.cpp backend and model.
 pub struct LlamaCppProvider {
@@ -32,7 +37,8 @@ pub struct LlamaCppProvider {
 impl LlamaCppProvider {
     /// Loads a GGUF model with the given GPU layer count.
     pub fn new(model_path: &Path, n_gpu_layers: u32) -> Result<Self> {
-        let backend = LlamaBackend::init().context("Failed to init llama backend")?;
+        let backend = LlamaBackend::init()
+            .map_err(|e| ChironError::ModelLoad(format!("Failed to init llama backend: {e}")))?;
 
         #[cfg(feature = "cuda")]
         tracing::info!("GPU backend: CUDA");
@@ -47,7 +53,7 @@ impl LlamaCppProvider {
 
         let model_params = LlamaModelParams::default().with_n_gpu_layers(n_gpu_layers);
         let model = LlamaModel::load_from_file(&backend, model_path, &model_params)
-            .map_err(|e| anyhow::anyhow!("Failed to load model: {e}"))?;
+            .map_err(|e| ChironError::ModelLoad(format!("Failed to load model: {e}")))?;
 
         tracing::info!(
             path = %model_path.display(),
@@ -72,18 +78,18 @@ impl LlamaCppProvider {
             .iter()
             .map(|(role, content)| {
                 LlamaChatMessage::new(role.clone(), content.clone())
-                    .map_err(|e| anyhow::anyhow!("Failed to create chat message: {e}"))
+                    .map_err(|e| ChironError::Inference(format!("Failed to create chat message: {e}")))
             })
             .collect::<Result<Vec<_>>>()?;
 
         let template = self
             .model
             .chat_template(None)
-            .map_err(|e| anyhow::anyhow!("Failed to get chat template: {e}"))?;
+            .map_err(|e| ChironError::Inference(format!("Failed to get chat template: {e}")))?;
 
         self.model
             .apply_chat_template(&template, &chat_messages, add_ass)
-            .map_err(|e| anyhow::anyhow!("Failed to apply chat template: {e}"))
+            .map_err(|e| ChironError::Inference(format!("Failed to apply chat template: {e}")))
     }
 
     /// Tokenizes a string using the model's tokenizer.
@@ -95,7 +101,7 @@ impl LlamaCppProvider {
         };
         self.model
             .str_to_token(text, bos)
-            .map_err(|e| anyhow::anyhow!("Tokenization failed: {e}"))
+            .map_err(|e| ChironError::Inference(format!("Tokenization failed: {e}")))
     }
 
     /// Creates a new context for inference.
@@ -103,7 +109,7 @@ impl LlamaCppProvider {
         let ctx_params = LlamaContextParams::default().with_n_ctx(std::num::NonZeroU32::new(n_ctx));
         self.model
             .new_context(&self.backend, ctx_params)
-            .map_err(|e| anyhow::anyhow!("Failed to create context: {e}"))
+            .map_err(|e| ChironError::Inference(format!("Failed to create context: {e}")))
     }
 }
 
@@ -112,30 +118,37 @@ impl LlamaCppProvider {
 pub struct LlamaCppResponse {
     pub text: String,
     pub think_content: Option<String>,
+    pub prompt_tokens: usize,
     pub tokens_generated: usize,
 }
 
 /// Response type for streaming completions (final response).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlamaCppStreamingResponse {
+    pub prompt_tokens: usize,
     pub tokens_generated: usize,
 }
 
 impl rig::completion::GetTokenUsage for LlamaCppStreamingResponse {
     fn token_usage(&self) -> Option<Usage> {
-        Some(Usage {
-            input_tokens: 0,
-            output_tokens: self.tokens_generated as u64,
-            total_tokens: self.tokens_generated as u64,
-            cached_input_tokens: 0,
-        })
+        Some(usage(self.prompt_tokens, self.tokens_generated))
+    }
+}
+
+fn usage(prompt_tokens: usize, tokens_generated: usize) -> Usage {
+    Usage {
+        input_tokens: prompt_tokens as u64,
+        output_tokens: tokens_generated as u64,
+        total_tokens: (prompt_tokens + tokens_generated) as u64,
+        cached_input_tokens: 0,
     }
 }
 
-/// Completion model backed by llama.cpp via llama-cpp-2.
+/// Completion model backed by llama.cpp via llama-cpp-2 (or fixture replay
+/// with a mock backend).
 #[derive(Clone)]
 pub struct LlamaCppCompletionModel {
-    provider: Arc<Mutex<LlamaCppProvider>>,
+    backend: Backend,
     config: GenerationConfig,
     /// Shared buffer where streaming inference deposits think block content.
     /// Read by the orchestrator after streaming completes.
@@ -145,17 +158,14 @@ pub struct LlamaCppCompletionModel {
 /// The client type for LlamaCppCompletionModel.
 #[derive(Clone)]
 pub struct LlamaCppClient {
-    pub provider: Arc<Mutex<LlamaCppProvider>>,
+    pub backend: Backend,
     pub config: GenerationConfig,
 }
 
-/// Creates a completion model from a shared provider and config.
-pub fn completion_model(
-    provider: &Arc<Mutex<LlamaCppProvider>>,
-    config: GenerationConfig,
-) -> LlamaCppCompletionModel {
+/// Creates a completion model from a shared backend and config.
+pub fn completion_model(backend: &Backend, config: GenerationConfig) -> LlamaCppCompletionModel {
     LlamaCppCompletionModel {
-        provider: provider.clone(),
+        backend: backend.clone(),
         config,
         think_buffer: Arc::new(Mutex::new(None)),
     }
@@ -175,7 +185,7 @@ impl CompletionModel for LlamaCppCompletionModel {
 
     fn make(client: &Self::Client, _model: impl Into<String>) -> Self {
         Self {
-            provider: client.provider.clone(),
+            backend: client.backend.clone(),
             config: client.config.clone(),
             think_buffer: Arc::new(Mutex::new(None)),
         }
@@ -185,58 +195,79 @@ impl CompletionModel for LlamaCppCompletionModel {
         &self,
         request: CompletionRequest,
     ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
-        let provider = self.provider.clone();
+        let provider = match &self.backend {
+            Backend::LlamaCpp(provider) => provider.clone(),
+            Backend::Mock(mock) => {
+                let prompt = last_user_text(&request);
+                if let Some(e) = mock.failure(&prompt) {
+                    return Err(completion_error(e));
+                }
+                let (text, think_content) = parse_think_blocks(mock.respond(&prompt));
+                let response = LlamaCppResponse {
+                    prompt_tokens: approx_prompt_tokens(&request),
+                    tokens_generated: text.split_whitespace().count(),
+                    text,
+                    think_content,
+                };
+                return Ok(completion_response(response, !request.tools.is_empty()));
+            }
+        };
         let config = self.config.clone();
         let max_tokens_override = request.max_tokens.map(|t| t as usize);
+        let tools_enabled = !request.tools.is_empty();
 
-        let prompt_text = format_request(&provider, &request)?;
-
+        let span = inference_span(false);
         let result = tokio::task::spawn_blocking(move || {
-            run_inference(&provider, &prompt_text, &config, max_tokens_override)
+            span.in_scope(|| {
+                let prompt_text = format_request(&provider, &request)?;
+                run_inference(&provider, &prompt_text, &config, max_tokens_override)
+            })
         })
         .await
         .map_err(|e| CompletionError::ProviderError(format!("Task join error: {e}")))?
-        .map_err(|e| CompletionError::ProviderError(format!("{e}")))?;
-
-        Ok(CompletionResponse {
-            choice: OneOrMany::one(AssistantContent::text(&result.text)),
-            usage: Usage {
-                input_tokens: 0,
-                output_tokens: result.tokens_generated as u64,
-                total_tokens: result.tokens_generated as u64,
-                cached_input_tokens: 0,
-            },
-            raw_response: result,
-            message_id: None,
-        })
+        .map_err(completion_error)?;
+
+        Ok(completion_response(result, tools_enabled))
     }
 
     async fn stream(
         &self,
         request: CompletionRequest,
     ) -> Result<StreamingCompletionResponse<Self::StreamingResponse>, CompletionError> {
-        let provider = self.provider.clone();
+        let provider = match &self.backend {
+            Backend::LlamaCpp(provider) => provider.clone(),
+            Backend::Mock(mock) => return Ok(stream_mock(mock, &request, &self.think_buffer)),
+        };
         let config = self.config.clone();
         let max_tokens_override = request.max_tokens.map(|t| t as usize);
         let think_buffer = self.think_buffer.clone();
-
-        let prompt_text = format_request(&provider, &request)?;
+        let gate = (!request.tools.is_empty()).then(ToolCallGate::default);
 
         let (tx, rx) = mpsc::channel::<
             Result<RawStreamingChoice<LlamaCppStreamingResponse>, CompletionError>,
         >(32);
 
+        let span = inference_span(true);
         tokio::task::spawn_blocking(move || {
-            let result = run_inference_streaming(
-                &provider,
-                &prompt_text,
-                &config,
-                &tx,
-                max_tokens_override,
-                &think_buffer,
-            );
+            let _span = span.enter();
+            // A panic would drop `tx` and end the stream as if the reply were
+            // complete, so it's sent as an error instead
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                format_request(&provider, &request).and_then(|prompt_text| {
+                    run_inference_streaming(
+                        &provider,
+                        &prompt_text,
+                        &config,
+                        &tx,
+                        max_tokens_override,
+                        &think_buffer,
+                        gate,
+                    )
+                })
+            }))
+            .unwrap_or_else(|_| Err(ChironError::Inference("Generation panicked".to_string())));
             if let Err(e) = result {
-                let _ = tx.blocking_send(Err(CompletionError::ProviderError(format!("{e}"))));
+                let _ = tx.blocking_send(Err(completion_error(e)));
             }
         });
 
@@ -245,48 +276,177 @@ impl CompletionModel for LlamaCppCompletionModel {
     }
 }
 
-/// Formats a CompletionRequest into a prompt string using the model's chat template.
-fn format_request(
-    provider: &Arc<Mutex<LlamaCppProvider>>,
+/// Span for one llama.cpp generation, created on the async side so it nests
+/// under the turn and entered on the blocking thread that runs it. Token
+/// counts are recorded as generation proceeds.
+fn inference_span(streaming: bool) -> tracing::Span {
+    tracing::info_span!(
+        "inference",
+        streaming,
+        prompt_tokens = tracing::field::Empty,
+        tokens_generated = tracing::field::Empty,
+    )
+}
+
+/// Wraps a finished generation as a rig response. With tools offered, any
+/// `<tool_call>` blocks in the text become tool calls for rig to run.
+fn completion_response(result: LlamaCppResponse, tools_enabled: bool) -> CompletionResponse<LlamaCppResponse> {
+    let (text, calls) = if tools_enabled {
+        tool_calls::parse_tool_calls(&result.text)
+    } else {
+        (result.text.clone(), Vec::new())
+    };
+    let mut content: Vec<AssistantContent> = calls
+        .into_iter()
+        .map(|call| AssistantContent::tool_call(new_call_id(), call.name, call.arguments))
+        .collect();
+    if content.is_empty() || !text.is_empty() {
+        content.insert(0, AssistantContent::text(&text));
+    }
+    CompletionResponse {
+        choice: OneOrMany::many(content).expect("response has text or a tool call"),
+        usage: usage(result.prompt_tokens, result.tokens_generated),
+        raw_response: result,
+        message_id: None,
+    }
+}
+
+/// Replays a mock response as a stream: the think block goes to the think
+/// buffer (as with real inference) and the visible text is sent word by word.
+/// A scripted failure is sent as the stream's only item.
+fn stream_mock(
+    mock: &MockBackend,
     request: &CompletionRequest,
-) -> Result<String, CompletionError> {
-    let provider = provider
-        .lock()
-        .map_err(|e| CompletionError::ProviderError(format!("Lock poisoned: {e}")))?;
+    think_output: &Arc<Mutex<Option<String>>>,
+) -> StreamingCompletionResponse<LlamaCppStreamingResponse> {
+    let prompt = last_user_text(request);
+    if let Some(e) = mock.failure(&prompt) {
+        let failed: Result<RawStreamingChoice<LlamaCppStreamingResponse>, CompletionError> = Err(completion_error(e));
+        return StreamingCompletionResponse::stream(Box::pin(futures::stream::iter([failed])));
+    }
+    let (visible, think_content) = parse_think_blocks(mock.respond(&prompt));
+    // Like real inference, a reply after a tool call without its own think
+    // block keeps the one from the call
+    if let (Some(think), Ok(mut buf)) = (think_content, think_output.lock()) {
+        *buf = Some(think);
+    }
+    let (visible, calls) = if request.tools.is_empty() {
+        (visible, Vec::new())
+    } else {
+        tool_calls::parse_tool_calls(&visible)
+    };
+
+    let mut chunks: Vec<Result<RawStreamingChoice<LlamaCppStreamingResponse>, CompletionError>> = visible
+        .split_inclusive(' ')
+        .map(|word| Ok(RawStreamingChoice::Message(word.to_string())))
+        .collect();
+    let tokens_generated = chunks.len();
+    chunks.extend(calls.into_iter().map(|call| Ok(streaming_tool_call(call))));
+    chunks.push(Ok(RawStreamingChoice::FinalResponse(LlamaCppStreamingResponse {
+        prompt_tokens: approx_prompt_tokens(request),
+        tokens_generated,
+    })));
+    StreamingCompletionResponse::stream(Box::pin(futures::stream::iter(chunks)))
+}
+
+/// Text of the latest user message in the request (the prompt being
+/// answered). After a tool call that is the tool's result, so mock fixtures
+/// can key a follow-up reply on it.
+fn last_user_text(request: &CompletionRequest) -> String {
+    request
+        .chat_history
+        .iter()
+        .rev()
+        .find_map(|message| match message {
+            Message::User { content } => Some(
+                content
+                    .iter()
+                    .filter_map(|c| match c {
+                        rig::message::UserContent::Text(t) => Some(t.text.clone()),
+                        rig::message::UserContent::ToolResult(r) => Some(tool_calls::tool_result_text(r)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(""),
+            ),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Stand-in for the prompt token count when there is no tokenizer (mock
+/// backend): words in the preamble and user messages, so it still grows
+/// with the conversation.
+fn approx_prompt_tokens(request: &CompletionRequest) -> usize {
+    let preamble = request.preamble.as_deref().unwrap_or_default().split_whitespace().count();
+    let messages: usize = request
+        .chat_history
+        .iter()
+        .map(|message| match message {
+            Message::User { content } => user_text(content).split_whitespace().count(),
+            _ => 0,
+        })
+        .sum();
+    preamble + messages
+}
+
+/// Concatenates the text parts of a user message, with tool results as
+/// `<tool_response>` blocks.
+fn user_text(content: &OneOrMany<rig::message::UserContent>) -> String {
+    content
+        .iter()
+        .filter_map(|c| match c {
+            rig::message::UserContent::Text(t) => Some(t.text.clone()),
+            rig::message::UserContent::ToolResult(r) => Some(tool_calls::render_tool_result(r)),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+/// IDs only need to pair a call with its result within one turn.
+fn new_call_id() -> String {
+    format!("call_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn streaming_tool_call(call: ToolFunction) -> RawStreamingChoice<LlamaCppStreamingResponse> {
+    RawStreamingChoice::ToolCall(RawStreamingToolCall::new(new_call_id(), call.name, call.arguments))
+}
+
+/// Formats a CompletionRequest into a prompt string using the model's chat template.
+fn format_request(provider: &Arc<Mutex<LlamaCppProvider>>, request: &CompletionRequest) -> Result<String> {
+    let provider = lock_provider(provider)?;
 
     let mut messages: Vec<(String, String)> = Vec::new();
 
-    // System message: preamble + RAG documents (if any)
-    if let Some(preamble) = &request.preamble {
-        let mut system_content = preamble.clone();
+    // System message: preamble + RAG documents + tools (if any)
+    if request.preamble.is_some() || !request.tools.is_empty() {
+        let mut system_content = request.preamble.clone().unwrap_or_default();
         if !request.documents.is_empty() {
             system_content.push_str("\n\n# Reference Context\n");
             for doc in &request.documents {
                 system_content.push_str(&format!("{doc}\n"));
             }
         }
-        messages.push(("system".to_string(), system_content));
+        if !request.tools.is_empty() {
+            system_content.push_str("\n\n");
+            system_content.push_str(&tool_calls::tools_section(&request.tools));
+        }
+        messages.push(("system".to_string(), system_content.trim_start().to_string()));
     }
 
     // Chat history
     for message in request.chat_history.iter() {
         match message {
             Message::User { content } => {
-                let text: String = content
-                    .iter()
-                    .filter_map(|c| match c {
-                        rig::message::UserContent::Text(t) => Some(t.text.as_str()),
-                        _ => None,
-                    })
-                    .collect::<Vec<_>>()
-                    .join("");
-                messages.push(("user".to_string(), text));
+                messages.push(("user".to_string(), user_text(content)));
             }
             Message::Assistant { content, .. } => {
                 let text: String = content
                     .iter()
                     .filter_map(|c| match c {
-                        AssistantContent::Text(t) => Some(t.text.as_str()),
+                        AssistantContent::Text(t) => Some(t.text.clone()),
+                        AssistantContent::ToolCall(call) => Some(tool_calls::render_tool_call(call)),
                         _ => None,
                     })
                     .collect::<Vec<_>>()
@@ -299,9 +459,49 @@ fn format_request(
         }
     }
 
-    provider
-        .apply_chat_template(&messages, true)
-        .map_err(|e| CompletionError::ProviderError(format!("{e}")))
+    provider.apply_chat_template(&messages, true)
+}
+
+/// How long a request waits for the model while another reply is using it.
+const MODEL_WAIT: Duration = Duration::from_secs(120);
+
+/// Takes the model for one request, waiting up to `MODEL_WAIT` while another
+/// reply (another session, or the Telegram bridge) holds it. Blocks, so it
+/// runs on the generation thread.
+fn lock_provider(provider: &Arc<Mutex<LlamaCppProvider>>) -> Result<MutexGuard<'_, LlamaCppProvider>> {
+    let deadline = Instant::now() + MODEL_WAIT;
+    loop {
+        match provider.try_lock() {
+            Ok(guard) => return Ok(guard),
+            Err(TryLockError::Poisoned(e)) => return Err(ChironError::Inference(format!("Lock poisoned: {e}"))),
+            Err(TryLockError::WouldBlock) if Instant::now() >= deadline => {
+                return Err(ChironError::ModelUnavailable(format!(
+                    "The model is still busy with another reply after {}s",
+                    MODEL_WAIT.as_secs()
+                )));
+            }
+            Err(TryLockError::WouldBlock) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+/// Hands a generation error to rig with its kind intact, so
+/// `streaming_error` can tell a busy model from a failed one.
+fn completion_error(e: ChironError) -> CompletionError {
+    CompletionError::RequestError(Box::new(e))
+}
+
+/// The error behind a failed stream: a busy model stays
+/// `ModelUnavailable`; anything else failed the generation.
+pub fn streaming_error(e: StreamingError) -> ChironError {
+    if let StreamingError::Completion(CompletionError::RequestError(source)) = e {
+        match source.downcast::<ChironError>() {
+            Ok(e) if e.is_transient() => return *e,
+            Ok(e) => return ChironError::Inference(format!("Streaming failed: {e}")),
+            Err(source) => return ChironError::Inference(format!("Streaming failed: {source}")),
+        }
+    }
+    ChironError::Inference(format!("Streaming failed: {e}"))
 }
 
 /// Builds a sampler chain with temperature, top-k, top-p.
@@ -315,7 +515,7 @@ fn build_sampler(config: &GenerationConfig) -> LlamaSampler {
         }
         s.push(LlamaSampler::top_p(config.top_p as f32, 1));
         s.push(LlamaSampler::temp(config.temperature as f32));
-        s.push(LlamaSampler::dist(config.seed.unwrap_or(42) as u32));
+        s.push(LlamaSampler::dist(config.seed.unwrap_or(42)));
         s
     };
 
@@ -361,12 +561,11 @@ fn run_inference(
     max_tokens_override: Option<usize>,
 ) -> Result<LlamaCppResponse> {
     let t0 = Instant::now();
-    let provider = provider
-        .lock()
-        .map_err(|e| anyhow::anyhow!("Lock poisoned: {e}"))?;
+    let provider = lock_provider(provider)?;
 
     let tokens = provider.tokenize(prompt_text, false)?;
     let prompt_token_count = tokens.len();
+    tracing::Span::current().record("prompt_tokens", prompt_token_count);
     let max_tokens = max_tokens_override.unwrap_or(config.max_tokens);
 
     let t1 = Instant::now();
@@ -386,11 +585,11 @@ fn run_inference(
         let is_last = i == tokens.len() - 1;
         batch
             .add(token, i as i32, &[0], is_last)
-            .map_err(|_| anyhow::anyhow!("Failed to add token to batch"))?;
+            .map_err(|_| ChironError::Inference("Failed to add token to batch".into()))?;
     }
 
     ctx.decode(&mut batch)
-        .map_err(|e| anyhow::anyhow!("Prefill decode failed: {e}"))?;
+        .map_err(|e| ChironError::Inference(format!("Prefill decode failed: {e}")))?;
 
     let t2 = Instant::now();
     let prefill_ms = t2.duration_since(t1).as_millis() as u64;
@@ -417,10 +616,10 @@ fn run_inference(
         batch.clear();
         batch
             .add(token, n_decoded as i32 - 1, &[0], true)
-            .map_err(|_| anyhow::anyhow!("Failed to add token to batch"))?;
+            .map_err(|_| ChironError::Inference("Failed to add token to batch".into()))?;
 
         ctx.decode(&mut batch)
-            .map_err(|e| anyhow::anyhow!("Decode failed: {e}"))?;
+            .map_err(|e| ChironError::Inference(format!("Decode failed: {e}")))?;
     }
 
     let decode_elapsed = decode_start.elapsed();
@@ -449,6 +648,7 @@ fn run_inference(
         prefill_ms,
         "Generation complete"
     );
+    tracing::Span::current().record("tokens_generated", tokens_generated);
 
     let (visible_text, think_content) = parse_think_blocks(&full_text);
 
@@ -459,27 +659,44 @@ fn run_inference(
     Ok(LlamaCppResponse {
         text: visible_text,
         think_content,
+        prompt_tokens: prompt_token_count,
         tokens_generated,
     })
 }
 
+type StreamSender = mpsc::Sender<Result<RawStreamingChoice<LlamaCppStreamingResponse>, CompletionError>>;
+
+/// Sends visible text, through the tool call gate when tools are offered.
+/// Returns false once the receiver has gone away.
+fn send_visible(tx: &StreamSender, gate: &mut Option<ToolCallGate>, text: String) -> bool {
+    let text = match gate {
+        Some(gate) => match gate.push(&text) {
+            Some(text) => text,
+            None => return true,
+        },
+        None => text,
+    };
+    text.is_empty() || tx.blocking_send(Ok(RawStreamingChoice::Message(text))).is_ok()
+}
+
 /// Runs streaming inference, sending visible tokens through the channel.
-/// Think blocks are buffered and NOT streamed to the user.
+/// Think blocks are buffered and NOT streamed to the user. With a tool call
+/// gate, a reply that is a tool call is sent as a tool call instead of text.
 fn run_inference_streaming(
     provider: &Arc<Mutex<LlamaCppProvider>>,
     prompt_text: &str,
     config: &GenerationConfig,
-    tx: &mpsc::Sender<Result<RawStreamingChoice<LlamaCppStreamingResponse>, CompletionError>>,
+    tx: &StreamSender,
     max_tokens_override: Option<usize>,
     think_output: &Arc<Mutex<Option<String>>>,
+    mut gate: Option<ToolCallGate>,
 ) -> Result<()> {
     let t0 = Instant::now();
-    let provider = provider
-        .lock()
-        .map_err(|e| anyhow::anyhow!("Lock poisoned: {e}"))?;
+    let provider = lock_provider(provider)?;
 
     let tokens = provider.tokenize(prompt_text, false)?;
     let prompt_token_count = tokens.len();
+    tracing::Span::current().record("prompt_tokens", prompt_token_count);
     let max_tokens = max_tokens_override.unwrap_or(config.max_tokens);
 
     let t1 = Instant::now();
@@ -498,11 +715,11 @@ fn run_inference_streaming(
         let is_last = i == tokens.len() - 1;
         batch
             .add(token, i as i32, &[0], is_last)
-            .map_err(|_| anyhow::anyhow!("Failed to add token to batch"))?;
+            .map_err(|_| ChironError::Inference("Failed to add token to batch".into()))?;
     }
 
     ctx.decode(&mut batch)
-        .map_err(|e| anyhow::anyhow!("Prefill decode failed: {e}"))?;
+        .map_err(|e| ChironError::Inference(format!("Prefill decode failed: {e}")))?;
 
     let t2 = Instant::now();
     tracing::info!(
@@ -525,6 +742,13 @@ fn run_inference_streaming(
 
     let decode_start = Instant::now();
     for _ in 0..max_tokens {
+        // The reader went away (e.g. an interrupted turn), so stop early
+        // and release the model
+        if tx.is_closed() {
+            tracing::info!(tokens_generated, "Stream closed; stopping generation");
+            return Ok(());
+        }
+
         let token = sampler.sample(&ctx, -1);
         sampler.accept(token);
 
@@ -550,14 +774,8 @@ fn run_inference_streaming(
                 if text_buffer.contains("<think>") {
                     in_think_block = true;
                     if let Some(pos) = text_buffer.find("<think>") {
-                        let before = &text_buffer[..pos];
-                        if !before.is_empty()
-                            && tx
-                                .blocking_send(Ok(RawStreamingChoice::Message(
-                                    before.to_string(),
-                                )))
-                                .is_err()
-                        {
+                        let before = text_buffer[..pos].to_string();
+                        if !send_visible(tx, &mut gate, before) {
                             return Ok(());
                         }
                         think_buffer = text_buffer[pos + "<think>".len()..].to_string();
@@ -575,11 +793,7 @@ fn run_inference_streaming(
                         let after = think_buffer[pos + "</think>".len()..].to_string();
                         think_buffer = think_buffer[..pos].to_string();
                         let trimmed = after.trim_start().to_string();
-                        if !trimmed.is_empty()
-                            && tx
-                                .blocking_send(Ok(RawStreamingChoice::Message(trimmed)))
-                                .is_err()
-                        {
+                        if !send_visible(tx, &mut gate, trimmed) {
                             return Ok(());
                         }
                     }
@@ -587,11 +801,7 @@ fn run_inference_streaming(
             }
         } else {
             // Outside think block — stream directly
-            if !piece.is_empty()
-                && tx
-                    .blocking_send(Ok(RawStreamingChoice::Message(piece)))
-                    .is_err()
-            {
+            if !send_visible(tx, &mut gate, piece) {
                 return Ok(());
             }
         }
@@ -600,15 +810,27 @@ fn run_inference_streaming(
         batch.clear();
         batch
             .add(token, n_decoded as i32 - 1, &[0], true)
-            .map_err(|_| anyhow::anyhow!("Failed to add token to batch"))?;
+            .map_err(|_| ChironError::Inference("Failed to add token to batch".into()))?;
 
         ctx.decode(&mut batch)
-            .map_err(|e| anyhow::anyhow!("Decode failed: {e}"))?;
+            .map_err(|e| ChironError::Inference(format!("Decode failed: {e}")))?;
     }
 
     // If we never entered/closed a think block, flush text_buffer
     if !think_closed && !in_think_block && !text_buffer.is_empty() {
-        let _ = tx.blocking_send(Ok(RawStreamingChoice::Message(text_buffer)));
+        send_visible(tx, &mut gate, text_buffer);
+    }
+
+    // Release what the gate held back: plain text, or the tool calls
+    if let Some(gate) = gate {
+        let (visible, calls) = gate.finish();
+        if !visible.is_empty() {
+            let _ = tx.blocking_send(Ok(RawStreamingChoice::Message(visible)));
+        }
+        for call in calls {
+            tracing::info!(tool = call.name, "Model called a tool");
+            let _ = tx.blocking_send(Ok(streaming_tool_call(call)));
+        }
     }
 
     let decode_elapsed = decode_start.elapsed();
@@ -638,9 +860,13 @@ fn run_inference_streaming(
         total_ms = t0.elapsed().as_millis() as u64,
         "Generation complete"
     );
+    tracing::Span::current().record("tokens_generated", tokens_generated);
 
     let _ = tx.blocking_send(Ok(RawStreamingChoice::FinalResponse(
-        LlamaCppStreamingResponse { tokens_generated },
+        LlamaCppStreamingResponse {
+            prompt_tokens: prompt_token_count,
+            tokens_generated,
+        },
     )));
 
     Ok(())
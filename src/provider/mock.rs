@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::{ChironError, Result};
+
+/// Directory searched for `--mock <scenario>` fixtures given by name.
+pub const FIXTURE_DIR: &str = "fixtures/mock";
+
+/// A fixture file: canned responses for one scenario.
+#[derive(Debug, Deserialize)]
+struct MockFixture {
+    scenario: String,
+    /// Reply for prompts with no matching response.
+    fallback: String,
+    #[serde(default)]
+    responses: Vec<MockResponse>,
+}
+
+/// A canned response, matched by the user's message text or its hash.
+#[derive(Debug, Deserialize)]
+struct MockResponse {
+    prompt: Option<String>,
+    prompt_hash: Option<String>,
+    /// Full model output, think block included.
+    #[serde(default)]
+    response: String,
+    /// Fail instead of answering, to exercise error handling.
+    error: Option<MockFailure>,
+}
+
+/// How a mocked generation fails.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MockFailure {
+    /// The model stayed busy (`ChironError::ModelUnavailable`).
+    Unavailable,
+    /// Generation itself failed (`ChironError::Inference`).
+    Inference,
+    /// A bug: the reply panics partway through.
+    Panic,
+}
+
+/// Deterministic inference backend that replays fixture responses instead
+/// of running a model. The reply for a turn is chosen by hashing the user's
+/// latest message, so a scripted conversation always gets the same output.
+#[derive(Debug)]
+pub struct MockBackend {
+    scenario: String,
+    fallback: String,
+    responses: HashMap<String, String>,
+    failures: HashMap<String, MockFailure>,
+}
+
+impl MockBackend {
+    /// Loads a fixture by path, or by scenario name from `FIXTURE_DIR`.
+    pub fn load(scenario_or_path: &str) -> Result<Self> {
+        let path = fixture_path(scenario_or_path);
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            ChironError::ModelLoad(format!("Failed to read mock fixture {}: {e}", path.display()))
+        })?;
+        Self::parse(&content).map_err(|e| {
+            ChironError::ModelLoad(format!("Invalid mock fixture {}: {e}", path.display()))
+        })
+    }
+
+    /// Parses fixture TOML. Each response needs a `prompt` or a `prompt_hash`.
+    fn parse(content: &str) -> std::result::Result<Self, String> {
+        let fixture: MockFixture = toml::from_str(content).map_err(|e| e.to_string())?;
+        let mut responses = HashMap::new();
+        let mut failures = HashMap::new();
+        for (i, entry) in fixture.responses.into_iter().enumerate() {
+            let hash = match (entry.prompt_hash, entry.prompt) {
+                (Some(hash), _) => hash,
+                (None, Some(prompt)) => prompt_hash(&prompt),
+                (None, None) => return Err(format!("response {} has no prompt or prompt_hash", i + 1)),
+            };
+            if let Some(failure) = entry.error {
+                failures.insert(hash, failure);
+            } else {
+                responses.insert(hash, entry.response);
+            }
+        }
+        Ok(Self {
+            scenario: fixture.scenario,
+            fallback: fixture.fallback,
+            responses,
+            failures,
+        })
+    }
+
+    /// The canned output for a user message, or the fallback. Misses are
+    /// logged with the hash so a response can be added to the fixture.
+    pub fn respond(&self, prompt: &str) -> &str {
+        let hash = prompt_hash(prompt);
+        match self.responses.get(&hash) {
+            Some(response) => response,
+            None => {
+                tracing::warn!(scenario = self.scenario, prompt_hash = hash, "No mock response for prompt; using fallback");
+                &self.fallback
+            }
+        }
+    }
+
+    /// The error a fixture scripts for this user message, if any; checked
+    /// before `respond`. A scripted panic happens here.
+    pub fn failure(&self, prompt: &str) -> Option<ChironError> {
+        let message = format!("Mock failure for scenario {}", self.scenario);
+        self.failures.get(&prompt_hash(prompt)).map(|failure| match failure {
+            MockFailure::Unavailable => ChironError::ModelUnavailable(message),
+            MockFailure::Inference => ChironError::Inference(message),
+            MockFailure::Panic => panic!("{message}"),
+        })
+    }
+
+    pub fn scenario(&self) -> &str {
+        &self.scenario
+    }
+}
+
+/// Resolves a scenario name to `FIXTURE_DIR/<name>.toml`; anything that
+/// looks like a path is used as is.
+fn fixture_path(scenario_or_path: &str) -> PathBuf {
+    let path = Path::new(scenario_or_path);
+    if path.extension().is_some() || path.components().count() > 1 {
+        path.to_path_buf()
+    } else {
+        Path::new(FIXTURE_DIR).join(format!("{scenario_or_path}.toml"))
+    }
+}
+
+/// Stable 64-bit FNV-1a hash of the trimmed, lowercased prompt, as 16 hex
+/// digits. Unlike `DefaultHasher`, it won't change between Rust releases, so
+/// hashes written into fixtures keep matching.
+pub fn prompt_hash(prompt: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in prompt.trim().to_lowercase().bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"
+scenario = "test"
+fallback = "Tell me more."
+
+[[responses]]
+prompt = "I've been feeling down."
+response = "<think>[MI-STAGE: engage]</think>That sounds heavy."
+
+[[responses]]
+prompt_hash = "0123456789abcdef"
+response = "By hash."
+"#;
+
+    #[test]
+    fn test_respond_by_prompt_hash_and_fallback() {
+        let mock = MockBackend::parse(FIXTURE).unwrap();
+        assert_eq!(mock.scenario(), "test");
+        assert_eq!(
+            mock.respond("  i've been feeling DOWN. "),
+            "<think>[MI-STAGE: engage]</think>That sounds heavy.",
+            "matching ignores case and surrounding whitespace"
+        );
+        assert_eq!(mock.respond("something else"), "Tell me more.");
+    }
+
+    #[test]
+    fn test_prompt_hash_is_stable() {
+        assert_eq!(prompt_hash(""), "cbf29ce484222325");
+        assert_eq!(prompt_hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_scripted_failures() {
+        let fixture = "scenario = \"x\"\nfallback = \"\"\n[[responses]]\nprompt = \"busy?\"\nerror = \"unavailable\"\n";
+        let mock = MockBackend::parse(fixture).unwrap();
+        assert!(mock.failure("Busy?").unwrap().is_transient());
+        assert!(mock.failure("hello").is_none());
+    }
+
+    #[test]
+    fn test_response_needs_a_key() {
+        let err = MockBackend::parse("scenario = \"x\"\nfallback = \"\"\n[[responses]]\nresponse = \"hi\"\n").unwrap_err();
+        assert!(err.contains("response 1"), "{err}");
+    }
+
+    #[test]
+    fn test_fixture_path() {
+        assert_eq!(fixture_path("standard_5turn"), Path::new("fixtures/mock/standard_5turn.toml"));
+        assert_eq!(fixture_path("my/fixture.toml"), Path::new("my/fixture.toml"));
+    }
+
+    #[test]
+    fn test_bundled_fixtures_parse() {
+        for entry in std::fs::read_dir(FIXTURE_DIR).unwrap() {
+            let path = entry.unwrap().path();
+            MockBackend::load(path.to_str().unwrap()).unwrap();
+        }
+    }
+}
@@ -1,7 +1,30 @@
 pub mod config;
 pub mod llamacpp;
+pub mod mock;
+pub mod tool_calls;
+
+use std::sync::{Arc, Mutex};
 
 pub use llamacpp::{completion_model, LlamaCppCompletionModel, LlamaCppProvider};
+pub use mock::MockBackend;
+
+/// What generates responses: a loaded llama.cpp model, or fixture replay
+/// for reproducible runs without one (`--mock`).
+#[derive(Clone)]
+pub enum Backend {
+    LlamaCpp(Arc<Mutex<LlamaCppProvider>>),
+    Mock(Arc<MockBackend>),
+}
+
+impl Backend {
+    /// `llamacpp` or `mock`, recorded with each reply.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Backend::LlamaCpp(_) => "llamacpp",
+            Backend::Mock(_) => "mock",
+        }
+    }
+}
 
 /// Strips `<think>...</think>` blocks from model output.
 ///
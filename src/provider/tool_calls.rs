@@ -0,0 +1,197 @@
+use rig::completion::ToolDefinition;
+use rig::message::{ToolCall, ToolFunction, ToolResult, ToolResultContent};
+use serde::Deserialize;
+
+const TOOL_CALL_OPEN: &str = "<tool_call>";
+const TOOL_CALL_CLOSE: &str = "</tool_call>";
+
+/// A call as the model writes it inside a `<tool_call>` block.
+#[derive(Deserialize)]
+struct RawToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// System prompt section describing the available tools and the
+/// `<tool_call>` format (the Hermes/Qwen convention, which models trained
+/// on tool use recognise without an API-level `tools` parameter).
+pub fn tools_section(tools: &[ToolDefinition]) -> String {
+    let mut section = String::from(
+        "# Tools\n\nYou can call these tools. Only call one when it clearly helps; otherwise just reply.\n<tools>\n",
+    );
+    for tool in tools {
+        let definition = serde_json::json!({
+            "name": tool.name,
+            "description": tool.description,
+            "parameters": tool.parameters,
+        });
+        section.push_str(&definition.to_string());
+        section.push('\n');
+    }
+    section.push_str(
+        "</tools>\n\nTo call a tool, make the call your whole reply:\n<tool_call>\n{\"name\": \"<tool name>\", \"arguments\": {<arguments as JSON>}}\n</tool_call>\nThe result comes back in a <tool_response> block; then reply to the person as usual.",
+    );
+    section
+}
+
+/// Renders an earlier tool call back into the transcript the way the model wrote it.
+pub fn render_tool_call(call: &ToolCall) -> String {
+    let body = serde_json::json!({
+        "name": call.function.name,
+        "arguments": call.function.arguments,
+    });
+    format!("{TOOL_CALL_OPEN}\n{body}\n{TOOL_CALL_CLOSE}")
+}
+
+/// Renders a tool's result as the `<tool_response>` block the model expects.
+pub fn render_tool_result(result: &ToolResult) -> String {
+    format!("<tool_response>\n{}\n</tool_response>", tool_result_text(result))
+}
+
+/// The text parts of a tool result. rig JSON-encodes tool output, so a
+/// plain string result is unquoted.
+pub fn tool_result_text(result: &ToolResult) -> String {
+    let text = result
+        .content
+        .iter()
+        .filter_map(|c| match c {
+            ToolResultContent::Text(t) => Some(t.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("");
+    serde_json::from_str::<String>(&text).unwrap_or(text)
+}
+
+/// Splits generated text into the visible reply and the tool calls in it.
+/// Blocks that aren't valid calls are dropped with a warning rather than
+/// shown to the person.
+pub fn parse_tool_calls(text: &str) -> (String, Vec<ToolFunction>) {
+    let mut visible = String::new();
+    let mut calls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(TOOL_CALL_OPEN) {
+        visible.push_str(&rest[..start]);
+        let body_start = start + TOOL_CALL_OPEN.len();
+        let (body, after) = match rest[body_start..].find(TOOL_CALL_CLOSE) {
+            Some(end) => (&rest[body_start..body_start + end], &rest[body_start + end + TOOL_CALL_CLOSE.len()..]),
+            // Cut off by the token limit
+            None => (&rest[body_start..], ""),
+        };
+        match serde_json::from_str::<RawToolCall>(body.trim()) {
+            Ok(call) => calls.push(ToolFunction::new(call.name, call.arguments)),
+            Err(e) => tracing::warn!(error = %e, body, "Ignoring malformed tool call"),
+        }
+        rest = after;
+    }
+    visible.push_str(rest);
+    (visible.trim().to_string(), calls)
+}
+
+/// Holds back streamed text while it could still be the start of a tool
+/// call, so call syntax never reaches the person. A call has to open the
+/// reply; once the reply starts with anything else, text passes straight
+/// through.
+#[derive(Debug, Default)]
+pub struct ToolCallGate {
+    buffer: String,
+    state: GateState,
+}
+
+#[derive(Debug, Default, PartialEq)]
+enum GateState {
+    #[default]
+    Undecided,
+    Text,
+    Call,
+}
+
+impl ToolCallGate {
+    /// Takes the next piece of visible text and returns what can be shown now.
+    pub fn push(&mut self, piece: &str) -> Option<String> {
+        match self.state {
+            GateState::Text => return Some(piece.to_string()),
+            GateState::Call => {
+                self.buffer.push_str(piece);
+                return None;
+            }
+            GateState::Undecided => self.buffer.push_str(piece),
+        }
+        let start = self.buffer.trim_start();
+        if start.is_empty() || TOOL_CALL_OPEN.starts_with(start) {
+            None
+        } else if start.starts_with(TOOL_CALL_OPEN) {
+            self.state = GateState::Call;
+            None
+        } else {
+            self.state = GateState::Text;
+            Some(std::mem::take(&mut self.buffer))
+        }
+    }
+
+    /// Ends the reply: held-back text that turned out to be plain, and the
+    /// calls if it was a tool call.
+    pub fn finish(self) -> (String, Vec<ToolFunction>) {
+        match self.state {
+            GateState::Call => parse_tool_calls(&self.buffer),
+            _ => (self.buffer, Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_calls() {
+        let (visible, calls) = parse_tool_calls(
+            "<tool_call>\n{\"name\": \"search_memory\", \"arguments\": {\"query\": \"sister\"}}\n</tool_call>",
+        );
+        assert_eq!(visible, "");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "search_memory");
+        assert_eq!(calls[0].arguments["query"], "sister");
+
+        let (visible, calls) = parse_tool_calls("Sure. <tool_call>not json</tool_call> Anyway.");
+        assert_eq!(visible, "Sure.  Anyway.");
+        assert!(calls.is_empty(), "malformed calls are dropped");
+
+        let (visible, calls) = parse_tool_calls("No tools here.");
+        assert_eq!((visible.as_str(), calls.len()), ("No tools here.", 0));
+    }
+
+    #[test]
+    fn test_tool_result_text_unquotes_strings() {
+        let result = |output: &str| ToolResult {
+            id: "call_1".into(),
+            call_id: None,
+            content: ToolResultContent::from_tool_output(output),
+        };
+        assert_eq!(tool_result_text(&result("\"Saved: mood 4/10.\"")), "Saved: mood 4/10.");
+        assert_eq!(tool_result_text(&result("{\"count\": 2}")), "{\"count\": 2}");
+        assert_eq!(
+            render_tool_result(&result("\"ok\"")),
+            "<tool_response>\nok\n</tool_response>"
+        );
+    }
+
+    #[test]
+    fn test_gate_holds_back_tool_calls() {
+        let mut gate = ToolCallGate::default();
+        assert_eq!(gate.push("\n<tool"), None, "could still be a call");
+        assert_eq!(gate.push("_call>{\"name\": \"log_journal\", "), None);
+        assert_eq!(gate.push("\"arguments\": {\"mood\": 4}}</tool_call>"), None);
+        let (visible, calls) = gate.finish();
+        assert_eq!(visible, "");
+        assert_eq!(calls[0].name, "log_journal");
+        assert_eq!(calls[0].arguments["mood"], 4);
+
+        let mut gate = ToolCallGate::default();
+        assert_eq!(gate.push("<t"), None);
+        assert_eq!(gate.push("hink about it"), Some("<think about it".to_string()));
+        assert_eq!(gate.push(" more"), Some(" more".to_string()));
+        assert_eq!(gate.finish(), (String::new(), Vec::new()));
+    }
+}
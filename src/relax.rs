@@ -0,0 +1,104 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use tokio_rusqlite::Connection;
+
+use crate::catalog::{RelaxationCatalog, RelaxationScript};
+use crate::checkin::ask;
+use crate::i18n::{t, tf};
+use crate::memory::{coping, relaxation};
+use crate::speech::Speaker;
+
+/// Runs a `/relax` command: no argument lists the scripts with how often
+/// each has been done; an ID plays that script.
+pub async fn command(
+    conn: &Connection,
+    catalog: Option<&RelaxationCatalog>,
+    speaker: Option<&Speaker>,
+    session_id: &str,
+    args: &str,
+) -> Result<()> {
+    let Some(catalog) = catalog else {
+        println!("{}", t("relax.unavailable"));
+        return Ok(());
+    };
+
+    let id = args.trim();
+    if id.is_empty() {
+        let usage = relaxation::relaxation_usage(conn).await?;
+        for script in &catalog.scripts {
+            let times = usage.iter().find(|u| u.script_id == script.id).map_or(0, |u| u.times);
+            println!(
+                "  {:<10} {} ({}) — {}",
+                script.id,
+                script.name,
+                format_duration(script.duration_secs()),
+                script.description
+            );
+            if times > 0 {
+                println!("  {:<10} {}", "", tf("relax.times", &[("times", &times)]));
+            }
+        }
+        println!("{}", t("relax.usage"));
+        return Ok(());
+    }
+
+    let Some(script) = catalog.get_script(id) else {
+        println!("{}", tf("relax.unknown", &[("id", &id)]));
+        return Ok(());
+    };
+    play(conn, script, speaker, session_id).await
+}
+
+/// Plays a script: each step is printed (and spoken), then held for its
+/// pause. The completion is logged, and an optional 1–5 rating goes to the
+/// coping skills so the coach knows whether it helped.
+async fn play(conn: &Connection, script: &RelaxationScript, speaker: Option<&Speaker>, session_id: &str) -> Result<()> {
+    println!(
+        "\n{}\n",
+        tf("relax.starting", &[("name", &script.name), ("duration", &format_duration(script.duration_secs()))])
+    );
+    let started = Instant::now();
+
+    for step in &script.steps {
+        println!("{}", step.text);
+        io::stdout().flush()?;
+        if let Some(speaker) = speaker {
+            speaker.push(&step.text);
+            speaker.flush();
+        }
+        tokio::time::sleep(Duration::from_secs(step.pause_secs)).await;
+    }
+
+    relaxation::log_relaxation(conn, session_id, &script.id, started.elapsed().as_secs()).await?;
+    tracing::info!(script = script.id, "Relaxation script completed");
+
+    let answer = ask(&format!("\n{}", t("relax.rate")))?;
+    if let Some(rating) = answer.parse::<i32>().ok().filter(|r| (1..=5).contains(r)) {
+        coping::rate_skill(conn, session_id, &script.name, rating).await?;
+        println!("{}", tf("coping.rated", &[("skill", &script.name), ("rating", &rating)]));
+    }
+    Ok(())
+}
+
+/// Formats seconds as `2 min` (rounded), or `45 s` under a minute.
+fn format_duration(secs: u64) -> String {
+    if secs < 60 {
+        format!("{secs} s")
+    } else {
+        format!("{} min", (secs + 30) / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45 s");
+        assert_eq!(format_duration(95), "2 min");
+        assert_eq!(format_duration(360), "6 min");
+    }
+}
@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::{mpsc, watch};
+
+use crate::config::Config;
+use crate::guardrails::{self, Guardrails};
+
+/// Editors save in bursts (truncate, write, rename); wait this long after
+/// the first change before reloading.
+const SETTLE: Duration = Duration::from_millis(200);
+
+/// The agent settings from `config.toml` that take effect without a restart.
+/// Orchestrators pick up new values at the start of their next turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentToggles {
+    /// `persona.tools`
+    pub tools: bool,
+    /// `persona.show_thinking`
+    pub show_thinking: bool,
+}
+
+impl AgentToggles {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            tools: config.persona.tools,
+            show_thinking: config.persona.show_thinking,
+        }
+    }
+
+    /// Describes what differs from `previous`, one line per setting.
+    fn changes_from(&self, previous: &Self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.tools != previous.tools {
+            changes.push(format!("persona.tools: {} -> {}", previous.tools, self.tools));
+        }
+        if self.show_thinking != previous.show_thinking {
+            changes.push(format!(
+                "persona.show_thinking: {} -> {}",
+                previous.show_thinking, self.show_thinking
+            ));
+        }
+        changes
+    }
+}
+
+/// Whether `after` changes anything besides the live settings, which only
+/// takes effect on the next start.
+fn needs_restart(before: &Config, after: &Config) -> bool {
+    fn without_live(config: &Config) -> String {
+        let mut config = config.clone();
+        config.persona.tools = false;
+        config.persona.show_thinking = false;
+        config.to_toml().unwrap_or_default()
+    }
+    without_live(before) != without_live(after)
+}
+
+/// Watches `config_path` and the `guardrails.toml` beside it, applying the
+/// safe subset of each change live and logging what changed:
+///
+/// - `guardrails.toml`: crisis keywords and the crisis response (`guardrails::set`)
+/// - `config.toml`: `AgentToggles`, sent on `toggles`; `pin` re-applies CLI
+///   flags so they keep winning over the file
+///
+/// Any other config change is logged as needing a restart. A file that fails
+/// to parse is reported and the previous settings stay in effect.
+pub fn spawn(
+    config_path: &Path,
+    toggles: watch::Sender<AgentToggles>,
+    pin: impl Fn(&mut AgentToggles) + Send + 'static,
+) -> Result<()> {
+    let guardrails_path = Guardrails::path_for(config_path);
+    let dir = match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    if !dir.is_dir() {
+        tracing::debug!(dir = %dir.display(), "Config directory doesn't exist; not watching for changes");
+        return Ok(());
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        match event {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(e) => tracing::warn!(error = %e, "Config watcher error"),
+        }
+    })
+    .context("Failed to start config watcher")?;
+    // Watch the directory, not the files: saving often replaces the file
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    tracing::info!(dir = %dir.display(), "Watching config and guardrails for changes");
+
+    let config_path = config_path.to_path_buf();
+    let mut config = Config::load(&config_path).unwrap_or_default();
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        while let Some(event) = rx.recv().await {
+            let mut events = vec![event];
+            tokio::time::sleep(SETTLE).await;
+            while let Ok(event) = rx.try_recv() {
+                events.push(event);
+            }
+
+            if touches(&events, &config_path) {
+                match Config::load(&config_path) {
+                    Ok(reloaded) => {
+                        apply_config(&config, &reloaded, &toggles, &pin);
+                        config = reloaded;
+                    }
+                    Err(e) => tracing::warn!(error = %format!("{e:#}"), "Ignoring invalid config change"),
+                }
+            }
+            if touches(&events, &guardrails_path) {
+                match Guardrails::load(&guardrails_path) {
+                    Ok(reloaded) => apply_guardrails(reloaded),
+                    Err(e) => tracing::warn!(error = %format!("{e:#}"), "Ignoring invalid guardrails change"),
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Whether any of `events` modified, created, or removed `path`.
+fn touches(events: &[notify::Event], path: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    events.iter().any(|event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event.paths.iter().any(|p| p.file_name() == Some(name))
+    })
+}
+
+fn apply_config(
+    before: &Config,
+    after: &Config,
+    toggles: &watch::Sender<AgentToggles>,
+    pin: &impl Fn(&mut AgentToggles),
+) {
+    let mut updated = AgentToggles::from_config(after);
+    pin(&mut updated);
+    let previous = *toggles.borrow();
+    for change in updated.changes_from(&previous) {
+        tracing::info!(change, "Applied config change");
+    }
+    toggles.send_if_modified(|current| {
+        let modified = *current != updated;
+        *current = updated;
+        modified
+    });
+    if needs_restart(before, after) {
+        tracing::warn!("config.toml changed settings that take effect after a restart");
+    }
+}
+
+fn apply_guardrails(reloaded: Guardrails) {
+    let changes = reloaded.changes_from(&guardrails::current());
+    if changes.is_empty() {
+        return;
+    }
+    for change in changes {
+        tracing::info!(change, "Applied guardrails change");
+    }
+    guardrails::set(reloaded);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_changes_and_restart_detection() {
+        let before = Config::default();
+        let mut after = before.clone();
+        after.persona.tools = !before.persona.tools;
+
+        let changes = AgentToggles::from_config(&after).changes_from(&AgentToggles::from_config(&before));
+        assert_eq!(changes, vec![format!("persona.tools: {} -> {}", before.persona.tools, after.persona.tools)]);
+        assert!(!needs_restart(&before, &after));
+
+        after.model.temperature = 0.2;
+        assert!(needs_restart(&before, &after));
+    }
+
+    #[test]
+    fn test_pinned_toggles_ignore_the_file() {
+        let (tx, rx) = watch::channel(AgentToggles { tools: false, show_thinking: true });
+        let mut after = Config::default();
+        after.persona.tools = true;
+        after.persona.show_thinking = false;
+
+        apply_config(&Config::default(), &after, &tx, &|toggles: &mut AgentToggles| {
+            toggles.show_thinking = true;
+        });
+        assert_eq!(*rx.borrow(), AgentToggles { tools: true, show_thinking: true });
+    }
+
+    #[test]
+    fn test_touches_matches_file_name_and_skips_reads() {
+        let path = Path::new("/home/me/.config/chiron/guardrails.toml");
+        let write = notify::Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(PathBuf::from("/home/me/.config/chiron/guardrails.toml"));
+        let read = notify::Event::new(EventKind::Access(notify::event::AccessKind::Any))
+            .add_path(PathBuf::from("/home/me/.config/chiron/guardrails.toml"));
+        let other = notify::Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(PathBuf::from("/home/me/.config/chiron/config.toml"));
+
+        assert!(touches(&[other.clone(), write], path));
+        assert!(!touches(&[read, other], path));
+    }
+}
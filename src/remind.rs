@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDateTime, NaiveTime, Utc};
+use tokio_rusqlite::Connection;
+
+use crate::config::ReminderConfig;
+use crate::i18n::{t, tf};
+use crate::memory::{homework, mood, preferences};
+use crate::output;
+use crate::timezone;
+
+/// A reminder to show once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    /// Preference key recording the day it was last sent, so each reminder
+    /// goes out at most once a day.
+    pub key: &'static str,
+    pub title: String,
+    pub body: String,
+}
+
+/// A daily window with no reminders. It may cross midnight
+/// (`22:00-08:00`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Parses `HH:MM-HH:MM`; empty means no quiet hours.
+    pub fn parse(s: &str) -> Result<Option<Self>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        let Some((start, end)) = s.split_once('-') else {
+            bail!("Invalid quiet hours '{s}' (expected HH:MM-HH:MM, e.g. 22:00-08:00)");
+        };
+        Ok(Some(Self { start: parse_time(start)?, end: parse_time(end)? }))
+    }
+
+    /// Whether `time` falls in the window (start inclusive, end exclusive).
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").with_context(|| format!("Invalid time '{}' (expected HH:MM)", s.trim()))
+}
+
+/// `chiron remind`: checks once for reminders that are due and sends them,
+/// or with `daemon`, keeps checking every `interval_minutes`. Nothing is
+/// sent during quiet hours, and each reminder at most once a day.
+pub async fn run(conn: &Connection, config: &ReminderConfig, daemon: bool) -> Result<()> {
+    let quiet_hours = QuietHours::parse(&config.quiet_hours)?;
+    if !config.checkin_time.is_empty() {
+        parse_time(&config.checkin_time).context("Invalid reminders.checkin_time")?;
+    }
+    if daemon {
+        output::status(format!("Checking for reminders every {} minutes", config.interval_minutes.max(1)));
+    }
+    loop {
+        let now = timezone::display_zone().convert(Utc::now());
+        if !quiet_hours.is_some_and(|q| q.contains(now.time())) {
+            let today = now.date().to_string();
+            for reminder in due(conn, config, now).await? {
+                if preferences::get_preference(conn, reminder.key).await?.as_deref() == Some(today.as_str()) {
+                    continue;
+                }
+                notify(&reminder, config.desktop);
+                preferences::set_preference(conn, reminder.key, &today).await?;
+            }
+        }
+        if !daemon {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(config.interval_minutes.max(1) * 60)).await;
+    }
+}
+
+/// The reminders due at `now` (display zone): the daily check-in once
+/// `checkin_time` has passed without one today, and homework due by today
+/// that hasn't been reviewed.
+pub async fn due(conn: &Connection, config: &ReminderConfig, now: NaiveDateTime) -> Result<Vec<Reminder>> {
+    let today = now.date();
+    let mut reminders = Vec::new();
+
+    if !config.checkin_time.is_empty() && now.time() >= parse_time(&config.checkin_time)? {
+        // Stored in UTC; yesterday's rows cover any display-zone offset
+        let since = (today - chrono::Days::new(1)).to_string();
+        let checked_in =
+            mood::checkins_since(conn, &since).await?.iter().any(|c| timezone::date(&c.created_at) == Some(today));
+        if !checked_in {
+            reminders.push(Reminder {
+                key: "reminded_checkin",
+                title: t("remind.checkin_title").to_string(),
+                body: t("remind.checkin_body").to_string(),
+            });
+        }
+    }
+
+    if config.homework {
+        let today = today.to_string();
+        let due: Vec<_> = homework::due_for_review(conn, "", &today)
+            .await?
+            .into_iter()
+            .filter(|h| h.due_date.is_some())
+            .collect();
+        if let Some(first) = due.first() {
+            reminders.push(Reminder {
+                key: "reminded_homework",
+                title: t("remind.homework_title").to_string(),
+                body: tf("remind.homework_body", &[("assignment", &first.assignment), ("count", &due.len())]),
+            });
+        }
+    }
+
+    Ok(reminders)
+}
+
+/// Shows a desktop notification when built with the `notifications`
+/// feature and `desktop` is on, falling back to printing the reminder.
+fn notify(reminder: &Reminder, desktop: bool) {
+    #[cfg(feature = "notifications")]
+    if desktop {
+        let shown = notify_rust::Notification::new()
+            .appname("Chiron")
+            .summary(&reminder.title)
+            .body(&reminder.body)
+            .show();
+        match shown {
+            Ok(_) => return,
+            Err(e) => tracing::warn!("Desktop notification failed: {e}"),
+        }
+    }
+    #[cfg(not(feature = "notifications"))]
+    let _ = desktop;
+    println!("{}: {}", reminder.title, reminder.body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::open_memory;
+
+    fn at(time: &str) -> NaiveTime {
+        parse_time(time).unwrap()
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_past_midnight() {
+        let night = QuietHours::parse("22:00-08:00").unwrap().unwrap();
+        assert!(night.contains(at("23:30")));
+        assert!(night.contains(at("03:00")));
+        assert!(!night.contains(at("08:00")));
+        assert!(!night.contains(at("12:00")));
+
+        let lunch = QuietHours::parse(" 12:00 - 13:00 ").unwrap().unwrap();
+        assert!(lunch.contains(at("12:30")));
+        assert!(!lunch.contains(at("23:00")));
+
+        assert_eq!(QuietHours::parse("").unwrap(), None);
+        assert!(QuietHours::parse("22:00").is_err());
+        assert!(QuietHours::parse("late-early").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_due_reminders() {
+        let conn = open_memory(":memory:").await.unwrap();
+        let config = ReminderConfig::default();
+        let today = timezone::today();
+        let evening = today.and_time(at("21:00"));
+        let keys = |reminders: Vec<Reminder>| reminders.iter().map(|r| r.key).collect::<Vec<_>>();
+
+        assert!(due(&conn, &config, today.and_time(at("09:00"))).await.unwrap().is_empty());
+        assert_eq!(keys(due(&conn, &config, evening).await.unwrap()), vec!["reminded_checkin"]);
+
+        homework::assign_homework(&conn, "s1", "Take a walk", Some(&today.to_string())).await.unwrap();
+        homework::assign_homework(&conn, "s1", "Journal", None).await.unwrap();
+        mood::save_checkin(&conn, 6, None, None, None).await.unwrap();
+        let reminders = due(&conn, &config, evening).await.unwrap();
+        assert_eq!(keys(reminders.clone()), vec!["reminded_homework"]);
+        assert!(reminders[0].body.contains("Take a walk"));
+    }
+}
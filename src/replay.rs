@@ -0,0 +1,288 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::orchestrator::events::{SafetyKind, TurnEvent};
+use crate::orchestrator::{Orchestrator, TurnResult};
+use crate::supervision::analyze_think_block;
+
+/// One line of a replay transcript: a user message and what the pipeline
+/// decided when it was recorded. A line with only `input` is a turn that
+/// has no baseline yet (run with `--update` to record one).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedTurn {
+    pub input: String,
+    #[serde(default)]
+    pub safety: Vec<SafetyKind>,
+    #[serde(default)]
+    pub agent: String,
+    #[serde(default)]
+    pub mi_stage: Option<String>,
+    #[serde(default)]
+    pub strategy: Option<String>,
+    #[serde(default)]
+    pub talk_type: Option<String>,
+    #[serde(default)]
+    pub themes: Vec<String>,
+    /// Kept for reading the transcript; wording is never diffed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+}
+
+impl RecordedTurn {
+    /// Every answered turn selects an agent, so no agent means never recorded.
+    pub fn has_baseline(&self) -> bool {
+        !self.agent.is_empty()
+    }
+}
+
+/// A decision that differs from the baseline.
+#[derive(Debug, PartialEq)]
+pub struct Regression {
+    /// 1-based turn number.
+    pub turn: usize,
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Regression {
+    /// Safety changes are the ones that must never slip through unnoticed.
+    pub fn is_safety(&self) -> bool {
+        self.field == "safety"
+    }
+}
+
+/// Reads a JSONL transcript, skipping blank lines.
+pub fn load(path: &Path) -> Result<Vec<RecordedTurn>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read transcript: {}", path.display()))?;
+    parse(&content).with_context(|| format!("Failed to parse transcript: {}", path.display()))
+}
+
+fn parse(content: &str) -> Result<Vec<RecordedTurn>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).with_context(|| format!("Line {}", i + 1)))
+        .collect()
+}
+
+/// Writes turns as a JSONL transcript, one turn per line.
+pub fn save(path: &Path, turns: &[RecordedTurn]) -> Result<()> {
+    let mut out = String::new();
+    for turn in turns {
+        out.push_str(&serde_json::to_string(turn)?);
+        out.push('\n');
+    }
+    std::fs::write(path, out).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Runs one input through the orchestrator and records its decisions.
+pub async fn observe(orchestrator: &mut Orchestrator, input: &str) -> Result<RecordedTurn> {
+    // Collect on a separate task so a long response can't push the early
+    // safety and routing events out of the channel before they're read.
+    let mut rx = orchestrator.subscribe();
+    let collector = tokio::spawn(async move {
+        let mut events = Vec::new();
+        loop {
+            match rx.recv().await {
+                Ok(TurnEvent::TokenChunk(_)) => {}
+                Ok(event) if event.is_terminal() => break,
+                Ok(event) => events.push(event),
+                Err(RecvError::Lagged(missed)) => tracing::warn!(missed, "Replay fell behind turn events"),
+                Err(RecvError::Closed) => break,
+            }
+        }
+        events
+    });
+
+    let result = orchestrator.run_turn_captured(input).await;
+    let events = collector.await.context("Event collector failed")?;
+    Ok(record(input, &events, &result?))
+}
+
+/// Builds a recorded turn from a turn's events and result.
+fn record(input: &str, events: &[TurnEvent], result: &TurnResult) -> RecordedTurn {
+    let mut turn = RecordedTurn {
+        input: input.to_string(),
+        response: Some(result.response.clone()),
+        ..Default::default()
+    };
+    for event in events {
+        match event {
+            TurnEvent::SafetyFlag { kind, .. } => turn.safety.push(kind.clone()),
+            TurnEvent::AgentSelected { agent } => turn.agent = agent.clone(),
+            _ => {}
+        }
+    }
+    if let Some(think) = result.think_content.as_deref() {
+        let analysis = analyze_think_block(think);
+        turn.mi_stage = analysis.mi_stage;
+        turn.strategy = analysis.strategy_used;
+        turn.talk_type = analysis.talk_type;
+        turn.themes = analysis.themes;
+    }
+    turn
+}
+
+/// Compares a replayed turn against its baseline. Theme order is ignored,
+/// and a turn with no baseline has nothing to regress from.
+pub fn diff(turn: usize, baseline: &RecordedTurn, actual: &RecordedTurn) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+    if !baseline.has_baseline() {
+        return regressions;
+    }
+    let mut check = |field: &'static str, expected: String, got: String| {
+        if expected != got {
+            regressions.push(Regression { turn, field, expected, actual: got });
+        }
+    };
+
+    check("safety", safety_label(&baseline.safety), safety_label(&actual.safety));
+    check("agent", baseline.agent.clone(), actual.agent.clone());
+    check("mi_stage", tag_label(&baseline.mi_stage), tag_label(&actual.mi_stage));
+    check("strategy", tag_label(&baseline.strategy), tag_label(&actual.strategy));
+    check("talk_type", tag_label(&baseline.talk_type), tag_label(&actual.talk_type));
+    check("themes", themes_label(&baseline.themes), themes_label(&actual.themes));
+    regressions
+}
+
+fn safety_label(flags: &[SafetyKind]) -> String {
+    if flags.is_empty() {
+        return "none".to_string();
+    }
+    flags
+        .iter()
+        .map(|kind| match kind {
+            SafetyKind::Crisis => "crisis",
+            SafetyKind::WarningSign => "warning_sign",
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn tag_label(tag: &Option<String>) -> String {
+    tag.clone().unwrap_or_else(|| "none".to_string())
+}
+
+fn themes_label(themes: &[String]) -> String {
+    let mut sorted = themes.to_vec();
+    sorted.sort();
+    if sorted.is_empty() { "none".to_string() } else { sorted.join(", ") }
+}
+
+/// Plain-text report: safety regressions first, then routing and tags.
+pub fn render_report(turns: usize, unrecorded: usize, regressions: &[Regression]) -> String {
+    let safety = regressions.iter().filter(|r| r.is_safety()).count();
+    let mut out = format!(
+        "Replayed {turns} turns: {} regressions ({safety} safety)",
+        regressions.len()
+    );
+    if unrecorded > 0 {
+        let _ = write!(out, ", {unrecorded} without a baseline (run with --update)");
+    }
+    out.push('\n');
+    let ordered = regressions
+        .iter()
+        .filter(|r| r.is_safety())
+        .chain(regressions.iter().filter(|r| !r.is_safety()));
+    for r in ordered {
+        let marker = if r.is_safety() { "!!" } else { "  " };
+        let _ = writeln!(
+            out,
+            "{marker} turn {} {}: expected {}, got {}",
+            r.turn, r.field, r.expected, r.actual
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(response: &str, think: Option<&str>) -> TurnResult {
+        TurnResult {
+            turn_number: 1,
+            input: String::new(),
+            response: response.to_string(),
+            think_content: think.map(str::to_string),
+            case_notes: None,
+            preamble_injected: String::new(),
+            duration_ms: 0,
+            prompt_variant: None,
+            interrupted: false,
+        }
+    }
+
+    #[test]
+    fn test_record_from_events_and_think_block() {
+        let events = vec![
+            TurnEvent::UserMessage { session_id: "s".into(), text: "hi".into() },
+            TurnEvent::AgentSelected { agent: "v7-unified".into() },
+            TurnEvent::SafetyFlag { kind: SafetyKind::WarningSign, message: "note".into() },
+        ];
+        let think = "[MI-STAGE: focus]\n[STRATEGY: complex reflection]\n[THEMES: Sleep, work]";
+        let turn = record("hi", &events, &result("Hello.", Some(think)));
+
+        assert_eq!(turn.agent, "v7-unified");
+        assert_eq!(turn.safety, vec![SafetyKind::WarningSign]);
+        assert_eq!(turn.mi_stage.as_deref(), Some("focus"));
+        assert_eq!(turn.strategy.as_deref(), Some("complex reflection"));
+        assert_eq!(turn.talk_type, None);
+        assert_eq!(turn.themes, vec!["sleep", "work"]);
+    }
+
+    #[test]
+    fn test_diff_flags_changes_and_ignores_theme_order() {
+        let baseline = RecordedTurn {
+            input: "x".into(),
+            safety: vec![SafetyKind::Crisis],
+            agent: "crisis".into(),
+            themes: vec!["work".into(), "sleep".into()],
+            response: Some("old wording".into()),
+            ..Default::default()
+        };
+        let same = RecordedTurn {
+            themes: vec!["sleep".into(), "work".into()],
+            response: Some("new wording".into()),
+            ..baseline.clone()
+        };
+        assert!(diff(1, &baseline, &same).is_empty());
+        assert!(diff(1, &RecordedTurn::default(), &same).is_empty(), "no baseline, no regressions");
+
+        let missed = RecordedTurn {
+            safety: vec![],
+            agent: "v7-unified".into(),
+            ..baseline.clone()
+        };
+        let regressions = diff(4, &baseline, &missed);
+        assert_eq!(regressions.len(), 2);
+        assert!(regressions[0].is_safety());
+        assert_eq!(regressions[0].expected, "crisis");
+        assert_eq!(regressions[0].actual, "none");
+
+        let report = render_report(5, 0, &regressions);
+        assert!(report.starts_with("Replayed 5 turns: 2 regressions (1 safety)"));
+        assert!(report.contains("!! turn 4 safety: expected crisis, got none"));
+    }
+
+    #[test]
+    fn test_parse_transcript() {
+        let turns = parse(
+            "{\"input\": \"hi\"}\n\n{\"input\": \"help\", \"safety\": [\"crisis\"], \"agent\": \"crisis\"}\n",
+        )
+        .unwrap();
+        assert_eq!(turns.len(), 2);
+        assert!(!turns[0].has_baseline());
+        assert_eq!(turns[1].safety, vec![SafetyKind::Crisis]);
+
+        let err = parse("{\"input\": \"hi\"}\nnot json\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 2");
+    }
+}
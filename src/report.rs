@@ -0,0 +1,557 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use tokio_rusqlite::Connection;
+
+use crate::memory::archive::{self, SessionArchive};
+use crate::memory::case_notes::{self, CaseNote};
+use crate::memory::mood::{self, CheckIn};
+use crate::memory::{self, ChatTurn};
+use crate::noise::{self, Noise};
+use crate::orchestrator::MAX_THEMES;
+use crate::router;
+use crate::supervision::taxonomy::{Category, Taxonomy};
+use crate::supervision::{extract_mi_stage, extract_strategy, extract_themes};
+use crate::timezone;
+
+/// How much personal detail a report includes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Redaction {
+    /// Everything, including check-in highlight/stressor text.
+    None,
+    /// Themes and techniques, but no free text written by the user.
+    Standard,
+    /// Aggregate numbers only (mood, session and crisis counts).
+    Strict,
+}
+
+/// Per-session rollup for the report.
+struct SessionRow {
+    date: String,
+    messages: usize,
+    mi_stage: Option<String>,
+    themes: Vec<String>,
+}
+
+/// Check-ins in one week.
+struct MoodWeek {
+    /// Monday of the week.
+    week: NaiveDate,
+    checkins: usize,
+    mood: f64,
+    /// Average sleep, and how many check-ins recorded it.
+    sleep: Option<(f64, usize)>,
+}
+
+/// Everything a report is rendered from.
+pub struct ReportData {
+    since: NaiveDate,
+    checkins: Vec<CheckIn>,
+    /// Check-ins in the period, their average mood, and the same by week.
+    checkin_count: usize,
+    average_mood: Option<f64>,
+    weeks: Vec<MoodWeek>,
+    sessions: Vec<SessionRow>,
+    session_count: usize,
+    message_count: usize,
+    /// Themes ranked by the number of sessions they appeared in.
+    concerns: Vec<(String, usize)>,
+    /// Strategies ranked by the number of turns they were used in.
+    techniques: Vec<(String, usize)>,
+    crisis_events: usize,
+    /// Set once the numbers are noised (see `add_noise`).
+    epsilon: Option<f64>,
+}
+
+/// How many statistics a noised report releases, which its epsilon is
+/// split across: the weekly check-in counts, mood sums, sleep counts, and
+/// sleep sums; the session, message, and crisis counts; and the concern
+/// and technique counts.
+const NOISED_RELEASES: usize = 9;
+
+/// Where concerns and techniques outside the taxonomy are counted in a
+/// noised report.
+const OTHER: &str = "other";
+
+/// Loads report data for activity on or after `since`.
+pub async fn gather(conn: &Connection, since: NaiveDate) -> Result<ReportData> {
+    let since_str = since.format("%Y-%m-%d").to_string();
+    let checkins = mood::checkins_since(conn, &since_str).await?;
+    let turns = memory::turns_since(conn, &since_str).await?;
+    let archives = archive::archives_since(conn, &since_str).await?;
+    let notes = case_notes::case_notes_since(conn, &since_str).await?;
+    Ok(build(since, checkins, &turns, &archives, &notes))
+}
+
+fn build(
+    since: NaiveDate,
+    checkins: Vec<CheckIn>,
+    turns: &[(String, ChatTurn)],
+    archives: &[SessionArchive],
+    notes: &[CaseNote],
+) -> ReportData {
+    // Keyed by session ID, but kept in first-seen order via `order`
+    let mut order = Vec::new();
+    let mut sessions: BTreeMap<&str, SessionRow> = BTreeMap::new();
+    // Archived sessions keep their counts but not their messages
+    for archived in archives {
+        order.push(archived.session_id.as_str());
+        sessions.insert(
+            archived.session_id.as_str(),
+            SessionRow {
+                date: timezone::day(&archived.started_at),
+                messages: archived.message_count as usize,
+                mi_stage: archived.progress.mi_stage_end.clone(),
+                themes: Vec::new(),
+            },
+        );
+    }
+    for (session_id, turn) in turns {
+        let row = sessions.entry(session_id).or_insert_with(|| {
+            order.push(session_id.as_str());
+            SessionRow {
+                date: timezone::day(&turn.created_at),
+                messages: 0,
+                mi_stage: None,
+                themes: Vec::new(),
+            }
+        });
+        row.messages += 1;
+    }
+
+    // Free-form tags are grouped under their canonical names, so "work
+    // stress" and "deadlines" both count as work
+    let taxonomy = Taxonomy::builtin();
+    let mut techniques: BTreeMap<String, usize> = BTreeMap::new();
+    for note in notes {
+        if let Some(row) = sessions.get_mut(note.session_id.as_str()) {
+            // Later notes carry the session's latest stage and themes
+            row.mi_stage = extract_mi_stage(&note.content).or(note.mi_stage.clone());
+            row.themes = extract_themes(&note.content).unwrap_or_default();
+        }
+        if let Some(strategy) = extract_strategy(&note.content) {
+            *techniques.entry(taxonomy.label(&strategy)).or_default() += 1;
+        }
+    }
+
+    let mut concerns: BTreeMap<String, usize> = BTreeMap::new();
+    for row in sessions.values() {
+        // Capped like the running themes, which bounds how much one session
+        // can change the counts (see `add_noise`)
+        let labels: BTreeSet<String> = row.themes.iter().take(MAX_THEMES).map(|t| taxonomy.label(t)).collect();
+        for label in labels {
+            *concerns.entry(label).or_default() += 1;
+        }
+    }
+
+    let crisis_events = turns
+        .iter()
+        .filter(|(_, t)| t.role == "user" && router::is_crisis(&t.content))
+        .count()
+        + archives.iter().map(|a| a.progress.crisis_messages as usize).sum::<usize>();
+
+    let weeks = by_week(&checkins)
+        .into_iter()
+        .map(|(week, entries)| {
+            let sleep: Vec<f64> = entries.iter().filter_map(|c| c.sleep_hours).collect();
+            MoodWeek {
+                week,
+                checkins: entries.len(),
+                mood: mood::average_mood(&entries).unwrap_or_default(),
+                sleep: (!sleep.is_empty()).then(|| (sleep.iter().sum::<f64>() / sleep.len() as f64, sleep.len())),
+            }
+        })
+        .collect();
+    let sessions: Vec<SessionRow> = order.into_iter().filter_map(|id| sessions.remove(id)).collect();
+
+    ReportData {
+        since,
+        checkin_count: checkins.len(),
+        average_mood: mood::average_mood(&checkins),
+        weeks,
+        checkins,
+        session_count: sessions.len(),
+        message_count: sessions.iter().map(|s| s.messages).sum(),
+        sessions,
+        concerns: ranked(concerns),
+        techniques: ranked(techniques),
+        crisis_events,
+        epsilon: None,
+    }
+}
+
+impl ReportData {
+    /// Adds Laplace noise to every number in the aggregate (`Strict`)
+    /// report, so it can be shared for research without exposing any one
+    /// message, check-in, or session. `epsilon` is the total budget, split
+    /// evenly across the `NOISED_RELEASES` statistics.
+    ///
+    /// Everything is released over a fixed domain, so what's present isn't
+    /// given away by what's listed: every week from `since` to `today`,
+    /// including empty ones, and every concern and technique in the
+    /// taxonomy, including those never seen. Totals and averages are
+    /// computed from the noised weekly numbers, never the true ones.
+    pub fn add_noise(&mut self, epsilon: f64, today: NaiveDate) -> Result<()> {
+        let noise = Noise::new(epsilon, NOISED_RELEASES)?;
+
+        // A check-in falls in exactly one week, so each weekly series is
+        // one release however many weeks it spans
+        let mut recorded: BTreeMap<NaiveDate, MoodWeek> =
+            std::mem::take(&mut self.weeks).into_iter().map(|w| (w.week, w)).collect();
+        let mut total_mood = 0.0;
+        let mut week = monday(self.since);
+        while week <= monday(today) {
+            let (checkins, mood, (hours, slept)) = recorded
+                .remove(&week)
+                .map_or((0, 0.0, (0.0, 0)), |w| (w.checkins, w.mood, w.sleep.unwrap_or((0.0, 0))));
+            // Moods run 1-10 and sleep 0-24 hours, so one check-in moves a
+            // sum by at most 9 or 24
+            let count = noise.count(checkins, 1.0);
+            let mood_sum = noise.sum(checkins as f64 * (mood - 1.0), 9.0);
+            let slept = (noise.count(slept, 1.0), noise.sum(slept as f64 * hours, 24.0));
+            total_mood += mood_sum;
+            self.weeks.push(MoodWeek {
+                week,
+                checkins: count,
+                mood: noise::mean(mood_sum, count, 1.0, 10.0).unwrap_or_default(),
+                sleep: noise::mean(slept.1, slept.0, 0.0, 24.0).map(|hours| (hours, slept.0)),
+            });
+            week += chrono::Duration::days(7);
+        }
+        self.checkin_count = self.weeks.iter().map(|w| w.checkins).sum();
+        self.average_mood = noise::mean(total_mood, self.checkin_count, 1.0, 10.0);
+
+        self.session_count = noise.count(self.session_count, 1.0);
+        self.message_count = noise.count(self.message_count, 1.0);
+        self.crisis_events = noise.count(self.crisis_events, 1.0);
+
+        // A session adds to at most `MAX_THEMES` concerns; a case note names
+        // one technique
+        let taxonomy = Taxonomy::builtin();
+        let concerns = [Category::Concern, Category::Emotion, Category::Event]
+            .into_iter()
+            .flat_map(|category| taxonomy.labels(category))
+            .collect();
+        self.concerns = noised_over(&self.concerns, concerns, &noise, MAX_THEMES as f64);
+        self.techniques = noised_over(&self.techniques, taxonomy.labels(Category::Technique), &noise, 1.0);
+        self.epsilon = Some(epsilon);
+        Ok(())
+    }
+}
+
+/// Noised counts for every name in `domain`, plus `OTHER` for names
+/// outside it, ranked. Zero counts are kept.
+fn noised_over(
+    counts: &[(String, usize)],
+    domain: BTreeSet<String>,
+    noise: &Noise,
+    sensitivity: f64,
+) -> Vec<(String, usize)> {
+    let mut full: BTreeMap<String, usize> =
+        domain.into_iter().chain([OTHER.to_string()]).map(|name| (name, 0)).collect();
+    for (name, count) in counts {
+        let key = if full.contains_key(name) { name.as_str() } else { OTHER };
+        *full.entry(key.to_string()).or_default() += count;
+    }
+    ranked(full.into_iter().map(|(name, count)| (name, noise.count(count, sensitivity))).collect())
+}
+
+fn monday(date: NaiveDate) -> NaiveDate {
+    date.week(chrono::Weekday::Mon).first_day()
+}
+
+fn ranked(counts: BTreeMap<String, usize>) -> Vec<(String, usize)> {
+    let mut ranked: Vec<_> = counts.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+/// Renders the report as Markdown.
+pub fn render_markdown(data: &ReportData, redaction: Redaction, today: NaiveDate) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Chiron Progress Report\n");
+    let _ = writeln!(
+        out,
+        "Period: {} to {} · Redaction: {redaction:?}\n",
+        data.since, today
+    );
+    let _ = writeln!(
+        out,
+        "> Generated from a self-help peer-support tool. Themes and techniques are \
+         inferred by a language model and are not a clinical assessment.\n"
+    );
+
+    if let Some(epsilon) = data.epsilon {
+        let _ = writeln!(
+            out,
+            "> Every number below has Laplace noise added for differential privacy (ε = {epsilon} in \
+             total, split across {NOISED_RELEASES} statistics), so counts and averages are approximate. \
+             Every week and every taxonomy tag is listed, including those with nothing recorded.\n"
+        );
+    }
+
+    // Mood
+    let _ = writeln!(out, "## Mood Check-ins\n");
+    match data.average_mood {
+        None => {
+            let _ = writeln!(out, "No check-ins in this period.\n");
+        }
+        Some(average) => {
+            let _ = writeln!(
+                out,
+                "{} check-ins, average mood {average:.1}/10.\n",
+                data.checkin_count
+            );
+            let _ = writeln!(out, "| Week of | Check-ins | Avg mood | Avg sleep (h) |");
+            let _ = writeln!(out, "|---------|-----------|----------|---------------|");
+            for week in &data.weeks {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} |",
+                    week.week,
+                    week.checkins,
+                    if week.checkins == 0 { "–".to_string() } else { format!("{:.1}", week.mood) },
+                    week.sleep.map_or("–".to_string(), |(hours, _)| format!("{hours:.1}"))
+                );
+            }
+            let _ = writeln!(out);
+        }
+    }
+
+    // Noised tag counts are aggregate enough to share
+    if redaction != Redaction::Strict || data.epsilon.is_some() {
+        let _ = writeln!(out, "## Primary Concerns\n");
+        write_ranked(&mut out, &data.concerns, "sessions");
+        let _ = writeln!(out, "## Techniques Practiced\n");
+        write_ranked(&mut out, &data.techniques, "turns");
+    }
+
+    let _ = writeln!(out, "## Safety\n");
+    let _ = writeln!(
+        out,
+        "Messages that triggered the crisis-keyword safety response: {}\n",
+        data.crisis_events
+    );
+
+    let _ = writeln!(out, "## Sessions\n");
+    if data.session_count == 0 {
+        let _ = writeln!(out, "No sessions in this period.\n");
+    } else if redaction == Redaction::Strict {
+        let _ = writeln!(
+            out,
+            "{} sessions, {} messages.\n",
+            data.session_count, data.message_count
+        );
+    } else {
+        let _ = writeln!(out, "| Date | Messages | MI stage | Themes |");
+        let _ = writeln!(out, "|------|----------|----------|--------|");
+        for s in &data.sessions {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                s.date,
+                s.messages,
+                s.mi_stage.as_deref().unwrap_or("–"),
+                if s.themes.is_empty() { "–".to_string() } else { s.themes.join(", ") }
+            );
+        }
+        let _ = writeln!(out);
+    }
+
+    if redaction == Redaction::None {
+        let notes: Vec<_> = data
+            .checkins
+            .iter()
+            .filter(|c| c.highlight.is_some() || c.stressor.is_some())
+            .collect();
+        if !notes.is_empty() {
+            let _ = writeln!(out, "## Check-in Notes\n");
+            for c in notes {
+                let _ = writeln!(
+                    out,
+                    "- {}: highlight: {}; stressor: {}",
+                    timezone::day(&c.created_at),
+                    c.highlight.as_deref().unwrap_or("–"),
+                    c.stressor.as_deref().unwrap_or("–")
+                );
+            }
+            let _ = writeln!(out);
+        }
+    }
+
+    out
+}
+
+fn write_ranked(out: &mut String, items: &[(String, usize)], unit: &str) {
+    if items.is_empty() {
+        let _ = writeln!(out, "None recorded.\n");
+        return;
+    }
+    for (name, count) in items {
+        let _ = writeln!(out, "- {name} ({count} {unit})");
+    }
+    let _ = writeln!(out);
+}
+
+/// Groups check-ins by the Monday of their week.
+fn by_week(checkins: &[CheckIn]) -> Vec<(NaiveDate, Vec<CheckIn>)> {
+    let mut weeks: BTreeMap<NaiveDate, Vec<CheckIn>> = BTreeMap::new();
+    for c in checkins {
+        let Some(date) = timezone::date(&c.created_at) else {
+            continue;
+        };
+        weeks.entry(monday(date)).or_default().push(c.clone());
+    }
+    weeks.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn checkin(mood: i32, date: &str, highlight: Option<&str>) -> CheckIn {
+        CheckIn {
+            id: 0,
+            mood,
+            sleep_hours: Some(7.0),
+            highlight: highlight.map(str::to_string),
+            stressor: None,
+            created_at: format!("{date} 09:00:00"),
+        }
+    }
+
+    fn turn(role: &str, content: &str) -> ChatTurn {
+        ChatTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: "2026-03-02 10:00:00".to_string(),
+            source: "text".to_string(),
+            prompt_variant: None,
+            interrupted: false,
+            emotions: None,
+            provenance: None,
+        }
+    }
+
+    fn sample() -> ReportData {
+        let turns = vec![
+            ("s1".to_string(), turn("user", "work is overwhelming")),
+            ("s1".to_string(), turn("assistant", "That sounds like a lot.")),
+            ("s1".to_string(), turn("user", "sometimes I want to die")),
+            ("s1".to_string(), turn("assistant", "I hear you...")),
+        ];
+        let notes = vec![CaseNote {
+            session_id: "s1".to_string(),
+            mi_stage: Some("engage".to_string()),
+            content: "MI Stage: focus\nStrategy Used: reflection\nRunning Themes: work stress, sleep"
+                .to_string(),
+            created_at: "2026-03-02 10:00:00".to_string(),
+        }];
+        let checkins = vec![
+            checkin(4, "2026-03-02", Some("called my sister")),
+            checkin(6, "2026-03-04", None),
+        ];
+        build(day("2026-03-01"), checkins, &turns, &[], &notes)
+    }
+
+    #[test]
+    fn test_build_rollups() {
+        let data = sample();
+        assert_eq!(data.sessions.len(), 1);
+        assert_eq!(data.sessions[0].messages, 4);
+        assert_eq!(data.sessions[0].mi_stage.as_deref(), Some("focus"));
+        assert_eq!(data.concerns.len(), 2);
+        assert!(data.concerns.contains(&("work".to_string(), 1)));
+        assert_eq!(data.techniques, vec![("reflection".to_string(), 1)]);
+        assert_eq!(data.crisis_events, 1);
+    }
+
+    #[test]
+    fn test_archived_sessions_keep_their_rollup() {
+        let archives = vec![SessionArchive {
+            session_id: "s0".to_string(),
+            summary: "Discussed sleep and work stress.".to_string(),
+            message_count: 12,
+            started_at: "2026-03-01 09:00:00".to_string(),
+            last_active_at: "2026-03-01 09:40:00".to_string(),
+            progress: archive::SessionProgress {
+                mi_stage_end: Some("evoke".to_string()),
+                crisis_messages: 1,
+                ..Default::default()
+            },
+        }];
+        let notes = vec![CaseNote {
+            session_id: "s0".to_string(),
+            mi_stage: Some("evoke".to_string()),
+            content: "MI Stage: evoke\nRunning Themes: sleep".to_string(),
+            created_at: "2026-03-01 09:40:00".to_string(),
+        }];
+        let data = build(day("2026-03-01"), Vec::new(), &[], &archives, &notes);
+        assert_eq!(data.sessions.len(), 1);
+        assert_eq!(data.sessions[0].messages, 12);
+        assert_eq!(data.sessions[0].mi_stage.as_deref(), Some("evoke"));
+        assert_eq!(data.concerns, vec![("sleep".to_string(), 1)]);
+        assert_eq!(data.crisis_events, 1);
+
+        // The summary is for the person's records, not the report
+        let full = render_markdown(&data, Redaction::None, day("2026-03-05"));
+        assert!(!full.contains("Discussed sleep"));
+    }
+
+    #[test]
+    fn test_redaction_levels() {
+        let data = sample();
+        let today = day("2026-03-05");
+
+        let full = render_markdown(&data, Redaction::None, today);
+        assert!(full.contains("called my sister"));
+        assert!(full.contains("work stress"));
+
+        let standard = render_markdown(&data, Redaction::Standard, today);
+        assert!(!standard.contains("called my sister"));
+        assert!(standard.contains("reflection"));
+
+        let strict = render_markdown(&data, Redaction::Strict, today);
+        assert!(!strict.contains("work stress"));
+        assert!(!strict.contains("reflection"));
+        assert!(strict.contains("average mood 5.0/10"));
+        assert!(strict.contains("crisis-keyword safety response: 1"));
+        // Raw message text never appears at any level
+        assert!(!full.contains("want to die"));
+    }
+
+    #[test]
+    fn test_noised_report() {
+        let today = day("2026-03-12");
+        let mut data = sample();
+        data.add_noise(1e9, today).unwrap();
+        let strict = render_markdown(&data, Redaction::Strict, today);
+        assert!(strict.contains("ε = 1000000000 in total, split across 9 statistics"), "{strict}");
+        assert!(strict.contains("average mood 5.0/10"), "{strict}");
+        assert!(strict.contains("1 sessions, 4 messages"), "{strict}");
+        assert!(!strict.contains("called my sister"));
+        // Every week in the period is listed, empty or not
+        assert!(strict.contains("| 2026-02-23 | 0 | – | – |"), "{strict}");
+        assert!(strict.contains("| 2026-03-02 | 2 | 5.0 | 7.0 |"), "{strict}");
+        assert!(strict.contains("| 2026-03-09 | 0 | – | – |"), "{strict}");
+        // So is every tag in the taxonomy, seen or not
+        assert!(strict.contains("work (1 sessions)"), "{strict}");
+        assert!(strict.contains("grief (0 sessions)"), "{strict}");
+        assert!(strict.contains("validation (0 turns)"), "{strict}");
+        assert!(strict.contains("other (0 turns)"), "{strict}");
+
+        let mut noisy = sample();
+        noisy.add_noise(0.01, today).unwrap();
+        assert_eq!(noisy.weeks.len(), 3);
+        assert!(noisy.average_mood.is_none_or(|m| (1.0..=10.0).contains(&m)));
+        assert_eq!(noisy.techniques.len(), Taxonomy::builtin().labels(Category::Technique).len() + 1);
+
+        assert!(sample().add_noise(0.0, today).is_err());
+    }
+}
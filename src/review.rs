@@ -0,0 +1,184 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use tokio_rusqlite::Connection;
+
+use crate::checkin::ask;
+use crate::memory::annotations::{self, Annotation, ReviewItem, Verdict};
+use crate::orchestrator::events::SafetyKind;
+use crate::replay::{self, RecordedTurn};
+
+const HELP: &str = "a appropriate · i inappropriate · r toggle missed risk · b <text> better alternative · \
+n <text> note · c clear · Enter next · p previous · q quit";
+
+/// One reviewer command at the review prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Verdict(Verdict),
+    ToggleMissedRisk,
+    Alternative(String),
+    Note(String),
+    Clear,
+    Next,
+    Previous,
+    Quit,
+}
+
+fn parse_command(line: &str) -> Option<Command> {
+    let (word, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+    let rest = rest.trim();
+    match word {
+        "" => Some(Command::Next),
+        "a" => Some(Command::Verdict(Verdict::Appropriate)),
+        "i" => Some(Command::Verdict(Verdict::Inappropriate)),
+        "r" => Some(Command::ToggleMissedRisk),
+        "b" if !rest.is_empty() => Some(Command::Alternative(rest.to_string())),
+        "n" if !rest.is_empty() => Some(Command::Note(rest.to_string())),
+        "c" => Some(Command::Clear),
+        "p" => Some(Command::Previous),
+        "q" => Some(Command::Quit),
+        _ => None,
+    }
+}
+
+/// Applies an editing command; returns `false` for navigation commands.
+fn apply(annotation: &mut Annotation, command: &Command) -> bool {
+    match command {
+        Command::Verdict(verdict) => annotation.verdict = Some(*verdict),
+        Command::ToggleMissedRisk => annotation.missed_risk = !annotation.missed_risk,
+        Command::Alternative(text) => annotation.alternative = Some(text.clone()),
+        Command::Note(text) => annotation.note = Some(text.clone()),
+        Command::Clear => *annotation = Annotation::default(),
+        Command::Next | Command::Previous | Command::Quit => return false,
+    }
+    true
+}
+
+/// Steps through the session's coach replies on stdin, showing the message
+/// each answers, and saves the reviewer's annotations as they're made.
+pub async fn run(conn: &Connection, session_id: &str) -> Result<()> {
+    let mut items = annotations::review_items(conn, session_id).await?;
+    anyhow::ensure!(!items.is_empty(), "Session {session_id} has no replies to review");
+    println!("Reviewing {} replies in {session_id}.\n{HELP}", items.len());
+
+    let mut i = 0;
+    while i < items.len() {
+        print!("\n{}", render_item(&items[i], i, items.len()));
+        loop {
+            let Some(command) = parse_command(&ask(">")?) else {
+                println!("{HELP}");
+                continue;
+            };
+            let item = &mut items[i];
+            if apply(&mut item.annotation, &command) {
+                annotations::annotate(conn, session_id, item.turn_id, &item.annotation).await?;
+                println!("  {}", describe(&item.annotation));
+                continue;
+            }
+            match command {
+                Command::Previous => i = i.saturating_sub(1),
+                Command::Quit => i = items.len(),
+                _ => i += 1,
+            }
+            break;
+        }
+    }
+
+    let reviewed = items.iter().filter(|item| !item.annotation.is_empty()).count();
+    println!("\n{reviewed} of {} replies annotated.", items.len());
+    Ok(())
+}
+
+fn render_item(item: &ReviewItem, index: usize, total: usize) -> String {
+    let variant = item.prompt_variant.as_deref().unwrap_or("crisis response");
+    let mut out = format!("Reply {}/{total} [{variant}]\n", index + 1);
+    let _ = writeln!(out, "Client: {}", item.input);
+    let _ = writeln!(out, "Chiron: {}", item.response);
+    if !item.annotation.is_empty() {
+        let _ = writeln!(out, "  {}", describe(&item.annotation));
+    }
+    out
+}
+
+/// One-line summary of an annotation.
+fn describe(annotation: &Annotation) -> String {
+    if annotation.is_empty() {
+        return "(not reviewed)".to_string();
+    }
+    let mut parts = Vec::new();
+    if let Some(verdict) = annotation.verdict {
+        parts.push(verdict.as_str().to_string());
+    }
+    if annotation.missed_risk {
+        parts.push("missed risk".to_string());
+    }
+    if let Some(alternative) = &annotation.alternative {
+        parts.push(format!("better: \"{alternative}\""));
+    }
+    if let Some(note) = &annotation.note {
+        parts.push(format!("note: {note}"));
+    }
+    format!("Review: {}", parts.join("; "))
+}
+
+/// Review totals per prompt variant, for `chiron review` with no session.
+pub async fn summary(conn: &Connection) -> Result<String> {
+    let rows = annotations::review_summary(conn).await?;
+    if rows.is_empty() {
+        return Ok("No replies reviewed yet.\n".to_string());
+    }
+    let mut out = String::new();
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "{:<24} {:>6} reviewed  {:>4} appropriate  {:>4} inappropriate  {:>4} missed risk",
+            row.prompt_variant.as_deref().unwrap_or("(crisis response)"),
+            row.reviewed,
+            row.appropriate,
+            row.inappropriate,
+            row.missed_risk
+        );
+    }
+    Ok(out)
+}
+
+/// Writes the messages whose reply missed risk as a `chiron replay`
+/// transcript whose baseline is a crisis response, so replaying it reports
+/// a safety regression for each one the crisis check still misses.
+pub async fn export_missed_risk(conn: &Connection, path: &Path) -> Result<usize> {
+    let turns: Vec<RecordedTurn> = annotations::missed_risk_inputs(conn)
+        .await?
+        .into_iter()
+        .map(|input| RecordedTurn {
+            input,
+            safety: vec![SafetyKind::Crisis],
+            agent: "crisis".to_string(),
+            ..Default::default()
+        })
+        .collect();
+    replay::save(path, &turns)?;
+    Ok(turns.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_edit_annotation() {
+        let mut annotation = Annotation::default();
+        for line in ["i", "r", "b   How long has this been going on? ", "n leading"] {
+            assert!(apply(&mut annotation, &parse_command(line).unwrap()));
+        }
+        assert_eq!(
+            describe(&annotation),
+            "Review: inappropriate; missed risk; better: \"How long has this been going on?\"; note: leading"
+        );
+        assert_eq!(parse_command(""), Some(Command::Next));
+        assert_eq!(parse_command("b"), None);
+        assert!(!apply(&mut annotation, &Command::Quit));
+        assert!(apply(&mut annotation, &Command::Clear));
+        assert!(annotation.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::BTreeSet;
+
+use chrono::{Duration, NaiveDateTime};
+use tokio_rusqlite::Connection;
+
+use crate::error::Result;
+use crate::memory::risk_history::{self, RiskEvents};
+use crate::router;
+use crate::timezone;
+
+/// Earlier crises, carried into new sessions so the coach keeps a closer
+/// watch (see `memory.risk_history_days`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskHistory {
+    /// Messages that got the crisis response.
+    pub crisis_messages: usize,
+    pub crisis_sessions: usize,
+    /// Date (`YYYY-MM-DD`) of the latest crisis message or missed risk.
+    pub last_crisis: Option<String>,
+    /// Replies a reviewer marked as missing risk.
+    pub missed_risk: usize,
+    /// Early warning signs flagged, most recent last, without repeats.
+    pub warning_signs: Vec<String>,
+}
+
+/// Crisis history from sessions other than `session_id` in the last `days`
+/// days, or `None` if there was no crisis (or `days` is 0).
+pub async fn load(conn: &Connection, session_id: &str, days: u32, now: NaiveDateTime) -> Result<Option<RiskHistory>> {
+    if days == 0 {
+        return Ok(None);
+    }
+    let since = (now - Duration::days(days.into())).format("%Y-%m-%d %H:%M:%S").to_string();
+    let events = risk_history::risk_events(conn, &since, session_id).await?;
+    Ok(summarize(&events))
+}
+
+/// Scores stored messages with the current crisis check, so keywords added
+/// since count too.
+fn summarize(events: &RiskEvents) -> Option<RiskHistory> {
+    let mut sessions = BTreeSet::new();
+    let mut dates = Vec::new();
+    let mut crisis_messages = 0;
+    for (session_id, created_at, content) in &events.messages {
+        if router::is_crisis(content) {
+            crisis_messages += 1;
+            sessions.insert(session_id.as_str());
+            dates.push(created_at.as_str());
+        }
+    }
+    for (session_id, last_active_at, count) in &events.archived {
+        crisis_messages += *count as usize;
+        sessions.insert(session_id.as_str());
+        dates.push(last_active_at.as_str());
+    }
+    dates.extend(events.missed_risk.iter().map(String::as_str));
+    if crisis_messages == 0 && events.missed_risk.is_empty() {
+        return None;
+    }
+
+    let mut warning_signs: Vec<String> = Vec::new();
+    for (sign, _) in events.warning_flags.iter().rev() {
+        if !warning_signs.contains(sign) {
+            warning_signs.insert(0, sign.clone());
+        }
+    }
+    Some(RiskHistory {
+        crisis_messages,
+        crisis_sessions: sessions.len(),
+        last_crisis: dates.into_iter().max().map(timezone::day),
+        missed_risk: events.missed_risk.len(),
+        warning_signs,
+    })
+}
+
+impl RiskHistory {
+    /// The preamble section that asks the coach to keep a closer watch.
+    pub fn to_prompt(&self, days: u32) -> String {
+        let mut facts = Vec::new();
+        if self.crisis_messages > 0 {
+            facts.push(format!(
+                "{} message(s) in {} earlier session(s) got the crisis response",
+                self.crisis_messages, self.crisis_sessions
+            ));
+        }
+        if self.missed_risk > 0 {
+            facts.push(format!("a reviewer found risk that was missed {} time(s)", self.missed_risk));
+        }
+        let mut out = format!("In the last {days} days, {}", facts.join(", and "));
+        if let Some(date) = &self.last_crisis {
+            out.push_str(&format!(", most recently on {date}"));
+        }
+        out.push('.');
+        if !self.warning_signs.is_empty() {
+            out.push_str(&format!(" Early warning signs flagged since: {}.", self.warning_signs.join(", ")));
+        }
+        out.push_str(
+            "\nKeep a closer watch this session. Early on, and whenever the conversation turns heavy, \
+             check in gently about how they're coping and whether they're safe. If they mention wanting \
+             to die or hurt themselves, ask directly and point them to crisis support. Don't open with \
+             their history or assume they're in crisis now.",
+        );
+        out
+    }
+}
+
+/// The preamble section for a turn where someone with crisis history uses
+/// a softer risk phrase (see `router::elevated_risk`).
+pub fn check_in_prompt(phrase: &str) -> String {
+    format!(
+        "They just said \"{phrase}\", and they've been in crisis recently. Acknowledge what they said, \
+         then ask directly and kindly whether they're having thoughts of suicide or of hurting themselves, \
+         and remind them crisis support is there any time."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(session: &str, at: &str, content: &str) -> (String, String, String) {
+        (session.to_string(), at.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_summarize_counts_crises_only() {
+        let mut events = RiskEvents {
+            messages: vec![
+                message("a", "2026-03-01 10:00:00", "work is a lot"),
+                message("a", "2026-03-01 10:05:00", "I want to die"),
+                message("b", "2026-03-09 22:00:00", "thinking about suicide"),
+            ],
+            warning_flags: vec![
+                ("not sleeping".to_string(), "2026-03-02 09:00:00".to_string()),
+                ("skipping meals".to_string(), "2026-03-03 09:00:00".to_string()),
+                ("not sleeping".to_string(), "2026-03-04 09:00:00".to_string()),
+            ],
+            ..Default::default()
+        };
+        let history = summarize(&events).unwrap();
+        assert_eq!((history.crisis_messages, history.crisis_sessions), (2, 2));
+        assert_eq!(history.last_crisis.as_deref(), Some("2026-03-09"));
+        assert_eq!(history.warning_signs, vec!["skipping meals", "not sleeping"]);
+        assert!(history.to_prompt(90).starts_with(
+            "In the last 90 days, 2 message(s) in 2 earlier session(s) got the crisis response, most recently on 2026-03-09."
+        ));
+
+        events.messages.truncate(1);
+        assert_eq!(summarize(&events), None);
+        events.archived.push(("old".to_string(), "2026-01-10 08:00:00".to_string(), 3));
+        assert_eq!(summarize(&events).unwrap().crisis_messages, 3);
+    }
+}
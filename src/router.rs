@@ -1,5 +1,13 @@
+use crate::language::{self, PatternSet};
+use crate::{guardrails, i18n};
+
 /// Checks if user input contains crisis indicators.
 ///
+/// English keywords are always checked, along with every language's in
+/// `locales/crisis.toml` (whatever language the message seems to be in, so
+/// a code-switched one is still caught), and any from `guardrails.toml`
+/// (see `guardrails`).
+///
 /// Simple keyword matching — the fine-tuned model handles nuanced crisis
 /// detection in its think block, but this catches obvious cases for
 /// immediate hardcoded response before model inference.
@@ -16,13 +24,54 @@ pub fn is_crisis(input: &str) -> bool {
     ];
     let lower = input.to_lowercase();
     PATTERNS.iter().any(|p| lower.contains(p))
+        || language::all().any(|set| set.patterns.iter().any(|p| lower.contains(p.as_str())))
+        || guardrails::current().matches_crisis(&lower)
 }
 
-/// Returns a hardcoded crisis response with resource information.
-pub fn crisis_response() -> &'static str {
-    "I hear you, and I'm really glad you told me. What you're feeling matters. \
-     Please reach out to the 988 Suicide & Crisis Lifeline (call or text 988) \
-     or text HOME to 741741 for the Crisis Text Line. You don't have to go through this alone."
+/// The softer risk phrase in `input`, if any: hopelessness, feeling like a
+/// burden, wanting to disappear. Not a crisis on its own, but worth a
+/// direct check-in from someone with recent crisis history.
+///
+/// English phrases are always checked, along with those for the interface
+/// and response languages and the message's own (or all of them, if it
+/// can't be told).
+pub fn elevated_risk(input: &str) -> Option<String> {
+    const PATTERNS: &[&str] = &[
+        "hopeless",
+        "can't go on",
+        "cant go on",
+        "can't take it anymore",
+        "no point in anything",
+        "what's the point",
+        "a burden",
+        "disappear",
+        "give up on everything",
+        "hurt myself",
+        "don't want to be here",
+    ];
+    let lower = input.to_lowercase();
+    PATTERNS
+        .iter()
+        .map(|p| p.to_string())
+        .chain(elevated_sets(&lower).flat_map(|set| set.elevated_patterns.iter().cloned()))
+        .find(|p| lower.contains(p.as_str()))
+}
+
+/// The languages a message's risk phrases are checked in besides English:
+/// the interface and response languages, and its own (or all of them, if it
+/// can't be told).
+fn elevated_sets(lower: &str) -> impl Iterator<Item = &'static PatternSet> {
+    i18n::pattern_sets().chain(language::sets_for(lower))
+}
+
+/// Returns the crisis response with resource information: the
+/// `guardrails.toml` override if set, otherwise the response language's
+/// (the interface language unless `persona.response_language` is set).
+pub fn crisis_response() -> String {
+    match &guardrails::current().crisis_response {
+        Some(response) => response.clone(),
+        None => i18n::response_t("crisis.response").to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -37,6 +86,41 @@ mod tests {
         assert!(is_crisis("WANT TO DIE"));
     }
 
+    #[derive(serde::Deserialize)]
+    struct Corpus {
+        case: Vec<Case>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Case {
+        text: String,
+        language: Option<String>,
+        #[serde(default)]
+        crisis: bool,
+        #[serde(default)]
+        elevated: bool,
+    }
+
+    #[test]
+    fn test_multilingual_corpus() {
+        let corpus: Corpus = toml::from_str(include_str!("../fixtures/crisis/multilingual.toml")).unwrap();
+        for case in &corpus.case {
+            assert_eq!(language::detect(&case.text), case.language.as_deref(), "language of {:?}", case.text);
+            assert_eq!(is_crisis(&case.text), case.crisis, "crisis in {:?}", case.text);
+            if !case.crisis {
+                assert_eq!(elevated_risk(&case.text).is_some(), case.elevated, "risk phrase in {:?}", case.text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_elevated_risk() {
+        assert_eq!(elevated_risk("I feel like such a burden to them").as_deref(), Some("a burden"));
+        assert_eq!(elevated_risk("Honestly it all feels HOPELESS").as_deref(), Some("hopeless"));
+        assert_eq!(elevated_risk("I'm stressed about work"), None);
+        assert!(!is_crisis("I can't go on like this"));
+    }
+
     #[test]
     fn test_non_crisis() {
         assert!(!is_crisis("I've been feeling down lately"));
@@ -0,0 +1,239 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tokio_rusqlite::Connection;
+
+use crate::checkin::ask;
+use crate::graduation;
+use crate::i18n::t;
+use crate::memory::coping;
+use crate::memory::safety_plan::{self, SafetyPlan};
+use crate::memory::warning_signs;
+use crate::router;
+
+/// Wallet card width in characters (fits a folded index card when printed).
+const CARD_WIDTH: usize = 42;
+
+/// Entries per section on the card.
+const CARD_ENTRIES: usize = 3;
+
+/// Crisis lines printed on every card.
+const CRISIS_LINES: &[&str] = &["Call or text 988 (Crisis Lifeline)", "Text HOME to 741741", "Emergency: 911"];
+
+/// Walks through each section of the safety plan on stdin. Current entries
+/// are shown first; Enter on the first line keeps them, otherwise the new
+/// lines (one per entry, blank to finish) replace them.
+pub async fn edit(conn: &Connection) -> Result<()> {
+    let mut plan = safety_plan::load_safety_plan(conn).await?;
+    println!("{}", t("safety_plan.intro"));
+
+    for section in SafetyPlan::SECTIONS {
+        println!("\n{}", t(section_key(section)));
+        for entry in plan.section(section) {
+            println!("  - {entry}");
+        }
+        let mut entries = Vec::new();
+        loop {
+            let line = ask("  +")?;
+            if line.is_empty() {
+                break;
+            }
+            entries.push(line);
+        }
+        if !entries.is_empty() {
+            if let Some(slot) = plan.section_mut(section) {
+                *slot = entries;
+            }
+        }
+    }
+
+    safety_plan::save_safety_plan(conn, &plan).await?;
+    println!("\n{}", t("safety_plan.saved"));
+    Ok(())
+}
+
+/// Builds the wallet card: warning signs, top coping steps, and contacts,
+/// wrapped to `CARD_WIDTH` inside a border. Coping steps fall back to skills
+/// rated helpful when the plan lists none. `None` until a plan is written.
+pub async fn card(conn: &Connection) -> Result<Option<String>> {
+    let plan = safety_plan::load_safety_plan(conn).await?;
+    if plan.is_empty() {
+        return Ok(None);
+    }
+    let signs: Vec<String> = warning_signs::list_signs(conn)
+        .await?
+        .into_iter()
+        .map(|s| s.sign)
+        .collect();
+    let skills = coping::list_skills(conn).await?;
+    let helpful: Vec<String> = coping::helpful_skills(&skills).into_iter().map(str::to_string).collect();
+    Ok(Some(render_card(&plan, &signs, &helpful)))
+}
+
+/// What `/crisis` and `chiron crisis` print: crisis resources in the
+/// response language, then the whole safety plan with their warning signs.
+/// No model is involved, so it works offline or when the model won't load,
+/// and the resources are shown even if the plan can't be read.
+pub async fn break_glass(conn: &Connection) -> String {
+    let resources = router::crisis_response();
+    let plan = match safety_plan::load_safety_plan(conn).await {
+        Ok(plan) if plan.is_empty() => return format!("{resources}\n\n{}", t("safety_plan.empty")),
+        Ok(plan) => plan,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load the safety plan for /crisis");
+            return resources;
+        }
+    };
+    let signs: Vec<String> = match warning_signs::list_signs(conn).await {
+        Ok(signs) => signs.into_iter().map(|s| s.sign).collect(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load warning signs for /crisis");
+            Vec::new()
+        }
+    };
+    format!("{resources}\n\n{}", render_plan(&plan, &signs))
+}
+
+/// The whole plan, every entry of every section, under its title.
+fn render_plan(plan: &SafetyPlan, signs: &[String]) -> String {
+    let mut out = t("safety_plan.card_title").to_string();
+    let sections = std::iter::once((t("safety_plan.card_signs"), signs))
+        .chain(SafetyPlan::SECTIONS.iter().map(|section| (t(section_key(section)), plan.section(section))));
+    for (heading, entries) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n\n{heading}"));
+        for entry in entries {
+            out.push_str(&format!("\n  - {entry}"));
+        }
+    }
+    out
+}
+
+/// Renders the card from its parts (see `card`).
+fn render_card(plan: &SafetyPlan, signs: &[String], helpful: &[String]) -> String {
+    let coping = if plan.coping.is_empty() { helpful } else { plan.coping.as_slice() };
+    let contacts: Vec<String> = plan.contacts.iter().chain(&plan.professionals).cloned().collect();
+
+    let mut lines = vec![center(t("safety_plan.card_title")), String::new()];
+    for (heading, entries) in [
+        (t("safety_plan.card_signs"), signs),
+        (t("safety_plan.card_coping"), coping),
+        (t("safety_plan.card_contacts"), contacts.as_slice()),
+        (t("safety_plan.card_reasons"), plan.reasons.as_slice()),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        lines.push(heading.to_string());
+        for entry in entries.iter().take(CARD_ENTRIES) {
+            lines.extend(wrap(&format!("- {entry}"), CARD_WIDTH - 4));
+        }
+        lines.push(String::new());
+    }
+    lines.push(t("safety_plan.card_crisis").to_string());
+    lines.extend(CRISIS_LINES.iter().map(|l| l.to_string()));
+
+    let border = format!("+{}+", "-".repeat(CARD_WIDTH - 2));
+    let mut out = format!("{border}\n");
+    for line in lines {
+        out.push_str(&format!("| {line:<width$} |\n", width = CARD_WIDTH - 4));
+    }
+    out.push_str(&border);
+    out.push('\n');
+    out
+}
+
+/// Writes the card to `path`. `.pdf` goes through pandoc (as a monospaced
+/// block, so the layout survives); anything else is plain text.
+pub fn export(card: &str, path: &Path) -> Result<()> {
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf")) {
+        return graduation::export(&format!("```\n{card}```\n"), path);
+    }
+    std::fs::write(path, card).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Locale key for a section's heading/prompt.
+fn section_key(section: &str) -> &'static str {
+    match section {
+        "coping" => "safety_plan.section_coping",
+        "places" => "safety_plan.section_places",
+        "contacts" => "safety_plan.section_contacts",
+        "professionals" => "safety_plan.section_professionals",
+        "environment" => "safety_plan.section_environment",
+        _ => "safety_plan.section_reasons",
+    }
+}
+
+/// Centers `text` within the card's inner width.
+fn center(text: &str) -> String {
+    let inner = CARD_WIDTH - 4;
+    let pad = inner.saturating_sub(text.chars().count()) / 2;
+    format!("{}{text}", " ".repeat(pad))
+}
+
+/// Greedy word wrap; continuation lines are indented to sit under the text
+/// after a leading `- `. Words longer than `width` are left whole.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let len = current.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::replace(&mut current, "  ".to_string()));
+        }
+        if !current.is_empty() && !current.ends_with("  ") {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.trim().is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("- call my sister when it gets bad", 16),
+            vec!["- call my sister", "  when it gets", "  bad"]
+        );
+        assert_eq!(wrap("- short", 16), vec!["- short"]);
+    }
+
+    #[test]
+    fn test_card_layout() {
+        let plan = SafetyPlan {
+            contacts: vec!["Sam: 555-0100".into()],
+            professionals: vec!["Dr. Lee: 555-0199".into()],
+            ..Default::default()
+        };
+        let card = render_card(&plan, &["Skipping meals".into()], &["Paced breathing".into()]);
+
+        assert!(card.lines().all(|l| l.chars().count() == CARD_WIDTH), "{card}");
+        assert!(card.contains("- Skipping meals"));
+        assert!(card.contains("- Paced breathing"), "falls back to helpful skills");
+        assert!(card.contains("- Dr. Lee: 555-0199"));
+        assert!(card.contains("988"));
+    }
+
+    #[test]
+    fn test_full_plan() {
+        let plan = SafetyPlan {
+            coping: (1..=5).map(|i| format!("Step {i}")).collect(),
+            reasons: vec!["My dog".into()],
+            ..Default::default()
+        };
+        let text = render_plan(&plan, &[]);
+        assert!(text.starts_with("MY SAFETY PLAN"), "{text}");
+        assert!(text.contains("  - Step 5"), "every entry, not just the card's first three");
+        assert!(text.contains("My reasons for living:\n  - My dog"), "{text}");
+        assert!(!text.contains("When I notice:"), "empty sections are left out");
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::Write;
+
+use anyhow::{bail, Context, Result};
+
+/// Service name the OS keyring files Chiron's entries under.
+const SERVICE: &str = "chiron";
+
+/// A credential Chiron can read from the OS keyring (`chiron secrets set`).
+/// The environment variable still wins when set, for CI and one-off runs.
+pub struct Secret {
+    pub name: &'static str,
+    pub env: &'static str,
+    pub description: &'static str,
+}
+
+pub const SECRETS: &[Secret] = &[
+    Secret {
+        name: "telegram-token",
+        env: "TELEGRAM_BOT_TOKEN",
+        description: "Telegram bot token from @BotFather",
+    },
+    Secret {
+        name: "sync-passphrase",
+        env: "CHIRON_SYNC_PASSPHRASE",
+        description: "Passphrase that encrypts synced sessions",
+    },
+    Secret {
+        name: "sync-password",
+        env: "CHIRON_SYNC_PASSWORD",
+        description: "WebDAV password or app token for sync",
+    },
+    Secret {
+        name: "aws-access-key-id",
+        env: "AWS_ACCESS_KEY_ID",
+        description: "S3 access key ID for sync",
+    },
+    Secret {
+        name: "aws-secret-access-key",
+        env: "AWS_SECRET_ACCESS_KEY",
+        description: "S3 secret access key for sync",
+    },
+];
+
+/// Where a secret's value comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Env,
+    Keyring,
+}
+
+fn lookup(name: &str) -> Result<&'static Secret> {
+    match SECRETS.iter().find(|s| s.name == name) {
+        Some(secret) => Ok(secret),
+        None => bail!("Unknown secret '{name}'. Available:\n{}", list_names()),
+    }
+}
+
+fn list_names() -> String {
+    SECRETS.iter().map(|s| format!("  {:<22} {}", s.name, s.description)).collect::<Vec<_>>().join("\n")
+}
+
+fn entry(secret: &Secret) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, secret.name)
+        .with_context(|| format!("Failed to open keyring entry for {}", secret.name))
+}
+
+/// Reads a secret from its environment variable, falling back to the OS
+/// keyring. `None` if it's in neither.
+pub fn get(name: &str) -> Result<Option<(String, Source)>> {
+    let secret = lookup(name)?;
+    if let Some(value) = std::env::var(secret.env).ok().filter(|v| !v.is_empty()) {
+        return Ok(Some((value, Source::Env)));
+    }
+    match entry(secret)?.get_password() {
+        Ok(value) => Ok(Some((value, Source::Keyring))),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {name} from the OS keyring")),
+    }
+}
+
+/// Like `get`, but a missing secret is an error that says how to set it.
+pub fn require(name: &str) -> Result<String> {
+    match get(name)? {
+        Some((value, _)) => Ok(value),
+        None => {
+            let secret = lookup(name)?;
+            bail!(
+                "{} isn't set. Store it with `chiron secrets set {name}` or set {}.",
+                secret.description,
+                secret.env
+            )
+        }
+    }
+}
+
+/// Stores a secret in the OS keyring, replacing any previous value.
+pub fn set(name: &str, value: &str) -> Result<()> {
+    let secret = lookup(name)?;
+    if value.is_empty() {
+        bail!("Refusing to store an empty {name}");
+    }
+    entry(secret)?
+        .set_password(value)
+        .with_context(|| format!("Failed to store {name} in the OS keyring"))
+}
+
+/// Deletes a secret from the OS keyring. Returns false if it wasn't stored.
+pub fn remove(name: &str) -> Result<bool> {
+    let secret = lookup(name)?;
+    match entry(secret)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove {name} from the OS keyring")),
+    }
+}
+
+/// Known secrets and where each is currently set, without their values.
+pub fn status() -> String {
+    let mut out = String::new();
+    for secret in SECRETS {
+        let source = match get(secret.name) {
+            Ok(Some((_, Source::Env))) => format!("set ({})", secret.env),
+            Ok(Some((_, Source::Keyring))) => "set (keyring)".to_string(),
+            Ok(None) => "not set".to_string(),
+            Err(e) => format!("unavailable: {e:#}"),
+        };
+        let _ = writeln!(out, "{:<22} {:<28} {}", secret.name, source, secret.description);
+    }
+    out
+}
+
+/// Reads the value to store: hidden input on a terminal, otherwise the
+/// first line of stdin (`pass show chiron/sync | chiron secrets set ...`).
+pub fn read_value(name: &str) -> Result<String> {
+    use std::io::IsTerminal;
+
+    let value = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("{name}: ")).context("Failed to read secret")?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line).context("Failed to read secret from stdin")?;
+        line
+    };
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_known_and_unknown_names() {
+        assert_eq!(lookup("sync-passphrase").unwrap().env, "CHIRON_SYNC_PASSPHRASE");
+        let err = lookup("openai-key").unwrap_err().to_string();
+        assert!(err.contains("Unknown secret 'openai-key'"), "{err}");
+        assert!(err.contains("telegram-token"), "{err}");
+
+        let mut names: Vec<_> = SECRETS.iter().map(|s| s.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), SECRETS.len());
+    }
+}
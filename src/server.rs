@@ -0,0 +1,465 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::{Stream, StreamExt};
+use serde::Deserialize;
+
+use crate::assessment::scoring::InstrumentCatalog;
+use crate::engine::{ChatEvent, ChironEngine};
+use crate::error::ChironError;
+use crate::i18n::t;
+use crate::memory;
+use crate::memory::feedback::Feedback;
+use crate::orchestrator::TurnResult;
+use crate::router::crisis_response;
+
+/// Single-page web UI served at `/` when `chiron serve --ui` is used.
+const INDEX_HTML: &str = include_str!("../static/index.html");
+
+/// JSON error response: `{"error": "..."}` with an HTTP status.
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.status)
+    }
+}
+
+impl From<ChironError> for ApiError {
+    fn from(e: ChironError) -> Self {
+        // A busy model may answer on retry; failed generations and storage faults won't
+        let status = match &e {
+            ChironError::SessionNotFound(_) => StatusCode::NOT_FOUND,
+            e if e.is_transient() => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        if status.is_server_error() {
+            tracing::error!(error = %e, "Request failed");
+        }
+        Self {
+            status,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = Json(serde_json::json!({ "error": self.message }));
+        (self.status, body).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct PostMessage {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct PostAssessment {
+    responses: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+struct MoodQuery {
+    #[serde(default = "default_mood_days")]
+    days: u32,
+}
+
+fn default_mood_days() -> u32 {
+    30
+}
+
+#[derive(Deserialize)]
+struct PostFeedback {
+    feedback: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Builds the API router.
+///
+/// - `POST /sessions` — start a session
+/// - `GET  /sessions` — list stored sessions
+/// - `GET  /sessions/{id}/messages` — session transcript
+/// - `POST /sessions/{id}/messages` — send a message; response streamed as SSE
+/// - `GET  /sessions/{id}/ws` — WebSocket chat with streamed JSON events
+/// - `POST /sessions/{id}/feedback` — rate the latest reply (👍/👎)
+/// - `POST /sessions/{id}/end` — end a session and return its summary
+/// - `GET  /assessments` — the questionnaires that can be scored
+/// - `POST /assessments/{id}` — score a completed questionnaire
+/// - `GET  /mood` — check-ins and session ratings over the last `?days=`
+///
+/// With `ui` set, the embedded web UI is also served at `/`. Every route
+/// goes through `check_origin`, with `port` the one the server listens on.
+pub fn router(state: Arc<ChironEngine>, ui: bool, port: u16) -> Router {
+    let router = if ui {
+        Router::new().route("/", get(|| async { Html(INDEX_HTML) }))
+    } else {
+        Router::new()
+    };
+    router
+        .route("/sessions", post(create_session).get(list_sessions))
+        .route(
+            "/sessions/{id}/messages",
+            get(get_messages).post(post_message),
+        )
+        .route("/sessions/{id}/ws", get(ws_session))
+        .route("/sessions/{id}/feedback", post(post_feedback))
+        .route("/sessions/{id}/end", post(end_session))
+        .route("/assessments", get(list_assessments))
+        .route("/assessments/{id}", post(score_assessment))
+        .route("/mood", get(get_mood))
+        .with_state(state)
+        .layer(middleware::from_fn_with_state(port, check_origin))
+}
+
+/// Refuses requests a web page could make on someone's behalf: a `Host`
+/// that isn't this server's IP address or `localhost` (a DNS rebinding
+/// page), or an `Origin` other than the server's own (a cross-site request
+/// or WebSocket, which browsers send without asking). Clients outside a
+/// browser send no `Origin` and pass.
+async fn check_origin(State(port): State<u16>, request: Request, next: Next) -> Response {
+    if allowed_request(request.headers(), port) {
+        return next.run(request).await;
+    }
+    tracing::warn!("Refused a request from another site");
+    ApiError {
+        status: StatusCode::FORBIDDEN,
+        message: "Requests must come from this server's own address".to_string(),
+    }
+    .into_response()
+}
+
+fn allowed_request(headers: &HeaderMap, port: u16) -> bool {
+    let Some(host) = headers.get(header::HOST).and_then(|h| h.to_str().ok()) else {
+        return false;
+    };
+    if !direct_host(host, port) {
+        return false;
+    }
+    match headers.get(header::ORIGIN) {
+        None => true,
+        Some(origin) => origin.to_str().is_ok_and(|origin| {
+            origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://")) == Some(host)
+        }),
+    }
+}
+
+/// Whether a `Host` header names this server directly, by IP address or
+/// `localhost`, on the port it listens on. A DNS name could point anywhere.
+fn direct_host(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, host_port)) if !host_port.ends_with(']') => (name, host_port.parse().ok()),
+        _ => (host, Some(80)),
+    };
+    let name = name.strip_prefix('[').and_then(|n| n.strip_suffix(']')).unwrap_or(name);
+    host_port == Some(port) && (name.eq_ignore_ascii_case("localhost") || name.parse::<IpAddr>().is_ok())
+}
+
+/// Runs the HTTP server until the process is stopped.
+pub async fn serve(state: Arc<ChironEngine>, addr: SocketAddr, ui: bool) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    tracing::info!(%addr, "Server listening");
+    axum::serve(listener, router(state, ui, addr.port()))
+        .await
+        .context("Server error")
+}
+
+async fn create_session(State(state): State<Arc<ChironEngine>>) -> impl IntoResponse {
+    let session_id = state.start_session("session").await;
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "session_id": session_id })),
+    )
+}
+
+async fn list_sessions(
+    State(state): State<Arc<ChironEngine>>,
+) -> Result<Json<Vec<memory::SessionInfo>>, ApiError> {
+    Ok(Json(memory::list_sessions(&state.chat_conn).await?))
+}
+
+async fn get_messages(
+    State(state): State<Arc<ChironEngine>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<memory::ChatTurn>>, ApiError> {
+    let turns = memory::load_session_turns(&state.chat_conn, &session_id).await?;
+    if turns.is_empty() && !state.is_active(&session_id).await {
+        return Err(ChironError::SessionNotFound(session_id).into());
+    }
+    Ok(Json(turns))
+}
+
+/// Runs one turn through the full orchestrator pipeline (crisis check,
+/// RAG, case notes) and streams it back as server-sent events:
+///
+/// - `safety` — the message matched crisis keywords (sent before any tokens)
+/// - `token` — a chunk of visible response text
+/// - `done` — JSON with `turn_number`, `response`, and `duration_ms`
+/// - `reply` — a chat command's output (`/goal`, `/end`, ...) or the
+///   scripted reply while the model is unavailable; data is the text
+/// - `error` — the turn failed; data is the error message
+async fn post_message(
+    State(state): State<Arc<ChironEngine>>,
+    Path(session_id): Path<String>,
+    Json(body): Json<PostMessage>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let content = body.content.trim().to_string();
+    if content.is_empty() {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "Message content is empty".to_string(),
+        });
+    }
+
+    let events = state.send_message(&session_id, content).await?.map(|event| {
+        Ok(match event {
+            ChatEvent::Safety => Event::default().event("safety").data("crisis"),
+            ChatEvent::Token(text) => Event::default().event("token").data(text),
+            ChatEvent::Done(result) => Event::default().event("done").data(done_json(&result).to_string()),
+            ChatEvent::Reply(text) => Event::default().event("reply").data(text),
+            ChatEvent::Error(message) => Event::default().event("error").data(message),
+        })
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// The `done` payload shared by SSE and WebSocket clients.
+fn done_json(result: &TurnResult) -> serde_json::Value {
+    serde_json::json!({
+        "turn_number": result.turn_number,
+        "response": result.response,
+        "duration_ms": result.duration_ms,
+    })
+}
+
+async fn ws_session(
+    State(state): State<Arc<ChironEngine>>,
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    state.session(&session_id).await?;
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, session_id)))
+}
+
+/// WebSocket chat loop. Each text frame is a `{"content": "..."}` message;
+/// the turn is streamed back as JSON frames tagged by `type`:
+///
+/// - `safety` — the message matched crisis keywords (sent before any tokens)
+/// - `token` — a chunk of visible response text
+/// - `done` — `turn_number`, `response`, and `duration_ms`
+/// - `reply` — `text` answering a chat command, or sent while the model is
+///   unavailable
+/// - `error` — the turn failed or the frame was malformed
+async fn handle_socket(mut socket: WebSocket, state: Arc<ChironEngine>, session_id: String) {
+    while let Some(Ok(frame)) = socket.recv().await {
+        let text = match frame {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let content = match serde_json::from_str::<PostMessage>(&text) {
+            Ok(msg) if !msg.content.trim().is_empty() => msg.content.trim().to_string(),
+            _ => {
+                let error = serde_json::json!({
+                    "type": "error",
+                    "message": "Expected {\"content\": \"...\"}",
+                });
+                if send_json(&mut socket, error).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let mut events = match state.send_message(&session_id, content).await {
+            Ok(events) => events,
+            Err(e) => {
+                let error = serde_json::json!({ "type": "error", "message": e.to_string() });
+                if send_json(&mut socket, error).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        // A dropped client just stops the frames; the turn still finishes and is saved.
+        let mut connected = true;
+        while let Some(event) = events.next().await {
+            let frame = match event {
+                ChatEvent::Safety => serde_json::json!({ "type": "safety", "event": "crisis" }),
+                ChatEvent::Token(text) => serde_json::json!({ "type": "token", "text": text }),
+                ChatEvent::Done(result) => {
+                    let mut done = done_json(&result);
+                    done["type"] = "done".into();
+                    done
+                }
+                ChatEvent::Reply(text) => serde_json::json!({ "type": "reply", "text": text }),
+                ChatEvent::Error(message) => serde_json::json!({ "type": "error", "message": message }),
+            };
+            if send_json(&mut socket, frame).await.is_err() {
+                connected = false;
+                break;
+            }
+        }
+        if !connected {
+            break;
+        }
+    }
+}
+
+async fn send_json(socket: &mut WebSocket, value: serde_json::Value) -> Result<(), axum::Error> {
+    socket.send(Message::Text(value.to_string().into())).await
+}
+
+/// Rates the session's latest reply: `{"feedback": "good" | "bad",
+/// "reason": "..."}`, the reason optional.
+async fn post_feedback(
+    State(state): State<Arc<ChironEngine>>,
+    Path(session_id): Path<String>,
+    Json(body): Json<PostFeedback>,
+) -> Result<StatusCode, ApiError> {
+    let Some(feedback) = Feedback::parse(&body.feedback) else {
+        return Err(ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: "Feedback must be \"good\" or \"bad\"".to_string(),
+        });
+    };
+    if !memory::feedback::rate_last_reply(&state.chat_conn, &session_id, feedback, body.reason.as_deref()).await? {
+        return Err(ChironError::SessionNotFound(session_id).into());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn end_session(
+    State(state): State<Arc<ChironEngine>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let summary = state.end_session(&session_id).await?;
+    Ok(Json(serde_json::json!({
+        "session_id": session_id,
+        "summary": summary,
+    })))
+}
+
+/// Each instrument with its items and the response scale they're answered on.
+async fn list_assessments() -> Json<serde_json::Value> {
+    let catalog = InstrumentCatalog::builtin();
+    let instruments: Vec<_> = catalog
+        .instruments()
+        .iter()
+        .map(|instrument| {
+            let scale = catalog.scale_for(instrument);
+            serde_json::json!({
+                "id": instrument.id,
+                "name": instrument.name,
+                "description": instrument.description,
+                "prompt": instrument.prompt,
+                "items": instrument.items,
+                "scale": { "min": scale.min, "max": scale.max, "anchors": scale.anchors },
+            })
+        })
+        .collect();
+    Json(serde_json::json!(instruments))
+}
+
+/// Scores `{"responses": [...]}`, one per item in order. Critical items
+/// answered above the minimum (e.g. PHQ-9 item 9) add `crisis` resources
+/// whatever the total, as in `chiron assess`. Nothing is stored.
+async fn score_assessment(
+    Path(id): Path<String>,
+    Json(body): Json<PostAssessment>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let catalog = InstrumentCatalog::builtin();
+    let Some(instrument) = catalog.get(&id) else {
+        return Err(ApiError {
+            status: StatusCode::NOT_FOUND,
+            message: format!("Unknown assessment '{id}'"),
+        });
+    };
+    let score = catalog.score(instrument, &body.responses).map_err(|e| ApiError {
+        status: StatusCode::BAD_REQUEST,
+        message: e.to_string(),
+    })?;
+    Ok(Json(serde_json::json!({
+        "id": instrument.id,
+        "total": score.total,
+        "band": score.band,
+        "flagged_items": score.flagged_items,
+        "crisis": (!score.flagged_items.is_empty()).then(crisis_response),
+        "disclaimer": t("assessment.disclaimer"),
+    })))
+}
+
+/// Mood check-ins and the ORS (well-being) and SRS (alliance) session
+/// ratings from the last `days` days (default 30), oldest first: the data
+/// behind `chiron mood chart`.
+async fn get_mood(
+    State(state): State<Arc<ChironEngine>>,
+    Query(query): Query<MoodQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let conn = &state.chat_conn;
+    let checkins = memory::mood::recent_checkins(conn, query.days).await?;
+    Ok(Json(serde_json::json!({
+        "days": query.days,
+        "average_mood": memory::mood::average_mood(&checkins),
+        "checkins": checkins,
+        "ors": memory::outcomes::recent_ratings(conn, "ors", query.days).await?,
+        "srs": memory::outcomes::recent_ratings(conn, "srs", query.days).await?,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(host: &str, origin: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, host.parse().unwrap());
+        if let Some(origin) = origin {
+            headers.insert(header::ORIGIN, origin.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_only_direct_same_origin_requests_are_allowed() {
+        // Clients outside a browser, and the web UI itself
+        assert!(allowed_request(&headers("127.0.0.1:8080", None), 8080));
+        assert!(allowed_request(&headers("localhost:8080", Some("http://localhost:8080")), 8080));
+        assert!(allowed_request(&headers("[::1]:8080", Some("http://[::1]:8080")), 8080));
+        assert!(allowed_request(&headers("192.168.1.5:8080", Some("http://192.168.1.5:8080")), 8080));
+
+        // A page on another site, directly or through DNS rebinding
+        assert!(!allowed_request(&headers("127.0.0.1:8080", Some("https://evil.example")), 8080));
+        assert!(!allowed_request(&headers("evil.example:8080", Some("http://evil.example:8080")), 8080));
+        assert!(!allowed_request(&headers("evil.example:8080", None), 8080));
+        assert!(!allowed_request(&headers("127.0.0.1:9090", None), 8080));
+        assert!(!allowed_request(&HeaderMap::new(), 8080));
+    }
+
+    #[test]
+    fn test_direct_host_defaults_to_port_80() {
+        assert!(direct_host("127.0.0.1", 80));
+        assert!(direct_host("[::1]", 80));
+        assert!(!direct_host("127.0.0.1", 8080));
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::catalog::PromptCatalog;
+use crate::checkin::ask;
+use crate::config::{Config, SyncBackend};
+use crate::guardrails::Guardrails;
+use crate::i18n;
+use crate::secrets;
+use crate::sync::remote::Remote;
+use crate::timezone::DisplayZone;
+
+/// `chiron init`: walks through the settings a new user needs, checks the
+/// model file and sync server, and writes the config file (plus
+/// `guardrails.toml` for local crisis resources). Existing values are the
+/// defaults, so it can be rerun to change them.
+pub async fn run(config_path: &Path, mut config: Config) -> Result<()> {
+    println!("Chiron setup. Press Enter to keep the value in [brackets].\n");
+    if config_path.exists() {
+        println!("Updating {}", config_path.display());
+    }
+
+    // --- Model ---
+    println!("\n== Model ==");
+    loop {
+        let path = PathBuf::from(ask_default("GGUF model file", &config.model.path.display().to_string())?);
+        match check_model(&path) {
+            Ok(()) => {
+                println!("  ✓ {} is a GGUF model", path.display());
+                config.model.path = path;
+                break;
+            }
+            Err(e) => {
+                println!("  ✗ {e:#}");
+                if yes_no(&ask("  Use it anyway? [y/N]")?, false) {
+                    config.model.path = path;
+                    break;
+                }
+            }
+        }
+    }
+    let gpu = yes_no(&ask(&format!("Offload the model to the GPU? [{}]", hint(config.model.n_gpu_layers > 0)))?, config.model.n_gpu_layers > 0);
+    config.model.n_gpu_layers = if gpu { 99 } else { 0 };
+    let context_size = config.model.context_size;
+    config.model.context_size =
+        ask_until(&format!("Context window in tokens [{context_size}]"), |a| parse_or(a, context_size))?;
+
+    // --- Language and crisis resources ---
+    println!("\n== Language ==");
+    let languages = i18n::languages();
+    let prompt = format!("Language ({}) [{}]", languages.join(", "), config.persona.language);
+    config.persona.language = ask_until(&prompt, |a| choose(a, &languages, &config.persona.language))?;
+    let prompt = format!("Time zone for timestamps (local, utc, or +HH:MM) [{}]", config.persona.timezone);
+    config.persona.timezone = ask_until(&prompt, |a| match a {
+        "" => Ok(config.persona.timezone.clone()),
+        zone => DisplayZone::parse(zone).map(|_| zone.to_string()),
+    })?;
+    let guardrails_path = Guardrails::path_for(config_path);
+    let mut guardrails = Guardrails::load(&guardrails_path)?;
+    println!(
+        "\nIn a crisis, Chiron shows crisis resources. The built-in text is general; \
+         you can replace it with hotlines for where you live."
+    );
+    let resources = ask(match guardrails.crisis_response {
+        Some(_) => "Crisis resources (Enter keeps yours, '-' restores the built-in text):",
+        None => "Crisis resources, e.g. \"Call 112 or the Samaritans on 116 123\" (Enter for the built-in text):",
+    })?;
+    let resources_changed = match resources.as_str() {
+        "" => false,
+        "-" => guardrails.crisis_response.take().is_some(),
+        text => {
+            guardrails.crisis_response = Some(text.to_string());
+            true
+        }
+    };
+
+    // --- Storage, privacy, and sync ---
+    println!("\n== Storage ==");
+    config.storage.db_path = ask_default("Database file", &config.storage.db_path)?;
+    println!(
+        "  Conversations are stored unencrypted on this computer; protect it with disk encryption. \
+         Syncing to a server is end-to-end encrypted."
+    );
+    let idle = config.privacy.idle_lock_minutes;
+    config.privacy.idle_lock_minutes =
+        ask_until(&format!("Lock chat after this many idle minutes (0 = never) [{idle}]"), |a| parse_or(a, idle))?;
+    let syncing = !config.sync.endpoint.is_empty();
+    if yes_no(&ask(&format!("Sync sessions to a WebDAV or S3 server, encrypted? [{}]", hint(syncing)))?, syncing) {
+        setup_sync(&mut config).await?;
+    } else {
+        config.sync.endpoint.clear();
+    }
+
+    // --- Persona ---
+    println!("\n== Coach ==");
+    match PromptCatalog::load(&config.persona.coach_variants) {
+        Ok(catalog) => {
+            for v in &catalog.variants {
+                println!("  {:<16} {}", v.id, v.description);
+            }
+            let ids: Vec<&str> = catalog.variants.iter().map(|v| v.id.as_str()).collect();
+            let current = match config.persona.coach_variant.as_str() {
+                "" => ids.first().copied().unwrap_or_default(),
+                id => id,
+            };
+            config.persona.coach_variant = ask_until(&format!("Coach variant [{current}]"), |a| choose(a, &ids, current))?;
+        }
+        Err(e) => println!("  (couldn't list coach variants: {e:#})"),
+    }
+    config.persona.show_thinking = yes_no(
+        &ask(&format!("Show the coach's reasoning after each reply? [{}]", hint(config.persona.show_thinking)))?,
+        config.persona.show_thinking,
+    );
+    config.persona.tools = yes_no(
+        &ask(&format!("Let the coach call tools (needs a tool-calling model)? [{}]", hint(config.persona.tools)))?,
+        config.persona.tools,
+    );
+
+    config.save(config_path)?;
+    println!("\nWrote {}", config_path.display());
+    if resources_changed {
+        guardrails.save(&guardrails_path)?;
+        println!("Wrote {}", guardrails_path.display());
+    }
+    println!("Run `chiron` to start a session, or `chiron config show` to review every setting.");
+    Ok(())
+}
+
+/// Asks for the sync server and credentials, stores the secrets in the OS
+/// keyring, and tries the connection.
+async fn setup_sync(config: &mut Config) -> Result<()> {
+    let backend = match config.sync.backend {
+        SyncBackend::Webdav => "webdav",
+        SyncBackend::S3 => "s3",
+    };
+    let prompt = format!("  Server type (webdav, s3) [{backend}]");
+    config.sync.backend = match ask_until(&prompt, |a| choose(a, &["webdav", "s3"], backend))?.as_str() {
+        "s3" => SyncBackend::S3,
+        _ => SyncBackend::Webdav,
+    };
+    config.sync.endpoint = ask_default("  Server URL", &config.sync.endpoint)?;
+    match config.sync.backend {
+        SyncBackend::Webdav => {
+            config.sync.username = ask_default("  User name (empty for none)", &config.sync.username)?;
+            if !config.sync.username.is_empty() {
+                store_secret("sync-password")?;
+            }
+        }
+        SyncBackend::S3 => {
+            config.sync.region = ask_default("  Region", &config.sync.region)?;
+            store_secret("aws-access-key-id")?;
+            store_secret("aws-secret-access-key")?;
+        }
+    }
+    store_secret("sync-passphrase")?;
+
+    print!("  Testing the connection... ");
+    io::stdout().flush()?;
+    let tested = async {
+        let remote = Remote::from_config(&config.sync)?;
+        remote.prepare().await?;
+        remote.list("sessions/").await.map(|_| ())
+    }
+    .await;
+    match tested {
+        Ok(()) => println!("✓ connected"),
+        Err(e) => println!("✗ {e:#}\n  Saved anyway; fix it with `chiron config set sync.endpoint ...` and `chiron secrets set`."),
+    }
+    Ok(())
+}
+
+/// Prompts for a secret unless one is already stored.
+fn store_secret(name: &str) -> Result<()> {
+    if secrets::get(name)?.is_some() && !yes_no(&ask(&format!("  {name} is already set. Replace it? [y/N]"))?, false) {
+        return Ok(());
+    }
+    let value = secrets::read_value(&format!("  {name}"))?;
+    if !value.is_empty() {
+        secrets::set(name, &value)?;
+    }
+    Ok(())
+}
+
+/// Checks that `path` is a readable GGUF file.
+pub fn check_model(path: &Path) -> Result<()> {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .with_context(|| format!("Can't read {}", path.display()))?;
+    if &magic != b"GGUF" {
+        bail!("{} isn't a GGUF model file", path.display());
+    }
+    Ok(())
+}
+
+/// Asks until `parse` accepts the answer.
+fn ask_until<T>(prompt: &str, parse: impl Fn(&str) -> Result<T>) -> Result<T> {
+    loop {
+        match parse(&ask(prompt)?) {
+            Ok(value) => return Ok(value),
+            Err(e) => println!("  ✗ {e:#}"),
+        }
+    }
+}
+
+fn ask_default(prompt: &str, current: &str) -> Result<String> {
+    let answer = ask(&format!("{prompt} [{current}]"))?;
+    Ok(if answer.is_empty() { current.to_string() } else { answer })
+}
+
+fn hint(default: bool) -> &'static str {
+    if default { "Y/n" } else { "y/N" }
+}
+
+/// Reads a yes/no answer; anything else keeps `default`.
+fn yes_no(answer: &str, default: bool) -> bool {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+/// One of `options`, or `default` for an empty answer.
+fn choose(answer: &str, options: &[&str], default: &str) -> Result<String> {
+    let answer = answer.trim().to_lowercase();
+    if answer.is_empty() {
+        return Ok(default.to_string());
+    }
+    match options.iter().find(|o| o.to_lowercase() == answer) {
+        Some(option) => Ok(option.to_string()),
+        None => bail!("'{answer}' isn't one of {}", options.join(", ")),
+    }
+}
+
+/// A number, or `default` for an empty answer.
+fn parse_or<T: std::str::FromStr>(answer: &str, default: T) -> Result<T> {
+    match answer.trim() {
+        "" => Ok(default),
+        n => n.parse().ok().with_context(|| format!("'{n}' isn't a number")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers_and_model_check() {
+        assert!(yes_no("Yes", false));
+        assert!(!yes_no("n", true));
+        assert!(yes_no("", true));
+        assert_eq!(choose("ES", &["en", "es"], "en").unwrap(), "es");
+        assert_eq!(choose("", &["en", "es"], "en").unwrap(), "en");
+        assert!(choose("fr", &["en", "es"], "en").is_err());
+        assert_eq!(parse_or("", 4096usize).unwrap(), 4096);
+        assert_eq!(parse_or(" 8192 ", 4096usize).unwrap(), 8192);
+        assert!(parse_or("lots", 4096usize).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let model = dir.path().join("model.gguf");
+        std::fs::write(&model, b"GGUF\x03\x00\x00\x00").unwrap();
+        assert!(check_model(&model).is_ok());
+        std::fs::write(&model, b"<html>").unwrap();
+        assert!(check_model(&model).is_err());
+        assert!(check_model(&dir.path().join("missing.gguf")).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use tokio_rusqlite::Connection;
+
+use crate::checkin::ask;
+use crate::i18n::{t, tf};
+use crate::memory::mood::{self, CheckIn};
+use crate::memory::sleep::{self, SleepEntry};
+use crate::timezone;
+
+/// Days of diary and check-ins used for the sleep/mood correlation.
+const CORRELATION_DAYS: u32 = 30;
+
+/// Minimum nights with both a diary entry and a mood check-in before a
+/// correlation is reported.
+const MIN_CORRELATION_PAIRS: usize = 5;
+
+/// A week of sleep diary entries, averaged.
+#[derive(Debug, PartialEq)]
+pub struct WeeklySummary {
+    pub nights: usize,
+    pub avg_hours: f64,
+    pub avg_quality: f64,
+    pub avg_awakenings: f64,
+    /// Minutes between the earliest and latest bedtime.
+    pub bedtime_spread: i64,
+}
+
+/// Runs `/sleep log`: bedtime, wake time, awakenings, and quality (1–5).
+pub async fn log(conn: &Connection) -> Result<()> {
+    let bedtime = ask_until(t("sleep.bedtime"), t("sleep.time_invalid"), parse_clock)?;
+    let wake_time = ask_until(t("sleep.wake_time"), t("sleep.time_invalid"), parse_clock)?;
+    let awakenings = ask_until(t("sleep.awakenings"), t("sleep.awakenings_invalid"), |a| {
+        a.parse::<i32>().ok().filter(|n| *n >= 0)
+    })?;
+    let quality = ask_until(t("sleep.quality"), t("sleep.quality_invalid"), |a| {
+        a.parse::<i32>().ok().filter(|q| (1..=5).contains(q))
+    })?;
+
+    sleep::save_sleep_entry(conn, &bedtime, &wake_time, awakenings, quality).await?;
+    println!("{}", t("sleep.saved"));
+    Ok(())
+}
+
+/// Runs `/sleep week`: averages for the last 7 nights, how sleep tracks
+/// mood over the last month, and sleep-hygiene tips for what stands out.
+pub async fn show_week(conn: &Connection) -> Result<()> {
+    let week = sleep::recent_sleep_entries(conn, 7).await?;
+    let Some(summary) = summarize(&week) else {
+        println!("{}", t("sleep.no_entries"));
+        return Ok(());
+    };
+
+    println!(
+        "{}",
+        tf(
+            "sleep.summary",
+            &[
+                ("nights", &summary.nights),
+                ("hours", &format!("{:.1}", summary.avg_hours)),
+                ("quality", &format!("{:.1}", summary.avg_quality)),
+                ("awakenings", &format!("{:.1}", summary.avg_awakenings)),
+            ],
+        )
+    );
+
+    let entries = sleep::recent_sleep_entries(conn, CORRELATION_DAYS).await?;
+    let checkins = mood::recent_checkins(conn, CORRELATION_DAYS).await?;
+    match mood_correlation(&entries, &checkins) {
+        Some((r, days)) => {
+            let key = if r >= 0.3 {
+                "sleep.correlation_positive"
+            } else if r <= -0.3 {
+                "sleep.correlation_negative"
+            } else {
+                "sleep.correlation_none"
+            };
+            println!("{}", tf(key, &[("r", &format!("{r:.2}")), ("days", &days)]));
+        }
+        None => println!("{}", t("sleep.correlation_insufficient")),
+    }
+
+    for tip in hygiene_tips(&summary) {
+        println!("- {tip}");
+    }
+    Ok(())
+}
+
+/// Averages diary entries, or `None` if there are none.
+pub fn summarize(entries: &[SleepEntry]) -> Option<WeeklySummary> {
+    if entries.is_empty() {
+        return None;
+    }
+    let n = entries.len() as f64;
+    let hours: Vec<f64> = entries.iter().filter_map(SleepEntry::hours).collect();
+
+    // Measure bedtimes from noon so 23:30 and 00:30 are an hour apart
+    let bedtimes: Vec<i64> = entries
+        .iter()
+        .filter_map(|e| NaiveTime::parse_from_str(&e.bedtime, "%H:%M").ok())
+        .map(|b| (b.hour() as i64 * 60 + b.minute() as i64 + 12 * 60) % (24 * 60))
+        .collect();
+    let spread = match (bedtimes.iter().min(), bedtimes.iter().max()) {
+        (Some(min), Some(max)) => max - min,
+        _ => 0,
+    };
+
+    Some(WeeklySummary {
+        nights: entries.len(),
+        avg_hours: hours.iter().sum::<f64>() / hours.len().max(1) as f64,
+        avg_quality: entries.iter().map(|e| e.quality as f64).sum::<f64>() / n,
+        avg_awakenings: entries.iter().map(|e| e.awakenings as f64).sum::<f64>() / n,
+        bedtime_spread: spread,
+    })
+}
+
+/// Pearson correlation between hours slept and that day's mood, with the
+/// number of days paired. `None` until there are enough paired days.
+pub fn mood_correlation(entries: &[SleepEntry], checkins: &[CheckIn]) -> Option<(f64, usize)> {
+    // Average mood per day, in case of several check-ins
+    let mut moods: BTreeMap<NaiveDate, (f64, f64)> = BTreeMap::new();
+    for c in checkins {
+        let Some(date) = timezone::date(&c.created_at) else {
+            continue;
+        };
+        let day = moods.entry(date).or_default();
+        day.0 += c.mood as f64;
+        day.1 += 1.0;
+    }
+
+    let pairs: Vec<(f64, f64)> = entries
+        .iter()
+        .filter_map(|e| {
+            let (total, count) = moods.get(&timezone::date(&e.created_at)?)?;
+            Some((e.hours()?, total / count))
+        })
+        .collect();
+    if pairs.len() < MIN_CORRELATION_PAIRS {
+        return None;
+    }
+    pearson(&pairs).map(|r| (r, pairs.len()))
+}
+
+/// Sleep-hygiene suggestions targeted at what stands out in the week.
+pub fn hygiene_tips(summary: &WeeklySummary) -> Vec<&'static str> {
+    let mut tips = Vec::new();
+    if summary.bedtime_spread > 90 {
+        tips.push(t("sleep.tip_irregular"));
+    }
+    if summary.avg_hours < 7.0 {
+        tips.push(t("sleep.tip_short"));
+    }
+    if summary.avg_awakenings >= 2.0 {
+        tips.push(t("sleep.tip_awakenings"));
+    }
+    if summary.avg_quality <= 2.5 {
+        tips.push(t("sleep.tip_quality"));
+    }
+    tips
+}
+
+fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    // No variation in either series means no meaningful correlation
+    (var_x > 0.0 && var_y > 0.0).then(|| cov / (var_x * var_y).sqrt())
+}
+
+/// Parses a clock time (`23:30`, `7:05`, or a bare hour like `7`) as `HH:MM`.
+fn parse_clock(answer: &str) -> Option<String> {
+    let (hour, minute) = answer.split_once(':').unwrap_or((answer, "0"));
+    let time = NaiveTime::from_hms_opt(hour.trim().parse().ok()?, minute.trim().parse().ok()?, 0)?;
+    Some(time.format("%H:%M").to_string())
+}
+
+/// Asks until `parse` accepts the answer, printing `invalid` after each miss.
+fn ask_until<T>(prompt: &str, invalid: &str, parse: impl Fn(&str) -> Option<T>) -> Result<T> {
+    loop {
+        if let Some(value) = parse(&ask(prompt)?) {
+            return Ok(value);
+        }
+        println!("{invalid}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(bedtime: &str, wake_time: &str, awakenings: i32, quality: i32, day: &str) -> SleepEntry {
+        SleepEntry {
+            id: 0,
+            bedtime: bedtime.into(),
+            wake_time: wake_time.into(),
+            awakenings,
+            quality,
+            created_at: format!("{day} 08:00:00"),
+        }
+    }
+
+    fn checkin(mood: i32, day: &str) -> CheckIn {
+        CheckIn {
+            id: 0,
+            mood,
+            sleep_hours: None,
+            highlight: None,
+            stressor: None,
+            created_at: format!("{day} 20:00:00"),
+        }
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("23:30").as_deref(), Some("23:30"));
+        assert_eq!(parse_clock("7").as_deref(), Some("07:00"));
+        assert_eq!(parse_clock("25:00"), None);
+        assert_eq!(parse_clock("late"), None);
+    }
+
+    #[test]
+    fn test_summary_and_tips() {
+        let week = [
+            entry("22:30", "05:30", 3, 2, "2026-03-01"),
+            entry("01:00", "06:00", 2, 2, "2026-03-02"),
+        ];
+        let summary = summarize(&week).unwrap();
+        assert_eq!(summary.avg_hours, 6.0);
+        assert_eq!(summary.avg_awakenings, 2.5);
+        assert_eq!(summary.bedtime_spread, 150, "spread measured across midnight");
+        assert_eq!(hygiene_tips(&summary).len(), 4);
+
+        let good = [entry("23:00", "07:00", 0, 4, "2026-03-01")];
+        assert!(hygiene_tips(&summarize(&good).unwrap()).is_empty());
+        assert!(summarize(&[]).is_none());
+    }
+
+    #[test]
+    fn test_mood_correlation() {
+        let days = ["2026-03-01", "2026-03-02", "2026-03-03", "2026-03-04", "2026-03-05"];
+        let entries: Vec<SleepEntry> = days
+            .iter()
+            .zip(["04:00", "05:00", "06:00", "07:00", "08:00"])
+            .map(|(day, wake)| entry("00:00", wake, 0, 3, day))
+            .collect();
+        let checkins: Vec<CheckIn> = days.iter().zip([3, 4, 5, 6, 7]).map(|(d, m)| checkin(m, d)).collect();
+
+        let (r, paired) = mood_correlation(&entries, &checkins).unwrap();
+        assert!((r - 1.0).abs() < 1e-9);
+        assert_eq!(paired, 5);
+
+        assert!(mood_correlation(&entries[..4], &checkins).is_none(), "too few days");
+    }
+}
@@ -0,0 +1,129 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Mutex};
+
+use crate::config::VoiceConfig;
+
+/// Speaks response text sentence by sentence as it streams in.
+///
+/// Tokens are buffered until a sentence is complete, then queued for a
+/// background thread that pipes each sentence to the configured TTS command
+/// (espeak-ng, `say`, or a piper pipeline). Speech therefore starts while
+/// the rest of the response is still generating.
+pub struct Speaker {
+    pending: Mutex<String>,
+    tx: mpsc::Sender<String>,
+}
+
+impl Speaker {
+    pub fn new(config: &VoiceConfig) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        let command = config.speak_command.clone();
+        let args = config.speak_args.clone();
+
+        std::thread::spawn(move || {
+            for sentence in rx {
+                if let Err(e) = speak(&command, &args, &sentence) {
+                    tracing::warn!(command, error = %e, "Text-to-speech failed");
+                }
+            }
+        });
+
+        Self {
+            pending: Mutex::new(String::new()),
+            tx,
+        }
+    }
+
+    /// Adds streamed text, queueing any sentences it completes.
+    pub fn push(&self, text: &str) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.push_str(text);
+        let (sentences, rest) = split_sentences(&pending);
+        for sentence in sentences {
+            let _ = self.tx.send(sentence);
+        }
+        *pending = rest;
+    }
+
+    /// Queues whatever is left in the buffer (end of response).
+    pub fn flush(&self) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let rest = std::mem::take(&mut *pending);
+        if !rest.trim().is_empty() {
+            let _ = self.tx.send(rest.trim().to_string());
+        }
+    }
+}
+
+fn speak(command: &str, args: &[String], text: &str) -> std::io::Result<()> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Splits text into complete sentences and an unfinished remainder.
+///
+/// A sentence ends at `.`, `!`, or `?` followed by whitespace, or at a
+/// newline. Markdown emphasis markers are dropped so they aren't read aloud.
+pub fn split_sentences(text: &str) -> (Vec<String>, String) {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '*' || c == '_' {
+            continue;
+        }
+        current.push(c);
+        let at_boundary = match c {
+            '.' | '!' | '?' => chars.peek().is_some_and(|n| n.is_whitespace()),
+            '\n' => true,
+            _ => false,
+        };
+        if at_boundary {
+            let sentence = current.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            current.clear();
+        }
+    }
+
+    (sentences, current.trim_start().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_complete_and_partial() {
+        let (sentences, rest) = split_sentences("That sounds hard. What helps? When I");
+        assert_eq!(sentences, vec!["That sounds hard.", "What helps?"]);
+        assert_eq!(rest, "When I");
+    }
+
+    #[test]
+    fn test_split_waits_for_whitespace_after_period() {
+        // "3." might be "3.5" — wait for the next character
+        let (sentences, rest) = split_sentences("About 3.");
+        assert!(sentences.is_empty());
+        assert_eq!(rest, "About 3.");
+    }
+
+    #[test]
+    fn test_split_on_newline_and_strip_markdown() {
+        let (sentences, rest) = split_sentences("*Breathe in*\nand out\n");
+        assert_eq!(sentences, vec!["Breathe in", "and out"]);
+        assert_eq!(rest, "");
+    }
+}
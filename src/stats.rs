@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use crate::memory::usage::{TurnUsage, UsageSummary};
+
+/// Prompt size bucket width for `chiron sessions stats` without a session.
+pub const BUCKET_TOKENS: u64 = 512;
+
+/// Renders per-reply token counts and latency for one session, so growth in
+/// prompt size can be read against time to first token.
+pub fn render_session(session_id: &str, turns: &[TurnUsage]) -> String {
+    if turns.is_empty() {
+        return format!("No usage recorded for session {session_id}.\n");
+    }
+    let prompt: u64 = turns.iter().map(|t| t.usage.prompt_tokens).sum();
+    let response: u64 = turns.iter().map(|t| t.usage.response_tokens).sum();
+    let latency: u64 = turns.iter().map(|t| t.usage.latency_ms).sum();
+
+    let mut out = format!(
+        "Session {session_id}: {} replies, {prompt} prompt tokens, {response} response tokens, {} generating\n\n",
+        turns.len(),
+        seconds(latency as f64),
+    );
+    let _ = writeln!(
+        out,
+        "{:>4}  {:>7}  {:>8}  {:>11}  {:>8}  {:>6}",
+        "turn", "prompt", "response", "first token", "total", "tok/s"
+    );
+    for turn in turns {
+        let usage = &turn.usage;
+        let _ = writeln!(
+            out,
+            "{:>4}  {:>7}  {:>8}  {:>11}  {:>8}  {:>6}",
+            turn.turn,
+            usage.prompt_tokens,
+            usage.response_tokens,
+            usage.first_token_ms.map(|ms| seconds(ms as f64)).unwrap_or_else(|| "-".to_string()),
+            seconds(usage.latency_ms as f64),
+            usage.tokens_per_sec().map(|t| format!("{t:.1}")).unwrap_or_else(|| "-".to_string()),
+        );
+    }
+    out
+}
+
+/// Renders usage across all sessions, with latency by prompt size.
+pub fn render_summary(summary: &UsageSummary) -> String {
+    if summary.replies == 0 {
+        return "No usage recorded yet.\n".to_string();
+    }
+    let mut out = format!(
+        "{} replies across {} sessions: {} prompt tokens, {} response tokens, {} average latency\n\n",
+        summary.replies,
+        summary.sessions,
+        summary.prompt_tokens,
+        summary.response_tokens,
+        summary.avg_latency_ms.map(seconds).unwrap_or_else(|| "-".to_string()),
+    );
+    let _ = writeln!(
+        out,
+        "{:>13}  {:>7}  {:>11}  {:>8}  {:>8}",
+        "prompt tokens", "replies", "first token", "total", "response"
+    );
+    for bucket in &summary.by_prompt_size {
+        let range = format!(
+            "{}–{}",
+            bucket.min_prompt_tokens,
+            bucket.min_prompt_tokens + BUCKET_TOKENS - 1
+        );
+        let _ = writeln!(
+            out,
+            "{:>13}  {:>7}  {:>11}  {:>8}  {:>8.0}",
+            range,
+            bucket.replies,
+            bucket.avg_first_token_ms.map(seconds).unwrap_or_else(|| "-".to_string()),
+            seconds(bucket.avg_latency_ms),
+            bucket.avg_response_tokens,
+        );
+    }
+    out
+}
+
+fn seconds(ms: f64) -> String {
+    format!("{:.1}s", ms / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::usage::{ReplyUsage, UsageBucket};
+
+    #[test]
+    fn test_render_session_and_summary() {
+        let turns = vec![TurnUsage {
+            turn: 2,
+            created_at: "2026-10-01 10:00:00".to_string(),
+            usage: ReplyUsage {
+                prompt_tokens: 820,
+                response_tokens: 64,
+                first_token_ms: Some(450),
+                latency_ms: 3200,
+            },
+        }];
+        let session = render_session("session_1", &turns);
+        assert!(session.starts_with("Session session_1: 1 replies, 820 prompt tokens, 64 response tokens, 3.2s"));
+        assert!(session.lines().last().unwrap().split_whitespace().eq(["2", "820", "64", "0.5s", "3.2s", "20.0"]));
+        assert_eq!(render_session("empty", &[]), "No usage recorded for session empty.\n");
+
+        let summary = UsageSummary {
+            sessions: 2,
+            replies: 3,
+            prompt_tokens: 1400,
+            response_tokens: 120,
+            avg_latency_ms: Some(1500.0),
+            by_prompt_size: vec![UsageBucket {
+                min_prompt_tokens: 512,
+                replies: 1,
+                avg_response_tokens: 40.0,
+                avg_first_token_ms: Some(500.0),
+                avg_latency_ms: 2000.0,
+            }],
+        };
+        let rendered = render_summary(&summary);
+        assert!(rendered.contains("3 replies across 2 sessions"));
+        assert!(rendered.lines().last().unwrap().split_whitespace().eq(["512–1023", "1", "0.5s", "2.0s", "40"]));
+    }
+}
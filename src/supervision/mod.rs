@@ -1,5 +1,6 @@
+pub mod taxonomy;
 pub mod think_parser;
 
 pub use think_parser::{
-    analyze_think_block, extract_mi_stage, extract_themes, merge_themes, ThinkAnalysis,
+    analyze_think_block, extract_mi_stage, extract_strategy, extract_themes, merge_themes, ThinkAnalysis,
 };
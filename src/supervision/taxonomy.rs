@@ -0,0 +1,207 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Tag definitions compiled into the binary.
+const TAXONOMY_TOML: &str = include_str!("../../prompts/taxonomy.toml");
+
+static BUILTIN: OnceLock<Taxonomy> = OnceLock::new();
+
+/// Top level of the taxonomy: the first segment of every tag path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// How they feel (`emotion/anxiety`).
+    Emotion,
+    /// What they're dealing with (`concern/substance/alcohol`).
+    Concern,
+    /// What the coach did (`technique/reflection/complex-reflection`).
+    Technique,
+    /// What happened in their life (`event/job-loss`).
+    Event,
+}
+
+impl Category {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "emotion" => Some(Category::Emotion),
+            "concern" => Some(Category::Concern),
+            "technique" => Some(Category::Technique),
+            "event" => Some(Category::Event),
+            _ => None,
+        }
+    }
+}
+
+/// One canonical tag, e.g. `concern/relationships/family`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tag {
+    pub path: String,
+    /// Free-form tags that mean this one.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Tag {
+    pub fn category(&self) -> Category {
+        self.path.split('/').next().and_then(Category::parse).expect("validated in Taxonomy::parse")
+    }
+
+    /// The last path segment, as written in prose: `job loss`.
+    pub fn label(&self) -> String {
+        self.path.rsplit('/').next().unwrap_or(&self.path).replace('-', " ")
+    }
+
+    /// The enclosing tag's path, if this isn't a top-level tag.
+    pub fn parent(&self) -> Option<&str> {
+        let (parent, _) = self.path.rsplit_once('/')?;
+        parent.contains('/').then_some(parent)
+    }
+}
+
+/// The controlled vocabulary for themes and strategies.
+#[derive(Debug, Deserialize)]
+pub struct Taxonomy {
+    #[serde(rename = "tag")]
+    tags: Vec<Tag>,
+    /// Normalized label or alias -> index into `tags`.
+    #[serde(skip)]
+    names: HashMap<String, usize>,
+}
+
+impl Taxonomy {
+    /// The built-in taxonomy (`prompts/taxonomy.toml`).
+    pub fn builtin() -> &'static Self {
+        BUILTIN.get_or_init(|| Self::parse(TAXONOMY_TOML).expect("built-in taxonomy.toml is valid"))
+    }
+
+    /// Parses and validates a taxonomy: every path starts with a category,
+    /// is listed once, and has its parent listed; no label or alias names
+    /// two tags.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut taxonomy: Self = toml::from_str(source).context("Failed to parse tag taxonomy")?;
+        let mut names = HashMap::new();
+        for (i, tag) in taxonomy.tags.iter().enumerate() {
+            let segments: Vec<&str> = tag.path.split('/').collect();
+            if segments.len() < 2 || segments.iter().any(|s| s.is_empty() || *s != s.to_lowercase()) {
+                bail!("Tag '{}' must be a lowercase path like 'emotion/anxiety'", tag.path);
+            }
+            if Category::parse(segments[0]).is_none() {
+                bail!("Tag '{}' isn't under emotion, concern, technique, or event", tag.path);
+            }
+            if taxonomy.tags[..i].iter().any(|t| t.path == tag.path) {
+                bail!("Tag '{}' is listed twice", tag.path);
+            }
+            if let Some(parent) = tag.parent() {
+                if !taxonomy.tags.iter().any(|t| t.path == parent) {
+                    bail!("Tag '{}' has no parent '{parent}'", tag.path);
+                }
+            }
+            for name in std::iter::once(tag.label()).chain(tag.aliases.iter().cloned()) {
+                if let Some(other) = names.insert(normalize(&name), i) {
+                    bail!("'{name}' names both '{}' and '{}'", taxonomy.tags[other].path, tag.path);
+                }
+            }
+        }
+        taxonomy.names = names;
+        Ok(taxonomy)
+    }
+
+    /// Looks up a canonical path.
+    pub fn get(&self, path: &str) -> Option<&Tag> {
+        self.tags.iter().find(|t| t.path == path)
+    }
+
+    /// Checks that `tag` is a canonical path, suggesting the one it maps to
+    /// if it isn't.
+    pub fn validate(&self, tag: &str) -> Result<&Tag> {
+        if let Some(canonical) = self.get(tag) {
+            return Ok(canonical);
+        }
+        match self.canonical(tag) {
+            Some(suggestion) => bail!("'{tag}' isn't a canonical tag; did you mean '{}'?", suggestion.path),
+            None => bail!("'{tag}' isn't in the tag taxonomy"),
+        }
+    }
+
+    /// Maps a free-form tag onto a canonical one: by path, label, or alias,
+    /// or failing those by the longest label or alias it contains as whole
+    /// words ("family support" -> `concern/relationships/family`).
+    pub fn canonical(&self, free: &str) -> Option<&Tag> {
+        let free = normalize(free);
+        if let Some(tag) = self.get(&free) {
+            return Some(tag);
+        }
+        if let Some(&i) = self.names.get(&free) {
+            return Some(&self.tags[i]);
+        }
+        let words: Vec<&str> = free.split(' ').collect();
+        self.names
+            .iter()
+            .filter(|(name, _)| {
+                let name: Vec<&str> = name.split(' ').collect();
+                words.windows(name.len()).any(|w| w == name.as_slice())
+            })
+            .max_by_key(|(name, i)| (name.len(), std::cmp::Reverse(**i)))
+            .map(|(_, &i)| &self.tags[i])
+    }
+
+    /// The canonical label for a free-form tag, or the tag itself when it
+    /// maps to nothing, for grouping in reports.
+    pub fn label(&self, free: &str) -> String {
+        self.canonical(free).map(Tag::label).unwrap_or_else(|| free.trim().to_lowercase())
+    }
+
+    /// The labels of every tag in `category`.
+    pub fn labels(&self, category: Category) -> BTreeSet<String> {
+        self.tags.iter().filter(|t| t.category() == category).map(Tag::label).collect()
+    }
+}
+
+/// Lowercase, with `-`, `_`, and runs of spaces as single spaces.
+fn normalize(tag: &str) -> String {
+    tag.to_lowercase()
+        .replace(['-', '_'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_maps_free_form_tags() {
+        let taxonomy = Taxonomy::builtin();
+        let path = |free: &str| taxonomy.canonical(free).map(|t| t.path.as_str());
+        assert_eq!(path("drinking"), Some("concern/substance/alcohol"));
+        assert_eq!(path("Low mood"), Some("emotion/sadness"));
+        assert_eq!(path("job loss"), Some("event/job-loss"));
+        assert_eq!(path("complex reflection"), Some("technique/reflection/complex-reflection"));
+        assert_eq!(path("work stress"), Some("concern/work"));
+        assert_eq!(path("family support"), Some("concern/relationships/family"));
+        assert_eq!(path("confidence ruler"), Some("technique/evoking"));
+        assert_eq!(path("check-in"), None);
+
+        let family = taxonomy.get("concern/relationships/family").unwrap();
+        assert_eq!(family.category(), Category::Concern);
+        assert_eq!(family.parent(), Some("concern/relationships"));
+        assert_eq!(taxonomy.label("meetings"), "meetings");
+        assert!(taxonomy.validate("emotion/anxiety").is_ok());
+        let err = taxonomy.validate("anxious").unwrap_err().to_string();
+        assert!(err.contains("emotion/anxiety"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_taxonomies() {
+        let orphan = "[[tag]]\npath = \"concern/substance/alcohol\"";
+        assert!(Taxonomy::parse(orphan).unwrap_err().to_string().contains("no parent"));
+        let uncategorized = "[[tag]]\npath = \"mood/sad\"";
+        assert!(Taxonomy::parse(uncategorized).is_err());
+        let ambiguous = "[[tag]]\npath = \"emotion/sadness\"\naliases = [\"down\"]\n\
+                         [[tag]]\npath = \"emotion/exhaustion\"\naliases = [\"down\"]";
+        assert!(Taxonomy::parse(ambiguous).unwrap_err().to_string().contains("names both"));
+    }
+}
@@ -15,6 +15,9 @@ pub struct ThinkAnalysis {
     pub user_facts: Vec<(String, String)>,
     /// Significant turn signal detected via `[SIGNIFICANT: signal_type]` tag.
     pub significant_signal: Option<String>,
+    /// Homework agreed this turn via `[HOMEWORK: task | YYYY-MM-DD]`, as
+    /// (task, due date). The due date is optional.
+    pub homework: Option<(String, Option<String>)>,
     /// Raw think block content for logging.
     pub raw_think: String,
 }
@@ -40,6 +43,7 @@ pub fn analyze_think_block(think_content: &str) -> ThinkAnalysis {
         .unwrap_or_default();
     let user_facts = parse_user_facts(think_content);
     let significant_signal = parse_tag(think_content, "SIGNIFICANT");
+    let homework = parse_homework(think_content);
 
     ThinkAnalysis {
         mi_stage,
@@ -48,6 +52,7 @@ pub fn analyze_think_block(think_content: &str) -> ThinkAnalysis {
         themes,
         user_facts,
         significant_signal,
+        homework,
         raw_think: think_content.to_string(),
     }
 }
@@ -98,6 +103,25 @@ fn parse_user_facts(text: &str) -> Vec<(String, String)> {
     facts
 }
 
+/// Parses a `[HOMEWORK: task | YYYY-MM-DD]` tag, keeping the task's case
+/// since it is shown back to the user. A due date that isn't `YYYY-MM-DD` is
+/// dropped rather than stored.
+fn parse_homework(text: &str) -> Option<(String, Option<String>)> {
+    let pattern = "[homework:";
+    let start = text.to_lowercase().find(pattern)? + pattern.len();
+    let end = text[start..].find(']')?;
+    let value = text[start..start + end].trim();
+    let (task, due) = value.split_once('|').unwrap_or((value, ""));
+    let task = task.trim();
+    if task.is_empty() || task.eq_ignore_ascii_case("none") {
+        return None;
+    }
+    let due = chrono::NaiveDate::parse_from_str(due.trim(), "%Y-%m-%d")
+        .ok()
+        .map(|d| d.to_string());
+    Some((task.to_string(), due))
+}
+
 /// Extracts themes from the `Running Themes:` line in case notes.
 pub fn extract_themes(notes: &str) -> Option<Vec<String>> {
     notes
@@ -135,6 +159,15 @@ pub fn extract_mi_stage(notes: &str) -> Option<String> {
         })
 }
 
+/// Extracts the `Strategy Used:` value from case notes.
+pub fn extract_strategy(notes: &str) -> Option<String> {
+    notes
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Strategy Used:"))
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+}
+
 /// Merges previous and new themes with recency-biased capping.
 ///
 /// New themes get priority (most recently observed). Previous themes fill
@@ -251,6 +284,22 @@ mod tests {
         assert!(analysis.user_facts.is_empty());
     }
 
+    #[test]
+    fn test_parse_homework() {
+        let think = "[MI-STAGE: plan]\n[HOMEWORK: Walk after dinner twice | 2026-03-05]";
+        let analysis = analyze_think_block(think);
+        assert_eq!(
+            analysis.homework,
+            Some(("Walk after dinner twice".into(), Some("2026-03-05".into())))
+        );
+
+        let undated = analyze_think_block("[HOMEWORK: Try box breathing | next week]");
+        assert_eq!(undated.homework, Some(("Try box breathing".into(), None)));
+
+        assert_eq!(analyze_think_block("[HOMEWORK: none]").homework, None);
+        assert_eq!(analyze_think_block("[MI-STAGE: plan]").homework, None);
+    }
+
     #[test]
     fn test_parse_significant_signal() {
         let think = "[MI-STAGE: evoke]\n[SIGNIFICANT: change_talk]\nThey expressed desire to change.";
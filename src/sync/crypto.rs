@@ -0,0 +1,166 @@
+use std::num::NonZeroU32;
+
+use anyhow::{bail, Context, Result};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{hmac, pbkdf2};
+
+/// Blob format marker and version.
+const MAGIC: &[u8; 4] = b"CHS1";
+
+/// PBKDF2-HMAC-SHA256 rounds (OWASP's 2023 recommendation).
+const ITERATIONS: u32 = 600_000;
+
+pub const SALT_LEN: usize = 16;
+
+/// Keys derived from the sync passphrase: one encrypts blobs, the other
+/// names them, so the server sees neither content nor session IDs.
+pub struct SyncKey {
+    cipher: LessSafeKey,
+    names: hmac::Key,
+}
+
+impl SyncKey {
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Self {
+        Self::derive_with(passphrase, salt, ITERATIONS)
+    }
+
+    pub(super) fn derive_with(passphrase: &str, salt: &[u8], iterations: u32) -> Self {
+        let mut out = [0u8; 64];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(iterations).expect("iterations must be non-zero"),
+            salt,
+            passphrase.as_bytes(),
+            &mut out,
+        );
+        let (cipher, names) = out.split_at(32);
+        Self {
+            cipher: LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, cipher).expect("32-byte key")),
+            names: hmac::Key::new(hmac::HMAC_SHA256, names),
+        }
+    }
+
+    /// Value stored beside the salt, so a wrong passphrase is caught before
+    /// anything is decrypted or pushed.
+    pub fn check(&self) -> String {
+        hex(hmac::sign(&self.names, b"chiron-sync-check").as_ref())
+    }
+
+    /// Opaque, stable object name for a session.
+    pub fn blob_id(&self, session_id: &str) -> String {
+        hex(&hmac::sign(&self.names, session_id.as_bytes()).as_ref()[..16])
+    }
+
+    /// Encrypts `plaintext` as `MAGIC || nonce || ciphertext+tag`. The blob
+    /// name is authenticated too, so blobs can't be swapped on the server.
+    pub fn seal(&self, name: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("Failed to generate a nonce"))?;
+
+        let mut in_out = plaintext.to_vec();
+        self.cipher
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(name.as_bytes()), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt {name}"))?;
+
+        let mut blob = Vec::with_capacity(MAGIC.len() + NONCE_LEN + in_out.len());
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&in_out);
+        Ok(blob)
+    }
+
+    /// Decrypts a blob written by `seal` under the same name.
+    pub fn open(&self, name: &str, blob: &[u8]) -> Result<Vec<u8>> {
+        let Some(rest) = blob.strip_prefix(MAGIC.as_slice()) else {
+            bail!("{name} is not a Chiron sync blob");
+        };
+        if rest.len() < NONCE_LEN {
+            bail!("{name} is truncated");
+        }
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow::anyhow!("Bad nonce in {name}"))?;
+
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .cipher
+            .open_in_place(nonce, Aad::from(name.as_bytes()), &mut in_out)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt {name}: wrong key or tampered data"))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+pub fn random_salt() -> Result<[u8; SALT_LEN]> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| anyhow::anyhow!("Failed to generate a salt"))?;
+    Ok(salt)
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decodes lowercase or uppercase hex. Salts come from the remote, so
+/// anything else is an error rather than a panic.
+pub fn unhex(s: &str) -> Result<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        bail!("Invalid hex: odd length");
+    }
+    if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("Invalid hex: {s:?}");
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).with_context(|| format!("Invalid hex: {s:?}")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(passphrase: &str) -> SyncKey {
+        // Few rounds keep the tests fast; the format doesn't depend on them
+        SyncKey::derive_with(passphrase, b"0123456789abcdef", 2)
+    }
+
+    #[test]
+    fn test_seal_and_open_round_trip() {
+        let key = key("correct horse");
+        let blob = key.seal("sessions/a.bin", b"hello").unwrap();
+        assert!(blob.starts_with(MAGIC));
+        assert_eq!(key.open("sessions/a.bin", &blob).unwrap(), b"hello");
+
+        // Renamed, tampered, or opened with another passphrase
+        assert!(key.open("sessions/b.bin", &blob).is_err());
+        let mut tampered = blob.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(key.open("sessions/a.bin", &tampered).is_err());
+        assert!(self::key("wrong").open("sessions/a.bin", &blob).is_err());
+        assert!(key.open("sessions/a.bin", b"CHS1").is_err());
+    }
+
+    #[test]
+    fn test_names_and_check_depend_on_the_passphrase() {
+        let a = key("correct horse");
+        assert_eq!(a.blob_id("session_1"), key("correct horse").blob_id("session_1"));
+        assert_eq!(a.blob_id("session_1").len(), 32);
+        assert_ne!(a.blob_id("session_1"), a.blob_id("session_2"));
+        assert_ne!(a.check(), key("wrong").check());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(hex(&[0, 15, 255]), "000fff");
+        assert_eq!(unhex("000fff").unwrap(), vec![0, 15, 255]);
+        assert!(unhex("abc").is_err());
+        assert!(unhex("zz").is_err());
+        assert!(unhex("+1").is_err(), "from_str_radix would take the sign");
+        assert!(unhex("é").is_err(), "multibyte characters don't panic");
+        assert!(unhex("0é0").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+pub mod crypto;
+pub mod remote;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio_rusqlite::Connection;
+
+use self::crypto::SyncKey;
+use self::remote::{Remote, RemoteObject};
+use crate::config::SyncConfig;
+use crate::secrets;
+use crate::memory::sync::{self as store, MergeStats, SyncMessage};
+
+/// Salt and passphrase check, kept in the clear at the endpoint root so
+/// every device derives the same keys.
+const KEY_FILE: &str = "chiron-sync.json";
+
+const SESSIONS_DIR: &str = "sessions/";
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    /// Hex-encoded PBKDF2 salt.
+    salt: String,
+    /// `SyncKey::check` for the passphrase the remote was set up with.
+    check: String,
+}
+
+/// One device's copy of a session, encrypted into
+/// `sessions/<blob id>.<device id>.bin`. Each device writes only its own
+/// blobs, so concurrent pushes never overwrite each other.
+#[derive(Serialize, Deserialize)]
+struct SessionBlob {
+    session_id: String,
+    messages: Vec<SyncMessage>,
+}
+
+/// What a sync run changed.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Other devices' session copies merged.
+    pub pulled: usize,
+    pub merged: MergeStats,
+    /// This device's sessions uploaded.
+    pub pushed: usize,
+}
+
+/// Pulls other devices' changes, then pushes this device's. Chat messages
+/// are synced; archives, notes, and ratings stay on the device.
+pub async fn run(conn: &Connection, config: &SyncConfig) -> Result<SyncReport> {
+    if config.endpoint.is_empty() {
+        bail!("Sync isn't set up. Run `chiron config set sync.endpoint <url>` first.");
+    }
+    let passphrase = secrets::require("sync-passphrase")?;
+
+    let remote = Remote::from_config(config)?;
+    remote.prepare().await?;
+    let key = open_key(&remote, &passphrase).await?;
+    let device_id = device_id(conn).await?;
+
+    let mut report = SyncReport::default();
+    pull(conn, &remote, &key, &device_id, &mut report).await?;
+    push(conn, &remote, &key, &device_id, &mut report).await?;
+    Ok(report)
+}
+
+/// Derives the keys from the remote's salt, setting the remote up on first
+/// use. A passphrase that doesn't match the remote's is refused.
+async fn open_key(remote: &Remote, passphrase: &str) -> Result<SyncKey> {
+    match remote.get(KEY_FILE).await? {
+        Some(bytes) => {
+            let file: KeyFile = serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {KEY_FILE}"))?;
+            if file.version != 1 {
+                bail!("{KEY_FILE} has unsupported version {}", file.version);
+            }
+            let key = SyncKey::derive(passphrase, &crypto::unhex(&file.salt)?);
+            if key.check() != file.check {
+                bail!("The sync-passphrase secret doesn't match the passphrase this remote was set up with (see `chiron secrets set sync-passphrase`)");
+            }
+            Ok(key)
+        }
+        None => {
+            let salt = crypto::random_salt()?;
+            let key = SyncKey::derive(passphrase, &salt);
+            let file = KeyFile {
+                version: 1,
+                salt: crypto::hex(&salt),
+                check: key.check(),
+            };
+            remote.put(KEY_FILE, serde_json::to_vec_pretty(&file)?).await?;
+            tracing::info!("Set up sync remote");
+            Ok(key)
+        }
+    }
+}
+
+/// This device's random ID, created on first sync.
+async fn device_id(conn: &Connection) -> Result<String> {
+    if let Some(id) = store::get_state(conn, "device_id").await? {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    store::set_state(conn, "device_id", &id).await?;
+    Ok(id)
+}
+
+fn blob_name(key: &SyncKey, session_id: &str, device_id: &str) -> String {
+    format!("{SESSIONS_DIR}{}.{device_id}.bin", key.blob_id(session_id))
+}
+
+/// The device that wrote a blob, from its name.
+fn blob_device(name: &str) -> Option<&str> {
+    let (_, device) = name.strip_prefix(SESSIONS_DIR)?.strip_suffix(".bin")?.rsplit_once('.')?;
+    Some(device)
+}
+
+/// Merges every other device's blob that changed since the last pull.
+async fn pull(
+    conn: &Connection,
+    remote: &Remote,
+    key: &SyncKey,
+    device_id: &str,
+    report: &mut SyncReport,
+) -> Result<()> {
+    for RemoteObject { name, etag } in remote.list(SESSIONS_DIR).await? {
+        match blob_device(&name) {
+            Some(device) if device != device_id => {}
+            _ => continue,
+        }
+        let etag_key = format!("etag:{name}");
+        if etag.is_some() && store::get_state(conn, &etag_key).await? == etag {
+            continue;
+        }
+        let Some(bytes) = remote.get(&name).await? else {
+            continue;
+        };
+        let blob: SessionBlob = serde_json::from_slice(&key.open(&name, &bytes)?)
+            .with_context(|| format!("Failed to parse {name}"))?;
+
+        let merged = store::merge_messages(conn, &blob.session_id, blob.messages).await?;
+        tracing::debug!(blob = %name, inserted = merged.inserted, updated = merged.updated, "Merged session");
+        report.pulled += 1;
+        report.merged.inserted += merged.inserted;
+        report.merged.updated += merged.updated;
+        if let Some(etag) = etag {
+            store::set_state(conn, &etag_key, &etag).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Uploads this device's copy of every session changed since the last push.
+async fn push(
+    conn: &Connection,
+    remote: &Remote,
+    key: &SyncKey,
+    device_id: &str,
+    report: &mut SyncReport,
+) -> Result<()> {
+    let started = store::now(conn).await?;
+    let since = store::get_state(conn, "last_push").await?;
+    for session_id in store::changed_sessions(conn, since.as_deref()).await? {
+        let blob = SessionBlob {
+            messages: store::session_messages(conn, &session_id).await?,
+            session_id,
+        };
+        let name = blob_name(key, &blob.session_id, device_id);
+        let sealed = key.seal(&name, &serde_json::to_vec(&blob)?)?;
+        remote.put(&name, sealed).await?;
+        report.pushed += 1;
+    }
+    store::set_state(conn, "last_push", &started).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blob_names_carry_the_device() {
+        let key = SyncKey::derive_with("correct horse", b"0123456789abcdef", 2);
+        let name = blob_name(&key, "session_1", "dev1");
+        assert!(name.starts_with(SESSIONS_DIR));
+        assert!(!name.contains("session_1"));
+        assert_eq!(blob_device(&name), Some("dev1"));
+        assert_eq!(blob_device("chiron-sync.json"), None);
+        assert_eq!(blob_device("sessions/notes.txt"), None);
+    }
+}
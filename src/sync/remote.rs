@@ -0,0 +1,411 @@
+use anyhow::{bail, Context, Result};
+use reqwest::{Method, StatusCode};
+use ring::{digest, hmac};
+
+use super::crypto::hex;
+use crate::config::{SyncBackend, SyncConfig};
+use crate::secrets;
+
+/// An object listed on the remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteObject {
+    /// Path relative to the endpoint, e.g. `sessions/<id>.<device>.bin`.
+    pub name: String,
+    /// Changes whenever the object is rewritten, if the server reports one.
+    pub etag: Option<String>,
+}
+
+/// A user-controlled storage endpoint. Only whole objects are read and
+/// written, so anything that speaks WebDAV or the S3 API will do.
+pub enum Remote {
+    WebDav {
+        client: reqwest::Client,
+        base_url: String,
+        /// Basic auth user and password, if set.
+        auth: Option<(String, String)>,
+    },
+    S3 {
+        client: reqwest::Client,
+        base_url: reqwest::Url,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+impl Remote {
+    /// Builds the client for `config`, reading credentials from the
+    /// environment or the OS keyring (see `secrets`).
+    pub fn from_config(config: &SyncConfig) -> Result<Self> {
+        let base_url = config.endpoint.trim_end_matches('/').to_string();
+        let client = reqwest::Client::new();
+        Ok(match config.backend {
+            SyncBackend::Webdav => {
+                let auth = match config.username.as_str() {
+                    "" => None,
+                    user => Some((user.to_string(), secrets::require("sync-password")?)),
+                };
+                Remote::WebDav { client, base_url, auth }
+            }
+            SyncBackend::S3 => Remote::S3 {
+                client,
+                base_url: reqwest::Url::parse(&base_url)
+                    .with_context(|| format!("Invalid sync endpoint '{base_url}'"))?,
+                region: config.region.clone(),
+                access_key: secrets::require("aws-access-key-id")?,
+                secret_key: secrets::require("aws-secret-access-key")?,
+            },
+        })
+    }
+
+    /// Creates the `sessions/` collection on WebDAV servers, which don't
+    /// create parent directories on PUT.
+    pub async fn prepare(&self) -> Result<()> {
+        if let Remote::WebDav { .. } = self {
+            let response = self
+                .webdav(Method::from_bytes(b"MKCOL").expect("valid method"), "sessions/")
+                .send()
+                .await
+                .context("MKCOL request failed")?;
+            // 405: the collection already exists
+            if !response.status().is_success() && response.status() != StatusCode::METHOD_NOT_ALLOWED {
+                bail!("Failed to create sessions/ on the sync server: {}", response.status());
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads an object, or `None` if it doesn't exist.
+    pub async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let request = match self {
+            Remote::WebDav { .. } => self.webdav(Method::GET, name),
+            Remote::S3 { .. } => self.s3(Method::GET, name, &[], &[])?,
+        };
+        let response = request.send().await.with_context(|| format!("GET {name} failed"))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            bail!("GET {name} failed: {}", response.status());
+        }
+        let body = response.bytes().await.with_context(|| format!("Failed to read {name}"))?;
+        Ok(Some(body.to_vec()))
+    }
+
+    /// Uploads an object, replacing any previous version.
+    pub async fn put(&self, name: &str, body: Vec<u8>) -> Result<()> {
+        let request = match self {
+            Remote::WebDav { .. } => self.webdav(Method::PUT, name).body(body),
+            Remote::S3 { .. } => self.s3(Method::PUT, name, &[], &body)?.body(body),
+        };
+        let response = request.send().await.with_context(|| format!("PUT {name} failed"))?;
+        if !response.status().is_success() {
+            bail!("PUT {name} failed: {}", response.status());
+        }
+        Ok(())
+    }
+
+    /// Lists the objects directly under `dir` (e.g. `sessions/`).
+    pub async fn list(&self, dir: &str) -> Result<Vec<RemoteObject>> {
+        match self {
+            Remote::WebDav { .. } => {
+                let response = self
+                    .webdav(Method::from_bytes(b"PROPFIND").expect("valid method"), dir)
+                    .header("Depth", "1")
+                    .send()
+                    .await
+                    .with_context(|| format!("PROPFIND {dir} failed"))?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(Vec::new());
+                }
+                if !response.status().is_success() {
+                    bail!("PROPFIND {dir} failed: {}", response.status());
+                }
+                let xml = response.text().await.context("Failed to read PROPFIND response")?;
+                Ok(parse_propfind(&xml, dir))
+            }
+            Remote::S3 { .. } => {
+                let mut objects = Vec::new();
+                let mut token: Option<String> = None;
+                loop {
+                    let mut query = vec![("list-type", "2".to_string()), ("prefix", dir.to_string())];
+                    if let Some(token) = &token {
+                        query.push(("continuation-token", token.clone()));
+                    }
+                    let response = self
+                        .s3(Method::GET, "", &query, &[])?
+                        .send()
+                        .await
+                        .with_context(|| format!("Listing {dir} failed"))?;
+                    if !response.status().is_success() {
+                        bail!("Listing {dir} failed: {}", response.status());
+                    }
+                    let xml = response.text().await.context("Failed to read bucket listing")?;
+                    objects.extend(parse_list_objects(&xml));
+                    token = match xml_value(&xml, "IsTruncated").as_deref() {
+                        Some("true") => xml_value(&xml, "NextContinuationToken"),
+                        _ => None,
+                    };
+                    if token.is_none() {
+                        return Ok(objects);
+                    }
+                }
+            }
+        }
+    }
+
+    fn webdav(&self, method: Method, name: &str) -> reqwest::RequestBuilder {
+        let Remote::WebDav { client, base_url, auth } = self else {
+            unreachable!("WebDAV request on an S3 remote");
+        };
+        let request = client.request(method, format!("{base_url}/{name}"));
+        match auth {
+            Some((user, password)) => request.basic_auth(user, Some(password)),
+            None => request,
+        }
+    }
+
+    /// Builds a path-style S3 request signed with AWS Signature Version 4.
+    fn s3(&self, method: Method, key: &str, query: &[(&str, String)], body: &[u8]) -> Result<reqwest::RequestBuilder> {
+        let Remote::S3 { client, base_url, region, access_key, secret_key } = self else {
+            unreachable!("S3 request on a WebDAV remote");
+        };
+        let host = match base_url.port() {
+            Some(port) => format!("{}:{port}", base_url.host_str().unwrap_or_default()),
+            None => base_url.host_str().unwrap_or_default().to_string(),
+        };
+        let path = match key {
+            "" => format!("{}/", base_url.path().trim_end_matches('/')),
+            key => format!("{}/{}", base_url.path().trim_end_matches('/'), uri_encode(key, false)),
+        };
+        let mut query: Vec<String> = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+        query.sort();
+        let query = query.join("&");
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex(digest::digest(&digest::SHA256, body).as_ref());
+        let authorization = sigv4_authorization(&SigV4 {
+            method: method.as_str(),
+            host: &host,
+            path: &path,
+            query: &query,
+            payload_hash: &payload_hash,
+            amz_date: &amz_date,
+            region,
+            access_key,
+            secret_key,
+        });
+
+        let mut url = format!("{}://{host}{path}", base_url.scheme());
+        if !query.is_empty() {
+            url = format!("{url}?{query}");
+        }
+        Ok(client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("Authorization", authorization))
+    }
+}
+
+/// The parts of an S3 request covered by its signature.
+struct SigV4<'a> {
+    method: &'a str,
+    host: &'a str,
+    /// URI-encoded path.
+    path: &'a str,
+    /// Sorted, URI-encoded query string.
+    query: &'a str,
+    payload_hash: &'a str,
+    /// `YYYYMMDDTHHMMSSZ`
+    amz_date: &'a str,
+    region: &'a str,
+    access_key: &'a str,
+    secret_key: &'a str,
+}
+
+fn sigv4_authorization(req: &SigV4) -> String {
+    let date = &req.amz_date[..8];
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{signed_headers}\n{}",
+        req.method, req.path, req.query, req.host, req.payload_hash, req.amz_date, req.payload_hash
+    );
+    let scope = format!("{date}/{}/s3/aws4_request", req.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        req.amz_date,
+        hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+    );
+    let key = signing_key(req.secret_key, date, req.region, "s3");
+    let signature = hex(hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), string_to_sign.as_bytes()).as_ref());
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        req.access_key
+    )
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let mut key = format!("AWS4{secret_key}").into_bytes();
+    for part in [date, region, service, "aws4_request"] {
+        key = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), part.as_bytes())
+            .as_ref()
+            .to_vec();
+    }
+    key
+}
+
+/// Percent-encodes everything but unreserved characters (and `/` unless
+/// `encode_slash`), as SigV4 requires.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+/// Files in a PROPFIND `multistatus`, named relative to the endpoint.
+fn parse_propfind(xml: &str, dir: &str) -> Vec<RemoteObject> {
+    xml_blocks(xml, "response")
+        .into_iter()
+        .filter_map(|response| {
+            let href = xml_value(response, "href")?;
+            // The collection itself is listed too
+            if href.ends_with('/') {
+                return None;
+            }
+            let file = href.rsplit('/').next()?;
+            Some(RemoteObject {
+                name: format!("{dir}{file}"),
+                etag: xml_value(response, "getetag"),
+            })
+        })
+        .collect()
+}
+
+/// Objects in a ListObjectsV2 response.
+fn parse_list_objects(xml: &str) -> Vec<RemoteObject> {
+    xml_blocks(xml, "Contents")
+        .into_iter()
+        .filter_map(|contents| {
+            Some(RemoteObject {
+                name: xml_value(contents, "Key")?,
+                etag: xml_value(contents, "ETag"),
+            })
+        })
+        .collect()
+}
+
+/// The contents of each `<tag>` element, ignoring namespace prefixes
+/// (`<d:response>` matches `response`). Enough for the flat responses
+/// WebDAV and S3 send; not a general XML parser.
+fn xml_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = xml;
+    while let Some((_, content_start)) = find_tag(rest, tag, false) {
+        let body = &rest[content_start..];
+        let Some((end, after)) = find_tag(body, tag, true) else {
+            break;
+        };
+        blocks.push(&body[..end]);
+        rest = &body[after..];
+    }
+    blocks
+}
+
+/// Position of the next opening (or closing) `tag`: the index of its `<`
+/// and the index just past its `>`. Self-closing elements are skipped.
+fn find_tag(xml: &str, tag: &str, closing: bool) -> Option<(usize, usize)> {
+    for (start, _) in xml.match_indices('<') {
+        let mut name = &xml[start + 1..];
+        if closing {
+            let Some(stripped) = name.strip_prefix('/') else {
+                continue;
+            };
+            name = stripped;
+        }
+        let name_end = name.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+        let local = name[..name_end].rsplit(':').next().unwrap_or_default();
+        if local != tag {
+            continue;
+        }
+        let end = start + xml[start..].find('>')?;
+        if !closing && xml[..end].ends_with('/') {
+            continue;
+        }
+        return Some((start, end + 1));
+    }
+    None
+}
+
+/// Trimmed, unescaped text of the first `<tag>` element.
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let value = xml_blocks(xml, tag).into_iter().next()?.trim();
+    Some(
+        value
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_propfind_skips_the_collection() {
+        let xml = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response><d:href>/dav/chiron/sessions/</d:href><d:propstat><d:prop/></d:propstat></d:response>
+              <d:response>
+                <d:href>/dav/chiron/sessions/ab12.dev1.bin</d:href>
+                <d:propstat><d:prop><d:getetag>&quot;e1&quot;</d:getetag></d:prop></d:propstat>
+              </d:response>
+              <D:response xmlns:D="DAV:"><D:href>/dav/chiron/sessions/cd34.dev2.bin</D:href></D:response>
+            </d:multistatus>"#;
+        assert_eq!(
+            parse_propfind(xml, "sessions/"),
+            vec![
+                RemoteObject { name: "sessions/ab12.dev1.bin".to_string(), etag: Some("\"e1\"".to_string()) },
+                RemoteObject { name: "sessions/cd34.dev2.bin".to_string(), etag: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_list_objects() {
+        let xml = r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <IsTruncated>true</IsTruncated>
+            <Contents><Key>sessions/ab12.dev1.bin</Key><ETag>&quot;9b2c&quot;</ETag></Contents>
+            <NextContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</NextContinuationToken>
+        </ListBucketResult>"#;
+        assert_eq!(
+            parse_list_objects(xml),
+            vec![RemoteObject { name: "sessions/ab12.dev1.bin".to_string(), etag: Some("\"9b2c\"".to_string()) }]
+        );
+        assert_eq!(xml_value(xml, "IsTruncated").as_deref(), Some("true"));
+        assert_eq!(
+            uri_encode(&xml_value(xml, "NextContinuationToken").unwrap(), true),
+            "1ueGcxLPRx1Tr%2FXYExHnhbYLgveDs2J%2Fwm36Hy4vbOwM%3D"
+        );
+    }
+
+    #[test]
+    fn test_sigv4_signing_key_matches_aws_example() {
+        // From the AWS "derive a signing key" documentation
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+}
@@ -0,0 +1,212 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rig::completion::Prompt;
+use serde::Deserialize;
+
+use crate::engine::ChironEngine;
+use crate::orchestrator::OutputSink;
+use crate::progress::Progress;
+use crate::provider::LlamaCppCompletionModel;
+
+/// Client lines are a sentence or three.
+const CLIENT_MAX_TOKENS: u64 = 128;
+
+/// Varied enough that dialogues with the same persona differ.
+const CLIENT_TEMPERATURE: f64 = 0.9;
+
+const CLIENT_PREAMBLE: &str = "You role-play a person talking with a peer-support coach, to create practice \
+dialogues. Stay in character as the person described below. Reply with only what they would say next: one to \
+three sentences, first person, no narration, no stage directions, and never speak as the coach.";
+
+/// A simulated client for `chiron synth`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Persona {
+    pub id: String,
+    /// Who they are and what they bring, given to the model playing them.
+    pub description: String,
+    /// Their first message.
+    pub opening: String,
+    /// Scripted follow-ups, used in order instead of model-written lines
+    /// (needed with `--mock`, whose replies are canned coach text).
+    #[serde(default)]
+    pub messages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PersonaCatalog {
+    #[serde(rename = "persona")]
+    pub personas: Vec<Persona>,
+}
+
+impl PersonaCatalog {
+    /// Loads personas from a TOML file of `[[persona]]` tables.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let catalog: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        anyhow::ensure!(!catalog.personas.is_empty(), "No personas in {}", path.display());
+        Ok(catalog)
+    }
+}
+
+/// One simulated conversation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialogue {
+    pub persona: String,
+    /// `(role, content)` in order, starting with the client.
+    pub messages: Vec<(String, String)>,
+    /// Coach prompt variants that replied, in first-use order.
+    pub prompt_variants: Vec<String>,
+}
+
+impl Dialogue {
+    /// JSONL line in the chat layout of `chiron export`,
+    /// marked synthetic.
+    pub fn to_json(&self) -> serde_json::Value {
+        let messages: Vec<serde_json::Value> = self
+            .messages
+            .iter()
+            .map(|(role, content)| serde_json::json!({ "role": role, "content": content }))
+            .collect();
+        serde_json::json!({
+            "messages": messages,
+            "synthetic": true,
+            "persona": self.persona,
+            "prompt_variants": self.prompt_variants,
+        })
+    }
+}
+
+/// Runs `n` dialogues of up to `turns` exchanges, cycling through the
+/// personas, each in its own scratch session of `engine`. A dialogue ends
+/// early at a crisis response, which is fixed text and left out.
+pub async fn run(
+    engine: &ChironEngine,
+    client_model: &LlamaCppCompletionModel,
+    personas: &[Persona],
+    n: usize,
+    turns: usize,
+) -> Result<Vec<Dialogue>> {
+    let mut dialogues = Vec::with_capacity(n);
+    let progress = Progress::new("Dialogue", n);
+    for i in 0..n {
+        let persona = &personas[i % personas.len()];
+        progress.start(i, &persona.id);
+        let session_id = engine.start_session("synth").await;
+        let session = engine.session(&session_id).await?;
+        let mut dialogue = Dialogue {
+            persona: persona.id.clone(),
+            messages: Vec::new(),
+            prompt_variants: Vec::new(),
+        };
+        {
+            let mut orchestrator = session.lock().await;
+            orchestrator.set_output(OutputSink::Silent);
+            let _following = progress.follow(orchestrator.subscribe());
+            for turn in 0..turns {
+                let input = match turn {
+                    0 => persona.opening.clone(),
+                    _ => match persona.messages.get(turn - 1) {
+                        Some(message) => message.clone(),
+                        None if persona.messages.is_empty() => {
+                            client_line(client_model, persona, &dialogue.messages).await?
+                        }
+                        // The script ran out
+                        None => break,
+                    },
+                };
+                let result = orchestrator
+                    .run_turn_captured(&input)
+                    .await
+                    .with_context(|| format!("Dialogue {} turn {} failed", i + 1, turn + 1))?;
+                let Some(variant) = result.prompt_variant else {
+                    break;
+                };
+                if !dialogue.prompt_variants.contains(&variant) {
+                    dialogue.prompt_variants.push(variant);
+                }
+                dialogue.messages.push(("user".to_string(), input));
+                dialogue.messages.push(("assistant".to_string(), result.response));
+            }
+            orchestrator.flush_saves().await.context("Failed to save the dialogue")?;
+        }
+        engine.sessions.lock().await.remove(&session_id);
+        if !dialogue.messages.is_empty() {
+            dialogues.push(dialogue);
+        }
+    }
+    progress.finish();
+    Ok(dialogues)
+}
+
+/// The persona's next message, written by the model from the dialogue so far.
+async fn client_line(
+    model: &LlamaCppCompletionModel,
+    persona: &Persona,
+    messages: &[(String, String)],
+) -> Result<String> {
+    let agent = rig::agent::AgentBuilder::new(model.clone())
+        .preamble(&format!("{CLIENT_PREAMBLE}\n\nThe person: {}", persona.description))
+        .temperature(CLIENT_TEMPERATURE)
+        .max_tokens(CLIENT_MAX_TOKENS)
+        .build();
+    let answer = agent
+        .prompt(transcript(messages).as_str())
+        .await
+        .with_context(|| format!("Client simulation failed for persona '{}'", persona.id))?;
+    Ok(clean_line(&answer))
+}
+
+/// The dialogue so far, ending with a cue for the person's next line.
+fn transcript(messages: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (role, content) in messages {
+        let speaker = if role == "user" { "Person" } else { "Coach" };
+        out.push_str(&format!("{speaker}: {content}\n"));
+    }
+    out.push_str("Person:");
+    out
+}
+
+/// Drops a speaker label or quotes the model added around the line.
+fn clean_line(answer: &str) -> String {
+    let line = answer.trim();
+    let line = line.strip_prefix("Person:").unwrap_or(line).trim();
+    line.trim_matches('"').trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_load_example_personas() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("prompts/personas.toml");
+        let catalog = PersonaCatalog::load(&path).unwrap();
+        assert!(catalog.personas.len() >= 3);
+        assert!(catalog.personas.iter().all(|p| !p.opening.is_empty() && !p.messages.is_empty()));
+    }
+
+    #[test]
+    fn test_transcript_and_json() {
+        let messages = vec![
+            ("user".to_string(), "Work is a lot.".to_string()),
+            ("assistant".to_string(), "It sounds heavy.".to_string()),
+        ];
+        assert_eq!(transcript(&messages), "Person: Work is a lot.\nCoach: It sounds heavy.\nPerson:");
+        assert_eq!(clean_line(" Person: \"It is.\" "), "It is.");
+
+        let dialogue = Dialogue {
+            persona: "burnout".to_string(),
+            messages,
+            prompt_variants: vec!["v1".to_string()],
+        };
+        let json = dialogue.to_json();
+        assert_eq!(json["synthetic"], true);
+        assert_eq!(json["messages"][1]["role"], "assistant");
+    }
+}
@@ -0,0 +1,392 @@
+//! Logging setup: human-readable logs on the terminal, plus optional JSON
+//! lines in a file (`--log-file`) for later analysis.
+//!
+//! Each record in the file carries the fields of the spans around it, so
+//! everything logged during a turn shares that turn's `turn_id`. Spans are
+//! written when they close, with how long they took (`elapsed_ms`).
+//!
+//! Both outputs apply a `ContentPolicy` to fields that can hold what the
+//! person wrote or what the coach said: by default only their length is
+//! logged. Message text belongs in those fields, never in the log message
+//! itself.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use anyhow::{Context as _, Result};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::field::{MakeVisitor, VisitFmt, VisitOutput};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Fields that can carry conversation text (messages, replies, model
+/// output, and the themes read from it).
+const CONTENT_FIELDS: &[&str] =
+    &["input", "response", "content", "text", "query", "body", "answer", "themes", "theme"];
+
+/// How conversation text in `CONTENT_FIELDS` appears in logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentPolicy {
+    /// Length only (the default).
+    Redact,
+    /// Verbatim (`--log-content`), for local debugging only.
+    Include,
+}
+
+impl ContentPolicy {
+    fn applies_to(self, field: &Field) -> bool {
+        self == Self::Redact && CONTENT_FIELDS.contains(&field.name())
+    }
+}
+
+/// Keeps only the length, still enough to spot outliers. No hash: short
+/// messages could be recovered from one by guessing.
+fn redact(text: &str) -> String {
+    format!("[redacted: {} chars]", text.chars().count())
+}
+
+/// Level written to the log file unless `RUST_LOG` says otherwise.
+const FILE_LEVEL: &str = "info,llama_cpp_2=warn";
+
+/// Installs the global subscriber: terminal logs at `warn` (`info` with
+/// `--verbose`), and with `log_file`, JSON lines at `info`. `RUST_LOG`
+/// overrides both levels.
+pub fn init(verbose: bool, log_file: Option<&Path>, content: ContentPolicy) -> Result<()> {
+    let default_level = if verbose { "info" } else { "warn" };
+    let terminal = tracing_subscriber::fmt::layer()
+        .fmt_fields(PolicyFields(content))
+        .with_filter(env_filter(default_level));
+    let file = match log_file {
+        Some(path) => Some(JsonFileLayer::create(path, content)?.with_filter(env_filter(FILE_LEVEL))),
+        None => None,
+    };
+    tracing_subscriber::registry().with(terminal).with(file).init();
+
+    if content == ContentPolicy::Include {
+        eprintln!(
+            "\x1b[1;31mWARNING: --log-content is on. Logs will contain everything said in sessions. \
+             Use it only for local debugging, and delete the logs afterwards.\x1b[0m"
+        );
+        tracing::warn!("Logging message content (--log-content)");
+    }
+    Ok(())
+}
+
+fn env_filter(default: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+}
+
+/// Writes events and closed spans as JSON lines.
+pub struct JsonFileLayer {
+    file: Mutex<File>,
+    content: ContentPolicy,
+}
+
+/// What a span has recorded so far, kept in its extensions.
+struct SpanData {
+    fields: Map<String, Value>,
+    started: Instant,
+}
+
+impl JsonFileLayer {
+    /// Appends to `path`, creating it if needed.
+    pub fn create(path: &Path, content: ContentPolicy) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        Ok(Self { file: Mutex::new(file), content })
+    }
+
+    fn write(&self, record: Map<String, Value>) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{}", Value::Object(record));
+        }
+    }
+}
+
+/// The fields every line starts with.
+fn base_record(kind: &str, metadata: &Metadata<'_>) -> Map<String, Value> {
+    let mut record = Map::new();
+    record.insert(
+        "timestamp".into(),
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true).into(),
+    );
+    record.insert("kind".into(), kind.into());
+    record.insert("level".into(), metadata.level().as_str().into());
+    record.insert("target".into(), metadata.target().into());
+    record
+}
+
+/// Adds the enclosing spans, outermost first, and lifts the turn's
+/// correlation ID to the top level.
+fn add_scope<'a, S>(record: &mut Map<String, Value>, scope: impl Iterator<Item = SpanRef<'a, S>>)
+where
+    S: LookupSpan<'a>,
+{
+    let mut spans = Vec::new();
+    for span in scope {
+        let mut entry = Map::new();
+        entry.insert("name".into(), span.name().into());
+        if let Some(data) = span.extensions().get::<SpanData>() {
+            if let Some(turn_id) = data.fields.get("turn_id") {
+                record.insert("turn_id".into(), turn_id.clone());
+            }
+            entry.extend(data.fields.clone());
+        }
+        spans.push(Value::Object(entry));
+    }
+    if !spans.is_empty() {
+        record.insert("spans".into(), Value::Array(spans));
+    }
+}
+
+impl<S> Layer<S> for JsonFileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor { fields: &mut fields, content: self.content });
+        span.extensions_mut().insert(SpanData { fields, started: Instant::now() });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+            values.record(&mut JsonVisitor { fields: &mut data.fields, content: self.content });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut record = base_record("event", event.metadata());
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor { fields: &mut fields, content: self.content });
+        record.insert("fields".into(), Value::Object(fields));
+        if let Some(scope) = ctx.event_scope(event) {
+            add_scope(&mut record, scope.from_root());
+        }
+        self.write(record);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let mut record = base_record("span", span.metadata());
+        record.insert("name".into(), span.name().into());
+        if let Some(data) = span.extensions().get::<SpanData>() {
+            record.insert("elapsed_ms".into(), (data.started.elapsed().as_millis() as u64).into());
+        }
+        // The closing span itself is the innermost entry
+        add_scope(&mut record, span.scope().from_root());
+        self.write(record);
+    }
+}
+
+/// Collects span and event fields as JSON under the content policy.
+struct JsonVisitor<'a> {
+    fields: &'a mut Map<String, Value>,
+    content: ContentPolicy,
+}
+
+impl JsonVisitor<'_> {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = if !self.content.applies_to(field) {
+            value
+        } else if let Value::String(text) = &value {
+            redact(text).into()
+        } else {
+            redact(&value.to_string()).into()
+        };
+        self.fields.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+/// Formats terminal fields like the default `fmt` formatter
+/// (`name=value`, space-separated) under the content policy.
+#[derive(Clone, Copy)]
+struct PolicyFields(ContentPolicy);
+
+impl<'a> MakeVisitor<Writer<'a>> for PolicyFields {
+    type Visitor = PolicyFieldVisitor<'a>;
+
+    fn make_visitor(&self, writer: Writer<'a>) -> Self::Visitor {
+        PolicyFieldVisitor { writer, content: self.0, result: Ok(()), first: true }
+    }
+}
+
+struct PolicyFieldVisitor<'a> {
+    writer: Writer<'a>,
+    content: ContentPolicy,
+    result: fmt::Result,
+    first: bool,
+}
+
+impl PolicyFieldVisitor<'_> {
+    fn write(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        let separator = if std::mem::take(&mut self.first) { "" } else { " " };
+        self.result = match field.name() {
+            "message" => write!(self.writer, "{separator}{value:?}"),
+            name => write!(self.writer, "{separator}{name}={value:?}"),
+        };
+    }
+}
+
+impl Visit for PolicyFieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.content.applies_to(field) {
+            self.write(field, &format_args!("{}", redact(value)));
+        } else {
+            self.write(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.content.applies_to(field) {
+            self.write(field, &format_args!("{}", redact(&format!("{value:?}"))));
+        } else {
+            self.write(field, value);
+        }
+    }
+}
+
+impl VisitOutput<fmt::Result> for PolicyFieldVisitor<'_> {
+    fn finish(self) -> fmt::Result {
+        self.result
+    }
+}
+
+impl VisitFmt for PolicyFieldVisitor<'_> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        &mut self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_log_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chiron.log");
+        let layer = JsonFileLayer::create(&path, ContentPolicy::Redact).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let turn = tracing::info_span!("turn", turn_id = "t1", input = "I feel awful");
+            let _turn = turn.enter();
+            tracing::info_span!("inference", tokens_generated = tracing::field::Empty).in_scope(|| {
+                tracing::Span::current().record("tokens_generated", 42);
+                tracing::info!(response = "That sounds hard.", "Generation complete");
+            });
+        });
+
+        let lines: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3, "event, inference span, turn span");
+
+        let event = &lines[0];
+        assert_eq!(event["kind"], "event");
+        assert_eq!(event["turn_id"], "t1");
+        assert_eq!(event["fields"]["message"], "Generation complete");
+        assert_eq!(event["fields"]["response"], "[redacted: 17 chars]");
+        assert_eq!(event["spans"][0]["input"], redact("I feel awful").as_str());
+        assert_eq!(event["spans"][1]["tokens_generated"], 42);
+
+        let inference = &lines[1];
+        assert_eq!((inference["kind"].as_str(), inference["name"].as_str()), (Some("span"), Some("inference")));
+        assert_eq!(inference["turn_id"], "t1");
+        assert!(inference["elapsed_ms"].is_u64());
+        assert_eq!(lines[2]["name"], "turn");
+    }
+
+    /// Terminal output for one event, formatted with `PolicyFields`.
+    fn terminal_line(content: ContentPolicy) -> String {
+        let output = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let writer = output.clone();
+        let layer = tracing_subscriber::fmt::layer()
+            .fmt_fields(PolicyFields(content))
+            .without_time()
+            .with_ansi(false)
+            .with_writer(move || SharedBuffer(writer.clone()));
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::warn!(input = "my sister called", themes = "family", attempts = 2, "No mock response");
+        });
+        let bytes = output.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    struct SharedBuffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_terminal_redacts_content_unless_included() {
+        let redacted = terminal_line(ContentPolicy::Redact);
+        assert!(redacted.contains("No mock response input=[redacted: 16 chars]"), "{redacted}");
+        assert!(redacted.contains("attempts=2"), "{redacted}");
+        assert!(!redacted.contains("sister"), "{redacted}");
+        assert!(redacted.contains("themes=[redacted: 6 chars]"), "{redacted}");
+
+        let included = terminal_line(ContentPolicy::Include);
+        assert!(included.contains("input=\"my sister called\""), "{included}");
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, NaiveDate};
+use rig::embeddings::EmbeddingModel as _;
+use rig_fastembed::EmbeddingModel;
+use tokio_rusqlite::Connection;
+
+use crate::memory::case_notes::{self, CaseNote};
+use crate::supervision::{extract_themes, merge_themes};
+use crate::timezone;
+
+/// Theme tags at least this similar (cosine) are counted as one theme, so
+/// "work stress", "job pressure", and "deadlines at work" group together.
+const SIMILARITY: f64 = 0.85;
+
+/// Themes embedded per call.
+const EMBED_BATCH: usize = 256;
+
+/// Themes listed per week.
+const TOP_PER_WEEK: usize = 3;
+
+/// A theme the coach tagged on a given day.
+#[derive(Debug, Clone, PartialEq)]
+struct Mention {
+    day: NaiveDate,
+    theme: String,
+}
+
+/// Runs `chiron themes`: groups the themes tagged in case notes over the
+/// last `days` by meaning and prints the dominant ones week by week.
+pub async fn run(conn: &Connection, embedding_model: &EmbeddingModel, days: u32, today: NaiveDate) -> Result<()> {
+    let start = today - Duration::days(days as i64 - 1);
+    // From the beginning, so the first note in the window is compared with
+    // the one before it
+    let notes = case_notes::case_notes_since(conn, "0000-01-01").await?;
+    let mentions: Vec<Mention> = mentions(&notes).into_iter().filter(|m| m.day >= start).collect();
+    if mentions.is_empty() {
+        println!("No themes recorded in the last {days} days.");
+        return Ok(());
+    }
+
+    let mut distinct: Vec<String> = mentions.iter().map(|m| m.theme.clone()).collect();
+    distinct.sort();
+    distinct.dedup();
+    let mut vectors: Vec<Vec<f64>> = Vec::with_capacity(distinct.len());
+    for batch in distinct.chunks(EMBED_BATCH) {
+        let embeddings = embedding_model
+            .embed_texts(batch.to_vec())
+            .await
+            .context("Failed to embed themes")?;
+        vectors.extend(embeddings.into_iter().map(|e| e.vec));
+    }
+    let clusters = cluster(&distinct, &vectors, &mentions);
+
+    print!("{}", render(&mentions, &clusters, start, today));
+    Ok(())
+}
+
+/// The themes each case note newly raised, or brought back to the front.
+/// Notes carry a running list with this turn's themes first (see
+/// `merge_themes`), so the new ones are the shortest prefix that rebuilds
+/// the list from the previous note's.
+fn mentions(notes: &[CaseNote]) -> Vec<Mention> {
+    let mut previous: Vec<String> = Vec::new();
+    let mut mentions = Vec::new();
+    for note in notes {
+        let current = extract_themes(&note.content).unwrap_or_default();
+        let raised = (0..=current.len())
+            .map(|k| &current[..k])
+            .find(|prefix| merge_themes(&previous, prefix, current.len()) == current)
+            .unwrap_or(&current[..]);
+        if let Some(day) = timezone::date(&note.created_at) {
+            mentions.extend(raised.iter().map(|theme| Mention {
+                day,
+                theme: theme.clone(),
+            }));
+        }
+        previous = current;
+    }
+    mentions
+}
+
+/// A group of theme tags with similar meanings, named after its most
+/// mentioned tag.
+#[derive(Debug, Clone, PartialEq)]
+struct Cluster {
+    name: String,
+    members: Vec<String>,
+}
+
+/// Groups themes greedily, most mentioned first: each joins the first
+/// cluster whose name it's similar enough to, or starts its own.
+fn cluster(themes: &[String], vectors: &[Vec<f64>], mentions: &[Mention]) -> Vec<Cluster> {
+    let count = |theme: &str| mentions.iter().filter(|m| m.theme == theme).count();
+    let mut order: Vec<usize> = (0..themes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(count(&themes[i])));
+
+    let mut clusters: Vec<(usize, Cluster)> = Vec::new();
+    for i in order {
+        match clusters
+            .iter_mut()
+            .find(|(lead, _)| cosine(&vectors[*lead], &vectors[i]) >= SIMILARITY)
+        {
+            Some((_, cluster)) => cluster.members.push(themes[i].clone()),
+            None => clusters.push((
+                i,
+                Cluster {
+                    name: themes[i].clone(),
+                    members: vec![themes[i].clone()],
+                },
+            )),
+        }
+    }
+    clusters.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Cosine similarity of two embeddings; 0 if either is all zeros.
+pub fn cosine(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Monday of the week `day` falls in.
+fn week_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// One line per week from `start` to `today` with its top themes, then
+/// each theme's total and the tags grouped under it.
+fn render(mentions: &[Mention], clusters: &[Cluster], start: NaiveDate, today: NaiveDate) -> String {
+    let cluster_of: HashMap<&str, usize> = clusters
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| c.members.iter().map(move |m| (m.as_str(), i)))
+        .collect();
+
+    let mut out = String::new();
+    let days = (today - start).num_days() + 1;
+    let _ = writeln!(out, "Themes, last {days} days (mentions per week)\n");
+
+    let mut totals = vec![0usize; clusters.len()];
+    let mut week = week_of(start);
+    while week <= today {
+        let mut counts = vec![0usize; clusters.len()];
+        for m in mentions.iter().filter(|m| week_of(m.day) == week) {
+            counts[cluster_of[m.theme.as_str()]] += 1;
+        }
+        let mut ranked: Vec<usize> = (0..clusters.len()).filter(|&i| counts[i] > 0).collect();
+        ranked.sort_by_key(|&i| std::cmp::Reverse(counts[i]));
+        let top: Vec<String> = ranked
+            .iter()
+            .take(TOP_PER_WEEK)
+            .map(|&i| format!("{} ({})", clusters[i].name, counts[i]))
+            .collect();
+        let line = if top.is_empty() { "-".to_string() } else { top.join(", ") };
+        let _ = writeln!(out, "Week of {week}  {line}");
+        for (total, count) in totals.iter_mut().zip(counts) {
+            *total += count;
+        }
+        week += Duration::days(7);
+    }
+
+    let _ = writeln!(out, "\nOverall");
+    let mut ranked: Vec<usize> = (0..clusters.len()).collect();
+    ranked.sort_by_key(|&i| std::cmp::Reverse(totals[i]));
+    for i in ranked {
+        let others: Vec<&str> = clusters[i]
+            .members
+            .iter()
+            .filter(|m| **m != clusters[i].name)
+            .map(String::as_str)
+            .collect();
+        let _ = write!(out, "  {:<24} {:>3}", clusters[i].name, totals[i]);
+        if !others.is_empty() {
+            let _ = write!(out, "  (also: {})", others.join(", "));
+        }
+        let _ = writeln!(out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(day: &str, themes: &str) -> CaseNote {
+        CaseNote {
+            session_id: "s1".to_string(),
+            mi_stage: None,
+            content: format!("MI Stage: engage\nRunning Themes: {themes}"),
+            created_at: format!("{day} 10:00:00"),
+        }
+    }
+
+    fn day(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_mentions_count_newly_raised_themes() {
+        let notes = [
+            note("2026-09-07", "work stress, sleep"),
+            note("2026-09-08", "work stress, sleep"),
+            note("2026-09-09", "sleep, work stress"),
+            note("2026-09-10", "deadlines, sleep, work stress"),
+        ];
+        let themes: Vec<(String, String)> = mentions(&notes)
+            .into_iter()
+            .map(|m| (m.day.to_string(), m.theme))
+            .collect();
+        assert_eq!(
+            themes,
+            vec![
+                ("2026-09-07".to_string(), "work stress".to_string()),
+                ("2026-09-07".to_string(), "sleep".to_string()),
+                ("2026-09-09".to_string(), "sleep".to_string()),
+                ("2026-09-10".to_string(), "deadlines".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cluster_and_render_weekly_timeline() {
+        let themes = vec!["deadlines".to_string(), "sleep".to_string(), "work stress".to_string()];
+        let vectors = vec![vec![0.9, 0.1], vec![0.0, 1.0], vec![1.0, 0.0]];
+        let mentions = vec![
+            Mention { day: day("2026-09-07"), theme: "work stress".to_string() },
+            Mention { day: day("2026-09-08"), theme: "work stress".to_string() },
+            Mention { day: day("2026-09-09"), theme: "sleep".to_string() },
+            Mention { day: day("2026-09-29"), theme: "deadlines".to_string() },
+        ];
+        let clusters = cluster(&themes, &vectors, &mentions);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].name, "work stress");
+        assert_eq!(clusters[0].members, vec!["work stress", "deadlines"]);
+
+        let out = render(&mentions, &clusters, day("2026-09-07"), day("2026-09-30"));
+        assert!(out.starts_with("Themes, last 24 days"), "{out}");
+        assert!(out.contains("Week of 2026-09-07  work stress (2), sleep (1)"), "{out}");
+        assert!(out.contains("Week of 2026-09-14  -"), "{out}");
+        assert!(out.contains("Week of 2026-09-28  work stress (1)"), "{out}");
+        assert!(out.contains("(also: deadlines)"), "{out}");
+    }
+}
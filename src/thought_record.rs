@@ -0,0 +1,175 @@
+use crate::i18n::t;
+use crate::memory::thought_records::ThoughtRecord;
+
+/// Steps of a CBT thought record, in the order they must be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Situation,
+    AutomaticThought,
+    Emotion,
+    IntensityBefore,
+    EvidenceFor,
+    EvidenceAgainst,
+    BalancedThought,
+    IntensityAfter,
+}
+
+impl Step {
+    /// The step that follows this one, or `None` after the re-rating.
+    fn next(self) -> Option<Step> {
+        use Step::*;
+        Some(match self {
+            Situation => AutomaticThought,
+            AutomaticThought => Emotion,
+            Emotion => IntensityBefore,
+            IntensityBefore => EvidenceFor,
+            EvidenceFor => EvidenceAgainst,
+            EvidenceAgainst => BalancedThought,
+            BalancedThought => IntensityAfter,
+            IntensityAfter => return None,
+        })
+    }
+
+    fn prompt_key(self) -> &'static str {
+        match self {
+            Step::Situation => "thought_record.situation",
+            Step::AutomaticThought => "thought_record.automatic_thought",
+            Step::Emotion => "thought_record.emotion",
+            Step::IntensityBefore => "thought_record.intensity_before",
+            Step::EvidenceFor => "thought_record.evidence_for",
+            Step::EvidenceAgainst => "thought_record.evidence_against",
+            Step::BalancedThought => "thought_record.balanced_thought",
+            Step::IntensityAfter => "thought_record.intensity_after",
+        }
+    }
+}
+
+/// Result of answering the current step.
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+    /// Answer accepted; ask the next question.
+    Next(&'static str),
+    /// Answer rejected; the step is repeated with this message.
+    Invalid(&'static str),
+    /// All steps answered.
+    Done(ThoughtRecord),
+}
+
+/// A guided thought record in progress (situation → automatic thought →
+/// emotion/intensity → evidence → balanced thought → re-rate).
+///
+/// Steps can't be skipped or reordered: each answer must be valid before
+/// the next question is asked.
+#[derive(Debug)]
+pub struct ThoughtRecordFlow {
+    step: Step,
+    record: ThoughtRecord,
+}
+
+impl Default for ThoughtRecordFlow {
+    fn default() -> Self {
+        Self {
+            step: Step::Situation,
+            record: ThoughtRecord::default(),
+        }
+    }
+}
+
+impl ThoughtRecordFlow {
+    /// The question for the current step.
+    pub fn prompt(&self) -> &'static str {
+        t(self.step.prompt_key())
+    }
+
+    /// Records an answer to the current step and advances.
+    pub fn answer(&mut self, input: &str) -> Progress {
+        let input = input.trim();
+        if input.is_empty() {
+            return Progress::Invalid(t("thought_record.required"));
+        }
+
+        let record = &mut self.record;
+        match self.step {
+            Step::Situation => record.situation = input.to_string(),
+            Step::AutomaticThought => record.automatic_thought = input.to_string(),
+            Step::Emotion => record.emotion = input.to_string(),
+            Step::EvidenceFor => record.evidence_for = input.to_string(),
+            Step::EvidenceAgainst => record.evidence_against = input.to_string(),
+            Step::BalancedThought => record.balanced_thought = input.to_string(),
+            Step::IntensityBefore | Step::IntensityAfter => {
+                let Some(intensity) = parse_intensity(input) else {
+                    return Progress::Invalid(t("thought_record.intensity_invalid"));
+                };
+                if self.step == Step::IntensityBefore {
+                    record.intensity_before = intensity;
+                } else {
+                    record.intensity_after = intensity;
+                }
+            }
+        }
+
+        match self.step.next() {
+            Some(next) => {
+                self.step = next;
+                Progress::Next(self.prompt())
+            }
+            None => Progress::Done(std::mem::take(&mut self.record)),
+        }
+    }
+}
+
+/// Parses an intensity rating from 0 to 100 (optional trailing "%").
+fn parse_intensity(answer: &str) -> Option<i32> {
+    answer
+        .trim_end_matches('%')
+        .trim()
+        .parse()
+        .ok()
+        .filter(|i| (0..=100).contains(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_completes_in_order() {
+        let mut flow = ThoughtRecordFlow::default();
+        assert_eq!(flow.prompt(), t("thought_record.situation"));
+
+        let answers = [
+            "Missed the bus",
+            "I ruin everything",
+            "anxious",
+            "80",
+            "Late last week too",
+            "On time all month before that",
+            "Buses run late and I usually manage",
+        ];
+        for answer in answers {
+            assert!(matches!(flow.answer(answer), Progress::Next(_)), "{answer}");
+        }
+
+        let Progress::Done(record) = flow.answer("40%") else {
+            panic!("expected the re-rating to finish the record");
+        };
+        assert_eq!(record.automatic_thought, "I ruin everything");
+        assert_eq!(record.intensity_before, 80);
+        assert_eq!(record.intensity_after, 40);
+    }
+
+    #[test]
+    fn test_flow_rejects_invalid_answers() {
+        let mut flow = ThoughtRecordFlow::default();
+        assert!(matches!(flow.answer("  "), Progress::Invalid(_)));
+        assert_eq!(flow.step, Step::Situation, "empty answer must not advance");
+
+        flow.answer("Argument with my sister");
+        flow.answer("She hates me");
+        flow.answer("hurt");
+        assert!(matches!(flow.answer("very"), Progress::Invalid(_)));
+        assert!(matches!(flow.answer("120"), Progress::Invalid(_)));
+        assert_eq!(flow.step, Step::IntensityBefore);
+        assert!(matches!(flow.answer("70"), Progress::Next(_)));
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
+
+static ACTIVE: OnceLock<DisplayZone> = OnceLock::new();
+
+/// The time zone timestamps are shown in. They're always stored in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayZone {
+    /// The system's local time zone.
+    Local,
+    Utc,
+    /// A fixed offset from UTC, like `+02:00`.
+    Fixed(FixedOffset),
+}
+
+impl DisplayZone {
+    /// Parses `local`, `utc`, or an offset like `+02:00` or `-0530`.
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "local" | "" => Ok(DisplayZone::Local),
+            "utc" | "z" => Ok(DisplayZone::Utc),
+            offset => {
+                let digits = offset.replace(':', "");
+                let (sign, rest) = match digits.as_bytes().first() {
+                    Some(b'+') => (1, &digits[1..]),
+                    Some(b'-') => (-1, &digits[1..]),
+                    _ => bail!("Invalid timezone '{s}' (expected local, utc, or an offset like +02:00)"),
+                };
+                let (hours, minutes) = match rest.len() {
+                    2 => (rest, "00"),
+                    4 => rest.split_at(2),
+                    _ => bail!("Invalid timezone '{s}' (expected local, utc, or an offset like +02:00)"),
+                };
+                let seconds = hours
+                    .parse::<i32>()
+                    .ok()
+                    .zip(minutes.parse::<i32>().ok())
+                    .filter(|&(h, m)| h <= 14 && m < 60)
+                    .map(|(h, m)| sign * (h * 3600 + m * 60))
+                    .ok_or_else(|| anyhow!("Invalid timezone offset '{s}'"))?;
+                Ok(DisplayZone::Fixed(FixedOffset::east_opt(seconds).expect("offset is within ±14h")))
+            }
+        }
+    }
+
+    /// Converts a stored UTC instant to this zone.
+    pub fn convert(self, utc: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            DisplayZone::Local => utc.with_timezone(&Local).naive_local(),
+            DisplayZone::Utc => utc.naive_utc(),
+            DisplayZone::Fixed(offset) => utc.with_timezone(&offset).naive_local(),
+        }
+    }
+}
+
+/// Selects the display time zone (`persona.timezone`). Call once at
+/// startup; until then (and in tests) timestamps are shown in UTC.
+pub fn init(setting: &str) -> Result<()> {
+    let zone = DisplayZone::parse(setting)?;
+    ACTIVE.set(zone).map_err(|_| anyhow!("Timezone already initialized"))
+}
+
+/// The display time zone selected at startup.
+pub fn display_zone() -> DisplayZone {
+    ACTIVE.get().copied().unwrap_or(DisplayZone::Utc)
+}
+
+/// Parses a stored timestamp: SQLite `datetime` (`YYYY-MM-DD HH:MM:SS`,
+/// UTC) or RFC 3339.
+pub fn parse_utc(timestamp: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+        .map(|t| t.and_utc())
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc)))
+}
+
+/// A stored timestamp for display, as `YYYY-MM-DD HH:MM` in the display
+/// zone. Timestamps that don't parse are shown as stored.
+pub fn datetime(timestamp: &str) -> String {
+    match parse_utc(timestamp) {
+        Some(utc) => display_zone().convert(utc).format("%Y-%m-%d %H:%M").to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// The display-zone day a stored timestamp falls on, for grouping by day.
+pub fn date(timestamp: &str) -> Option<NaiveDate> {
+    match parse_utc(timestamp) {
+        Some(utc) => Some(display_zone().convert(utc).date()),
+        None => timestamp.get(..10).and_then(|d| d.parse().ok()),
+    }
+}
+
+/// [`date`] as `YYYY-MM-DD` for display, or the timestamp as stored if it
+/// doesn't parse.
+pub fn day(timestamp: &str) -> String {
+    date(timestamp).map(|d| d.to_string()).unwrap_or_else(|| timestamp.to_string())
+}
+
+/// Today in the display zone.
+pub fn today() -> NaiveDate {
+    display_zone().convert(Utc::now()).date()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_convert() {
+        assert_eq!(DisplayZone::parse("Local").unwrap(), DisplayZone::Local);
+        assert_eq!(DisplayZone::parse("UTC").unwrap(), DisplayZone::Utc);
+        assert!(DisplayZone::parse("Europe/Madrid").is_err());
+        assert!(DisplayZone::parse("+15:00").is_err());
+
+        let utc = parse_utc("2026-03-01 23:30:00").unwrap();
+        assert_eq!(parse_utc("2026-03-01T23:30:00+00:00"), Some(utc));
+        let east = DisplayZone::parse("+02:00").unwrap();
+        assert_eq!(east.convert(utc).to_string(), "2026-03-02 01:30:00");
+        let west = DisplayZone::parse("-0530").unwrap();
+        assert_eq!(west.convert(utc).to_string(), "2026-03-01 18:00:00");
+
+        // Not initialized in tests: shown in UTC
+        assert_eq!(datetime("2026-03-01 23:30:00"), "2026-03-01 23:30");
+        assert_eq!(datetime("yesterday"), "yesterday");
+        assert_eq!(date("2026-03-01"), "2026-03-01".parse().ok());
+        assert_eq!(day("2026-03-01T23:30:00-02:00"), "2026-03-02");
+    }
+}
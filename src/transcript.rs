@@ -0,0 +1,235 @@
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use tokio_rusqlite::Connection;
+
+use crate::memory::archive;
+use crate::memory::observe::{self, Cursor, Observed};
+use crate::memory::{self, SessionInfo};
+use crate::supervision::{extract_strategy, extract_themes};
+use crate::{timezone, wrap};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const ALERT: &str = "\x1b[1;31m";
+const WARN: &str = "\x1b[33m";
+
+/// `chiron sessions show`: a session's messages with their times, the
+/// coach's notes after each turn (MI stage, strategy, new themes), and
+/// warning signs, with crisis responses highlighted. Shown through the
+/// pager when stdout is a terminal, unless `pager` is false.
+pub async fn show(conn: &Connection, session_id: &str, pager: bool) -> Result<()> {
+    let Some(session) = memory::list_sessions(conn).await?.into_iter().find(|s| s.session_id == session_id) else {
+        bail!("No session {session_id} (see `chiron sessions`)");
+    };
+    let summary = match session.archived {
+        true => archive::archives_since(conn, "")
+            .await?
+            .into_iter()
+            .find(|a| a.session_id == session_id)
+            .map(|a| a.summary),
+        false => None,
+    };
+    let (items, _) = observe::observed_since(conn, Some(session_id), Cursor::default()).await?;
+
+    let terminal = io::stdout().is_terminal();
+    let width = if terminal { wrap::refresh_width() } else { None };
+    let text = render(&session, summary.as_deref(), items, terminal, width);
+    if pager && terminal {
+        page(&text)
+    } else {
+        print!("{text}");
+        Ok(())
+    }
+}
+
+/// The transcript as text: a header, then everything in the order it was
+/// saved, with a line for each new day. ANSI colors when `color`; messages
+/// wrapped when `width` is known.
+fn render(
+    session: &SessionInfo,
+    summary: Option<&str>,
+    mut items: Vec<Observed>,
+    color: bool,
+    width: Option<usize>,
+) -> String {
+    let paint = |style: &str, text: &str| if color { format!("{style}{text}{RESET}") } else { text.to_string() };
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{}\n{} – {} · {} messages{}",
+        paint(BOLD, &format!("Session {}", session.session_id)),
+        timezone::datetime(&session.started_at),
+        timezone::datetime(&session.last_active_at),
+        session.message_count,
+        if session.archived { " · archived" } else { "" }
+    );
+    if let Some(summary) = summary {
+        let _ = writeln!(out, "\nSummary (the transcript was archived):\n{summary}");
+    }
+
+    // Each kind comes oldest first; messages before the case note saved in
+    // the same second
+    items.sort_by_key(|item| timezone::parse_utc(created_at(item)));
+    let mut day = None;
+    let mut themes: Vec<String> = Vec::new();
+    for item in &items {
+        let date = timezone::date(created_at(item));
+        if date != day {
+            day = date;
+            let label = date.map(|d| d.format("%A %Y-%m-%d").to_string()).unwrap_or_default();
+            let _ = writeln!(out, "\n{}", paint(DIM, &format!("── {label} ──")));
+        }
+        let time = paint(DIM, &time_of(created_at(item)));
+        match item {
+            Observed::Message { role, content, crisis, interrupted, .. } => {
+                let speaker = if role == "user" { "You" } else { "Chiron" };
+                let mut marks = String::new();
+                if *crisis {
+                    marks.push_str(" [CRISIS RESPONSE]");
+                }
+                if *interrupted {
+                    marks.push_str(" [interrupted]");
+                }
+                let prefix = format!("{speaker}{marks}: ");
+                let content = match width {
+                    // The time and a space come before the prefix
+                    Some(width) => wrap::wrap(content, width, 6 + prefix.chars().count()),
+                    None => content.clone(),
+                };
+                let prefix = if *crisis { paint(ALERT, &prefix) } else { paint(BOLD, &prefix) };
+                let _ = writeln!(out, "{time} {prefix}{content}");
+            }
+            Observed::CaseNote { turn_number, mi_stage, content, .. } => {
+                let mut tags = vec![format!("turn {turn_number}")];
+                if let Some(stage) = mi_stage {
+                    tags.push(format!("MI stage: {stage}"));
+                }
+                if let Some(strategy) = extract_strategy(content) {
+                    tags.push(format!("strategy: {strategy}"));
+                }
+                let running = extract_themes(content).unwrap_or_default();
+                let new: Vec<&str> =
+                    running.iter().filter(|t| !themes.contains(t)).map(String::as_str).collect();
+                if !new.is_empty() {
+                    tags.push(format!("themes: {}", new.join(", ")));
+                }
+                themes = running;
+                let _ = writeln!(out, "{time}   {}", paint(DIM, &format!("· {}", tags.join(" · "))));
+            }
+            Observed::WarningSign { sign, .. } => {
+                let _ = writeln!(out, "{time}   {}", paint(WARN, &format!("!! Warning sign: {sign}")));
+            }
+        }
+    }
+    out
+}
+
+fn created_at(item: &Observed) -> &str {
+    match item {
+        Observed::Message { created_at, .. }
+        | Observed::CaseNote { created_at, .. }
+        | Observed::WarningSign { created_at, .. } => created_at,
+    }
+}
+
+/// `HH:MM` in the display time zone from a stored timestamp.
+fn time_of(created_at: &str) -> String {
+    match timezone::parse_utc(created_at) {
+        Some(utc) => timezone::display_zone().convert(utc).format("%H:%M").to_string(),
+        None => created_at.to_string(),
+    }
+}
+
+/// Pipes `text` through `$PAGER` (`less` by default, quitting at once if
+/// it fits on the screen), or prints it if the pager won't start.
+fn page(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()).unwrap_or_else(|| "less".to_string());
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
+    let less = std::env::var("LESS").unwrap_or_else(|_| "FRX".to_string());
+    let child = Command::new(program).args(words).env("LESS", less).stdin(Stdio::piped()).spawn();
+    let Ok(mut child) = child else {
+        print!("{text}");
+        return Ok(());
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; that's not an error
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().with_context(|| format!("Failed to run pager {program}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str, created_at: &str, crisis: bool) -> Observed {
+        Observed::Message {
+            session_id: "s1".to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: created_at.to_string(),
+            crisis,
+            interrupted: false,
+        }
+    }
+
+    #[test]
+    fn test_render_interleaves_notes_and_marks_crisis() {
+        let session = SessionInfo {
+            session_id: "s1".to_string(),
+            message_count: 4,
+            started_at: "2026-03-01 21:04:00".to_string(),
+            last_active_at: "2026-03-02 08:10:00".to_string(),
+            archived: false,
+        };
+        let note = |turn, themes: &str, created_at: &str| Observed::CaseNote {
+            session_id: "s1".to_string(),
+            turn_number: turn,
+            mi_stage: Some("engage".to_string()),
+            content: format!("MI Stage: engage\nStrategy Used: Open question\nRunning Themes: {themes}"),
+            created_at: created_at.to_string(),
+        };
+        // As `observed_since` returns them: messages, then notes, then flags
+        let items = vec![
+            message("user", "Work is a lot lately.", "2026-03-01 21:04:00", false),
+            message("assistant", "What's weighing most?", "2026-03-01 21:04:05", false),
+            message("user", "I can't go on", "2026-03-02 08:10:00", false),
+            message("assistant", "Please call 988.", "2026-03-02 08:10:00", true),
+            note(1, "work stress", "2026-03-01 21:04:05"),
+            note(2, "sleep, work stress", "2026-03-02 08:10:00"),
+            Observed::WarningSign {
+                session_id: "s1".to_string(),
+                sign: "hopelessness".to_string(),
+                created_at: "2026-03-02 08:10:00".to_string(),
+            },
+        ];
+
+        let text = render(&session, None, items, false, None);
+        let expected = "\
+Session s1
+2026-03-01 21:04 – 2026-03-02 08:10 · 4 messages
+
+── Sunday 2026-03-01 ──
+21:04 You: Work is a lot lately.
+21:04 Chiron: What's weighing most?
+21:04   · turn 1 · MI stage: engage · strategy: open question · themes: work stress
+
+── Monday 2026-03-02 ──
+08:10 You: I can't go on
+08:10 Chiron [CRISIS RESPONSE]: Please call 988.
+08:10   · turn 2 · MI stage: engage · strategy: open question · themes: sleep
+08:10   !! Warning sign: hopelessness
+";
+        assert_eq!(text, expected);
+
+        let colored = render(&session, Some("Talked about work."), vec![], true, None);
+        assert!(colored.starts_with("\x1b[1mSession s1\x1b[0m"));
+        assert!(colored.contains("\nSummary (the transcript was archived):\nTalked about work.\n"));
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use tokio_rusqlite::Connection;
+
+use crate::checkin::ask;
+use crate::i18n::{t, tf};
+use crate::memory::values::{self, ValueRating};
+use crate::router;
+
+/// Life domains explored by `/values explore` (from the Valued Living
+/// Questionnaire), as (ID, locale key for the display name).
+pub const DOMAINS: &[(&str, &str)] = &[
+    ("family", "values.domain_family"),
+    ("partner", "values.domain_partner"),
+    ("parenting", "values.domain_parenting"),
+    ("friendships", "values.domain_friendships"),
+    ("work", "values.domain_work"),
+    ("learning", "values.domain_learning"),
+    ("recreation", "values.domain_recreation"),
+    ("spirituality", "values.domain_spirituality"),
+    ("community", "values.domain_community"),
+    ("physical_health", "values.domain_physical_health"),
+];
+
+/// Runs `/values explore`: for each domain, how important it is and how
+/// closely the past two weeks matched it (1–10), plus an optional note on
+/// what matters there. Domains left blank are skipped.
+pub async fn explore(conn: &Connection) -> Result<()> {
+    println!("{}", t("values.intro"));
+
+    for (domain, name_key) in DOMAINS {
+        let name = t(name_key);
+        let Some(importance) = ask_rating(&tf("values.importance", &[("domain", &name)]), true)? else {
+            continue;
+        };
+        let Some(consistency) = ask_rating(&tf("values.consistency", &[("domain", &name)]), false)? else {
+            continue;
+        };
+        let note = ask(&tf("values.note", &[("domain", &name)]))?;
+
+        // Free text gets the same safety check as chat messages
+        if router::is_crisis(&note) {
+            println!("\n{}\n", router::crisis_response());
+        }
+
+        values::save_value_rating(
+            conn,
+            domain,
+            importance,
+            consistency,
+            Some(note.as_str()).filter(|n| !n.is_empty()),
+        )
+        .await?;
+    }
+
+    show(conn).await
+}
+
+/// Runs `/values`: the current profile, most important first, with a nudge
+/// toward setting a goal in the domain with the widest gap.
+pub async fn show(conn: &Connection) -> Result<()> {
+    let profile = values::values_profile(conn).await?;
+    if profile.is_empty() {
+        println!("{}", t("values.none"));
+        return Ok(());
+    }
+
+    println!("{}", t("values.profile_header"));
+    for v in &profile {
+        println!(
+            "  {:<16} {}",
+            domain_name(&v.domain),
+            tf(
+                "values.profile_row",
+                &[("importance", &v.importance), ("consistency", &v.consistency)],
+            )
+        );
+    }
+    if let Some(hint) = goal_hint(&profile) {
+        println!("{hint}");
+    }
+    Ok(())
+}
+
+/// Suggests a goal in the domain with the widest values gap, if any.
+pub fn goal_hint(profile: &[ValueRating]) -> Option<String> {
+    let gap = values::largest_gap(profile)?;
+    Some(tf("values.goal_hint", &[("domain", &domain_name(&gap.domain))]))
+}
+
+/// Localized display name for a domain ID (the ID itself if unknown).
+fn domain_name(domain: &str) -> &str {
+    DOMAINS
+        .iter()
+        .find(|(id, _)| *id == domain)
+        .map_or(domain, |(_, key)| t(key))
+}
+
+/// Asks for a 1–10 rating until valid. With `optional`, a blank answer
+/// returns `None`.
+fn ask_rating(prompt: &str, optional: bool) -> Result<Option<i32>> {
+    loop {
+        let answer = ask(prompt)?;
+        if answer.is_empty() && optional {
+            return Ok(None);
+        }
+        match parse_rating(&answer) {
+            Some(rating) => return Ok(Some(rating)),
+            None => println!("{}", t("values.rating_invalid")),
+        }
+    }
+}
+
+/// Parses a rating, accepting only whole numbers from 1 to 10.
+fn parse_rating(answer: &str) -> Option<i32> {
+    answer.parse().ok().filter(|r| (1..=10).contains(r))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("10"), Some(10));
+        assert_eq!(parse_rating("0"), None);
+        assert_eq!(parse_rating("high"), None);
+    }
+
+    #[test]
+    fn test_goal_hint_names_widest_gap() {
+        let rating = |domain: &str, importance, consistency| ValueRating {
+            domain: domain.into(),
+            importance,
+            consistency,
+            note: None,
+            created_at: String::new(),
+        };
+        let profile = [rating("family", 9, 8), rating("physical_health", 8, 2), rating("work", 7, 3)];
+        let hint = goal_hint(&profile).unwrap();
+        assert!(hint.contains(t("values.domain_physical_health")), "{hint}");
+
+        assert!(goal_hint(&profile[..1]).is_none());
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::VoiceConfig;
+
+/// Recording stops after this much trailing silence (seconds).
+const SILENCE_STOP_SECS: &str = "2.0";
+
+/// Hard cap on a single utterance (seconds).
+const MAX_RECORD_SECS: &str = "60";
+
+/// Local speech-to-text: records from the default microphone with sox and
+/// transcribes with the whisper.cpp CLI. Audio never leaves the machine;
+/// each recording goes to a fresh temporary file only the user can read,
+/// deleted after transcription.
+pub struct VoiceInput {
+    config: VoiceConfig,
+}
+
+impl VoiceInput {
+    /// Checks that the whisper model exists. The commands themselves are
+    /// resolved on first use.
+    pub fn new(config: VoiceConfig) -> Result<Self> {
+        if !config.whisper_model.exists() {
+            bail!(
+                "Whisper model not found: {} (set voice.whisper_model in the config file)",
+                config.whisper_model.display()
+            );
+        }
+        Ok(Self { config })
+    }
+
+    /// Records one utterance and returns its transcript (empty if nothing was heard).
+    pub fn listen(&self) -> Result<String> {
+        // Created 0600 with an unpredictable name; removed when dropped
+        let wav = tempfile::Builder::new()
+            .prefix("chiron_voice_")
+            .suffix(".wav")
+            .tempfile()
+            .context("Failed to create a file for the recording")?;
+        self.record(wav.path())?;
+        let transcript = self.transcribe(wav.path());
+        wav.close().context("Failed to delete the recording")?;
+        transcript
+    }
+
+    /// Records 16 kHz mono audio until the speaker pauses.
+    fn record(&self, wav_path: &Path) -> Result<()> {
+        let status = Command::new(&self.config.record_command)
+            .args(["-q", "-c", "1", "-r", "16000", "-b", "16"])
+            .arg(wav_path)
+            // Start on sound, stop after SILENCE_STOP_SECS of silence
+            .args(["silence", "1", "0.1", "1%", "1", SILENCE_STOP_SECS, "1%"])
+            .args(["trim", "0", MAX_RECORD_SECS])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()
+            .with_context(|| format!("Failed to run '{}'", self.config.record_command))?;
+
+        if !status.success() {
+            bail!("'{}' exited with {status}", self.config.record_command);
+        }
+        Ok(())
+    }
+
+    fn transcribe(&self, wav_path: &Path) -> Result<String> {
+        let output = Command::new(&self.config.whisper_command)
+            .arg("-m")
+            .arg(&self.config.whisper_model)
+            .arg("-f")
+            .arg(wav_path)
+            .args(["--no-timestamps", "--no-prints"])
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("Failed to run '{}'", self.config.whisper_command))?;
+
+        if !output.status.success() {
+            bail!(
+                "'{}' failed: {}",
+                self.config.whisper_command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(clean_transcript(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Joins whisper output lines and drops non-speech annotations such as
+/// `[BLANK_AUDIO]` or `(coughs)`.
+pub fn clean_transcript(raw: &str) -> String {
+    let mut text = String::new();
+    let mut depth = 0usize;
+    for c in raw.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_transcript() {
+        assert_eq!(
+            clean_transcript(" I've been feeling\n really tired lately.\n"),
+            "I've been feeling really tired lately."
+        );
+        assert_eq!(clean_transcript("[BLANK_AUDIO]\n"), "");
+        assert_eq!(clean_transcript("(sighs) okay [Music] fine"), "okay fine");
+    }
+}
@@ -0,0 +1,125 @@
+use tokio_rusqlite::Connection;
+
+use crate::error::Result;
+use crate::i18n::{t, tf};
+use crate::memory::coping;
+use crate::memory::warning_signs::{self, WarningSign};
+
+/// Checks `text` against the user's early warning signs. Signs not yet
+/// flagged in this session are recorded, and a gentle note is returned
+/// naming them and suggesting coping skills that helped before.
+///
+/// `source` is `chat` or `checkin`.
+pub async fn check(conn: &Connection, session_id: &str, text: &str, source: &str) -> Result<Option<String>> {
+    let signs = warning_signs::list_signs(conn).await?;
+    let mut matched: Vec<&WarningSign> = Vec::new();
+    for sign in signs.iter().filter(|s| s.matches(text)) {
+        if warning_signs::flagged_in_session(conn, sign.id, session_id).await? {
+            continue;
+        }
+        warning_signs::record_flag(conn, sign.id, session_id, source).await?;
+        matched.push(sign);
+    }
+    if matched.is_empty() {
+        return Ok(None);
+    }
+    tracing::info!(count = matched.len(), source, "Early warning sign mentioned");
+
+    let names: Vec<&str> = matched.iter().map(|s| s.sign.as_str()).collect();
+    let skills = coping::list_skills(conn).await?;
+    let helpful = coping::helpful_skills(&skills);
+    let suggestion = if helpful.is_empty() {
+        t("warning.try_default").to_string()
+    } else {
+        tf("warning.try_skills", &[("skills", &helpful.join(", "))])
+    };
+
+    Ok(Some(format!(
+        "{}\n{suggestion}",
+        tf("warning.flag", &[("signs", &names.join(", "))])
+    )))
+}
+
+/// Runs a `/warning` command: `add <sign>[: cue, cue]`, `list`, or
+/// `remove <id>`. A bare `/warning` lists signs.
+pub async fn command(conn: &Connection, args: &str) -> Result<String> {
+    let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+    let rest = rest.trim();
+
+    match action {
+        "add" if !rest.is_empty() => {
+            let (sign, cues) = parse_sign(rest);
+            let id = warning_signs::add_sign(conn, &sign, &cues).await?;
+            Ok(tf("warning.added", &[("id", &id), ("sign", &sign)]))
+        }
+        "list" | "" => {
+            let signs = warning_signs::list_signs(conn).await?;
+            if signs.is_empty() {
+                return Ok(t("warning.none").to_string());
+            }
+            let lines: Vec<String> = signs
+                .iter()
+                .map(|s| {
+                    if s.cues.is_empty() {
+                        format!("#{} {}", s.id, s.sign)
+                    } else {
+                        format!("#{} {} ({})", s.id, s.sign, s.cues.join(", "))
+                    }
+                })
+                .collect();
+            Ok(lines.join("\n"))
+        }
+        "remove" => {
+            let Ok(id) = rest.trim_start_matches('#').parse::<i64>() else {
+                return Ok(t("warning.usage").to_string());
+            };
+            if warning_signs::remove_sign(conn, id).await? {
+                Ok(tf("warning.removed", &[("id", &id)]))
+            } else {
+                Ok(tf("warning.not_found", &[("id", &id)]))
+            }
+        }
+        _ => Ok(t("warning.usage").to_string()),
+    }
+}
+
+/// Splits `sign: cue, cue` into the sign and its cue phrases.
+fn parse_sign(input: &str) -> (String, Vec<String>) {
+    let (sign, cues) = input.split_once(':').unwrap_or((input, ""));
+    let cues = cues
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect();
+    (sign.trim().to_string(), cues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sign() {
+        assert_eq!(
+            parse_sign("skipping meals: skipped lunch,  no appetite "),
+            ("skipping meals".to_string(), vec!["skipped lunch".to_string(), "no appetite".to_string()])
+        );
+        assert_eq!(parse_sign("cancelling plans"), ("cancelling plans".to_string(), vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_check_flags_once_per_session() {
+        let conn = crate::memory::open_memory(":memory:").await.unwrap();
+        command(&conn, "add skipping meals: no appetite").await.unwrap();
+        coping::rate_skill(&conn, "s0", "Walk outside", 5).await.unwrap();
+
+        let flag = check(&conn, "s1", "I've had no appetite all week", "chat").await.unwrap().unwrap();
+        assert!(flag.contains("skipping meals"), "{flag}");
+        assert!(flag.contains("Walk outside"), "{flag}");
+
+        assert!(check(&conn, "s1", "still no appetite", "chat").await.unwrap().is_none());
+        assert!(check(&conn, "s2", "I slept fine", "chat").await.unwrap().is_none());
+        assert!(check(&conn, "s2", "no appetite", "chat").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,156 @@
+use std::io::{self, IsTerminal};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Terminal width in columns; 0 when stdout isn't a terminal (no wrapping).
+static WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Used when stdout is a terminal but its size can't be read.
+const FALLBACK_WIDTH: usize = 80;
+
+/// Re-measures the terminal. Returns the new width, or `None` when stdout
+/// isn't a terminal.
+pub fn refresh_width() -> Option<usize> {
+    let width = if io::stdout().is_terminal() { measure().unwrap_or(FALLBACK_WIDTH) } else { 0 };
+    WIDTH.store(width, Ordering::Relaxed);
+    width_of(width)
+}
+
+/// The last measured terminal width, or `None` when not wrapping.
+pub fn width() -> Option<usize> {
+    width_of(WIDTH.load(Ordering::Relaxed))
+}
+
+fn width_of(width: usize) -> Option<usize> {
+    (width > 0).then_some(width)
+}
+
+/// Columns of the terminal on stdin (`stty size` prints `rows cols`), or
+/// `$COLUMNS`.
+fn measure() -> Option<usize> {
+    let stty = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .and_then(|out| String::from_utf8_lossy(&out.stdout).split_whitespace().nth(1)?.parse().ok());
+    stty.or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .filter(|&columns| columns > 0)
+}
+
+/// Measures the terminal now and again whenever it's resized (SIGWINCH),
+/// so replies wrap to the new width from their next line on.
+pub fn watch_resize() {
+    refresh_width();
+    #[cfg(unix)]
+    tokio::spawn(async {
+        use tokio::signal::unix::{signal, SignalKind};
+        let Ok(mut resized) = signal(SignalKind::window_change()) else {
+            return;
+        };
+        while resized.recv().await.is_some() {
+            refresh_width();
+        }
+    });
+}
+
+/// Wraps text at word boundaries as it streams in. A word is held back
+/// until it ends, so it's never split across lines; whitespace is held
+/// until the next word, so lines don't end in spaces.
+#[derive(Debug, Default)]
+pub struct LineWrapper {
+    column: usize,
+    word: String,
+    space: String,
+}
+
+impl LineWrapper {
+    /// Starts a new block of text after a prefix `column` characters wide
+    /// (`Chiron: `).
+    pub fn start(&mut self, column: usize) {
+        *self = Self { column, ..Self::default() };
+    }
+
+    /// Wraps the next chunk for a terminal `width` columns wide, returning
+    /// what can be printed now.
+    pub fn push(&mut self, text: &str, width: usize) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    self.flush_word(&mut out, width);
+                    self.space.clear();
+                    out.push('\n');
+                    self.column = 0;
+                }
+                c if c.is_whitespace() => {
+                    self.flush_word(&mut out, width);
+                    self.space.push(c);
+                }
+                c => self.word.push(c),
+            }
+        }
+        out
+    }
+
+    /// Whatever is still held back at the end of the text.
+    pub fn finish(&mut self, width: usize) -> String {
+        let mut out = String::new();
+        self.flush_word(&mut out, width);
+        self.space.clear();
+        out
+    }
+
+    fn flush_word(&mut self, out: &mut String, width: usize) {
+        if self.word.is_empty() {
+            return;
+        }
+        let space = self.space.chars().count();
+        let len = self.word.chars().count();
+        if self.column > 0 && self.column + space + len > width {
+            out.push('\n');
+            self.column = 0;
+        } else {
+            out.push_str(&self.space);
+            self.column += space;
+        }
+        out.push_str(&self.word);
+        self.column += len;
+        self.word.clear();
+        self.space.clear();
+    }
+}
+
+/// Wraps a complete text the way [`LineWrapper`] would stream it.
+pub fn wrap(text: &str, width: usize, column: usize) -> String {
+    let mut wrapper = LineWrapper::default();
+    wrapper.start(column);
+    let mut out = wrapper.push(text, width);
+    out.push_str(&wrapper.finish(width));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraps_the_same_however_it_streams() {
+        let text = "That sounds like a lot to carry.\n  Which part weighs most today?";
+        let whole = wrap(text, 20, 8);
+        assert_eq!(whole, "That sounds\nlike a lot to carry.\n  Which part weighs\nmost today?");
+
+        let mut wrapper = LineWrapper::default();
+        wrapper.start(8);
+        let mut streamed = String::new();
+        for chunk in ["Th", "at sou", "nds like a", " lot to car", "ry.\n ", " Which part w", "eighs most today?"] {
+            streamed.push_str(&wrapper.push(chunk, 20));
+        }
+        streamed.push_str(&wrapper.finish(20));
+        assert_eq!(streamed, whole);
+
+        // A word longer than the line gets a line of its own
+        assert_eq!(wrap("a supercalifragilistic b", 10, 0), "a\nsupercalifragilistic\nb");
+    }
+}